@@ -1,21 +1,339 @@
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
-use tauri::Manager;
+use tauri::{AppHandle, Emitter, Listener, Manager};
 
-use crate::repo::vault_repo;
-use crate::state::VaultState;
+use crate::domain::planning::MdSyncPolicy;
+use crate::domain::webview::WebviewStatePayload;
+use crate::features::ai::embedding::EmbeddingEngine;
+use crate::features::vault_drive;
+use crate::ipc::ApiError;
+use crate::repo::{settings_repo, vault_repo};
+use crate::security::db_crypto;
+use crate::services::planning_service::{self, PlanningService};
+use crate::state::{BoardIndexState, VaultState};
+use crate::webview_bridge::WEBVIEW_STATE_EVENT;
+
+const TIMER_HEARTBEAT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+// How often journaled note syncs (lazy policy or locked notes) are written
+const MD_SYNC_FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+// How often pending board index regenerations are looked for, and how long task changes must
+// have settled before one runs
+const BOARD_INDEX_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+const BOARD_INDEX_DEBOUNCE: std::time::Duration = std::time::Duration::from_secs(3);
+// Emitted with a HealthCheckReport once the startup health check of the vault finished
+pub const HEALTH_CHECK_EVENT: &str = "vault-health-check";
 
 pub fn init_vault_state(app: &tauri::App) -> tauri::Result<VaultState> {
     let config_dir = app.path().app_config_dir()?;
     fs::create_dir_all(&config_dir)?;
     let config_path = config_dir.join("vault.json");
-    Ok(VaultState {
-        root: Mutex::new(vault_repo::load_persisted_vault(&config_path)),
+    let root = vault_repo::load_persisted_vault(&config_path);
+    let stale = match root {
+        Some(_) => None,
+        None => vault_repo::load_stale_vault(&config_path),
+    };
+    if let Some(stale) = &stale {
+        tracing::warn!(target: "vault", "persisted vault not found, relocation needed: vault_root={}", stale.vault_root);
+    }
+    let state = VaultState {
+        root: Mutex::new(root.clone()),
         config_path,
+        stale: Mutex::new(stale),
+    };
+    // Files written before vault ids were recorded get one once the vault has it
+    if let Some(root) = root {
+        let recorded = vault_repo::read_persisted(&state.config_path).and_then(|p| p.vault_id);
+        if recorded.is_none() && vault_repo::read_vault_id(&root).is_some() {
+            if let Err(err) = vault_repo::persist_vault(&state, &root) {
+                tracing::warn!(target: "vault", "vault id not recorded: {}", err.message);
+            }
+        }
+    }
+    Ok(state)
+}
+
+// App-level settings live next to vault.json; every settings write is broadcast to the windows
+pub fn init_settings_layers(app: &tauri::App) -> tauri::Result<()> {
+    settings_repo::init_app_settings_dir(app.path().app_config_dir()?);
+    let app_handle = app.handle().clone();
+    settings_repo::on_settings_changed(move |event| {
+        if let Err(e) = app_handle.emit(settings_repo::SETTINGS_CHANGED_EVENT, event) {
+            tracing::warn!(target: "settings", "failed to emit settings change: {}", e);
+        }
+    });
+    Ok(())
+}
+
+// Pick up note writes queued while the vault's drive was away in an earlier session
+pub fn init_vault_drive_state(
+    app: &tauri::App,
+    vault_state: &VaultState,
+) -> tauri::Result<crate::state::VaultDriveState> {
+    let queue_path = vault_drive::pending_writes_path(&app.path().app_config_dir()?);
+    let vault_root = vault_state.root.lock().ok().and_then(|root| root.clone());
+    let queue = vault_root
+        .as_deref()
+        .map(|root| vault_drive::load_pending_writes(&queue_path, root))
+        .unwrap_or_default();
+    Ok(crate::state::VaultDriveState {
+        available: Mutex::new(vault_root.is_some_and(|root| root.is_dir())),
+        queue: Mutex::new(queue),
+        queue_path,
     })
 }
 
+pub fn init_focus_state() -> crate::state::FocusState {
+    crate::state::FocusState {
+        session: Mutex::new(None),
+    }
+}
+
+// Detect a timer left running when the app last quit; resolved later via planning_recover_timers
+pub fn init_timer_recovery_state(
+    app: &tauri::App,
+    vault_state: &VaultState,
+) -> crate::state::TimerRecoveryState {
+    let vault_root = vault_state.root.lock().ok().and_then(|root| root.clone());
+    let dangling_timer_id = vault_root.and_then(|root| {
+        match PlanningService::new(app.handle(), &root)
+            .and_then(|service| service.detect_dangling_timer())
+        {
+            Ok(timer_id) => timer_id,
+            Err(err) => {
+                tracing::warn!(target: "planning", "timer recovery check failed: {}", err.message);
+                None
+            }
+        }
+    });
+    if let Some(timer_id) = &dangling_timer_id {
+        tracing::info!(target: "planning", "dangling timer found at startup: timer_id={}", timer_id);
+    }
+    crate::state::TimerRecoveryState {
+        dangling_timer_id: Mutex::new(dangling_timer_id),
+    }
+}
+
+// Clean up after interrupted writes in the background so startup is not held up
+pub fn spawn_health_check(app: &tauri::App, vault_state: &VaultState) {
+    let Some(vault_root) = vault_state.root.lock().ok().and_then(|root| root.clone()) else {
+        return;
+    };
+    let app_handle = app.handle().clone();
+    std::thread::spawn(move || {
+        let report = planning_service::run_health_check(&vault_root);
+        if let Err(e) = app_handle.emit(HEALTH_CHECK_EVENT, &report) {
+            tracing::warn!(target: "planning", "failed to emit health check: {}", e);
+        }
+    });
+}
+
+// Service a periodic thread keeps between ticks. It is reopened only when the vault, its
+// planning settings or its database lock change, so a tick does not reopen the database and
+// re-run its migrations.
+#[derive(Default)]
+struct CachedService {
+    opened: Option<(PathBuf, (serde_json::Value, bool), PlanningService)>,
+}
+
+impl CachedService {
+    fn get(
+        &mut self,
+        app_handle: &AppHandle,
+        vault_root: &Path,
+    ) -> Result<&PlanningService, ApiError> {
+        let settings = settings_repo::get_planning_settings(vault_root).unwrap_or_default();
+        let unlocked = db_crypto::unlocked_cipher(vault_root).is_ok_and(|cipher| cipher.is_some());
+        let key = (
+            serde_json::to_value(&settings).unwrap_or_default(),
+            unlocked,
+        );
+        let service = match self.opened.take() {
+            Some((root, opened_key, service)) if root == vault_root && opened_key == key => service,
+            _ => PlanningService::new(app_handle, vault_root)?,
+        };
+        let (_, _, service) = self.opened.insert((vault_root.to_path_buf(), key, service));
+        Ok(service)
+    }
+}
+
+// Write journaled note syncs and queued daily log entries in batches
+pub fn spawn_md_sync_flush(app: &tauri::App) {
+    let app_handle = app.handle().clone();
+    std::thread::spawn(move || {
+        let mut cached = CachedService::default();
+        loop {
+            std::thread::sleep(MD_SYNC_FLUSH_INTERVAL);
+            let vault_state = app_handle.state::<VaultState>();
+            let vault_root = vault_state.root.lock().ok().and_then(|root| root.clone());
+            let Some(vault_root) = vault_root else {
+                continue;
+            };
+            let service = match cached.get(&app_handle, &vault_root) {
+                Ok(service) => service,
+                Err(err) => {
+                    tracing::warn!(target: "planning", "md sync flush failed: {}", err.message);
+                    continue;
+                }
+            };
+            // Editor locks on daily notes expire even when the release never arrives
+            if let Err(err) = service.flush_daily_log_queue() {
+                tracing::warn!(target: "planning", "daily log flush failed: {}", err.message);
+            }
+            let md_sync = settings_repo::get_planning_settings(&vault_root)
+                .is_ok_and(|settings| settings.md_sync != MdSyncPolicy::Off);
+            if !md_sync {
+                continue;
+            }
+            if let Err(err) = service.flush_md_sync_journal() {
+                tracing::warn!(target: "planning", "md sync flush failed: {}", err.message);
+            }
+        }
+    });
+}
+
+// Starts out pending so enabled board indexes catch up with changes made while the app was closed
+pub fn init_board_index_state() -> BoardIndexState {
+    BoardIndexState {
+        changed_at: Mutex::new(Some(std::time::Instant::now())),
+    }
+}
+
+// Regenerate the board index files once task changes have settled
+pub fn spawn_board_index_writer(app: &tauri::App) {
+    let app_handle = app.handle().clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(BOARD_INDEX_POLL_INTERVAL);
+        let index_state = app_handle.state::<BoardIndexState>();
+        if !index_state.take_settled(BOARD_INDEX_DEBOUNCE) {
+            continue;
+        }
+        let vault_state = app_handle.state::<VaultState>();
+        let vault_root = vault_state.root.lock().ok().and_then(|root| root.clone());
+        let Some(vault_root) = vault_root.filter(|root| {
+            settings_repo::get_planning_settings(root)
+                .is_ok_and(|settings| settings.board_index.enabled)
+        }) else {
+            continue;
+        };
+        let written = PlanningService::new(&app_handle, &vault_root)
+            .and_then(|service| service.write_board_indexes());
+        match written {
+            // Indexes open in the editor are retried once the next change settles
+            Ok(result) if !result.locked.is_empty() => index_state.mark_changed(),
+            Ok(_) => {}
+            Err(err) => {
+                tracing::warn!(target: "planning", "board index update failed: {}", err.message);
+            }
+        }
+    });
+}
+
+// Periodically record that the app is alive while a timer runs and report overdue tasks
+pub fn spawn_timer_heartbeat(app: &tauri::App) {
+    let app_handle = app.handle().clone();
+    std::thread::spawn(move || {
+        let mut cached = CachedService::default();
+        loop {
+            std::thread::sleep(TIMER_HEARTBEAT_INTERVAL);
+            let vault_state = app_handle.state::<VaultState>();
+            let vault_root = vault_state.root.lock().ok().and_then(|root| root.clone());
+            let Some(vault_root) = vault_root else {
+                continue;
+            };
+            let service = match cached.get(&app_handle, &vault_root) {
+                Ok(service) => service,
+                Err(err) => {
+                    tracing::warn!(target: "planning", "timer heartbeat failed: {}", err.message);
+                    continue;
+                }
+            };
+            if let Err(err) = service.record_timer_heartbeat() {
+                tracing::warn!(target: "planning", "timer heartbeat failed: {}", err.message);
+            }
+            if let Err(err) = service.notify_overdue_tasks() {
+                tracing::warn!(target: "webhooks", "overdue check failed: {}", err.message);
+            }
+        }
+    });
+}
+
+// Persist embedded browser navigation from the bridge's state events into the current vault
+pub fn listen_webview_history(app: &tauri::App) {
+    let app_handle = app.handle().clone();
+    app.listen(WEBVIEW_STATE_EVENT, move |event| {
+        let payload: WebviewStatePayload = match serde_json::from_str(event.payload()) {
+            Ok(payload) => payload,
+            Err(err) => {
+                tracing::warn!(target: "webview", "invalid webview state event: {}", err);
+                return;
+            }
+        };
+        let vault_state = app_handle.state::<VaultState>();
+        let vault_root = vault_state.root.lock().ok().and_then(|root| root.clone());
+        let Some(vault_root) = vault_root else {
+            return;
+        };
+        let result = PlanningService::new(&app_handle, &vault_root)
+            .and_then(|service| service.record_webview_state(payload));
+        if let Err(err) = result {
+            tracing::warn!(target: "webview", "webview history not recorded: {}", err.message);
+        }
+    });
+}
+
+// Load the embedding model in the background when the persisted vault asks for it; otherwise it
+// loads on first use
+pub fn spawn_embedding_preload(app: &tauri::App, vault_state: &VaultState) {
+    let vault_root = vault_state.root.lock().ok().and_then(|root| root.clone());
+    let Some(settings) = vault_root
+        .and_then(|root| crate::repo::settings_repo::get_ai_settings(&root).ok())
+        .filter(|settings| settings.preload_embedding_model)
+    else {
+        return;
+    };
+    let app_handle = app.handle().clone();
+    std::thread::spawn(move || {
+        let engine = app_handle.state::<EmbeddingEngine>();
+        engine.select(settings.embedding_model);
+        if !engine.is_downloaded(settings.embedding_model) {
+            return;
+        }
+        if let Err(err) = engine.warm_up() {
+            tracing::warn!(target: "ai", "embedding preload failed: {}", err);
+        }
+    });
+}
+
+// Start the localhost HTTP API when the persisted vault has it enabled
+pub fn init_http_api_state(
+    app: &tauri::App,
+    vault_state: &VaultState,
+) -> crate::state::HttpApiState {
+    let vault_root = vault_state.root.lock().ok().and_then(|root| root.clone());
+    let settings = vault_root
+        .and_then(|root| crate::repo::settings_repo::get_http_api_settings(&root).ok())
+        .filter(|settings| settings.enabled && !settings.token.is_empty());
+
+    let server =
+        settings.and_then(|settings| {
+            match crate::features::http_api::HttpApiServer::start(
+                app.handle().clone(),
+                settings.port,
+            ) {
+                Ok(server) => Some(server),
+                Err(err) => {
+                    tracing::warn!(target: "http_api", "HTTP API not started: {}", err.message);
+                    None
+                }
+            }
+        });
+    crate::state::HttpApiState {
+        server: Mutex::new(server),
+    }
+}
+
 pub fn init_app_state() -> crate::state::AppState {
     crate::state::AppState {
         http_client: reqwest::Client::new(),
@@ -0,0 +1,363 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::domain::board::DEFAULT_BOARD_ID;
+use crate::domain::plaintext_export::PlainTextFormat;
+use crate::domain::planning::{CreateTaskInput, Task, TaskPriority, TaskStatus};
+use crate::domain::timezone;
+use crate::ipc::{ApiError, ErrorCode};
+use crate::repo::settings_repo;
+use crate::security::path_policy;
+use crate::services::planning_service::PlanningService;
+use crate::services::vault_service;
+
+const USAGE: &str = r#"Usage: tauri-planning-app --cli --vault <path> <command> [options]
+
+Commands:
+  scan                          List markdown files in the vault
+  search <query>                Search tasks [--notes] [--limit N]
+  task add <title>              Create a task [--due YYYY-MM-DD] [--status S] [--priority P]
+                                [--estimate MIN] [--board ID] [--tag TAG]...
+  task list                     List tasks [--status S] [--archived]
+  task done <task_id>           Mark a task as done
+  export board [board_id]       Write a board checklist into the vault
+  export org|taskpaper [board]  Write boards and tasks as an org-mode or TaskPaper file
+  export tasks                  Print every task as JSON
+
+Options:
+  --json                        Print machine-readable JSON
+"#;
+
+// Options that take a value; everything else starting with "--" is a switch
+const VALUE_OPTIONS: &[&str] = &[
+    "--vault",
+    "--due",
+    "--status",
+    "--priority",
+    "--estimate",
+    "--board",
+    "--tag",
+    "--limit",
+];
+
+struct CliArgs {
+    positionals: Vec<String>,
+    options: Vec<(String, String)>,
+    switches: Vec<String>,
+}
+
+impl CliArgs {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut parsed = Self {
+            positionals: Vec::new(),
+            options: Vec::new(),
+            switches: Vec::new(),
+        };
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if VALUE_OPTIONS.contains(&arg.as_str()) {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| format!("{} needs a value", arg))?;
+                parsed.options.push((arg.clone(), value.clone()));
+            } else if arg.starts_with("--") {
+                parsed.switches.push(arg.clone());
+            } else {
+                parsed.positionals.push(arg.clone());
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn values(&self, name: &str) -> Vec<String> {
+        self.options
+            .iter()
+            .filter(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+            .collect()
+    }
+
+    fn switch(&self, name: &str) -> bool {
+        self.switches.iter().any(|switch| switch == name)
+    }
+
+    fn positional(&self, index: usize) -> Option<&str> {
+        self.positionals.get(index).map(String::as_str)
+    }
+}
+
+fn invalid(message: String) -> ApiError {
+    ApiError {
+        code: ErrorCode::InvalidInput,
+        message,
+        details: None,
+    }
+}
+
+// Parse a lowercase wire value such as "todo" or "p1" the same way the frontend sends it
+fn parse_wire<T: serde::de::DeserializeOwned>(name: &str, value: &str) -> Result<T, ApiError> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| invalid(format!("Invalid {}: {}", name, value)))
+}
+
+pub fn resolve_vault(value: Option<&str>) -> Result<PathBuf, ApiError> {
+    let value = value.ok_or_else(|| invalid("--vault <path> is required".to_string()))?;
+    let path = PathBuf::from(value);
+    path_policy::ensure_no_symlink(&path)?;
+    let canonical = path.canonicalize().map_err(|err| ApiError {
+        code: ErrorCode::NotFound,
+        message: format!("Vault path does not exist: {}", err),
+        details: Some(serde_json::json!({ "path": value })),
+    })?;
+    if !canonical.is_dir() {
+        return Err(ApiError {
+            code: ErrorCode::NotFound,
+            message: "Vault path is not a directory".to_string(),
+            details: Some(serde_json::json!({ "path": value })),
+        });
+    }
+    Ok(canonical)
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), ApiError> {
+    let data = serde_json::to_string_pretty(value).map_err(|err| ApiError {
+        code: ErrorCode::JsonError,
+        message: format!("Failed to encode output: {}", err),
+        details: None,
+    })?;
+    println!("{}", data);
+    Ok(())
+}
+
+fn print_task(task: &Task) {
+    println!(
+        "{}\t{}\t{}\t{}\t{}",
+        task.id,
+        task.status,
+        task.priority
+            .map(|priority| priority.to_string())
+            .unwrap_or_else(|| "-".to_string()),
+        task.due_date.as_deref().unwrap_or("-"),
+        task.title
+    );
+}
+
+fn run_scan(vault_root: &Path, json: bool) -> Result<(), ApiError> {
+    let (files, warnings) = vault_service::list_markdown_files(vault_root)?;
+    for warning in &warnings {
+        eprintln!("warning: {}", warning.message);
+    }
+    if json {
+        return print_json(&files);
+    }
+    for file in files {
+        println!("{}", file);
+    }
+    Ok(())
+}
+
+fn run_search(service: &PlanningService, args: &CliArgs, json: bool) -> Result<(), ApiError> {
+    let query = args.positionals[1..].join(" ");
+    if query.trim().is_empty() {
+        return Err(invalid("search needs a query".to_string()));
+    }
+    let limit = match args.value("--limit") {
+        Some(value) => value
+            .parse()
+            .map_err(|_| invalid(format!("Invalid limit: {}", value)))?,
+        None => 20,
+    };
+
+    let hits = service.search_tasks(&query, args.switch("--notes"), limit)?;
+    if json {
+        return print_json(&hits);
+    }
+    for hit in hits {
+        println!("{:.2}\t{}\t{}", hit.score, hit.task.id, hit.task.title);
+    }
+    Ok(())
+}
+
+fn run_task_add(
+    vault_root: &Path,
+    service: &PlanningService,
+    args: &CliArgs,
+    json: bool,
+) -> Result<(), ApiError> {
+    let title = args.positionals[2..].join(" ");
+    if title.trim().is_empty() {
+        return Err(invalid("task add needs a title".to_string()));
+    }
+    let status: TaskStatus = match args.value("--status") {
+        Some(value) => parse_wire("status", value)?,
+        None => TaskStatus::Todo,
+    };
+    let priority: Option<TaskPriority> = args
+        .value("--priority")
+        .map(|value| parse_wire("priority", value))
+        .transpose()?;
+    let estimate_min = args
+        .value("--estimate")
+        .map(|value| {
+            value
+                .parse::<i64>()
+                .map_err(|_| invalid(format!("Invalid estimate: {}", value)))
+        })
+        .transpose()?;
+
+    // Scripts usually mean "today" when they add a todo without a due date
+    let due_date = match args.value("--due") {
+        Some(value) => Some(value.to_string()),
+        None if matches!(status, TaskStatus::Todo | TaskStatus::Doing) => {
+            let planning_settings =
+                settings_repo::get_planning_settings(vault_root).unwrap_or_default();
+            let tz = settings_repo::resolve_timezone(&planning_settings);
+            Some(timezone::today_in(tz).format("%Y-%m-%d").to_string())
+        }
+        None => None,
+    };
+    let tags = args.values("--tag");
+
+    let task = service.create_task(CreateTaskInput {
+        title,
+        description: None,
+        status,
+        priority,
+        due_date,
+        board_id: args.value("--board").map(str::to_string),
+        estimate_min,
+        tags: (!tags.is_empty()).then_some(tags),
+        labels: None,
+        subtasks: None,
+        periodicity: None,
+        scheduled_start: None,
+        scheduled_end: None,
+        note_path: None,
+        color: None,
+        op_id: None,
+    })?;
+
+    if json {
+        return print_json(&task);
+    }
+    print_task(&task);
+    Ok(())
+}
+
+fn run_task_list(service: &PlanningService, args: &CliArgs, json: bool) -> Result<(), ApiError> {
+    let status: Option<TaskStatus> = args
+        .value("--status")
+        .map(|value| parse_wire("status", value))
+        .transpose()?;
+    let tasks = service.list_tasks(status, args.switch("--archived"))?;
+    if json {
+        return print_json(&tasks);
+    }
+    for task in &tasks {
+        print_task(task);
+    }
+    Ok(())
+}
+
+fn run_export(service: &PlanningService, args: &CliArgs, json: bool) -> Result<(), ApiError> {
+    match args.positional(1) {
+        Some("board") => {
+            let board_id = args.positional(2).unwrap_or(DEFAULT_BOARD_ID);
+            let response = service.export_board_md(board_id)?;
+            if json {
+                return print_json(&response);
+            }
+            println!("{}\t{} tasks", response.md_path, response.task_count);
+            Ok(())
+        }
+        Some(format @ ("org" | "taskpaper")) => {
+            let format: PlainTextFormat = parse_wire("format", format)?;
+            let response = service.export_plaintext(format, args.positional(2))?;
+            if json {
+                return print_json(&response);
+            }
+            println!("{}\t{} tasks", response.path, response.task_count);
+            Ok(())
+        }
+        Some("tasks") => print_json(&service.list_tasks(None, true)?),
+        _ => Err(invalid(
+            "export needs \"board\", \"org\", \"taskpaper\" or \"tasks\"".to_string(),
+        )),
+    }
+}
+
+fn dispatch(args: &CliArgs) -> Result<(), ApiError> {
+    let json = args.switch("--json");
+    let vault_root = resolve_vault(args.value("--vault"))?;
+
+    if args.positional(0) == Some("scan") {
+        return run_scan(&vault_root, json);
+    }
+
+    let service = PlanningService::open(&vault_root)?;
+    match (args.positional(0), args.positional(1)) {
+        (Some("search"), _) => run_search(&service, args, json),
+        (Some("task"), Some("add")) => run_task_add(&vault_root, &service, args, json),
+        (Some("task"), Some("list")) => run_task_list(&service, args, json),
+        (Some("task"), Some("done")) => {
+            let task_id = args
+                .positional(2)
+                .ok_or_else(|| invalid("task done needs a task id".to_string()))?;
+            service.mark_task_done(task_id)?;
+            if !json {
+                println!("{}", task_id);
+            }
+            Ok(())
+        }
+        (Some("export"), _) => run_export(&service, args, json),
+        _ => Err(invalid("Unknown command".to_string())),
+    }
+}
+
+// Run a CLI command against a vault without starting the GUI; returns the process exit code
+pub fn run(args: &[String]) -> i32 {
+    // Logs go to stderr so stdout stays parseable for scripts
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::WARN)
+        .with_target(false)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let args = match CliArgs::parse(args) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return 2;
+        }
+    };
+    if args.positionals.is_empty() || args.switch("--help") {
+        eprint!("{}", USAGE);
+        return if args.switch("--help") { 0 } else { 2 };
+    }
+
+    let known = matches!(
+        (args.positional(0), args.positional(1)),
+        (Some("scan" | "search" | "export"), _) | (Some("task"), Some("add" | "list" | "done"))
+    );
+    if !known {
+        eprintln!("error: Unknown command\n\n{}", USAGE);
+        return 2;
+    }
+
+    match dispatch(&args) {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("error: [{}] {}", err.code.as_str(), err.message);
+            1
+        }
+    }
+}
@@ -1,11 +1,29 @@
-use crate::features::ai::embedding::EmbeddingEngine;
-use tauri::State;
+use crate::features::ai::embedding::{
+    EmbeddingEngine, EmbeddingModelChoice, EmbeddingStatus, EMBEDDING_DOWNLOAD_EVENT,
+};
+use crate::features::ai::semantic_index::{
+    self, HybridSearchResponse, RelatedNote, SemanticIndexReport,
+};
+use crate::ipc::{ApiError, ApiResponse, ErrorCode};
+use crate::repo::settings_repo;
+use crate::state::{AppState, VaultState};
+use tauri::{AppHandle, Emitter, State};
+
+// Use the embedding model chosen in the open vault's AI settings
+fn select_vault_model(engine: &EmbeddingEngine, vault_state: &VaultState) {
+    let vault_root = vault_state.root.lock().ok().and_then(|root| root.clone());
+    if let Some(settings) = vault_root.and_then(|root| settings_repo::get_ai_settings(&root).ok()) {
+        engine.select(settings.embedding_model);
+    }
+}
 
 #[tauri::command]
 pub async fn ai_generate_embeddings(
     texts: Vec<String>,
     engine: State<'_, EmbeddingEngine>,
+    vault_state: State<'_, VaultState>,
 ) -> Result<Vec<Vec<f32>>, String> {
+    select_vault_model(&engine, &vault_state);
     engine.embed_documents(texts).map_err(|e| e.to_string())
 }
 
@@ -14,7 +32,9 @@ pub async fn ai_search_similar(
     query: String,
     candidates: Vec<String>,
     engine: State<'_, EmbeddingEngine>,
+    vault_state: State<'_, VaultState>,
 ) -> Result<Vec<(String, f32)>, String> {
+    select_vault_model(&engine, &vault_state);
     // 1. Embed query
     let query_embedding_res = engine.embed_documents(vec![query.clone()]);
     let query_embedding = match query_embedding_res {
@@ -42,3 +62,119 @@ pub async fn ai_search_similar(
 
     Ok(results)
 }
+
+// Available embedding models, which are downloaded, and whether the model host is reachable
+#[tauri::command]
+pub async fn get_embedding_status(
+    engine: State<'_, EmbeddingEngine>,
+    vault_state: State<'_, VaultState>,
+    app_state: State<'_, AppState>,
+) -> Result<ApiResponse<EmbeddingStatus>, ApiError> {
+    select_vault_model(&engine, &vault_state);
+    let status = engine.status(&app_state.http_client).await;
+    Ok(ApiResponse::ok(status))
+}
+
+// Download an embedding model (the vault's selected one by default), emitting
+// EMBEDDING_DOWNLOAD_EVENT as files arrive
+#[tauri::command]
+pub async fn download_embedding_model(
+    model: Option<EmbeddingModelChoice>,
+    engine: State<'_, EmbeddingEngine>,
+    vault_state: State<'_, VaultState>,
+    app_state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<EmbeddingStatus>, ApiError> {
+    select_vault_model(&engine, &vault_state);
+    let model = model.unwrap_or_else(|| engine.selected());
+    engine
+        .download(&app_state.http_client, model, |progress| {
+            if let Err(e) = app_handle.emit(EMBEDDING_DOWNLOAD_EVENT, progress) {
+                tracing::warn!(target: "ai", "failed to emit download progress: {}", e);
+            }
+        })
+        .await?;
+
+    let status = engine.status(&app_state.http_client).await;
+    Ok(ApiResponse::ok(status))
+}
+
+// Chunk and embed changed notes into the semantic index; full re-embeds every note
+#[tauri::command]
+pub async fn rebuild_semantic_index(
+    full: Option<bool>,
+    engine: State<'_, EmbeddingEngine>,
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<SemanticIndexReport>, ApiError> {
+    let vault_path = {
+        let vault_root = vault_state.root.lock()?;
+        match vault_root.as_ref() {
+            Some(path) => path.clone(),
+            None => {
+                return Err(ApiError {
+                    code: ErrorCode::VaultNotSelected,
+                    message: "Vault not selected".to_string(),
+                    details: None,
+                });
+            }
+        }
+    };
+
+    select_vault_model(&engine, &vault_state);
+    let report = semantic_index::rebuild(&vault_path, &engine, full.unwrap_or(false))?;
+    Ok(ApiResponse::ok(report))
+}
+
+#[tauri::command]
+pub async fn search_hybrid(
+    query: String,
+    limit: Option<usize>,
+    engine: State<'_, EmbeddingEngine>,
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<HybridSearchResponse>, ApiError> {
+    let vault_path = {
+        let vault_root = vault_state.root.lock()?;
+        match vault_root.as_ref() {
+            Some(path) => path.clone(),
+            None => {
+                return Err(ApiError {
+                    code: ErrorCode::VaultNotSelected,
+                    message: "Vault not selected".to_string(),
+                    details: None,
+                });
+            }
+        }
+    };
+
+    select_vault_model(&engine, &vault_state);
+    let response =
+        semantic_index::search_hybrid(&vault_path, &engine, &query, limit.unwrap_or(20))?;
+    Ok(ApiResponse::ok(response))
+}
+
+#[tauri::command]
+pub async fn suggest_related_notes(
+    path: String,
+    limit: Option<usize>,
+    engine: State<'_, EmbeddingEngine>,
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<Vec<RelatedNote>>, ApiError> {
+    let vault_path = {
+        let vault_root = vault_state.root.lock()?;
+        match vault_root.as_ref() {
+            Some(path) => path.clone(),
+            None => {
+                return Err(ApiError {
+                    code: ErrorCode::VaultNotSelected,
+                    message: "Vault not selected".to_string(),
+                    details: None,
+                });
+            }
+        }
+    };
+
+    select_vault_model(&engine, &vault_state);
+    let related =
+        semantic_index::suggest_related_notes(&vault_path, &engine, &path, limit.unwrap_or(10))?;
+    Ok(ApiResponse::ok(related))
+}
@@ -0,0 +1,56 @@
+use tauri::{AppHandle, Emitter, State};
+
+use crate::domain::clipping::{self, ClipResult};
+use crate::features::{clipper, http_api::PLANNING_CHANGED_EVENT};
+use crate::ipc::{ApiError, ApiResponse, ErrorCode};
+use crate::services::planning_service::PlanningService;
+use crate::state::{AppState, VaultState};
+
+// Save the readable content of a web page as a markdown note in the clippings folder, with an
+// optional read-later task linked to it
+#[tauri::command]
+pub async fn clip_url(
+    url: String,
+    read_later: Option<bool>,
+    vault_state: State<'_, VaultState>,
+    app_state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<ClipResult>, ApiError> {
+    // The vault lock is not held across the page download
+    let vault_path = {
+        let vault_root = vault_state.root.lock()?;
+        vault_root.clone().ok_or_else(|| ApiError {
+            code: ErrorCode::VaultNotSelected,
+            message: "Vault not selected".to_string(),
+            details: None,
+        })?
+    };
+
+    let (final_url, html) = clipper::fetch_page(&app_state.http_client, &url).await?;
+    let page = clipper::extract_page(&html, &final_url);
+    let path = clipper::save_clipping(&vault_path, &page)?;
+
+    let task = if read_later.unwrap_or(false) {
+        let service = PlanningService::new(&app_handle, &vault_path)?;
+        let task = service.create_read_later_task(&page.title, &page.url, Some(&path))?;
+        let payload = serde_json::json!({ "source": "clipper" });
+        if let Err(e) = app_handle.emit(PLANNING_CHANGED_EVENT, payload) {
+            tracing::warn!(target: "clipper", "failed to emit planning change: {}", e);
+        }
+        Some(task)
+    } else {
+        None
+    };
+
+    tracing::info!(target: "clipper", "page clipped: path={}, read_later={}", path, task.is_some());
+    Ok(ApiResponse::ok(ClipResult {
+        word_count: clipping::word_count(&page.markdown),
+        path,
+        url: page.url,
+        title: page.title,
+        byline: page.byline,
+        site_name: page.site_name,
+        excerpt: page.excerpt,
+        task,
+    }))
+}
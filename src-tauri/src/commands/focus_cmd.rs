@@ -0,0 +1,126 @@
+use chrono::Utc;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::domain::focus::{FocusEndResponse, FocusSession, FocusStateDTO, FOCUS_STATE_EVENT};
+use crate::ipc::{ApiError, ApiResponse, ErrorCode};
+use crate::services::planning_service::PlanningService;
+use crate::state::{FocusState, VaultState};
+
+// Broadcast the current focus state so the frontend can toggle the distraction-free screen
+fn emit_focus_state(app_handle: &AppHandle, session: Option<FocusSession>) {
+    if let Err(e) = app_handle.emit(FOCUS_STATE_EVENT, FocusStateDTO::from_session(session)) {
+        tracing::warn!(target: "planning", "failed to emit focus state: {}", e);
+    }
+}
+
+// Start a focus session: start the task timer and enable do-not-disturb
+#[tauri::command]
+pub async fn focus_start(
+    task_id: String,
+    planned_min: i64,
+    vault_state: State<'_, VaultState>,
+    focus_state: State<'_, FocusState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<FocusStateDTO>, ApiError> {
+    if planned_min <= 0 {
+        return Err(ApiError {
+            code: ErrorCode::InvalidInput,
+            message: "planned_min must be positive".to_string(),
+            details: Some(serde_json::json!({ "planned_min": planned_min })),
+        });
+    }
+
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let mut current = focus_state.session.lock()?;
+    if let Some(active) = current.as_ref() {
+        return Err(ApiError {
+            code: ErrorCode::InvalidStateTransition,
+            message: "A focus session is already active".to_string(),
+            details: Some(serde_json::json!({ "task_id": active.task_id })),
+        });
+    }
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let task = service.start_focus(&task_id)?;
+
+    let started_at = Utc::now();
+    let session = FocusSession {
+        task_id: task.id,
+        title: task.title,
+        planned_min,
+        started_at: started_at.to_rfc3339(),
+        ends_at: (started_at + chrono::Duration::minutes(planned_min)).to_rfc3339(),
+    };
+    *current = Some(session.clone());
+    drop(current);
+
+    emit_focus_state(&app_handle, Some(session.clone()));
+
+    Ok(ApiResponse::ok(FocusStateDTO::from_session(Some(session))))
+}
+
+// End the active focus session: stop the task timer and lift do-not-disturb
+#[tauri::command]
+pub async fn focus_end(
+    vault_state: State<'_, VaultState>,
+    focus_state: State<'_, FocusState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<FocusEndResponse>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let mut current = focus_state.session.lock()?;
+    let session = match current.clone() {
+        Some(session) => session,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::InvalidStateTransition,
+                message: "No focus session is active".to_string(),
+                details: None,
+            });
+        }
+    };
+    let elapsed_min = chrono::DateTime::parse_from_rfc3339(&session.started_at)
+        .map(|started_at| (Utc::now() - started_at.with_timezone(&Utc)).num_minutes())
+        .unwrap_or(0);
+
+    // Without a vault there is no timer to stop; a failed stop leaves the session active
+    let task = match vault_root.as_ref() {
+        Some(vault_path) => {
+            let service = PlanningService::new(&app_handle, vault_path)?;
+            Some(service.end_focus(&session.task_id)?)
+        }
+        None => None,
+    };
+    *current = None;
+    drop(current);
+
+    emit_focus_state(&app_handle, None);
+
+    Ok(ApiResponse::ok(FocusEndResponse {
+        session,
+        elapsed_min,
+        task,
+    }))
+}
+
+// Get the current focus state (used to restore the focus screen after reload)
+#[tauri::command]
+pub async fn focus_get_state(
+    focus_state: State<'_, FocusState>,
+) -> Result<ApiResponse<FocusStateDTO>, ApiError> {
+    let session = focus_state.session.lock()?.clone();
+    let mut state = FocusStateDTO::from_session(session);
+    state.do_not_disturb = focus_state.do_not_disturb();
+
+    Ok(ApiResponse::ok(state))
+}
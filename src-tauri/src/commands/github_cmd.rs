@@ -0,0 +1,92 @@
+use std::path::{Path, PathBuf};
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::domain::github::{GithubStatusDTO, GithubSyncReport};
+use crate::features::{github, http_api::PLANNING_CHANGED_EVENT};
+use crate::ipc::{ApiError, ApiResponse, ErrorCode};
+use crate::paths;
+use crate::repo::credentials_repo;
+use crate::services::planning_service::PlanningService;
+use crate::state::{AppState, VaultState};
+
+fn current_vault(vault_state: &State<'_, VaultState>) -> Result<PathBuf, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    vault_root.clone().ok_or_else(|| ApiError {
+        code: ErrorCode::VaultNotSelected,
+        message: "Vault not selected".to_string(),
+        details: None,
+    })
+}
+
+fn status(app_handle: &AppHandle, vault_path: &Path) -> Result<GithubStatusDTO, ApiError> {
+    let config_dir = paths::get_app_config_dir(app_handle)?;
+    let service = PlanningService::new(app_handle, vault_path)?;
+    Ok(GithubStatusDTO {
+        token_set: credentials_repo::get_github_token(&config_dir)?.is_some(),
+        links: service.list_github_board_links()?,
+    })
+}
+
+// Whether a GitHub token is stored and which boards are linked
+#[tauri::command]
+pub async fn github_get_status(
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<GithubStatusDTO>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    Ok(ApiResponse::ok(status(&app_handle, &vault_path)?))
+}
+
+// Store the personal access token outside the vault; None removes it
+#[tauri::command]
+pub async fn github_set_token(
+    token: Option<String>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<GithubStatusDTO>, ApiError> {
+    let config_dir = paths::get_app_config_dir(&app_handle)?;
+    credentials_repo::save_github_token(&config_dir, token.as_deref())?;
+    let vault_path = current_vault(&vault_state)?;
+    Ok(ApiResponse::ok(status(&app_handle, &vault_path)?))
+}
+
+// Link a board to an "owner/name" repository; None unlinks it
+#[tauri::command]
+pub async fn github_link_board(
+    board_id: String,
+    repo: Option<String>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<GithubStatusDTO>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    service.link_board_to_github(&board_id, repo.as_deref())?;
+    Ok(ApiResponse::ok(status(&app_handle, &vault_path)?))
+}
+
+// Import new issues and exchange status/label changes with every linked repository
+#[tauri::command]
+pub async fn github_sync_now(
+    vault_state: State<'_, VaultState>,
+    app_state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<GithubSyncReport>, ApiError> {
+    // The vault lock is not held across network calls
+    let vault_path = current_vault(&vault_state)?;
+    let config_dir = paths::get_app_config_dir(&app_handle)?;
+    let token = credentials_repo::get_github_token(&config_dir)?.ok_or_else(|| ApiError {
+        code: ErrorCode::InvalidInput,
+        message: "GitHub token not set".to_string(),
+        details: None,
+    })?;
+
+    let report = github::sync_now(&vault_path, &app_state.http_client, token).await?;
+    if report.imported + report.updated_tasks > 0 {
+        let payload = serde_json::json!({ "source": "github" });
+        if let Err(e) = app_handle.emit(PLANNING_CHANGED_EVENT, payload) {
+            tracing::warn!(target: "github", "failed to emit planning change: {}", e);
+        }
+    }
+    Ok(ApiResponse::ok(report))
+}
@@ -0,0 +1,81 @@
+use tauri::{AppHandle, State};
+
+use crate::features::http_api::{self, HttpApiStatus};
+use crate::ipc::{ApiError, ApiResponse, ErrorCode};
+use crate::repo::settings_repo::{self, HttpApiSettings};
+use crate::state::VaultState;
+
+fn status(app_handle: &AppHandle, settings: HttpApiSettings) -> HttpApiStatus {
+    HttpApiStatus {
+        enabled: settings.enabled,
+        port: settings.port,
+        token: settings.token,
+        metrics: settings.metrics,
+        running: http_api::is_running(app_handle),
+    }
+}
+
+// Get the localhost HTTP API settings of the current vault
+#[tauri::command]
+pub async fn http_api_get_settings(
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<HttpApiStatus>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let settings = settings_repo::get_http_api_settings(vault_path)?;
+    Ok(ApiResponse::ok(status(&app_handle, settings)))
+}
+
+// Enable, disable or reconfigure the localhost HTTP API; a token is generated on first enable
+#[tauri::command]
+pub async fn http_api_save_settings(
+    enabled: bool,
+    port: Option<u16>,
+    regenerate_token: Option<bool>,
+    metrics: Option<bool>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<HttpApiStatus>, ApiError> {
+    let settings = {
+        let vault_root = vault_state.root.lock()?;
+        let vault_path = match vault_root.as_ref() {
+            Some(path) => path,
+            None => {
+                return Err(ApiError {
+                    code: ErrorCode::VaultNotSelected,
+                    message: "Vault not selected".to_string(),
+                    details: None,
+                });
+            }
+        };
+
+        let mut settings = settings_repo::get_http_api_settings(vault_path)?;
+        settings.enabled = enabled;
+        if let Some(port) = port {
+            settings.port = port;
+        }
+        if let Some(metrics) = metrics {
+            settings.metrics = metrics;
+        }
+        if regenerate_token.unwrap_or(false) || (enabled && settings.token.is_empty()) {
+            settings.token = uuid::Uuid::new_v4().simple().to_string();
+        }
+        settings_repo::save_http_api_settings(vault_path, settings.clone())?;
+        settings
+    };
+
+    // The vault lock is released first; stopping waits for in-flight requests that take it
+    http_api::apply_settings(&app_handle, &settings)?;
+    Ok(ApiResponse::ok(status(&app_handle, settings)))
+}
@@ -0,0 +1,8 @@
+use crate::features::metrics::{self, PerfMetrics};
+use crate::ipc::{ApiError, ApiResponse};
+
+// Latency, database and scan histograms recorded since the app started
+#[tauri::command]
+pub async fn get_perf_metrics() -> Result<ApiResponse<PerfMetrics>, ApiError> {
+    Ok(ApiResponse::ok(metrics::snapshot()?))
+}
@@ -1,4 +1,15 @@
 pub mod ai_cmd;
+pub mod clipper_cmd;
+pub mod focus_cmd;
+pub mod github_cmd;
+pub mod http_api_cmd;
+pub mod metrics_cmd;
 pub mod planning_cmd;
 pub mod plugins;
+pub mod reading_list_cmd;
+pub mod reminder_cmd;
+pub mod settings_cmd;
 pub mod vault;
+pub mod webhook_cmd;
+pub mod webview_cmd;
+pub mod workspace_cmd;
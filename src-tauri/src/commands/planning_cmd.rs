@@ -1,18 +1,52 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
 use tauri::{AppHandle, Manager, State};
 
+use crate::domain::analytics::EstimateReportDTO;
+use crate::domain::board::{
+    BoardColumn, BoardColumnInput, BoardIndexResult, BoardMeta, DoneTasksPage, ExportBoardResponse,
+};
+use crate::domain::calendar::CalendarRangeDTO;
+use crate::domain::capture::{
+    ImageCaptureResponse, LineRange, NoteCaptureResponse, SmartCaptureResponse,
+    VoiceCaptureResponse,
+};
+use crate::domain::export::{ImportResult, MergeVaultResult, PlanningExport};
+use crate::domain::journal::{JournalStreakDTO, MissingDay, StreakBasis};
+use crate::domain::links::TaskNoteLink;
+use crate::domain::note_query::{NoteQueryInput, NoteQueryResult};
+use crate::domain::outliner_import::{OutlinerFormat, OutlinerImportResponse};
+use crate::domain::plaintext_export::{PlainTextExportResponse, PlainTextFormat};
 use crate::domain::planning::{
-    CreateTaskInput, OpenDailyInput, OpenDailyResponse, OpenTaskNoteResponse, ReorderTaskInput,
-    Task, TodayDTO, UpdateTaskInput,
+    BatchCreateResponse, CreateTaskInput, DbEncryptionStatus, HealthCheckReport,
+    LayoutMigrationResult, OpenDailyInput, OpenDailyResponse, OpenTaskNoteResponse,
+    ReorderTaskInput, SlugRepairResult, Task, TaskDueBrief, TaskNoteConsolidation, TaskPeriodicity,
+    Timer, TimerRecoveryAction, TimerRecoveryResult, TodayDTO, UpdateTaskInput,
+};
+use crate::domain::rules::RulesRunResult;
+use crate::domain::schedule::{
+    DayScheduleDTO, ScheduleTaskInput, ScheduleTaskResponse, ShiftScheduleResponse,
 };
-use crate::ipc::{ApiError, ApiResponse};
-use crate::repo::settings_repo::{self, AiSettings};
-use crate::services::planning_service::PlanningService;
-use crate::state::{AppState, VaultState};
+use crate::domain::search::TaskSearchHit;
+use crate::domain::sprint::{Sprint, SprintBurndown, SprintInput, SprintSummaryDTO};
+use crate::domain::table_import::{TableImportResponse, TableMapping};
+use crate::domain::tagging::{self, TagCount, TagSuggestionResponse};
+use crate::domain::task_fields::{
+    BoardField, BoardFieldInput, FieldValuesByTask, TaskFieldFilter, TaskFieldsDTO,
+};
+use crate::domain::week::WeekReviewDTO;
+use crate::features::ai::embedding::EmbeddingEngine;
+use crate::ipc::{ApiError, ApiResponse, ErrorCode};
+use crate::paths::VaultLayout;
+use crate::repo::settings_repo::{self, AiSettings, PlanningSettings};
+use crate::services::planning_service::{self, PlanningService};
+use crate::state::{AppState, BoardIndexState, TimerRecoveryState, VaultState};
 
 // Get all data needed for today's home page
 #[tauri::command]
 pub async fn planning_list_today(
-    today: String,
+    today: Option<String>,
     vault_state: State<'_, VaultState>,
     app_handle: AppHandle,
 ) -> Result<ApiResponse<TodayDTO>, ApiError> {
@@ -21,7 +55,34 @@ pub async fn planning_list_today(
         Some(path) => path,
         None => {
             return Err(ApiError {
-                code: "VaultNotSelected".to_string(),
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.get_today_data(today.as_deref())?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Page through done tasks, including those aged out of the kanban
+#[tauri::command]
+pub async fn planning_list_done(
+    offset: Option<i64>,
+    limit: Option<i64>,
+    board: Option<String>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<DoneTasksPage>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
                 message: "Vault not selected".to_string(),
                 details: None,
             });
@@ -29,7 +90,8 @@ pub async fn planning_list_today(
     };
 
     let service = PlanningService::new(&app_handle, vault_path)?;
-    let data = service.get_today_data(&today)?;
+    let data =
+        service.list_done_tasks(offset.unwrap_or(0), limit.unwrap_or(50), board.as_deref())?;
 
     Ok(ApiResponse::ok(data))
 }
@@ -46,7 +108,7 @@ pub async fn planning_create_task(
         Some(path) => path,
         None => {
             return Err(ApiError {
-                code: "VaultNotSelected".to_string(),
+                code: ErrorCode::VaultNotSelected,
                 message: "Vault not selected".to_string(),
                 details: None,
             });
@@ -59,6 +121,31 @@ pub async fn planning_create_task(
     Ok(ApiResponse::ok(task))
 }
 
+// Create several tasks at once, e.g. the suggestions of a smart capture; all or none are kept
+#[tauri::command]
+pub async fn planning_create_tasks_batch(
+    inputs: Vec<CreateTaskInput>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<BatchCreateResponse>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.create_tasks_batch(inputs)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
 // Update an existing task
 #[tauri::command]
 pub async fn planning_update_task(
@@ -71,7 +158,7 @@ pub async fn planning_update_task(
         Some(path) => path,
         None => {
             return Err(ApiError {
-                code: "VaultNotSelected".to_string(),
+                code: ErrorCode::VaultNotSelected,
                 message: "Vault not selected".to_string(),
                 details: None,
             });
@@ -96,7 +183,7 @@ pub async fn planning_mark_done(
         Some(path) => path,
         None => {
             return Err(ApiError {
-                code: "VaultNotSelected".to_string(),
+                code: ErrorCode::VaultNotSelected,
                 message: "Vault not selected".to_string(),
                 details: None,
             });
@@ -121,7 +208,7 @@ pub async fn planning_reopen_task(
         Some(path) => path,
         None => {
             return Err(ApiError {
-                code: "VaultNotSelected".to_string(),
+                code: ErrorCode::VaultNotSelected,
                 message: "Vault not selected".to_string(),
                 details: None,
             });
@@ -146,7 +233,7 @@ pub async fn planning_start_task(
         Some(path) => path,
         None => {
             return Err(ApiError {
-                code: "VaultNotSelected".to_string(),
+                code: ErrorCode::VaultNotSelected,
                 message: "Vault not selected".to_string(),
                 details: None,
             });
@@ -171,7 +258,7 @@ pub async fn planning_stop_task(
         Some(path) => path,
         None => {
             return Err(ApiError {
-                code: "VaultNotSelected".to_string(),
+                code: ErrorCode::VaultNotSelected,
                 message: "Vault not selected".to_string(),
                 details: None,
             });
@@ -184,6 +271,142 @@ pub async fn planning_stop_task(
     Ok(ApiResponse::ok(()))
 }
 
+// Report the timer left running by the previous session, or resume/close it
+#[tauri::command]
+pub async fn planning_recover_timers(
+    action: Option<TimerRecoveryAction>,
+    recovery_state: State<'_, TimerRecoveryState>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<TimerRecoveryResult>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let mut dangling_timer_id = recovery_state.dangling_timer_id.lock()?;
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.recover_timers(dangling_timer_id.as_deref(), action)?;
+
+    // Once resolved (or gone) there is nothing left to recover
+    if action.is_some() || data.dangling.is_none() {
+        *dangling_timer_id = None;
+    }
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Timer history of a task
+#[tauri::command]
+pub async fn planning_list_timers(
+    task_id: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<Timer>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.list_timers(&task_id)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Record a manual time entry
+#[tauri::command]
+pub async fn planning_add_manual_timer(
+    task_id: String,
+    start: String,
+    end: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Timer>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.add_manual_timer(&task_id, &start, &end)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Correct a stopped timer
+#[tauri::command]
+pub async fn planning_edit_timer(
+    timer_id: String,
+    start: String,
+    end: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Timer>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.edit_timer(&timer_id, &start, &end)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Delete a stopped timer
+#[tauri::command]
+pub async fn planning_delete_timer(
+    timer_id: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<()>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    service.delete_timer(&timer_id)?;
+
+    Ok(ApiResponse::ok(()))
+}
+
 // Open a daily log file (create if not exists)
 #[tauri::command]
 pub async fn planning_open_daily(
@@ -196,7 +419,7 @@ pub async fn planning_open_daily(
         Some(path) => path,
         None => {
             return Err(ApiError {
-                code: "VaultNotSelected".to_string(),
+                code: ErrorCode::VaultNotSelected,
                 message: "Vault not selected".to_string(),
                 details: None,
             });
@@ -221,7 +444,7 @@ pub async fn planning_open_task_note(
         Some(path) => path,
         None => {
             return Err(ApiError {
-                code: "VaultNotSelected".to_string(),
+                code: ErrorCode::VaultNotSelected,
                 message: "Vault not selected".to_string(),
                 details: None,
             });
@@ -238,6 +461,7 @@ pub async fn planning_open_task_note(
 #[tauri::command]
 pub async fn planning_reorder_tasks(
     tasks: Vec<ReorderTaskInput>,
+    override_wip_limit: Option<bool>,
     vault_state: State<'_, VaultState>,
     app_handle: AppHandle,
 ) -> Result<ApiResponse<()>, ApiError> {
@@ -246,7 +470,7 @@ pub async fn planning_reorder_tasks(
         Some(path) => path,
         None => {
             return Err(ApiError {
-                code: "VaultNotSelected".to_string(),
+                code: ErrorCode::VaultNotSelected,
                 message: "Vault not selected".to_string(),
                 details: None,
             });
@@ -254,25 +478,24 @@ pub async fn planning_reorder_tasks(
     };
 
     let service = PlanningService::new(&app_handle, vault_path)?;
-    service.reorder_tasks(tasks)?;
+    service.reorder_tasks(tasks, override_wip_limit.unwrap_or(false))?;
 
     Ok(ApiResponse::ok(()))
 }
 
-// Get UI state for the current vault
+// Schedule a task into a time block with overlap detection
 #[tauri::command]
-#[allow(dead_code)]
-pub async fn planning_get_ui_state(
-    vault_id: String,
+pub async fn planning_schedule_task(
+    input: ScheduleTaskInput,
     vault_state: State<'_, VaultState>,
     app_handle: AppHandle,
-) -> Result<ApiResponse<Option<String>>, ApiError> {
+) -> Result<ApiResponse<ScheduleTaskResponse>, ApiError> {
     let vault_root = vault_state.root.lock()?;
     let vault_path = match vault_root.as_ref() {
         Some(path) => path,
         None => {
             return Err(ApiError {
-                code: "VaultNotSelected".to_string(),
+                code: ErrorCode::VaultNotSelected,
                 message: "Vault not selected".to_string(),
                 details: None,
             });
@@ -280,26 +503,26 @@ pub async fn planning_get_ui_state(
     };
 
     let service = PlanningService::new(&app_handle, vault_path)?;
-    let ui_state = service.get_ui_state(&vault_id)?;
+    let data = service.schedule_task(input)?;
 
-    Ok(ApiResponse::ok(ui_state))
+    Ok(ApiResponse::ok(data))
 }
 
-// Set UI state for the current vault
+// Shift the scheduled blocks of several tasks by delta_minutes in one transaction
 #[tauri::command]
-#[allow(dead_code)]
-pub async fn planning_set_ui_state(
-    vault_id: String,
-    partial_state_json: String,
+pub async fn planning_shift_schedule(
+    task_ids: Vec<String>,
+    delta_minutes: i64,
+    force: Option<bool>,
     vault_state: State<'_, VaultState>,
     app_handle: AppHandle,
-) -> Result<ApiResponse<()>, ApiError> {
+) -> Result<ApiResponse<ShiftScheduleResponse>, ApiError> {
     let vault_root = vault_state.root.lock()?;
     let vault_path = match vault_root.as_ref() {
         Some(path) => path,
         None => {
             return Err(ApiError {
-                code: "VaultNotSelected".to_string(),
+                code: ErrorCode::VaultNotSelected,
                 message: "Vault not selected".to_string(),
                 details: None,
             });
@@ -307,104 +530,1740 @@ pub async fn planning_set_ui_state(
     };
 
     let service = PlanningService::new(&app_handle, vault_path)?;
-    service.set_ui_state(&vault_id, &partial_state_json)?;
+    let data = service.shift_schedule(&task_ids, delta_minutes, force.unwrap_or(false))?;
 
-    Ok(ApiResponse::ok(()))
+    Ok(ApiResponse::ok(data))
 }
 
-// Delete a task
+// Get ordered time blocks and gaps for a day
 #[tauri::command]
-pub async fn planning_delete_task(
+pub async fn planning_get_day_schedule(
+    day: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<DayScheduleDTO>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.get_day_schedule(&day)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Preview the next n occurrence dates of a recurring task
+#[tauri::command]
+pub async fn planning_preview_occurrences(
     task_id: String,
+    n: usize,
     vault_state: State<'_, VaultState>,
     app_handle: AppHandle,
-) -> Result<ApiResponse<()>, ApiError> {
+) -> Result<ApiResponse<Vec<String>>, ApiError> {
     let vault_root = vault_state.root.lock()?;
     let vault_path = match vault_root.as_ref() {
         Some(path) => path,
         None => {
             return Err(ApiError {
-                code: "VaultNotSelected".to_string(),
+                code: ErrorCode::VaultNotSelected,
                 message: "Vault not selected".to_string(),
                 details: None,
             });
         }
     };
 
-    let mut service = PlanningService::new(&app_handle, vault_path)?;
-    service.delete_task(&task_id)?;
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.preview_occurrences(&task_id, n)?;
 
-    Ok(ApiResponse::ok(()))
+    Ok(ApiResponse::ok(data))
 }
 
-// AI Smart Capture
+// Convert a periodicity into an RFC 5545 RRULE string
 #[tauri::command]
-pub async fn planning_ai_smart_capture(
-    text: String,
+pub async fn planning_periodicity_to_rrule(
+    periodicity: TaskPeriodicity,
     vault_state: State<'_, VaultState>,
-    app_state: State<'_, AppState>,
-    _app_handle: AppHandle,
-) -> Result<ApiResponse<Vec<CreateTaskInput>>, ApiError> {
-    let vault_path = {
-        let vault_root = vault_state.root.lock()?;
-        match vault_root.as_ref() {
-            Some(path) => path.clone(),
-            None => {
-                return Err(ApiError {
-                    code: "VaultNotSelected".to_string(),
-                    message: "Vault not selected".to_string(),
-                    details: None,
-                });
-            }
+    app_handle: AppHandle,
+) -> Result<ApiResponse<String>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
         }
     };
 
-    // Call static method directly
-    let tasks =
-        PlanningService::ai_smart_capture(&vault_path, &app_state.http_client, &text).await?;
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.periodicity_to_rrule(&periodicity)?;
 
-    Ok(ApiResponse::ok(tasks))
+    Ok(ApiResponse::ok(data))
 }
 
-// Get AI Settings
+// Parse an RFC 5545 RRULE string into a periodicity starting at start_date
 #[tauri::command]
-pub async fn planning_get_ai_settings(
+pub async fn planning_periodicity_from_rrule(
+    rrule: String,
+    start_date: String,
     vault_state: State<'_, VaultState>,
-) -> Result<ApiResponse<AiSettings>, ApiError> {
+    app_handle: AppHandle,
+) -> Result<ApiResponse<TaskPeriodicity>, ApiError> {
     let vault_root = vault_state.root.lock()?;
     let vault_path = match vault_root.as_ref() {
         Some(path) => path,
         None => {
             return Err(ApiError {
-                code: "VaultNotSelected".to_string(),
+                code: ErrorCode::VaultNotSelected,
                 message: "Vault not selected".to_string(),
                 details: None,
             });
         }
     };
 
-    let settings = settings_repo::get_ai_settings(vault_path)?;
-    Ok(ApiResponse::ok(settings))
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.periodicity_from_rrule(&rrule, &start_date)?;
+
+    Ok(ApiResponse::ok(data))
 }
 
-// Save AI Settings
+// Calendar buckets for a month or week view over an inclusive day range
 #[tauri::command]
-pub async fn planning_save_ai_settings(
-    settings: AiSettings,
+pub async fn planning_calendar_range(
+    from: String,
+    to: String,
     vault_state: State<'_, VaultState>,
-) -> Result<ApiResponse<()>, ApiError> {
+    app_handle: AppHandle,
+) -> Result<ApiResponse<CalendarRangeDTO>, ApiError> {
     let vault_root = vault_state.root.lock()?;
     let vault_path = match vault_root.as_ref() {
         Some(path) => path,
         None => {
             return Err(ApiError {
-                code: "VaultNotSelected".to_string(),
+                code: ErrorCode::VaultNotSelected,
                 message: "Vault not selected".to_string(),
                 details: None,
             });
         }
     };
 
-    settings_repo::save_ai_settings(vault_path, settings)?;
-    Ok(ApiResponse::ok(()))
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.get_calendar_range(&from, &to)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Estimate vs actual report over an inclusive day range
+#[tauri::command]
+pub async fn planning_estimate_report(
+    from: String,
+    to: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<EstimateReportDTO>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.get_estimate_report(&from, &to)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Apply due-date escalation rules now
+#[tauri::command]
+pub async fn planning_run_rules(
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<RulesRunResult>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.run_rules()?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Search tasks by title, description, tags and optionally note bodies
+#[tauri::command]
+pub async fn planning_search_tasks(
+    query: String,
+    include_notes: Option<bool>,
+    limit: Option<usize>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<TaskSearchHit>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.search_tasks(
+        &query,
+        include_notes.unwrap_or(false),
+        limit.unwrap_or(50).clamp(1, 200),
+    )?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Link a task to a vault note
+#[tauri::command]
+pub async fn planning_link_note(
+    task_id: String,
+    note_path: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<TaskNoteLink>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.link_note(&task_id, &note_path)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Remove a task-note link
+#[tauri::command]
+pub async fn planning_unlink_note(
+    task_id: String,
+    note_path: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<()>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    service.unlink_note(&task_id, &note_path)?;
+
+    Ok(ApiResponse::ok(()))
+}
+
+// Notes referenced by a task
+#[tauri::command]
+pub async fn planning_list_task_notes(
+    task_id: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<TaskNoteLink>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.list_task_notes(&task_id)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Tasks referencing a note
+#[tauri::command]
+pub async fn planning_list_note_tasks(
+    note_path: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<Task>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.list_note_tasks(&note_path)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Get the kanban columns of a board
+#[tauri::command]
+pub async fn planning_list_board_columns(
+    board_id: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<BoardColumn>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.list_board_columns(&board_id)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// List boards with settings of their own (theme color)
+#[tauri::command]
+pub async fn planning_list_boards(
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<BoardMeta>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.list_boards()?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Set or clear a board's theme color
+#[tauri::command]
+pub async fn planning_set_board_color(
+    board_id: String,
+    color: Option<String>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<BoardMeta>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.set_board_color(&board_id, color.as_deref())?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Export a board as a markdown checklist inside the vault
+#[tauri::command]
+pub async fn planning_export_board_md(
+    board_id: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<ExportBoardResponse>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.export_board_md(&board_id)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Export boards, tasks and subtasks as an org-mode or TaskPaper file inside the vault
+#[tauri::command]
+pub async fn planning_export_plaintext(
+    format: PlainTextFormat,
+    board_id: Option<String>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<PlainTextExportResponse>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.export_plaintext(format, board_id.as_deref())?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Regenerate the markdown index file of every board now
+#[tauri::command]
+pub async fn planning_write_board_indexes(
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<BoardIndexResult>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.write_board_indexes()?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Export the entire planning dataset as one versioned JSON document
+#[tauri::command]
+pub async fn planning_export_all(
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<PlanningExport>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.export_all()?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Import a document produced by planning_export_all; replace clears existing data first
+#[tauri::command]
+pub async fn planning_import_all(
+    document: PlanningExport,
+    replace: Option<bool>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<ImportResult>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.import_all(document, replace.unwrap_or(false))?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Create tasks from a pasted CSV or markdown table; dry_run previews the mapped rows
+#[tauri::command]
+pub async fn planning_import_table(
+    text: String,
+    mapping: TableMapping,
+    dry_run: Option<bool>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<TableImportResponse>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.import_table(&text, &mapping, dry_run.unwrap_or(false))?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Import TODO items and journals of a Logseq graph or a folder of org files; dry_run previews
+// what was found
+#[tauri::command]
+pub async fn planning_import_outliner(
+    source_path: String,
+    format: OutlinerFormat,
+    dry_run: Option<bool>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<OutlinerImportResponse>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data =
+        service.import_outliner(Path::new(&source_path), format, dry_run.unwrap_or(false))?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Merge the tasks, timers and day logs of another vault into the current one
+#[tauri::command]
+pub async fn planning_merge_vault(
+    other_vault_path: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<MergeVaultResult>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.merge_vault(Path::new(&other_vault_path))?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Replace the kanban columns of a board
+#[tauri::command]
+pub async fn planning_save_board_columns(
+    board_id: String,
+    columns: Vec<BoardColumnInput>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<BoardColumn>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.save_board_columns(&board_id, columns)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Get the custom field schema of a board
+#[tauri::command]
+pub async fn planning_list_board_fields(
+    board_id: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<BoardField>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.list_board_fields(&board_id)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Replace the custom field schema of a board
+#[tauri::command]
+pub async fn planning_save_board_fields(
+    board_id: String,
+    fields: Vec<BoardFieldInput>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<BoardField>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.save_board_fields(&board_id, fields)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Custom field values of a task with its board's schema
+#[tauri::command]
+pub async fn planning_get_task_fields(
+    task_id: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<TaskFieldsDTO>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.get_task_fields(&task_id)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Set custom field values of a task; a null value clears the field
+#[tauri::command]
+pub async fn planning_set_task_fields(
+    task_id: String,
+    values: BTreeMap<String, Option<String>>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<TaskFieldsDTO>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.set_task_fields(&task_id, values)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Custom field values of every task on a board, by task id
+#[tauri::command]
+pub async fn planning_list_board_field_values(
+    board_id: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<FieldValuesByTask>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.list_board_field_values(&board_id)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Tasks whose custom fields match every filter
+#[tauri::command]
+pub async fn planning_query_tasks_by_fields(
+    filters: Vec<TaskFieldFilter>,
+    board_id: Option<String>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<Task>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.query_tasks_by_fields(board_id.as_deref(), &filters)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Create a sprint
+#[tauri::command]
+pub async fn planning_create_sprint(
+    input: SprintInput,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Sprint>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.create_sprint(input)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Rename, re-date or re-plan the capacity of a sprint
+#[tauri::command]
+pub async fn planning_update_sprint(
+    id: String,
+    input: SprintInput,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Sprint>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.update_sprint(&id, input)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Delete a sprint; its tasks become unassigned
+#[tauri::command]
+pub async fn planning_delete_sprint(
+    id: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<()>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    service.delete_sprint(&id)?;
+
+    Ok(ApiResponse::ok(()))
+}
+
+// List sprints, most recent first
+#[tauri::command]
+pub async fn planning_list_sprints(
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<Sprint>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.list_sprints()?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Commit tasks to a sprint, optionally with their points
+#[tauri::command]
+pub async fn planning_assign_to_sprint(
+    sprint_id: String,
+    task_ids: Vec<String>,
+    points: Option<f64>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<SprintSummaryDTO>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.assign_to_sprint(&sprint_id, task_ids, points)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Take tasks out of their sprint; returns how many were in one
+#[tauri::command]
+pub async fn planning_unassign_from_sprint(
+    task_ids: Vec<String>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<usize>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.unassign_from_sprint(task_ids)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Committed and completed points of a sprint with its tasks
+#[tauri::command]
+pub async fn planning_get_sprint_summary(
+    sprint_id: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<SprintSummaryDTO>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.sprint_summary(&sprint_id)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Remaining points per day of a sprint against the ideal line
+#[tauri::command]
+pub async fn planning_get_sprint_burndown(
+    sprint_id: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<SprintBurndown>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.sprint_burndown(&sprint_id)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Open tasks past their due day, as minimal projections for widgets
+#[tauri::command]
+pub async fn planning_list_overdue(
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<TaskDueBrief>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.list_overdue()?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Open tasks due today through the next `days` days (default 7)
+#[tauri::command]
+pub async fn planning_list_upcoming(
+    days: Option<i64>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<TaskDueBrief>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.list_upcoming(days.unwrap_or(planning_service::DEFAULT_UPCOMING_DAYS))?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Current and longest run of logged days; a day counts per `basis` (default: note or completed task)
+#[tauri::command]
+pub async fn planning_journal_streak(
+    basis: Option<StreakBasis>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<JournalStreakDTO>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.journal_streak(basis.unwrap_or_default())?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Days in an inclusive range without a daily note, for backfilling
+#[tauri::command]
+pub async fn planning_missing_days(
+    from: String,
+    to: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<MissingDay>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.missing_days(&from, &to)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Notes whose frontmatter matches a filter such as `status = "active" AND type = "project"`,
+// sorted and projected to the requested fields
+#[tauri::command]
+pub async fn query_notes(
+    input: NoteQueryInput,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<NoteQueryResult>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.query_notes(&input)?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Weekly review for the configured week containing `day`
+#[tauri::command]
+pub async fn planning_week_review(
+    day: Option<String>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<WeekReviewDTO>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.get_week_review(day.as_deref())?;
+
+    Ok(ApiResponse::ok(data))
+}
+
+// Get UI state for the current vault
+#[tauri::command]
+#[allow(dead_code)]
+pub async fn planning_get_ui_state(
+    vault_id: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Option<String>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let ui_state = service.get_ui_state(&vault_id)?;
+
+    Ok(ApiResponse::ok(ui_state))
+}
+
+// Set UI state for the current vault
+#[tauri::command]
+#[allow(dead_code)]
+pub async fn planning_set_ui_state(
+    vault_id: String,
+    partial_state_json: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<()>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    service.set_ui_state(&vault_id, &partial_state_json)?;
+
+    Ok(ApiResponse::ok(()))
+}
+
+// Delete a task
+#[tauri::command]
+pub async fn planning_delete_task(
+    task_id: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<()>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let mut service = PlanningService::new(&app_handle, vault_path)?;
+    service.delete_task(&task_id)?;
+
+    Ok(ApiResponse::ok(()))
+}
+
+// AI Smart Capture
+#[tauri::command]
+pub async fn planning_ai_smart_capture(
+    text: String,
+    vault_state: State<'_, VaultState>,
+    app_state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<SmartCaptureResponse>, ApiError> {
+    let vault_path = {
+        let vault_root = vault_state.root.lock()?;
+        match vault_root.as_ref() {
+            Some(path) => path.clone(),
+            None => {
+                return Err(ApiError {
+                    code: ErrorCode::VaultNotSelected,
+                    message: "Vault not selected".to_string(),
+                    details: None,
+                });
+            }
+        }
+    };
+
+    // Call static method directly; embeddings are optional and only sharpen duplicate detection
+    let engine = app_handle.try_state::<EmbeddingEngine>();
+    let tasks = PlanningService::ai_smart_capture(
+        &vault_path,
+        &app_state.http_client,
+        &text,
+        engine.as_deref(),
+    )
+    .await?;
+
+    Ok(ApiResponse::ok(tasks))
+}
+
+// AI Smart Capture from a vault note; create turns the new suggestions into tasks linked to the note
+#[tauri::command]
+pub async fn ai_capture_from_note(
+    path: String,
+    range: Option<LineRange>,
+    create: Option<bool>,
+    annotate: Option<bool>,
+    vault_state: State<'_, VaultState>,
+    app_state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<NoteCaptureResponse>, ApiError> {
+    let vault_path = {
+        let vault_root = vault_state.root.lock()?;
+        match vault_root.as_ref() {
+            Some(path) => path.clone(),
+            None => {
+                return Err(ApiError {
+                    code: ErrorCode::VaultNotSelected,
+                    message: "Vault not selected".to_string(),
+                    details: None,
+                });
+            }
+        }
+    };
+
+    let engine = app_handle.try_state::<EmbeddingEngine>();
+    let mut response = PlanningService::ai_capture_from_note(
+        &vault_path,
+        &app_state.http_client,
+        &path,
+        range,
+        engine.as_deref(),
+    )
+    .await?;
+
+    if create.unwrap_or(false) {
+        let service = PlanningService::new(&app_handle, &vault_path)?;
+        service.create_captured_tasks(&mut response, annotate.unwrap_or(false))?;
+    }
+
+    Ok(ApiResponse::ok(response))
+}
+
+// Voice capture: transcribe a vault audio file (path) or a recording (bytes plus file_name for
+// its format), then smart capture the transcript unless capture is false
+#[tauri::command]
+pub async fn ai_voice_capture(
+    path: Option<String>,
+    bytes: Option<Vec<u8>>,
+    file_name: Option<String>,
+    capture: Option<bool>,
+    vault_state: State<'_, VaultState>,
+    app_state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<VoiceCaptureResponse>, ApiError> {
+    let vault_path = {
+        let vault_root = vault_state.root.lock()?;
+        match vault_root.as_ref() {
+            Some(path) => path.clone(),
+            None => {
+                return Err(ApiError {
+                    code: ErrorCode::VaultNotSelected,
+                    message: "Vault not selected".to_string(),
+                    details: None,
+                });
+            }
+        }
+    };
+
+    let audio = bytes.map(|bytes| {
+        let file_name = file_name.unwrap_or_else(|| "recording.webm".to_string());
+        (file_name, bytes)
+    });
+    let engine = app_handle.try_state::<EmbeddingEngine>();
+    let response = PlanningService::ai_voice_capture(
+        &vault_path,
+        &app_state.http_client,
+        path.as_deref(),
+        audio,
+        capture.unwrap_or(true),
+        engine.as_deref(),
+    )
+    .await?;
+
+    Ok(ApiResponse::ok(response))
+}
+
+// OCR a vault image; the text is appended to `note` (under `heading` when given) and/or
+// smart captured. With neither, only the recognized text is returned.
+#[tauri::command]
+pub async fn ai_capture_from_image(
+    path: String,
+    note: Option<String>,
+    heading: Option<String>,
+    capture: Option<bool>,
+    vault_state: State<'_, VaultState>,
+    app_state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<ImageCaptureResponse>, ApiError> {
+    let vault_path = {
+        let vault_root = vault_state.root.lock()?;
+        match vault_root.as_ref() {
+            Some(path) => path.clone(),
+            None => {
+                return Err(ApiError {
+                    code: ErrorCode::VaultNotSelected,
+                    message: "Vault not selected".to_string(),
+                    details: None,
+                });
+            }
+        }
+    };
+
+    let engine = app_handle.try_state::<EmbeddingEngine>();
+    let response = PlanningService::ai_capture_from_image(
+        &vault_path,
+        &app_state.http_client,
+        &path,
+        note.as_deref().map(|note| (note, heading.as_deref())),
+        capture.unwrap_or(false),
+        engine.as_deref(),
+    )
+    .await?;
+
+    Ok(ApiResponse::ok(response))
+}
+
+// Tags used on tasks with their counts, most used first
+#[tauri::command]
+pub async fn planning_list_tags(
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<TagCount>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let tags = service.list_tags()?;
+
+    Ok(ApiResponse::ok(tags))
+}
+
+// Suggest tags for text or a vault note from the existing vocabulary plus a few new ones
+#[tauri::command]
+pub async fn ai_suggest_tags(
+    text_or_path: String,
+    max_new: Option<usize>,
+    vault_state: State<'_, VaultState>,
+    app_state: State<'_, AppState>,
+) -> Result<ApiResponse<TagSuggestionResponse>, ApiError> {
+    let vault_path = {
+        let vault_root = vault_state.root.lock()?;
+        match vault_root.as_ref() {
+            Some(path) => path.clone(),
+            None => {
+                return Err(ApiError {
+                    code: ErrorCode::VaultNotSelected,
+                    message: "Vault not selected".to_string(),
+                    details: None,
+                });
+            }
+        }
+    };
+
+    let response = PlanningService::ai_suggest_tags(
+        &vault_path,
+        &app_state.http_client,
+        &text_or_path,
+        max_new.unwrap_or(tagging::DEFAULT_MAX_NEW_TAGS),
+    )
+    .await?;
+
+    Ok(ApiResponse::ok(response))
+}
+
+// Drop every cached AI reply of the vault; returns the number removed
+#[tauri::command]
+pub async fn clear_ai_cache(
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<usize>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let cleared = service.clear_ai_cache()?;
+
+    Ok(ApiResponse::ok(cleared))
+}
+
+// Get AI Settings
+#[tauri::command]
+pub async fn planning_get_ai_settings(
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<AiSettings>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let settings = settings_repo::get_ai_settings(vault_path)?;
+    Ok(ApiResponse::ok(settings))
+}
+
+// Save AI Settings
+#[tauri::command]
+pub async fn planning_save_ai_settings(
+    settings: AiSettings,
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<()>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    settings_repo::save_ai_settings(vault_path, settings)?;
+    Ok(ApiResponse::ok(()))
+}
+
+// Get planning settings (timezone, week layout, escalation rules)
+#[tauri::command]
+pub async fn planning_get_planning_settings(
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<PlanningSettings>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let settings = settings_repo::get_planning_settings(vault_path)?;
+    Ok(ApiResponse::ok(settings))
+}
+
+// Save planning settings (timezone, week layout, escalation rules)
+#[tauri::command]
+pub async fn planning_save_planning_settings(
+    settings: PlanningSettings,
+    vault_state: State<'_, VaultState>,
+    board_index_state: State<'_, BoardIndexState>,
+) -> Result<ApiResponse<()>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    settings_repo::save_planning_settings(vault_path, settings)?;
+    // Enabling or moving the board indexes writes them without waiting for a task change
+    board_index_state.mark_changed();
+    Ok(ApiResponse::ok(()))
+}
+
+// Change the vault layout, moving existing planning data and task notes to match
+#[tauri::command]
+pub async fn planning_set_vault_layout(
+    layout: VaultLayout,
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<LayoutMigrationResult>, ApiError> {
+    // The vault lock is held throughout so other commands cannot reopen the old layout mid-move
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let data = planning_service::migrate_vault_layout(vault_path, layout)?;
+    Ok(ApiResponse::ok(data))
+}
+
+// Move task notes left outside the vault layout into it; `dry_run` only reports what would move
+#[tauri::command]
+pub async fn planning_consolidate_task_notes(
+    dry_run: Option<bool>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<TaskNoteConsolidation>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.consolidate_task_notes(dry_run.unwrap_or(false))?;
+    Ok(ApiResponse::ok(data))
+}
+
+// Move tasks that share a slug onto slugs of their own and enforce unique slugs from then on
+#[tauri::command]
+pub async fn planning_repair_task_slugs(
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<SlugRepairResult>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let data = service.repair_task_slugs()?;
+    Ok(ApiResponse::ok(data))
+}
+
+// Run the startup health check again, e.g. after a sync brought in leftovers from another device
+#[tauri::command]
+pub async fn planning_health_check(
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<HealthCheckReport>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let data = planning_service::run_health_check(vault_path);
+    Ok(ApiResponse::ok(data))
+}
+
+// Whether the planning database is encrypted and, if so, unlocked
+#[tauri::command]
+pub async fn planning_db_encryption_status(
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<DbEncryptionStatus>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let data = planning_service::db_encryption_status(vault_path)?;
+    Ok(ApiResponse::ok(data))
+}
+
+// Unlock an encrypted planning database for this session
+#[tauri::command]
+pub async fn planning_unlock_db(
+    passphrase: String,
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<()>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    planning_service::unlock_planning_db(vault_path, &passphrase)?;
+    Ok(ApiResponse::ok(()))
+}
+
+// Forget the database key; planning commands fail until unlocked again
+#[tauri::command]
+pub async fn planning_lock_db(
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<()>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    planning_service::lock_planning_db(vault_path)?;
+    Ok(ApiResponse::ok(()))
+}
+
+// Encrypt task descriptions of the planning database under a passphrase; returns tasks encrypted
+#[tauri::command]
+pub async fn planning_enable_db_encryption(
+    passphrase: String,
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<usize>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let data = planning_service::enable_db_encryption(vault_path, &passphrase)?;
+    Ok(ApiResponse::ok(data))
+}
+
+// Decrypt the planning database back to plaintext; returns tasks decrypted
+#[tauri::command]
+pub async fn planning_disable_db_encryption(
+    passphrase: String,
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<usize>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let data = planning_service::disable_db_encryption(vault_path, &passphrase)?;
+    Ok(ApiResponse::ok(data))
 }
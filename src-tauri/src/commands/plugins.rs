@@ -3,7 +3,7 @@ use std::path::PathBuf;
 
 use tauri::State;
 
-use crate::ipc::{ApiError, ApiResponse};
+use crate::ipc::{ApiError, ApiResponse, ErrorCode};
 use crate::services::plugins_service;
 use crate::state::VaultState;
 
@@ -12,7 +12,7 @@ fn current_vault_root(state: &State<'_, VaultState>) -> Result<PathBuf, ApiError
     match guard.as_ref() {
         Some(path) => Ok(path.clone()),
         None => Err(ApiError {
-            code: "NoVaultSelected".to_string(),
+            code: ErrorCode::NoVaultSelected,
             message: "No vault selected".to_string(),
             details: None,
         }),
@@ -54,7 +54,7 @@ pub async fn plugins_list(
 ) -> Result<ApiResponse<PluginsListResponse>, ApiError> {
     let vault_root = match current_vault_root(&state) {
         Ok(path) => path,
-        Err(err) => return Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
     };
 
     let result =
@@ -73,9 +73,9 @@ pub async fn plugins_list(
                 })
                 .collect(),
         })),
-        Ok(Err(err)) => Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
         Err(err) => Ok(ApiResponse::err(
-            "Unknown",
+            ErrorCode::Unknown,
             "Plugins list task failed",
             Some(serde_json::json!({ "error": err.to_string() })),
         )),
@@ -95,7 +95,7 @@ pub async fn plugins_read_manifest(
 ) -> Result<ApiResponse<PluginManifest>, ApiError> {
     let vault_root = match current_vault_root(&state) {
         Ok(path) => path,
-        Err(err) => return Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
     };
     let plugin_id = input.plugin_id;
     let result = tauri::async_runtime::spawn_blocking(move || {
@@ -105,9 +105,9 @@ pub async fn plugins_read_manifest(
 
     match result {
         Ok(Ok(manifest)) => Ok(ApiResponse::ok(manifest)),
-        Ok(Err(err)) => Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
         Err(err) => Ok(ApiResponse::err(
-            "Unknown",
+            ErrorCode::Unknown,
             "Plugins read manifest task failed",
             Some(serde_json::json!({ "error": err.to_string() })),
         )),
@@ -133,7 +133,7 @@ pub async fn plugins_read_entry(
 ) -> Result<ApiResponse<PluginsReadEntryResponse>, ApiError> {
     let vault_root = match current_vault_root(&state) {
         Ok(path) => path,
-        Err(err) => return Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
     };
     let plugin_id = input.plugin_id;
     let entry = input.entry;
@@ -144,9 +144,9 @@ pub async fn plugins_read_entry(
 
     match result {
         Ok(Ok(content)) => Ok(ApiResponse::ok(PluginsReadEntryResponse { content })),
-        Ok(Err(err)) => Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
         Err(err) => Ok(ApiResponse::err(
-            "Unknown",
+            ErrorCode::Unknown,
             "Plugins read entry task failed",
             Some(serde_json::json!({ "error": err.to_string() })),
         )),
@@ -174,7 +174,7 @@ pub async fn plugins_set_enabled(
 ) -> Result<ApiResponse<PluginsSetEnabledResponse>, ApiError> {
     let vault_root = match current_vault_root(&state) {
         Ok(path) => path,
-        Err(err) => return Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
     };
     let plugin_id = input.plugin_id;
     let enabled = input.enabled;
@@ -186,9 +186,9 @@ pub async fn plugins_set_enabled(
 
     match result {
         Ok(Ok(())) => Ok(ApiResponse::ok(PluginsSetEnabledResponse { ok: true })),
-        Ok(Err(err)) => Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
         Err(err) => Ok(ApiResponse::err(
-            "Unknown",
+            ErrorCode::Unknown,
             "Plugins set enabled task failed",
             Some(serde_json::json!({ "error": err.to_string() })),
         )),
@@ -214,7 +214,7 @@ pub async fn vault_read_text(
 ) -> Result<ApiResponse<VaultReadTextResponse>, ApiError> {
     let vault_root = match current_vault_root(&state) {
         Ok(path) => path,
-        Err(err) => return Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
     };
     let rel_path = PathBuf::from(input.path);
     let result = tauri::async_runtime::spawn_blocking(move || {
@@ -227,9 +227,9 @@ pub async fn vault_read_text(
             content: response.content,
             mtime: response.mtime,
         })),
-        Ok(Err(err)) => Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
         Err(err) => Ok(ApiResponse::err(
-            "Unknown",
+            ErrorCode::Unknown,
             "Vault read task failed",
             Some(serde_json::json!({ "error": err.to_string() })),
         )),
@@ -255,7 +255,7 @@ pub async fn vault_write_text(
 ) -> Result<ApiResponse<VaultWriteTextResponse>, ApiError> {
     let vault_root = match current_vault_root(&state) {
         Ok(path) => path,
-        Err(err) => return Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
     };
     let rel_path = PathBuf::from(input.path);
     let content = input.content;
@@ -268,9 +268,9 @@ pub async fn vault_write_text(
             path: response.path,
             mtime: response.mtime,
         })),
-        Ok(Err(err)) => Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
         Err(err) => Ok(ApiResponse::err(
-            "Unknown",
+            ErrorCode::Unknown,
             "Vault write task failed",
             Some(serde_json::json!({ "error": err.to_string() })),
         )),
@@ -293,7 +293,7 @@ pub async fn vault_list_files(
 ) -> Result<ApiResponse<VaultListFilesResponse>, ApiError> {
     let vault_root = match current_vault_root(&state) {
         Ok(path) => path,
-        Err(err) => return Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
     };
 
     let rel_path = PathBuf::from(input.path);
@@ -321,9 +321,9 @@ pub async fn vault_list_files(
 
     match result {
         Ok(Ok(response)) => Ok(ApiResponse::ok(response)),
-        Ok(Err(err)) => Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
         Err(err) => Ok(ApiResponse::err(
-            "Unknown",
+            ErrorCode::Unknown,
             "Vault list files task failed",
             Some(serde_json::json!({ "error": err.to_string() })),
         )),
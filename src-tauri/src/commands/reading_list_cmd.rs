@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::domain::reading_list::{ReadingItem, ReadingItemTaskResponse, ReadingStatus};
+use crate::features::{clipper, http_api::PLANNING_CHANGED_EVENT};
+use crate::ipc::{ApiError, ApiResponse, ErrorCode};
+use crate::services::planning_service::PlanningService;
+use crate::state::{AppState, VaultState};
+
+fn current_vault(vault_state: &State<'_, VaultState>) -> Result<PathBuf, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    vault_root.clone().ok_or_else(|| ApiError {
+        code: ErrorCode::VaultNotSelected,
+        message: "Vault not selected".to_string(),
+        details: None,
+    })
+}
+
+// Save a page to the reading list; `source_label` is the webview whose "webview-open" event
+// carried the link
+#[tauri::command]
+pub async fn reading_list_add(
+    url: String,
+    title: Option<String>,
+    source_label: Option<String>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<ReadingItem>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let item = service.add_to_reading_list(&url, title, source_label)?;
+    Ok(ApiResponse::ok(item))
+}
+
+#[tauri::command]
+pub async fn reading_list_list(
+    status: Option<ReadingStatus>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<ReadingItem>>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let items = service.list_reading_list(status)?;
+    Ok(ApiResponse::ok(items))
+}
+
+// Mark an item read, or unread again with `read: false`
+#[tauri::command]
+pub async fn reading_list_mark_read(
+    id: String,
+    read: Option<bool>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<ReadingItem>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let item = service.mark_reading_item(&id, read.unwrap_or(true))?;
+    Ok(ApiResponse::ok(item))
+}
+
+#[tauri::command]
+pub async fn reading_list_to_task(
+    id: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<ReadingItemTaskResponse>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let response = service.reading_item_to_task(&id)?;
+
+    let payload = serde_json::json!({ "source": "reading_list" });
+    if let Err(e) = app_handle.emit(PLANNING_CHANGED_EVENT, payload) {
+        tracing::warn!(target: "planning", "failed to emit planning change: {}", e);
+    }
+    Ok(ApiResponse::ok(response))
+}
+
+// Clip the item's page into the clippings folder and attach the note to the item. An item whose
+// clipping still exists is returned as is.
+#[tauri::command]
+pub async fn reading_list_clip(
+    id: String,
+    vault_state: State<'_, VaultState>,
+    app_state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<ReadingItem>, ApiError> {
+    // The vault lock is not held across the page download
+    let vault_path = current_vault(&vault_state)?;
+    let item = PlanningService::new(&app_handle, &vault_path)?.get_reading_item(&id)?;
+    if item
+        .note_path
+        .as_ref()
+        .is_some_and(|path| vault_path.join(path).is_file())
+    {
+        return Ok(ApiResponse::ok(item));
+    }
+
+    let (final_url, html) = clipper::fetch_page(&app_state.http_client, &item.url).await?;
+    let page = clipper::extract_page(&html, &final_url);
+    let path = clipper::save_clipping(&vault_path, &page)?;
+
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let item = service.set_reading_item_note(&id, &path)?;
+    tracing::info!(target: "clipper", "reading list item clipped: id={}, path={}", id, path);
+    Ok(ApiResponse::ok(item))
+}
+
+// Remove an item from the reading list; its task and clipping are kept
+#[tauri::command]
+pub async fn reading_list_remove(
+    id: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<bool>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let removed = service.remove_reading_item(&id)?;
+    Ok(ApiResponse::ok(removed))
+}
@@ -0,0 +1,124 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, State};
+
+use crate::domain::notifications::Notification;
+use crate::domain::reminders::{Reminder, ReminderRepeat};
+use crate::features::notifications::{self, TestNotificationResponse};
+use crate::ipc::{ApiError, ApiResponse, ErrorCode};
+use crate::services::planning_service::PlanningService;
+use crate::state::VaultState;
+
+fn current_vault(vault_state: &State<'_, VaultState>) -> Result<PathBuf, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    vault_root.clone().ok_or_else(|| ApiError {
+        code: ErrorCode::VaultNotSelected,
+        message: "Vault not selected".to_string(),
+        details: None,
+    })
+}
+
+// Remind about a task at `remind_at` (RFC3339, or a date and time in the vault timezone),
+// optionally repeating
+#[tauri::command]
+pub async fn reminder_create(
+    task_id: String,
+    remind_at: String,
+    repeat: Option<ReminderRepeat>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Reminder>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let reminder = service.create_reminder(&task_id, &remind_at, repeat)?;
+    Ok(ApiResponse::ok(reminder))
+}
+
+#[tauri::command]
+pub async fn reminder_update(
+    id: String,
+    remind_at: String,
+    repeat: Option<ReminderRepeat>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Reminder>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let reminder = service.update_reminder(&id, &remind_at, repeat)?;
+    Ok(ApiResponse::ok(reminder))
+}
+
+#[tauri::command]
+pub async fn reminder_delete(
+    id: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<bool>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let removed = service.delete_reminder(&id)?;
+    Ok(ApiResponse::ok(removed))
+}
+
+// Reminders of one task, or of every task without `task_id`
+#[tauri::command]
+pub async fn reminder_list(
+    task_id: Option<String>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<Reminder>>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let reminders = service.list_reminders(task_id.as_deref())?;
+    Ok(ApiResponse::ok(reminders))
+}
+
+// Delivered and snoozed notifications, most recent first; dismissed ones with `include_dismissed`
+#[tauri::command]
+pub async fn list_notifications(
+    include_dismissed: Option<bool>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<Notification>>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let notifications = service.list_notifications(include_dismissed.unwrap_or(false))?;
+    Ok(ApiResponse::ok(notifications))
+}
+
+// Show a notification again at `until`; the snooze survives restarts
+#[tauri::command]
+pub async fn snooze_notification(
+    id: String,
+    until: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Notification>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let notification = service.snooze_notification(&id, &until)?;
+    Ok(ApiResponse::ok(notification))
+}
+
+#[tauri::command]
+pub async fn dismiss_notification(
+    id: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Notification>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let notification = service.dismiss_notification(&id)?;
+    Ok(ApiResponse::ok(notification))
+}
+
+// Show a sample notification and report whether real ones would be held back right now
+#[tauri::command]
+pub async fn test_notification(
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<TestNotificationResponse>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let response = notifications::send_test(&app_handle, &vault_path)?;
+    Ok(ApiResponse::ok(response))
+}
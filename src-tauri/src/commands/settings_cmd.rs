@@ -0,0 +1,106 @@
+use std::path::{Path, PathBuf};
+
+use tauri::{AppHandle, State};
+
+use crate::features::http_api;
+use crate::ipc::{ApiError, ApiResponse, ErrorCode};
+use crate::repo::settings_repo::{
+    self, EffectiveAiConfig, SettingsLayer, SettingsLayersDTO, SettingsSection, SettingsValidation,
+};
+use crate::state::VaultState;
+
+fn current_vault(vault_state: &State<'_, VaultState>) -> Result<PathBuf, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    vault_root.clone().ok_or_else(|| ApiError {
+        code: ErrorCode::VaultNotSelected,
+        message: "Vault not selected".to_string(),
+        details: None,
+    })
+}
+
+// The HTTP API server holds its settings while running; restart it when they change here
+fn apply_section(
+    app_handle: &AppHandle,
+    vault_path: &Path,
+    section: SettingsSection,
+) -> Result<(), ApiError> {
+    if section == SettingsSection::HttpApi {
+        let settings = settings_repo::get_http_api_settings(vault_path)?;
+        http_api::apply_settings(app_handle, &settings)?;
+    }
+    Ok(())
+}
+
+// Problems in a layer's settings.json (the vault's by default), by section and path, without
+// changing the file
+#[tauri::command]
+pub async fn validate_settings(
+    layer: Option<SettingsLayer>,
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<SettingsValidation>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let validation =
+        settings_repo::validate_settings(&vault_path, layer.unwrap_or(SettingsLayer::Vault))?;
+    Ok(ApiResponse::ok(validation))
+}
+
+// Reset one settings section to its defaults; returns the validation of the result
+#[tauri::command]
+pub async fn reset_settings_section(
+    section: SettingsSection,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<SettingsValidation>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let validation = settings_repo::reset_settings_section(&vault_path, section)?;
+    apply_section(&app_handle, &vault_path, section)?;
+    Ok(ApiResponse::ok(validation))
+}
+
+// Settings as stored in the app and vault layers, with the layer each section is read from
+#[tauri::command]
+pub async fn get_settings_layers(
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<SettingsLayersDTO>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let layers = settings_repo::get_settings_layers(&vault_path)?;
+    Ok(ApiResponse::ok(layers))
+}
+
+// AI provider config actually used, with the source of each field; the API key is masked
+#[tauri::command]
+pub async fn get_effective_ai_config(
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<EffectiveAiConfig>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let config = settings_repo::get_effective_ai_config(&vault_path)?;
+    Ok(ApiResponse::ok(config))
+}
+
+// Write one section of one layer; a null value removes it from that layer
+#[tauri::command]
+pub async fn set_settings_layer_section(
+    layer: SettingsLayer,
+    section: SettingsSection,
+    value: Option<serde_json::Value>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<SettingsLayersDTO>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let layers = settings_repo::set_settings_layer_section(&vault_path, layer, section, value)?;
+    apply_section(&app_handle, &vault_path, section)?;
+    Ok(ApiResponse::ok(layers))
+}
+
+#[tauri::command]
+pub async fn move_settings_section(
+    section: SettingsSection,
+    to: SettingsLayer,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<SettingsLayersDTO>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let layers = settings_repo::move_settings_section(&vault_path, section, to)?;
+    apply_section(&app_handle, &vault_path, section)?;
+    Ok(ApiResponse::ok(layers))
+}
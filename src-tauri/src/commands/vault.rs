@@ -1,13 +1,20 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 
-use crate::ipc::{ApiError, ApiResponse};
+use crate::domain::lint::LintDiagnostic;
+use crate::domain::note_stats::{FolderStatsDTO, NoteStats};
+use crate::domain::outline::HeadingNode;
+use crate::features::link_check::{self, LinkCheckReport};
+use crate::features::vault_drive;
+use crate::ipc::{ApiError, ApiResponse, ErrorCode};
 use crate::repo::vault_repo;
+use crate::security::note_locks::{self, WriteOrigin};
 use crate::security::path_policy;
+use crate::services::planning_service::{self, PlanningService};
 use crate::services::vault_service;
-use crate::state::VaultState;
+use crate::state::{AppState, VaultDriveState, VaultState};
 
 #[derive(Serialize)]
 pub struct SelectVaultResponse {
@@ -15,9 +22,26 @@ pub struct SelectVaultResponse {
     pub vault_root: String,
 }
 
+#[derive(Serialize)]
+pub struct AppStateResponse {
+    // "no_vault" until a vault is selected or created, then "ready"; "relocation_needed" when the
+    // persisted vault folder is gone
+    pub status: String,
+    #[serde(rename = "vaultRoot")]
+    pub vault_root: Option<String>,
+    #[serde(rename = "staleVaultRoot")]
+    pub stale_vault_root: Option<String>, // Where the vault used to be, while relocation is needed
+}
+
+#[derive(Deserialize)]
+pub struct CreateVaultInput {
+    pub path: String, // Existing parent directory
+    pub name: String,
+}
+
 #[derive(Serialize)]
 pub struct WarningItem {
-    pub code: String,
+    pub code: ErrorCode,
     pub message: String,
     pub path: Option<String>,
 }
@@ -28,6 +52,41 @@ pub struct ScanVaultResponse {
     pub vault_root: String,
     pub tree: Vec<vault_service::FileNode>,
     pub warnings: Vec<WarningItem>,
+    pub limits: vault_service::ScanLimits,
+    pub truncated: bool, // The entry limit was hit and the tree is incomplete
+}
+
+pub const SCAN_PROGRESS_EVENT: &str = "scan-progress";
+
+// One batch of a streamed scan; the last event of a scan has done set and no nodes
+#[derive(Serialize, Clone)]
+pub struct ScanProgressEvent {
+    #[serde(rename = "scanId")]
+    pub scan_id: String,
+    pub nodes: Vec<vault_service::FileNode>,
+    pub scanned: usize, // Entries seen so far
+    pub done: bool,
+}
+
+#[derive(Serialize)]
+pub struct ScanVaultStreamResponse {
+    #[serde(rename = "scanId")]
+    pub scan_id: String,
+    #[serde(rename = "vaultRoot")]
+    pub vault_root: String,
+    #[serde(rename = "entryCount")]
+    pub entry_count: usize,
+    pub warnings: Vec<WarningItem>,
+}
+
+#[derive(Serialize)]
+pub struct ScanVaultChangesResponse {
+    #[serde(rename = "scannedAt")]
+    pub scanned_at: u64,
+    pub created: Vec<vault_service::FileNode>,
+    pub modified: Vec<vault_service::FileNode>,
+    pub deleted: Vec<String>,
+    pub warnings: Vec<WarningItem>,
 }
 
 #[derive(Serialize)]
@@ -41,6 +100,17 @@ pub struct ReadMarkdownResponse {
 pub struct WriteMarkdownResponse {
     pub path: String,
     pub mtime: Option<u64>,
+    pub queued: bool, // Held until the unavailable vault returns
+}
+
+#[derive(Serialize)]
+pub struct VaultDriveStatus {
+    #[serde(rename = "vaultRoot")]
+    pub vault_root: String,
+    pub drive: vault_drive::DriveKind,
+    pub available: bool,
+    #[serde(rename = "queuedWrites")]
+    pub queued_writes: usize,
 }
 
 #[derive(Deserialize)]
@@ -52,6 +122,62 @@ pub struct ReadMarkdownInput {
 pub struct WriteMarkdownInput {
     pub path: String,
     pub content: String,
+    #[serde(default)]
+    pub origin: WriteOrigin, // Editor unless a plugin or background job writes
+}
+
+#[derive(Serialize)]
+pub struct NoteFrontmatterResponse {
+    pub path: String,
+    pub frontmatter: serde_json::Value,
+    pub mtime: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct SetNoteFrontmatterInput {
+    pub path: String,
+    pub patch: serde_json::Map<String, serde_json::Value>, // null values remove keys
+    #[serde(default)]
+    pub origin: WriteOrigin,
+}
+
+#[derive(Serialize)]
+pub struct NoteOutlineResponse {
+    pub path: String,
+    pub headings: Vec<HeadingNode>,
+}
+
+#[derive(Serialize)]
+pub struct NoteStatsResponse {
+    pub path: String,
+    pub stats: NoteStats,
+}
+
+#[derive(Serialize)]
+pub struct LintNoteResponse {
+    pub path: String,
+    pub diagnostics: Vec<LintDiagnostic>,
+}
+
+#[derive(Deserialize)]
+pub struct AppendUnderHeadingInput {
+    pub path: String,
+    pub heading: String, // "## Title" matches that level only; "Title" matches any level
+    pub text: String,
+    #[serde(default)]
+    pub origin: WriteOrigin,
+}
+
+#[derive(Deserialize)]
+pub struct NoteLockInput {
+    pub path: String,
+}
+
+#[derive(Serialize)]
+pub struct NoteLockResponse {
+    pub path: String,
+    #[serde(rename = "lockedAt")]
+    pub locked_at: Option<u64>, // None once released
 }
 
 #[derive(Deserialize)]
@@ -68,6 +194,32 @@ pub struct RenameMarkdownResponse {
     #[serde(rename = "newPath")]
     pub new_path: String,
     pub mtime: Option<u64>,
+    #[serde(rename = "linkUpdates")]
+    pub link_updates: vault_service::LinkUpdateReport,
+}
+
+#[derive(Deserialize)]
+pub struct BatchRenameInput {
+    pub paths: Vec<String>,
+    pub pattern: String, // Tokens: {name}, {date}, {index}
+    #[serde(rename = "dryRun", default)]
+    pub dry_run: bool,
+}
+
+#[derive(Serialize)]
+pub struct PlannedRenameItem {
+    #[serde(rename = "oldPath")]
+    pub old_path: String,
+    #[serde(rename = "newPath")]
+    pub new_path: String,
+}
+
+#[derive(Serialize)]
+pub struct BatchRenameResponse {
+    pub renames: Vec<PlannedRenameItem>,
+    pub applied: bool,
+    #[serde(rename = "linkUpdates")]
+    pub link_updates: vault_service::LinkUpdateReport,
 }
 
 #[derive(Deserialize)]
@@ -93,12 +245,47 @@ pub struct CreateEntryResponse {
     pub kind: String,
 }
 
+#[derive(Deserialize)]
+pub struct CopyEntryInput {
+    pub path: String,
+    #[serde(rename = "targetDir")]
+    pub target_dir: Option<String>, // Vault root when empty
+}
+
+#[derive(Deserialize)]
+pub struct DuplicateNoteInput {
+    pub path: String,
+}
+
+#[derive(Serialize)]
+pub struct CopyEntryResponse {
+    pub path: String,
+    pub kind: String,
+}
+
+// Update links to a renamed entry in notes and in the task link index; the rename itself already
+// happened, so failures here are logged rather than returned
+fn follow_rename(vault_root: &Path, old_path: &str, new_path: &str) -> vault_service::LinkUpdateReport {
+    if old_path == new_path {
+        return vault_service::LinkUpdateReport::default();
+    }
+    if let Err(err) =
+        PlanningService::open(vault_root).and_then(|service| service.rename_note_links(old_path, new_path))
+    {
+        tracing::warn!(target: "vault", "task note links not updated: {}", err.message);
+    }
+    vault_service::propagate_link_updates(vault_root, old_path, new_path).unwrap_or_else(|err| {
+        tracing::warn!(target: "vault", "note links not updated: {}", err.message);
+        vault_service::LinkUpdateReport::default()
+    })
+}
+
 fn current_vault_root(state: &State<'_, VaultState>) -> Result<PathBuf, ApiError> {
     let guard = state.root.lock().expect("vault mutex poisoned");
     match guard.as_ref() {
         Some(path) => Ok(path.clone()),
         None => Err(ApiError {
-            code: "NoVaultSelected".to_string(),
+            code: ErrorCode::NoVaultSelected,
             message: "No vault selected".to_string(),
             details: None,
         }),
@@ -109,29 +296,29 @@ fn current_vault_root(state: &State<'_, VaultState>) -> Result<PathBuf, ApiError
 pub fn select_vault(state: State<'_, VaultState>) -> ApiResponse<SelectVaultResponse> {
     let folder = rfd::FileDialog::new().pick_folder();
     let Some(path) = folder else {
-        return ApiResponse::err("NoVaultSelected", "Vault selection cancelled", None);
+        return ApiResponse::err(ErrorCode::NoVaultSelected, "Vault selection cancelled", None);
     };
 
     if let Err(err) = path_policy::ensure_no_symlink(&path) {
-        return ApiResponse::err(&err.code, &err.message, err.details);
+        return ApiResponse::err(err.code, &err.message, err.details);
     }
 
     let canonical = match path.canonicalize() {
         Ok(path) => path,
         Err(err) => {
             return ApiResponse::err(
-                "Unknown",
+                ErrorCode::Unknown,
                 "Failed to resolve vault path",
                 Some(serde_json::json!({ "error": err.to_string() })),
             )
         }
     };
     if !canonical.is_dir() {
-        return ApiResponse::err("NotFound", "Vault path is not a directory", None);
+        return ApiResponse::err(ErrorCode::NotFound, "Vault path is not a directory", None);
     }
 
     if let Err(err) = vault_repo::persist_vault(&state, &canonical) {
-        return ApiResponse::err(&err.code, &err.message, err.details);
+        return ApiResponse::err(err.code, &err.message, err.details);
     }
     let mut guard = state.root.lock().expect("vault mutex poisoned");
     *guard = Some(canonical.clone());
@@ -141,6 +328,145 @@ pub fn select_vault(state: State<'_, VaultState>) -> ApiResponse<SelectVaultResp
     })
 }
 
+#[tauri::command]
+pub fn get_app_state(state: State<'_, VaultState>) -> ApiResponse<AppStateResponse> {
+    let guard = state.root.lock().expect("vault mutex poisoned");
+    let vault_root = guard
+        .as_ref()
+        .map(|path| path.to_string_lossy().to_string());
+    let stale_vault_root = match &vault_root {
+        Some(_) => None,
+        None => state
+            .stale
+            .lock()
+            .expect("vault mutex poisoned")
+            .as_ref()
+            .map(|stale| stale.vault_root.clone()),
+    };
+    let status = if vault_root.is_some() {
+        "ready"
+    } else if stale_vault_root.is_some() {
+        "relocation_needed"
+    } else {
+        "no_vault"
+    };
+
+    ApiResponse::ok(AppStateResponse {
+        status: status.to_string(),
+        vault_root,
+        stale_vault_root,
+    })
+}
+
+// Point the app at the new location of a moved vault. The folder must hold the same vault id
+// as the one persisted; `path` skips the folder picker.
+#[tauri::command]
+pub fn relocate_vault(
+    state: State<'_, VaultState>,
+    path: Option<String>,
+) -> Result<ApiResponse<SelectVaultResponse>, ApiError> {
+    let Some(stale) = state.stale.lock()?.clone() else {
+        return Err(ApiError {
+            code: ErrorCode::InvalidStateTransition,
+            message: "No vault needs relocating".to_string(),
+            details: None,
+        });
+    };
+    let folder = match path {
+        Some(path) => Some(PathBuf::from(path.trim())),
+        None => rfd::FileDialog::new().pick_folder(),
+    };
+    let Some(folder) = folder else {
+        return Err(ApiError {
+            code: ErrorCode::NoVaultSelected,
+            message: "Vault relocation cancelled".to_string(),
+            details: None,
+        });
+    };
+
+    path_policy::ensure_no_symlink(&folder)?;
+    let canonical = folder.canonicalize().map_err(|err| ApiError {
+        code: ErrorCode::NotFound,
+        message: "Failed to resolve vault path".to_string(),
+        details: Some(serde_json::json!({ "error": err.to_string() })),
+    })?;
+    if !canonical.is_dir() {
+        return Err(ApiError {
+            code: ErrorCode::NotFound,
+            message: "Vault path is not a directory".to_string(),
+            details: None,
+        });
+    }
+
+    let Some(found_id) = vault_repo::read_vault_id(&canonical) else {
+        return Err(ApiError {
+            code: ErrorCode::NotFound,
+            message: "Folder does not contain a vault".to_string(),
+            details: Some(serde_json::json!({ "path": canonical.to_string_lossy() })),
+        });
+    };
+    if let Some(expected_id) = &stale.vault_id {
+        if *expected_id != found_id {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "Folder contains a different vault".to_string(),
+                details: Some(serde_json::json!({
+                    "expected_vault_id": expected_id,
+                    "found_vault_id": found_id,
+                })),
+            });
+        }
+    }
+
+    vault_repo::persist_vault(&state, &canonical)?;
+    *state.root.lock()? = Some(canonical.clone());
+    tracing::info!(target: "vault", "vault relocated: from={}, to={}", stale.vault_root, canonical.to_string_lossy());
+
+    Ok(ApiResponse::ok(SelectVaultResponse {
+        vault_root: canonical.to_string_lossy().to_string(),
+    }))
+}
+
+#[tauri::command]
+pub async fn create_new_vault(
+    state: State<'_, VaultState>,
+    app_handle: AppHandle,
+    input: CreateVaultInput,
+) -> Result<ApiResponse<SelectVaultResponse>, ApiError> {
+    let parent = PathBuf::from(input.path.trim());
+    let name = input.name;
+    let result =
+        tauri::async_runtime::spawn_blocking(move || vault_service::create_vault(&parent, &name))
+            .await;
+
+    let vault_root = match result {
+        Ok(Ok(vault_root)) => vault_root,
+        Ok(Err(err)) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
+        Err(err) => {
+            return Ok(ApiResponse::err(
+                ErrorCode::WriteFailed,
+                "Create vault task failed",
+                Some(serde_json::json!({ "error": err.to_string() })),
+            ))
+        }
+    };
+
+    // Opening the planning service creates the planning directory, database and vault id
+    if let Err(err) = PlanningService::new(&app_handle, &vault_root) {
+        return Ok(ApiResponse::err(err.code, &err.message, err.details));
+    }
+
+    if let Err(err) = vault_repo::persist_vault(&state, &vault_root) {
+        return Ok(ApiResponse::err(err.code, &err.message, err.details));
+    }
+    let mut guard = state.root.lock().expect("vault mutex poisoned");
+    *guard = Some(vault_root.clone());
+
+    Ok(ApiResponse::ok(SelectVaultResponse {
+        vault_root: vault_root.to_string_lossy().to_string(),
+    }))
+}
+
 #[tauri::command]
 pub async fn scan_vault(
     state: State<'_, VaultState>,
@@ -148,7 +474,7 @@ pub async fn scan_vault(
 ) -> Result<ApiResponse<ScanVaultResponse>, ApiError> {
     let vault_root = match current_vault_root(&state) {
         Ok(path) => path,
-        Err(err) => return Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
     };
 
     let rel_path = path.and_then(|value| {
@@ -175,10 +501,122 @@ pub async fn scan_vault(
                     path: warning.path,
                 })
                 .collect(),
+            limits: response.limits,
+            truncated: response.truncated,
+        })),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
+        Err(err) => Ok(ApiResponse::err(
+            ErrorCode::ScanFailed,
+            "Scan task failed",
+            Some(serde_json::json!({ "error": err.to_string() })),
+        )),
+    }
+}
+
+// Scan the whole tree, emitting scan-progress batches as directories are read. The caller may
+// pass scan_id to match events to this call; the response arrives after the last batch.
+#[tauri::command]
+pub async fn scan_vault_stream(
+    state: State<'_, VaultState>,
+    app_handle: AppHandle,
+    path: Option<String>,
+    scan_id: Option<String>,
+) -> Result<ApiResponse<ScanVaultStreamResponse>, ApiError> {
+    let vault_root = match current_vault_root(&state) {
+        Ok(path) => path,
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
+    };
+
+    let rel_path = path.and_then(|value| {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(trimmed))
+        }
+    });
+    let scan_id = scan_id
+        .filter(|id| !id.trim().is_empty())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    let emit_id = scan_id.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let emit = |nodes: Vec<vault_service::FileNode>, scanned: usize, done: bool| {
+            let event = ScanProgressEvent {
+                scan_id: emit_id.clone(),
+                nodes,
+                scanned,
+                done,
+            };
+            if let Err(e) = app_handle.emit(SCAN_PROGRESS_EVENT, event) {
+                tracing::warn!(target: "vault", "failed to emit scan progress: {}", e);
+            }
+        };
+        let result = vault_service::scan_vault_stream(&vault_root, rel_path, |nodes, scanned| {
+            emit(nodes, scanned, false)
+        });
+        let scanned = result.as_ref().map(|result| result.entry_count).unwrap_or(0);
+        emit(Vec::new(), scanned, true);
+        result
+    })
+    .await;
+    match result {
+        Ok(Ok(response)) => Ok(ApiResponse::ok(ScanVaultStreamResponse {
+            scan_id,
+            vault_root: response.vault_root,
+            entry_count: response.entry_count,
+            warnings: response
+                .warnings
+                .into_iter()
+                .map(|warning| WarningItem {
+                    code: warning.code,
+                    message: warning.message,
+                    path: warning.path,
+                })
+                .collect(),
         })),
-        Ok(Err(err)) => Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
         Err(err) => Ok(ApiResponse::err(
-            "ScanFailed",
+            ErrorCode::ScanFailed,
+            "Scan task failed",
+            Some(serde_json::json!({ "error": err.to_string() })),
+        )),
+    }
+}
+
+#[tauri::command]
+pub async fn scan_vault_changes(
+    state: State<'_, VaultState>,
+    since_mtime: u64,
+) -> Result<ApiResponse<ScanVaultChangesResponse>, ApiError> {
+    let vault_root = match current_vault_root(&state) {
+        Ok(path) => path,
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
+    };
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        vault_service::scan_vault_changes(&vault_root, since_mtime)
+    })
+    .await;
+    match result {
+        Ok(Ok(response)) => Ok(ApiResponse::ok(ScanVaultChangesResponse {
+            scanned_at: response.scanned_at,
+            created: response.created,
+            modified: response.modified,
+            deleted: response.deleted,
+            warnings: response
+                .warnings
+                .into_iter()
+                .map(|warning| WarningItem {
+                    code: warning.code,
+                    message: warning.message,
+                    path: warning.path,
+                })
+                .collect(),
+        })),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
+        Err(err) => Ok(ApiResponse::err(
+            ErrorCode::ScanFailed,
             "Scan task failed",
             Some(serde_json::json!({ "error": err.to_string() })),
         )),
@@ -192,7 +630,7 @@ pub async fn read_markdown(
 ) -> Result<ApiResponse<ReadMarkdownResponse>, ApiError> {
     let vault_root = match current_vault_root(&state) {
         Ok(path) => path,
-        Err(err) => return Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
     };
 
     let rel_path = PathBuf::from(&input.path);
@@ -205,9 +643,9 @@ pub async fn read_markdown(
             content: response.content,
             mtime: response.mtime,
         })),
-        Ok(Err(err)) => Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
         Err(err) => Ok(ApiResponse::err(
-            "Unknown",
+            ErrorCode::Unknown,
             "Read task failed",
             Some(serde_json::json!({ "error": err.to_string() })),
         )),
@@ -217,17 +655,305 @@ pub async fn read_markdown(
 #[tauri::command]
 pub async fn write_markdown(
     state: State<'_, VaultState>,
+    drive_state: State<'_, VaultDriveState>,
     input: WriteMarkdownInput,
 ) -> Result<ApiResponse<WriteMarkdownResponse>, ApiError> {
     let vault_root = match current_vault_root(&state) {
         Ok(path) => path,
-        Err(err) => return Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
     };
 
+    // A vanished vault (ejected or disconnected drive) keeps the write for later instead of failing
+    if !vault_root.is_dir() {
+        vault_drive::queue_write(
+            &drive_state,
+            &vault_root,
+            &input.path,
+            input.content,
+            input.origin,
+        )?;
+        return Ok(ApiResponse::ok(WriteMarkdownResponse {
+            path: input.path,
+            mtime: None,
+            queued: true,
+        }));
+    }
+
     let rel_path = PathBuf::from(&input.path);
     let content = input.content;
+    let origin = input.origin;
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let written = vault_service::write_text_file(&vault_root, &rel_path, &content, origin)?;
+        // Task notes feed the kanban card preview; a failed refresh does not fail the write
+        if let Err(err) = planning_service::refresh_task_note_preview(&vault_root, &written.path) {
+            tracing::warn!(target: "vault", "task preview not refreshed: path={}, error={}", written.path, err.message);
+        }
+        Ok::<_, ApiError>(written)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(response)) => Ok(ApiResponse::ok(WriteMarkdownResponse {
+            path: response.path,
+            mtime: response.mtime,
+            queued: false,
+        })),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
+        Err(err) => Ok(ApiResponse::err(
+            ErrorCode::WriteFailed,
+            "Write task failed",
+            Some(serde_json::json!({ "error": err.to_string() })),
+        )),
+    }
+}
+
+// Drive the vault lives on and whether it is currently reachable
+#[tauri::command]
+pub fn get_vault_drive_status(
+    state: State<'_, VaultState>,
+    drive_state: State<'_, VaultDriveState>,
+) -> Result<ApiResponse<VaultDriveStatus>, ApiError> {
+    let vault_root = current_vault_root(&state)?;
+    Ok(ApiResponse::ok(VaultDriveStatus {
+        drive: vault_drive::detect_drive_kind(&vault_root),
+        available: vault_root.is_dir(),
+        queued_writes: drive_state.queue.lock()?.len(),
+        vault_root: vault_root.to_string_lossy().to_string(),
+    }))
+}
+
+#[tauri::command]
+pub async fn get_note_frontmatter(
+    state: State<'_, VaultState>,
+    input: ReadMarkdownInput,
+) -> Result<ApiResponse<NoteFrontmatterResponse>, ApiError> {
+    let vault_root = match current_vault_root(&state) {
+        Ok(path) => path,
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
+    };
+
+    let rel_path = PathBuf::from(input.path.trim());
+    let result =
+        tauri::async_runtime::spawn_blocking(move || vault_service::get_note_frontmatter(&vault_root, &rel_path))
+            .await;
+
+    match result {
+        Ok(Ok(response)) => Ok(ApiResponse::ok(NoteFrontmatterResponse {
+            path: response.path,
+            frontmatter: response.frontmatter,
+            mtime: response.mtime,
+        })),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
+        Err(err) => Ok(ApiResponse::err(
+            ErrorCode::Unknown,
+            "Read task failed",
+            Some(serde_json::json!({ "error": err.to_string() })),
+        )),
+    }
+}
+
+#[tauri::command]
+pub async fn set_note_frontmatter(
+    state: State<'_, VaultState>,
+    input: SetNoteFrontmatterInput,
+) -> Result<ApiResponse<NoteFrontmatterResponse>, ApiError> {
+    let vault_root = match current_vault_root(&state) {
+        Ok(path) => path,
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
+    };
+
+    let rel_path = PathBuf::from(input.path.trim());
+    let patch = input.patch;
+    let origin = input.origin;
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        vault_service::set_note_frontmatter(&vault_root, &rel_path, &patch, origin)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(response)) => Ok(ApiResponse::ok(NoteFrontmatterResponse {
+            path: response.path,
+            frontmatter: response.frontmatter,
+            mtime: response.mtime,
+        })),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
+        Err(err) => Ok(ApiResponse::err(
+            ErrorCode::WriteFailed,
+            "Write task failed",
+            Some(serde_json::json!({ "error": err.to_string() })),
+        )),
+    }
+}
+
+#[tauri::command]
+pub async fn get_note_outline(
+    state: State<'_, VaultState>,
+    input: ReadMarkdownInput,
+) -> Result<ApiResponse<NoteOutlineResponse>, ApiError> {
+    let vault_root = match current_vault_root(&state) {
+        Ok(path) => path,
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
+    };
+
+    let path = input.path.trim().to_string();
+    let rel_path = PathBuf::from(&path);
+    let result =
+        tauri::async_runtime::spawn_blocking(move || vault_service::get_note_outline(&vault_root, &rel_path)).await;
+
+    match result {
+        Ok(Ok(headings)) => Ok(ApiResponse::ok(NoteOutlineResponse { path, headings })),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
+        Err(err) => Ok(ApiResponse::err(
+            ErrorCode::Unknown,
+            "Read task failed",
+            Some(serde_json::json!({ "error": err.to_string() })),
+        )),
+    }
+}
+
+#[tauri::command]
+pub async fn note_stats(
+    state: State<'_, VaultState>,
+    input: ReadMarkdownInput,
+) -> Result<ApiResponse<NoteStatsResponse>, ApiError> {
+    let vault_root = match current_vault_root(&state) {
+        Ok(path) => path,
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
+    };
+
+    let path = input.path.trim().to_string();
+    let rel_path = PathBuf::from(&path);
+    let result =
+        tauri::async_runtime::spawn_blocking(move || vault_service::get_note_stats(&vault_root, &rel_path)).await;
+
+    match result {
+        Ok(Ok(stats)) => Ok(ApiResponse::ok(NoteStatsResponse { path, stats })),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
+        Err(err) => Ok(ApiResponse::err(
+            ErrorCode::Unknown,
+            "Read task failed",
+            Some(serde_json::json!({ "error": err.to_string() })),
+        )),
+    }
+}
+
+#[tauri::command]
+pub async fn lint_note(
+    state: State<'_, VaultState>,
+    input: ReadMarkdownInput,
+) -> Result<ApiResponse<LintNoteResponse>, ApiError> {
+    let vault_root = match current_vault_root(&state) {
+        Ok(path) => path,
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
+    };
+
+    let path = input.path.trim().to_string();
+    let rel_path = PathBuf::from(&path);
+    let result =
+        tauri::async_runtime::spawn_blocking(move || vault_service::lint_note(&vault_root, &rel_path)).await;
+
+    match result {
+        Ok(Ok(diagnostics)) => Ok(ApiResponse::ok(LintNoteResponse { path, diagnostics })),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
+        Err(err) => Ok(ApiResponse::err(
+            ErrorCode::Unknown,
+            "Read task failed",
+            Some(serde_json::json!({ "error": err.to_string() })),
+        )),
+    }
+}
+
+// Broken wikilinks and relative links under a scope (a note, a folder, or the whole vault),
+// grouped by note. External URLs are requested only when `external` is set.
+#[tauri::command]
+pub async fn check_links(
+    state: State<'_, VaultState>,
+    app_state: State<'_, AppState>,
+    scope: Option<String>,
+    external: Option<bool>,
+    concurrency: Option<usize>,
+) -> Result<ApiResponse<LinkCheckReport>, ApiError> {
+    let vault_root = match current_vault_root(&state) {
+        Ok(path) => path,
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
+    };
+
+    let scope = scope
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let result =
+        tauri::async_runtime::spawn_blocking(move || link_check::scan_links(&vault_root, scope)).await;
+    let mut scan = match result {
+        Ok(Ok(scan)) => scan,
+        Ok(Err(err)) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
+        Err(err) => {
+            return Ok(ApiResponse::err(
+                ErrorCode::ScanFailed,
+                "Link check task failed",
+                Some(serde_json::json!({ "error": err.to_string() })),
+            ))
+        }
+    };
+    if external.unwrap_or(false) {
+        link_check::check_external(
+            &app_state.http_client,
+            &mut scan,
+            concurrency.unwrap_or(link_check::DEFAULT_CONCURRENCY),
+        )
+        .await;
+    }
+    Ok(ApiResponse::ok(link_check::into_report(scan)))
+}
+
+// Stats totalled over every note under a folder; no path means the whole vault
+#[tauri::command]
+pub async fn folder_note_stats(
+    state: State<'_, VaultState>,
+    path: Option<String>,
+) -> Result<ApiResponse<FolderStatsDTO>, ApiError> {
+    let vault_root = match current_vault_root(&state) {
+        Ok(path) => path,
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
+    };
+
+    let rel_path = path.and_then(|value| {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(trimmed))
+        }
+    });
+    let result =
+        tauri::async_runtime::spawn_blocking(move || vault_service::get_folder_stats(&vault_root, rel_path)).await;
+
+    match result {
+        Ok(Ok(stats)) => Ok(ApiResponse::ok(stats)),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
+        Err(err) => Ok(ApiResponse::err(
+            ErrorCode::Unknown,
+            "Read task failed",
+            Some(serde_json::json!({ "error": err.to_string() })),
+        )),
+    }
+}
+
+#[tauri::command]
+pub async fn append_under_heading(
+    state: State<'_, VaultState>,
+    input: AppendUnderHeadingInput,
+) -> Result<ApiResponse<WriteMarkdownResponse>, ApiError> {
+    let vault_root = match current_vault_root(&state) {
+        Ok(path) => path,
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
+    };
+
+    let rel_path = PathBuf::from(input.path.trim());
+    let heading = input.heading;
+    let text = input.text;
+    let origin = input.origin;
     let result = tauri::async_runtime::spawn_blocking(move || {
-        vault_service::write_text_file(&vault_root, &rel_path, &content)
+        vault_service::append_under_heading(&vault_root, &rel_path, &heading, &text, origin)
     })
     .await;
 
@@ -235,16 +961,65 @@ pub async fn write_markdown(
         Ok(Ok(response)) => Ok(ApiResponse::ok(WriteMarkdownResponse {
             path: response.path,
             mtime: response.mtime,
+            queued: false,
         })),
-        Ok(Err(err)) => Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
         Err(err) => Ok(ApiResponse::err(
-            "WriteFailed",
+            ErrorCode::WriteFailed,
             "Write task failed",
             Some(serde_json::json!({ "error": err.to_string() })),
         )),
     }
 }
 
+// Mark a note as being edited; call again periodically to keep the lock
+#[tauri::command]
+pub async fn acquire_note_lock(
+    state: State<'_, VaultState>,
+    input: NoteLockInput,
+) -> Result<ApiResponse<NoteLockResponse>, ApiError> {
+    let vault_root = match current_vault_root(&state) {
+        Ok(path) => path,
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
+    };
+
+    let path = input.path.trim().to_string();
+    match note_locks::acquire(&vault_root, Path::new(&path)) {
+        Ok(locked_at) => Ok(ApiResponse::ok(NoteLockResponse {
+            path,
+            locked_at: Some(locked_at),
+        })),
+        Err(err) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
+    }
+}
+
+#[tauri::command]
+pub async fn release_note_lock(
+    state: State<'_, VaultState>,
+    input: NoteLockInput,
+) -> Result<ApiResponse<NoteLockResponse>, ApiError> {
+    let vault_root = match current_vault_root(&state) {
+        Ok(path) => path,
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
+    };
+
+    let path = input.path.trim().to_string();
+    match note_locks::release(&vault_root, Path::new(&path)) {
+        Ok(()) => {
+            // Daily log entries queued while the note was open go in now
+            tauri::async_runtime::spawn_blocking(move || {
+                let flushed = PlanningService::open(&vault_root)
+                    .and_then(|service| service.flush_daily_log_queue());
+                if let Err(err) = flushed {
+                    tracing::warn!(target: "vault", "daily log flush failed: {}", err.message);
+                }
+            });
+            Ok(ApiResponse::ok(NoteLockResponse { path, locked_at: None }))
+        }
+        Err(err) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
+    }
+}
+
 #[tauri::command]
 pub async fn rename_markdown(
     state: State<'_, VaultState>,
@@ -252,24 +1027,82 @@ pub async fn rename_markdown(
 ) -> Result<ApiResponse<RenameMarkdownResponse>, ApiError> {
     let vault_root = match current_vault_root(&state) {
         Ok(path) => path,
-        Err(err) => return Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
     };
 
     let rel_path = PathBuf::from(input.path.trim());
     let new_name = input.new_name;
-    let result =
-        tauri::async_runtime::spawn_blocking(move || vault_service::rename_entry(&vault_root, &rel_path, &new_name))
-            .await;
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let renamed = vault_service::rename_entry(&vault_root, &rel_path, &new_name)?;
+        let link_updates = follow_rename(&vault_root, &renamed.old_path, &renamed.new_path);
+        Ok::<_, ApiError>((renamed, link_updates))
+    })
+    .await;
 
     match result {
-        Ok(Ok(response)) => Ok(ApiResponse::ok(RenameMarkdownResponse {
+        Ok(Ok((response, link_updates))) => Ok(ApiResponse::ok(RenameMarkdownResponse {
             old_path: response.old_path,
             new_path: response.new_path,
             mtime: response.mtime,
+            link_updates,
         })),
-        Ok(Err(err)) => Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
         Err(err) => Ok(ApiResponse::err(
-            "WriteFailed",
+            ErrorCode::WriteFailed,
+            "Rename task failed",
+            Some(serde_json::json!({ "error": err.to_string() })),
+        )),
+    }
+}
+
+#[tauri::command]
+pub async fn batch_rename(
+    state: State<'_, VaultState>,
+    input: BatchRenameInput,
+) -> Result<ApiResponse<BatchRenameResponse>, ApiError> {
+    let vault_root = match current_vault_root(&state) {
+        Ok(path) => path,
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
+    };
+
+    let rel_paths: Vec<PathBuf> = input.paths.iter().map(|path| PathBuf::from(path.trim())).collect();
+    let pattern = input.pattern;
+    let dry_run = input.dry_run;
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let response = vault_service::batch_rename(&vault_root, &rel_paths, &pattern, dry_run)?;
+        let mut link_updates = vault_service::LinkUpdateReport::default();
+        if response.applied {
+            for rename in &response.renames {
+                let report = follow_rename(&vault_root, &rename.old_path, &rename.new_path);
+                link_updates.updated_links += report.updated_links;
+                link_updates.updated_files.extend(report.updated_files);
+                link_updates.skipped_files.extend(report.skipped_files);
+            }
+            link_updates.updated_files.sort();
+            link_updates.updated_files.dedup();
+            link_updates.skipped_files.sort();
+            link_updates.skipped_files.dedup();
+        }
+        Ok::<_, ApiError>((response, link_updates))
+    })
+    .await;
+
+    match result {
+        Ok(Ok((response, link_updates))) => Ok(ApiResponse::ok(BatchRenameResponse {
+            renames: response
+                .renames
+                .into_iter()
+                .map(|rename| PlannedRenameItem {
+                    old_path: rename.old_path,
+                    new_path: rename.new_path,
+                })
+                .collect(),
+            applied: response.applied,
+            link_updates,
+        })),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
+        Err(err) => Ok(ApiResponse::err(
+            ErrorCode::WriteFailed,
             "Rename task failed",
             Some(serde_json::json!({ "error": err.to_string() })),
         )),
@@ -283,7 +1116,7 @@ pub async fn delete_entry(
 ) -> Result<ApiResponse<DeleteEntryResponse>, ApiError> {
     let vault_root = match current_vault_root(&state) {
         Ok(path) => path,
-        Err(err) => return Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
     };
 
     let rel_path = PathBuf::from(input.path.trim());
@@ -292,9 +1125,9 @@ pub async fn delete_entry(
 
     match result {
         Ok(Ok(response)) => Ok(ApiResponse::ok(DeleteEntryResponse { path: response.path })),
-        Ok(Err(err)) => Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
         Err(err) => Ok(ApiResponse::err(
-            "WriteFailed",
+            ErrorCode::WriteFailed,
             "Delete task failed",
             Some(serde_json::json!({ "error": err.to_string() })),
         )),
@@ -308,7 +1141,7 @@ pub async fn create_entry(
 ) -> Result<ApiResponse<CreateEntryResponse>, ApiError> {
     let vault_root = match current_vault_root(&state) {
         Ok(path) => path,
-        Err(err) => return Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
     };
 
     let parent_rel = input.parent_path.and_then(|value| {
@@ -330,12 +1163,78 @@ pub async fn create_entry(
             path: response.path,
             kind: response.kind,
         })),
-        Ok(Err(err)) => Ok(ApiResponse::err(&err.code, &err.message, err.details)),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
         Err(err) => Ok(ApiResponse::err(
-            "WriteFailed",
+            ErrorCode::WriteFailed,
             "Create task failed",
             Some(serde_json::json!({ "error": err.to_string() })),
         )),
     }
 }
 
+
+#[tauri::command]
+pub async fn copy_entry(
+    state: State<'_, VaultState>,
+    input: CopyEntryInput,
+) -> Result<ApiResponse<CopyEntryResponse>, ApiError> {
+    let vault_root = match current_vault_root(&state) {
+        Ok(path) => path,
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
+    };
+
+    let rel_path = PathBuf::from(input.path.trim());
+    let target_dir = input.target_dir.and_then(|value| {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(trimmed))
+        }
+    });
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        vault_service::copy_entry(&vault_root, &rel_path, target_dir.as_deref())
+    })
+    .await;
+
+    match result {
+        Ok(Ok(response)) => Ok(ApiResponse::ok(CopyEntryResponse {
+            path: response.path,
+            kind: response.kind,
+        })),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
+        Err(err) => Ok(ApiResponse::err(
+            ErrorCode::WriteFailed,
+            "Copy task failed",
+            Some(serde_json::json!({ "error": err.to_string() })),
+        )),
+    }
+}
+
+#[tauri::command]
+pub async fn duplicate_note(
+    state: State<'_, VaultState>,
+    input: DuplicateNoteInput,
+) -> Result<ApiResponse<CopyEntryResponse>, ApiError> {
+    let vault_root = match current_vault_root(&state) {
+        Ok(path) => path,
+        Err(err) => return Ok(ApiResponse::err(err.code, &err.message, err.details)),
+    };
+
+    let rel_path = PathBuf::from(input.path.trim());
+    let result =
+        tauri::async_runtime::spawn_blocking(move || vault_service::duplicate_note(&vault_root, &rel_path)).await;
+
+    match result {
+        Ok(Ok(response)) => Ok(ApiResponse::ok(CopyEntryResponse {
+            path: response.path,
+            kind: response.kind,
+        })),
+        Ok(Err(err)) => Ok(ApiResponse::err(err.code, &err.message, err.details)),
+        Err(err) => Ok(ApiResponse::err(
+            ErrorCode::WriteFailed,
+            "Duplicate task failed",
+            Some(serde_json::json!({ "error": err.to_string() })),
+        )),
+    }
+}
@@ -0,0 +1,74 @@
+use tauri::{AppHandle, State};
+
+use crate::domain::webhooks::{WebhookConfig, WebhookDelivery};
+use crate::ipc::{ApiError, ApiResponse, ErrorCode};
+use crate::repo::settings_repo;
+use crate::services::planning_service::PlanningService;
+use crate::state::VaultState;
+
+// Get the webhooks configured for the current vault
+#[tauri::command]
+pub async fn webhook_list(
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<Vec<WebhookConfig>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let webhooks = settings_repo::get_webhooks(vault_path)?;
+    Ok(ApiResponse::ok(webhooks))
+}
+
+// Replace the webhook list; returns it with ids assigned
+#[tauri::command]
+pub async fn webhook_save(
+    webhooks: Vec<WebhookConfig>,
+    vault_state: State<'_, VaultState>,
+) -> Result<ApiResponse<Vec<WebhookConfig>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let webhooks = settings_repo::save_webhooks(vault_path, webhooks)?;
+    Ok(ApiResponse::ok(webhooks))
+}
+
+// Most recent webhook deliveries, newest first
+#[tauri::command]
+pub async fn webhook_list_deliveries(
+    limit: Option<i64>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<WebhookDelivery>>, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = match vault_root.as_ref() {
+        Some(path) => path,
+        None => {
+            return Err(ApiError {
+                code: ErrorCode::VaultNotSelected,
+                message: "Vault not selected".to_string(),
+                details: None,
+            });
+        }
+    };
+
+    let service = PlanningService::new(&app_handle, vault_path)?;
+    let deliveries = service.list_webhook_deliveries(limit.unwrap_or(50))?;
+    Ok(ApiResponse::ok(deliveries))
+}
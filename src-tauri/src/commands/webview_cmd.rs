@@ -0,0 +1,173 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use tauri::webview::WebviewBuilder;
+use tauri::{AppHandle, LogicalPosition, LogicalSize, Manager, State, Url, WebviewUrl};
+use uuid::Uuid;
+
+use crate::domain::webview::{
+    WebviewBounds, WebviewHistoryEntry, WebviewSession, WebviewSessionDTO,
+};
+use crate::ipc::{ApiError, ApiResponse, ErrorCode};
+use crate::services::planning_service::PlanningService;
+use crate::state::VaultState;
+use crate::webview_bridge::WEBVIEW_LABEL_PREFIX;
+
+// Window embedded browser webviews are attached to
+const MAIN_WINDOW_LABEL: &str = "main";
+// History entries returned when the caller gives no limit
+const DEFAULT_HISTORY_LIMIT: i64 = 50;
+
+fn current_vault(vault_state: &State<'_, VaultState>) -> Result<PathBuf, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    vault_root.clone().ok_or_else(|| ApiError {
+        code: ErrorCode::VaultNotSelected,
+        message: "Vault not selected".to_string(),
+        details: None,
+    })
+}
+
+fn webview_failed(message: String, label: &str) -> ApiError {
+    ApiError {
+        code: ErrorCode::WebviewFailed,
+        message,
+        details: Some(serde_json::json!({ "label": label })),
+    }
+}
+
+// Open an embedded browser webview in the main window and record it as a session of the vault.
+// A label of an existing session reopens that session; otherwise a new label is generated.
+#[tauri::command]
+pub async fn webview_open(
+    url: String,
+    label: Option<String>,
+    bounds: Option<WebviewBounds>,
+    visible: Option<bool>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<WebviewSessionDTO>, ApiError> {
+    let parsed = Url::parse(url.trim())
+        .ok()
+        .filter(|parsed| matches!(parsed.scheme(), "http" | "https"))
+        .ok_or_else(|| ApiError {
+            code: ErrorCode::InvalidInput,
+            message: "Only http and https pages can be opened".to_string(),
+            details: Some(serde_json::json!({ "url": url })),
+        })?;
+    let label = match label {
+        Some(label) if !label.starts_with(WEBVIEW_LABEL_PREFIX) => {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: format!("Webview labels must start with {}", WEBVIEW_LABEL_PREFIX),
+                details: Some(serde_json::json!({ "label": label })),
+            });
+        }
+        Some(label) => label,
+        None => format!("{}web-{}", WEBVIEW_LABEL_PREFIX, Uuid::new_v4()),
+    };
+    if app_handle.get_webview(&label).is_some() {
+        return Err(webview_failed(
+            "Webview is already open".to_string(),
+            &label,
+        ));
+    }
+
+    let window = app_handle
+        .get_window(MAIN_WINDOW_LABEL)
+        .ok_or_else(|| webview_failed("Main window not found".to_string(), &label))?;
+    let bounds = bounds.unwrap_or_default();
+    let webview = window
+        .add_child(
+            WebviewBuilder::new(&label, WebviewUrl::External(parsed.clone())),
+            LogicalPosition::new(bounds.x, bounds.y),
+            LogicalSize::new(bounds.width, bounds.height),
+        )
+        .map_err(|e| webview_failed(format!("Failed to create webview: {}", e), &label))?;
+    if !visible.unwrap_or(true) {
+        if let Err(e) = webview.hide() {
+            tracing::warn!(target: "webview", "failed to hide new webview: label={}, error={}", label, e);
+        }
+    }
+
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let now = Utc::now().to_rfc3339();
+    let previous = service
+        .list_webview_sessions()?
+        .into_iter()
+        .find(|session| session.label == label);
+    let session = WebviewSession {
+        label: label.clone(),
+        url: parsed.to_string(),
+        title: previous.as_ref().and_then(|session| session.title.clone()),
+        opened_at: previous.map_or_else(|| now.clone(), |session| session.opened_at),
+        updated_at: now,
+    };
+    service.save_webview_session(session.clone())?;
+
+    tracing::info!(target: "webview", "webview opened: label={}", label);
+    Ok(ApiResponse::ok(WebviewSessionDTO {
+        session,
+        open: true,
+    }))
+}
+
+// Close an embedded webview and forget its session and history; returns whether anything was closed
+#[tauri::command]
+pub async fn webview_close(
+    label: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<bool>, ApiError> {
+    let mut closed = false;
+    if let Some(webview) = app_handle.get_webview(&label) {
+        webview
+            .close()
+            .map_err(|e| webview_failed(format!("Failed to close webview: {}", e), &label))?;
+        closed = true;
+    }
+
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    closed |= service.remove_webview_session(&label)?;
+
+    tracing::info!(target: "webview", "webview closed: label={}, closed={}", label, closed);
+    Ok(ApiResponse::ok(closed))
+}
+
+// Sessions of the vault, oldest first, with whether each has a live webview. Sessions left from
+// a previous run are listed closed so the frontend can reopen them.
+#[tauri::command]
+pub async fn webview_list(
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<WebviewSessionDTO>>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let sessions = service
+        .list_webview_sessions()?
+        .into_iter()
+        .map(|session| WebviewSessionDTO {
+            open: app_handle.get_webview(&session.label).is_some(),
+            session,
+        })
+        .collect();
+    Ok(ApiResponse::ok(sessions))
+}
+
+// Pages visited in embedded webviews, newest first; all webviews of the vault when no label is given
+#[tauri::command]
+pub async fn webview_get_history(
+    label: Option<String>,
+    limit: Option<i64>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<WebviewHistoryEntry>>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let history = service.webview_history(
+        label.as_deref(),
+        limit.unwrap_or(DEFAULT_HISTORY_LIMIT).max(1),
+    )?;
+    Ok(ApiResponse::ok(history))
+}
@@ -0,0 +1,165 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::domain::workspace::{
+    FavoriteDTO, FavoriteKind, FavoriteRef, LayoutInput, LayoutSummary, NoteCursor, RecentNote,
+    RecentNoteDTO, WorkspaceLayout, WorkspaceStateDTO, DEFAULT_RECENT_NOTES_LIMIT,
+};
+use crate::ipc::{ApiError, ApiResponse, ErrorCode};
+use crate::services::planning_service::PlanningService;
+use crate::state::VaultState;
+
+// Emitted after a note is recorded as opened so other windows can refresh their recent notes
+pub const RECENT_NOTES_CHANGED_EVENT: &str = "recent-notes-changed";
+
+fn current_vault(vault_state: &State<'_, VaultState>) -> Result<PathBuf, ApiError> {
+    let vault_root = vault_state.root.lock()?;
+    vault_root.clone().ok_or_else(|| ApiError {
+        code: ErrorCode::VaultNotSelected,
+        message: "Vault not selected".to_string(),
+        details: None,
+    })
+}
+
+// Everything the frontend restores for the vault at startup
+#[tauri::command]
+pub async fn get_workspace_state(
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<WorkspaceStateDTO>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let state = service.get_workspace_state()?;
+    Ok(ApiResponse::ok(state))
+}
+
+#[tauri::command]
+pub async fn favorites_list(
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<FavoriteDTO>>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let favorites = service.list_favorites()?;
+    Ok(ApiResponse::ok(favorites))
+}
+
+// Pin a note (vault-relative path) or a task (id); returns the updated favorites
+#[tauri::command]
+pub async fn favorites_add(
+    kind: FavoriteKind,
+    target: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<FavoriteDTO>>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let favorites = service.add_favorite(FavoriteRef { kind, target })?;
+    Ok(ApiResponse::ok(favorites))
+}
+
+#[tauri::command]
+pub async fn favorites_remove(
+    kind: FavoriteKind,
+    target: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<FavoriteDTO>>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let favorites = service.remove_favorite(FavoriteRef { kind, target })?;
+    Ok(ApiResponse::ok(favorites))
+}
+
+#[tauri::command]
+pub async fn favorites_reorder(
+    order: Vec<FavoriteRef>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<FavoriteDTO>>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let favorites = service.reorder_favorites(order)?;
+    Ok(ApiResponse::ok(favorites))
+}
+
+// Called when the editor opens a note and as its cursor moves, so reopening the note (after a
+// reload or in another window) resumes at the same place
+#[tauri::command]
+pub async fn record_note_opened(
+    path: String,
+    cursor: Option<NoteCursor>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<RecentNote>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let note = service.record_note_opened(&path, cursor)?;
+
+    if let Err(e) = app_handle.emit(RECENT_NOTES_CHANGED_EVENT, &note) {
+        tracing::warn!(target: "workspace", "failed to emit recent notes change: {}", e);
+    }
+    Ok(ApiResponse::ok(note))
+}
+
+#[tauri::command]
+pub async fn get_recent_notes(
+    limit: Option<i64>,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<RecentNoteDTO>>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let notes = service.list_recent_notes(limit.unwrap_or(DEFAULT_RECENT_NOTES_LIMIT).max(1))?;
+    Ok(ApiResponse::ok(notes))
+}
+
+// Save the current panes, tabs and active note/board under a name
+#[tauri::command]
+pub async fn save_layout(
+    name: String,
+    layout: LayoutInput,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<WorkspaceLayout>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let layout = service.save_layout(&name, layout)?;
+    Ok(ApiResponse::ok(layout))
+}
+
+#[tauri::command]
+pub async fn load_layout(
+    name: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<WorkspaceLayout>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let layout = service.load_layout(&name)?;
+    Ok(ApiResponse::ok(layout))
+}
+
+#[tauri::command]
+pub async fn list_layouts(
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<Vec<LayoutSummary>>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let layouts = service.list_layouts()?;
+    Ok(ApiResponse::ok(layouts))
+}
+
+#[tauri::command]
+pub async fn delete_layout(
+    name: String,
+    vault_state: State<'_, VaultState>,
+    app_handle: AppHandle,
+) -> Result<ApiResponse<bool>, ApiError> {
+    let vault_path = current_vault(&vault_state)?;
+    let service = PlanningService::new(&app_handle, &vault_path)?;
+    let deleted = service.delete_layout(&name)?;
+    Ok(ApiResponse::ok(deleted))
+}
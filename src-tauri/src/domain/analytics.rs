@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::planning::Task;
+
+// Actual time above estimate * this ratio counts as under-estimated
+pub const UNDER_ESTIMATE_RATIO: f64 = 1.25;
+// Minimum number of estimated tasks before a tag can be flagged as chronic
+pub const CHRONIC_MIN_TASKS: usize = 3;
+
+// Estimate vs actual for a single task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskEstimateRow {
+    pub task_id: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub estimate_min: i64,
+    pub actual_min: i64,
+    pub ratio: f64,
+    pub under_estimated: bool,
+}
+
+// Estimate vs actual aggregated per tag
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagEstimateRow {
+    pub tag: String,
+    pub task_count: usize,
+    pub under_estimated_count: usize,
+    pub estimate_min: i64,
+    pub actual_min: i64,
+    pub ratio: f64,
+    pub chronic_under_estimation: bool,
+}
+
+// Estimate vs actual report over a date range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstimateReportDTO {
+    pub from: String,
+    pub to: String,
+    pub total_estimate_min: i64,
+    pub total_actual_min: i64,
+    pub ratio: f64,
+    pub chronic_under_estimation: bool,
+    pub tasks: Vec<TaskEstimateRow>,
+    pub tags: Vec<TagEstimateRow>,
+}
+
+fn ratio(actual_min: i64, estimate_min: i64) -> f64 {
+    if estimate_min <= 0 {
+        return 0.0;
+    }
+    actual_min as f64 / estimate_min as f64
+}
+
+fn is_chronic(task_count: usize, under_count: usize, ratio: f64) -> bool {
+    task_count >= CHRONIC_MIN_TASKS
+        && (under_count * 2 > task_count || ratio > UNDER_ESTIMATE_RATIO)
+}
+
+// Build the report from tasks paired with their tracked seconds in the range
+pub fn build_estimate_report(from: &str, to: &str, rows: Vec<(Task, i64)>) -> EstimateReportDTO {
+    let mut tasks = Vec::new();
+    let mut by_tag: BTreeMap<String, (usize, usize, i64, i64)> = BTreeMap::new();
+
+    for (task, actual_sec) in rows {
+        let Some(estimate_min) = task.estimate_min.filter(|min| *min > 0) else {
+            continue;
+        };
+        let actual_min = actual_sec / 60;
+        let task_ratio = ratio(actual_min, estimate_min);
+        let under_estimated = task_ratio > UNDER_ESTIMATE_RATIO;
+        let tags = task.tags.clone().unwrap_or_default();
+
+        for tag in &tags {
+            let entry = by_tag.entry(tag.clone()).or_insert((0, 0, 0, 0));
+            entry.0 += 1;
+            if under_estimated {
+                entry.1 += 1;
+            }
+            entry.2 += estimate_min;
+            entry.3 += actual_min;
+        }
+
+        tasks.push(TaskEstimateRow {
+            task_id: task.id,
+            title: task.title,
+            tags,
+            estimate_min,
+            actual_min,
+            ratio: task_ratio,
+            under_estimated,
+        });
+    }
+
+    // Worst offenders first
+    tasks.sort_by(|a, b| {
+        b.ratio
+            .partial_cmp(&a.ratio)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let tags = by_tag
+        .into_iter()
+        .map(
+            |(tag, (task_count, under_count, estimate_min, actual_min))| {
+                let tag_ratio = ratio(actual_min, estimate_min);
+                TagEstimateRow {
+                    tag,
+                    task_count,
+                    under_estimated_count: under_count,
+                    estimate_min,
+                    actual_min,
+                    ratio: tag_ratio,
+                    chronic_under_estimation: is_chronic(task_count, under_count, tag_ratio),
+                }
+            },
+        )
+        .collect();
+
+    let total_estimate_min: i64 = tasks.iter().map(|row| row.estimate_min).sum();
+    let total_actual_min: i64 = tasks.iter().map(|row| row.actual_min).sum();
+    let under_count = tasks.iter().filter(|row| row.under_estimated).count();
+    let total_ratio = ratio(total_actual_min, total_estimate_min);
+
+    EstimateReportDTO {
+        from: from.to_string(),
+        to: to.to_string(),
+        total_estimate_min,
+        total_actual_min,
+        ratio: total_ratio,
+        chronic_under_estimation: is_chronic(tasks.len(), under_count, total_ratio),
+        tasks,
+        tags,
+    }
+}
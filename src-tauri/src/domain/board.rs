@@ -0,0 +1,672 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::links::normalize_note_path;
+use crate::domain::planning::{Task, TaskStatus};
+use crate::domain::task_fields::FieldValuesByTask;
+use crate::ipc::{ApiError, ErrorCode};
+
+// Board used for tasks without a board_id
+pub const DEFAULT_BOARD_ID: &str = "default";
+// Longest kanban card excerpt, in characters
+pub const MAX_EXCERPT_CHARS: usize = 160;
+// Frontmatter key marking a generated board index; files without it are never touched
+const BOARD_INDEX_KEY: &str = "planning_board:";
+// Embeds ![[...]] with these extensions are images
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "avif"];
+
+// How long finished tasks stay in the kanban payload; older ones are paged with planning_list_done
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoneColumnConfig {
+    #[serde(default = "default_window_days")]
+    pub window_days: u32, // 0 keeps every done task on the board
+}
+
+impl Default for DoneColumnConfig {
+    fn default() -> Self {
+        Self {
+            window_days: default_window_days(),
+        }
+    }
+}
+
+fn default_window_days() -> u32 {
+    14
+}
+
+// Longer windows are clamped; they would overflow the date arithmetic and show every task anyway
+pub const MAX_DONE_WINDOW_DAYS: u32 = 36_500;
+
+// Generated Boards/<board>.md files that mirror board state for vault-only readers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardIndexConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_index_dir")]
+    pub dir: String, // Vault-relative
+}
+
+impl Default for BoardIndexConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: default_index_dir(),
+        }
+    }
+}
+
+fn default_index_dir() -> String {
+    "Boards".to_string()
+}
+
+// Index files written or removed by one regeneration; unchanged files are left alone
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BoardIndexResult {
+    pub written: Vec<String>,
+    pub removed: Vec<String>, // Indexes of boards that no longer exist
+    pub locked: Vec<String>,  // Open in the editor; left for the next regeneration
+}
+
+// One page of done tasks, most recently completed first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoneTasksPage {
+    pub tasks: Vec<Task>,
+    pub total: i64,
+    pub offset: i64,
+    pub limit: i64,
+}
+
+// Kanban column registered for a board
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardColumn {
+    pub board_id: String,
+    pub key: String,
+    pub name: String,
+    pub wip_limit: Option<i64>,
+    pub maps_to: TaskStatus, // Core status a task takes when moved into this column
+    pub order_index: i64,
+}
+
+// Board settings kept beside its column registry; a board has a row once something is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardMeta {
+    pub board_id: String,
+    pub color: Option<String>, // "#rrggbb"
+    pub updated_at: String,
+}
+
+// Column definition input (board_id and order come from the save call)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardColumnInput {
+    pub key: String,
+    pub name: String,
+    pub wip_limit: Option<i64>,
+    pub maps_to: TaskStatus,
+}
+
+// A column together with the tasks currently in it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KanbanColumn {
+    #[serde(flatten)]
+    pub column: BoardColumn,
+    pub tasks: Vec<Task>,
+}
+
+// Kanban of a single board grouped by its columns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardKanban {
+    pub board_id: String,
+    pub color: Option<String>,
+    pub columns: Vec<KanbanColumn>,
+}
+
+pub fn task_board_id(task: &Task) -> &str {
+    task.board_id.as_deref().unwrap_or(DEFAULT_BOARD_ID)
+}
+
+// Colors are stored as lowercase "#rrggbb"; the "#rgb" shorthand is expanded
+pub fn normalize_color(value: &str) -> Result<String, ApiError> {
+    let value = value.trim();
+    let hex = value
+        .strip_prefix('#')
+        .filter(|hex| matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or_else(|| ApiError {
+            code: ErrorCode::InvalidInput,
+            message: "Color must be a hex value like #3b82f6".to_string(),
+            details: Some(serde_json::json!({ "color": value })),
+        })?;
+    let hex = hex.to_ascii_lowercase();
+    if hex.len() == 3 {
+        Ok(hex.chars().fold("#".to_string(), |mut color, c| {
+            color.push(c);
+            color.push(c);
+            color
+        }))
+    } else {
+        Ok(format!("#{}", hex))
+    }
+}
+
+// Built-in columns used until a board registers its own
+pub fn default_columns(board_id: &str) -> Vec<BoardColumn> {
+    [
+        ("todo", "Todo", TaskStatus::Todo),
+        ("doing", "Doing", TaskStatus::Doing),
+        ("verify", "Verify", TaskStatus::Verify),
+        ("done", "Done", TaskStatus::Done),
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(index, (key, name, maps_to))| BoardColumn {
+        board_id: board_id.to_string(),
+        key: key.to_string(),
+        name: name.to_string(),
+        wip_limit: None,
+        maps_to,
+        order_index: index as i64,
+    })
+    .collect()
+}
+
+// Column a task sits in: its explicit column if still registered, else the first column for its status
+pub fn column_for_task<'a>(columns: &'a [BoardColumn], task: &Task) -> Option<&'a BoardColumn> {
+    resolve_column(columns, task.column_key.as_deref(), &task.status)
+}
+
+fn resolve_column<'a>(
+    columns: &'a [BoardColumn],
+    column_key: Option<&str>,
+    status: &TaskStatus,
+) -> Option<&'a BoardColumn> {
+    column_key
+        .and_then(|key| columns.iter().find(|column| column.key == key))
+        .or_else(|| columns.iter().find(|column| column.maps_to == *status))
+}
+
+// Aggregated tasks sharing a board, column key and status (one SQL GROUP BY row)
+#[derive(Debug, Clone)]
+pub struct ColumnTally {
+    pub board_id: String,
+    pub column_key: Option<String>,
+    pub status: TaskStatus,
+    pub count: i64,
+    pub estimate_remaining_min: i64, // Estimates of unfinished tasks only
+    pub overdue: i64,
+}
+
+// Task count of one board column
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnCount {
+    pub key: String,
+    pub count: i64,
+}
+
+// Header figures of a single board
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardStats {
+    pub board_id: String,
+    pub columns: Vec<ColumnCount>,
+    pub estimate_remaining_min: i64,
+    pub overdue: i64,
+}
+
+// Header figures for the Home page, across all boards and per board
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KanbanStats {
+    pub boards: Vec<BoardStats>,
+    pub estimate_remaining_min: i64,
+    pub overdue: i64,
+}
+
+// Fold SQL tallies into per-column counts using the same column resolution as group_boards
+pub fn summarize_tallies(registry: &[BoardColumn], tallies: &[ColumnTally]) -> KanbanStats {
+    let mut boards: BTreeMap<String, Vec<BoardColumn>> = BTreeMap::new();
+    for column in registry {
+        boards
+            .entry(column.board_id.clone())
+            .or_default()
+            .push(column.clone());
+    }
+    for tally in tallies {
+        if !boards.contains_key(&tally.board_id) {
+            boards.insert(tally.board_id.clone(), default_columns(&tally.board_id));
+        }
+    }
+
+    let mut stats = KanbanStats::default();
+    for (board_id, mut columns) in boards {
+        columns.sort_by_key(|column| column.order_index);
+        let mut board = BoardStats {
+            board_id,
+            columns: columns
+                .iter()
+                .map(|column| ColumnCount {
+                    key: column.key.clone(),
+                    count: 0,
+                })
+                .collect(),
+            estimate_remaining_min: 0,
+            overdue: 0,
+        };
+        for tally in tallies
+            .iter()
+            .filter(|tally| tally.board_id == board.board_id)
+        {
+            board.estimate_remaining_min += tally.estimate_remaining_min;
+            board.overdue += tally.overdue;
+            if let Some(column) =
+                resolve_column(&columns, tally.column_key.as_deref(), &tally.status)
+            {
+                if let Some(slot) = board.columns.iter_mut().find(|slot| slot.key == column.key) {
+                    slot.count += tally.count;
+                }
+            }
+        }
+        stats.estimate_remaining_min += board.estimate_remaining_min;
+        stats.overdue += board.overdue;
+        stats.boards.push(board);
+    }
+    stats
+}
+
+// Group tasks into per-board columns; boards without registered columns use the defaults
+pub fn group_boards(
+    registry: &[BoardColumn],
+    tasks: &[Task],
+    colors: &BTreeMap<String, String>,
+) -> Vec<BoardKanban> {
+    let mut boards: BTreeMap<String, Vec<BoardColumn>> = BTreeMap::new();
+    for column in registry {
+        boards
+            .entry(column.board_id.clone())
+            .or_default()
+            .push(column.clone());
+    }
+    for task in tasks {
+        let board_id = task_board_id(task);
+        if !boards.contains_key(board_id) {
+            boards.insert(board_id.to_string(), default_columns(board_id));
+        }
+    }
+
+    boards
+        .into_iter()
+        .map(|(board_id, mut columns)| {
+            columns.sort_by_key(|column| column.order_index);
+            let mut grouped: Vec<KanbanColumn> = columns
+                .iter()
+                .map(|column| KanbanColumn {
+                    column: column.clone(),
+                    tasks: Vec::new(),
+                })
+                .collect();
+            for task in tasks.iter().filter(|task| task_board_id(task) == board_id) {
+                if let Some(column) = column_for_task(&columns, task) {
+                    if let Some(slot) = grouped
+                        .iter_mut()
+                        .find(|slot| slot.column.key == column.key)
+                    {
+                        slot.tasks.push(task.clone());
+                    }
+                }
+            }
+            BoardKanban {
+                color: colors.get(&board_id).cloned(),
+                board_id,
+                columns: grouped,
+            }
+        })
+        .collect()
+}
+
+// Board export response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportBoardResponse {
+    pub md_path: String,
+    pub task_count: usize,
+}
+
+// Render a board's columns and tasks as a shareable markdown checklist
+// Custom field values are written as Dataview-style inline fields, [key:: value]
+pub fn render_board_markdown(
+    board: &BoardKanban,
+    fields: &FieldValuesByTask,
+    exported_at: &str,
+) -> String {
+    let mut out = format!("# {}\n\n_Exported {}_\n", board.board_id, exported_at);
+
+    for column in &board.columns {
+        out.push_str(&format!(
+            "\n## {} ({})\n\n",
+            column.column.name,
+            column.tasks.len()
+        ));
+        if column.tasks.is_empty() {
+            out.push_str("_No tasks_\n");
+            continue;
+        }
+
+        for task in &column.tasks {
+            let checked = if task.status == TaskStatus::Done {
+                "x"
+            } else {
+                " "
+            };
+            let mut line = format!("- [{}] {}", checked, task.title);
+            if let Some(priority) = task.priority {
+                line.push_str(&format!(" `{}`", priority));
+            }
+            for tag in task.tags.iter().flatten() {
+                line.push_str(&format!(" #{}", tag.replace(' ', "_")));
+            }
+            if let Some(due_date) = &task.due_date {
+                line.push_str(&format!(" (due {})", due_date));
+            }
+            for (key, value) in fields.get(&task.id).into_iter().flatten() {
+                line.push_str(&format!(" [{}:: {}]", key, value));
+            }
+            out.push_str(&line);
+            out.push('\n');
+
+            for subtask in task.subtasks.iter().flatten() {
+                let checked = if subtask.completed { "x" } else { " " };
+                out.push_str(&format!("    - [{}] {}\n", checked, subtask.title));
+            }
+        }
+    }
+
+    out
+}
+
+// Board an index file was generated for, from its frontmatter; None for files written by hand
+pub fn board_index_owner(content: &str) -> Option<&str> {
+    let rest = content.strip_prefix("---\n")?;
+    let (frontmatter, _) = rest.split_once("\n---")?;
+    frontmatter
+        .lines()
+        .find_map(|line| line.strip_prefix(BOARD_INDEX_KEY))
+        .map(str::trim)
+}
+
+// Link from a file in `from_dir` to a vault-relative path; angle brackets allow spaces
+fn relative_link(from_dir: &str, target: &str) -> String {
+    let depth = from_dir.split('/').filter(|part| !part.is_empty()).count();
+    format!("<{}{}>", "../".repeat(depth), target)
+}
+
+// Render a board as a checklist linking each task to its note. The output carries no
+// timestamp so an unchanged board renders identically and its file is not rewritten.
+pub fn render_board_index(board: &BoardKanban, index_dir: &str) -> String {
+    let mut out = format!(
+        "---\n{} {}\n---\n\n# {}\n\n_Generated from the planning board; edits here are overwritten._\n",
+        BOARD_INDEX_KEY, board.board_id, board.board_id
+    );
+
+    for column in &board.columns {
+        out.push_str(&format!(
+            "\n## {} ({})\n\n",
+            column.column.name,
+            column.tasks.len()
+        ));
+        if column.tasks.is_empty() {
+            out.push_str("_No tasks_\n");
+            continue;
+        }
+
+        for task in &column.tasks {
+            let checked = if task.status == TaskStatus::Done {
+                "x"
+            } else {
+                " "
+            };
+            let title = match &task.md_rel_path {
+                Some(md_rel_path) => format!(
+                    "[{}]({})",
+                    task.title.replace(['[', ']'], ""),
+                    relative_link(index_dir, md_rel_path)
+                ),
+                None => task.title.clone(),
+            };
+            let mut line = format!("- [{}] {}", checked, title);
+            if let Some(due_date) = &task.due_date {
+                line.push_str(&format!(" (due {})", due_date));
+            }
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+// Cover image and opening text of a task note, shown on its kanban card
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CardPreview {
+    pub cover_image: Option<String>, // Vault-relative path or http(s) URL
+    pub excerpt: Option<String>,
+}
+
+// Image target resolved like note links: ./ and ../ against the note's directory, anything
+// else from the vault root. Inline data URLs are too large to cache.
+fn resolve_image(target: &str, base_dir: &str) -> Option<String> {
+    let target = target.trim().trim_start_matches('<').trim_end_matches('>');
+    let target = target.split(" \"").next().unwrap_or(target).trim();
+    if target.is_empty() || target.starts_with("data:") {
+        return None;
+    }
+    if target.starts_with("http://") || target.starts_with("https://") {
+        return Some(target.to_string());
+    }
+    if target.contains("://") {
+        return None;
+    }
+    let decoded = target.replace("%20", " ");
+    if decoded.starts_with("./") || decoded.starts_with("../") {
+        normalize_note_path(&format!("{}/{}", base_dir, decoded))
+    } else {
+        normalize_note_path(&decoded)
+    }
+}
+
+// First image of a line: ![alt](path), an ![[image.png]] embed or <img src="...">
+fn first_image(line: &str, base_dir: &str) -> Option<String> {
+    let mut candidates: Vec<(usize, Option<String>)> = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = line[offset..].find("![") {
+        let start = offset + start;
+        let rest = &line[start + 2..];
+        if let Some(inner) = rest.strip_prefix('[') {
+            if let Some(end) = inner.find("]]") {
+                let target = inner[..end].split(['|', '#']).next().unwrap_or("").trim();
+                let is_image = target.rsplit_once('.').is_some_and(|(_, ext)| {
+                    IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str())
+                });
+                if is_image {
+                    candidates.push((start, resolve_image(target, base_dir)));
+                    break;
+                }
+            }
+        } else if let Some(close) = rest.find("](") {
+            if let Some(end) = rest[close + 2..].find(')') {
+                let target = &rest[close + 2..close + 2 + end];
+                candidates.push((start, resolve_image(target, base_dir)));
+                break;
+            }
+        }
+        offset = start + 2;
+    }
+    if let Some(start) = line.find("<img") {
+        let tag = &line[start
+            ..line[start..]
+                .find('>')
+                .map_or(line.len(), |end| start + end)];
+        let src = tag.find("src=").and_then(|index| {
+            let value = &tag[index + 4..];
+            let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            let value = &value[1..];
+            value.find(quote).map(|end| &value[..end])
+        });
+        if let Some(src) = src {
+            candidates.push((start, resolve_image(src, base_dir)));
+        }
+    }
+    candidates
+        .into_iter()
+        .min_by_key(|(start, _)| *start)
+        .and_then(|(_, image)| image)
+}
+
+// Readable text of a markdown line: markers, images and markup removed, links reduced to labels
+fn plain_text(line: &str) -> String {
+    let mut line = line.trim_start();
+    while let Some(rest) = line.strip_prefix('>') {
+        line = rest.trim_start();
+    }
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(rest) = line.strip_prefix(marker) {
+            line = rest.trim_start();
+            break;
+        }
+    }
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits > 0 && (line[digits..].starts_with(". ") || line[digits..].starts_with(") ")) {
+        line = line[digits + 2..].trim_start();
+    }
+    for checkbox in ["[ ] ", "[x] ", "[X] "] {
+        if let Some(rest) = line.strip_prefix(checkbox) {
+            line = rest;
+            break;
+        }
+    }
+
+    let mut out = String::new();
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if let Some(inner) = rest.strip_prefix("![[") {
+            rest = inner.find("]]").map_or("", |end| &inner[end + 2..]);
+            continue;
+        }
+        if let Some(inner) = rest.strip_prefix("[[") {
+            if let Some(end) = inner.find("]]") {
+                let link = &inner[..end];
+                let label = match link.split_once('|') {
+                    Some((_, alias)) => alias,
+                    None => link.split('#').next().unwrap_or(link),
+                };
+                out.push_str(label);
+                rest = &inner[end + 2..];
+                continue;
+            }
+        }
+        let image = rest.starts_with("![");
+        let label_start = if image { 2 } else { 1 };
+        if image || c == '[' {
+            let link = rest[label_start..].find(']').and_then(|close| {
+                let close = label_start + close;
+                let target = rest[close + 1..].strip_prefix('(')?;
+                let end = target.find(')')?;
+                Some((close, close + 2 + end + 1))
+            });
+            if let Some((close, end)) = link {
+                if !image {
+                    out.push_str(&rest[label_start..close]);
+                }
+                rest = &rest[end..];
+                continue;
+            }
+        }
+        if c == '<' && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/') {
+            if let Some(end) = rest.find('>') {
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        if !matches!(c, '*' | '`' | '~') {
+            out.push(c);
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Preview of a task note body; `base_dir` is the note's vault-relative directory. Headings, fenced
+// code and HTML comments are left out of the excerpt.
+pub fn card_preview(body: &str, base_dir: &str) -> CardPreview {
+    let mut cover_image = None;
+    let mut excerpt = String::new();
+    let mut fence: Option<&str> = None;
+    let mut in_comment = false;
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if let Some(open) = fence {
+            if trimmed.starts_with(open) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"]
+            .into_iter()
+            .find(|marker| trimmed.starts_with(marker))
+        {
+            fence = Some(marker);
+            continue;
+        }
+
+        let mut text = String::new();
+        let mut rest = trimmed;
+        loop {
+            if in_comment {
+                match rest.find("-->") {
+                    Some(end) => {
+                        rest = &rest[end + 3..];
+                        in_comment = false;
+                    }
+                    None => break,
+                }
+            }
+            match rest.find("<!--") {
+                Some(start) => {
+                    text.push_str(&rest[..start]);
+                    rest = &rest[start + 4..];
+                    in_comment = true;
+                }
+                None => {
+                    text.push_str(rest);
+                    break;
+                }
+            }
+        }
+
+        if cover_image.is_none() {
+            cover_image = first_image(&text, base_dir);
+        }
+        let full = excerpt.chars().count() > MAX_EXCERPT_CHARS;
+        if !full && !text.starts_with('#') {
+            let plain = plain_text(&text);
+            // Bare list markers and rules like --- carry nothing to show
+            if plain.chars().any(char::is_alphanumeric) {
+                if !excerpt.is_empty() {
+                    excerpt.push(' ');
+                }
+                excerpt.push_str(&plain);
+            }
+        }
+        if full && cover_image.is_some() {
+            break;
+        }
+    }
+
+    if excerpt.chars().count() > MAX_EXCERPT_CHARS {
+        excerpt = excerpt
+            .chars()
+            .take(MAX_EXCERPT_CHARS - 1)
+            .collect::<String>();
+        excerpt = excerpt.trim_end().to_string();
+        excerpt.push('…');
+    }
+    CardPreview {
+        cover_image,
+        excerpt: (!excerpt.is_empty()).then_some(excerpt),
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::planning::Task;
+use crate::domain::recurrence;
+use crate::domain::schedule::{self, ScheduleBlock};
+use crate::domain::timezone;
+use crate::domain::week::WeekConfig;
+
+// Longest range a single calendar query may cover
+pub const MAX_CALENDAR_DAYS: i64 = 62;
+
+// Everything the calendar shows for one day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarDay {
+    pub day: String,
+    pub due: Vec<Task>,
+    pub blocks: Vec<ScheduleBlock>,
+    pub recurrences: Vec<Task>, // Virtual instances with scheduled_start set to the occurrence
+    pub has_daily_note: bool,
+}
+
+// Calendar data for an inclusive day range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarRangeDTO {
+    pub from: String,
+    pub to: String,
+    pub days: Vec<CalendarDay>,
+}
+
+// Bucket due dates, scheduled blocks and recurrence instances into the days of [from, to]
+pub fn build_calendar(
+    from: NaiveDate,
+    to: NaiveDate,
+    due_tasks: Vec<Task>,
+    scheduled_tasks: &[Task],
+    recurring_tasks: &[Task],
+    tz: Option<Tz>,
+    week: &WeekConfig,
+) -> BTreeMap<NaiveDate, CalendarDay> {
+    let mut days = BTreeMap::new();
+    for date in from.iter_days().take_while(|date| *date <= to) {
+        days.insert(
+            date,
+            CalendarDay {
+                day: date.format("%Y-%m-%d").to_string(),
+                due: Vec::new(),
+                blocks: Vec::new(),
+                recurrences: Vec::new(),
+                has_daily_note: false,
+            },
+        );
+    }
+
+    for task in due_tasks {
+        let due_date = task
+            .due_date
+            .as_deref()
+            .and_then(|value| timezone::to_local_date(value, tz));
+        if let Some(day) = due_date.and_then(|date| days.get_mut(&date)) {
+            day.due.push(task);
+        }
+    }
+
+    // A block spanning midnight shows up on every day it touches
+    let mut scheduled: Vec<_> = scheduled_tasks
+        .iter()
+        .filter_map(|task| Some((schedule::task_schedule_range(task, tz)?, task)))
+        .collect();
+    scheduled.sort_by_key(|(range, _)| *range);
+    for ((start, end), task) in scheduled {
+        let Some(block) = schedule::block_from_task(task, tz) else {
+            continue;
+        };
+        for (date, day) in days.range_mut(start.date()..=end.date()) {
+            let (day_start, day_end) = schedule::day_bounds(*date);
+            if schedule::overlaps(start, end, day_start, day_end) {
+                day.blocks.push(block.clone());
+            }
+        }
+    }
+
+    for task in recurring_tasks {
+        let Some(series) = task
+            .periodicity
+            .as_ref()
+            .and_then(|periodicity| recurrence::Series::new(periodicity, tz, week))
+        else {
+            continue;
+        };
+        let own_start = task
+            .scheduled_start
+            .as_deref()
+            .and_then(|value| timezone::to_local_date(value, tz));
+        for (date, day) in days.iter_mut() {
+            // The task's own scheduled block already covers that day
+            if own_start == Some(*date) {
+                continue;
+            }
+            if let Some(scheduled_start) = series.occurrence_on(*date) {
+                let mut instance = task.clone();
+                instance.scheduled_start = Some(scheduled_start);
+                day.recurrences.push(instance);
+            }
+        }
+    }
+
+    days
+}
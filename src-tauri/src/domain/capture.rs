@@ -0,0 +1,261 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::planning::{CreateTaskInput, Task};
+use crate::paths::nfc;
+use crate::services::ai_service::TokenUsage;
+
+// Titles at least this similar (character bigram Dice coefficient) count as the same task
+pub const FUZZY_THRESHOLD: f32 = 0.8;
+// Embedding cosine similarity above which two titles mean the same thing
+pub const EMBEDDING_THRESHOLD: f32 = 0.9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMethod {
+    Exact,
+    Fuzzy,
+    Embedding,
+}
+
+// Existing open task a suggestion repeats
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateOf {
+    pub task_id: String,
+    pub title: String,
+    pub method: MatchMethod,
+    pub score: f32,
+}
+
+// One task proposed by smart capture; duplicate_of is set when it repeats an open task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureSuggestion {
+    #[serde(flatten)]
+    pub task: CreateTaskInput,
+    pub is_new: bool,
+    pub duplicate_of: Option<DuplicateOf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmartCaptureResponse {
+    pub tasks: Vec<CaptureSuggestion>,
+    pub usage: TokenUsage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceCaptureResponse {
+    pub path: Option<String>, // Vault audio file the transcript came from, when given by path
+    pub transcript: String,
+    pub capture: Option<SmartCaptureResponse>, // None when only the transcript was asked for
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageCaptureResponse {
+    pub path: String, // Vault image the text was read from
+    pub text: String,
+    pub note: Option<String>, // Note the text was appended to
+    pub capture: Option<SmartCaptureResponse>,
+    pub usage: TokenUsage, // Spent on recognition; capture reports its own
+}
+
+// Block appended to a note for text read from an image: the image embed, then the text
+pub fn image_text_block(image_link: &str, text: &str) -> String {
+    format!("![]({})\n\n{}\n", image_link, text.trim())
+}
+
+// Case, width and whitespace insensitive form of a title
+pub fn normalize_title(title: &str) -> String {
+    nfc(title)
+        .chars()
+        .map(|c| match c {
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            c => c,
+        })
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+// Dice coefficient over character bigrams; works for CJK titles without word boundaries
+pub fn title_similarity(a: &str, b: &str) -> f32 {
+    let bigrams = |text: &str| {
+        let chars: Vec<char> = text.chars().collect();
+        if chars.len() < 2 {
+            return chars.iter().map(|c| (*c, '\0')).collect::<Vec<_>>();
+        }
+        chars.windows(2).map(|pair| (pair[0], pair[1])).collect()
+    };
+    let left = bigrams(a);
+    let mut right = bigrams(b);
+    if left.is_empty() || right.is_empty() {
+        return 0.0;
+    }
+    let total = left.len() + right.len();
+    let mut shared = 0;
+    for bigram in left {
+        if let Some(index) = right.iter().position(|other| *other == bigram) {
+            right.swap_remove(index);
+            shared += 1;
+        }
+    }
+    (2 * shared) as f32 / total as f32
+}
+
+// Closest open task by exact or fuzzy title match
+fn match_title(title: &str, open_tasks: &[Task]) -> Option<DuplicateOf> {
+    let normalized = normalize_title(title);
+    if normalized.is_empty() {
+        return None;
+    }
+    let mut best: Option<DuplicateOf> = None;
+    for task in open_tasks {
+        let other = normalize_title(&task.title);
+        let (method, score) = if other == normalized {
+            (MatchMethod::Exact, 1.0)
+        } else {
+            (MatchMethod::Fuzzy, title_similarity(&normalized, &other))
+        };
+        if score >= FUZZY_THRESHOLD && best.as_ref().is_none_or(|best| score > best.score) {
+            best = Some(DuplicateOf {
+                task_id: task.id.clone(),
+                title: task.title.clone(),
+                method,
+                score,
+            });
+        }
+        if method == MatchMethod::Exact {
+            break;
+        }
+    }
+    best
+}
+
+// Mark each proposal as new or a duplicate of an open task. `similarity(i, j)` gives the embedding
+// similarity of proposal i and open task j when embeddings are available.
+pub fn mark_duplicates(
+    tasks: Vec<CreateTaskInput>,
+    open_tasks: &[Task],
+    similarity: Option<&dyn Fn(usize, usize) -> f32>,
+) -> Vec<CaptureSuggestion> {
+    tasks
+        .into_iter()
+        .enumerate()
+        .map(|(index, task)| {
+            let duplicate_of = match_title(&task.title, open_tasks).or_else(|| {
+                let similarity = similarity?;
+                open_tasks
+                    .iter()
+                    .enumerate()
+                    .map(|(other, open)| (open, similarity(index, other)))
+                    .filter(|(_, score)| *score >= EMBEDDING_THRESHOLD)
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(open, score)| DuplicateOf {
+                        task_id: open.id.clone(),
+                        title: open.title.clone(),
+                        method: MatchMethod::Embedding,
+                        score,
+                    })
+            });
+            CaptureSuggestion {
+                task,
+                is_new: duplicate_of.is_none(),
+                duplicate_of,
+            }
+        })
+        .collect()
+}
+
+// 1-based, inclusive line range of a note
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LineRange {
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+// Result of capturing from a note; created and annotated_lines stay empty unless creation was asked for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteCaptureResponse {
+    pub path: String,
+    pub range: LineRange,
+    pub suggestions: Vec<CaptureSuggestion>,
+    pub created: Vec<Task>,
+    pub annotated_lines: usize,
+    pub usage: TokenUsage,
+}
+
+// Text of the lines in range, with the range clamped to the note; the whole note when range is None.
+// None when the range is empty or starts past the end of the note.
+pub fn slice_lines(content: &str, range: Option<LineRange>) -> Option<(String, LineRange)> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let range = range.unwrap_or(LineRange {
+        start_line: 1,
+        end_line: lines.len(),
+    });
+    let end_line = range.end_line.min(lines.len());
+    if range.start_line == 0 || range.start_line > end_line {
+        return None;
+    }
+    let text = lines[range.start_line - 1..end_line].concat();
+    Some((
+        text,
+        LineRange {
+            start_line: range.start_line,
+            end_line,
+        },
+    ))
+}
+
+// Share of the title's bigrams found in the line
+fn title_coverage(title: &str, line: &str) -> f32 {
+    let title = normalize_title(title);
+    let line = normalize_title(line);
+    if title.is_empty() || line.is_empty() {
+        return 0.0;
+    }
+    if line.contains(&title) {
+        return 1.0;
+    }
+    let chars: Vec<char> = title.chars().collect();
+    if chars.len() < 2 {
+        return 0.0;
+    }
+    let pairs: Vec<String> = chars.windows(2).map(|pair| pair.iter().collect()).collect();
+    let found = pairs
+        .iter()
+        .filter(|pair| line.contains(pair.as_str()))
+        .count();
+    found as f32 / pairs.len() as f32
+}
+
+// Append each link to the line in range that best matches its title; lines already carrying a
+// link are skipped. Returns the new content and the number of annotated lines.
+pub fn annotate_lines(
+    content: &str,
+    range: LineRange,
+    links: &[(String, String)],
+) -> (String, usize) {
+    const MIN_COVERAGE: f32 = 0.6;
+    let mut lines: Vec<String> = content.split_inclusive('\n').map(str::to_string).collect();
+    let end_line = range.end_line.min(lines.len());
+    let mut annotated = vec![false; lines.len()];
+    let mut count = 0;
+    for (title, link) in links {
+        let best = (range.start_line.max(1) - 1..end_line)
+            .filter(|index| !annotated[*index] && !lines[*index].contains(link.as_str()))
+            .map(|index| (index, title_coverage(title, &lines[index])))
+            .filter(|(_, coverage)| *coverage >= MIN_COVERAGE)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        let Some((index, _)) = best else {
+            continue;
+        };
+        let line = &mut lines[index];
+        let ending_len = line.len() - line.trim_end_matches(['\r', '\n']).len();
+        let ending = line.split_off(line.len() - ending_len);
+        line.truncate(line.trim_end().len());
+        line.push(' ');
+        line.push_str(link);
+        line.push_str(&ending);
+        annotated[index] = true;
+        count += 1;
+    }
+    (lines.concat(), count)
+}
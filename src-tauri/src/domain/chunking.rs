@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::frontmatter;
+use crate::domain::outline::parse_heading;
+
+// Sizes are in bytes of the note text
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ChunkConfig {
+    pub max_bytes: usize,
+    pub overlap_bytes: usize, // Trailing paragraphs of a chunk repeated at the start of the next
+}
+
+impl Default for ChunkConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: 1500,
+            overlap_bytes: 200,
+        }
+    }
+}
+
+// A piece of a note for embedding; offsets are byte offsets into the whole note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteChunk {
+    pub index: usize,
+    pub heading: Option<String>, // Nearest heading above the chunk
+    pub anchor: Option<String>,  // That heading's #fragment, unique within the note
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+// A stored chunk with the embedding it was indexed under
+#[derive(Debug, Clone)]
+pub struct IndexedChunk {
+    pub path: String,
+    pub chunk: NoteChunk,
+    pub embedding: Vec<f32>,
+}
+
+impl NoteChunk {
+    // The heading keeps a short paragraph tied to the topic it belongs to
+    pub fn embedding_input(&self) -> String {
+        match &self.heading {
+            Some(heading) => format!("{}\n{}", heading, self.text),
+            None => self.text.clone(),
+        }
+    }
+}
+
+// GitHub-style heading fragment: lowercase, spaces to hyphens, punctuation dropped
+pub fn heading_anchor(text: &str) -> String {
+    text.trim()
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_whitespace() => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Block {
+    start: usize,
+    end: usize,
+    code: bool, // Fenced code is never split or carried over as overlap
+}
+
+struct Section {
+    heading: Option<(String, String)>, // Text and anchor
+    blocks: Vec<Block>,
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+// Cut an oversized paragraph at line ends, then sentence ends, then whitespace
+fn split_paragraph(content: &str, block: Block, max_bytes: usize) -> Vec<Block> {
+    let mut pieces = Vec::new();
+    let mut start = block.start;
+    while block.end - start > max_bytes {
+        let window = &content[start..floor_char_boundary(content, start + max_bytes)];
+        let after = |(index, c): (usize, char)| index + c.len_utf8();
+        let cut = window
+            .char_indices()
+            .rev()
+            .find(|(_, c)| matches!(c, '\n' | '。' | '！' | '？' | '；'))
+            .or_else(|| window.char_indices().rev().find(|(_, c)| c.is_whitespace()))
+            .map(after)
+            .unwrap_or(window.len());
+        // A window smaller than one character still has to move forward
+        let cut = if cut == 0 {
+            content[start..].chars().next().map_or(1, char::len_utf8)
+        } else {
+            cut
+        };
+        pieces.push(Block {
+            start,
+            end: start + cut,
+            code: false,
+        });
+        start += cut;
+    }
+    pieces.push(Block {
+        start,
+        end: block.end,
+        code: false,
+    });
+    pieces
+}
+
+// Headings, paragraphs and fenced code blocks of the note body, outside frontmatter
+fn sections(content: &str) -> Vec<Section> {
+    let (_, body) = frontmatter::split(content);
+    let mut offset = content.len() - body.len();
+    let mut sections = vec![Section {
+        heading: None,
+        blocks: Vec::new(),
+    }];
+    let mut anchors: HashMap<String, usize> = HashMap::new();
+    let mut paragraph: Option<Block> = None;
+    let mut fence: Option<(&str, usize)> = None;
+
+    for line in body.split_inclusive('\n') {
+        let line_end = offset + line.len();
+        let trimmed = line.trim_start();
+        let marker = ["```", "~~~"]
+            .into_iter()
+            .find(|marker| trimmed.starts_with(marker));
+        let blocks = &mut sections.last_mut().expect("sections is never empty").blocks;
+        match (fence, marker) {
+            (Some((open, start)), Some(marker)) if open == marker => {
+                blocks.push(Block {
+                    start,
+                    end: line_end,
+                    code: true,
+                });
+                fence = None;
+            }
+            (Some(_), _) => {}
+            (None, Some(marker)) => {
+                blocks.extend(paragraph.take());
+                fence = Some((marker, offset));
+            }
+            (None, None) => {
+                if let Some((_, text)) = parse_heading(line) {
+                    blocks.extend(paragraph.take());
+                    let base = heading_anchor(&text);
+                    let seen = anchors.entry(base.clone()).or_insert(0);
+                    let anchor = if *seen == 0 {
+                        base
+                    } else {
+                        format!("{}-{}", base, seen)
+                    };
+                    *seen += 1;
+                    sections.push(Section {
+                        heading: Some((text, anchor)),
+                        blocks: Vec::new(),
+                    });
+                } else if line.trim().is_empty() {
+                    blocks.extend(paragraph.take());
+                } else {
+                    let block = paragraph.get_or_insert(Block {
+                        start: offset,
+                        end: line_end,
+                        code: false,
+                    });
+                    block.end = line_end;
+                }
+            }
+        }
+        offset = line_end;
+    }
+
+    let blocks = &mut sections.last_mut().expect("sections is never empty").blocks;
+    if let Some((_, start)) = fence {
+        // Unclosed fence runs to the end of the note
+        blocks.push(Block {
+            start,
+            end: content.len(),
+            code: true,
+        });
+    }
+    blocks.extend(paragraph);
+    sections
+}
+
+// Split a note into chunks of whole paragraphs and code blocks. Chunks never cross a heading,
+// consecutive chunks of a section share up to overlap_bytes of trailing paragraphs, and only
+// paragraphs longer than max_bytes are cut.
+pub fn chunk_markdown(content: &str, config: ChunkConfig) -> Vec<NoteChunk> {
+    let max_bytes = config.max_bytes.max(1);
+    let mut chunks: Vec<NoteChunk> = Vec::new();
+    let mut emit = |heading: &Option<(String, String)>, blocks: &[Block]| {
+        let (Some(first), Some(last)) = (blocks.first(), blocks.last()) else {
+            return;
+        };
+        let text = content[first.start..last.end].trim_end();
+        if text.trim().is_empty() {
+            return;
+        }
+        chunks.push(NoteChunk {
+            index: chunks.len(),
+            heading: heading.as_ref().map(|(text, _)| text.clone()),
+            anchor: heading.as_ref().map(|(_, anchor)| anchor.clone()),
+            start: first.start,
+            end: first.start + text.len(),
+            text: text.to_string(),
+        });
+    };
+
+    for section in sections(content) {
+        let blocks = section.blocks.into_iter().flat_map(|block| {
+            if block.code || block.end - block.start <= max_bytes {
+                vec![block]
+            } else {
+                split_paragraph(content, block, max_bytes)
+            }
+        });
+        let mut current: Vec<Block> = Vec::new();
+        let mut size = 0;
+        for block in blocks {
+            let len = block.end - block.start;
+            if !current.is_empty() && size + len > max_bytes {
+                emit(&section.heading, &current);
+                let mut carried: Vec<Block> = Vec::new();
+                let mut carried_size = 0;
+                for previous in current.iter().rev() {
+                    let previous_len = previous.end - previous.start;
+                    if previous.code || carried_size + previous_len > config.overlap_bytes {
+                        break;
+                    }
+                    carried.insert(0, *previous);
+                    carried_size += previous_len;
+                }
+                // Carrying the whole chunk would repeat it
+                if carried.len() == current.len() {
+                    carried_size -= carried[0].end - carried[0].start;
+                    carried.remove(0);
+                }
+                current = carried;
+                size = carried_size;
+            }
+            current.push(block);
+            size += len;
+        }
+        emit(&section.heading, &current);
+    }
+    chunks
+}
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+
+use crate::domain::frontmatter;
+use crate::domain::planning::Task;
+use crate::ipc::ApiError;
+
+// Vault folder clipped pages are saved into
+pub const CLIPPINGS_DIR: &str = "Clippings";
+// Tag of the task created for a clipping to read later
+pub const READ_LATER_TAG: &str = "read-later";
+
+// Readable content and metadata extracted from a web page
+#[derive(Debug, Clone, Default)]
+pub struct ClippedPage {
+    pub url: String,
+    pub title: String,
+    pub byline: Option<String>,
+    pub site_name: Option<String>,
+    pub excerpt: Option<String>,
+    pub published: Option<String>,
+    pub markdown: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipResult {
+    pub path: String,
+    pub url: String,
+    pub title: String,
+    pub byline: Option<String>,
+    pub site_name: Option<String>,
+    pub excerpt: Option<String>,
+    pub word_count: usize,
+    pub task: Option<Task>, // Read-later task, when one was requested
+}
+
+// Note for a clipping: source frontmatter, then the title and the page as markdown
+pub fn render_clipping(page: &ClippedPage, clipped_at: &str) -> Result<String, ApiError> {
+    let mut mapping = Mapping::new();
+    let mut insert = |key: &str, value: Option<&str>| {
+        if let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) {
+            mapping.insert(Value::from(key), Value::from(value));
+        }
+    };
+    insert("title", Some(&page.title));
+    insert("source", Some(&page.url));
+    insert("author", page.byline.as_deref());
+    insert("site", page.site_name.as_deref());
+    insert("published", page.published.as_deref());
+    insert("description", page.excerpt.as_deref());
+    insert("clipped", Some(clipped_at));
+    mapping.insert(
+        Value::from("tags"),
+        Value::Sequence(vec![Value::from("clippings")]),
+    );
+
+    let body = format!("# {}\n\n{}\n", page.title.trim(), page.markdown.trim());
+    frontmatter::render(&mapping, &body)
+}
+
+// Rewrite setext headings (a line underlined with = or -) as # headings, which the outline
+// and chunker understand. Fenced code is left alone.
+pub fn atx_headings(markdown: &str) -> String {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut in_fence = false;
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+        if line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        let underline = lines
+            .get(index + 1)
+            .map(|next| next.trim())
+            .and_then(|next| {
+                if next.len() >= 3 && next.chars().all(|c| c == '=') {
+                    Some("#")
+                } else if next.len() >= 3 && next.chars().all(|c| c == '-') {
+                    Some("##")
+                } else {
+                    None
+                }
+            });
+        match underline {
+            Some(level) if !in_fence && !line.trim().is_empty() => {
+                out.push(format!("{} {}", level, line.trim()));
+                index += 2;
+            }
+            _ => {
+                out.push(line.to_string());
+                index += 1;
+            }
+        }
+    }
+    out.join("\n")
+}
+
+pub fn word_count(markdown: &str) -> usize {
+    markdown.split_whitespace().count()
+}
@@ -0,0 +1,145 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+// Where completed tasks and stopped timers are logged in the daily note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyLogConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_heading")]
+    pub heading: String, // Created at the end of the note when missing
+    #[serde(default)]
+    pub template: Option<String>, // Body of new daily notes; None uses DEFAULT_TEMPLATE
+}
+
+impl Default for DailyLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            heading: default_heading(),
+            template: None,
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+// Matches the section of the built-in daily template
+fn default_heading() -> String {
+    "## 今日完成".to_string()
+}
+
+// Built-in daily note body; each {{...}} variable is filled from planning data when the note is created
+pub const DEFAULT_TEMPLATE: &str = concat!(
+    "# {{date}}\n\n",
+    "## 今日到期\n\n{{due_today}}\n\n",
+    "## 日程\n\n{{events}}\n\n",
+    "## 进行中\n\n{{active_timers}}\n\n",
+    "## 昨日完成\n\n{{completed_yesterday}}\n\n",
+    "## 今日完成\n\n- \n\n",
+    "## 明日计划\n\n- \n\n",
+    "## 反思与总结\n\n",
+);
+
+// One task line of a template list
+#[derive(Debug, Clone)]
+pub struct TemplateTask {
+    pub title: String,
+    pub link: Option<String>, // Relative to the daily note
+    pub time: Option<String>, // "09:00–10:15", or "09:00–" for a running timer
+    pub done: Option<bool>,   // Renders a checkbox when set
+}
+
+// Values of the template variables for one day
+#[derive(Debug, Clone, Default)]
+pub struct DailyTemplateData {
+    pub date: String,
+    pub due_today: Vec<TemplateTask>,
+    pub completed_yesterday: Vec<TemplateTask>,
+    pub active_timers: Vec<TemplateTask>,
+    pub events: Vec<TemplateTask>,
+}
+
+// "- [ ] 09:00–10:15 [Title](link)"; an empty list keeps the skeleton's "- " placeholder
+fn render_list(tasks: &[TemplateTask]) -> String {
+    if tasks.is_empty() {
+        return "- ".to_string();
+    }
+    tasks
+        .iter()
+        .map(|task| {
+            let mut line = "- ".to_string();
+            match task.done {
+                Some(true) => line.push_str("[x] "),
+                Some(false) => line.push_str("[ ] "),
+                None => {}
+            }
+            if let Some(time) = &task.time {
+                line.push_str(time);
+                line.push(' ');
+            }
+            let title = task.title.replace('[', "\\[").replace(']', "\\]");
+            match &task.link {
+                Some(link) => line.push_str(&format!("[{}]({})", title, link)),
+                None => line.push_str(&title),
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Fill the known variables; anything else in braces is left as written
+pub fn render_template(template: &str, data: &DailyTemplateData) -> String {
+    template
+        .replace("{{date}}", &data.date)
+        .replace("{{due_today}}", &render_list(&data.due_today))
+        .replace(
+            "{{completed_yesterday}}",
+            &render_list(&data.completed_yesterday),
+        )
+        .replace("{{active_timers}}", &render_list(&data.active_timers))
+        .replace("{{events}}", &render_list(&data.events))
+}
+
+// 45m, 1h 05m
+pub fn format_duration(duration_sec: i64) -> String {
+    let minutes = duration_sec.max(0) / 60;
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+// "- 09:00–10:15 Title (1h 15m)"
+pub fn timer_entry(
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    title: &str,
+    duration_sec: i64,
+) -> String {
+    format!(
+        "- {}–{} {} ({})",
+        start.format("%H:%M"),
+        end.format("%H:%M"),
+        title,
+        format_duration(duration_sec)
+    )
+}
+
+// "- [x] 10:15 Title (1h 15m tracked)"; the duration is left out when nothing was tracked
+pub fn completion_entry(at: NaiveDateTime, title: &str, tracked_sec: i64) -> String {
+    if tracked_sec >= 60 {
+        format!(
+            "- [x] {} {} ({} tracked)",
+            at.format("%H:%M"),
+            title,
+            format_duration(tracked_sec)
+        )
+    } else {
+        format!("- [x] {} {}", at.format("%H:%M"), title)
+    }
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::board::{BoardColumn, BoardMeta};
+use crate::domain::links::TaskNoteLink;
+use crate::domain::planning::{DayLog, Task, Timer};
+use crate::domain::task_fields::{BoardField, TaskFieldValue};
+use crate::repo::settings_repo::PlanningSettings;
+
+// Identifies a planning export document; bump the version on incompatible changes
+pub const EXPORT_FORMAT: &str = "tauri-planning-app/planning";
+pub const EXPORT_VERSION: u32 = 1;
+
+// Entire planning dataset of a vault as one portable document
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlanningExport {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    pub tasks: Vec<Task>,
+    #[serde(default)]
+    pub timers: Vec<Timer>,
+    #[serde(default)]
+    pub day_logs: Vec<DayLog>,
+    #[serde(default)]
+    pub board_columns: Vec<BoardColumn>,
+    #[serde(default)]
+    pub boards: Vec<BoardMeta>,
+    #[serde(default)]
+    pub note_links: Vec<TaskNoteLink>,
+    #[serde(default)]
+    pub board_fields: Vec<BoardField>,
+    #[serde(default)]
+    pub task_fields: Vec<TaskFieldValue>,
+    #[serde(default)]
+    pub settings: Option<PlanningSettings>, // Layout excluded on import; it needs a migration
+}
+
+// Rows written by an import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub replaced: bool, // Existing planning data was cleared first
+    pub tasks: usize,
+    pub timers: usize,
+    pub day_logs: usize,
+    pub board_columns: usize,
+    pub note_links: usize,
+    pub task_fields: usize,
+    pub restored_notes: usize, // Task notes recreated because the file was missing
+    pub settings_imported: bool,
+}
+
+// Outcome of merging another vault's planning data into this one
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MergeVaultResult {
+    pub tasks_imported: usize,
+    pub tasks_remapped: usize, // Imported under a new id because the id was taken here
+    pub tasks_skipped: usize,  // Already present with the same id, title and creation time
+    pub timers_imported: usize,
+    pub day_logs_imported: usize,
+    pub day_logs_skipped: usize, // Day already logged in this vault
+    pub notes_copied: usize,
+}
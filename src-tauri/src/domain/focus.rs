@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::planning::Task;
+
+// Event emitted whenever a focus session starts or ends
+pub const FOCUS_STATE_EVENT: &str = "focus-state";
+
+// Active focus session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusSession {
+    pub task_id: String,
+    pub title: String,
+    pub planned_min: i64,
+    pub started_at: String,
+    pub ends_at: String,
+}
+
+// Focus state broadcast to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusStateDTO {
+    pub active: bool,
+    pub do_not_disturb: bool, // In-app notifications must be suppressed while set
+    pub session: Option<FocusSession>,
+}
+
+// Focus end response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusEndResponse {
+    pub session: FocusSession,
+    pub elapsed_min: i64,
+    pub task: Option<Task>,
+}
+
+impl FocusStateDTO {
+    pub fn from_session(session: Option<FocusSession>) -> Self {
+        Self {
+            active: session.is_some(),
+            do_not_disturb: session.is_some(),
+            session,
+        }
+    }
+}
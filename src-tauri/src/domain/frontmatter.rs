@@ -0,0 +1,95 @@
+use serde_yaml::{Mapping, Value};
+
+use crate::ipc::{ApiError, ErrorCode};
+
+// Split a note into its YAML frontmatter (without the --- fences) and the body after it.
+// Notes without a closed frontmatter block are all body.
+pub fn split(content: &str) -> (Option<&str>, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (None, content);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        let trimmed = line.trim_end_matches(['\r', '\n']);
+        if trimmed == "---" || trimmed == "..." {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, content)
+}
+
+// Parse frontmatter YAML into a mapping; empty frontmatter is an empty mapping
+pub fn parse(yaml: Option<&str>) -> Result<Mapping, ApiError> {
+    let Some(yaml) = yaml.filter(|yaml| !yaml.trim().is_empty()) else {
+        return Ok(Mapping::new());
+    };
+    match serde_yaml::from_str::<Value>(yaml) {
+        Ok(Value::Mapping(mapping)) => Ok(mapping),
+        Ok(Value::Null) => Ok(Mapping::new()),
+        Ok(_) => Err(ApiError {
+            code: ErrorCode::DecodeFailed,
+            message: "Frontmatter is not a key/value mapping".to_string(),
+            details: None,
+        }),
+        Err(err) => Err(ApiError {
+            code: ErrorCode::DecodeFailed,
+            message: "Failed to parse frontmatter".to_string(),
+            details: Some(serde_json::json!({ "error": err.to_string() })),
+        }),
+    }
+}
+
+// Apply a JSON merge patch: null removes a key, anything else replaces it
+pub fn apply_patch(
+    mapping: &mut Mapping,
+    patch: &serde_json::Map<String, serde_json::Value>,
+) -> Result<(), ApiError> {
+    for (key, value) in patch {
+        let key = Value::String(key.clone());
+        if value.is_null() {
+            mapping.remove(&key);
+            continue;
+        }
+        let value = serde_yaml::to_value(value).map_err(|err| ApiError {
+            code: ErrorCode::InvalidInput,
+            message: "Invalid frontmatter value".to_string(),
+            details: Some(serde_json::json!({ "error": err.to_string() })),
+        })?;
+        mapping.insert(key, value);
+    }
+    Ok(())
+}
+
+// Reassemble a note; an empty mapping drops the frontmatter block
+pub fn render(mapping: &Mapping, body: &str) -> Result<String, ApiError> {
+    if mapping.is_empty() {
+        return Ok(body.to_string());
+    }
+    let yaml = serde_yaml::to_string(mapping).map_err(|err| ApiError {
+        code: ErrorCode::WriteFailed,
+        message: "Failed to encode frontmatter".to_string(),
+        details: Some(serde_json::json!({ "error": err.to_string() })),
+    })?;
+    Ok(format!("---\n{}---\n{}", yaml, body))
+}
+
+// Frontmatter as JSON for the frontend; keys that are not strings are stringified
+pub fn to_json(mapping: &Mapping) -> serde_json::Value {
+    let mut object = serde_json::Map::new();
+    for (key, value) in mapping {
+        let key = match key {
+            Value::String(key) => key.clone(),
+            other => serde_yaml::to_string(other)
+                .map(|key| key.trim_end().to_string())
+                .unwrap_or_default(),
+        };
+        let value = serde_json::to_value(value).unwrap_or(serde_json::Value::Null);
+        object.insert(key, value);
+    }
+    serde_json::Value::Object(object)
+}
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ipc::{ApiError, ErrorCode};
+
+// Board whose tasks mirror the issues of a GitHub repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GithubBoardLink {
+    pub board_id: String,
+    pub repo: String,                   // "owner/name"
+    pub last_synced_at: Option<String>, // Issues updated since then are fetched on the next sync
+}
+
+// Task mirroring one issue, with the state both sides agreed on at the last sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GithubIssueLink {
+    pub task_id: String,
+    pub repo: String,
+    pub issue_number: i64,
+    pub synced_closed: bool,
+    pub synced_labels: Vec<String>, // Sorted
+    pub synced_at: String,
+}
+
+// Outcome of github_sync_now
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GithubSyncReport {
+    pub repos: usize,
+    pub imported: usize,       // New tasks created from issues
+    pub updated_tasks: usize,  // Tasks changed because their issue changed
+    pub updated_issues: usize, // Issues changed because their task changed
+    pub rate_limited: bool,    // Sync stopped early; the rest is picked up next time
+    pub rate_limit_reset_at: Option<String>,
+    pub errors: Vec<String>,
+}
+
+// Token presence (never the token itself) and linked boards
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GithubStatusDTO {
+    pub token_set: bool,
+    pub links: Vec<GithubBoardLink>,
+}
+
+// Validate an "owner/name" repository reference
+pub fn normalize_repo(repo: &str) -> Result<String, ApiError> {
+    let repo = repo.trim().trim_end_matches(".git");
+    let repo = repo
+        .strip_prefix("https://github.com/")
+        .unwrap_or(repo)
+        .trim_matches('/');
+    let valid_part = |part: &str| {
+        !part.is_empty()
+            && part != "."
+            && part != ".."
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    match repo.split_once('/') {
+        Some((owner, name)) if valid_part(owner) && valid_part(name) => Ok(repo.to_string()),
+        _ => Err(ApiError {
+            code: ErrorCode::InvalidInput,
+            message: "Repository must look like owner/name".to_string(),
+            details: Some(serde_json::json!({ "repo": repo })),
+        }),
+    }
+}
+
+// Label set in the order it is stored and compared
+pub fn sorted_labels(labels: &[String]) -> Vec<String> {
+    let mut labels: Vec<String> = labels
+        .iter()
+        .map(|label| label.trim().to_string())
+        .filter(|label| !label.is_empty())
+        .collect();
+    labels.sort();
+    labels.dedup();
+    labels
+}
@@ -0,0 +1,92 @@
+use std::collections::BTreeSet;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+// Longest range the missing-days query walks
+pub const MAX_MISSING_DAYS_RANGE: i64 = 366;
+
+// What makes a day count as logged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StreakBasis {
+    #[default]
+    Either, // A daily note or a completed task
+    Note,
+    Task,
+    Both,
+}
+
+impl StreakBasis {
+    pub fn is_logged(&self, has_note: bool, completed_count: usize) -> bool {
+        let has_task = completed_count > 0;
+        match self {
+            StreakBasis::Either => has_note || has_task,
+            StreakBasis::Note => has_note,
+            StreakBasis::Task => has_task,
+            StreakBasis::Both => has_note && has_task,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalStreakDTO {
+    pub basis: StreakBasis,
+    pub current: usize, // Ends today, or yesterday while today is not logged yet
+    pub longest: usize,
+    pub today_logged: bool,
+    pub last_logged_day: Option<String>,
+}
+
+// A day without a daily note, with what could be backfilled into it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissingDay {
+    pub day: String,
+    pub is_workday: bool,
+    pub completed_count: usize,
+}
+
+pub fn streak(
+    logged: &BTreeSet<NaiveDate>,
+    today: NaiveDate,
+    basis: StreakBasis,
+) -> JournalStreakDTO {
+    let logged: Vec<NaiveDate> = logged.range(..=today).copied().collect();
+
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for day in &logged {
+        run = match previous {
+            Some(previous) if previous.succ_opt() == Some(*day) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(*day);
+    }
+
+    let today_logged = logged.last() == Some(&today);
+    let anchor = if today_logged {
+        Some(today)
+    } else {
+        today.pred_opt()
+    };
+    let mut current = 0;
+    let mut expected = anchor;
+    for day in logged.iter().rev() {
+        if Some(*day) == expected {
+            current += 1;
+            expected = day.pred_opt();
+        } else if Some(*day) < expected {
+            break;
+        }
+    }
+
+    JournalStreakDTO {
+        basis,
+        current,
+        longest,
+        today_logged,
+        last_logged_day: logged.last().map(|day| day.format("%Y-%m-%d").to_string()),
+    }
+}
@@ -0,0 +1,379 @@
+use serde::{Deserialize, Serialize};
+
+use crate::paths::nfc;
+
+// Link source: added by the user, or extracted from the task markdown body
+pub const LINK_SOURCE_MANUAL: &str = "manual";
+pub const LINK_SOURCE_AUTO: &str = "auto";
+
+// Link between a task and a vault note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskNoteLink {
+    pub task_id: String,
+    pub note_path: String, // Vault-relative, forward slashes
+    pub source: String,
+    pub created_at: String,
+}
+
+// Normalize a vault-relative note path to NFC with forward slashes; None if it is empty or escapes the vault
+pub fn normalize_note_path(path: &str) -> Option<String> {
+    let path = nfc(path.trim()).replace('\\', "/");
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+    if parts.is_empty() {
+        return None;
+    }
+    Some(parts.join("/"))
+}
+
+// Extract note links ([[wikilinks]] and relative markdown links) from a task body.
+// `base_dir` is the vault-relative directory of the task file, used for ./ and ../ targets.
+pub fn extract_note_links(body: &str, base_dir: &str) -> Vec<String> {
+    let mut links = Vec::new();
+
+    // [[Note]], [[folder/Note|alias]], [[Note#heading]]
+    let mut rest = body;
+    while let Some(start) = rest.find("[[") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("]]") else {
+            break;
+        };
+        let target = after[..end].split(['|', '#']).next().unwrap_or("").trim();
+        if !target.is_empty() {
+            let target = if target.contains('.') {
+                target.to_string()
+            } else {
+                format!("{}.md", target)
+            };
+            links.extend(normalize_note_path(&target));
+        }
+        rest = &after[end + 2..];
+    }
+
+    // [label](path.md)
+    let mut rest = body;
+    while let Some(start) = rest.find("](") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find(')') else {
+            break;
+        };
+        let target = after[..end].split('#').next().unwrap_or("").trim();
+        let target = target.trim_start_matches('<').trim_end_matches('>');
+        if !target.is_empty() && !target.contains("://") && !target.starts_with("mailto:") {
+            let joined = if target.starts_with("./") || target.starts_with("../") {
+                format!("{}/{}", base_dir, target)
+            } else {
+                target.to_string()
+            };
+            links.extend(normalize_note_path(&joined));
+        }
+        rest = &after[end + 1..];
+    }
+
+    links.sort();
+    links.dedup();
+    links
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkKind {
+    Wiki,     // [[Note]]
+    Markdown, // [label](path.md), resolved in the vault
+    External, // [label](https://...)
+}
+
+// A link as written in a note: the resolved vault path (or URL for external links) and the byte
+// range of the target text in the body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkRef {
+    pub kind: LinkKind,
+    pub target: String,
+    pub raw: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+// Every link in a body with its position, resolved like extract_note_links. Links whose target
+// escapes the vault are reported with the raw text as target.
+pub fn link_refs(body: &str, base_dir: &str) -> Vec<LinkRef> {
+    let mut refs = Vec::new();
+
+    let mut offset = 0;
+    while let Some(start) = body[offset..].find("[[") {
+        let target_start = offset + start + 2;
+        let Some(end) = body[target_start..].find("]]") else {
+            break;
+        };
+        let inner = &body[target_start..target_start + end];
+        let raw = inner.split(['|', '#']).next().unwrap_or("");
+        let trimmed = raw.trim();
+        if !trimmed.is_empty() && !inner.contains('\n') {
+            let target = if trimmed.contains('.') {
+                trimmed.to_string()
+            } else {
+                format!("{}.md", trimmed)
+            };
+            refs.push(LinkRef {
+                kind: LinkKind::Wiki,
+                target: normalize_note_path(&target).unwrap_or_else(|| trimmed.to_string()),
+                raw: trimmed.to_string(),
+                start: target_start,
+                end: target_start + raw.len(),
+            });
+        }
+        offset = target_start + end + 2;
+    }
+
+    let mut offset = 0;
+    while let Some(start) = body[offset..].find("](") {
+        let target_start = offset + start + 2;
+        let Some(end) = body[target_start..].find(')') else {
+            break;
+        };
+        let inner = &body[target_start..target_start + end];
+        let raw = inner.split('#').next().unwrap_or("");
+        let trimmed = raw.trim().trim_start_matches('<').trim_end_matches('>');
+        // Link titles: [label](path.md "Title")
+        let trimmed = trimmed.split(" \"").next().unwrap_or(trimmed).trim();
+        offset = target_start + end + 1;
+        if trimmed.is_empty() || inner.contains('\n') || trimmed.starts_with("mailto:") {
+            continue;
+        }
+        let (kind, target) = if trimmed.contains("://") {
+            (LinkKind::External, trimmed.to_string())
+        } else {
+            let decoded = trimmed.replace("%20", " ");
+            let joined = if decoded.starts_with("./") || decoded.starts_with("../") {
+                format!("{}/{}", base_dir, decoded)
+            } else {
+                decoded
+            };
+            (
+                LinkKind::Markdown,
+                normalize_note_path(&joined).unwrap_or_else(|| trimmed.to_string()),
+            )
+        };
+        refs.push(LinkRef {
+            kind,
+            target,
+            raw: trimmed.to_string(),
+            start: target_start,
+            end: target_start + raw.len(),
+        });
+    }
+
+    refs.sort_by_key(|link| link.start);
+    refs
+}
+
+// Map a note path affected by a rename; directories carry their contents along
+fn remap_path(path: &str, old_path: &str, new_path: &str) -> Option<String> {
+    if path == old_path {
+        return Some(new_path.to_string());
+    }
+    path.strip_prefix(old_path)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .map(|rest| format!("{}/{}", new_path, rest))
+}
+
+// Relative markdown link target from a directory to a vault-relative path
+pub fn relative_link(from_dir: &str, to: &str) -> String {
+    let from: Vec<&str> = from_dir
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect();
+    let to_parts: Vec<&str> = to.split('/').collect();
+    let common = from
+        .iter()
+        .zip(&to_parts)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let ups = from.len() - common;
+    let rest = to_parts[common..].join("/");
+    if ups == 0 {
+        format!("./{}", rest)
+    } else {
+        format!("{}{}", "../".repeat(ups), rest)
+    }
+}
+
+// Rewrite the links of a note body after `old_path` was renamed to `new_path`, resolving links the
+// same way extract_note_links does. `old_base_dir` / `new_base_dir` are the note's own directory
+// before and after the rename (they differ when the note moved with a renamed directory).
+// Returns the new body and the number of rewritten links, or None when nothing changed.
+pub fn rewrite_note_links(
+    body: &str,
+    old_base_dir: &str,
+    new_base_dir: &str,
+    old_path: &str,
+    new_path: &str,
+) -> Option<(String, usize)> {
+    let mut count = 0;
+
+    // [[Note]], [[folder/Note|alias]], [[Note#heading]]
+    let mut output = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(start) = rest.find("[[") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("]]") else {
+            break;
+        };
+        let inner = &after[..end];
+        let split_at = inner.find(['|', '#']).unwrap_or(inner.len());
+        let (target, suffix) = inner.split_at(split_at);
+        let implicit_ext = !target.contains('.');
+        let resolved = if implicit_ext {
+            format!("{}.md", target.trim())
+        } else {
+            target.trim().to_string()
+        };
+        let remapped = normalize_note_path(&resolved)
+            .and_then(|resolved| remap_path(&resolved, old_path, new_path));
+        output.push_str(&rest[..start + 2]);
+        match remapped {
+            Some(remapped) => {
+                let remapped = match remapped.strip_suffix(".md") {
+                    Some(stem) if implicit_ext => stem.to_string(),
+                    _ => remapped,
+                };
+                output.push_str(&remapped);
+                output.push_str(suffix);
+                count += 1;
+            }
+            None => output.push_str(inner),
+        }
+        output.push_str("]]");
+        rest = &after[end + 2..];
+    }
+    output.push_str(rest);
+
+    // [label](path.md)
+    let body = output;
+    let mut output = String::with_capacity(body.len());
+    let mut rest = body.as_str();
+    while let Some(start) = rest.find("](") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find(')') else {
+            break;
+        };
+        let inner = &after[..end];
+        let split_at = inner.find('#').unwrap_or(inner.len());
+        let (raw_target, anchor) = inner.split_at(split_at);
+        let trimmed = raw_target.trim();
+        let bracketed = trimmed.starts_with('<') && trimmed.ends_with('>');
+        let target = trimmed.trim_start_matches('<').trim_end_matches('>');
+        let encoded = target.contains("%20");
+        let target = target.replace("%20", " ");
+        let relative = target.starts_with("./") || target.starts_with("../");
+
+        let mut replacement = None;
+        if !target.is_empty() && !target.contains("://") && !target.starts_with("mailto:") {
+            let joined = if relative {
+                format!("{}/{}", old_base_dir, target)
+            } else {
+                target.clone()
+            };
+            if let Some(resolved) = normalize_note_path(&joined) {
+                let remapped = remap_path(&resolved, old_path, new_path);
+                // Relative links from a note that moved need recomputing even if their target did not
+                if remapped.is_some() || (relative && old_base_dir != new_base_dir) {
+                    let destination = remapped.unwrap_or(resolved);
+                    let mut link = if relative {
+                        relative_link(new_base_dir, &destination)
+                    } else {
+                        destination
+                    };
+                    if encoded {
+                        link = link.replace(' ', "%20");
+                    }
+                    if bracketed || link.contains(' ') {
+                        link = format!("<{}>", link);
+                    }
+                    if link != trimmed {
+                        replacement = Some(link);
+                    }
+                }
+            }
+        }
+
+        output.push_str(&rest[..start + 2]);
+        match replacement {
+            Some(link) => {
+                output.push_str(&link);
+                output.push_str(anchor);
+                count += 1;
+            }
+            None => output.push_str(inner),
+        }
+        output.push(')');
+        rest = &after[end + 1..];
+    }
+    output.push_str(rest);
+
+    (count > 0).then_some((output, count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrite_keeps_wikilink_aliases_and_headings() {
+        let body = "See [[Projects/Old|the plan]], [[Projects/Old#Goals]], [[Projects/Old.md]] and [[Other]]";
+        let (rewritten, count) =
+            rewrite_note_links(body, "Notes", "Notes", "Projects/Old.md", "Projects/New.md")
+                .unwrap();
+        assert_eq!(
+            rewritten,
+            "See [[Projects/New|the plan]], [[Projects/New#Goals]], [[Projects/New.md]] and [[Other]]"
+        );
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn rewrite_recomputes_relative_links_of_a_note_moved_with_its_directory() {
+        let body = "[spec](./spec.md) [home](../../Home.md) [beta](../Beta/x.md#Top)";
+        let (rewritten, count) = rewrite_note_links(
+            body,
+            "Projects/Alpha",
+            "Archive/2024/Alpha",
+            "Projects/Alpha",
+            "Archive/2024/Alpha",
+        )
+        .unwrap();
+        // Links inside the moved directory still resolve as written
+        assert_eq!(
+            rewritten,
+            "[spec](./spec.md) [home](../../../Home.md) [beta](../../../Projects/Beta/x.md#Top)"
+        );
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn rewrite_keeps_percent_encoded_and_bracketed_targets_in_their_form() {
+        let body = "[a](My%20Notes/Old%20Plan.md#Intro) [b](<My Notes/Old Plan.md>)";
+        let (rewritten, count) =
+            rewrite_note_links(body, "", "", "My Notes/Old Plan.md", "My Notes/New Plan.md")
+                .unwrap();
+        assert_eq!(
+            rewritten,
+            "[a](My%20Notes/New%20Plan.md#Intro) [b](<My Notes/New Plan.md>)"
+        );
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn rewrite_returns_none_without_affected_links() {
+        let body = "[[Other]] [site](https://example.com/Old.md) [c](Notes/Older.md)";
+        assert!(rewrite_note_links(body, "", "", "Notes/Old.md", "Notes/New.md").is_none());
+    }
+}
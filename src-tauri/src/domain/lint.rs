@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::frontmatter;
+use crate::domain::links::{self, LinkKind};
+use crate::domain::note_stats;
+use crate::repo::settings_repo::LintSettings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintRule {
+    DuplicateWord,
+    LongParagraph,
+    BrokenLink,
+    Frontmatter,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Error,
+    Warning,
+    Info,
+}
+
+// One finding; start and end are byte offsets into the whole note, line and column (1-based,
+// column in characters) locate start for editors that work by position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintDiagnostic {
+    pub rule: LintRule,
+    pub severity: LintSeverity,
+    pub message: String,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+// Byte ranges of the body's paragraphs, leaving out headings and fenced code
+fn paragraphs(content: &str, body_start: usize) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut offset = body_start;
+    let mut fence: Option<&str> = None;
+    let mut current: Option<(usize, usize)> = None;
+    for line in content[body_start..].split_inclusive('\n') {
+        let trimmed = line.trim();
+        let marker = ["```", "~~~"]
+            .into_iter()
+            .find(|marker| trimmed.starts_with(marker));
+        let prose = match (fence, marker) {
+            (Some(open), Some(marker)) if open == marker => {
+                fence = None;
+                false
+            }
+            (None, Some(marker)) => {
+                fence = Some(marker);
+                false
+            }
+            (None, None) => !trimmed.is_empty() && !trimmed.starts_with('#'),
+            _ => false,
+        };
+        if prose {
+            let end = offset + line.trim_end().len();
+            current = Some(current.map_or((offset, end), |(start, _)| (start, end)));
+        } else if let Some(range) = current.take() {
+            ranges.push(range);
+        }
+        offset += line.len();
+    }
+    ranges.extend(current);
+    ranges
+}
+
+// Byte ranges of fenced code blocks, where links and repeated words are not prose
+pub fn fenced_ranges(content: &str, body_start: usize) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut offset = body_start;
+    let mut open: Option<(&str, usize)> = None;
+    for line in content[body_start..].split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let marker = ["```", "~~~"]
+            .into_iter()
+            .find(|marker| trimmed.starts_with(marker));
+        match (open, marker) {
+            (Some((fence, start)), Some(marker)) if fence == marker => {
+                ranges.push((start, offset + line.len()));
+                open = None;
+            }
+            (None, Some(marker)) => open = Some((marker, offset)),
+            _ => {}
+        }
+        offset += line.len();
+    }
+    if let Some((_, start)) = open {
+        ranges.push((start, content.len()));
+    }
+    ranges
+}
+
+// 1-based line and character column of a byte offset
+pub fn position(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+fn diagnostic(
+    content: &str,
+    rule: LintRule,
+    severity: LintSeverity,
+    message: String,
+    start: usize,
+    end: usize,
+) -> LintDiagnostic {
+    let (line, column) = position(content, start);
+    LintDiagnostic {
+        rule,
+        severity,
+        message,
+        start,
+        end,
+        line,
+        column,
+    }
+}
+
+// "the the": the same word twice with only whitespace between, ignoring case
+fn duplicate_words(content: &str, start: usize, end: usize) -> Vec<(usize, usize, String)> {
+    let text = &content[start..end];
+    let mut found = Vec::new();
+    let mut previous: Option<&str> = None;
+    let mut word_start: Option<usize> = None;
+    let mut gap_is_space = true;
+    for (index, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        if c.is_alphabetic() {
+            if word_start.is_none() {
+                word_start = Some(index);
+            }
+            continue;
+        }
+        if let Some(begin) = word_start.take() {
+            let word = &text[begin..index];
+            if gap_is_space
+                && previous.is_some_and(|previous| previous.to_lowercase() == word.to_lowercase())
+            {
+                found.push((start + begin, start + index, word.to_string()));
+            }
+            previous = Some(word);
+            gap_is_space = true;
+        }
+        if !c.is_whitespace() {
+            gap_is_space = false;
+        }
+    }
+    found
+}
+
+// Run the enabled checks. `exists` tells whether a vault-relative link target is present;
+// `base_dir` is the note's directory for relative links.
+pub fn lint(
+    content: &str,
+    base_dir: &str,
+    settings: &LintSettings,
+    exists: impl Fn(&str) -> bool,
+) -> Vec<LintDiagnostic> {
+    let mut diagnostics = Vec::new();
+    let (yaml, body) = frontmatter::split(content);
+    let body_start = content.len() - body.len();
+
+    if settings.frontmatter {
+        let opened = content.starts_with("---\n") || content.starts_with("---\r\n");
+        if opened && yaml.is_none() {
+            let end = content.find('\n').unwrap_or(content.len());
+            diagnostics.push(diagnostic(
+                content,
+                LintRule::Frontmatter,
+                LintSeverity::Error,
+                "Frontmatter is never closed with ---".to_string(),
+                0,
+                end,
+            ));
+        } else if let Err(err) = frontmatter::parse(yaml) {
+            let detail = err
+                .details
+                .as_ref()
+                .and_then(|details| details.get("error"))
+                .and_then(|error| error.as_str())
+                .map(|error| format!("{}: {}", err.message, error))
+                .unwrap_or(err.message);
+            diagnostics.push(diagnostic(
+                content,
+                LintRule::Frontmatter,
+                LintSeverity::Error,
+                detail,
+                0,
+                body_start,
+            ));
+        }
+    }
+
+    let paragraphs = paragraphs(content, body_start);
+    if settings.duplicate_words {
+        for (start, end) in &paragraphs {
+            for (word_start, word_end, word) in duplicate_words(content, *start, *end) {
+                diagnostics.push(diagnostic(
+                    content,
+                    LintRule::DuplicateWord,
+                    LintSeverity::Warning,
+                    format!("Repeated word \"{}\"", word),
+                    word_start,
+                    word_end,
+                ));
+            }
+        }
+    }
+
+    if settings.long_paragraphs {
+        for (start, end) in &paragraphs {
+            let (words, cjk_chars) = note_stats::count_words(&content[*start..*end]);
+            let count = words + cjk_chars;
+            if count > settings.max_paragraph_words {
+                diagnostics.push(diagnostic(
+                    content,
+                    LintRule::LongParagraph,
+                    LintSeverity::Info,
+                    format!(
+                        "Paragraph has {} words; consider splitting it (limit {})",
+                        count, settings.max_paragraph_words
+                    ),
+                    *start,
+                    *end,
+                ));
+            }
+        }
+    }
+
+    if settings.broken_links {
+        let fenced = fenced_ranges(content, body_start);
+        for link in links::link_refs(body, base_dir) {
+            let start = body_start + link.start;
+            let in_code = fenced
+                .iter()
+                .any(|(fence_start, fence_end)| (*fence_start..*fence_end).contains(&start));
+            if link.kind == LinkKind::External || in_code || exists(&link.target) {
+                continue;
+            }
+            diagnostics.push(diagnostic(
+                content,
+                LintRule::BrokenLink,
+                LintSeverity::Warning,
+                format!("Link target {} does not exist", link.target),
+                start,
+                body_start + link.end,
+            ));
+        }
+    }
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.start);
+    diagnostics
+}
@@ -1 +1,36 @@
+pub mod analytics;
+pub mod board;
+pub mod calendar;
+pub mod capture;
+pub mod chunking;
+pub mod clipping;
+pub mod daily_log;
+pub mod export;
+pub mod focus;
+pub mod frontmatter;
+pub mod github;
+pub mod journal;
+pub mod links;
+pub mod lint;
+pub mod note_query;
+pub mod note_stats;
+pub mod notifications;
+pub mod outline;
+pub mod outliner_import;
+pub mod plaintext_export;
 pub mod planning;
+pub mod reading_list;
+pub mod recurrence;
+pub mod reminders;
+pub mod rules;
+pub mod schedule;
+pub mod search;
+pub mod sprint;
+pub mod table_import;
+pub mod tagging;
+pub mod task_fields;
+pub mod timezone;
+pub mod webhooks;
+pub mod webview;
+pub mod week;
+pub mod workspace;
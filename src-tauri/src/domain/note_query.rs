@@ -0,0 +1,406 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::ipc::{ApiError, ErrorCode};
+
+// Most notes one query returns
+pub const MAX_QUERY_RESULTS: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteSortKey {
+    pub field: String,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+// filter: `status = "active" AND (type = project OR tags contains work)`. Fields are frontmatter
+// keys (dotted for nested maps) or file.path, file.name, file.folder and file.mtime.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NoteQueryInput {
+    #[serde(default)]
+    pub filter: Option<String>,
+    #[serde(default)]
+    pub sort: Vec<NoteSortKey>,
+    #[serde(default)]
+    pub fields: Vec<String>, // Empty returns the whole frontmatter
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteQueryRow {
+    pub path: String,
+    pub fields: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteQueryResult {
+    pub total: usize, // Matches before the limit
+    pub notes: Vec<NoteQueryRow>,
+}
+
+// A note as queries see it
+#[derive(Debug, Clone)]
+pub struct IndexedNote {
+    pub path: String,
+    pub mtime: i64, // Unix seconds
+    pub frontmatter: Map<String, Value>,
+}
+
+impl IndexedNote {
+    fn field(&self, key: &str) -> Option<Value> {
+        match key {
+            "file.path" => Some(Value::from(self.path.as_str())),
+            "file.name" => {
+                let name = self.path.rsplit('/').next().unwrap_or(&self.path);
+                Some(Value::from(name.strip_suffix(".md").unwrap_or(name)))
+            }
+            "file.folder" => Some(Value::from(
+                self.path.rsplit_once('/').map_or("", |(folder, _)| folder),
+            )),
+            "file.mtime" => Some(Value::from(self.mtime)),
+            _ => {
+                if let Some(value) = self.frontmatter.get(key) {
+                    return Some(value.clone());
+                }
+                let mut parts = key.split('.');
+                let mut value = self.frontmatter.get(parts.next()?)?;
+                for part in parts {
+                    value = value.get(part)?;
+                }
+                Some(value.clone())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Contains,
+}
+
+#[derive(Debug, Clone)]
+enum NoteFilter {
+    And(Box<NoteFilter>, Box<NoteFilter>),
+    Or(Box<NoteFilter>, Box<NoteFilter>),
+    Not(Box<NoteFilter>),
+    Exists(String),
+    Compare(String, Op, Value),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String), // Field name, keyword or unquoted value
+    Text(String), // Quoted
+    Op(Op),
+    Open,
+    Close,
+}
+
+fn invalid_query(message: &str, position: usize) -> ApiError {
+    ApiError {
+        code: ErrorCode::InvalidInput,
+        message: format!("Invalid query: {}", message),
+        details: Some(serde_json::json!({ "position": position })),
+    }
+}
+
+fn tokenize(filter: &str) -> Result<Vec<(Token, usize)>, ApiError> {
+    let mut tokens = Vec::new();
+    let mut chars = filter.char_indices().peekable();
+    while let Some(&(position, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let token = match c {
+            '(' => {
+                chars.next();
+                Token::Open
+            }
+            ')' => {
+                chars.next();
+                Token::Close
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '\\')) => text.extend(chars.next().map(|(_, c)| c)),
+                        Some((_, quote)) if quote == c => break,
+                        Some((_, c)) => text.push(c),
+                        None => return Err(invalid_query("unclosed quote", position)),
+                    }
+                }
+                Token::Text(text)
+            }
+            '=' | '!' | '<' | '>' => {
+                chars.next();
+                let equals = chars.next_if(|(_, next)| *next == '=').is_some();
+                Token::Op(match (c, equals) {
+                    ('=', _) => Op::Eq,
+                    ('!', true) => Op::Ne,
+                    ('<', false) => Op::Lt,
+                    ('<', true) => Op::Le,
+                    ('>', false) => Op::Gt,
+                    ('>', true) => Op::Ge,
+                    _ => return Err(invalid_query("expected != after !", position)),
+                })
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some((_, c)) =
+                    chars.next_if(|(_, c)| !c.is_whitespace() && !"()=!<>\"'".contains(*c))
+                {
+                    word.push(c);
+                }
+                Token::Word(word)
+            }
+        };
+        tokens.push((token, position));
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    next: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(token, _)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.next)
+            .map_or(self.end, |(_, position)| *position)
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found =
+            matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword));
+        if found {
+            self.next += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> Result<NoteFilter, ApiError> {
+        let mut filter = self.and()?;
+        while self.keyword("or") {
+            filter = NoteFilter::Or(Box::new(filter), Box::new(self.and()?));
+        }
+        Ok(filter)
+    }
+
+    fn and(&mut self) -> Result<NoteFilter, ApiError> {
+        let mut filter = self.not()?;
+        while self.keyword("and") {
+            filter = NoteFilter::And(Box::new(filter), Box::new(self.not()?));
+        }
+        Ok(filter)
+    }
+
+    fn not(&mut self) -> Result<NoteFilter, ApiError> {
+        if self.keyword("not") {
+            return Ok(NoteFilter::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<NoteFilter, ApiError> {
+        let position = self.position();
+        let field = match self.tokens.get(self.next).cloned() {
+            Some((Token::Open, _)) => {
+                self.next += 1;
+                let filter = self.or()?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(invalid_query("expected )", self.position()));
+                }
+                self.next += 1;
+                return Ok(filter);
+            }
+            Some((Token::Word(word), _)) => word,
+            _ => return Err(invalid_query("expected a field", position)),
+        };
+        self.next += 1;
+
+        let op = match self.peek() {
+            Some(Token::Op(op)) => *op,
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("contains") => Op::Contains,
+            // A bare field matches notes that have it
+            _ => return Ok(NoteFilter::Exists(field)),
+        };
+        self.next += 1;
+        let position = self.position();
+        let value = match self.tokens.get(self.next).cloned() {
+            Some((Token::Text(text), _)) => Value::from(text),
+            Some((Token::Word(word), _)) => match word.to_ascii_lowercase().as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "null" => Value::Null,
+                _ => word
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(serde_json::Number::from_f64)
+                    .map(Value::Number)
+                    .unwrap_or(Value::from(word)),
+            },
+            _ => return Err(invalid_query("expected a value", position)),
+        };
+        self.next += 1;
+        Ok(NoteFilter::Compare(field, op, value))
+    }
+}
+
+fn parse_filter(filter: &str) -> Result<NoteFilter, ApiError> {
+    let mut parser = Parser {
+        tokens: tokenize(filter)?,
+        next: 0,
+        end: filter.len(),
+    };
+    let parsed = parser.or()?;
+    if parser.next < parser.tokens.len() {
+        return Err(invalid_query("unexpected input", parser.position()));
+    }
+    Ok(parsed)
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn as_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.to_lowercase(),
+        other => other.to_string().to_lowercase(),
+    }
+}
+
+// Numbers compare as numbers, anything else as lowercase text (ISO dates sort)
+fn order(stored: &Value, wanted: &Value) -> Ordering {
+    match (as_number(stored), as_number(wanted)) {
+        (Some(stored), Some(wanted)) => stored.total_cmp(&wanted),
+        _ => as_text(stored).cmp(&as_text(wanted)),
+    }
+}
+
+fn compare(stored: &Value, op: Op, wanted: &Value) -> bool {
+    if let Value::Array(items) = stored {
+        // Lists match when any item does; != when none equals
+        return match op {
+            Op::Ne => !items.iter().any(|item| compare(item, Op::Eq, wanted)),
+            Op::Contains => items.iter().any(|item| compare(item, Op::Eq, wanted)),
+            _ => items.iter().any(|item| compare(item, op, wanted)),
+        };
+    }
+    match op {
+        Op::Eq => order(stored, wanted).is_eq(),
+        Op::Ne => order(stored, wanted).is_ne(),
+        Op::Gt => order(stored, wanted).is_gt(),
+        Op::Ge => order(stored, wanted).is_ge(),
+        Op::Lt => order(stored, wanted).is_lt(),
+        Op::Le => order(stored, wanted).is_le(),
+        Op::Contains => as_text(stored).contains(&as_text(wanted)),
+    }
+}
+
+fn matches(filter: &NoteFilter, note: &IndexedNote) -> bool {
+    match filter {
+        NoteFilter::And(left, right) => matches(left, note) && matches(right, note),
+        NoteFilter::Or(left, right) => matches(left, note) || matches(right, note),
+        NoteFilter::Not(inner) => !matches(inner, note),
+        NoteFilter::Exists(field) => note.field(field).is_some_and(|value| !value.is_null()),
+        NoteFilter::Compare(field, op, wanted) => match (note.field(field), wanted) {
+            (stored, Value::Null) => {
+                let missing = stored.is_none_or(|value| value.is_null());
+                match op {
+                    Op::Eq => missing,
+                    Op::Ne => !missing,
+                    _ => false,
+                }
+            }
+            (None | Some(Value::Null), _) => *op == Op::Ne,
+            (Some(stored), wanted) => compare(&stored, *op, wanted),
+        },
+    }
+}
+
+// Filter, sort and project indexed notes. Notes missing a sort field sort after the rest.
+pub fn run_query(
+    notes: Vec<IndexedNote>,
+    input: &NoteQueryInput,
+) -> Result<NoteQueryResult, ApiError> {
+    let filter = match input.filter.as_deref().map(str::trim) {
+        Some(filter) if !filter.is_empty() => Some(parse_filter(filter)?),
+        _ => None,
+    };
+    let mut matched: Vec<IndexedNote> = notes
+        .into_iter()
+        .filter(|note| filter.as_ref().is_none_or(|filter| matches(filter, note)))
+        .collect();
+
+    matched.sort_by(|a, b| {
+        for key in &input.sort {
+            let ordering = match (a.field(&key.field), b.field(&key.field)) {
+                (Some(a), Some(b)) if !a.is_null() && !b.is_null() => {
+                    let ordering = order(&a, &b);
+                    if key.descending {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                }
+                (Some(a), _) if !a.is_null() => Ordering::Less,
+                (_, Some(b)) if !b.is_null() => Ordering::Greater,
+                _ => Ordering::Equal,
+            };
+            if ordering.is_ne() {
+                return ordering;
+            }
+        }
+        a.path.cmp(&b.path)
+    });
+
+    let total = matched.len();
+    let limit = input
+        .limit
+        .unwrap_or(MAX_QUERY_RESULTS)
+        .min(MAX_QUERY_RESULTS);
+    let notes = matched
+        .into_iter()
+        .take(limit)
+        .map(|note| {
+            let fields = if input.fields.is_empty() {
+                note.frontmatter.clone()
+            } else {
+                input
+                    .fields
+                    .iter()
+                    .map(|field| (field.clone(), note.field(field).unwrap_or(Value::Null)))
+                    .collect()
+            };
+            NoteQueryRow {
+                path: note.path,
+                fields,
+            }
+        })
+        .collect();
+    Ok(NoteQueryResult { total, notes })
+}
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::frontmatter;
+use crate::domain::outline::{self, HeadingNode};
+
+// Reading speeds for the time estimate: space-separated words and CJK characters per minute
+const WORDS_PER_MINUTE: f64 = 200.0;
+const CJK_CHARS_PER_MINUTE: f64 = 300.0;
+
+// Counts over the note body; frontmatter is left out
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NoteStats {
+    pub words: usize,      // Each CJK character counts as one word
+    pub cjk_chars: usize,  // Han and kana characters among the words
+    pub characters: usize, // Excluding whitespace
+    pub headings: usize,
+    pub reading_minutes: usize, // Rounded up; 0 only for an empty note
+}
+
+impl NoteStats {
+    pub fn add(&mut self, other: &NoteStats) {
+        self.words += other.words;
+        self.cjk_chars += other.cjk_chars;
+        self.characters += other.characters;
+        self.headings += other.headings;
+        self.reading_minutes = reading_minutes(self.words - self.cjk_chars, self.cjk_chars);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteStatsEntry {
+    pub path: String,
+    pub stats: NoteStats,
+}
+
+// Totals over every note under a folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderStatsDTO {
+    pub path: String,
+    pub note_count: usize,
+    pub totals: NoteStats,
+    pub notes: Vec<NoteStatsEntry>,
+}
+
+// Scripts written without spaces between words, where every character reads as a word. Hangul
+// separates words with spaces and is counted like Latin text.
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'     // Hiragana, Katakana
+        | '\u{3400}'..='\u{4DBF}'   // CJK Extension A
+        | '\u{4E00}'..='\u{9FFF}'   // CJK Unified Ideographs
+        | '\u{F900}'..='\u{FAFF}'   // CJK Compatibility Ideographs
+        | '\u{20000}'..='\u{2FA1F}' // Extensions B onwards
+    )
+}
+
+fn reading_minutes(words: usize, cjk_chars: usize) -> usize {
+    let minutes = words as f64 / WORDS_PER_MINUTE + cjk_chars as f64 / CJK_CHARS_PER_MINUTE;
+    minutes.ceil() as usize
+}
+
+fn count_headings(headings: &[HeadingNode]) -> usize {
+    headings
+        .iter()
+        .map(|heading| 1 + count_headings(&heading.children))
+        .sum()
+}
+
+// Space-separated words and CJK characters in a piece of text
+pub fn count_words(text: &str) -> (usize, usize) {
+    let mut words = 0;
+    let mut cjk_chars = 0;
+    let mut in_word = false;
+    for c in text.chars() {
+        if is_cjk(c) {
+            cjk_chars += 1;
+            in_word = false;
+        } else if c.is_alphanumeric() {
+            if !in_word {
+                words += 1;
+                in_word = true;
+            }
+        } else {
+            // Apostrophes and hyphens inside a word keep it whole: don't, well-known
+            in_word = in_word && matches!(c, '\'' | '’' | '-');
+        }
+    }
+    (words, cjk_chars)
+}
+
+pub fn note_stats(content: &str) -> NoteStats {
+    let (_, body) = frontmatter::split(content);
+    let (words, cjk_chars) = count_words(body);
+    NoteStats {
+        words: words + cjk_chars,
+        cjk_chars,
+        characters: body.chars().filter(|c| !c.is_whitespace()).count(),
+        headings: count_headings(&outline::outline(content)),
+        reading_minutes: reading_minutes(words, cjk_chars),
+    }
+}
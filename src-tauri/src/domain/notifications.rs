@@ -0,0 +1,129 @@
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+
+// Days a dismissed notification is kept before it is pruned
+pub const DISMISSED_RETENTION_DAYS: i64 = 30;
+
+// Which notifications the scheduler shows and when it holds them back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_true")]
+    pub reminders: bool,
+    #[serde(default = "default_true")]
+    pub overdue: bool, // Once per task and due date, the day after it passed
+    #[serde(default = "default_true")]
+    pub sound: bool,
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            reminders: true,
+            overdue: true,
+            sound: true,
+            quiet_hours: None,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+// Wall-clock window in the vault timezone, "HH:MM"; wraps past midnight when start is after end
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+impl QuietHours {
+    pub fn parse(&self) -> Option<(NaiveTime, NaiveTime)> {
+        let start = NaiveTime::parse_from_str(self.start.trim(), "%H:%M").ok()?;
+        let end = NaiveTime::parse_from_str(self.end.trim(), "%H:%M").ok()?;
+        Some((start, end))
+    }
+
+    // Unreadable times never hold anything back
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        match self.parse() {
+            Some((start, end)) if start <= end => start <= time && time < end,
+            Some((start, end)) => time >= start || time < end,
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationKind {
+    Reminder,
+    Overdue,
+}
+
+impl NotificationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationKind::Reminder => "reminder",
+            NotificationKind::Overdue => "overdue",
+        }
+    }
+}
+
+impl From<&str> for NotificationKind {
+    fn from(s: &str) -> Self {
+        match s {
+            "overdue" => NotificationKind::Overdue,
+            _ => NotificationKind::Reminder,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationState {
+    Delivered,
+    Snoozed,
+    Dismissed,
+}
+
+impl NotificationState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationState::Delivered => "delivered",
+            NotificationState::Snoozed => "snoozed",
+            NotificationState::Dismissed => "dismissed",
+        }
+    }
+}
+
+impl From<&str> for NotificationState {
+    fn from(s: &str) -> Self {
+        match s {
+            "snoozed" => NotificationState::Snoozed,
+            "dismissed" => NotificationState::Dismissed,
+            _ => NotificationState::Delivered,
+        }
+    }
+}
+
+// One delivered occurrence of a reminder or overdue task. The store is keyed by source and
+// occurrence, so an occurrence is delivered once however often the app restarts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: String,
+    pub kind: NotificationKind,
+    pub source_id: String,     // Reminder id, or task id for overdue
+    pub occurrence_at: String, // When the reminder was due (RFC3339 UTC), or the passed due date
+    pub task_id: String,
+    pub title: String,
+    pub state: NotificationState,
+    pub snoozed_until: Option<String>, // RFC3339 UTC while snoozed
+    pub delivered_at: String,          // Last time it was shown, including after a snooze
+    pub updated_at: String,
+}
@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::frontmatter;
+
+// One ATX heading and the section it opens; offsets are byte offsets into the whole note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeadingNode {
+    pub level: usize,
+    pub text: String,
+    pub start: usize,         // Start of the heading line
+    pub content_start: usize, // First byte after the heading line
+    pub end: usize,           // Next heading of the same or a higher level, or end of note
+    pub children: Vec<HeadingNode>,
+}
+
+// "## Title" -> (2, "Title"); closing #s are dropped
+pub fn parse_heading(line: &str) -> Option<(usize, String)> {
+    let trimmed = line.trim_end_matches(['\r', '\n']);
+    let indent = trimmed.len() - trimmed.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let trimmed = &trimmed[indent..];
+    let level = trimmed.len() - trimmed.trim_start_matches('#').len();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let text = rest.trim().trim_end_matches('#').trim_end();
+    Some((level, text.to_string()))
+}
+
+// Headings in document order, outside frontmatter and fenced code blocks
+fn flat_headings(content: &str) -> Vec<HeadingNode> {
+    let (_, body) = frontmatter::split(content);
+    let mut offset = content.len() - body.len();
+    let mut fence: Option<&str> = None;
+    let mut headings: Vec<HeadingNode> = Vec::new();
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let marker = ["```", "~~~"]
+            .into_iter()
+            .find(|marker| trimmed.starts_with(marker));
+        match (fence, marker) {
+            (Some(open), Some(marker)) if open == marker => fence = None,
+            (None, Some(marker)) => fence = Some(marker),
+            (None, None) => {
+                if let Some((level, text)) = parse_heading(line) {
+                    headings.push(HeadingNode {
+                        level,
+                        text,
+                        start: offset,
+                        content_start: offset + line.len(),
+                        end: content.len(),
+                        children: Vec::new(),
+                    });
+                }
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+
+    for index in 0..headings.len() {
+        let level = headings[index].level;
+        if let Some(next) = headings[index + 1..]
+            .iter()
+            .find(|heading| heading.level <= level)
+        {
+            headings[index].end = next.start;
+        }
+    }
+    headings
+}
+
+// Nest a flat heading list by level
+fn nest(
+    headings: &mut std::iter::Peekable<std::vec::IntoIter<HeadingNode>>,
+    level: usize,
+) -> Vec<HeadingNode> {
+    let mut nodes = Vec::new();
+    while let Some(heading) = headings.next_if(|heading| heading.level > level) {
+        let mut heading = heading;
+        heading.children = nest(headings, heading.level);
+        nodes.push(heading);
+    }
+    nodes
+}
+
+pub fn outline(content: &str) -> Vec<HeadingNode> {
+    let mut headings = flat_headings(content).into_iter().peekable();
+    nest(&mut headings, 0)
+}
+
+// "## Title" matches that level only; a bare "Title" matches any level
+fn heading_matches(heading: &HeadingNode, query: &str) -> bool {
+    match parse_heading(query) {
+        Some((level, text)) => heading.level == level && heading.text == text,
+        None => heading.text == query.trim(),
+    }
+}
+
+// Append text at the end of the first matching section's own content, replacing an empty "- " placeholder
+// item there; the heading is added at the end of the note when missing
+pub fn append_under_heading(content: &str, heading: &str, text: &str) -> String {
+    let text = text.trim_end_matches(['\r', '\n']);
+    let headings = flat_headings(content);
+    let Some(index) = headings
+        .iter()
+        .position(|node| heading_matches(node, heading))
+    else {
+        let heading = if parse_heading(heading).is_some() {
+            heading.trim().to_string()
+        } else {
+            format!("## {}", heading.trim())
+        };
+        let separator = match content {
+            "" => "",
+            content if content.ends_with("\n\n") => "",
+            content if content.ends_with('\n') => "\n",
+            _ => "\n\n",
+        };
+        return format!("{content}{separator}{heading}\n\n{text}\n");
+    };
+
+    // Text goes before the first subsection, not under it
+    let section = &headings[index];
+    let section_end = headings
+        .get(index + 1)
+        .map_or(content.len(), |next| next.start);
+
+    let mut result = content[..section.content_start].to_string();
+    if !result.ends_with('\n') {
+        result.push('\n');
+    }
+
+    // Existing section content minus trailing blank lines and a trailing "- " placeholder
+    let mut kept = content[section.content_start..section_end].trim_end();
+    if kept == "-" {
+        kept = "";
+    } else if let Some(rest) = kept.strip_suffix("\n-") {
+        kept = rest.trim_end();
+    }
+    if kept.trim().is_empty() {
+        result.push('\n');
+    } else {
+        result.push_str(kept);
+        result.push('\n');
+    }
+    result.push_str(text);
+    result.push('\n');
+
+    let after = &content[section_end..];
+    if !after.is_empty() {
+        result.push('\n');
+        result.push_str(after);
+    }
+    result
+}
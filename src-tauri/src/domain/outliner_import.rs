@@ -0,0 +1,313 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::planning::{CreateTaskInput, Task, TaskPriority, TaskStatus};
+
+// Journal file names: Logseq's default, ISO dates and org-journal's
+const JOURNAL_DAY_FORMATS: &[&str] = &["%Y_%m_%d", "%Y-%m-%d", "%Y%m%d"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutlinerFormat {
+    Logseq, // Markdown graph: journals/2024_05_01.md and pages/*.md
+    Org,
+}
+
+impl OutlinerFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutlinerFormat::Logseq => "md",
+            OutlinerFormat::Org => "org",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            OutlinerFormat::Logseq => "Logseq",
+            OutlinerFormat::Org => "org-mode",
+        }
+    }
+}
+
+// A TODO-style block or headline found in a source file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlinerItem {
+    pub file: String, // Relative to the imported directory
+    pub line: usize,  // 1-based
+    pub title: String,
+    pub status: TaskStatus,
+    pub priority: Option<TaskPriority>,
+    pub tags: Vec<String>,
+    pub due_date: Option<String>,  // Deadline, else the scheduled day
+    pub closed_on: Option<String>, // Day a done item was closed, when the source records it
+}
+
+// A journal page, imported as the daily note of its day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlinerJournal {
+    pub file: String,
+    pub day: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlinerImportResponse {
+    pub format: OutlinerFormat,
+    pub dry_run: bool,
+    pub files: usize,
+    pub items: Vec<OutlinerItem>,
+    pub journals: Vec<OutlinerJournal>,
+    pub canceled: usize,    // Canceled items, which are not imported
+    pub created: Vec<Task>, // Empty for a dry run; includes tasks of an earlier import
+    pub daily_notes_created: usize,
+    pub daily_notes_skipped: usize, // Days that already had a daily note
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParsedOutlinerFile {
+    pub items: Vec<OutlinerItem>,
+    pub canceled: usize,
+}
+
+// Day of a journal file, from its name
+pub fn journal_day(file_stem: &str) -> Option<String> {
+    JOURNAL_DAY_FORMATS.iter().find_map(|format| {
+        NaiveDate::parse_from_str(file_stem, format)
+            .ok()
+            .map(|day| day.format("%Y-%m-%d").to_string())
+    })
+}
+
+// Workflow keywords of both tools; Some(None) for canceled items
+fn keyword_status(keyword: &str) -> Option<Option<TaskStatus>> {
+    match keyword {
+        "TODO" | "LATER" | "NEXT" | "WAIT" | "WAITING" => Some(Some(TaskStatus::Todo)),
+        "DOING" | "NOW" | "STARTED" | "IN-PROGRESS" => Some(Some(TaskStatus::Doing)),
+        "DONE" => Some(Some(TaskStatus::Done)),
+        "CANCELED" | "CANCELLED" => Some(None),
+        _ => None,
+    }
+}
+
+fn cookie_priority(text: &str) -> Option<(TaskPriority, &str)> {
+    let rest = text.strip_prefix("[#")?;
+    let (letter, rest) = rest.split_once(']')?;
+    let priority = match letter {
+        "A" => TaskPriority::High,
+        "B" => TaskPriority::Medium,
+        "C" => TaskPriority::Low,
+        _ => return None,
+    };
+    Some((priority, rest.trim_start()))
+}
+
+// First date of a planning line such as "DEADLINE: <2024-05-01 Wed>" or "CLOSED: [2024-05-01 Wed 10:12]"
+fn planning_date(line: &str, marker: &str) -> Option<String> {
+    let (_, rest) = line.split_once(marker)?;
+    let start = rest.find(['<', '['])? + 1;
+    let day = rest.get(start..start + 10)?;
+    NaiveDate::parse_from_str(day, "%Y-%m-%d")
+        .ok()
+        .map(|day| day.format("%Y-%m-%d").to_string())
+}
+
+fn apply_planning_line(item: &mut OutlinerItem, line: &str) {
+    if let Some(day) = planning_date(line, "DEADLINE:") {
+        item.due_date = Some(day);
+    } else if let Some(day) = planning_date(line, "SCHEDULED:") {
+        item.due_date.get_or_insert(day);
+    }
+    if let Some(day) = planning_date(line, "CLOSED:") {
+        item.closed_on = Some(day);
+    }
+}
+
+// Keyword, priority cookie and the remaining text of a block or headline
+fn parse_headline(text: &str) -> Option<(Option<TaskStatus>, Option<TaskPriority>, &str)> {
+    let (keyword, rest) = text.split_once(' ').unwrap_or((text, ""));
+    let status = keyword_status(keyword)?;
+    let rest = rest.trim_start();
+    Some(match cookie_priority(rest) {
+        Some((priority, rest)) => (status, Some(priority), rest),
+        None => (status, None, rest),
+    })
+}
+
+// Logseq titles carry tags inline as #tag or #[[multi word]]; page links keep their text
+fn logseq_title_and_tags(text: &str) -> (String, Vec<String>) {
+    let mut tags = Vec::new();
+    let mut words = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("#[[") {
+            if let Some((tag, tail)) = after.split_once("]]") {
+                tags.push(tag.trim().to_string());
+                rest = tail;
+                continue;
+            }
+        }
+        let end = rest.find(' ').unwrap_or(rest.len());
+        let (word, tail) = rest.split_at(end);
+        match word.strip_prefix('#').filter(|tag| !tag.is_empty()) {
+            Some(tag) => tags.push(tag.to_string()),
+            None if !word.is_empty() => words.push(word.replace("[[", "").replace("]]", "")),
+            None => {}
+        }
+        rest = tail.trim_start();
+    }
+    (words.join(" "), tags)
+}
+
+// Org titles end with their tags, e.g. "Write report   :work:urgent:"
+fn org_title_and_tags(text: &str) -> (String, Vec<String>) {
+    let text = text.trim_end();
+    if let Some((title, last)) = text.rsplit_once(char::is_whitespace) {
+        if last.len() > 2 && last.starts_with(':') && last.ends_with(':') {
+            let tags = last
+                .trim_matches(':')
+                .split(':')
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_string)
+                .collect();
+            return (title.trim_end().to_string(), tags);
+        }
+    }
+    (text.to_string(), Vec::new())
+}
+
+fn push_item(
+    parsed: &mut ParsedOutlinerFile,
+    file: &str,
+    line: usize,
+    headline: (
+        Option<TaskStatus>,
+        Option<TaskPriority>,
+        String,
+        Vec<String>,
+    ),
+) -> bool {
+    let (status, priority, title, tags) = headline;
+    let Some(status) = status else {
+        parsed.canceled += 1;
+        return false;
+    };
+    if title.trim().is_empty() {
+        return false;
+    }
+    parsed.items.push(OutlinerItem {
+        file: file.to_string(),
+        line,
+        title: title.trim().to_string(),
+        status,
+        priority,
+        tags,
+        due_date: None,
+        closed_on: None,
+    });
+    true
+}
+
+// TODO blocks of a Logseq page; SCHEDULED and DEADLINE lines under a block apply to it
+pub fn parse_logseq(file: &str, text: &str) -> ParsedOutlinerFile {
+    let mut parsed = ParsedOutlinerFile::default();
+    let mut in_item = false;
+    for (index, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if let Some(block) = trimmed.strip_prefix("- ") {
+            in_item = match parse_headline(block.trim()) {
+                Some((status, priority, rest)) => {
+                    let (title, tags) = logseq_title_and_tags(rest);
+                    push_item(
+                        &mut parsed,
+                        file,
+                        index + 1,
+                        (status, priority, title, tags),
+                    )
+                }
+                None => false,
+            };
+        } else if in_item {
+            if let Some(item) = parsed.items.last_mut() {
+                apply_planning_line(item, trimmed);
+            }
+        }
+    }
+    parsed
+}
+
+// TODO headlines of an org file; planning lines under a headline apply to it
+pub fn parse_org(file: &str, text: &str) -> ParsedOutlinerFile {
+    let mut parsed = ParsedOutlinerFile::default();
+    let mut in_item = false;
+    for (index, line) in text.lines().enumerate() {
+        let stars = line.chars().take_while(|c| *c == '*').count();
+        if stars > 0 && line[stars..].starts_with(' ') {
+            in_item = match parse_headline(line[stars..].trim()) {
+                Some((status, priority, rest)) => {
+                    let (title, tags) = org_title_and_tags(rest);
+                    push_item(
+                        &mut parsed,
+                        file,
+                        index + 1,
+                        (status, priority, title, tags),
+                    )
+                }
+                None => false,
+            };
+        } else if in_item {
+            if let Some(item) = parsed.items.last_mut() {
+                apply_planning_line(item, line);
+            }
+        }
+    }
+    parsed
+}
+
+// Org headlines read as markdown headings in a daily note; deeper levels stop at ######
+pub fn org_to_markdown(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let stars = line.chars().take_while(|c| *c == '*').count();
+            if stars > 0 && line[stars..].starts_with(' ') {
+                format!("{}{}", "#".repeat(stars.min(6)), &line[stars..])
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Task for an item; todo and doing tasks without a date of their own fall due on `default_due`
+pub fn to_create_input(
+    item: &OutlinerItem,
+    default_due: &str,
+    provenance: String,
+    op_id: String,
+) -> CreateTaskInput {
+    let due_date = match item.status {
+        TaskStatus::Done => item.due_date.clone(),
+        _ => Some(
+            item.due_date
+                .clone()
+                .unwrap_or_else(|| default_due.to_string()),
+        ),
+    };
+    CreateTaskInput {
+        title: item.title.clone(),
+        description: Some(provenance),
+        status: item.status,
+        priority: item.priority,
+        due_date,
+        board_id: None,
+        estimate_min: None,
+        tags: None,
+        labels: (!item.tags.is_empty()).then(|| item.tags.clone()),
+        subtasks: None,
+        periodicity: None,
+        scheduled_start: None,
+        scheduled_end: None,
+        note_path: None,
+        color: None,
+        op_id: Some(op_id),
+    }
+}
@@ -0,0 +1,262 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::planning::{Task, TaskPeriodicity, TaskPriority, TaskStatus};
+use crate::domain::schedule;
+use crate::domain::timezone;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlainTextFormat {
+    Org,
+    Taskpaper,
+}
+
+impl PlainTextFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PlainTextFormat::Org => "org",
+            PlainTextFormat::Taskpaper => "taskpaper",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlainTextExportResponse {
+    pub format: PlainTextFormat,
+    pub path: String, // Vault-relative
+    pub board_count: usize,
+    pub task_count: usize,
+}
+
+// Tasks of one board, in board order
+#[derive(Debug, Clone)]
+pub struct BoardTasks {
+    pub board_id: String,
+    pub tasks: Vec<Task>,
+}
+
+// Tags and labels of a task as single words, which both formats require
+fn task_tags(task: &Task) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in task.tags.iter().chain(task.labels.iter()).flatten() {
+        let tag: String = tag
+            .trim()
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+fn completed_on(task: &Task, tz: Option<Tz>) -> Option<NaiveDateTime> {
+    if task.status != TaskStatus::Done {
+        return None;
+    }
+    timezone::to_local_naive(task.completed_at.as_deref()?, tz)
+}
+
+fn due_day(task: &Task, tz: Option<Tz>) -> Option<NaiveDate> {
+    timezone::to_local_date(task.due_date.as_deref()?, tz)
+}
+
+// Org has no workday repeater; such tasks export without one
+fn org_repeater(periodicity: Option<&TaskPeriodicity>) -> String {
+    let Some(periodicity) = periodicity else {
+        return String::new();
+    };
+    let unit = match periodicity.strategy.as_str() {
+        "day" => "d",
+        "week" => "w",
+        "month" => "m",
+        "year" => "y",
+        _ => return String::new(),
+    };
+    format!(" +{}{}", periodicity.interval.max(1), unit)
+}
+
+// Org's default priorities are A-C; urgent and high both map to A
+fn org_priority(priority: TaskPriority) -> char {
+    match priority {
+        TaskPriority::Urgent | TaskPriority::High => 'A',
+        TaskPriority::Medium => 'B',
+        TaskPriority::Low => 'C',
+    }
+}
+
+fn org_keyword(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Todo => "TODO",
+        TaskStatus::Doing => "DOING",
+        TaskStatus::Verify => "VERIFY",
+        TaskStatus::Done => "DONE",
+    }
+}
+
+// "SCHEDULED: <2024-05-01 Wed 09:00-10:30>" style planning line, or None without any dates
+fn org_planning_line(task: &Task, tz: Option<Tz>) -> Option<String> {
+    let repeater = org_repeater(task.periodicity.as_ref());
+    let scheduled = schedule::task_schedule_range(task, tz);
+    let mut parts = Vec::new();
+    if let Some(closed) = completed_on(task, tz) {
+        parts.push(format!("CLOSED: [{}]", closed.format("%Y-%m-%d %a %H:%M")));
+    }
+    if let Some((start, end)) = scheduled {
+        let stamp = if start.date() == end.date() {
+            format!(
+                "<{}-{}{}>",
+                start.format("%Y-%m-%d %a %H:%M"),
+                end.format("%H:%M"),
+                repeater
+            )
+        } else {
+            format!(
+                "<{}{}>--<{}>",
+                start.format("%Y-%m-%d %a %H:%M"),
+                repeater,
+                end.format("%Y-%m-%d %a %H:%M")
+            )
+        };
+        parts.push(format!("SCHEDULED: {}", stamp));
+    }
+    if let Some(due) = due_day(task, tz) {
+        // The repeater goes on the scheduled stamp when there is one
+        let repeater = if scheduled.is_some() {
+            ""
+        } else {
+            repeater.as_str()
+        };
+        parts.push(format!(
+            "DEADLINE: <{}{}>",
+            due.format("%Y-%m-%d %a"),
+            repeater
+        ));
+    }
+    (!parts.is_empty()).then(|| parts.join(" "))
+}
+
+// Boards become top-level headlines, tasks second-level TODO headlines and subtasks
+// third-level ones. Task ids are kept in a property drawer.
+pub fn render_org(boards: &[BoardTasks], exported_at: &str, tz: Option<Tz>) -> String {
+    let mut out = format!(
+        "#+TITLE: Planning\n#+DATE: {}\n#+TODO: TODO DOING VERIFY | DONE\n",
+        exported_at
+    );
+
+    for board in boards {
+        out.push_str(&format!("\n* {}\n", board.board_id));
+        for task in &board.tasks {
+            let mut headline = format!("** {}", org_keyword(task.status));
+            if let Some(priority) = task.priority {
+                headline.push_str(&format!(" [#{}]", org_priority(priority)));
+            }
+            headline.push(' ');
+            headline.push_str(&task.title);
+            let tags = task_tags(task);
+            if !tags.is_empty() {
+                headline.push_str(&format!(" :{}:", tags.join(":")));
+            }
+            out.push_str(&headline);
+            out.push('\n');
+
+            if let Some(planning) = org_planning_line(task, tz) {
+                out.push_str(&format!("   {}\n", planning));
+            }
+            out.push_str("   :PROPERTIES:\n");
+            out.push_str(&format!("   :ID: {}\n", task.id));
+            if let Some(estimate_min) = task.estimate_min {
+                out.push_str(&format!(
+                    "   :EFFORT: {}:{:02}\n",
+                    estimate_min / 60,
+                    estimate_min % 60
+                ));
+            }
+            out.push_str("   :END:\n");
+
+            for subtask in task.subtasks.iter().flatten() {
+                let keyword = if subtask.completed { "DONE" } else { "TODO" };
+                out.push_str(&format!("*** {} {}\n", keyword, subtask.title));
+            }
+        }
+    }
+
+    out
+}
+
+fn taskpaper_repeat(periodicity: Option<&TaskPeriodicity>) -> Option<String> {
+    let periodicity = periodicity?;
+    Some(match periodicity.interval {
+        interval if interval > 1 => format!("{} {}", interval, periodicity.strategy),
+        _ => periodicity.strategy.clone(),
+    })
+}
+
+// Boards become projects, tasks "- " items with @tags for their status and dates, and
+// subtasks items indented under their task
+pub fn render_taskpaper(boards: &[BoardTasks], tz: Option<Tz>) -> String {
+    let mut out = String::new();
+
+    for board in boards {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("{}:\n", board.board_id));
+        for task in &board.tasks {
+            let mut line = format!("\t- {}", task.title);
+            match task.status {
+                TaskStatus::Doing => line.push_str(" @doing"),
+                TaskStatus::Verify => line.push_str(" @verify"),
+                _ => {}
+            }
+            if let Some(priority) = task.priority {
+                line.push_str(&format!(" @priority({})", priority));
+            }
+            if let Some((start, end)) = schedule::task_schedule_range(task, tz) {
+                line.push_str(&format!(
+                    " @start({}) @end({})",
+                    start.format("%Y-%m-%d %H:%M"),
+                    end.format("%Y-%m-%d %H:%M")
+                ));
+            }
+            if let Some(due) = due_day(task, tz) {
+                line.push_str(&format!(" @due({})", due.format("%Y-%m-%d")));
+            }
+            if let Some(repeat) = taskpaper_repeat(task.periodicity.as_ref()) {
+                line.push_str(&format!(" @repeat({})", repeat));
+            }
+            if let Some(estimate_min) = task.estimate_min {
+                line.push_str(&format!(" @estimate({}m)", estimate_min));
+            }
+            for tag in task_tags(task) {
+                line.push_str(&format!(" @{}", tag));
+            }
+            if task.status == TaskStatus::Done {
+                match completed_on(task, tz) {
+                    Some(closed) => {
+                        line.push_str(&format!(" @done({})", closed.format("%Y-%m-%d %H:%M")))
+                    }
+                    None => line.push_str(" @done"),
+                }
+            }
+            out.push_str(&line);
+            out.push('\n');
+
+            for subtask in task.subtasks.iter().flatten() {
+                let done = if subtask.completed { " @done" } else { "" };
+                out.push_str(&format!("\t\t- {}{}\n", subtask.title, done));
+            }
+        }
+    }
+
+    out
+}
@@ -1,6 +1,11 @@
+use chrono::Weekday;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
+use crate::domain::board::{BoardKanban, KanbanStats};
+use crate::ipc::ApiError;
+use crate::paths::VaultLayout;
+
 // Subtask model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Subtask {
@@ -12,12 +17,24 @@ pub struct Subtask {
 // Task periodicity model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskPeriodicity {
-    pub strategy: String, // "day", "week", "month", "year"
+    pub strategy: String, // "day", "week", "workday", "month", "year"
     pub interval: i32,
     pub start_date: String,
     pub end_rule: String, // "never", "date", "count"
     pub end_date: Option<String>,
     pub end_count: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekdays: Option<Vec<Weekday>>, // "week" only: recur on these days instead of the start weekday
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monthly: Option<MonthlyPattern>, // "month" only: replaces the start day of month
+}
+
+// Day-of-month pattern for monthly recurrences
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MonthlyPattern {
+    LastDay,
+    NthWeekday { nth: i8, weekday: Weekday }, // nth 1..=5, or -1 for the last one
 }
 
 // Task priority enum
@@ -120,15 +137,22 @@ pub struct Task {
     pub scheduled_end: Option<String>,
     pub due_date: Option<String>,
     pub board_id: Option<String>,
+    pub column_key: Option<String>, // Custom kanban column; None falls back to the status column
+    pub color: Option<String>,      // Card accent, "#rrggbb"
     pub note_path: Option<String>,
     pub task_dir_slug: Option<String>, // Directory slug for task folder
     pub md_rel_path: Option<String>,   // Relative path to markdown file
+    pub cover_image: Option<String>,   // First image in the task note, for kanban cards
+    pub excerpt: Option<String>,       // Opening text of the task note
     pub created_at: String,
     pub updated_at: String,
     pub completed_at: Option<String>,
     pub archived: i32,
 }
 
+// Timer source for entries recorded after the fact
+pub const TIMER_SOURCE_MANUAL_ENTRY: &str = "manual_entry";
+
 // Timer model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Timer {
@@ -140,6 +164,104 @@ pub struct Timer {
     pub source: String,
 }
 
+// Open timer left behind by a previous run of the app
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DanglingTimer {
+    pub timer: Timer,
+    pub task: Option<Task>,
+    pub last_activity_at: String, // Close time used when the user chooses not to resume
+}
+
+// How to resolve a dangling timer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimerRecoveryAction {
+    Resume,
+    Close,
+}
+
+// Timer recovery response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimerRecoveryResult {
+    pub dangling: Option<DanglingTimer>,
+    pub resolved: Option<TimerRecoveryAction>,
+}
+
+// Vault layout migration result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutMigrationResult {
+    pub layout: VaultLayout,
+    pub moved_dirs: Vec<String>,
+    pub renamed_notes: usize,
+    pub updated_tasks: usize,
+}
+
+// A task moved off a slug it shared with an older task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlugReassignment {
+    pub task_id: String,
+    pub old_slug: String,
+    pub new_slug: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlugRepairResult {
+    pub reassigned: Vec<SlugReassignment>,
+}
+
+// Task notes moved into the vault layout by a consolidation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskNoteMove {
+    pub task_id: String,
+    pub from: String,
+    pub to: String,
+}
+
+// A note a consolidation left where it is
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskNoteConflict {
+    pub task_id: Option<String>, // None for legacy notes of no known task
+    pub path: String,
+    pub reason: String,
+}
+
+// Task note consolidation result; a dry run reports what would happen without touching anything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskNoteConsolidation {
+    pub dry_run: bool,
+    pub moved: Vec<TaskNoteMove>,
+    pub deduplicated: Vec<String>, // Copies identical to the note in place, removed
+    pub conflicts: Vec<TaskNoteConflict>,
+    pub updated_tasks: usize,
+}
+
+// When the frontmatter of task notes follows changes to the database
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MdSyncPolicy {
+    #[default]
+    Eager, // On every change
+    Lazy, // Journaled and written by a background flush, once per task
+    Off,  // The database is the only store; notes keep the frontmatter they were created with
+}
+
+// Startup health check result; each step is best effort and failures are listed in errors
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HealthCheckReport {
+    pub checkpointed: bool,
+    pub replayed_md_syncs: usize, // Task notes written from the md sync journal
+    pub removed_temp_files: Vec<String>,
+    pub removed_task_dirs: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+// Encryption state of the planning database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbEncryptionStatus {
+    pub encrypted: bool,
+    pub unlocked: bool,
+}
+
 // Day log model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DayLog {
@@ -162,6 +284,8 @@ pub struct KanbanTasks {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TodayDTO {
     pub kanban: KanbanTasks,
+    pub boards: Vec<BoardKanban>, // Kanban grouped by each board's column registry
+    pub stats: KanbanStats,
     pub timeline: Vec<Task>,
     pub current_doing: Option<Task>,
     pub current_timer: Option<Timer>,
@@ -169,6 +293,22 @@ pub struct TodayDTO {
     pub server_now: String,
 }
 
+// Minimal open task with a due date, for the tray, notifications and widgets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskDueRef {
+    pub id: String,
+    pub title: String,
+    pub due_date: String,
+    pub priority: Option<TaskPriority>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskDueBrief {
+    #[serde(flatten)]
+    pub task: TaskDueRef,
+    pub days_until_due: i64, // Negative once overdue
+}
+
 // Task creation input
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateTaskInput {
@@ -186,6 +326,23 @@ pub struct CreateTaskInput {
     pub scheduled_start: Option<String>,
     pub scheduled_end: Option<String>,
     pub note_path: Option<String>,
+    pub color: Option<String>,
+    pub op_id: Option<String>, // Client-generated idempotency key
+}
+
+// Result of one input of a batch create, in input order
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchCreateItem {
+    pub index: usize,
+    pub task: Option<Task>, // None when the batch was rolled back
+    pub error: Option<ApiError>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchCreateResponse {
+    pub committed: bool, // False when an input failed and nothing was kept
+    pub created: Vec<Task>,
+    pub items: Vec<BatchCreateItem>,
 }
 
 // Task update input
@@ -202,12 +359,16 @@ pub struct UpdateTaskInput {
     pub periodicity: Option<TaskPeriodicity>,
     pub due_date: Option<Option<String>>,
     pub board_id: Option<String>,
+    pub color: Option<Option<String>>, // An empty string clears the color
     pub order_index: Option<i64>,
     pub estimate_min: Option<i64>,
     pub scheduled_start: Option<String>,
     pub scheduled_end: Option<String>,
     pub note_path: Option<String>,
     pub archived: Option<i32>,
+    pub op_id: Option<String>, // Client-generated idempotency key
+    pub override_wip_limit: Option<bool>, // Allow exceeding the target column's WIP limit
+    pub rename_slug: Option<bool>, // Rename the task directory after a title change
 }
 
 // Batch task reorder input
@@ -216,6 +377,7 @@ pub struct ReorderTaskInput {
     pub id: String,
     pub status: Option<TaskStatus>,
     pub order_index: i64,
+    pub column_key: Option<String>, // Target custom column; overrides status with its mapped status
 }
 
 // Open daily log input
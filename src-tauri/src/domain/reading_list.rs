@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::planning::Task;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadingStatus {
+    Unread,
+    Read,
+}
+
+impl ReadingStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReadingStatus::Unread => "unread",
+            ReadingStatus::Read => "read",
+        }
+    }
+}
+
+impl From<&str> for ReadingStatus {
+    fn from(s: &str) -> Self {
+        match s {
+            "read" => ReadingStatus::Read,
+            _ => ReadingStatus::Unread,
+        }
+    }
+}
+
+// A page saved to read later, usually from a link opened in an embedded webview
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadingItem {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub status: ReadingStatus,
+    pub added_at: String,
+    pub read_at: Option<String>,
+    pub note_path: Option<String>, // Clipping note saved from the page
+    pub task_id: Option<String>,   // Task created from the item
+    pub source_label: Option<String>, // Webview the link was opened from
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadingItemTaskResponse {
+    pub item: ReadingItem,
+    pub task: Task,
+}
+
+// Title shown until the page's own title is known: the host and path of the URL
+pub fn fallback_title(url: &str) -> String {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let without_query = without_scheme
+        .split(['?', '#'])
+        .next()
+        .unwrap_or(without_scheme)
+        .trim_end_matches('/');
+    without_query
+        .strip_prefix("www.")
+        .unwrap_or(without_query)
+        .to_string()
+}
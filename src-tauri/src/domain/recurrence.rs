@@ -0,0 +1,584 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use chrono_tz::Tz;
+
+use crate::domain::planning::{MonthlyPattern, TaskPeriodicity};
+use crate::domain::timezone;
+use crate::domain::week::WeekConfig;
+use crate::ipc::{ApiError, ErrorCode};
+
+// Upper bound on how far a series is walked when it has no end
+const MAX_SERIES_DAYS: i64 = 366 * 100;
+// Most occurrences a single preview may return
+pub const MAX_PREVIEW_OCCURRENCES: usize = 366;
+
+// First day and time of day of a series in vault wall-clock time.
+// Instants are anchored in the vault timezone so travel does not shift the series.
+pub fn series_start(periodicity: &TaskPeriodicity, tz: Option<Tz>) -> Option<(NaiveDate, String)> {
+    if let Some(ndt) = timezone::to_local_naive(&periodicity.start_date, tz) {
+        return Some((ndt.date(), ndt.format("%H:%M:%S").to_string()));
+    }
+    NaiveDate::parse_from_str(&periodicity.start_date, "%Y-%m-%d")
+        .ok()
+        .map(|date| (date, "00:00:00".to_string()))
+}
+
+fn end_date(periodicity: &TaskPeriodicity) -> Option<NaiveDate> {
+    if periodicity.end_rule != "date" {
+        return None;
+    }
+    periodicity
+        .end_date
+        .as_deref()
+        .and_then(|value| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok())
+}
+
+fn end_count(periodicity: &TaskPeriodicity) -> Option<usize> {
+    if periodicity.end_rule != "count" {
+        return None;
+    }
+    periodicity.end_count.map(|count| count.max(0) as usize)
+}
+
+fn invalid(message: &str) -> ApiError {
+    ApiError {
+        code: ErrorCode::InvalidInput,
+        message: message.to_string(),
+        details: None,
+    }
+}
+
+// Reject pattern fields that do not fit the strategy or cannot match any day
+pub fn validate(periodicity: &TaskPeriodicity) -> Result<(), ApiError> {
+    if let Some(weekdays) = &periodicity.weekdays {
+        if periodicity.strategy != "week" {
+            return Err(invalid("weekdays is only allowed for weekly recurrences"));
+        }
+        if weekdays.is_empty() {
+            return Err(invalid("weekdays must not be empty"));
+        }
+    }
+    if let Some(pattern) = &periodicity.monthly {
+        if periodicity.strategy != "month" {
+            return Err(invalid("monthly is only allowed for monthly recurrences"));
+        }
+        if let MonthlyPattern::NthWeekday { nth, .. } = pattern {
+            if !(1..=5).contains(nth) && *nth != -1 {
+                return Err(invalid(
+                    "nth must be between 1 and 5, or -1 for the last weekday",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_last_day_of_month(date: NaiveDate) -> bool {
+    date.succ_opt()
+        .is_none_or(|next| next.month() != date.month())
+}
+
+fn matches_monthly(pattern: &MonthlyPattern, date: NaiveDate) -> bool {
+    match pattern {
+        MonthlyPattern::LastDay => is_last_day_of_month(date),
+        MonthlyPattern::NthWeekday { nth, weekday } => {
+            if date.weekday() != *weekday {
+                return false;
+            }
+            if *nth == -1 {
+                return (date + chrono::Duration::days(7)).month() != date.month();
+            }
+            (date.day0() / 7 + 1) as i8 == *nth
+        }
+    }
+}
+
+// Whether the pattern lands on `date`, ignoring end rules
+fn matches_pattern(
+    periodicity: &TaskPeriodicity,
+    start_date: NaiveDate,
+    date: NaiveDate,
+    week: &WeekConfig,
+) -> bool {
+    if date < start_date {
+        return false;
+    }
+
+    let days = date.signed_duration_since(start_date).num_days();
+    let interval = periodicity.interval.max(1) as i64;
+
+    match periodicity.strategy.as_str() {
+        "day" => days % interval == 0,
+        "week" => {
+            let on_weekday = match &periodicity.weekdays {
+                Some(weekdays) => weekdays.contains(&date.weekday()),
+                None => date.weekday() == start_date.weekday(),
+            };
+            on_weekday && week.weeks_between(start_date, date) % interval == 0
+        }
+        "workday" => week.is_workday(date) && week.weeks_between(start_date, date) % interval == 0,
+        "month" => {
+            let total_months = (date.year() - start_date.year()) * 12 + date.month() as i32
+                - start_date.month() as i32;
+            let on_day = match &periodicity.monthly {
+                Some(pattern) => matches_monthly(pattern, date),
+                None => date.day() == start_date.day(),
+            };
+            on_day && total_months % (interval as i32) == 0
+        }
+        "year" => {
+            date.day() == start_date.day()
+                && date.month() == start_date.month()
+                && (date.year() - start_date.year()) % (interval as i32) == 0
+        }
+        _ => false,
+    }
+}
+
+// Every occurrence date of a series in order, honouring both end rules
+pub fn occurrences<'a>(
+    periodicity: &'a TaskPeriodicity,
+    tz: Option<Tz>,
+    week: &'a WeekConfig,
+) -> impl Iterator<Item = NaiveDate> + 'a {
+    let start_date = series_start(periodicity, tz).map(|(date, _)| date);
+    let last_date = start_date.map(|start| {
+        let horizon = start + chrono::Duration::days(MAX_SERIES_DAYS);
+        end_date(periodicity).map_or(horizon, |end| end.min(horizon))
+    });
+
+    start_date
+        .into_iter()
+        .flat_map(|start| start.iter_days())
+        .take_while(move |date| last_date.is_some_and(|last| *date <= last))
+        .filter(move |date| {
+            start_date.is_some_and(|start| matches_pattern(periodicity, start, *date, week))
+        })
+        .take(end_count(periodicity).unwrap_or(usize::MAX))
+}
+
+// A series resolved once for repeated day lookups, such as expanding it over a calendar range
+pub struct Series<'a> {
+    periodicity: &'a TaskPeriodicity,
+    week: &'a WeekConfig,
+    start_date: NaiveDate,
+    start_time: String,
+    last_date: Option<NaiveDate>, // Last day either end rule allows
+}
+
+impl<'a> Series<'a> {
+    pub fn new(
+        periodicity: &'a TaskPeriodicity,
+        tz: Option<Tz>,
+        week: &'a WeekConfig,
+    ) -> Option<Self> {
+        let (start_date, start_time) = series_start(periodicity, tz)?;
+        // A count-terminated series ends on its last counted occurrence, found with one walk
+        let last_date = match end_count(periodicity) {
+            Some(_) => Some(occurrences(periodicity, tz, week).last()?),
+            None => end_date(periodicity),
+        };
+        Some(Self {
+            periodicity,
+            week,
+            start_date,
+            start_time,
+            last_date,
+        })
+    }
+
+    // Start timestamp of the occurrence on `date`, None if the series does not recur that day
+    pub fn occurrence_on(&self, date: NaiveDate) -> Option<String> {
+        if self.last_date.is_some_and(|last| date > last)
+            || !matches_pattern(self.periodicity, self.start_date, date, self.week)
+        {
+            return None;
+        }
+        Some(format!("{}T{}", date.format("%Y-%m-%d"), self.start_time))
+    }
+}
+
+// Start timestamp of the series occurrence on `date`, None if it does not recur that day
+pub fn occurrence_on(
+    periodicity: &TaskPeriodicity,
+    date: NaiveDate,
+    tz: Option<Tz>,
+    week: &WeekConfig,
+) -> Option<String> {
+    Series::new(periodicity, tz, week)?.occurrence_on(date)
+}
+
+const RRULE_WEEKDAYS: [(Weekday, &str); 7] = [
+    (Weekday::Mon, "MO"),
+    (Weekday::Tue, "TU"),
+    (Weekday::Wed, "WE"),
+    (Weekday::Thu, "TH"),
+    (Weekday::Fri, "FR"),
+    (Weekday::Sat, "SA"),
+    (Weekday::Sun, "SU"),
+];
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    RRULE_WEEKDAYS
+        .iter()
+        .find(|(day, _)| *day == weekday)
+        .map(|(_, code)| *code)
+        .unwrap_or("MO")
+}
+
+fn parse_weekday_code(code: &str) -> Result<Weekday, ApiError> {
+    RRULE_WEEKDAYS
+        .iter()
+        .find(|(_, candidate)| *candidate == code)
+        .map(|(day, _)| *day)
+        .ok_or_else(|| invalid(&format!("Unknown RRULE weekday: {}", code)))
+}
+
+fn weekday_list(weekdays: &[Weekday]) -> String {
+    weekdays
+        .iter()
+        .map(|day| weekday_code(*day))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// RFC 5545 RRULE for a periodicity; DTSTART is carried separately by start_date
+pub fn to_rrule(
+    periodicity: &TaskPeriodicity,
+    tz: Option<Tz>,
+    week: &WeekConfig,
+) -> Result<String, ApiError> {
+    validate(periodicity)?;
+    let (start_date, _) = series_start(periodicity, tz)
+        .ok_or_else(|| invalid("start_date is not a valid date or time"))?;
+
+    let mut parts = Vec::new();
+    match periodicity.strategy.as_str() {
+        "day" => parts.push("FREQ=DAILY".to_string()),
+        "week" | "workday" => {
+            parts.push("FREQ=WEEKLY".to_string());
+            let weekdays = if periodicity.strategy == "workday" {
+                week.workdays.clone()
+            } else {
+                periodicity
+                    .weekdays
+                    .clone()
+                    .unwrap_or_else(|| vec![start_date.weekday()])
+            };
+            parts.push(format!("BYDAY={}", weekday_list(&weekdays)));
+            parts.push(format!("WKST={}", weekday_code(week.first_day)));
+        }
+        "month" => {
+            parts.push("FREQ=MONTHLY".to_string());
+            parts.push(match &periodicity.monthly {
+                Some(MonthlyPattern::LastDay) => "BYMONTHDAY=-1".to_string(),
+                Some(MonthlyPattern::NthWeekday { nth, weekday }) => {
+                    format!("BYDAY={}{}", nth, weekday_code(*weekday))
+                }
+                None => format!("BYMONTHDAY={}", start_date.day()),
+            });
+        }
+        "year" => {
+            parts.push("FREQ=YEARLY".to_string());
+            parts.push(format!("BYMONTH={}", start_date.month()));
+            parts.push(format!("BYMONTHDAY={}", start_date.day()));
+        }
+        other => return Err(invalid(&format!("Unknown recurrence strategy: {}", other))),
+    }
+    if periodicity.interval > 1 {
+        parts.push(format!("INTERVAL={}", periodicity.interval));
+    }
+    if let Some(count) = end_count(periodicity) {
+        parts.push(format!("COUNT={}", count));
+    }
+    if let Some(end) = end_date(periodicity) {
+        parts.push(format!("UNTIL={}", end.format("%Y%m%d")));
+    }
+
+    Ok(format!("RRULE:{}", parts.join(";")))
+}
+
+fn parse_rrule_number<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, ApiError> {
+    value
+        .parse()
+        .map_err(|_| invalid(&format!("Invalid RRULE {} value: {}", key, value)))
+}
+
+// Periodicity for an RFC 5545 RRULE anchored at start_date.
+// Only rules the recurrence model can reproduce exactly are accepted.
+pub fn from_rrule(
+    rrule: &str,
+    start_date: &str,
+    tz: Option<Tz>,
+    week: &WeekConfig,
+) -> Result<TaskPeriodicity, ApiError> {
+    let body = rrule.trim();
+    let body = body.strip_prefix("RRULE:").unwrap_or(body);
+
+    let mut freq = None;
+    let mut interval = 1;
+    let mut count = None;
+    let mut until = None;
+    let mut by_day = None;
+    let mut by_month_day = None;
+    let mut by_month = None;
+    for part in body.split(';').filter(|part| !part.is_empty()) {
+        let (key, value) = part
+            .split_once('=')
+            .ok_or_else(|| invalid(&format!("Malformed RRULE part: {}", part)))?;
+        let value = value.trim().to_uppercase();
+        match key.trim().to_uppercase().as_str() {
+            "FREQ" => freq = Some(value),
+            "INTERVAL" => interval = parse_rrule_number::<i32>("INTERVAL", &value)?.max(1),
+            "COUNT" => count = Some(parse_rrule_number::<i32>("COUNT", &value)?),
+            "UNTIL" => {
+                let date_part = value.get(..8).unwrap_or(&value);
+                let date = NaiveDate::parse_from_str(date_part, "%Y%m%d")
+                    .map_err(|_| invalid(&format!("Invalid RRULE UNTIL value: {}", value)))?;
+                until = Some(date.format("%Y-%m-%d").to_string());
+            }
+            "BYDAY" => by_day = Some(value),
+            "BYMONTHDAY" => by_month_day = Some(parse_rrule_number::<i32>("BYMONTHDAY", &value)?),
+            "BYMONTH" => by_month = Some(parse_rrule_number::<u32>("BYMONTH", &value)?),
+            // Week start only affects which weeks an interval counts, which the vault week config decides
+            "WKST" => {}
+            other => return Err(invalid(&format!("Unsupported RRULE part: {}", other))),
+        }
+    }
+    if count.is_some() && until.is_some() {
+        return Err(invalid("RRULE must not combine COUNT and UNTIL"));
+    }
+
+    let mut periodicity = TaskPeriodicity {
+        strategy: String::new(),
+        interval,
+        start_date: start_date.to_string(),
+        end_rule: if count.is_some() {
+            "count".to_string()
+        } else if until.is_some() {
+            "date".to_string()
+        } else {
+            "never".to_string()
+        },
+        end_date: until,
+        end_count: count,
+        weekdays: None,
+        monthly: None,
+    };
+    let (start, _) = series_start(&periodicity, tz)
+        .ok_or_else(|| invalid("start_date is not a valid date or time"))?;
+
+    let freq = freq.ok_or_else(|| invalid("RRULE is missing FREQ"))?;
+    let unsupported =
+        |part: &str| invalid(&format!("{} is not supported with FREQ={}", part, freq));
+    match freq.as_str() {
+        "DAILY" => {
+            if by_day.is_some() || by_month_day.is_some() || by_month.is_some() {
+                return Err(unsupported("BYDAY/BYMONTHDAY/BYMONTH"));
+            }
+            periodicity.strategy = "day".to_string();
+        }
+        "WEEKLY" => {
+            if by_month_day.is_some() || by_month.is_some() {
+                return Err(unsupported("BYMONTHDAY/BYMONTH"));
+            }
+            let weekdays = match &by_day {
+                Some(list) => list
+                    .split(',')
+                    .map(|code| parse_weekday_code(code.trim()))
+                    .collect::<Result<Vec<_>, _>>()?,
+                None => vec![start.weekday()],
+            };
+            let mut sorted = weekdays.clone();
+            sorted.sort_by_key(|day| day.num_days_from_monday());
+            let mut workdays = week.workdays.clone();
+            workdays.sort_by_key(|day| day.num_days_from_monday());
+
+            if sorted == workdays {
+                periodicity.strategy = "workday".to_string();
+            } else {
+                periodicity.strategy = "week".to_string();
+                if sorted != [start.weekday()] {
+                    periodicity.weekdays = Some(sorted);
+                }
+            }
+        }
+        "MONTHLY" => {
+            if by_month.is_some() {
+                return Err(unsupported("BYMONTH"));
+            }
+            periodicity.strategy = "month".to_string();
+            periodicity.monthly = match (&by_day, by_month_day) {
+                (Some(_), Some(_)) => {
+                    return Err(invalid("RRULE must not combine BYDAY and BYMONTHDAY"))
+                }
+                (Some(code), None) => {
+                    let split = code.len().saturating_sub(2);
+                    let (nth, day) = code.split_at(split);
+                    Some(MonthlyPattern::NthWeekday {
+                        nth: parse_rrule_number("BYDAY", nth)?,
+                        weekday: parse_weekday_code(day)?,
+                    })
+                }
+                (None, Some(-1)) => Some(MonthlyPattern::LastDay),
+                (None, Some(day)) if day == start.day() as i32 => None,
+                (None, Some(_)) => {
+                    return Err(invalid("BYMONTHDAY must match the day of start_date"))
+                }
+                (None, None) => None,
+            };
+        }
+        "YEARLY" => {
+            if by_day.is_some() {
+                return Err(unsupported("BYDAY"));
+            }
+            if by_month.is_some_and(|month| month != start.month())
+                || by_month_day.is_some_and(|day| day != start.day() as i32)
+            {
+                return Err(invalid("BYMONTH/BYMONTHDAY must match start_date"));
+            }
+            periodicity.strategy = "year".to_string();
+        }
+        other => return Err(invalid(&format!("Unsupported RRULE FREQ: {}", other))),
+    }
+
+    validate(&periodicity)?;
+    Ok(periodicity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-05-06 is a Monday
+    fn periodicity(strategy: &str) -> TaskPeriodicity {
+        TaskPeriodicity {
+            strategy: strategy.to_string(),
+            interval: 1,
+            start_date: "2024-05-06".to_string(),
+            end_rule: "never".to_string(),
+            end_date: None,
+            end_count: None,
+            weekdays: None,
+            monthly: None,
+        }
+    }
+
+    fn round_trip(periodicity: &TaskPeriodicity) -> (String, TaskPeriodicity) {
+        let week = WeekConfig::default();
+        let rrule = to_rrule(periodicity, None, &week).unwrap();
+        let parsed = from_rrule(&rrule, &periodicity.start_date, None, &week).unwrap();
+        (rrule, parsed)
+    }
+
+    #[test]
+    fn workday_rule_round_trips_through_weekly_byday() {
+        let (rrule, parsed) = round_trip(&periodicity("workday"));
+        assert_eq!(rrule, "RRULE:FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR;WKST=MO");
+        assert_eq!(parsed.strategy, "workday");
+        assert_eq!(parsed.weekdays, None);
+    }
+
+    #[test]
+    fn weekly_rule_listing_the_workdays_in_any_order_is_a_workday_rule() {
+        let week = WeekConfig::default();
+        let parsed = from_rrule(
+            "FREQ=WEEKLY;BYDAY=FR,MO,TH,TU,WE",
+            "2024-05-06",
+            None,
+            &week,
+        )
+        .unwrap();
+        assert_eq!(parsed.strategy, "workday");
+
+        let parsed = from_rrule("FREQ=WEEKLY;BYDAY=WE,MO", "2024-05-06", None, &week).unwrap();
+        assert_eq!(parsed.strategy, "week");
+        assert_eq!(parsed.weekdays, Some(vec![Weekday::Mon, Weekday::Wed]));
+    }
+
+    #[test]
+    fn last_day_of_month_round_trips_as_bymonthday_minus_one() {
+        let mut monthly = periodicity("month");
+        monthly.monthly = Some(MonthlyPattern::LastDay);
+        let (rrule, parsed) = round_trip(&monthly);
+        assert_eq!(rrule, "RRULE:FREQ=MONTHLY;BYMONTHDAY=-1");
+        assert_eq!(parsed.strategy, "month");
+        assert_eq!(parsed.monthly, Some(MonthlyPattern::LastDay));
+    }
+
+    #[test]
+    fn nth_weekday_round_trips_as_ordinal_byday() {
+        let mut monthly = periodicity("month");
+        monthly.monthly = Some(MonthlyPattern::NthWeekday {
+            nth: 2,
+            weekday: Weekday::Mon,
+        });
+        let (rrule, parsed) = round_trip(&monthly);
+        assert_eq!(rrule, "RRULE:FREQ=MONTHLY;BYDAY=2MO");
+        assert_eq!(parsed.monthly, monthly.monthly);
+
+        monthly.monthly = Some(MonthlyPattern::NthWeekday {
+            nth: -1,
+            weekday: Weekday::Fri,
+        });
+        let (rrule, parsed) = round_trip(&monthly);
+        assert_eq!(rrule, "RRULE:FREQ=MONTHLY;BYDAY=-1FR");
+        assert_eq!(parsed.monthly, monthly.monthly);
+    }
+
+    #[test]
+    fn count_and_until_round_trip_as_end_rules() {
+        let mut counted = periodicity("day");
+        counted.interval = 2;
+        counted.end_rule = "count".to_string();
+        counted.end_count = Some(5);
+        let (rrule, parsed) = round_trip(&counted);
+        assert_eq!(rrule, "RRULE:FREQ=DAILY;INTERVAL=2;COUNT=5");
+        assert_eq!(parsed.interval, 2);
+        assert_eq!(parsed.end_rule, "count");
+        assert_eq!(parsed.end_count, Some(5));
+
+        let mut until = periodicity("day");
+        until.end_rule = "date".to_string();
+        until.end_date = Some("2024-06-30".to_string());
+        let (rrule, parsed) = round_trip(&until);
+        assert_eq!(rrule, "RRULE:FREQ=DAILY;UNTIL=20240630");
+        assert_eq!(parsed.end_rule, "date");
+        assert_eq!(parsed.end_date.as_deref(), Some("2024-06-30"));
+    }
+
+    #[test]
+    fn until_with_a_time_keeps_its_date_and_count_with_until_is_rejected() {
+        let week = WeekConfig::default();
+        let parsed = from_rrule(
+            "FREQ=DAILY;UNTIL=20240630T235959Z",
+            "2024-05-06",
+            None,
+            &week,
+        )
+        .unwrap();
+        assert_eq!(parsed.end_date.as_deref(), Some("2024-06-30"));
+
+        assert!(from_rrule(
+            "FREQ=DAILY;COUNT=3;UNTIL=20240630",
+            "2024-05-06",
+            None,
+            &week
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn count_terminated_series_stops_after_its_last_occurrence() {
+        let week = WeekConfig::default();
+        let mut counted = periodicity("day");
+        counted.interval = 2;
+        counted.end_rule = "count".to_string();
+        counted.end_count = Some(3);
+        let series = Series::new(&counted, None, &week).unwrap();
+        let day = |value: &str| NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap();
+
+        assert!(series.occurrence_on(day("2024-05-10")).is_some());
+        assert!(series.occurrence_on(day("2024-05-09")).is_none());
+        assert!(series.occurrence_on(day("2024-05-12")).is_none());
+    }
+}
@@ -0,0 +1,88 @@
+use chrono::{DateTime, Datelike, Duration, SecondsFormat, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::timezone;
+use crate::ipc::{ApiError, ErrorCode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReminderRepeat {
+    Daily,
+    Weekdays, // Monday to Friday
+    Weekly,
+}
+
+impl ReminderRepeat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReminderRepeat::Daily => "daily",
+            ReminderRepeat::Weekdays => "weekdays",
+            ReminderRepeat::Weekly => "weekly",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "daily" => Some(ReminderRepeat::Daily),
+            "weekdays" => Some(ReminderRepeat::Weekdays),
+            "weekly" => Some(ReminderRepeat::Weekly),
+            _ => None,
+        }
+    }
+}
+
+// A moment a task asks for attention, independent of its due date
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: String,
+    pub task_id: String,
+    pub remind_at: String, // RFC3339 UTC; the next occurrence of a repeating reminder
+    pub repeat: Option<ReminderRepeat>,
+    pub fired_at: Option<String>, // Set once a one-off reminder went off
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// Stored instants are UTC to the second, so they compare as text
+pub fn format_instant(instant: DateTime<Utc>) -> String {
+    instant.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+// RFC3339, or a date and time in the vault timezone
+pub fn parse_remind_at(value: &str, tz: Option<Tz>) -> Result<DateTime<Utc>, ApiError> {
+    timezone::parse_instant(value, tz).ok_or_else(|| ApiError {
+        code: ErrorCode::InvalidInput,
+        message: "remind_at must be a date and time such as 2024-05-01T09:00".to_string(),
+        details: Some(serde_json::json!({ "remind_at": value })),
+    })
+}
+
+// First occurrence after `now`. Steps are taken on the vault wall clock, so a 09:00 reminder
+// stays at 09:00 across DST changes.
+pub fn next_occurrence(
+    remind_at: DateTime<Utc>,
+    repeat: ReminderRepeat,
+    now: DateTime<Utc>,
+    tz: Option<Tz>,
+) -> DateTime<Utc> {
+    let step = match repeat {
+        ReminderRepeat::Weekly => Duration::days(7),
+        ReminderRepeat::Daily | ReminderRepeat::Weekdays => Duration::days(1),
+    };
+    let mut local = timezone::wall_clock(remind_at, tz);
+    let mut next = remind_at;
+    while next <= now {
+        local += step;
+        while repeat == ReminderRepeat::Weekdays
+            && matches!(local.weekday(), Weekday::Sat | Weekday::Sun)
+        {
+            local += Duration::days(1);
+        }
+        // A time skipped by the DST change goes off an hour later that day
+        next = timezone::from_wall_clock(local, tz)
+            .or_else(|| timezone::from_wall_clock(local + Duration::hours(1), tz))
+            .unwrap_or(next + step);
+    }
+    next
+}
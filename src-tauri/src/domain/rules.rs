@@ -0,0 +1,132 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::planning::{Task, TaskPriority};
+use crate::domain::timezone;
+
+// When a rule fires, relative to the task's due date
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleCondition {
+    DueWithin { hours: i64 }, // Due in the next `hours` (overdue tasks included)
+    Overdue,
+}
+
+// What a rule changes on a matching task
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleActions {
+    #[serde(default)]
+    pub raise_priority: Option<TaskPriority>, // Never lowers an already higher priority
+    #[serde(default)]
+    pub add_tag: Option<String>,
+    #[serde(default)]
+    pub move_to_board: Option<String>,
+}
+
+// Due-date escalation rule stored in planning settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationRule {
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub condition: RuleCondition,
+    #[serde(default)]
+    pub actions: RuleActions,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+// Changes applied to one task by a rules run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleChange {
+    pub task_id: String,
+    pub rule_ids: Vec<String>,
+    pub priority: Option<TaskPriority>,
+    pub tags: Option<Vec<String>>,
+    pub board_id: Option<String>,
+}
+
+// Rules run result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RulesRunResult {
+    pub evaluated: usize,
+    pub changes: Vec<RuleChange>,
+}
+
+fn priority_rank(priority: TaskPriority) -> u8 {
+    match priority {
+        TaskPriority::Urgent => 0,
+        TaskPriority::High => 1,
+        TaskPriority::Medium => 2,
+        TaskPriority::Low => 3,
+    }
+}
+
+// Due instant in vault wall-clock time; date-only values are due at the end of that day
+fn due_at(task: &Task, tz: Option<Tz>) -> Option<NaiveDateTime> {
+    let value = task.due_date.as_deref()?;
+    timezone::to_local_naive(value, tz).or_else(|| {
+        NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(23, 59, 59)
+    })
+}
+
+fn matches(condition: &RuleCondition, due: NaiveDateTime, now: NaiveDateTime) -> bool {
+    match condition {
+        RuleCondition::DueWithin { hours } => due - now <= chrono::Duration::hours(*hours),
+        RuleCondition::Overdue => due < now,
+    }
+}
+
+// Evaluate enabled rules against a task; None when nothing would change
+pub fn evaluate(
+    rules: &[EscalationRule],
+    task: &Task,
+    now: NaiveDateTime,
+    tz: Option<Tz>,
+) -> Option<RuleChange> {
+    let due = due_at(task, tz)?;
+
+    let mut priority = task.priority;
+    let mut tags = task.tags.clone().unwrap_or_default();
+    let mut board_id = task.board_id.clone();
+    let mut rule_ids = Vec::new();
+
+    for rule in rules
+        .iter()
+        .filter(|rule| rule.enabled && matches(&rule.condition, due, now))
+    {
+        rule_ids.push(rule.id.clone());
+        if let Some(target) = rule.actions.raise_priority {
+            if priority.is_none_or(|current| priority_rank(target) < priority_rank(current)) {
+                priority = Some(target);
+            }
+        }
+        if let Some(tag) = rule.actions.add_tag.as_deref().map(str::trim) {
+            if !tag.is_empty() && !tags.iter().any(|existing| existing == tag) {
+                tags.push(tag.to_string());
+            }
+        }
+        if let Some(board) = &rule.actions.move_to_board {
+            board_id = Some(board.clone());
+        }
+    }
+
+    let change = RuleChange {
+        task_id: task.id.clone(),
+        rule_ids,
+        priority: (priority != task.priority).then_some(priority).flatten(),
+        tags: (tags != task.tags.clone().unwrap_or_default()).then_some(tags),
+        board_id: (board_id != task.board_id).then_some(board_id).flatten(),
+    };
+
+    if change.priority.is_none() && change.tags.is_none() && change.board_id.is_none() {
+        return None;
+    }
+    Some(change)
+}
@@ -0,0 +1,158 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::planning::{Task, TaskStatus};
+use crate::domain::timezone;
+
+// Canonical storage format for scheduled_start / scheduled_end: a UTC instant. One fixed
+// format keeps SQL string comparisons in time order whatever the vault timezone.
+pub const SCHEDULE_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+// Vault wall-clock times in schedule DTOs
+pub const LOCAL_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+// A scheduled time block on the timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleBlock {
+    pub task_id: String,
+    pub title: String,
+    pub status: TaskStatus,
+    pub start: String,
+    pub end: String,
+}
+
+// Free time between two scheduled blocks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleGap {
+    pub start: String,
+    pub end: String,
+    pub duration_min: i64,
+}
+
+// Ordered schedule for a single day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DayScheduleDTO {
+    pub day: String,
+    pub blocks: Vec<ScheduleBlock>,
+    pub gaps: Vec<ScheduleGap>,
+}
+
+// Schedule task input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleTaskInput {
+    pub task_id: String,
+    pub start: String,
+    pub end: String,
+    pub force: Option<bool>, // Schedule even when overlapping blocks exist
+}
+
+// Schedule task response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleTaskResponse {
+    pub scheduled: bool,
+    pub task: Option<Task>,
+    pub conflicts: Vec<ScheduleBlock>,
+}
+
+// Shift schedule response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShiftScheduleResponse {
+    pub shifted: bool,
+    pub tasks: Vec<Task>,
+    pub conflicts: Vec<ScheduleBlock>,
+}
+
+// Parse a schedule timestamp (RFC3339, YYYY-MM-DDTHH:MM:SS or YYYY-MM-DDTHH:MM) as vault wall-clock time
+pub fn parse_schedule_time(value: &str, tz: Option<Tz>) -> Option<NaiveDateTime> {
+    timezone::to_local_naive(value, tz)
+}
+
+pub fn format_local_time(value: NaiveDateTime) -> String {
+    value.format(LOCAL_TIME_FORMAT).to_string()
+}
+
+// A wall-clock time skipped by a DST change is taken as the same time an hour later
+fn wall_clock_to_utc(value: NaiveDateTime, tz: Option<Tz>) -> DateTime<Utc> {
+    timezone::from_wall_clock(value, tz)
+        .or_else(|| timezone::from_wall_clock(value + TimeDelta::hours(1), tz))
+        .unwrap_or_else(|| value.and_utc())
+}
+
+// Stored form of a vault wall-clock time
+pub fn format_schedule_time(value: NaiveDateTime, tz: Option<Tz>) -> String {
+    wall_clock_to_utc(value, tz)
+        .format(SCHEDULE_TIME_FORMAT)
+        .to_string()
+}
+
+// Stored form of a schedule value as entered: RFC3339 keeps its instant, naive values are
+// vault wall-clock time. None when the value is not a time.
+pub fn normalize_schedule_time(value: &str, tz: Option<Tz>) -> Option<String> {
+    let value = value.trim();
+    let instant = match DateTime::parse_from_rfc3339(value) {
+        Ok(instant) => instant.with_timezone(&Utc),
+        Err(_) => wall_clock_to_utc(timezone::to_local_naive(value, tz)?, tz),
+    };
+    Some(instant.format(SCHEDULE_TIME_FORMAT).to_string())
+}
+
+// Start (inclusive) and end (exclusive) of a calendar day
+pub fn day_bounds(day: NaiveDate) -> (NaiveDateTime, NaiveDateTime) {
+    let start = day.and_hms_opt(0, 0, 0).unwrap_or_default();
+    (start, start + chrono::Duration::days(1))
+}
+
+// Half-open interval overlap: touching blocks do not conflict
+pub fn overlaps(
+    a_start: NaiveDateTime,
+    a_end: NaiveDateTime,
+    b_start: NaiveDateTime,
+    b_end: NaiveDateTime,
+) -> bool {
+    a_start < b_end && b_start < a_end
+}
+
+// Parse a task's scheduled range; None if unscheduled or malformed
+pub fn task_schedule_range(task: &Task, tz: Option<Tz>) -> Option<(NaiveDateTime, NaiveDateTime)> {
+    let start = parse_schedule_time(task.scheduled_start.as_deref()?, tz)?;
+    let end = parse_schedule_time(task.scheduled_end.as_deref()?, tz)?;
+    if end <= start {
+        return None;
+    }
+    Some((start, end))
+}
+
+pub fn block_from_task(task: &Task, tz: Option<Tz>) -> Option<ScheduleBlock> {
+    let (start, end) = task_schedule_range(task, tz)?;
+    Some(ScheduleBlock {
+        task_id: task.id.clone(),
+        title: task.title.clone(),
+        status: task.status,
+        start: format_local_time(start),
+        end: format_local_time(end),
+    })
+}
+
+// Free gaps between consecutive blocks (blocks must be sorted by start)
+pub fn compute_gaps(ranges: &[(NaiveDateTime, NaiveDateTime)]) -> Vec<ScheduleGap> {
+    let mut gaps = Vec::new();
+    let mut busy_until: Option<NaiveDateTime> = None;
+
+    for (start, end) in ranges {
+        if let Some(until) = busy_until {
+            if *start > until {
+                gaps.push(ScheduleGap {
+                    start: format_local_time(until),
+                    end: format_local_time(*start),
+                    duration_min: (*start - until).num_minutes(),
+                });
+            }
+        }
+        busy_until = Some(match busy_until {
+            Some(until) if until > *end => until,
+            _ => *end,
+        });
+    }
+
+    gaps
+}
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::planning::Task;
+
+// Control characters FTS wraps around matched text; highlight_html turns them into <mark> tags
+// once the text around them is escaped
+pub const HIGHLIGHT_OPEN: &str = "\u{2}";
+pub const HIGHLIGHT_CLOSE: &str = "\u{3}";
+// The trigram index cannot match terms shorter than this
+pub const MIN_INDEXED_TERM_CHARS: usize = 3;
+
+// A ranked search match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskSearchHit {
+    pub task: Task,
+    pub score: f64, // Higher is better
+    pub title_highlight: String,
+    pub snippet: Option<String>, // Best matching excerpt from description, tags or note body
+}
+
+// Rank damping constant of reciprocal-rank fusion
+pub const RRF_K: f64 = 60.0;
+
+// A note matched by hybrid search, located at its best matching chunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HybridSearchHit {
+    pub path: String,
+    pub heading: Option<String>,
+    pub anchor: Option<String>,
+    pub start: usize,
+    pub end: usize,
+    pub snippet: String,
+    pub score: f64,                  // Fused score, higher is better
+    pub keyword_rank: Option<usize>, // 1-based rank among keyword matches
+    pub keyword_score: Option<f64>,  // bm25 relevance, higher is better
+    pub vector_rank: Option<usize>,  // 1-based rank among similar notes
+    pub vector_score: Option<f32>,   // Cosine similarity to the query
+}
+
+// Fuse ranked lists of keys (best first) by reciprocal rank. Returns each key once with its
+// fused score and 1-based rank in every list, best first.
+pub fn reciprocal_rank_fusion(lists: &[&[String]]) -> Vec<(String, f64, Vec<Option<usize>>)> {
+    let mut fused: Vec<(String, f64, Vec<Option<usize>>)> = Vec::new();
+    for (list_index, list) in lists.iter().enumerate() {
+        for (position, key) in list.iter().enumerate() {
+            let index = match fused.iter().position(|(existing, _, _)| existing == key) {
+                Some(index) => index,
+                None => {
+                    fused.push((key.clone(), 0.0, vec![None; lists.len()]));
+                    fused.len() - 1
+                }
+            };
+            let (_, score, ranks) = &mut fused[index];
+            // A key repeated within one list only counts at its best rank
+            if ranks[list_index].is_none() {
+                ranks[list_index] = Some(position + 1);
+                *score += 1.0 / (RRF_K + (position + 1) as f64);
+            }
+        }
+    }
+    fused.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    fused
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Escape FTS highlight()/snippet() output and mark its matches with <mark> tags
+pub fn highlight_html(raw: &str) -> String {
+    escape_html(raw)
+        .replace(HIGHLIGHT_OPEN, "<mark>")
+        .replace(HIGHLIGHT_CLOSE, "</mark>")
+}
+
+// Split a free-text query into search terms
+pub fn query_terms(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .map(|term| term.to_string())
+        .collect()
+}
+
+// FTS5 match expression requiring every term; None when a term is too short for the index
+pub fn fts_match_expr(terms: &[String], include_notes: bool) -> Option<String> {
+    if terms.is_empty()
+        || terms
+            .iter()
+            .any(|term| term.chars().count() < MIN_INDEXED_TERM_CHARS)
+    {
+        return None;
+    }
+
+    let expr = terms
+        .iter()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+
+    Some(if include_notes {
+        expr
+    } else {
+        format!("{{title description tags}} : ({})", expr)
+    })
+}
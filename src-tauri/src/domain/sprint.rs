@@ -0,0 +1,201 @@
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::planning::{Task, TaskStatus};
+use crate::domain::timezone;
+use crate::domain::week::WeekConfig;
+use crate::ipc::{ApiError, ErrorCode};
+
+// Longest sprint accepted; keeps burndown series small
+pub const MAX_SPRINT_DAYS: i64 = 366;
+
+// Time box tasks are committed to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sprint {
+    pub id: String,
+    pub name: String,
+    pub start_date: String,    // YYYY-MM-DD, inclusive
+    pub end_date: String,      // YYYY-MM-DD, inclusive
+    pub capacity: Option<f64>, // Points the sprint is planned to hold
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SprintInput {
+    pub name: String,
+    pub start_date: String,
+    pub end_date: String,
+    #[serde(default)]
+    pub capacity: Option<f64>,
+}
+
+// A task committed to a sprint with the points it was estimated at
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SprintTask {
+    pub task: Task,
+    pub points: Option<f64>,
+    pub added_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SprintSummaryDTO {
+    pub sprint: Sprint,
+    pub tasks: Vec<SprintTask>,
+    pub committed_points: f64,
+    pub completed_points: f64,
+    pub task_count: usize,
+    pub completed_count: usize,
+    pub unestimated_count: usize, // Tasks without points; they count as zero
+    pub over_capacity: bool,
+}
+
+// Remaining points at the end of one sprint day; None for days still ahead
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurndownPoint {
+    pub day: String,
+    pub remaining: Option<f64>,
+    pub ideal: f64,
+    pub is_workday: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SprintBurndown {
+    pub sprint_id: String,
+    pub committed_points: f64,
+    pub points: Vec<BurndownPoint>,
+}
+
+// Parse and check the dates of a sprint
+pub fn validate_input(input: &SprintInput) -> Result<(NaiveDate, NaiveDate), ApiError> {
+    if input.name.trim().is_empty() {
+        return Err(ApiError {
+            code: ErrorCode::InvalidInput,
+            message: "Sprint name is empty".to_string(),
+            details: None,
+        });
+    }
+    let parse = |value: &str| {
+        NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|e| ApiError {
+            code: ErrorCode::DateTimeError,
+            message: format!("Failed to parse date: {}", e),
+            details: Some(serde_json::json!({ "value": value })),
+        })
+    };
+    let start = parse(&input.start_date)?;
+    let end = parse(&input.end_date)?;
+    if start > end || (end - start).num_days() >= MAX_SPRINT_DAYS {
+        return Err(ApiError {
+            code: ErrorCode::InvalidInput,
+            message: format!(
+                "Sprint must end on or after its start and last at most {} days",
+                MAX_SPRINT_DAYS
+            ),
+            details: Some(serde_json::json!({
+                "start_date": input.start_date,
+                "end_date": input.end_date,
+            })),
+        });
+    }
+    if input
+        .capacity
+        .is_some_and(|capacity| !capacity.is_finite() || capacity < 0.0)
+    {
+        return Err(ApiError {
+            code: ErrorCode::InvalidInput,
+            message: "Sprint capacity must be a non-negative number".to_string(),
+            details: Some(serde_json::json!({ "capacity": input.capacity })),
+        });
+    }
+    Ok((start, end))
+}
+
+// Day a task was finished in the vault timezone; None while it is open
+fn completed_on(task: &Task, tz: Option<Tz>) -> Option<NaiveDate> {
+    if task.status != TaskStatus::Done {
+        return None;
+    }
+    let completed_at = task.completed_at.as_deref().unwrap_or(&task.updated_at);
+    timezone::to_local_date(completed_at, tz)
+}
+
+pub fn summarize(sprint: Sprint, tasks: Vec<SprintTask>) -> SprintSummaryDTO {
+    let points = |task: &SprintTask| task.points.unwrap_or(0.0);
+    let committed_points: f64 = tasks.iter().map(points).sum();
+    let done: Vec<&SprintTask> = tasks
+        .iter()
+        .filter(|task| task.task.status == TaskStatus::Done)
+        .collect();
+    SprintSummaryDTO {
+        committed_points,
+        completed_points: done.iter().map(|task| points(task)).sum(),
+        task_count: tasks.len(),
+        completed_count: done.len(),
+        unestimated_count: tasks.iter().filter(|task| task.points.is_none()).count(),
+        over_capacity: sprint
+            .capacity
+            .is_some_and(|capacity| committed_points > capacity),
+        sprint,
+        tasks,
+    }
+}
+
+// Remaining points per sprint day against an ideal line that only drops on workdays. Work
+// finished before the sprint started counts on its first day.
+pub fn burndown(
+    sprint: &Sprint,
+    tasks: &[SprintTask],
+    today: NaiveDate,
+    tz: Option<Tz>,
+    week: &WeekConfig,
+) -> SprintBurndown {
+    let committed: f64 = tasks.iter().map(|task| task.points.unwrap_or(0.0)).sum();
+    let (Ok(start), Ok(end)) = (
+        NaiveDate::parse_from_str(&sprint.start_date, "%Y-%m-%d"),
+        NaiveDate::parse_from_str(&sprint.end_date, "%Y-%m-%d"),
+    ) else {
+        return SprintBurndown {
+            sprint_id: sprint.id.clone(),
+            committed_points: committed,
+            points: Vec::new(),
+        };
+    };
+
+    let days: Vec<NaiveDate> = start.iter_days().take_while(|day| *day <= end).collect();
+    let workdays = days
+        .iter()
+        .filter(|day| week.is_workday(**day))
+        .count()
+        .max(1);
+    let mut workdays_done = 0;
+    let points = days
+        .iter()
+        .map(|day| {
+            let is_workday = week.is_workday(*day);
+            if is_workday {
+                workdays_done += 1;
+            }
+            let remaining = (*day <= today).then(|| {
+                let burned: f64 = tasks
+                    .iter()
+                    .filter(|task| completed_on(&task.task, tz).is_some_and(|done| done <= *day))
+                    .map(|task| task.points.unwrap_or(0.0))
+                    .sum();
+                committed - burned
+            });
+            BurndownPoint {
+                day: day.format("%Y-%m-%d").to_string(),
+                remaining,
+                ideal: committed * (1.0 - workdays_done as f64 / workdays as f64),
+                is_workday,
+            }
+        })
+        .collect();
+
+    SprintBurndown {
+        sprint_id: sprint.id.clone(),
+        committed_points: committed,
+        points,
+    }
+}
@@ -0,0 +1,473 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::planning::{CreateTaskInput, Task, TaskPriority, TaskStatus};
+use crate::ipc::{ApiError, ErrorCode};
+
+// Rows accepted from one paste; larger plans can be pasted in parts
+pub const MAX_IMPORT_ROWS: usize = 1000;
+// Due dates a spreadsheet usually produces, tried when no date_format is given
+const DUE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableFormat {
+    Csv,
+    Tsv, // What spreadsheets put on the clipboard
+    Markdown,
+}
+
+// Column by header name (case-insensitive) or zero-based position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TableColumn {
+    Index(usize),
+    Name(String),
+}
+
+fn default_has_header() -> bool {
+    true
+}
+
+// Which pasted column feeds which task field; unmapped fields are left empty
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableMapping {
+    pub title: TableColumn,
+    #[serde(default)]
+    pub due: Option<TableColumn>,
+    #[serde(default)]
+    pub priority: Option<TableColumn>,
+    #[serde(default)]
+    pub tags: Option<TableColumn>, // Split on commas, semicolons and spaces; a leading # is dropped
+    #[serde(default)]
+    pub description: Option<TableColumn>,
+    #[serde(default)]
+    pub status: Option<TableColumn>,
+    #[serde(default = "default_has_header")]
+    pub has_header: bool,
+    #[serde(default)]
+    pub date_format: Option<String>, // chrono format of the due column, e.g. "%d/%m/%Y"
+    #[serde(default)]
+    pub default_due: Option<String>, // Rows without a due date; today when None
+    #[serde(default)]
+    pub board_id: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsedTable {
+    pub format: TableFormat,
+    pub headers: Vec<String>,
+    pub rows: Vec<(usize, Vec<String>)>, // Line the row starts on, 1-based, and its cells
+}
+
+// One data row: the task it maps to, or why it can't be imported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableImportRow {
+    pub line: usize,
+    pub task: Option<CreateTaskInput>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableImportResponse {
+    pub format: TableFormat,
+    pub headers: Vec<String>,
+    pub rows: Vec<TableImportRow>,
+    pub valid: usize,
+    pub invalid: usize,
+    pub dry_run: bool,
+    pub created: Vec<Task>, // Empty for a dry run
+}
+
+fn invalid_table(message: &str, details: Option<serde_json::Value>) -> ApiError {
+    ApiError {
+        code: ErrorCode::InvalidInput,
+        message: message.to_string(),
+        details,
+    }
+}
+
+// A markdown table starts with a pipe row followed by a |---|---| separator row
+fn is_markdown_separator(cells: &[String]) -> bool {
+    !cells.is_empty()
+        && cells.iter().all(|cell| {
+            let cell = cell.trim().trim_start_matches(':').trim_end_matches(':');
+            !cell.is_empty() && cell.chars().all(|c| c == '-')
+        })
+}
+
+fn markdown_cells(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cell.push('|');
+                chars.next();
+            }
+            '|' => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+fn parse_markdown(text: &str) -> Vec<(usize, Vec<String>)> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| line.trim_start().starts_with('|'))
+        .map(|(index, line)| (index + 1, markdown_cells(line)))
+        .filter(|(_, cells)| !is_markdown_separator(cells))
+        .collect()
+}
+
+// RFC 4180 style: quoted cells may hold the delimiter, newlines and "" for a quote
+fn parse_delimited(text: &str, delimiter: char) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    cell.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                c => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    cell.push(c);
+                }
+            }
+            continue;
+        }
+        match c {
+            '"' if cell.trim().is_empty() => {
+                cell.clear();
+                in_quotes = true;
+            }
+            c if c == delimiter => record.push(std::mem::take(&mut cell).trim().to_string()),
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut cell).trim().to_string());
+                records.push((record_line, std::mem::take(&mut record)));
+                line += 1;
+                record_line = line;
+            }
+            c => cell.push(c),
+        }
+    }
+    if !cell.is_empty() || !record.is_empty() {
+        record.push(cell.trim().to_string());
+        records.push((record_line, record));
+    }
+    records
+}
+
+fn detect_format(text: &str) -> TableFormat {
+    let first = text
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or_default();
+    if first.trim_start().starts_with('|') {
+        TableFormat::Markdown
+    } else if first.contains('\t') {
+        TableFormat::Tsv
+    } else {
+        TableFormat::Csv
+    }
+}
+
+// Parse pasted CSV, TSV or a markdown table. Rows with no content are dropped.
+pub fn parse_table(text: &str, has_header: bool) -> Result<ParsedTable, ApiError> {
+    let format = detect_format(text);
+    let rows = match format {
+        TableFormat::Markdown => parse_markdown(text),
+        TableFormat::Tsv => parse_delimited(text, '\t'),
+        TableFormat::Csv => {
+            // Spreadsheets in comma-decimal locales export with semicolons
+            let first = text.lines().next().unwrap_or_default();
+            let delimiter = if first.matches(';').count() > first.matches(',').count() {
+                ';'
+            } else {
+                ','
+            };
+            parse_delimited(text, delimiter)
+        }
+    };
+    let mut rows = rows
+        .into_iter()
+        .filter(|(_, cells)| cells.iter().any(|cell| !cell.is_empty()));
+
+    let headers = if has_header {
+        rows.next().map(|(_, cells)| cells).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    let rows: Vec<(usize, Vec<String>)> = rows.collect();
+    if rows.is_empty() {
+        return Err(invalid_table("Table has no data rows", None));
+    }
+    if rows.len() > MAX_IMPORT_ROWS {
+        return Err(invalid_table(
+            "Table has too many rows to import at once",
+            Some(serde_json::json!({ "rows": rows.len(), "max_rows": MAX_IMPORT_ROWS })),
+        ));
+    }
+    Ok(ParsedTable {
+        format,
+        headers,
+        rows,
+    })
+}
+
+fn resolve_column(column: &TableColumn, headers: &[String]) -> Result<usize, ApiError> {
+    match column {
+        TableColumn::Index(index) => Ok(*index),
+        TableColumn::Name(name) => headers
+            .iter()
+            .position(|header| header.trim().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| {
+                invalid_table(
+                    "Mapped column is not in the table header",
+                    Some(serde_json::json!({ "column": name, "headers": headers })),
+                )
+            }),
+    }
+}
+
+fn parse_priority(value: &str) -> Option<TaskPriority> {
+    match value.trim().to_lowercase().as_str() {
+        "p0" | "urgent" | "critical" => Some(TaskPriority::Urgent),
+        "p1" | "high" => Some(TaskPriority::High),
+        "p2" | "medium" | "normal" => Some(TaskPriority::Medium),
+        "p3" | "low" => Some(TaskPriority::Low),
+        _ => None,
+    }
+}
+
+fn parse_status(value: &str) -> Option<TaskStatus> {
+    match value.trim().to_lowercase().as_str() {
+        "todo" | "to do" | "backlog" | "open" => Some(TaskStatus::Todo),
+        "doing" | "in progress" | "started" => Some(TaskStatus::Doing),
+        "verify" | "review" | "in review" => Some(TaskStatus::Verify),
+        "done" | "completed" | "closed" => Some(TaskStatus::Done),
+        _ => None,
+    }
+}
+
+fn parse_due(value: &str, date_format: Option<&str>) -> Option<String> {
+    let value = value.trim();
+    let date = match date_format {
+        Some(format) => NaiveDate::parse_from_str(value, format).ok(),
+        None => DUE_FORMATS.iter().find_map(|format| {
+            // Spreadsheet datetimes keep only their date
+            let day = value.split_whitespace().next().unwrap_or(value);
+            NaiveDate::parse_from_str(day, format).ok()
+        }),
+    };
+    date.map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+fn parse_tags(value: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in value.split(|c: char| c == ',' || c == ';' || c.is_whitespace()) {
+        let tag = tag.trim().trim_start_matches('#');
+        if !tag.is_empty() && !tags.iter().any(|known| known == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+// Map every data row to a task input, collecting per-row problems instead of failing.
+// `default_due` is used for rows whose due cell is empty.
+pub fn map_rows(
+    table: &ParsedTable,
+    mapping: &TableMapping,
+    default_due: &str,
+) -> Result<Vec<TableImportRow>, ApiError> {
+    let column = |column: &Option<TableColumn>| {
+        column
+            .as_ref()
+            .map(|column| resolve_column(column, &table.headers))
+            .transpose()
+    };
+    let title_column = resolve_column(&mapping.title, &table.headers)?;
+    let due_column = column(&mapping.due)?;
+    let priority_column = column(&mapping.priority)?;
+    let tags_column = column(&mapping.tags)?;
+    let description_column = column(&mapping.description)?;
+    let status_column = column(&mapping.status)?;
+    let board_id = mapping
+        .board_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|board_id| !board_id.is_empty());
+
+    let rows = table
+        .rows
+        .iter()
+        .map(|(line, cells)| {
+            let cell = |index: Option<usize>| {
+                index
+                    .and_then(|index| cells.get(index))
+                    .map(|value| value.trim())
+                    .filter(|value| !value.is_empty())
+            };
+            let mut errors = Vec::new();
+
+            let title = cell(Some(title_column)).unwrap_or_default();
+            if title.is_empty() {
+                errors.push("Title is empty".to_string());
+            }
+            let due_date = match cell(due_column) {
+                Some(value) => parse_due(value, mapping.date_format.as_deref()).or_else(|| {
+                    errors.push(format!("Unrecognized due date: {}", value));
+                    None
+                }),
+                None => Some(default_due.to_string()),
+            };
+            let priority = cell(priority_column).and_then(|value| {
+                parse_priority(value).or_else(|| {
+                    errors.push(format!("Unrecognized priority: {}", value));
+                    None
+                })
+            });
+            let status = match cell(status_column) {
+                Some(value) => parse_status(value).unwrap_or_else(|| {
+                    errors.push(format!("Unrecognized status: {}", value));
+                    TaskStatus::Todo
+                }),
+                None => TaskStatus::Todo,
+            };
+            let tags = cell(tags_column)
+                .map(parse_tags)
+                .filter(|tags| !tags.is_empty());
+
+            let task = errors.is_empty().then(|| CreateTaskInput {
+                title: title.to_string(),
+                description: cell(description_column).map(str::to_string),
+                status,
+                priority,
+                due_date,
+                board_id: board_id.map(str::to_string),
+                estimate_min: None,
+                tags,
+                labels: None,
+                subtasks: None,
+                periodicity: None,
+                scheduled_start: None,
+                scheduled_end: None,
+                note_path: None,
+                color: None,
+                op_id: None,
+            });
+            TableImportRow {
+                line: *line,
+                task,
+                errors,
+            }
+        })
+        .collect();
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn csv_quoted_cells_keep_delimiters_newlines_and_quotes() {
+        let text =
+            "Title,Due\n\"Buy milk, eggs\",2024-05-01\n\"Write\n\"\"report\"\"\",2024-05-02\n";
+        let table = parse_table(text, true).unwrap();
+        assert_eq!(table.format, TableFormat::Csv);
+        assert_eq!(table.headers, cells(&["Title", "Due"]));
+        assert_eq!(
+            table.rows,
+            vec![
+                (2, cells(&["Buy milk, eggs", "2024-05-01"])),
+                (3, cells(&["Write\n\"report\"", "2024-05-02"])),
+            ]
+        );
+    }
+
+    #[test]
+    fn semicolon_and_tab_delimiters_are_detected() {
+        let table = parse_table("Title;Due\nA;2024-05-01", true).unwrap();
+        assert_eq!(table.format, TableFormat::Csv);
+        assert_eq!(table.rows, vec![(2, cells(&["A", "2024-05-01"]))]);
+
+        // The blank line is dropped but still counts for line numbers
+        let table = parse_table("Title\tTags\nA\t#x, y\n\nB\t\n", true).unwrap();
+        assert_eq!(table.format, TableFormat::Tsv);
+        assert_eq!(
+            table.rows,
+            vec![(2, cells(&["A", "#x, y"])), (4, cells(&["B", ""]))]
+        );
+    }
+
+    #[test]
+    fn markdown_table_skips_the_separator_and_unescapes_pipes() {
+        let text = "| Title | Due |\n|---|:--:|\n| a \\| b | 2024-05-01 |\n";
+        let table = parse_table(text, true).unwrap();
+        assert_eq!(table.format, TableFormat::Markdown);
+        assert_eq!(table.headers, cells(&["Title", "Due"]));
+        assert_eq!(table.rows, vec![(3, cells(&["a | b", "2024-05-01"]))]);
+    }
+
+    #[test]
+    fn table_without_data_rows_is_rejected() {
+        assert!(parse_table("Title\n", true).is_err());
+        assert!(parse_table("\n\n", false).is_err());
+        assert_eq!(parse_table("Title\n", false).unwrap().rows.len(), 1);
+    }
+
+    #[test]
+    fn map_rows_collects_problems_per_row() {
+        let table = parse_table(
+            "Title,Due,Priority,Tags\nA,2024/05/03,high,#x y x\n,05-03,urgent,\nB,,,\n",
+            true,
+        )
+        .unwrap();
+        let mapping: TableMapping = serde_json::from_value(serde_json::json!({
+            "title": "title",
+            "due": "Due",
+            "priority": "PRIORITY",
+            "tags": 3,
+        }))
+        .unwrap();
+        let rows = map_rows(&table, &mapping, "2024-05-10").unwrap();
+
+        let task = rows[0].task.as_ref().unwrap();
+        assert_eq!(task.due_date.as_deref(), Some("2024-05-03"));
+        assert_eq!(task.priority, Some(TaskPriority::High));
+        assert_eq!(task.tags, Some(cells(&["x", "y"])));
+
+        assert!(rows[1].task.is_none());
+        assert_eq!(
+            rows[1].errors,
+            cells(&["Title is empty", "Unrecognized due date: 05-03"])
+        );
+
+        let task = rows[2].task.as_ref().unwrap();
+        assert_eq!(task.due_date.as_deref(), Some("2024-05-10"));
+        assert_eq!(task.priority, None);
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::paths::nfc;
+use crate::services::ai_service::TokenUsage;
+
+// Tags outside the vocabulary the AI may propose unless the caller asks otherwise
+pub const DEFAULT_MAX_NEW_TAGS: usize = 3;
+
+// A tag in use and how many tasks carry it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagSuggestion {
+    pub tag: String,
+    pub confidence: f32, // 0.0 to 1.0 as reported by the model
+    pub is_new: bool,    // Not in the existing tag vocabulary
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagSuggestionResponse {
+    pub path: Option<String>, // Set when the input was a vault note
+    pub suggestions: Vec<TagSuggestion>,
+    pub usage: TokenUsage,
+}
+
+// Comparable form of a tag: no leading '#', NFC, lowercase
+pub fn tag_key(tag: &str) -> String {
+    nfc(tag.trim().trim_start_matches('#').trim()).to_lowercase()
+}
+
+// Fit raw model proposals to the vocabulary: known tags take the vocabulary's spelling, unknown
+// ones are kept only up to max_new (most confident first), and duplicates collapse to the most
+// confident. Returned best first.
+pub fn constrain_suggestions(
+    proposals: Vec<(String, f32)>,
+    vocabulary: &[String],
+    max_new: usize,
+) -> Vec<TagSuggestion> {
+    let mut proposals: Vec<(String, f32)> = proposals
+        .into_iter()
+        .map(|(tag, confidence)| {
+            let confidence = if confidence.is_finite() {
+                confidence.clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            (tag, confidence)
+        })
+        .collect();
+    proposals.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut seen = HashSet::new();
+    let mut new_tags = 0;
+    let mut suggestions = Vec::new();
+    for (tag, confidence) in proposals {
+        let key = tag_key(&tag);
+        if key.is_empty() || !seen.insert(key.clone()) {
+            continue;
+        }
+        match vocabulary.iter().find(|known| tag_key(known) == key) {
+            Some(known) => suggestions.push(TagSuggestion {
+                tag: known.clone(),
+                confidence,
+                is_new: false,
+            }),
+            None if new_tags < max_new => {
+                new_tags += 1;
+                suggestions.push(TagSuggestion {
+                    // Tags are single words; spaces would split them in markdown
+                    tag: tag
+                        .trim()
+                        .trim_start_matches('#')
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join("-"),
+                    confidence,
+                    is_new: true,
+                });
+            }
+            None => {}
+        }
+    }
+    suggestions
+}
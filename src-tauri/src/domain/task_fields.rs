@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::ipc::{ApiError, ErrorCode};
+use crate::repo::planning_md_repo::SYSTEM_FIELDS;
+
+// Longest field key; keys double as frontmatter keys
+pub const MAX_FIELD_KEY_CHARS: usize = 40;
+// Fields a board schema may define
+pub const MAX_BOARD_FIELDS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+    Text,
+    Number,
+    Date,    // YYYY-MM-DD
+    Select,  // One of the field's options
+    Boolean, // Stored as "true" or "false"
+}
+
+impl FieldKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FieldKind::Text => "text",
+            FieldKind::Number => "number",
+            FieldKind::Date => "date",
+            FieldKind::Select => "select",
+            FieldKind::Boolean => "boolean",
+        }
+    }
+}
+
+impl From<&str> for FieldKind {
+    fn from(s: &str) -> Self {
+        match s {
+            "number" => FieldKind::Number,
+            "date" => FieldKind::Date,
+            "select" => FieldKind::Select,
+            "boolean" => FieldKind::Boolean,
+            _ => FieldKind::Text,
+        }
+    }
+}
+
+// Custom field a board defines for its tasks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardField {
+    pub board_id: String,
+    pub key: String, // Also the task frontmatter key
+    pub name: String,
+    pub kind: FieldKind,
+    pub options: Vec<String>, // Select fields only
+    pub required: bool,
+    pub order_index: i64,
+}
+
+// Field definition input (board_id and order come from the save call)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardFieldInput {
+    pub key: String,
+    pub name: String,
+    pub kind: FieldKind,
+    #[serde(default)]
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+// Field values of several tasks: task id to key to value
+pub type FieldValuesByTask = BTreeMap<String, BTreeMap<String, String>>;
+
+// One stored field value; the export row shape
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskFieldValue {
+    pub task_id: String,
+    pub key: String,
+    pub value: String,
+}
+
+// Fields of a task next to the schema of its board
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskFieldsDTO {
+    pub task_id: String,
+    pub board_id: String,
+    pub values: BTreeMap<String, String>,
+    pub schema: Vec<BoardField>,
+    pub missing_required: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldFilterOp {
+    #[default]
+    Eq,
+    Contains,
+    Gt,
+    Lt,
+    Exists,
+}
+
+// Condition on one field; every filter of a query must match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskFieldFilter {
+    pub key: String,
+    #[serde(default)]
+    pub op: FieldFilterOp,
+    #[serde(default)]
+    pub value: Option<String>, // Ignored by exists
+}
+
+fn invalid_field(message: String, details: serde_json::Value) -> ApiError {
+    ApiError {
+        code: ErrorCode::InvalidInput,
+        message,
+        details: Some(details),
+    }
+}
+
+// Lowercase letters, digits and underscores, starting with a letter; never a system key
+pub fn validate_field_key(key: &str) -> Result<(), ApiError> {
+    let valid = key.chars().count() <= MAX_FIELD_KEY_CHARS
+        && key.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid {
+        return Err(invalid_field(
+            format!(
+                "Field key must be lowercase letters, digits or _ and at most {} characters",
+                MAX_FIELD_KEY_CHARS
+            ),
+            serde_json::json!({ "key": key }),
+        ));
+    }
+    if SYSTEM_FIELDS.contains(&key) {
+        return Err(invalid_field(
+            "Field key is reserved for task frontmatter".to_string(),
+            serde_json::json!({ "key": key }),
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_schema(fields: &[BoardFieldInput]) -> Result<(), ApiError> {
+    if fields.len() > MAX_BOARD_FIELDS {
+        return Err(invalid_field(
+            format!("A board defines at most {} fields", MAX_BOARD_FIELDS),
+            serde_json::json!({ "count": fields.len() }),
+        ));
+    }
+    let mut keys = std::collections::HashSet::new();
+    for field in fields {
+        validate_field_key(&field.key)?;
+        if !keys.insert(field.key.as_str()) {
+            return Err(invalid_field(
+                "Duplicate field key".to_string(),
+                serde_json::json!({ "key": field.key }),
+            ));
+        }
+        if field.name.trim().is_empty() {
+            return Err(invalid_field(
+                "Field name is empty".to_string(),
+                serde_json::json!({ "key": field.key }),
+            ));
+        }
+        if field.kind == FieldKind::Select
+            && field.options.iter().all(|option| option.trim().is_empty())
+        {
+            return Err(invalid_field(
+                "Select field needs at least one option".to_string(),
+                serde_json::json!({ "key": field.key }),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Canonical stored form of a value for its field; fields outside the schema are free text
+pub fn normalize_value(
+    field: Option<&BoardField>,
+    key: &str,
+    value: &str,
+) -> Result<String, ApiError> {
+    let value = value.trim();
+    if value.contains(['\n', '\r']) {
+        return Err(invalid_field(
+            "Field value must be a single line".to_string(),
+            serde_json::json!({ "key": key }),
+        ));
+    }
+    let Some(field) = field else {
+        return Ok(value.to_string());
+    };
+    let rejected = |expected: &str| {
+        invalid_field(
+            format!("Field {} expects {}", field.key, expected),
+            serde_json::json!({ "key": key, "value": value, "kind": field.kind }),
+        )
+    };
+    match field.kind {
+        FieldKind::Text => Ok(value.to_string()),
+        FieldKind::Number => value
+            .parse::<f64>()
+            .ok()
+            .filter(|number| number.is_finite())
+            .map(|_| value.to_string())
+            .ok_or_else(|| rejected("a number")),
+        FieldKind::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .map_err(|_| rejected("a YYYY-MM-DD date")),
+        FieldKind::Select => field
+            .options
+            .iter()
+            .find(|option| option.trim().eq_ignore_ascii_case(value))
+            .map(|option| option.trim().to_string())
+            .ok_or_else(|| rejected("one of its options")),
+        FieldKind::Boolean => match value.to_lowercase().as_str() {
+            "true" | "yes" | "1" => Ok("true".to_string()),
+            "false" | "no" | "0" => Ok("false".to_string()),
+            _ => Err(rejected("true or false")),
+        },
+    }
+}
+
+pub fn missing_required(schema: &[BoardField], values: &BTreeMap<String, String>) -> Vec<String> {
+    schema
+        .iter()
+        .filter(|field| field.required && !values.contains_key(&field.key))
+        .map(|field| field.key.clone())
+        .collect()
+}
+
+// Greater and less than compare as numbers when both sides are, else as text (ISO dates sort)
+fn compare(stored: &str, wanted: &str) -> std::cmp::Ordering {
+    match (stored.parse::<f64>(), wanted.parse::<f64>()) {
+        (Ok(stored), Ok(wanted)) => stored.total_cmp(&wanted),
+        _ => stored.cmp(wanted),
+    }
+}
+
+pub fn matches_filters(values: &BTreeMap<String, String>, filters: &[TaskFieldFilter]) -> bool {
+    filters.iter().all(|filter| {
+        let Some(stored) = values.get(&filter.key) else {
+            return false;
+        };
+        let wanted = filter.value.as_deref().unwrap_or_default().trim();
+        match filter.op {
+            FieldFilterOp::Exists => true,
+            FieldFilterOp::Eq => stored.eq_ignore_ascii_case(wanted),
+            FieldFilterOp::Contains => stored.to_lowercase().contains(&wanted.to_lowercase()),
+            FieldFilterOp::Gt => compare(stored, wanted).is_gt(),
+            FieldFilterOp::Lt => compare(stored, wanted).is_lt(),
+        }
+    })
+}
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+
+use crate::ipc::{ApiError, ErrorCode};
+
+// Parse an IANA timezone name such as "Europe/Berlin"
+pub fn parse_timezone(name: &str) -> Result<Tz, ApiError> {
+    name.trim().parse::<Tz>().map_err(|_| ApiError {
+        code: ErrorCode::InvalidInput,
+        message: format!("Unknown timezone: {}", name),
+        details: Some(serde_json::json!({ "timezone": name })),
+    })
+}
+
+// Current calendar day in the vault timezone (system timezone when unset)
+pub fn today_in(tz: Option<Tz>) -> NaiveDate {
+    match tz {
+        Some(tz) => Utc::now().with_timezone(&tz).date_naive(),
+        None => Local::now().date_naive(),
+    }
+}
+
+// Current wall-clock time in the vault timezone (system timezone when unset)
+pub fn now_in(tz: Option<Tz>) -> NaiveDateTime {
+    match tz {
+        Some(tz) => Utc::now().with_timezone(&tz).naive_local(),
+        None => Local::now().naive_local(),
+    }
+}
+
+// Convert a stored instant to vault wall-clock time.
+// RFC3339 instants are shifted into the vault timezone; naive values are already wall-clock.
+pub fn to_local_naive(value: &str, tz: Option<Tz>) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(match tz {
+            Some(tz) => dt.with_timezone(&tz).naive_local(),
+            None => dt.naive_local(),
+        });
+    }
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M"))
+        .ok()
+}
+
+// Wall-clock time of a stored RFC3339 instant in the vault timezone (system timezone when unset)
+pub fn instant_to_local(value: &str, tz: Option<Tz>) -> Option<NaiveDateTime> {
+    let dt = DateTime::parse_from_rfc3339(value.trim()).ok()?;
+    Some(match tz {
+        Some(tz) => dt.with_timezone(&tz).naive_local(),
+        None => dt.with_timezone(&Local).naive_local(),
+    })
+}
+
+// Parse a user-entered instant: RFC3339 as-is, naive values as vault wall-clock time
+pub fn parse_instant(value: &str, tz: Option<Tz>) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    from_wall_clock(to_local_naive(value, tz)?, tz)
+}
+
+// Wall-clock time of an instant in the vault timezone (system timezone when unset)
+pub fn wall_clock(instant: DateTime<Utc>, tz: Option<Tz>) -> NaiveDateTime {
+    match tz {
+        Some(tz) => instant.with_timezone(&tz).naive_local(),
+        None => instant.with_timezone(&Local).naive_local(),
+    }
+}
+
+// Instant of a vault wall-clock time; None for a time skipped by a DST change
+pub fn from_wall_clock(naive: NaiveDateTime, tz: Option<Tz>) -> Option<DateTime<Utc>> {
+    match tz {
+        Some(tz) => naive
+            .and_local_timezone(tz)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc)),
+        None => naive
+            .and_local_timezone(Local)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc)),
+    }
+}
+
+// Calendar day of a stored date or instant in the vault timezone
+pub fn to_local_date(value: &str, tz: Option<Tz>) -> Option<NaiveDate> {
+    to_local_naive(value, tz)
+        .map(|dt| dt.date())
+        .or_else(|| NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").ok())
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::planning::Task;
+
+// Task events a webhook can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WebhookEvent {
+    #[serde(rename = "task_created")]
+    Created,
+    #[serde(rename = "task_completed")]
+    Completed,
+    #[serde(rename = "task_overdue")]
+    Overdue, // Sent once per due date after it has passed
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::Created => "task_created",
+            WebhookEvent::Completed => "task_completed",
+            WebhookEvent::Overdue => "task_overdue",
+        }
+    }
+}
+
+// Outbound webhook; stored in the vault settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    #[serde(default)]
+    pub id: String,
+    pub url: String,
+    #[serde(default)]
+    pub secret: String, // Signs the body as X-Planning-Signature when set
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl WebhookConfig {
+    pub fn subscribes_to(&self, event: WebhookEvent) -> bool {
+        self.enabled && self.events.contains(&event)
+    }
+}
+
+// JSON body posted to the webhook URL
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload {
+    pub delivery_id: String,
+    pub event: WebhookEvent,
+    pub occurred_at: String,
+    pub task: Task,
+}
+
+// One delivery attempt series, kept for troubleshooting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub webhook_id: String,
+    pub event: String,
+    pub task_id: String,
+    pub status_code: Option<i64>,
+    pub attempts: i64,
+    pub success: bool,
+    pub error: Option<String>,
+    pub delivered_at: String,
+}
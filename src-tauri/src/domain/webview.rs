@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+// ui_state key holding the embedded browser sessions, an object keyed by webview label
+pub const WEBVIEW_SESSIONS_KEY: &str = "webview_sessions";
+
+// Placement of an embedded webview inside the main window, in logical pixels
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WebviewBounds {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Default for WebviewBounds {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: 800.0,
+            height: 600.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebviewSession {
+    pub label: String,
+    pub url: String,
+    pub title: Option<String>,
+    pub opened_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebviewSessionDTO {
+    #[serde(flatten)]
+    pub session: WebviewSession,
+    pub open: bool, // A live webview exists for the label in this run of the app
+}
+
+// A page visited in an embedded webview
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebviewHistoryEntry {
+    pub label: String,
+    pub url: String,
+    pub title: Option<String>,
+    pub visited_at: String,
+}
+
+// Payload of the "webview-state" event emitted by the bridge script
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebviewStatePayload {
+    pub label: String,
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub ready_state: Option<String>,
+}
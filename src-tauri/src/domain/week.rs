@@ -0,0 +1,74 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::planning::Task;
+
+// Week layout used by weekly recurrences, reviews and stats bucketing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeekConfig {
+    #[serde(default = "default_first_day")]
+    pub first_day: Weekday, // "Mon", "Sun", ...
+    #[serde(default = "default_workdays")]
+    pub workdays: Vec<Weekday>,
+}
+
+impl Default for WeekConfig {
+    fn default() -> Self {
+        Self {
+            first_day: default_first_day(),
+            workdays: default_workdays(),
+        }
+    }
+}
+
+fn default_first_day() -> Weekday {
+    Weekday::Mon
+}
+
+fn default_workdays() -> Vec<Weekday> {
+    vec![
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+    ]
+}
+
+impl WeekConfig {
+    // First day of the week containing `date`
+    pub fn week_start(&self, date: NaiveDate) -> NaiveDate {
+        let offset =
+            (date.weekday().num_days_from_monday() + 7 - self.first_day.num_days_from_monday()) % 7;
+        date - chrono::Duration::days(offset as i64)
+    }
+
+    // Whole weeks between the weeks containing `from` and `to`
+    pub fn weeks_between(&self, from: NaiveDate, to: NaiveDate) -> i64 {
+        (self.week_start(to) - self.week_start(from)).num_days() / 7
+    }
+
+    pub fn is_workday(&self, date: NaiveDate) -> bool {
+        self.workdays.contains(&date.weekday())
+    }
+}
+
+// A single day in the weekly review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeekReviewDay {
+    pub day: String,
+    pub is_workday: bool,
+    pub completed_count: usize,
+    pub tracked_min: i64,
+}
+
+// Weekly review bucketed by the configured week layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeekReviewDTO {
+    pub week_start: String,
+    pub week_end: String,
+    pub days: Vec<WeekReviewDay>,
+    pub completed: Vec<Task>,
+    pub tracked_min: i64,
+    pub workday_tracked_min: i64,
+}
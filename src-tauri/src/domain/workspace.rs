@@ -0,0 +1,312 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::links;
+use crate::ipc::{ApiError, ErrorCode};
+
+// Recent notes returned when the caller gives no limit
+pub const DEFAULT_RECENT_NOTES_LIMIT: i64 = 20;
+// ui_state key holding the named layouts, an object keyed by layout name
+pub const LAYOUTS_KEY: &str = "layouts";
+// Version written into saved layouts; bump it and migrate in `migrate_layout` when the shape changes
+pub const LAYOUT_VERSION: u32 = 1;
+const MAX_LAYOUT_NAME_CHARS: usize = 64;
+const MAX_LAYOUT_PANES: usize = 8;
+const MAX_PANE_TABS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FavoriteKind {
+    Note,
+    Task,
+}
+
+impl FavoriteKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FavoriteKind::Note => "note",
+            FavoriteKind::Task => "task",
+        }
+    }
+}
+
+impl From<&str> for FavoriteKind {
+    fn from(s: &str) -> Self {
+        match s {
+            "task" => FavoriteKind::Task,
+            _ => FavoriteKind::Note,
+        }
+    }
+}
+
+// Identifies a favorite: a vault-relative note path or a task id
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FavoriteRef {
+    pub kind: FavoriteKind,
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Favorite {
+    pub kind: FavoriteKind,
+    pub target: String,
+    pub order_index: i64,
+    pub added_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavoriteDTO {
+    #[serde(flatten)]
+    pub favorite: Favorite,
+    pub title: String, // Task title or note file name
+    pub missing: bool, // The note or task no longer exists
+}
+
+// Where the editor was in a note, to resume there
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct NoteCursor {
+    pub line: u32,   // 0-based
+    pub column: u32, // 0-based, in characters
+    pub scroll_top: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentNote {
+    pub path: String,
+    pub opened_at: String,
+    pub cursor: Option<NoteCursor>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentNoteDTO {
+    #[serde(flatten)]
+    pub note: RecentNote,
+    pub missing: bool, // The note was deleted or moved since
+}
+
+// A tab of a saved layout; mirrors the frontend tab types
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum LayoutTab {
+    Home,
+    Markdown { file_path: String },
+    Web { url: String },
+    Task { task_id: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutPane {
+    pub tabs: Vec<LayoutTab>,
+    pub active_tab: Option<usize>,
+    pub size: f64, // Share of the window; sizes of a layout sum to 1
+}
+
+// Layout as sent by the frontend to be saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutInput {
+    pub panes: Vec<LayoutPane>,
+    pub active_pane: Option<usize>,
+    pub active_note: Option<String>,
+    pub active_board: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceLayout {
+    pub version: u32,
+    pub name: String,
+    pub panes: Vec<LayoutPane>,
+    pub active_pane: usize,
+    pub active_note: Option<String>,
+    pub active_board: Option<String>,
+    pub saved_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutSummary {
+    pub name: String,
+    pub saved_at: String,
+    pub pane_count: usize,
+    pub tab_count: usize,
+}
+
+impl From<&WorkspaceLayout> for LayoutSummary {
+    fn from(layout: &WorkspaceLayout) -> Self {
+        Self {
+            name: layout.name.clone(),
+            saved_at: layout.saved_at.clone(),
+            pane_count: layout.panes.len(),
+            tab_count: layout.panes.iter().map(|pane| pane.tabs.len()).sum(),
+        }
+    }
+}
+
+// Backend-held workspace state of a vault, loaded once when the frontend starts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceStateDTO {
+    pub vault_id: String,
+    pub favorites: Vec<FavoriteDTO>,
+    pub recent_notes: Vec<RecentNoteDTO>,
+    pub layouts: Vec<LayoutSummary>,
+}
+
+fn invalid_layout(message: &str, details: serde_json::Value) -> ApiError {
+    ApiError {
+        code: ErrorCode::InvalidInput,
+        message: message.to_string(),
+        details: Some(details),
+    }
+}
+
+pub fn validate_layout_name(name: &str) -> Result<String, ApiError> {
+    let name = name.trim();
+    if name.is_empty()
+        || name.chars().count() > MAX_LAYOUT_NAME_CHARS
+        || name.chars().any(char::is_control)
+    {
+        return Err(invalid_layout(
+            "Layout name must be 1 to 64 printable characters",
+            serde_json::json!({ "name": name }),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+fn validate_tab(tab: LayoutTab) -> Result<LayoutTab, ApiError> {
+    Ok(match tab {
+        LayoutTab::Home => LayoutTab::Home,
+        LayoutTab::Markdown { file_path } => {
+            let normalized = links::normalize_note_path(&file_path).ok_or_else(|| {
+                invalid_layout(
+                    "Layout note paths must be vault-relative",
+                    serde_json::json!({ "file_path": file_path }),
+                )
+            })?;
+            LayoutTab::Markdown {
+                file_path: normalized,
+            }
+        }
+        LayoutTab::Web { url } => {
+            let url = url.trim().to_string();
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(invalid_layout(
+                    "Layout web tabs must be http or https pages",
+                    serde_json::json!({ "url": url }),
+                ));
+            }
+            LayoutTab::Web { url }
+        }
+        LayoutTab::Task { task_id } => {
+            let task_id = task_id.trim().to_string();
+            if task_id.is_empty() {
+                return Err(invalid_layout(
+                    "Layout task tabs need a task id",
+                    serde_json::json!({}),
+                ));
+            }
+            LayoutTab::Task { task_id }
+        }
+    })
+}
+
+// Check a layout from the frontend and normalize it: note paths made vault-relative, pane sizes
+// scaled to sum to 1, out-of-range active indexes rejected
+pub fn validate_layout(
+    name: &str,
+    input: LayoutInput,
+    saved_at: &str,
+) -> Result<WorkspaceLayout, ApiError> {
+    let name = validate_layout_name(name)?;
+    if input.panes.is_empty() || input.panes.len() > MAX_LAYOUT_PANES {
+        return Err(invalid_layout(
+            "A layout has 1 to 8 panes",
+            serde_json::json!({ "panes": input.panes.len() }),
+        ));
+    }
+
+    let mut panes = Vec::with_capacity(input.panes.len());
+    for (index, pane) in input.panes.into_iter().enumerate() {
+        if !pane.size.is_finite() || pane.size <= 0.0 {
+            return Err(invalid_layout(
+                "Pane sizes must be positive",
+                serde_json::json!({ "pane": index, "size": pane.size }),
+            ));
+        }
+        if pane.tabs.len() > MAX_PANE_TABS {
+            return Err(invalid_layout(
+                "A pane has at most 50 tabs",
+                serde_json::json!({ "pane": index, "tabs": pane.tabs.len() }),
+            ));
+        }
+        if pane
+            .active_tab
+            .is_some_and(|active| active >= pane.tabs.len())
+        {
+            return Err(invalid_layout(
+                "Active tab is not in the pane",
+                serde_json::json!({ "pane": index, "active_tab": pane.active_tab }),
+            ));
+        }
+        let tabs = pane
+            .tabs
+            .into_iter()
+            .map(validate_tab)
+            .collect::<Result<Vec<_>, _>>()?;
+        panes.push(LayoutPane {
+            tabs,
+            active_tab: pane.active_tab,
+            size: pane.size,
+        });
+    }
+    let total: f64 = panes.iter().map(|pane| pane.size).sum();
+    for pane in &mut panes {
+        pane.size /= total;
+    }
+
+    let active_pane = input.active_pane.unwrap_or(0);
+    if active_pane >= panes.len() {
+        return Err(invalid_layout(
+            "Active pane is not in the layout",
+            serde_json::json!({ "active_pane": active_pane }),
+        ));
+    }
+    let active_note = match input.active_note {
+        Some(path) => Some(links::normalize_note_path(&path).ok_or_else(|| {
+            invalid_layout(
+                "Active note must be a vault-relative path",
+                serde_json::json!({ "active_note": path }),
+            )
+        })?),
+        None => None,
+    };
+    let active_board = input
+        .active_board
+        .map(|board| board.trim().to_string())
+        .filter(|board| !board.is_empty());
+
+    Ok(WorkspaceLayout {
+        version: LAYOUT_VERSION,
+        name,
+        panes,
+        active_pane,
+        active_note,
+        active_board,
+        saved_at: saved_at.to_string(),
+    })
+}
+
+// Bring a stored layout up to the current version. Layouts from a newer version of the app are
+// refused rather than misread.
+pub fn migrate_layout(value: serde_json::Value) -> Result<WorkspaceLayout, ApiError> {
+    let version = value
+        .get("version")
+        .and_then(|version| version.as_u64())
+        .unwrap_or(0);
+    if version > LAYOUT_VERSION as u64 {
+        return Err(invalid_layout(
+            "Layout was saved by a newer version of the app",
+            serde_json::json!({ "version": version, "supported": LAYOUT_VERSION }),
+        ));
+    }
+    let mut layout: WorkspaceLayout = serde_json::from_value(value)?;
+    layout.version = LAYOUT_VERSION;
+    Ok(layout)
+}
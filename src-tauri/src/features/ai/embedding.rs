@@ -1,24 +1,183 @@
-use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
-use std::sync::Mutex;
+use fastembed::{
+    EmbeddingModel, InitOptionsUserDefined, TextEmbedding, TokenizerFiles,
+    UserDefinedEmbeddingModel,
+};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, TryLockError};
+use std::time::Duration;
+
+use crate::ipc::{ApiError, ErrorCode};
+
+pub const EMBEDDING_DOWNLOAD_EVENT: &str = "embedding-download-progress";
+
+const HF_ENDPOINT: &str = "https://huggingface.co";
+const TOKENIZER_FILES: [&str; 4] = [
+    "tokenizer.json",
+    "config.json",
+    "special_tokens_map.json",
+    "tokenizer_config.json",
+];
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Embedding models the app offers; the files are only fetched by download_embedding_model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EmbeddingModelChoice {
+    #[default]
+    AllMiniLmL6V2, // English, smallest and fastest
+    MultilingualE5Small, // 100 languages including Chinese
+    BgeSmallZhV15,       // Chinese
+}
+
+impl EmbeddingModelChoice {
+    pub const ALL: [EmbeddingModelChoice; 3] = [
+        EmbeddingModelChoice::AllMiniLmL6V2,
+        EmbeddingModelChoice::MultilingualE5Small,
+        EmbeddingModelChoice::BgeSmallZhV15,
+    ];
+
+    fn model(self) -> EmbeddingModel {
+        match self {
+            EmbeddingModelChoice::AllMiniLmL6V2 => EmbeddingModel::AllMiniLML6V2,
+            EmbeddingModelChoice::MultilingualE5Small => EmbeddingModel::MultilingualE5Small,
+            EmbeddingModelChoice::BgeSmallZhV15 => EmbeddingModel::BGESmallZHV15,
+        }
+    }
+
+    // Stable name stored with indexed embeddings; vectors of different models never mix
+    pub fn id(self) -> &'static str {
+        match self {
+            EmbeddingModelChoice::AllMiniLmL6V2 => "all-mini-lm-l6-v2",
+            EmbeddingModelChoice::MultilingualE5Small => "multilingual-e5-small",
+            EmbeddingModelChoice::BgeSmallZhV15 => "bge-small-zh-v15",
+        }
+    }
+
+    pub fn multilingual(self) -> bool {
+        !matches!(self, EmbeddingModelChoice::AllMiniLmL6V2)
+    }
+
+    // E5 models are trained with an instruction prefix; "query: " suits symmetric similarity
+    fn input_prefix(self) -> &'static str {
+        match self {
+            EmbeddingModelChoice::MultilingualE5Small => "query: ",
+            _ => "",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingModelInfo {
+    pub model: EmbeddingModelChoice,
+    pub model_code: String, // Hugging Face repository
+    pub dim: usize,
+    pub multilingual: bool,
+    pub downloaded: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingStatus {
+    pub selected: EmbeddingModelChoice,
+    pub loaded: Option<EmbeddingModelChoice>,
+    pub busy: bool, // Loading a model or embedding right now
+    pub online: bool,
+    pub last_error: Option<String>, // Why the selected model last failed to load
+    pub models: Vec<EmbeddingModelInfo>,
+}
+
+// Payload of EMBEDDING_DOWNLOAD_EVENT, sent as each file streams in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingDownloadProgress {
+    pub model: EmbeddingModelChoice,
+    pub file: String,
+    pub file_index: usize,
+    pub file_count: usize,
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>, // Of the current file, when the server reports it
+    pub done: bool,
+}
 
 pub struct EmbeddingEngine {
-    model: Mutex<TextEmbedding>,
+    models_dir: PathBuf,
+    selected: Mutex<EmbeddingModelChoice>,
+    loaded: Mutex<Option<(EmbeddingModelChoice, TextEmbedding)>>,
+    last_error: Mutex<Option<String>>,
 }
 
 impl EmbeddingEngine {
-    pub fn new() -> Result<Self, anyhow::Error> {
-        // Initialize with AllMiniLML6V2 which is a good balance of speed and quality
-        let model = TextEmbedding::try_new(InitOptions::new(EmbeddingModel::AllMiniLML6V2))?;
-        Ok(Self {
-            model: Mutex::new(model),
-        })
+    // Nothing is loaded or downloaded until a model is used
+    pub fn new(models_dir: PathBuf) -> Self {
+        Self {
+            models_dir,
+            selected: Mutex::new(EmbeddingModelChoice::default()),
+            loaded: Mutex::new(None),
+            last_error: Mutex::new(None),
+        }
+    }
+
+    // Switch models; the previous one is unloaded on the next embedding call
+    pub fn select(&self, model: EmbeddingModelChoice) {
+        if let Ok(mut selected) = self.selected.lock() {
+            *selected = model;
+        }
+    }
+
+    pub fn selected(&self) -> EmbeddingModelChoice {
+        self.selected
+            .lock()
+            .map(|selected| *selected)
+            .unwrap_or_default()
+    }
+
+    // Load the selected model now instead of on the first embedding call
+    pub fn warm_up(&self) -> Result<(), anyhow::Error> {
+        self.with_model(|_, _| Ok(()))
     }
 
     pub fn embed_documents(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, anyhow::Error> {
-        let model = self.model.lock().unwrap();
-        // Batch embedding
-        let embeddings = model.embed(texts, None)?;
-        Ok(embeddings)
+        self.with_model(|selected, model| {
+            let prefix = selected.input_prefix();
+            let texts = if prefix.is_empty() {
+                texts
+            } else {
+                texts
+                    .into_iter()
+                    .map(|text| format!("{}{}", prefix, text))
+                    .collect()
+            };
+            // Batch embedding
+            let embeddings = model.embed(texts, None)?;
+            Ok(embeddings)
+        })
+    }
+
+    // Run f with the selected model, loading it first if another one (or none) is loaded
+    fn with_model<R>(
+        &self,
+        f: impl FnOnce(EmbeddingModelChoice, &TextEmbedding) -> Result<R, anyhow::Error>,
+    ) -> Result<R, anyhow::Error> {
+        let selected = self.selected();
+        let mut loaded = self
+            .loaded
+            .lock()
+            .map_err(|_| anyhow::anyhow!("embedding engine is unavailable"))?;
+        if loaded.as_ref().map(|(model, _)| *model) != Some(selected) {
+            *loaded = None;
+            let result = self.load(selected);
+            if let Ok(mut last_error) = self.last_error.lock() {
+                *last_error = result.as_ref().err().map(|e| e.to_string());
+            }
+            *loaded = Some((selected, result?));
+            tracing::info!(target: "ai", "embedding model loaded: model={:?}", selected);
+        }
+        let Some((_, model)) = loaded.as_ref() else {
+            anyhow::bail!("embedding model failed to load");
+        };
+        f(selected, model)
     }
 
     pub fn cosine_similarity(vec1: &[f32], vec2: &[f32]) -> f32 {
@@ -32,4 +191,204 @@ impl EmbeddingEngine {
 
         dot_product / (magnitude1 * magnitude2)
     }
+
+    pub async fn status(&self, client: &Client) -> EmbeddingStatus {
+        let models = EmbeddingModelChoice::ALL
+            .iter()
+            .filter_map(|model| {
+                let embedding_model = model.model();
+                let info = TextEmbedding::get_model_info(&embedding_model).ok()?;
+                Some(EmbeddingModelInfo {
+                    model: *model,
+                    model_code: info.model_code.clone(),
+                    dim: info.dim,
+                    multilingual: model.multilingual(),
+                    downloaded: self.is_downloaded(*model),
+                })
+            })
+            .collect();
+        // Never wait on a model that is still loading
+        let (loaded, busy) = match self.loaded.try_lock() {
+            Ok(loaded) => (loaded.as_ref().map(|(model, _)| *model), false),
+            Err(TryLockError::WouldBlock) => (None, true),
+            Err(TryLockError::Poisoned(_)) => (None, false),
+        };
+        EmbeddingStatus {
+            selected: self.selected(),
+            loaded,
+            busy,
+            online: probe_online(client).await,
+            last_error: self.last_error.lock().ok().and_then(|e| e.clone()),
+            models,
+        }
+    }
+
+    pub fn is_downloaded(&self, model: EmbeddingModelChoice) -> bool {
+        model_files(model)
+            .map(|(code, files)| {
+                let dir = self.model_dir(&code);
+                files.iter().all(|file| dir.join(file).is_file())
+            })
+            .unwrap_or(false)
+    }
+
+    // Fetch the model files missing from the models directory. Each file is streamed to a .part
+    // file and renamed when complete, so an interrupted download never looks finished.
+    pub async fn download(
+        &self,
+        client: &Client,
+        model: EmbeddingModelChoice,
+        on_progress: impl Fn(EmbeddingDownloadProgress),
+    ) -> Result<(), ApiError> {
+        let (code, files) = model_files(model).map_err(|e| ApiError {
+            code: ErrorCode::InvalidInput,
+            message: format!("Unknown embedding model: {}", e),
+            details: None,
+        })?;
+        let dir = self.model_dir(&code);
+        let missing: Vec<&String> = files
+            .iter()
+            .filter(|file| !dir.join(file).is_file())
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        if !probe_online(client).await {
+            return Err(offline_error(model));
+        }
+
+        let file_count = missing.len();
+        for (file_index, file) in missing.into_iter().enumerate() {
+            let target = dir.join(file);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(download_io_error)?;
+            }
+            let url = format!("{}/{}/resolve/main/{}", HF_ENDPOINT, code, file);
+            let mut response = client
+                .get(&url)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| {
+                    if e.is_connect() || e.is_timeout() {
+                        offline_error(model)
+                    } else {
+                        download_error(file, e)
+                    }
+                })?;
+
+            let mut progress = EmbeddingDownloadProgress {
+                model,
+                file: file.clone(),
+                file_index,
+                file_count,
+                downloaded_bytes: 0,
+                total_bytes: response.content_length(),
+                done: false,
+            };
+            let partial = target.with_extension("part");
+            let mut out = fs::File::create(&partial).map_err(download_io_error)?;
+            while let Some(chunk) = response
+                .chunk()
+                .await
+                .map_err(|e| download_error(file, e))?
+            {
+                out.write_all(&chunk).map_err(download_io_error)?;
+                progress.downloaded_bytes += chunk.len() as u64;
+                on_progress(progress.clone());
+            }
+            out.sync_all().map_err(download_io_error)?;
+            drop(out);
+            fs::rename(&partial, &target).map_err(download_io_error)?;
+
+            progress.done = file_index + 1 == file_count;
+            on_progress(progress);
+        }
+        tracing::info!(target: "ai", "embedding model downloaded: model={}", code);
+        Ok(())
+    }
+
+    fn model_dir(&self, model_code: &str) -> PathBuf {
+        self.models_dir.join(model_code.replace('/', "--"))
+    }
+
+    fn load(&self, model: EmbeddingModelChoice) -> Result<TextEmbedding, anyhow::Error> {
+        let (code, files) = model_files(model)?;
+        let dir = self.model_dir(&code);
+        if !files.iter().all(|file| dir.join(file).is_file()) {
+            anyhow::bail!(
+                "embedding model {} is not downloaded; run download_embedding_model first",
+                code
+            );
+        }
+        let read = |file: &str| fs::read(dir.join(file));
+        let tokenizer_files = TokenizerFiles {
+            tokenizer_file: read("tokenizer.json")?,
+            config_file: read("config.json")?,
+            special_tokens_map_file: read("special_tokens_map.json")?,
+            tokenizer_config_file: read("tokenizer_config.json")?,
+        };
+        let embedding_model = model.model();
+        let info = TextEmbedding::get_model_info(&embedding_model)?;
+        let mut user_model =
+            UserDefinedEmbeddingModel::new(read(&info.model_file)?, tokenizer_files)
+                .with_quantization(TextEmbedding::get_quantization_mode(&embedding_model));
+        if let Some(pooling) = TextEmbedding::get_default_pooling_method(&embedding_model) {
+            user_model = user_model.with_pooling(pooling);
+        }
+        TextEmbedding::try_new_from_user_defined(user_model, InitOptionsUserDefined::new())
+    }
+}
+
+// Repository and file list of a model
+fn model_files(model: EmbeddingModelChoice) -> Result<(String, Vec<String>), anyhow::Error> {
+    let embedding_model = model.model();
+    let info = TextEmbedding::get_model_info(&embedding_model)?;
+    let files = std::iter::once(info.model_file.clone())
+        .chain(info.additional_files.iter().cloned())
+        .chain(TOKENIZER_FILES.iter().map(|file| file.to_string()))
+        .collect();
+    Ok((info.model_code.clone(), files))
+}
+
+// Whether the model host answers at all; any HTTP status counts as online
+async fn probe_online(client: &Client) -> bool {
+    client
+        .head(HF_ENDPOINT)
+        .timeout(PROBE_TIMEOUT)
+        .send()
+        .await
+        .is_ok()
+}
+
+fn offline_error(model: EmbeddingModelChoice) -> ApiError {
+    ApiError {
+        code: ErrorCode::Offline,
+        message: "Cannot reach the model host; connect to the internet to download the model"
+            .to_string(),
+        details: Some(serde_json::json!({ "model": model, "host": HF_ENDPOINT })),
+    }
+}
+
+fn download_error(file: &str, err: reqwest::Error) -> ApiError {
+    ApiError {
+        code: ErrorCode::AiRequestFailed,
+        message: format!("Failed to download {}: {}", file, err),
+        details: err
+            .status()
+            .map(|status| serde_json::json!({ "status": status.as_u16() })),
+    }
+}
+
+fn download_io_error(err: std::io::Error) -> ApiError {
+    ApiError {
+        code: ErrorCode::WriteFailed,
+        message: format!("Failed to save embedding model: {}", err),
+        details: None,
+    }
+}
+
+// Directory holding downloaded models, under the app's data directory
+pub fn models_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("models")
 }
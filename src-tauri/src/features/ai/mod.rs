@@ -1 +1,5 @@
 pub mod embedding;
+pub mod ocr;
+pub mod rate_limit;
+pub mod semantic_index;
+pub mod transcription;
@@ -0,0 +1,110 @@
+use std::path::Path;
+use std::process::Command;
+
+use reqwest::Client;
+
+use crate::ipc::{map_read_error, ApiError, ErrorCode};
+use crate::repo::settings_repo::{AiSettings, OcrProvider};
+use crate::services::ai_service::{AiService, TokenUsage};
+
+// Larger images are rejected by the hosted vision APIs
+pub const MAX_IMAGE_BYTES: usize = 20 * 1024 * 1024;
+
+// Image formats accepted for OCR, by extension
+fn image_mime(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    Some(match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "webp" => "image/webp",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        "tif" | "tiff" => "image/tiff",
+        _ => return None,
+    })
+}
+
+fn ocr_error(message: String, details: Option<serde_json::Value>) -> ApiError {
+    ApiError {
+        code: ErrorCode::OcrFailed,
+        message,
+        details,
+    }
+}
+
+// Recognize the text in an image file with the provider chosen in the AI settings.
+// `image_path` is the resolved file on disk.
+pub async fn extract_text(
+    client: &Client,
+    settings: &AiSettings,
+    image_path: &Path,
+) -> Result<(String, TokenUsage), ApiError> {
+    let Some(mime) = image_mime(image_path) else {
+        return Err(ApiError {
+            code: ErrorCode::InvalidInput,
+            message: "File is not a supported image".to_string(),
+            details: Some(serde_json::json!({ "path": image_path.to_string_lossy() })),
+        });
+    };
+
+    let (text, usage) = match settings.ocr.provider {
+        OcrProvider::Api => {
+            let image = std::fs::read(image_path).map_err(map_read_error)?;
+            if image.len() > MAX_IMAGE_BYTES {
+                return Err(ApiError {
+                    code: ErrorCode::InvalidInput,
+                    message: "Image is larger than the vision API accepts".to_string(),
+                    details: Some(serde_json::json!({
+                        "bytes": image.len(),
+                        "max_bytes": MAX_IMAGE_BYTES,
+                    })),
+                });
+            }
+            AiService::new(client.clone(), settings.clone())
+                .read_image_text(mime, &image)
+                .await?
+        }
+        OcrProvider::Local => {
+            let settings = settings.clone();
+            let image_path = image_path.to_path_buf();
+            let text = tauri::async_runtime::spawn_blocking(move || {
+                extract_text_local(&settings, &image_path)
+            })
+            .await
+            .map_err(|e| ocr_error(format!("OCR task failed: {}", e), None))??;
+            (text, TokenUsage::default())
+        }
+    };
+
+    Ok((text.trim().to_string(), usage))
+}
+
+// Tesseract writes the recognized text to stdout when the output base is "stdout"
+fn extract_text_local(settings: &AiSettings, image_path: &Path) -> Result<String, ApiError> {
+    let ocr = &settings.ocr;
+    let output = Command::new(&ocr.tesseract_binary)
+        .arg(image_path)
+        .arg("stdout")
+        .arg("-l")
+        .arg(&ocr.tesseract_languages)
+        .output()
+        .map_err(|e| {
+            ocr_error(
+                format!("Failed to run tesseract: {}", e),
+                Some(serde_json::json!({ "tesseract_binary": ocr.tesseract_binary })),
+            )
+        })?;
+    if !output.status.success() {
+        return Err(ocr_error(
+            "tesseract exited with an error".to_string(),
+            Some(serde_json::json!({
+                "status": output.status.code(),
+                "stderr": String::from_utf8_lossy(&output.stderr).trim(),
+            })),
+        ));
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout).to_string();
+    tracing::info!(target: "ai", "image text read locally: languages={}, chars={}", ocr.tesseract_languages, text.chars().count());
+    Ok(text)
+}
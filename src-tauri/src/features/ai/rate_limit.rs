@@ -0,0 +1,37 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+// Start times of the requests made to each provider endpoint within the last minute
+fn windows() -> &'static Mutex<HashMap<String, VecDeque<Instant>>> {
+    static WINDOWS: OnceLock<Mutex<HashMap<String, VecDeque<Instant>>>> = OnceLock::new();
+    WINDOWS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// Take a request slot for the endpoint, or return how long until one frees up.
+// A ceiling of 0 means unlimited.
+pub fn acquire(endpoint: &str, requests_per_minute: u32) -> Result<(), Duration> {
+    if requests_per_minute == 0 {
+        return Ok(());
+    }
+    // A poisoned limiter stops limiting rather than blocking every AI call
+    let Ok(mut windows) = windows().lock() else {
+        return Ok(());
+    };
+    let now = Instant::now();
+    let window = windows.entry(endpoint.to_string()).or_default();
+    while window
+        .front()
+        .is_some_and(|start| now.duration_since(*start) >= WINDOW)
+    {
+        window.pop_front();
+    }
+    if window.len() >= requests_per_minute as usize {
+        let oldest = window.front().copied().unwrap_or(now);
+        return Err(WINDOW.saturating_sub(now.duration_since(oldest)));
+    }
+    window.push_back(now);
+    Ok(())
+}
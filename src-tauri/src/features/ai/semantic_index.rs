@@ -0,0 +1,398 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::domain::chunking::{self, ChunkConfig, IndexedChunk, NoteChunk};
+use crate::domain::links;
+use crate::domain::search::{self, HybridSearchHit};
+use crate::features::ai::embedding::EmbeddingEngine;
+use crate::ipc::{ApiError, ErrorCode};
+use crate::repo::planning_repo::PlanningRepo;
+use crate::repo::settings_repo;
+use crate::services::vault_service;
+
+// Chunks embedded per model call
+const EMBED_BATCH_SIZE: usize = 32;
+// Notes each side of hybrid search contributes per requested result
+const HYBRID_CANDIDATES_PER_HIT: usize = 3;
+// Length of the excerpt shown when the keyword side has no snippet for a note
+const EXCERPT_CHARS: usize = 160;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexWarning {
+    pub code: ErrorCode,
+    pub message: String,
+    pub path: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct HybridSearchResponse {
+    pub hits: Vec<HybridSearchHit>,
+    pub semantic: bool, // False when only keyword matches were available
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RelatedNote {
+    pub path: String,
+    pub score: f32, // Cosine similarity between the two notes' mean embeddings
+    pub heading: Option<String>, // Section of the related note closest to the current note
+    pub anchor: Option<String>,
+    pub snippet: String,
+}
+
+#[derive(Default, Serialize)]
+pub struct SemanticIndexReport {
+    pub model: String,
+    pub indexed: usize,              // Notes (re)embedded
+    pub unchanged: usize,            // Notes skipped because content and model match the index
+    pub removed: usize,              // Notes dropped from the index because they no longer exist
+    pub chunks: usize,               // Chunks written
+    pub warnings: Vec<IndexWarning>, // Scan problems and notes that could not be read
+}
+
+pub fn embedding_error(err: anyhow::Error) -> ApiError {
+    ApiError {
+        code: ErrorCode::EmbeddingFailed,
+        message: format!("Embedding failed: {}", err),
+        details: None,
+    }
+}
+
+pub fn open_repo(vault_root: &Path) -> Result<PlanningRepo, ApiError> {
+    let layout = settings_repo::get_planning_settings(vault_root)
+        .unwrap_or_default()
+        .layout;
+    PlanningRepo::new(vault_root, &layout)
+}
+
+fn content_hash(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+// Chunk and embed one note, replacing what the index held for it
+fn index_note(
+    repo: &PlanningRepo,
+    engine: &EmbeddingEngine,
+    path: &str,
+    content: &str,
+    hash: &str,
+) -> Result<Vec<(NoteChunk, Vec<f32>)>, ApiError> {
+    let chunks = chunking::chunk_markdown(content, ChunkConfig::default());
+    let mut embedded: Vec<(NoteChunk, Vec<f32>)> = Vec::with_capacity(chunks.len());
+    for batch in chunks.chunks(EMBED_BATCH_SIZE) {
+        let inputs = batch.iter().map(NoteChunk::embedding_input).collect();
+        let embeddings = engine.embed_documents(inputs).map_err(embedding_error)?;
+        embedded.extend(batch.iter().cloned().zip(embeddings));
+    }
+    repo.replace_note_chunks(path, hash, engine.selected().id(), &embedded)?;
+    Ok(embedded)
+}
+
+// Chunk and embed every markdown note whose content or embedding model changed since it was
+// last indexed, and drop notes that were deleted. `full` re-embeds everything.
+pub fn rebuild(
+    vault_root: &Path,
+    engine: &EmbeddingEngine,
+    full: bool,
+) -> Result<SemanticIndexReport, ApiError> {
+    let model = engine.selected().id();
+    let repo = open_repo(vault_root)?;
+    let indexed = repo.list_note_index()?;
+    let (files, warnings) = vault_service::list_markdown_files(vault_root)?;
+    let mut report = SemanticIndexReport {
+        model: model.to_string(),
+        warnings: warnings
+            .into_iter()
+            .map(|warning| IndexWarning {
+                code: warning.code,
+                message: warning.message,
+                path: warning.path,
+            })
+            .collect(),
+        ..SemanticIndexReport::default()
+    };
+
+    let present: HashSet<&String> = files.iter().collect();
+    for path in indexed.keys().filter(|path| !present.contains(path)) {
+        repo.remove_note_chunks(path)?;
+        report.removed += 1;
+    }
+
+    for path in &files {
+        let note = match vault_service::read_text_file(vault_root, Path::new(path)) {
+            Ok(note) => note,
+            Err(err) => {
+                report.warnings.push(IndexWarning {
+                    code: err.code,
+                    message: err.message,
+                    path: Some(path.clone()),
+                });
+                continue;
+            }
+        };
+        let hash = content_hash(&note.content);
+        let current = indexed.get(path);
+        if !full
+            && current.is_some_and(|(old_hash, old_model)| *old_hash == hash && old_model == model)
+        {
+            report.unchanged += 1;
+            continue;
+        }
+
+        report.chunks += index_note(&repo, engine, path, &note.content, &hash)?.len();
+        report.indexed += 1;
+    }
+
+    tracing::info!(target: "ai", "semantic index rebuilt: model={}, indexed={}, unchanged={}, removed={}, chunks={}", model, report.indexed, report.unchanged, report.removed, report.chunks);
+    Ok(report)
+}
+
+struct KeywordMatch {
+    chunk: NoteChunk,
+    score: Option<f64>, // None for unranked substring matches
+    snippet: Option<String>,
+}
+
+fn excerpt(text: &str) -> String {
+    let mut excerpt: String = text.chars().take(EXCERPT_CHARS).collect();
+    if excerpt.len() < text.len() {
+        excerpt.push('…');
+    }
+    excerpt
+}
+
+// Keep the first (best) entry per note
+fn best_per_note<T>(ranked: Vec<(String, T)>) -> Vec<(String, T)> {
+    let mut seen = HashSet::new();
+    ranked
+        .into_iter()
+        .filter(|(path, _)| seen.insert(path.clone()))
+        .collect()
+}
+
+// Search indexed notes by keyword and by meaning, fusing both rankings by reciprocal rank.
+// Each note appears once, located at its best matching chunk. Without a usable embedding
+// model the keyword matches are returned alone.
+pub fn search_hybrid(
+    vault_root: &Path,
+    engine: &EmbeddingEngine,
+    query: &str,
+    limit: usize,
+) -> Result<HybridSearchResponse, ApiError> {
+    let terms = search::query_terms(query);
+    if terms.is_empty() || limit == 0 {
+        return Ok(HybridSearchResponse {
+            hits: Vec::new(),
+            semantic: false,
+        });
+    }
+    let candidates = limit * HYBRID_CANDIDATES_PER_HIT;
+    let repo = open_repo(vault_root)?;
+
+    // Chunks come back best first; a short term falls back to unranked substring matches
+    let keyword: Vec<(String, KeywordMatch)> = match search::fts_match_expr(&terms, true) {
+        Some(match_expr) => repo
+            .search_note_chunk_fts(&match_expr, candidates * 2)?
+            .into_iter()
+            .map(|(path, chunk, score, snippet)| {
+                let snippet = snippet
+                    .contains(search::HIGHLIGHT_OPEN)
+                    .then(|| search::highlight_html(&snippet));
+                (
+                    path,
+                    KeywordMatch {
+                        chunk,
+                        score: Some(score),
+                        snippet,
+                    },
+                )
+            })
+            .collect(),
+        None => repo
+            .search_note_chunk_like(&terms, candidates * 2)?
+            .into_iter()
+            .map(|(path, chunk)| {
+                (
+                    path,
+                    KeywordMatch {
+                        chunk,
+                        score: None,
+                        snippet: None,
+                    },
+                )
+            })
+            .collect(),
+    };
+    let mut keyword = best_per_note(keyword);
+    keyword.truncate(candidates);
+
+    let model = engine.selected().id();
+    let chunks = repo.list_note_chunks(model)?;
+    let query_embedding = if chunks.is_empty() {
+        None
+    } else {
+        match engine.embed_documents(vec![query.to_string()]) {
+            Ok(mut embeddings) => embeddings.pop(),
+            Err(err) => {
+                tracing::warn!(target: "ai", "hybrid search without vectors: model={}, error={}", model, err);
+                None
+            }
+        }
+    };
+    let semantic = query_embedding.is_some();
+    let mut vector: Vec<(String, (NoteChunk, f32))> = match &query_embedding {
+        Some(query_embedding) => chunks
+            .into_iter()
+            .map(|indexed| {
+                let score = EmbeddingEngine::cosine_similarity(query_embedding, &indexed.embedding);
+                (indexed.path, (indexed.chunk, score))
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    vector.sort_by(|a, b| b.1 .1.total_cmp(&a.1 .1));
+    let mut vector = best_per_note(vector);
+    vector.truncate(candidates);
+
+    let keyword_paths: Vec<String> = keyword.iter().map(|(path, _)| path.clone()).collect();
+    let vector_paths: Vec<String> = vector.iter().map(|(path, _)| path.clone()).collect();
+    let mut keyword: HashMap<String, KeywordMatch> = keyword.into_iter().collect();
+    let mut vector: HashMap<String, (NoteChunk, f32)> = vector.into_iter().collect();
+
+    let hits = search::reciprocal_rank_fusion(&[&keyword_paths, &vector_paths])
+        .into_iter()
+        .take(limit)
+        .filter_map(|(path, score, ranks)| {
+            let keyword_hit = keyword.remove(&path);
+            let vector_hit = vector.remove(&path);
+            let vector_score = vector_hit.as_ref().map(|(_, score)| *score);
+            // The keyword chunk carries the highlighted snippet, so it locates the hit when present
+            let (chunk, keyword_score, snippet) = match (keyword_hit, vector_hit) {
+                (Some(matched), _) => (matched.chunk, matched.score, matched.snippet),
+                (None, Some((chunk, _))) => (chunk, None, None),
+                (None, None) => return None,
+            };
+            Some(HybridSearchHit {
+                snippet: snippet.unwrap_or_else(|| search::escape_html(&excerpt(&chunk.text))),
+                path,
+                heading: chunk.heading,
+                anchor: chunk.anchor,
+                start: chunk.start,
+                end: chunk.end,
+                score,
+                keyword_rank: ranks[0],
+                keyword_score,
+                vector_rank: ranks[1],
+                vector_score,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    tracing::info!(target: "ai", "hybrid search: hits={}, keyword_candidates={}, vector_candidates={}, semantic={}", hits.len(), keyword_paths.len(), vector_paths.len(), semantic);
+    Ok(HybridSearchResponse { hits, semantic })
+}
+
+fn mean_embedding<'a>(embeddings: impl Iterator<Item = &'a Vec<f32>>) -> Option<Vec<f32>> {
+    let mut sum: Option<Vec<f32>> = None;
+    let mut count = 0;
+    for embedding in embeddings {
+        let sum = sum.get_or_insert_with(|| vec![0.0; embedding.len()]);
+        for (total, value) in sum.iter_mut().zip(embedding) {
+            *total += value;
+        }
+        count += 1;
+    }
+    sum.map(|sum| sum.into_iter().map(|total| total / count as f32).collect())
+}
+
+// Whether a link extracted from a note points at `path`; a bare [[Note]] matches by file name
+fn link_points_to(link: &str, path: &str) -> bool {
+    link == path || (!link.contains('/') && path.rsplit('/').next() == Some(link))
+}
+
+fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+// Notes whose meaning is closest to the given note, leaving out notes it already links to or
+// that link to it. The note itself is (re)indexed first when the index is stale for it.
+pub fn suggest_related_notes(
+    vault_root: &Path,
+    engine: &EmbeddingEngine,
+    path: &str,
+    limit: usize,
+) -> Result<Vec<RelatedNote>, ApiError> {
+    let path = links::normalize_note_path(path).ok_or_else(|| ApiError {
+        code: ErrorCode::PathOutsideVault,
+        message: "Note path is outside the vault".to_string(),
+        details: Some(serde_json::json!({ "path": path })),
+    })?;
+    let model = engine.selected().id();
+    let repo = open_repo(vault_root)?;
+    let note = vault_service::read_text_file(vault_root, Path::new(&path))?;
+    let hash = content_hash(&note.content);
+    let current = repo
+        .list_note_index()?
+        .remove(&path)
+        .is_some_and(|(old_hash, old_model)| old_hash == hash && old_model == model);
+    if !current {
+        index_note(&repo, engine, &path, &note.content, &hash)?;
+    }
+
+    let mut by_note: HashMap<String, Vec<IndexedChunk>> = HashMap::new();
+    for indexed in repo.list_note_chunks(model)? {
+        by_note
+            .entry(indexed.path.clone())
+            .or_default()
+            .push(indexed);
+    }
+    let Some(own_chunks) = by_note.remove(&path) else {
+        return Ok(Vec::new());
+    };
+    let Some(centroid) = mean_embedding(own_chunks.iter().map(|indexed| &indexed.embedding)) else {
+        return Ok(Vec::new());
+    };
+    let outgoing = links::extract_note_links(&note.content, parent_dir(&path));
+
+    let mut related: Vec<RelatedNote> = by_note
+        .into_iter()
+        .filter(|(other, _)| !outgoing.iter().any(|link| link_points_to(link, other)))
+        .filter(|(other, chunks)| {
+            let text: Vec<&str> = chunks
+                .iter()
+                .map(|indexed| indexed.chunk.text.as_str())
+                .collect();
+            !links::extract_note_links(&text.join("\n\n"), parent_dir(other))
+                .iter()
+                .any(|link| link_points_to(link, &path))
+        })
+        .filter_map(|(other, chunks)| {
+            let other_centroid = mean_embedding(chunks.iter().map(|indexed| &indexed.embedding))?;
+            let closest = chunks.into_iter().max_by(|a, b| {
+                EmbeddingEngine::cosine_similarity(&centroid, &a.embedding)
+                    .total_cmp(&EmbeddingEngine::cosine_similarity(&centroid, &b.embedding))
+            })?;
+            Some(RelatedNote {
+                path: other,
+                score: EmbeddingEngine::cosine_similarity(&centroid, &other_centroid),
+                heading: closest.chunk.heading,
+                anchor: closest.chunk.anchor,
+                snippet: excerpt(&closest.chunk.text),
+            })
+        })
+        .collect();
+    related.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    related.truncate(limit);
+
+    tracing::info!(target: "ai", "related notes suggested: path={}, model={}, related={}", path, model, related.len());
+    Ok(related)
+}
@@ -0,0 +1,138 @@
+use std::path::Path;
+use std::process::Command;
+
+use reqwest::Client;
+use uuid::Uuid;
+
+use crate::ipc::{ApiError, ErrorCode};
+use crate::repo::settings_repo::{AiSettings, TranscriptionProvider};
+use crate::services::ai_service::AiService;
+
+// Upload limit of the hosted transcription APIs
+pub const MAX_API_AUDIO_BYTES: usize = 25 * 1024 * 1024;
+
+fn transcription_error(message: String, details: Option<serde_json::Value>) -> ApiError {
+    ApiError {
+        code: ErrorCode::TranscriptionFailed,
+        message,
+        details,
+    }
+}
+
+// Turn recorded audio into text with the provider chosen in the AI settings.
+// `file_name` only needs the right extension; providers sniff the format from it.
+pub async fn transcribe(
+    client: &Client,
+    settings: &AiSettings,
+    file_name: &str,
+    audio: Vec<u8>,
+) -> Result<String, ApiError> {
+    if audio.is_empty() {
+        return Err(ApiError {
+            code: ErrorCode::InvalidInput,
+            message: "Audio is empty".to_string(),
+            details: None,
+        });
+    }
+
+    let transcript = match settings.transcription.provider {
+        TranscriptionProvider::Api => {
+            if audio.len() > MAX_API_AUDIO_BYTES {
+                return Err(ApiError {
+                    code: ErrorCode::InvalidInput,
+                    message: "Audio is larger than the transcription API accepts".to_string(),
+                    details: Some(serde_json::json!({
+                        "bytes": audio.len(),
+                        "max_bytes": MAX_API_AUDIO_BYTES,
+                    })),
+                });
+            }
+            let mut api_settings = settings.clone();
+            if !settings.transcription.base_url.is_empty() {
+                api_settings.base_url = settings.transcription.base_url.clone();
+            }
+            if !settings.transcription.api_key.is_empty() {
+                api_settings.api_key = settings.transcription.api_key.clone();
+            }
+            AiService::new(client.clone(), api_settings)
+                .transcribe(file_name, &audio)
+                .await?
+        }
+        TranscriptionProvider::Local => {
+            let settings = settings.clone();
+            let file_name = file_name.to_string();
+            tauri::async_runtime::spawn_blocking(move || {
+                transcribe_local(&settings, &file_name, &audio)
+            })
+            .await
+            .map_err(|e| transcription_error(format!("Transcription task failed: {}", e), None))??
+        }
+    };
+
+    Ok(transcript.trim().to_string())
+}
+
+// Run whisper.cpp over the audio written to a temporary file; it prints the transcript to stdout
+fn transcribe_local(
+    settings: &AiSettings,
+    file_name: &str,
+    audio: &[u8],
+) -> Result<String, ApiError> {
+    let transcription = &settings.transcription;
+    if transcription.whisper_model.is_empty() || !Path::new(&transcription.whisper_model).is_file()
+    {
+        return Err(ApiError {
+            code: ErrorCode::InvalidInput,
+            message: "Local transcription needs a whisper model file in the AI settings"
+                .to_string(),
+            details: Some(serde_json::json!({ "whisper_model": transcription.whisper_model })),
+        });
+    }
+
+    let extension = Path::new(file_name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("wav");
+    let audio_path = std::env::temp_dir().join(format!("capture-{}.{}", Uuid::new_v4(), extension));
+    std::fs::write(&audio_path, audio).map_err(|e| {
+        transcription_error(format!("Failed to stage audio for whisper: {}", e), None)
+    })?;
+
+    let mut command = Command::new(&transcription.whisper_binary);
+    command
+        .arg("-m")
+        .arg(&transcription.whisper_model)
+        .arg("-f")
+        .arg(&audio_path)
+        .args(["--no-timestamps", "--no-prints"]);
+    if let Some(language) = &transcription.language {
+        command.arg("-l").arg(language);
+    }
+    let output = command.output();
+    let _ = std::fs::remove_file(&audio_path);
+
+    let output = output.map_err(|e| {
+        transcription_error(
+            format!("Failed to run whisper: {}", e),
+            Some(serde_json::json!({ "whisper_binary": transcription.whisper_binary })),
+        )
+    })?;
+    if !output.status.success() {
+        return Err(transcription_error(
+            "whisper exited with an error".to_string(),
+            Some(serde_json::json!({
+                "status": output.status.code(),
+                "stderr": String::from_utf8_lossy(&output.stderr).trim(),
+            })),
+        ));
+    }
+
+    let transcript = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    tracing::info!(target: "ai", "audio transcribed locally: audio_bytes={}, chars={}", audio.len(), transcript.chars().count());
+    Ok(transcript)
+}
@@ -0,0 +1,452 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::Utc;
+use ego_tree::{NodeId, NodeRef};
+use reqwest::header::{CONTENT_TYPE, USER_AGENT};
+use reqwest::{Client, Url};
+use scraper::{ElementRef, Html, Node, Selector};
+
+use crate::domain::clipping::{self, ClippedPage, CLIPPINGS_DIR};
+use crate::ipc::{ApiError, ErrorCode};
+use crate::paths::generate_slug;
+use crate::security::note_locks::WriteOrigin;
+use crate::security::path_policy;
+use crate::services::vault_service;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_PAGE_BYTES: usize = 5 * 1024 * 1024;
+const CLIPPER_USER_AGENT: &str = "Mozilla/5.0 (compatible; tauri-planning-app clipper)";
+// Paragraphs shorter than this are navigation, captions or buttons
+const MIN_PARAGRAPH_CHARS: usize = 25;
+
+// Never part of the readable content
+const DROPPED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "iframe", "form", "button", "input", "select", "textarea",
+    "svg", "canvas", "nav", "aside", "footer", "template", "object", "embed", "link", "meta",
+];
+const VOID_TAGS: &[&str] = &["img", "br", "hr"];
+// class/id words of page chrome; matched as word prefixes
+const UNLIKELY_HINTS: &[&str] = &[
+    "advert",
+    "banner",
+    "breadcrumb",
+    "combx",
+    "comment",
+    "cookie",
+    "disqus",
+    "footer",
+    "masthead",
+    "menu",
+    "modal",
+    "nav",
+    "newsletter",
+    "popup",
+    "promo",
+    "related",
+    "share",
+    "sharing",
+    "sidebar",
+    "social",
+    "sponsor",
+    "subscribe",
+];
+// Matched as whole words only, they are too short to be prefixes
+const UNLIKELY_WORDS: &[&str] = &["ad", "ads", "header", "extra"];
+const LIKELY_HINTS: &[&str] = &[
+    "article", "blog", "body", "content", "entry", "main", "post", "story", "text",
+];
+
+fn clip_failed(message: String, url: &str) -> ApiError {
+    ApiError {
+        code: ErrorCode::ClipFailed,
+        message,
+        details: Some(serde_json::json!({ "url": url })),
+    }
+}
+
+fn selector(css: &str) -> Selector {
+    Selector::parse(css).expect("static selector is valid")
+}
+
+fn collapsed_text(element: ElementRef<'_>) -> String {
+    element
+        .text()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn hint_words(element: ElementRef<'_>) -> Vec<String> {
+    let value = element.value();
+    format!(
+        "{} {}",
+        value.attr("class").unwrap_or(""),
+        value.id().unwrap_or("")
+    )
+    .to_lowercase()
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .map(str::to_string)
+    .collect()
+}
+
+fn has_hint(words: &[String], prefixes: &[&str]) -> bool {
+    words
+        .iter()
+        .any(|word| prefixes.iter().any(|prefix| word.starts_with(prefix)))
+}
+
+fn has_chrome_hint(words: &[String]) -> bool {
+    has_hint(words, UNLIKELY_HINTS)
+        || words
+            .iter()
+            .any(|word| UNLIKELY_WORDS.contains(&word.as_str()))
+}
+
+fn class_weight(element: ElementRef<'_>) -> f64 {
+    let words = hint_words(element);
+    let mut weight = 0.0;
+    if has_chrome_hint(&words) {
+        weight -= 25.0;
+    }
+    if has_hint(&words, LIKELY_HINTS) {
+        weight += 25.0;
+    }
+    weight
+}
+
+// Page chrome (menus, share bars, comments) that is not also marked as content
+fn is_unlikely(element: ElementRef<'_>) -> bool {
+    if matches!(element.value().name(), "html" | "body" | "article" | "main") {
+        return false;
+    }
+    let words = hint_words(element);
+    has_chrome_hint(&words) && !has_hint(&words, LIKELY_HINTS)
+}
+
+fn tag_weight(name: &str) -> f64 {
+    match name {
+        "article" => 10.0,
+        "div" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    }
+}
+
+// Share of an element's text that sits inside links
+fn link_density(element: ElementRef<'_>) -> f64 {
+    let total = collapsed_text(element).chars().count();
+    if total == 0 {
+        return 0.0;
+    }
+    let linked: usize = element
+        .select(&selector("a"))
+        .map(|link| collapsed_text(link).chars().count())
+        .sum();
+    linked as f64 / total as f64
+}
+
+// Readability-style scoring: each paragraph scores by length and commas, and the score flows
+// to its parent (full), grandparent (half) and great-grandparent (a sixth). The best scoring
+// container, discounted by link density, holds the article.
+fn score_candidates(document: &Html) -> HashMap<NodeId, f64> {
+    let mut scores: HashMap<NodeId, f64> = HashMap::new();
+    for paragraph in document.select(&selector("p, pre, td, blockquote")) {
+        if paragraph
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(is_unlikely)
+        {
+            continue;
+        }
+        let text = collapsed_text(paragraph);
+        let length = text.chars().count();
+        if length < MIN_PARAGRAPH_CHARS {
+            continue;
+        }
+        let commas = text.matches([',', '，', '、']).count() as f64;
+        let score = 1.0 + commas + (length as f64 / 100.0).min(3.0);
+        for (ancestor, divider) in paragraph
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .zip([1.0, 2.0, 6.0])
+        {
+            *scores
+                .entry(ancestor.id())
+                .or_insert_with(|| tag_weight(ancestor.value().name()) + class_weight(ancestor)) +=
+                score / divider;
+        }
+    }
+
+    for (id, score) in scores.iter_mut() {
+        if let Some(element) = document.tree.get(*id).and_then(ElementRef::wrap) {
+            *score *= 1.0 - link_density(element);
+        }
+    }
+    scores
+}
+
+// The top candidate plus siblings that look like part of the same article
+fn content_nodes<'a>(document: &'a Html, scores: &HashMap<NodeId, f64>) -> Vec<ElementRef<'a>> {
+    let top = scores
+        .iter()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .and_then(|(id, score)| {
+            let element = document.tree.get(*id).and_then(ElementRef::wrap)?;
+            Some((element, *score))
+        });
+    let Some((top, top_score)) = top else {
+        let fallback = ["article", "main", "body"]
+            .iter()
+            .find_map(|css| document.select(&selector(css)).next());
+        return fallback.into_iter().collect();
+    };
+    let Some(parent) = top.parent().and_then(ElementRef::wrap) else {
+        return vec![top];
+    };
+
+    let threshold = (top_score * 0.2).max(10.0);
+    parent
+        .child_elements()
+        .filter(|sibling| {
+            if sibling.id() == top.id() {
+                return true;
+            }
+            if scores
+                .get(&sibling.id())
+                .is_some_and(|score| *score >= threshold)
+            {
+                return true;
+            }
+            sibling.value().name() == "p"
+                && collapsed_text(*sibling).chars().count() > 80
+                && link_density(*sibling) < 0.25
+        })
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Re-serialize content without page chrome, keeping only the attributes markdown needs and
+// resolving links and images against the page URL
+fn write_clean_html(node: NodeRef<'_, Node>, base: &Url, title: &str, out: &mut String) {
+    match node.value() {
+        Node::Text(text) => out.push_str(&escape_html(text)),
+        Node::Element(element) => {
+            let name = element.name();
+            let Some(element_ref) = ElementRef::wrap(node) else {
+                return;
+            };
+            if DROPPED_TAGS.contains(&name) || is_unlikely(element_ref) {
+                return;
+            }
+            // The clipping supplies its own title heading
+            if name == "h1" && collapsed_text(element_ref) == title {
+                return;
+            }
+
+            out.push('<');
+            out.push_str(name);
+            for attr in ["href", "src", "alt", "title"] {
+                let value = match attr {
+                    // Lazy-loaded images keep the real source in data-src
+                    "src" => element.attr("data-src").or_else(|| element.attr("src")),
+                    _ => element.attr(attr),
+                };
+                let Some(value) = value else {
+                    continue;
+                };
+                let value = match attr {
+                    "href" | "src" => base
+                        .join(value)
+                        .map(|url| url.to_string())
+                        .unwrap_or_else(|_| value.to_string()),
+                    _ => value.to_string(),
+                };
+                out.push_str(&format!(" {}=\"{}\"", attr, escape_html(&value)));
+            }
+            out.push('>');
+            if VOID_TAGS.contains(&name) {
+                return;
+            }
+            for child in node.children() {
+                write_clean_html(child, base, title, out);
+            }
+            out.push_str(&format!("</{}>", name));
+        }
+        _ => {}
+    }
+}
+
+fn meta_content(document: &Html, css: &str) -> Option<String> {
+    document
+        .select(&selector(css))
+        .filter_map(|element| element.value().attr("content"))
+        .map(str::trim)
+        .find(|content| !content.is_empty())
+        .map(str::to_string)
+}
+
+// Pull the readable article and its metadata out of a page
+pub fn extract_page(html: &str, url: &Url) -> ClippedPage {
+    let document = Html::parse_document(html);
+    let first_text = |css: &str| {
+        document
+            .select(&selector(css))
+            .map(collapsed_text)
+            .find(|text| !text.is_empty())
+    };
+
+    let title = meta_content(&document, "meta[property='og:title']")
+        .or_else(|| first_text("title"))
+        .or_else(|| first_text("h1"))
+        .unwrap_or_else(|| url.host_str().unwrap_or(url.as_str()).to_string());
+    let byline = meta_content(&document, "meta[name='author']")
+        .or_else(|| meta_content(&document, "meta[property='article:author']"))
+        .filter(|author| !author.starts_with("http"))
+        .or_else(|| first_text("[rel='author'], .byline, .author"));
+    let site_name = meta_content(&document, "meta[property='og:site_name']")
+        .or_else(|| url.host_str().map(str::to_string));
+    let excerpt = meta_content(&document, "meta[property='og:description']")
+        .or_else(|| meta_content(&document, "meta[name='description']"));
+    let published =
+        meta_content(&document, "meta[property='article:published_time']").or_else(|| {
+            document
+                .select(&selector("time[datetime]"))
+                .find_map(|time| time.value().attr("datetime").map(str::to_string))
+        });
+
+    let scores = score_candidates(&document);
+    let mut content = String::new();
+    for node in content_nodes(&document, &scores) {
+        write_clean_html(*node, url, &title, &mut content);
+    }
+    let markdown = clipping::atx_headings(&html2md::parse_html(&content));
+
+    ClippedPage {
+        url: url.to_string(),
+        title,
+        byline,
+        site_name,
+        excerpt,
+        published,
+        markdown: markdown.trim().to_string(),
+    }
+}
+
+// Download a page as HTML; returns the final URL after redirects
+pub async fn fetch_page(client: &Client, url: &str) -> Result<(Url, String), ApiError> {
+    let parsed = Url::parse(url.trim())
+        .ok()
+        .filter(|parsed| matches!(parsed.scheme(), "http" | "https"))
+        .ok_or_else(|| ApiError {
+            code: ErrorCode::InvalidInput,
+            message: "Only http and https pages can be clipped".to_string(),
+            details: Some(serde_json::json!({ "url": url })),
+        })?;
+
+    let mut response = client
+        .get(parsed)
+        .header(USER_AGENT, CLIPPER_USER_AGENT)
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| clip_failed(format!("Failed to fetch page: {}", e), url))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ApiError {
+            code: ErrorCode::ClipFailed,
+            message: format!("Page returned HTTP {}", status.as_u16()),
+            details: Some(serde_json::json!({ "url": url, "status": status.as_u16() })),
+        });
+    }
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("text/html")
+        .to_lowercase();
+    if !content_type.contains("html") {
+        return Err(ApiError {
+            code: ErrorCode::InvalidInput,
+            message: "URL is not an HTML page".to_string(),
+            details: Some(serde_json::json!({ "url": url, "content_type": content_type })),
+        });
+    }
+    if response
+        .content_length()
+        .is_some_and(|length| length as usize > MAX_PAGE_BYTES)
+    {
+        return Err(clip_failed("Page is too large to clip".to_string(), url));
+    }
+
+    let final_url = response.url().clone();
+    // Chunked responses carry no Content-Length; stop reading once the cap is passed
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| clip_failed(format!("Failed to read page: {}", e), url))?
+    {
+        if body.len() + chunk.len() > MAX_PAGE_BYTES {
+            return Err(clip_failed("Page is too large to clip".to_string(), url));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok((final_url, String::from_utf8_lossy(&body).into_owned()))
+}
+
+// Write the clipping to a new note in the clippings folder; returns its vault-relative path
+pub fn save_clipping(vault_root: &Path, page: &ClippedPage) -> Result<String, ApiError> {
+    let content = clipping::render_clipping(page, &Utc::now().to_rfc3339())?;
+    path_policy::ensure_or_create_dir_in_vault(vault_root, &vault_root.join(CLIPPINGS_DIR))?;
+
+    let stem = generate_slug(&page.title);
+    for index in 0..100 {
+        let name = if index == 0 {
+            format!("{}.md", stem)
+        } else {
+            format!("{} ({}).md", stem, index)
+        };
+        let rel_path = format!("{}/{}", CLIPPINGS_DIR, name);
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(vault_root.join(&rel_path))
+        {
+            Ok(_file) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(err) => {
+                return Err(clip_failed(
+                    format!("Failed to create clipping: {}", err),
+                    &page.url,
+                ))
+            }
+        }
+        if let Err(err) = vault_service::write_text_file(
+            vault_root,
+            Path::new(&rel_path),
+            &content,
+            WriteOrigin::Background,
+        ) {
+            let _ = std::fs::remove_file(vault_root.join(&rel_path));
+            return Err(err);
+        }
+        return Ok(rel_path);
+    }
+    Err(clip_failed(
+        "Failed to allocate a clipping file name".to_string(),
+        &page.url,
+    ))
+}
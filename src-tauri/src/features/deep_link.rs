@@ -0,0 +1,175 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::domain::planning::{CreateTaskInput, OpenDailyInput, TaskStatus};
+use crate::features::http_api::PLANNING_CHANGED_EVENT;
+use crate::ipc::{ApiError, ErrorCode};
+use crate::services::planning_service::PlanningService;
+use crate::state::VaultState;
+
+pub const DEEP_LINK_SCHEME: &str = "planningapp";
+// Emitted after a deep link was handled so the frontend can navigate to the result
+pub const DEEP_LINK_EVENT: &str = "deep-link";
+const MAIN_WINDOW_LABEL: &str = "main";
+
+// Supported planningapp:// targets
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeepLink {
+    OpenTask { task_id: String },
+    OpenDailyToday,
+    Capture { text: String },
+}
+
+// Payload of DEEP_LINK_EVENT
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum DeepLinkEvent {
+    OpenTask { task_id: String, md_path: String },
+    OpenDaily { day: String, md_path: String },
+    Capture { task_id: String },
+}
+
+fn invalid_link(url: &str, message: &str) -> ApiError {
+    ApiError {
+        code: ErrorCode::InvalidInput,
+        message: message.to_string(),
+        details: Some(serde_json::json!({ "url": url })),
+    }
+}
+
+fn query_value(url: &Url, name: &str) -> Option<String> {
+    url.query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+pub fn parse(raw: &str) -> Result<DeepLink, ApiError> {
+    let url = Url::parse(raw).map_err(|_| invalid_link(raw, "Malformed deep link"))?;
+    if url.scheme() != DEEP_LINK_SCHEME {
+        return Err(invalid_link(raw, "Unsupported deep link scheme"));
+    }
+
+    // planningapp://task/open parses with "task" as host and "/open" as path
+    let host = url.host_str().unwrap_or_default();
+    let path = url.path().trim_matches('/');
+    match (host, path) {
+        ("task", "open") => query_value(&url, "id")
+            .map(|task_id| DeepLink::OpenTask { task_id })
+            .ok_or_else(|| invalid_link(raw, "task/open needs an id")),
+        ("daily", "today") => Ok(DeepLink::OpenDailyToday),
+        ("capture", "") => query_value(&url, "text")
+            .map(|text| DeepLink::Capture { text })
+            .ok_or_else(|| invalid_link(raw, "capture needs text")),
+        _ => Err(invalid_link(raw, "Unknown deep link")),
+    }
+}
+
+// Deep links passed on the command line when the OS launches the app for a URL
+pub fn links_from_args(args: &[String]) -> Vec<String> {
+    let prefix = format!("{}://", DEEP_LINK_SCHEME);
+    args.iter()
+        .filter(|arg| arg.starts_with(&prefix))
+        .cloned()
+        .collect()
+}
+
+fn execute(app_handle: &AppHandle, link: DeepLink) -> Result<DeepLinkEvent, ApiError> {
+    let vault_state = app_handle.state::<VaultState>();
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = vault_root.as_ref().ok_or_else(|| ApiError {
+        code: ErrorCode::VaultNotSelected,
+        message: "Vault not selected".to_string(),
+        details: None,
+    })?;
+    let service = PlanningService::new(app_handle, vault_path)?;
+
+    match link {
+        DeepLink::OpenTask { task_id } => {
+            let response = service.open_task_note(&task_id)?;
+            Ok(DeepLinkEvent::OpenTask {
+                task_id,
+                md_path: response.md_path,
+            })
+        }
+        DeepLink::OpenDailyToday => {
+            let day = service.today();
+            let response = service.open_daily(OpenDailyInput { day: day.clone() })?;
+            Ok(DeepLinkEvent::OpenDaily {
+                day,
+                md_path: response.md_path,
+            })
+        }
+        DeepLink::Capture { text } => {
+            let task = service.create_task(CreateTaskInput {
+                title: text,
+                description: None,
+                status: TaskStatus::Todo,
+                priority: None,
+                due_date: Some(service.today()),
+                board_id: None,
+                estimate_min: None,
+                tags: None,
+                labels: None,
+                subtasks: None,
+                periodicity: None,
+                scheduled_start: None,
+                scheduled_end: None,
+                note_path: None,
+                color: None,
+                op_id: None,
+            })?;
+            if let Err(e) = app_handle.emit(
+                PLANNING_CHANGED_EVENT,
+                serde_json::json!({ "source": "deep_link" }),
+            ) {
+                tracing::warn!(target: "deep_link", "failed to emit planning change: {}", e);
+            }
+            Ok(DeepLinkEvent::Capture { task_id: task.id })
+        }
+    }
+}
+
+pub fn focus_main_window(app_handle: &AppHandle) {
+    let Some(window) = app_handle.get_webview_window(MAIN_WINDOW_LABEL) else {
+        return;
+    };
+    let _ = window.unminimize();
+    let _ = window.show();
+    let _ = window.set_focus();
+}
+
+// Handle links opened while the app runs: macOS open events, and on Windows and Linux the links
+// a second instance forwards before exiting
+pub fn listen(app: &tauri::App) {
+    // Installers register the scheme; this covers dev builds and AppImages
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        tracing::warn!(target: "deep_link", "failed to register deep link scheme: {}", e);
+    }
+    let app_handle = app.handle().clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            dispatch(&app_handle, url.as_str());
+        }
+    });
+}
+
+// Route a deep link into the planning service, then raise the window and tell the frontend
+pub fn dispatch(app_handle: &AppHandle, raw: &str) {
+    let result = parse(raw).and_then(|link| execute(app_handle, link));
+    focus_main_window(app_handle);
+
+    match result {
+        Ok(event) => {
+            tracing::info!(target: "deep_link", "deep link handled: url={}", raw);
+            if let Err(e) = app_handle.emit(DEEP_LINK_EVENT, event) {
+                tracing::warn!(target: "deep_link", "failed to emit deep link event: {}", e);
+            }
+        }
+        Err(err) => {
+            tracing::warn!(target: "deep_link", "deep link failed: url={}, error={}", raw, err);
+        }
+    }
+}
@@ -0,0 +1,484 @@
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::Deserialize;
+
+use crate::domain::github::{self, GithubBoardLink, GithubIssueLink, GithubSyncReport};
+use crate::domain::planning::{CreateTaskInput, Task, TaskStatus, UpdateTaskInput};
+use crate::ipc::{ApiError, ErrorCode};
+use crate::services::planning_service::PlanningService;
+
+const API_BASE: &str = "https://api.github.com";
+const API_VERSION: &str = "2022-11-28";
+const USER_AGENT: &str = "tauri-planning-app";
+const PER_PAGE: usize = 100;
+// Requests left untouched so other tools using the same token keep working
+const RATE_LIMIT_RESERVE: i64 = 10;
+
+#[derive(Debug, Deserialize)]
+struct GithubLabel {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubMilestone {
+    due_on: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubIssue {
+    number: i64,
+    title: String,
+    body: Option<String>,
+    state: String, // "open" or "closed"
+    #[serde(default)]
+    labels: Vec<GithubLabel>,
+    milestone: Option<GithubMilestone>,
+    pull_request: Option<serde_json::Value>, // Present when the "issue" is a pull request
+}
+
+impl GithubIssue {
+    fn closed(&self) -> bool {
+        self.state == "closed"
+    }
+
+    fn label_names(&self) -> Vec<String> {
+        let names: Vec<String> = self.labels.iter().map(|label| label.name.clone()).collect();
+        github::sorted_labels(&names)
+    }
+}
+
+// Pending change from a task to its issue
+struct IssuePush {
+    link: GithubIssueLink,
+    closed: bool,
+    labels: Vec<String>,
+}
+
+fn rate_limited(reset_at: Option<i64>) -> ApiError {
+    ApiError {
+        code: ErrorCode::GithubRateLimited,
+        message: "GitHub rate limit reached".to_string(),
+        details: reset_at.map(|reset_at| serde_json::json!({ "reset_at": reset_at })),
+    }
+}
+
+fn request_failed(message: String) -> ApiError {
+    ApiError {
+        code: ErrorCode::GithubRequestFailed,
+        message,
+        details: None,
+    }
+}
+
+// REST client that tracks the remaining rate limit and stops before exhausting it
+struct GithubClient {
+    client: Client,
+    token: String,
+    remaining: Option<i64>,
+    reset_at: Option<i64>, // Unix seconds
+}
+
+impl GithubClient {
+    fn new(client: Client, token: String) -> Self {
+        Self {
+            client,
+            token,
+            remaining: None,
+            reset_at: None,
+        }
+    }
+
+    fn header_i64(response: &Response, name: &str) -> Option<i64> {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+    }
+
+    async fn send(&mut self, request: RequestBuilder) -> Result<Response, ApiError> {
+        if self
+            .remaining
+            .is_some_and(|remaining| remaining <= RATE_LIMIT_RESERVE)
+        {
+            return Err(rate_limited(self.reset_at));
+        }
+
+        let response = request
+            .bearer_auth(&self.token)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", API_VERSION)
+            .header("User-Agent", USER_AGENT)
+            .send()
+            .await
+            .map_err(|e| request_failed(format!("Failed to reach GitHub: {}", e)))?;
+
+        if let Some(remaining) = Self::header_i64(&response, "x-ratelimit-remaining") {
+            self.remaining = Some(remaining);
+        }
+        if let Some(reset_at) = Self::header_i64(&response, "x-ratelimit-reset") {
+            self.reset_at = Some(reset_at);
+        }
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        // Primary limits answer 403 with no requests left; secondary limits send Retry-After
+        let retry_after = Self::header_i64(&response, "retry-after");
+        if status == StatusCode::TOO_MANY_REQUESTS
+            || (status == StatusCode::FORBIDDEN
+                && (self.remaining == Some(0) || retry_after.is_some()))
+        {
+            if let Some(retry_after) = retry_after {
+                self.reset_at = Some(Utc::now().timestamp() + retry_after);
+            }
+            self.remaining = Some(0);
+            return Err(rate_limited(self.reset_at));
+        }
+
+        let body = response.text().await.unwrap_or_default();
+        Err(ApiError {
+            code: ErrorCode::GithubRequestFailed,
+            message: format!("GitHub returned {}", status),
+            details: Some(serde_json::json!({ "status": status.as_u16(), "body": body })),
+        })
+    }
+
+    // Issues (not pull requests) updated since the given time, oldest first
+    async fn list_issues(
+        &mut self,
+        repo: &str,
+        since: Option<&str>,
+    ) -> Result<Vec<GithubIssue>, ApiError> {
+        let mut issues = Vec::new();
+        for page in 1.. {
+            let mut query = vec![
+                ("state", "all".to_string()),
+                ("sort", "updated".to_string()),
+                ("direction", "asc".to_string()),
+                ("per_page", PER_PAGE.to_string()),
+                ("page", page.to_string()),
+            ];
+            if let Some(since) = since {
+                query.push(("since", since.to_string()));
+            }
+            let request = self
+                .client
+                .get(format!("{}/repos/{}/issues", API_BASE, repo))
+                .query(&query);
+            let batch: Vec<GithubIssue> =
+                self.send(request).await?.json().await.map_err(|e| {
+                    request_failed(format!("Failed to decode GitHub issues: {}", e))
+                })?;
+
+            let last_page = batch.len() < PER_PAGE;
+            issues.extend(
+                batch
+                    .into_iter()
+                    .filter(|issue| issue.pull_request.is_none()),
+            );
+            if last_page {
+                break;
+            }
+        }
+        Ok(issues)
+    }
+
+    async fn update_issue(
+        &mut self,
+        repo: &str,
+        issue_number: i64,
+        closed: bool,
+        labels: &[String],
+    ) -> Result<(), ApiError> {
+        let mut body = serde_json::json!({
+            "state": if closed { "closed" } else { "open" },
+            "labels": labels,
+        });
+        if closed {
+            body["state_reason"] = serde_json::json!("completed");
+        }
+        let request = self
+            .client
+            .patch(format!(
+                "{}/repos/{}/issues/{}",
+                API_BASE, repo, issue_number
+            ))
+            .json(&body);
+        self.send(request).await?;
+        Ok(())
+    }
+}
+
+fn task_closed(task: &Task) -> bool {
+    task.status == TaskStatus::Done
+}
+
+fn task_labels(task: &Task) -> Vec<String> {
+    github::sorted_labels(task.labels.as_deref().unwrap_or_default())
+}
+
+fn empty_update(task_id: &str) -> UpdateTaskInput {
+    UpdateTaskInput {
+        id: task_id.to_string(),
+        title: None,
+        description: None,
+        status: None,
+        priority: None,
+        tags: None,
+        labels: None,
+        subtasks: None,
+        periodicity: None,
+        due_date: None,
+        board_id: None,
+        color: None,
+        order_index: None,
+        estimate_min: None,
+        scheduled_start: None,
+        scheduled_end: None,
+        note_path: None,
+        archived: None,
+        op_id: None,
+        override_wip_limit: None,
+        rename_slug: None,
+    }
+}
+
+fn import_issue(
+    service: &PlanningService,
+    link: &GithubBoardLink,
+    issue: &GithubIssue,
+) -> Result<(), ApiError> {
+    // Todo tasks need a due date; the milestone's is the best hint GitHub has
+    let due_date = issue
+        .milestone
+        .as_ref()
+        .and_then(|milestone| milestone.due_on.as_deref())
+        .and_then(|due_on| due_on.get(..10))
+        .map(str::to_string)
+        .unwrap_or_else(|| service.today());
+    let labels = issue.label_names();
+
+    let task = service.create_task(CreateTaskInput {
+        title: issue.title.clone(),
+        description: issue.body.clone().filter(|body| !body.trim().is_empty()),
+        status: TaskStatus::Todo,
+        priority: None,
+        due_date: Some(due_date),
+        board_id: Some(link.board_id.clone()),
+        estimate_min: None,
+        tags: None,
+        labels: (!labels.is_empty()).then(|| labels.clone()),
+        subtasks: None,
+        periodicity: None,
+        scheduled_start: None,
+        scheduled_end: None,
+        note_path: None,
+        color: None,
+        // Makes a retried import after a failed link write return the same task
+        op_id: Some(format!("github:{}#{}", link.repo, issue.number)),
+    })?;
+
+    service.save_github_issue_link(&GithubIssueLink {
+        task_id: task.id,
+        repo: link.repo.clone(),
+        issue_number: issue.number,
+        synced_closed: false,
+        synced_labels: labels,
+        synced_at: Utc::now().to_rfc3339(),
+    })
+}
+
+// Bring a linked task in line with its issue; returns whether the task changed
+fn pull_issue(
+    service: &PlanningService,
+    mut issue_link: GithubIssueLink,
+    issue: &GithubIssue,
+) -> Result<bool, ApiError> {
+    let Some(task) = service.find_task(&issue_link.task_id)? else {
+        // Deleted locally: keep the link so the issue is not imported again
+        return Ok(false);
+    };
+    // Local edits since the last sync win; they are pushed afterwards
+    if task_closed(&task) != issue_link.synced_closed
+        || task_labels(&task) != issue_link.synced_labels
+    {
+        return Ok(false);
+    }
+
+    let labels = issue.label_names();
+    let mut changed = false;
+    if issue.closed() && !task_closed(&task) {
+        service.mark_task_done(&task.id)?;
+        changed = true;
+    } else if !issue.closed() && task_closed(&task) {
+        if task.due_date.is_none() {
+            let mut update = empty_update(&task.id);
+            update.due_date = Some(Some(service.today()));
+            service.update_task(update)?;
+        }
+        service.reopen_task(&task.id)?;
+        changed = true;
+    }
+    if labels != task_labels(&task) || issue.title != task.title {
+        let mut update = empty_update(&task.id);
+        update.labels = Some(labels.clone());
+        update.title = Some(issue.title.clone());
+        service.update_task(update)?;
+        changed = true;
+    }
+
+    issue_link.synced_closed = issue.closed();
+    issue_link.synced_labels = labels;
+    issue_link.synced_at = Utc::now().to_rfc3339();
+    service.save_github_issue_link(&issue_link)?;
+    Ok(changed)
+}
+
+// Tasks whose status or labels changed since the last sync
+fn pending_pushes(service: &PlanningService, repo: &str) -> Result<Vec<IssuePush>, ApiError> {
+    let mut pushes = Vec::new();
+    for link in service.list_github_issue_links(repo)? {
+        let Some(task) = service.find_task(&link.task_id)? else {
+            continue;
+        };
+        let closed = task_closed(&task);
+        let labels = task_labels(&task);
+        if closed != link.synced_closed || labels != link.synced_labels {
+            pushes.push(IssuePush {
+                link,
+                closed,
+                labels,
+            });
+        }
+    }
+    Ok(pushes)
+}
+
+fn format_reset_at(reset_at: Option<i64>) -> Option<String> {
+    reset_at
+        .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0))
+        .map(|at| at.to_rfc3339())
+}
+
+// Sync one linked board; Err only for failures that should stop the whole run
+async fn sync_board(
+    vault_root: &Path,
+    gh: &mut GithubClient,
+    link: &GithubBoardLink,
+    report: &mut GithubSyncReport,
+) -> Result<(), ApiError> {
+    let fetch_started = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let issues = gh
+        .list_issues(&link.repo, link.last_synced_at.as_deref())
+        .await?;
+
+    // The service is not Sync, so it is opened between network calls rather than held across them
+    let pushes = {
+        let service = PlanningService::open(vault_root)?;
+        for issue in &issues {
+            let result = match service.get_github_issue_link(&link.repo, issue.number)? {
+                Some(issue_link) => pull_issue(&service, issue_link, issue).map(|changed| {
+                    if changed {
+                        report.updated_tasks += 1;
+                    }
+                }),
+                // Closed issues are history; only open ones become tasks
+                None if !issue.closed() => {
+                    import_issue(&service, link, issue).map(|_| report.imported += 1)
+                }
+                None => Ok(()),
+            };
+            if let Err(err) = result {
+                report
+                    .errors
+                    .push(format!("{}#{}: {}", link.repo, issue.number, err.message));
+            }
+        }
+        pending_pushes(&service, &link.repo)?
+    };
+
+    let mut pushed = Vec::new();
+    let mut push_error = None;
+    for push in pushes {
+        match gh
+            .update_issue(
+                &link.repo,
+                push.link.issue_number,
+                push.closed,
+                &push.labels,
+            )
+            .await
+        {
+            Ok(()) => pushed.push(push),
+            Err(err) if matches!(err.code, ErrorCode::GithubRateLimited) => {
+                push_error = Some(err);
+                break;
+            }
+            Err(err) => report.errors.push(format!(
+                "{}#{}: {}",
+                link.repo, push.link.issue_number, err.message
+            )),
+        }
+    }
+
+    let service = PlanningService::open(vault_root)?;
+    for push in pushed {
+        let mut issue_link = push.link;
+        issue_link.synced_closed = push.closed;
+        issue_link.synced_labels = push.labels;
+        issue_link.synced_at = Utc::now().to_rfc3339();
+        service.save_github_issue_link(&issue_link)?;
+        report.updated_issues += 1;
+    }
+    // Every issue up to the fetch was seen, so the next sync can start from there
+    service.set_github_sync_cursor(&link.board_id, &fetch_started)?;
+
+    match push_error {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+// Two-way sync of every linked board; stops early and reports when rate limited
+pub async fn sync_now(
+    vault_root: &Path,
+    client: &Client,
+    token: String,
+) -> Result<GithubSyncReport, ApiError> {
+    let links = PlanningService::open(vault_root)?.list_github_board_links()?;
+    let mut gh = GithubClient::new(client.clone(), token);
+    let mut report = GithubSyncReport::default();
+
+    for link in &links {
+        match sync_board(vault_root, &mut gh, link, &mut report).await {
+            Ok(()) => report.repos += 1,
+            Err(err) if matches!(err.code, ErrorCode::GithubRateLimited) => {
+                report.rate_limited = true;
+                report.rate_limit_reset_at = format_reset_at(gh.reset_at);
+                break;
+            }
+            Err(err) if matches!(err.code, ErrorCode::GithubRequestFailed) => {
+                report
+                    .errors
+                    .push(format!("{}: {}", link.repo, err.message));
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    tracing::info!(
+        target: "github",
+        "github sync finished: repos={}, imported={}, updated_tasks={}, updated_issues={}, rate_limited={}, errors={}",
+        report.repos,
+        report.imported,
+        report.updated_tasks,
+        report.updated_issues,
+        report.rate_limited,
+        report.errors.len()
+    );
+    Ok(report)
+}
@@ -0,0 +1,281 @@
+use std::io::Read;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::domain::planning::CreateTaskInput;
+use crate::features::metrics;
+use crate::ipc::{ApiError, ApiResponse, ErrorCode};
+use crate::repo::settings_repo::{self, HttpApiSettings};
+use crate::services::planning_service::PlanningService;
+use crate::state::{HttpApiState, VaultState};
+
+// Emitted after an external request changed planning data so open views can reload
+pub const PLANNING_CHANGED_EVENT: &str = "planning-changed";
+// Largest request body accepted
+const MAX_BODY_BYTES: u64 = 1024 * 1024;
+
+// Settings together with whether the server is currently listening
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpApiStatus {
+    pub enabled: bool,
+    pub port: u16,
+    pub token: String,
+    pub metrics: bool,
+    pub running: bool,
+}
+
+// Endpoints answer with JSON, except the Prometheus scrape endpoint
+enum Reply {
+    Json(serde_json::Value),
+    Text(String),
+}
+
+// Localhost REST server mirroring a subset of the planning commands
+pub struct HttpApiServer {
+    server: Arc<Server>,
+    worker: Option<JoinHandle<()>>,
+    pub port: u16,
+}
+
+impl HttpApiServer {
+    pub fn start(app_handle: AppHandle, port: u16) -> Result<Self, ApiError> {
+        let server = Server::http(format!("127.0.0.1:{}", port)).map_err(|e| ApiError {
+            code: ErrorCode::IoError,
+            message: format!("Failed to start HTTP API: {}", e),
+            details: Some(serde_json::json!({ "port": port })),
+        })?;
+        let server = Arc::new(server);
+
+        let worker_server = server.clone();
+        let worker = std::thread::spawn(move || {
+            for request in worker_server.incoming_requests() {
+                handle_request(&app_handle, request);
+            }
+        });
+
+        tracing::info!(target: "http_api", "HTTP API listening: port={}", port);
+        Ok(Self {
+            server,
+            worker: Some(worker),
+            port,
+        })
+    }
+
+    pub fn stop(mut self) {
+        self.server.unblock();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        tracing::info!(target: "http_api", "HTTP API stopped: port={}", self.port);
+    }
+}
+
+// Start, restart or stop the server so it matches the given settings
+pub fn apply_settings(
+    app_handle: &AppHandle,
+    settings: &HttpApiSettings,
+) -> Result<bool, ApiError> {
+    let http_api_state = app_handle.state::<HttpApiState>();
+    let mut server = http_api_state.server.lock()?;
+
+    let wanted = settings.enabled && !settings.token.is_empty();
+    if let Some(running) = server.take() {
+        if wanted && running.port == settings.port {
+            *server = Some(running);
+            return Ok(true);
+        }
+        running.stop();
+    }
+    if wanted {
+        *server = Some(HttpApiServer::start(app_handle.clone(), settings.port)?);
+    }
+    Ok(server.is_some())
+}
+
+pub fn is_running(app_handle: &AppHandle) -> bool {
+    app_handle
+        .state::<HttpApiState>()
+        .server
+        .lock()
+        .map(|server| server.is_some())
+        .unwrap_or(false)
+}
+
+fn status_for(code: &ErrorCode) -> u16 {
+    match code {
+        ErrorCode::PermissionDenied => 401,
+        ErrorCode::NotFound | ErrorCode::EntryNotFound => 404,
+        ErrorCode::VaultNotSelected | ErrorCode::NoVaultSelected => 409,
+        ErrorCode::InvalidInput
+        | ErrorCode::DecodeFailed
+        | ErrorCode::JsonError
+        | ErrorCode::DateTimeError
+        | ErrorCode::DueDateRequired
+        | ErrorCode::BoardIdRequired
+        | ErrorCode::InvalidStateTransition
+        | ErrorCode::InvalidSchedule
+        | ErrorCode::WipLimitExceeded
+        | ErrorCode::TimerOverlap => 400,
+        _ => 500,
+    }
+}
+
+fn handle_request(app_handle: &AppHandle, mut request: Request) {
+    let method = request.method().clone();
+    let url = request.url().to_string();
+
+    let (status, body, content_type) = match route(app_handle, &mut request) {
+        Ok(Reply::Json(data)) => (
+            200,
+            serde_json::to_string(&ApiResponse::ok(data)),
+            "application/json",
+        ),
+        Ok(Reply::Text(text)) => (200, Ok(text), "text/plain; version=0.0.4"),
+        Err(err) => {
+            if !matches!(err.code, ErrorCode::PermissionDenied) {
+                tracing::warn!(target: "http_api", "request failed: method={}, url={}, error={}", method, url, err);
+            }
+            let status = status_for(&err.code);
+            (
+                status,
+                serde_json::to_string(&ApiResponse::<()>::err(err.code, &err.message, err.details)),
+                "application/json",
+            )
+        }
+    };
+
+    let mut response = Response::from_string(body.unwrap_or_default()).with_status_code(status);
+    if let Ok(header) = Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes()) {
+        response = response.with_header(header);
+    }
+    if let Err(e) = request.respond(response) {
+        tracing::warn!(target: "http_api", "failed to send response: {}", e);
+    }
+}
+
+// Compare without short-circuiting so timing does not leak the token
+fn tokens_match(expected: &str, provided: &str) -> bool {
+    expected.len() == provided.len()
+        && expected
+            .bytes()
+            .zip(provided.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+fn authorize(request: &Request, token: &str) -> Result<(), ApiError> {
+    let provided = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Authorization"))
+        .and_then(|header| header.value.as_str().strip_prefix("Bearer "))
+        .map(str::trim);
+
+    match provided {
+        Some(provided) if tokens_match(token, provided) => Ok(()),
+        _ => Err(ApiError {
+            code: ErrorCode::PermissionDenied,
+            message: "Missing or invalid API token".to_string(),
+            details: None,
+        }),
+    }
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(request: &mut Request) -> Result<T, ApiError> {
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_BYTES)
+        .read_to_string(&mut body)
+        .map_err(|e| ApiError {
+            code: ErrorCode::DecodeFailed,
+            message: format!("Failed to read request body: {}", e),
+            details: None,
+        })?;
+    serde_json::from_str(&body).map_err(|e| ApiError {
+        code: ErrorCode::DecodeFailed,
+        message: format!("Invalid JSON body: {}", e),
+        details: None,
+    })
+}
+
+fn to_json<T: Serialize>(value: T) -> Result<serde_json::Value, ApiError> {
+    serde_json::to_value(value).map_err(|e| ApiError {
+        code: ErrorCode::JsonError,
+        message: format!("Failed to encode response: {}", e),
+        details: None,
+    })
+}
+
+fn notify_changed(app_handle: &AppHandle) {
+    let payload = serde_json::json!({ "source": "http_api" });
+    if let Err(e) = app_handle.emit(PLANNING_CHANGED_EVENT, payload) {
+        tracing::warn!(target: "http_api", "failed to emit planning change: {}", e);
+    }
+}
+
+fn route(app_handle: &AppHandle, request: &mut Request) -> Result<Reply, ApiError> {
+    // Held for the whole request, like a command, so the vault cannot change underneath it
+    let vault_state = app_handle.state::<VaultState>();
+    let vault_root = vault_state.root.lock()?;
+    let vault_path = vault_root.as_ref().ok_or_else(|| ApiError {
+        code: ErrorCode::VaultNotSelected,
+        message: "Vault not selected".to_string(),
+        details: None,
+    })?;
+
+    // Settings are re-read per request so disabling or rotating the token applies immediately
+    let settings = settings_repo::get_http_api_settings(vault_path)?;
+    if !settings.enabled || settings.token.is_empty() {
+        return Err(ApiError {
+            code: ErrorCode::PermissionDenied,
+            message: "HTTP API is disabled for this vault".to_string(),
+            details: None,
+        });
+    }
+    authorize(request, &settings.token)?;
+
+    let url = request.url().to_string();
+    let path = url.split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let method = request.method().clone();
+
+    if settings.metrics && method == Method::Get && segments == ["metrics"] {
+        return metrics::render_prometheus().map(Reply::Text);
+    }
+
+    let service = PlanningService::new(app_handle, vault_path)?;
+    let data = match (&method, segments.as_slice()) {
+        (Method::Get, ["v1", "today"]) => to_json(service.get_today_data(None)?),
+        (Method::Post, ["v1", "tasks"]) => {
+            let input: CreateTaskInput = read_json(request)?;
+            let task = service.create_task(input)?;
+            notify_changed(app_handle);
+            to_json(task)
+        }
+        (Method::Post, ["v1", "tasks", task_id, action]) => {
+            match *action {
+                "start" => service.start_task(task_id)?,
+                "stop" => service.stop_task(task_id)?,
+                "done" => service.mark_task_done(task_id)?,
+                _ => return Err(unknown_endpoint(&method, path)),
+            }
+            notify_changed(app_handle);
+            Ok(serde_json::Value::Null)
+        }
+        _ => Err(unknown_endpoint(&method, path)),
+    };
+    data.map(Reply::Json)
+}
+
+fn unknown_endpoint(method: &Method, path: &str) -> ApiError {
+    ApiError {
+        code: ErrorCode::NotFound,
+        message: "Unknown endpoint".to_string(),
+        details: Some(serde_json::json!({ "method": method.to_string(), "path": path })),
+    }
+}
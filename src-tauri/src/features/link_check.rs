@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use reqwest::header::USER_AGENT;
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+
+use crate::domain::frontmatter;
+use crate::domain::links::{self, normalize_note_path, LinkKind};
+use crate::domain::lint;
+use crate::ipc::ApiError;
+use crate::paths::rel_path_string;
+use crate::security::path_policy;
+use crate::services::vault_service;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+const PROBE_USER_AGENT: &str = "Mozilla/5.0 (compatible; tauri-planning-app link checker)";
+pub const DEFAULT_CONCURRENCY: usize = 8;
+pub const MAX_CONCURRENCY: usize = 32;
+// Distinct URLs requested per check; the rest are counted as skipped
+const MAX_EXTERNAL_URLS: usize = 2000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BrokenLink {
+    pub kind: LinkKind,
+    pub target: String, // Resolved vault path, or the URL
+    pub line: usize,
+    pub column: usize,
+    pub reason: String,
+    pub status: Option<u16>, // HTTP status of an external link that answered
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteLinkReport {
+    pub path: String,
+    pub broken: Vec<BrokenLink>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkCheckReport {
+    pub scope: Option<String>,
+    pub notes_checked: usize,
+    pub links_checked: usize,
+    pub external_checked: usize, // Distinct URLs requested; 0 unless external links were checked
+    pub external_skipped: usize,
+    pub broken_count: usize,
+    pub notes: Vec<NoteLinkReport>, // Only notes with broken links, by path
+}
+
+// Where an external URL appears: note, line, column
+type UrlOccurrence = (String, usize, usize);
+
+// Vault links checked so far; external URLs wait for check_external
+pub struct LinkScan {
+    scope: Option<String>,
+    notes_checked: usize,
+    links_checked: usize,
+    broken: BTreeMap<String, Vec<BrokenLink>>,
+    external: BTreeMap<String, Vec<UrlOccurrence>>,
+    external_checked: usize,
+    external_skipped: usize,
+}
+
+// Notes under the scope: a single note, a folder, or the whole vault
+fn scope_notes(vault_root: &Path, scope: Option<&str>) -> Result<Vec<String>, ApiError> {
+    if let Some(note) = scope.filter(|scope| scope.to_ascii_lowercase().ends_with(".md")) {
+        return Ok(normalize_note_path(note).into_iter().collect());
+    }
+    let mut notes = Vec::new();
+    vault_service::scan_vault_stream(vault_root, scope.map(PathBuf::from), |nodes, _| {
+        notes.extend(
+            nodes
+                .into_iter()
+                .filter(|node| node.node_type == "file")
+                .map(|node| node.path),
+        );
+    })?;
+    Ok(notes)
+}
+
+// Check wikilinks and relative links against the vault and collect external URLs. Links in
+// fenced code are left alone; unreadable notes are skipped.
+pub fn scan_links(vault_root: &Path, scope: Option<String>) -> Result<LinkScan, ApiError> {
+    let mut scan = LinkScan {
+        notes_checked: 0,
+        links_checked: 0,
+        broken: BTreeMap::new(),
+        external: BTreeMap::new(),
+        external_checked: 0,
+        external_skipped: 0,
+        scope,
+    };
+    for note_path in scope_notes(vault_root, scan.scope.as_deref())? {
+        let rel_path = PathBuf::from(&note_path);
+        let Ok(note) = vault_service::read_text_file(vault_root, &rel_path) else {
+            continue;
+        };
+        scan.notes_checked += 1;
+        let (_, body) = frontmatter::split(&note.content);
+        let body_start = note.content.len() - body.len();
+        let fenced = lint::fenced_ranges(&note.content, body_start);
+        let base_dir = rel_path.parent().map(rel_path_string).unwrap_or_default();
+        for link in links::link_refs(body, &base_dir) {
+            let start = body_start + link.start;
+            if fenced
+                .iter()
+                .any(|(fence_start, fence_end)| (*fence_start..*fence_end).contains(&start))
+            {
+                continue;
+            }
+            let (line, column) = lint::position(&note.content, start);
+            if link.kind == LinkKind::External {
+                if link.target.starts_with("http://") || link.target.starts_with("https://") {
+                    scan.links_checked += 1;
+                    scan.external.entry(link.target).or_default().push((
+                        note_path.clone(),
+                        line,
+                        column,
+                    ));
+                }
+                continue;
+            }
+            scan.links_checked += 1;
+            if path_policy::resolve_existing_path(vault_root, Path::new(&link.target)).is_err() {
+                scan.broken
+                    .entry(note_path.clone())
+                    .or_default()
+                    .push(BrokenLink {
+                        kind: link.kind,
+                        target: link.target,
+                        line,
+                        column,
+                        reason: "Target not found in the vault".to_string(),
+                        status: None,
+                    });
+            }
+        }
+    }
+    Ok(scan)
+}
+
+// Why a URL counts as broken, or None when it answered
+async fn probe(client: Client, url: String) -> Option<(String, Option<u16>)> {
+    let request = |method: reqwest::Method| {
+        client
+            .request(method, &url)
+            .header(USER_AGENT, PROBE_USER_AGENT)
+            .timeout(PROBE_TIMEOUT)
+            .send()
+    };
+    let mut response = request(reqwest::Method::HEAD).await;
+    // Some servers refuse HEAD but serve the page
+    if response.as_ref().is_ok_and(|response| {
+        matches!(
+            response.status(),
+            StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED | StatusCode::FORBIDDEN
+        )
+    }) {
+        response = request(reqwest::Method::GET).await;
+    }
+    match response {
+        Ok(response) => {
+            let status = response.status();
+            // Rate limiting says nothing about whether the page exists
+            if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS
+                || status.is_server_error()
+            {
+                Some((format!("HTTP {}", status.as_u16()), Some(status.as_u16())))
+            } else {
+                None
+            }
+        }
+        Err(err) if err.is_timeout() => Some(("Timed out".to_string(), None)),
+        Err(err) => Some((format!("Request failed: {}", err), None)),
+    }
+}
+
+// Request each distinct URL, at most `concurrency` at a time
+pub async fn check_external(client: &Client, scan: &mut LinkScan, concurrency: usize) {
+    let urls: Vec<String> = scan.external.keys().cloned().collect();
+    scan.external_skipped = urls.len().saturating_sub(MAX_EXTERNAL_URLS);
+    let urls = &urls[..urls.len().min(MAX_EXTERNAL_URLS)];
+    scan.external_checked = urls.len();
+    for chunk in urls.chunks(concurrency.clamp(1, MAX_CONCURRENCY)) {
+        let handles: Vec<_> = chunk
+            .iter()
+            .map(|url| {
+                let probe = probe(client.clone(), url.clone());
+                (url.clone(), tauri::async_runtime::spawn(probe))
+            })
+            .collect();
+        for (url, handle) in handles {
+            let Ok(Some((reason, status))) = handle.await else {
+                continue;
+            };
+            for (note_path, line, column) in scan.external.get(&url).into_iter().flatten() {
+                scan.broken
+                    .entry(note_path.clone())
+                    .or_default()
+                    .push(BrokenLink {
+                        kind: LinkKind::External,
+                        target: url.clone(),
+                        line: *line,
+                        column: *column,
+                        reason: reason.clone(),
+                        status,
+                    });
+            }
+        }
+    }
+}
+
+pub fn into_report(scan: LinkScan) -> LinkCheckReport {
+    let notes: Vec<NoteLinkReport> = scan
+        .broken
+        .into_iter()
+        .map(|(path, mut broken)| {
+            broken.sort_by_key(|link| (link.line, link.column));
+            NoteLinkReport { path, broken }
+        })
+        .collect();
+    LinkCheckReport {
+        scope: scan.scope,
+        notes_checked: scan.notes_checked,
+        links_checked: scan.links_checked,
+        external_checked: scan.external_checked,
+        external_skipped: scan.external_skipped,
+        broken_count: notes.iter().map(|note| note.broken.len()).sum(),
+        notes,
+    }
+}
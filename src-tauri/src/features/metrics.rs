@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+use crate::ipc::{ApiError, ErrorCode};
+
+// A histogram family; every observation carries one label value
+pub struct Metric {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub label: &'static str,
+    pub bounds: &'static [f64], // Upper bucket bounds, ascending; +Inf is implied
+}
+
+const LATENCY_BOUNDS_MS: &[f64] = &[
+    1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 10000.0,
+];
+const ENTRY_BOUNDS: &[f64] = &[10.0, 100.0, 500.0, 1000.0, 2000.0, 5000.0, 8000.0];
+
+// Duration of each traced service operation, keyed by span name
+pub const COMMAND_DURATION: Metric = Metric {
+    name: "command_duration_ms",
+    help: "Duration of planning and vault operations in milliseconds",
+    label: "command",
+    bounds: LATENCY_BOUNDS_MS,
+};
+
+// Duration of every statement run on the planning database, keyed by its leading keyword
+pub const DB_QUERY_DURATION: Metric = Metric {
+    name: "db_query_duration_ms",
+    help: "Duration of planning database statements in milliseconds",
+    label: "statement",
+    bounds: LATENCY_BOUNDS_MS,
+};
+
+// Entries returned by a vault scan
+pub const SCAN_ENTRIES: Metric = Metric {
+    name: "scan_entries",
+    help: "Entries returned per vault scan",
+    label: "scan",
+    bounds: ENTRY_BOUNDS,
+};
+
+struct Histogram {
+    bounds: &'static [f64],
+    counts: Vec<u64>, // Per bucket, not cumulative; the last slot is +Inf
+    sum: f64,
+    max: f64,
+}
+
+fn registry() -> &'static Mutex<BTreeMap<(&'static str, String), Histogram>> {
+    static REGISTRY: OnceLock<Mutex<BTreeMap<(&'static str, String), Histogram>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+fn started_at() -> &'static String {
+    static STARTED_AT: OnceLock<String> = OnceLock::new();
+    STARTED_AT.get_or_init(|| chrono::Utc::now().to_rfc3339())
+}
+
+pub fn observe(metric: &Metric, label: &str, value: f64) {
+    started_at();
+    // Metrics are best effort; a poisoned registry just stops recording
+    let Ok(mut histograms) = registry().lock() else {
+        return;
+    };
+    let histogram = histograms
+        .entry((metric.name, label.to_string()))
+        .or_insert_with(|| Histogram {
+            bounds: metric.bounds,
+            counts: vec![0; metric.bounds.len() + 1],
+            sum: 0.0,
+            max: 0.0,
+        });
+    let bucket = histogram
+        .bounds
+        .iter()
+        .position(|bound| value <= *bound)
+        .unwrap_or(histogram.bounds.len());
+    histogram.counts[bucket] += 1;
+    histogram.sum += value;
+    histogram.max = histogram.max.max(value);
+}
+
+pub fn observe_duration(metric: &Metric, label: &str, duration: Duration) {
+    observe(metric, label, duration.as_secs_f64() * 1000.0);
+}
+
+// SQLite profile callback; the statement text is reduced to its keyword to keep labels few
+pub fn record_db_query(sql: &str, duration: Duration) {
+    let keyword = sql
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_ascii_uppercase();
+    let label = match keyword.as_str() {
+        "SELECT" | "INSERT" | "UPDATE" | "DELETE" | "WITH" => keyword.as_str(),
+        "BEGIN" | "COMMIT" | "ROLLBACK" | "SAVEPOINT" | "RELEASE" => "TRANSACTION",
+        _ => "OTHER",
+    };
+    observe_duration(&DB_QUERY_DURATION, label, duration);
+}
+
+// Cumulative bucket count; le is None for +Inf
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BucketCount {
+    pub le: Option<f64>,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistogramSnapshot {
+    pub name: String,
+    pub label: String,
+    pub count: u64,
+    pub sum: f64,
+    pub max: f64,
+    pub buckets: Vec<BucketCount>,
+}
+
+// Everything recorded since the app started
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerfMetrics {
+    pub since: String,
+    pub histograms: Vec<HistogramSnapshot>,
+}
+
+pub fn snapshot() -> Result<PerfMetrics, ApiError> {
+    let histograms = registry().lock().map_err(|_| ApiError {
+        code: ErrorCode::Unknown,
+        message: "Metrics registry is unavailable".to_string(),
+        details: None,
+    })?;
+    let histograms = histograms
+        .iter()
+        .map(|((name, label), histogram)| {
+            let mut cumulative = 0;
+            let buckets = histogram
+                .counts
+                .iter()
+                .enumerate()
+                .map(|(index, count)| {
+                    cumulative += count;
+                    BucketCount {
+                        le: histogram.bounds.get(index).copied(),
+                        count: cumulative,
+                    }
+                })
+                .collect();
+            HistogramSnapshot {
+                name: name.to_string(),
+                label: label.clone(),
+                count: cumulative,
+                sum: histogram.sum,
+                max: histogram.max,
+                buckets,
+            }
+        })
+        .collect();
+    Ok(PerfMetrics {
+        since: started_at().clone(),
+        histograms,
+    })
+}
+
+// Prometheus text exposition format, metric names prefixed with planning_
+pub fn render_prometheus() -> Result<String, ApiError> {
+    let metrics = snapshot()?;
+    let mut out = String::new();
+    for metric in [&COMMAND_DURATION, &DB_QUERY_DURATION, &SCAN_ENTRIES] {
+        let name = format!("planning_{}", metric.name);
+        let _ = writeln!(out, "# HELP {} {}", name, metric.help);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        for histogram in metrics
+            .histograms
+            .iter()
+            .filter(|histogram| histogram.name == metric.name)
+        {
+            let label = histogram.label.replace('\\', "\\\\").replace('"', "\\\"");
+            for bucket in &histogram.buckets {
+                let le = bucket.le.map_or("+Inf".to_string(), |le| le.to_string());
+                let _ = writeln!(
+                    out,
+                    "{}_bucket{{{}=\"{}\",le=\"{}\"}} {}",
+                    name, metric.label, label, le, bucket.count
+                );
+            }
+            let _ = writeln!(
+                out,
+                "{}_sum{{{}=\"{}\"}} {}",
+                name, metric.label, label, histogram.sum
+            );
+            let _ = writeln!(
+                out,
+                "{}_count{{{}=\"{}\"}} {}",
+                name, metric.label, label, histogram.count
+            );
+        }
+    }
+    Ok(out)
+}
+
+// Times every tracing span from creation to close as COMMAND_DURATION under the span name
+pub struct SpanTimingLayer;
+
+impl<S> Layer<S> for SpanTimingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(Instant::now());
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(&id) {
+            if let Some(start) = span.extensions().get::<Instant>() {
+                observe_duration(&COMMAND_DURATION, span.name(), start.elapsed());
+            }
+        }
+    }
+}
@@ -1 +1,10 @@
 pub mod ai;
+pub mod clipper;
+pub mod deep_link;
+pub mod github;
+pub mod http_api;
+pub mod link_check;
+pub mod metrics;
+pub mod notifications;
+pub mod vault_drive;
+pub mod webhooks;
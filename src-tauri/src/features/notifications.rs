@@ -0,0 +1,147 @@
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::domain::notifications::{
+    Notification, NotificationKind, NotificationSettings, NotificationState,
+};
+use crate::domain::reminders;
+use crate::domain::timezone;
+use crate::ipc::ApiError;
+use crate::repo::settings_repo;
+use crate::services::planning_service::PlanningService;
+use crate::state::{FocusState, VaultState};
+
+// Emitted with a NotificationEvent whenever a reminder goes off, a task becomes overdue or a
+// snooze ends
+pub const NOTIFICATION_EVENT: &str = "notification";
+// How often due notifications are looked for; one shows at most this late
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationEvent<'a> {
+    #[serde(flatten)]
+    pub notification: &'a Notification,
+    pub sound: bool,
+    pub test: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HoldReason {
+    Disabled,
+    QuietHours,
+    Focus, // A focus session asks for do-not-disturb
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TestNotificationResponse {
+    pub sound: bool,
+    pub held_by: Option<HoldReason>, // Why real notifications would not show right now
+}
+
+// Deliver due notifications of the current vault to the frontend as they come up
+pub fn spawn_scheduler(app: &tauri::App) {
+    let app_handle = app.handle().clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(SCHEDULER_INTERVAL);
+        deliver_due(&app_handle);
+    });
+}
+
+// Why notifications are held back at this moment. Quiet hours and focus sessions leave due
+// notifications pending until they end; turning notifications off consumes them.
+fn hold_reason(
+    app_handle: &AppHandle,
+    vault_root: &Path,
+    settings: &NotificationSettings,
+) -> Option<HoldReason> {
+    if !settings.enabled {
+        return Some(HoldReason::Disabled);
+    }
+    if let Some(quiet_hours) = &settings.quiet_hours {
+        let planning = settings_repo::get_planning_settings(vault_root).unwrap_or_default();
+        let now = timezone::now_in(settings_repo::resolve_timezone(&planning));
+        if quiet_hours.contains(now.time()) {
+            return Some(HoldReason::QuietHours);
+        }
+    }
+    let focus_state = app_handle.try_state::<FocusState>()?;
+    focus_state.do_not_disturb().then_some(HoldReason::Focus)
+}
+
+fn emit(app_handle: &AppHandle, event: &NotificationEvent<'_>) {
+    if let Err(err) = app_handle.emit(NOTIFICATION_EVENT, event) {
+        tracing::warn!(target: "notifications", "failed to emit {}: {}", NOTIFICATION_EVENT, err);
+    }
+}
+
+fn deliver_due(app_handle: &AppHandle) {
+    let vault_state = app_handle.state::<VaultState>();
+    let Some(vault_root) = vault_state.root.lock().ok().and_then(|root| root.clone()) else {
+        return;
+    };
+    let settings = settings_repo::get_notification_settings(&vault_root).unwrap_or_default();
+    if matches!(
+        hold_reason(app_handle, &vault_root, &settings),
+        Some(HoldReason::QuietHours | HoldReason::Focus)
+    ) {
+        return;
+    }
+    let due = PlanningService::new(app_handle, &vault_root)
+        .and_then(|service| service.take_due_notifications(&settings));
+    let due = match due {
+        Ok(due) => due,
+        Err(err) => {
+            tracing::warn!(target: "notifications", "notification check failed: {}", err.message);
+            return;
+        }
+    };
+    for notification in &due {
+        tracing::info!(target: "notifications", "notification due: id={}, kind={}, task_id={}", notification.id, notification.kind.as_str(), notification.task_id);
+        emit(
+            app_handle,
+            &NotificationEvent {
+                notification,
+                sound: settings.sound,
+                test: false,
+            },
+        );
+    }
+}
+
+// Show a sample notification with the vault's settings, whatever holds real ones back
+pub fn send_test(
+    app_handle: &AppHandle,
+    vault_root: &Path,
+) -> Result<TestNotificationResponse, ApiError> {
+    let settings = settings_repo::get_notification_settings(vault_root)?;
+    let now = reminders::format_instant(Utc::now());
+    let notification = Notification {
+        id: "test".to_string(),
+        kind: NotificationKind::Reminder,
+        source_id: "test".to_string(),
+        occurrence_at: now.clone(),
+        task_id: String::new(),
+        title: "Test notification".to_string(),
+        state: NotificationState::Delivered,
+        snoozed_until: None,
+        delivered_at: now.clone(),
+        updated_at: now,
+    };
+    emit(
+        app_handle,
+        &NotificationEvent {
+            notification: &notification,
+            sound: settings.sound,
+            test: true,
+        },
+    );
+    Ok(TestNotificationResponse {
+        sound: settings.sound,
+        held_by: hold_reason(app_handle, vault_root, &settings),
+    })
+}
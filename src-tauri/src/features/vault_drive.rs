@@ -0,0 +1,322 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::ipc::{map_write_error, ApiError, ErrorCode};
+use crate::security::note_locks::WriteOrigin;
+use crate::services::vault_service;
+use crate::state::{VaultDriveState, VaultState};
+
+pub const VAULT_UNAVAILABLE_EVENT: &str = "vault-unavailable";
+pub const VAULT_AVAILABLE_EVENT: &str = "vault-available";
+// How often the vault folder is checked for disappearing or coming back
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+// Writes held while the vault is away; the oldest are dropped beyond this
+const MAX_QUEUED_WRITES: usize = 500;
+const PENDING_WRITES_FILE: &str = "pending_writes.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DriveKind {
+    Local,
+    Removable,
+    Network,
+    Unknown,
+}
+
+// A note write made while the vault was unavailable, replayed when it returns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedWrite {
+    pub path: String, // Vault-relative
+    pub content: String,
+    pub origin: WriteOrigin,
+    pub queued_at: u64, // Unix seconds
+}
+
+// Queue as kept on disk, so writes survive quitting while the drive is gone
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PendingWrites {
+    vault_root: String,
+    writes: Vec<QueuedWrite>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultAvailabilityEvent {
+    pub vault_root: String,
+    pub drive: DriveKind,
+    pub queued_writes: usize,
+    pub replayed: usize,     // Set when the vault comes back
+    pub failed: Vec<String>, // Queued paths that could not be written on replay
+}
+
+pub fn pending_writes_path(config_dir: &Path) -> PathBuf {
+    config_dir.join(PENDING_WRITES_FILE)
+}
+
+// Writes still queued for `vault_root` from an earlier session
+pub fn load_pending_writes(queue_path: &Path, vault_root: &Path) -> Vec<QueuedWrite> {
+    let Some(pending) = fs::read_to_string(queue_path)
+        .ok()
+        .and_then(|data| serde_json::from_str::<PendingWrites>(&data).ok())
+    else {
+        return Vec::new();
+    };
+    if Path::new(&pending.vault_root) != vault_root {
+        return Vec::new();
+    }
+    pending.writes
+}
+
+fn store_pending_writes(
+    queue_path: &Path,
+    vault_root: &Path,
+    writes: &[QueuedWrite],
+) -> Result<(), ApiError> {
+    if writes.is_empty() {
+        return match fs::remove_file(queue_path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                Err(map_write_error("Failed to clear queued writes", err))
+            }
+            _ => Ok(()),
+        };
+    }
+    let pending = PendingWrites {
+        vault_root: vault_root.to_string_lossy().to_string(),
+        writes: writes.to_vec(),
+    };
+    let data = serde_json::to_string(&pending).map_err(|err| ApiError {
+        code: ErrorCode::WriteFailed,
+        message: "Failed to encode queued writes".to_string(),
+        details: Some(serde_json::json!({ "error": err.to_string() })),
+    })?;
+    fs::write(queue_path, data).map_err(|err| map_write_error("Failed to store queued writes", err))
+}
+
+// Hold a note write until the vault is back; a later write to the same note replaces the earlier
+pub fn queue_write(
+    state: &VaultDriveState,
+    vault_root: &Path,
+    path: &str,
+    content: String,
+    origin: WriteOrigin,
+) -> Result<(), ApiError> {
+    let mut queue = state.queue.lock()?;
+    queue.retain(|write| write.path != path);
+    queue.push(QueuedWrite {
+        path: path.to_string(),
+        content,
+        origin,
+        queued_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    });
+    if queue.len() > MAX_QUEUED_WRITES {
+        let excess = queue.len() - MAX_QUEUED_WRITES;
+        queue.drain(..excess);
+    }
+    store_pending_writes(&state.queue_path, vault_root, &queue)
+}
+
+// Write queued notes in order; stops early if the vault goes away again. Returns the count
+// written and the paths that failed for other reasons.
+fn replay_writes(
+    state: &VaultDriveState,
+    vault_root: &Path,
+) -> Result<(usize, Vec<String>), ApiError> {
+    let mut queue = state.queue.lock()?;
+    let mut replayed = 0;
+    let mut failed = Vec::new();
+    while let Some(write) = queue.first() {
+        if !vault_root.is_dir() {
+            break;
+        }
+        if let Err(err) = vault_service::write_text_file(
+            vault_root,
+            Path::new(&write.path),
+            &write.content,
+            write.origin,
+        ) {
+            tracing::warn!(target: "vault", "queued write failed: path={}, error={}", write.path, err.message);
+            failed.push(write.path.clone());
+        } else {
+            replayed += 1;
+        }
+        queue.remove(0);
+    }
+    store_pending_writes(&state.queue_path, vault_root, &queue)?;
+    Ok((replayed, failed))
+}
+
+// Watch the vault folder and announce when it disappears or returns, replaying queued writes
+pub fn spawn_watch(app: &tauri::App) {
+    let app_handle = app.handle().clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(WATCH_INTERVAL);
+        check_vault(&app_handle);
+    });
+}
+
+fn check_vault(app_handle: &AppHandle) {
+    let vault_state = app_handle.state::<VaultState>();
+    let Some(vault_root) = vault_state.root.lock().ok().and_then(|root| root.clone()) else {
+        return;
+    };
+    let drive_state = app_handle.state::<VaultDriveState>();
+    let present = vault_root.is_dir();
+    let Ok(mut available) = drive_state.available.lock() else {
+        return;
+    };
+    if *available == present {
+        // Writes queued in an earlier session go out on the first check
+        let has_queue = drive_state
+            .queue
+            .lock()
+            .is_ok_and(|queue| !queue.is_empty());
+        if !(present && has_queue) {
+            return;
+        }
+    }
+    *available = present;
+    drop(available);
+
+    let mut event = VaultAvailabilityEvent {
+        vault_root: vault_root.to_string_lossy().to_string(),
+        drive: detect_drive_kind(&vault_root),
+        queued_writes: drive_state
+            .queue
+            .lock()
+            .map(|queue| queue.len())
+            .unwrap_or(0),
+        replayed: 0,
+        failed: Vec::new(),
+    };
+    let name = if present {
+        match replay_writes(&drive_state, &vault_root) {
+            Ok((replayed, failed)) => {
+                event.replayed = replayed;
+                event.failed = failed;
+            }
+            Err(err) => {
+                tracing::warn!(target: "vault", "queued writes not replayed: {}", err.message)
+            }
+        }
+        tracing::info!(target: "vault", "vault available again: replayed={}, failed={}", event.replayed, event.failed.len());
+        VAULT_AVAILABLE_EVENT
+    } else {
+        tracing::warn!(target: "vault", "vault unavailable: vault_root={}, drive={:?}", event.vault_root, event.drive);
+        VAULT_UNAVAILABLE_EVENT
+    };
+    if let Err(err) = app_handle.emit(name, &event) {
+        tracing::warn!(target: "vault", "failed to emit {}: {}", name, err);
+    }
+}
+
+// What kind of drive a path lives on, as far as the platform tells
+pub fn detect_drive_kind(path: &Path) -> DriveKind {
+    platform_drive_kind(path)
+}
+
+#[cfg(windows)]
+fn platform_drive_kind(path: &Path) -> DriveKind {
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::{Component, Prefix};
+    use windows_sys::Win32::Storage::FileSystem::GetDriveTypeW;
+
+    const DRIVE_REMOVABLE: u32 = 2;
+    const DRIVE_FIXED: u32 = 3;
+    const DRIVE_REMOTE: u32 = 4;
+    const DRIVE_CDROM: u32 = 5;
+
+    let Some(Component::Prefix(prefix)) = path.components().next() else {
+        return DriveKind::Unknown;
+    };
+    let root = match prefix.kind() {
+        Prefix::UNC(..) | Prefix::VerbatimUNC(..) => return DriveKind::Network,
+        Prefix::Disk(drive) | Prefix::VerbatimDisk(drive) => format!("{}:\\", drive as char),
+        _ => return DriveKind::Unknown,
+    };
+    let wide: Vec<u16> = std::ffi::OsStr::new(&root)
+        .encode_wide()
+        .chain(Some(0))
+        .collect();
+    // SAFETY: `wide` is a NUL-terminated UTF-16 string that outlives the call
+    match unsafe { GetDriveTypeW(wide.as_ptr()) } {
+        DRIVE_REMOVABLE | DRIVE_CDROM => DriveKind::Removable,
+        DRIVE_REMOTE => DriveKind::Network,
+        DRIVE_FIXED => DriveKind::Local,
+        _ => DriveKind::Unknown,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn platform_drive_kind(path: &Path) -> DriveKind {
+    const NETWORK_FS: &[&str] = &[
+        "nfs",
+        "nfs4",
+        "cifs",
+        "smbfs",
+        "smb3",
+        "9p",
+        "afs",
+        "davfs",
+        "fuse.sshfs",
+        "fuse.rclone",
+    ];
+    let Ok(mounts) = fs::read_to_string("/proc/mounts") else {
+        return DriveKind::Unknown;
+    };
+    // The mount holding the path is the longest mount point it sits under
+    let mount = mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = fields.next()?;
+            let mount_point = fields.next()?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            Some((device, PathBuf::from(mount_point), fs_type))
+        })
+        .filter(|(_, mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(_, mount_point, _)| mount_point.as_os_str().len());
+    let Some((device, mount_point, fs_type)) = mount else {
+        return DriveKind::Unknown;
+    };
+    if NETWORK_FS.contains(&fs_type) {
+        return DriveKind::Network;
+    }
+    // /sys/class/block/<partition> resolves inside its disk, which carries the removable flag
+    let removable = device
+        .strip_prefix("/dev/")
+        .and_then(|name| Path::new("/sys/class/block").join(name).canonicalize().ok())
+        .is_some_and(|block| {
+            [Some(block.as_path()), block.parent()]
+                .into_iter()
+                .flatten()
+                .any(|dir| {
+                    fs::read_to_string(dir.join("removable")).is_ok_and(|flag| flag.trim() == "1")
+                })
+        });
+    if removable || mount_point.starts_with("/media") || mount_point.starts_with("/run/media") {
+        DriveKind::Removable
+    } else {
+        DriveKind::Local
+    }
+}
+
+// Everything mounted under /Volumes is an external disk or a network share
+#[cfg(target_os = "macos")]
+fn platform_drive_kind(path: &Path) -> DriveKind {
+    if path.starts_with("/Volumes") {
+        DriveKind::Removable
+    } else {
+        DriveKind::Local
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn platform_drive_kind(_path: &Path) -> DriveKind {
+    DriveKind::Unknown
+}
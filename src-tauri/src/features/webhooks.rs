@@ -0,0 +1,156 @@
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::Utc;
+use reqwest::{Client, StatusCode};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+use crate::domain::planning::Task;
+use crate::domain::webhooks::{WebhookConfig, WebhookDelivery, WebhookEvent, WebhookPayload};
+use crate::repo::settings_repo;
+use crate::services::planning_service::PlanningService;
+use crate::state::AppState;
+
+const MAX_ATTEMPTS: u32 = 4;
+// Doubled after every failed attempt: 2s, 4s, 8s
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const SHA256_BLOCK_SIZE: usize = 64;
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut block = [0u8; SHA256_BLOCK_SIZE];
+    if key.len() > SHA256_BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block.map(|byte| byte ^ 0x36));
+    inner.update(message);
+    let inner = inner.finalize();
+
+    let mut outer = Sha256::new();
+    outer.update(block.map(|byte| byte ^ 0x5c));
+    outer.update(inner);
+    outer.finalize().to_vec()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// Timeouts, 408 and 429 are worth retrying; other client errors are not
+fn retryable(status: StatusCode) -> bool {
+    !status.is_client_error()
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+// POST the payload with retries; blocks the calling thread
+fn deliver(client: &Client, webhook: &WebhookConfig, payload: &WebhookPayload) -> WebhookDelivery {
+    let body = serde_json::to_vec(payload).unwrap_or_default();
+    let signature = (!webhook.secret.is_empty()).then(|| {
+        format!(
+            "sha256={}",
+            to_hex(&hmac_sha256(webhook.secret.as_bytes(), &body))
+        )
+    });
+
+    let mut attempts = 0;
+    let mut status_code = None;
+    let mut error = None;
+    while attempts < MAX_ATTEMPTS {
+        if attempts > 0 {
+            std::thread::sleep(RETRY_BASE_DELAY * 2u32.pow(attempts - 1));
+        }
+        attempts += 1;
+
+        let mut request = client
+            .post(&webhook.url)
+            .timeout(REQUEST_TIMEOUT)
+            .header("Content-Type", "application/json")
+            .header("X-Planning-Event", payload.event.as_str())
+            .header("X-Planning-Delivery", &payload.delivery_id)
+            .body(body.clone());
+        if let Some(signature) = &signature {
+            request = request.header("X-Planning-Signature", signature);
+        }
+
+        match tauri::async_runtime::block_on(request.send()) {
+            Ok(response) => {
+                let status = response.status();
+                status_code = Some(i64::from(status.as_u16()));
+                if status.is_success() {
+                    error = None;
+                    break;
+                }
+                error = Some(format!("HTTP {}", status));
+                if !retryable(status) {
+                    break;
+                }
+            }
+            Err(e) => {
+                status_code = None;
+                error = Some(e.to_string());
+            }
+        }
+    }
+
+    WebhookDelivery {
+        id: payload.delivery_id.clone(),
+        webhook_id: webhook.id.clone(),
+        event: payload.event.as_str().to_string(),
+        task_id: payload.task.id.clone(),
+        status_code,
+        attempts: i64::from(attempts),
+        success: error.is_none(),
+        error,
+        delivered_at: Utc::now().to_rfc3339(),
+    }
+}
+
+// Send an event to every subscribed webhook in the background and log each delivery
+pub fn dispatch(app_handle: &AppHandle, vault_root: &Path, event: WebhookEvent, task: &Task) {
+    let webhooks = match settings_repo::get_webhooks(vault_root) {
+        Ok(webhooks) => webhooks,
+        Err(err) => {
+            tracing::warn!(target: "webhooks", "failed to load webhooks: {}", err.message);
+            return;
+        }
+    };
+    let client = app_handle.state::<AppState>().http_client.clone();
+
+    for webhook in webhooks
+        .into_iter()
+        .filter(|webhook| webhook.subscribes_to(event))
+    {
+        let payload = WebhookPayload {
+            delivery_id: uuid::Uuid::new_v4().to_string(),
+            event,
+            occurred_at: Utc::now().to_rfc3339(),
+            task: task.clone(),
+        };
+        let client = client.clone();
+        let vault_root = vault_root.to_path_buf();
+        std::thread::spawn(move || {
+            let delivery = deliver(&client, &webhook, &payload);
+            if !delivery.success {
+                tracing::warn!(
+                    target: "webhooks",
+                    "webhook delivery failed: webhook_id={}, event={}, attempts={}, error={:?}",
+                    delivery.webhook_id,
+                    delivery.event,
+                    delivery.attempts,
+                    delivery.error
+                );
+            }
+            if let Err(err) = PlanningService::open(&vault_root)
+                .and_then(|service| service.record_webhook_delivery(&delivery))
+            {
+                tracing::warn!(target: "webhooks", "failed to log webhook delivery: {}", err.message);
+            }
+        });
+    }
+}
@@ -1,10 +1,149 @@
 use rusqlite::Error as RusqliteError;
-use serde::Serialize;
+use serde::{Serialize, Serializer};
 use std::path::Path;
 
+// Central registry of error codes returned over IPC.
+// Each variant serializes to the same wire string the frontend already matches on,
+// so new subsystems must add a variant here instead of inventing ad-hoc strings.
+// The ErrorCode union in src/shared/types/api.ts mirrors these wire strings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    // Vault selection and path policy
+    VaultNotSelected,
+    NoVaultSelected,
+    NotFound,
+    PermissionDenied,
+    PathOutsideVault,
+    SymlinkNotAllowed,
+    // File system
+    WriteFailed,
+    DecodeFailed,
+    ScanFailed,
+    ScanLimited,
+    LargeVault,
+    FileReadError,
+    FileWriteError,
+    FileRenameError,
+    FileDeleteError,
+    IoError,
+    ConfigDirNotFound,
+    NoteLocked,
+    VaultUnavailable, // Vault folder is gone, e.g. an ejected or disconnected drive
+    // Plugins
+    InvalidManifest,
+    EntryNotFound,
+    // Planning
+    DatabaseError,
+    DateTimeError,
+    LockError,
+    DatabaseLocked, // Encrypted planning database not unlocked yet
+    MutexPoisoned,
+    JsonError,
+    InvalidStateTransition,
+    DueDateRequired,
+    BoardIdRequired,
+    InvalidSchedule,
+    InvalidInput,
+    WipLimitExceeded,
+    TimerOverlap,
+    // AI
+    AiRequestFailed,
+    AiProviderError,
+    AiParseFailed,
+    AiEmptyResponse,
+    RateLimited,
+    Offline,
+    EmbeddingFailed,
+    TranscriptionFailed,
+    OcrFailed,
+    ClipFailed,
+    WebviewFailed,
+    // Integrations
+    GithubRequestFailed,
+    GithubRateLimited,
+    Unknown,
+}
+
+impl ErrorCode {
+    // Wire representation shared with the frontend
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::VaultNotSelected => "VaultNotSelected",
+            ErrorCode::NoVaultSelected => "NoVaultSelected",
+            ErrorCode::NotFound => "NotFound",
+            ErrorCode::PermissionDenied => "PermissionDenied",
+            ErrorCode::PathOutsideVault => "PathOutsideVault",
+            ErrorCode::SymlinkNotAllowed => "SymlinkNotAllowed",
+            ErrorCode::WriteFailed => "WriteFailed",
+            ErrorCode::DecodeFailed => "DecodeFailed",
+            ErrorCode::ScanFailed => "ScanFailed",
+            ErrorCode::ScanLimited => "ScanLimited",
+            ErrorCode::LargeVault => "LargeVault",
+            ErrorCode::FileReadError => "FileReadError",
+            ErrorCode::FileWriteError => "FileWriteError",
+            ErrorCode::FileRenameError => "FileRenameError",
+            ErrorCode::FileDeleteError => "FileDeleteError",
+            ErrorCode::IoError => "IOError",
+            ErrorCode::ConfigDirNotFound => "ConfigDirNotFound",
+            ErrorCode::NoteLocked => "NoteLocked",
+            ErrorCode::VaultUnavailable => "VaultUnavailable",
+            ErrorCode::InvalidManifest => "InvalidManifest",
+            ErrorCode::EntryNotFound => "EntryNotFound",
+            ErrorCode::DatabaseError => "DatabaseError",
+            ErrorCode::DateTimeError => "DateTimeError",
+            ErrorCode::LockError => "LockError",
+            ErrorCode::DatabaseLocked => "DatabaseLocked",
+            ErrorCode::MutexPoisoned => "MutexPoisoned",
+            ErrorCode::JsonError => "JsonError",
+            ErrorCode::InvalidStateTransition => "InvalidStateTransition",
+            ErrorCode::DueDateRequired => "DUE_DATE_REQUIRED",
+            ErrorCode::BoardIdRequired => "BOARD_ID_REQUIRED",
+            ErrorCode::InvalidSchedule => "InvalidSchedule",
+            ErrorCode::InvalidInput => "InvalidInput",
+            ErrorCode::WipLimitExceeded => "WipLimitExceeded",
+            ErrorCode::TimerOverlap => "TimerOverlap",
+            ErrorCode::AiRequestFailed => "AiRequestFailed",
+            ErrorCode::AiProviderError => "AiProviderError",
+            ErrorCode::AiParseFailed => "AiParseFailed",
+            ErrorCode::AiEmptyResponse => "AiEmptyResponse",
+            ErrorCode::RateLimited => "RateLimited",
+            ErrorCode::Offline => "Offline",
+            ErrorCode::EmbeddingFailed => "EmbeddingFailed",
+            ErrorCode::TranscriptionFailed => "TranscriptionFailed",
+            ErrorCode::OcrFailed => "OcrFailed",
+            ErrorCode::ClipFailed => "ClipFailed",
+            ErrorCode::WebviewFailed => "WebviewFailed",
+            ErrorCode::GithubRequestFailed => "GithubRequestFailed",
+            ErrorCode::GithubRateLimited => "GithubRateLimited",
+            ErrorCode::Unknown => "Unknown",
+        }
+    }
+
+    // Map an io::Error kind onto the closest shared code
+    pub fn from_io_kind(kind: std::io::ErrorKind, fallback: ErrorCode) -> ErrorCode {
+        match kind {
+            std::io::ErrorKind::NotFound => ErrorCode::NotFound,
+            std::io::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+            _ => fallback,
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub struct ApiError {
-    pub code: String,
+    pub code: ErrorCode,
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<serde_json::Value>,
@@ -28,11 +167,11 @@ impl<T> ApiResponse<T> {
         ApiResponse::Ok { ok: true, data }
     }
 
-    pub fn err(code: &str, message: &str, details: Option<serde_json::Value>) -> Self {
+    pub fn err(code: ErrorCode, message: &str, details: Option<serde_json::Value>) -> Self {
         ApiResponse::Err {
             ok: false,
             error: ApiError {
-                code: code.to_string(),
+                code,
                 message: message.to_string(),
                 details,
             },
@@ -40,42 +179,32 @@ impl<T> ApiResponse<T> {
     }
 }
 
-pub fn map_io_error(code: &str, message: &str, err: std::io::Error) -> ApiError {
+pub fn map_io_error(code: ErrorCode, message: &str, err: std::io::Error) -> ApiError {
     ApiError {
-        code: code.to_string(),
+        code,
         message: message.to_string(),
         details: Some(serde_json::json!({ "error": err.to_string() })),
     }
 }
 
 pub fn map_read_error(err: std::io::Error) -> ApiError {
-    match err.kind() {
-        std::io::ErrorKind::NotFound => ApiError {
-            code: "NotFound".to_string(),
-            message: "File not found".to_string(),
-            details: Some(serde_json::json!({ "error": err.to_string() })),
-        },
-        std::io::ErrorKind::PermissionDenied => ApiError {
-            code: "PermissionDenied".to_string(),
-            message: "Permission denied".to_string(),
-            details: Some(serde_json::json!({ "error": err.to_string() })),
-        },
-        _ => ApiError {
-            code: "Unknown".to_string(),
-            message: "Failed to read file".to_string(),
-            details: Some(serde_json::json!({ "error": err.to_string() })),
-        },
+    let code = ErrorCode::from_io_kind(err.kind(), ErrorCode::Unknown);
+    let message = match code {
+        ErrorCode::NotFound => "File not found",
+        ErrorCode::PermissionDenied => "Permission denied",
+        _ => "Failed to read file",
+    };
+    ApiError {
+        code,
+        message: message.to_string(),
+        details: Some(serde_json::json!({ "error": err.to_string() })),
     }
 }
 
 pub fn map_write_error(message: &str, err: std::io::Error) -> ApiError {
-    let code = match err.kind() {
-        std::io::ErrorKind::PermissionDenied => "PermissionDenied",
-        std::io::ErrorKind::NotFound => "NotFound",
-        _ => "WriteFailed",
-    };
+    let code = ErrorCode::from_io_kind(err.kind(), ErrorCode::WriteFailed);
     ApiError {
-        code: code.to_string(),
+        code,
         message: message.to_string(),
         details: Some(serde_json::json!({ "error": err.to_string() })),
     }
@@ -87,13 +216,9 @@ pub fn write_error_with_context(
     step: &str,
     path: &Path,
 ) -> ApiError {
-    let code = match err.kind() {
-        std::io::ErrorKind::PermissionDenied => "PermissionDenied",
-        std::io::ErrorKind::NotFound => "NotFound",
-        _ => "WriteFailed",
-    };
+    let code = ErrorCode::from_io_kind(err.kind(), ErrorCode::WriteFailed);
     ApiError {
-        code: code.to_string(),
+        code,
         message: message.to_string(),
         details: Some(serde_json::json!({
             "step": step,
@@ -107,7 +232,7 @@ pub fn write_error_with_context(
 impl From<RusqliteError> for ApiError {
     fn from(err: RusqliteError) -> Self {
         ApiError {
-            code: "DatabaseError".to_string(),
+            code: ErrorCode::DatabaseError,
             message: format!("Database operation failed: {}", err),
             details: Some(serde_json::json!({ "error": err.to_string() })),
         }
@@ -118,7 +243,7 @@ impl From<RusqliteError> for ApiError {
 impl<T> From<std::sync::PoisonError<T>> for ApiError {
     fn from(err: std::sync::PoisonError<T>) -> Self {
         ApiError {
-            code: "MutexPoisoned".to_string(),
+            code: ErrorCode::MutexPoisoned,
             message: format!("Mutex was poisoned: {}", err),
             details: Some(serde_json::json!({ "error": err.to_string() })),
         }
@@ -129,7 +254,7 @@ impl<T> From<std::sync::PoisonError<T>> for ApiError {
 impl From<serde_json::Error> for ApiError {
     fn from(err: serde_json::Error) -> Self {
         ApiError {
-            code: "JsonError".to_string(),
+            code: ErrorCode::JsonError,
             message: format!("JSON operation failed: {}", err),
             details: Some(serde_json::json!({ "error": err.to_string() })),
         }
@@ -140,7 +265,7 @@ impl From<serde_json::Error> for ApiError {
 impl From<std::io::Error> for ApiError {
     fn from(err: std::io::Error) -> Self {
         ApiError {
-            code: "IOError".to_string(),
+            code: ErrorCode::IoError,
             message: format!("IO operation failed: {}", err),
             details: Some(serde_json::json!({ "error": err.to_string() })),
         }
@@ -1,8 +1,10 @@
 mod bootstrap;
+mod cli;
 mod commands;
 mod domain;
 mod features;
 mod ipc;
+mod mcp;
 mod paths;
 mod repo;
 mod security;
@@ -11,36 +13,97 @@ mod state;
 mod webview_bridge;
 
 use tauri::Manager;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
+
+// Entry point for `--cli` invocations of the desktop binary
+pub fn run_cli(args: &[String]) -> i32 {
+    cli::run(args)
+}
+
+// Entry point for `--mcp`: serve the vault to external AI agents over stdio
+pub fn run_mcp(args: &[String]) -> i32 {
+    mcp::run(args)
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Initialize tracing logging system
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::INFO)
-        .with_target(false)
+    // Span timings also feed the performance metrics
+    tracing_subscriber::registry()
+        .with(LevelFilter::INFO)
+        .with(tracing_subscriber::fmt::layer().with_target(false))
+        .with(features::metrics::SpanTimingLayer)
         .init();
 
-    tauri::Builder::default()
+    let builder = tauri::Builder::default();
+    // Must come first: a second launch hands its arguments to the running app and exits, and
+    // deep links among them reach deep_link::listen
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(
+        |app_handle, _args, _cwd| {
+            features::deep_link::focus_main_window(app_handle);
+        },
+    ));
+    builder
         .setup(|app| {
+            bootstrap::init_settings_layers(app)?;
             let state = bootstrap::init_vault_state(app)?;
+            let recovery_state = bootstrap::init_timer_recovery_state(app, &state);
+            let http_api_state = bootstrap::init_http_api_state(app, &state);
+            let drive_state = bootstrap::init_vault_drive_state(app, &state)?;
             app.manage(state);
+            app.manage(drive_state);
+            app.manage(http_api_state);
+            app.manage(recovery_state);
+            bootstrap::spawn_timer_heartbeat(app);
+            bootstrap::spawn_health_check(app, &app.state::<state::VaultState>());
+            bootstrap::spawn_md_sync_flush(app);
+            app.manage(bootstrap::init_board_index_state());
+            bootstrap::spawn_board_index_writer(app);
+            features::vault_drive::spawn_watch(app);
+            features::notifications::spawn_scheduler(app);
+            bootstrap::listen_webview_history(app);
             app.manage(bootstrap::init_app_state());
-            app.manage(
-                features::ai::embedding::EmbeddingEngine::new()
-                    .expect("failed to init embedding engine"),
-            );
+            app.manage(bootstrap::init_focus_state());
+            // Models load on first use and are only fetched by download_embedding_model
+            app.manage(features::ai::embedding::EmbeddingEngine::new(
+                features::ai::embedding::models_dir(&app.path().app_data_dir()?),
+            ));
+            bootstrap::spawn_embedding_preload(app, &app.state::<state::VaultState>());
+            features::deep_link::listen(app);
             Ok(())
         })
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(webview_bridge::init_webview_bridge())
         .plugin(tauri_plugin_opener::init())
         .invoke_handler(tauri::generate_handler![
+            commands::vault::get_app_state,
             commands::vault::select_vault,
+            commands::vault::relocate_vault,
+            commands::vault::get_vault_drive_status,
+            commands::vault::create_new_vault,
             commands::vault::scan_vault,
+            commands::vault::scan_vault_stream,
+            commands::vault::scan_vault_changes,
             commands::vault::read_markdown,
             commands::vault::write_markdown,
+            commands::vault::get_note_frontmatter,
+            commands::vault::set_note_frontmatter,
+            commands::vault::get_note_outline,
+            commands::vault::note_stats,
+            commands::vault::folder_note_stats,
+            commands::vault::lint_note,
+            commands::vault::check_links,
+            commands::vault::append_under_heading,
+            commands::vault::acquire_note_lock,
+            commands::vault::release_note_lock,
             commands::vault::rename_markdown,
+            commands::vault::batch_rename,
             commands::vault::delete_entry,
             commands::vault::create_entry,
+            commands::vault::copy_entry,
+            commands::vault::duplicate_note,
             commands::plugins::plugins_list,
             commands::plugins::plugins_read_manifest,
             commands::plugins::plugins_read_entry,
@@ -49,24 +112,157 @@ pub fn run() {
             commands::plugins::vault_write_text,
             commands::plugins::vault_list_files,
             commands::planning_cmd::planning_list_today,
+            commands::planning_cmd::planning_list_done,
             commands::planning_cmd::planning_create_task,
+            commands::planning_cmd::planning_create_tasks_batch,
             commands::planning_cmd::planning_update_task,
             commands::planning_cmd::planning_mark_done,
             commands::planning_cmd::planning_reopen_task,
             commands::planning_cmd::planning_start_task,
             commands::planning_cmd::planning_stop_task,
+            commands::planning_cmd::planning_list_timers,
+            commands::planning_cmd::planning_recover_timers,
+            commands::planning_cmd::planning_add_manual_timer,
+            commands::planning_cmd::planning_edit_timer,
+            commands::planning_cmd::planning_delete_timer,
             commands::planning_cmd::planning_open_daily,
             commands::planning_cmd::planning_open_task_note,
             commands::planning_cmd::planning_reorder_tasks,
+            commands::planning_cmd::planning_schedule_task,
+            commands::planning_cmd::planning_shift_schedule,
+            commands::planning_cmd::planning_get_day_schedule,
+            commands::planning_cmd::planning_calendar_range,
+            commands::planning_cmd::planning_preview_occurrences,
+            commands::planning_cmd::planning_periodicity_to_rrule,
+            commands::planning_cmd::planning_periodicity_from_rrule,
+            commands::planning_cmd::planning_estimate_report,
+            commands::planning_cmd::planning_week_review,
+            commands::planning_cmd::planning_run_rules,
+            commands::planning_cmd::planning_search_tasks,
+            commands::planning_cmd::planning_link_note,
+            commands::planning_cmd::planning_unlink_note,
+            commands::planning_cmd::planning_list_task_notes,
+            commands::planning_cmd::planning_list_note_tasks,
+            commands::planning_cmd::planning_list_board_columns,
+            commands::planning_cmd::planning_list_boards,
+            commands::planning_cmd::planning_set_board_color,
+            commands::planning_cmd::planning_save_board_columns,
+            commands::planning_cmd::planning_list_board_fields,
+            commands::planning_cmd::planning_save_board_fields,
+            commands::planning_cmd::planning_get_task_fields,
+            commands::planning_cmd::planning_set_task_fields,
+            commands::planning_cmd::planning_list_board_field_values,
+            commands::planning_cmd::planning_query_tasks_by_fields,
+            commands::planning_cmd::planning_create_sprint,
+            commands::planning_cmd::planning_update_sprint,
+            commands::planning_cmd::planning_delete_sprint,
+            commands::planning_cmd::planning_list_sprints,
+            commands::planning_cmd::planning_assign_to_sprint,
+            commands::planning_cmd::planning_unassign_from_sprint,
+            commands::planning_cmd::planning_get_sprint_summary,
+            commands::planning_cmd::planning_get_sprint_burndown,
+            commands::planning_cmd::planning_list_overdue,
+            commands::planning_cmd::planning_list_upcoming,
+            commands::planning_cmd::planning_journal_streak,
+            commands::planning_cmd::planning_missing_days,
+            commands::planning_cmd::query_notes,
+            commands::planning_cmd::planning_export_board_md,
+            commands::planning_cmd::planning_export_plaintext,
+            commands::planning_cmd::planning_write_board_indexes,
+            commands::planning_cmd::planning_export_all,
+            commands::planning_cmd::planning_import_all,
+            commands::planning_cmd::planning_import_table,
+            commands::planning_cmd::planning_import_outliner,
+            commands::planning_cmd::planning_merge_vault,
             commands::planning_cmd::planning_get_ui_state,
             commands::planning_cmd::planning_set_ui_state,
             commands::planning_cmd::planning_delete_task,
             commands::planning_cmd::planning_ai_smart_capture,
+            commands::planning_cmd::ai_capture_from_note,
+            commands::planning_cmd::ai_voice_capture,
+            commands::planning_cmd::ai_capture_from_image,
+            commands::planning_cmd::planning_list_tags,
+            commands::planning_cmd::ai_suggest_tags,
+            commands::planning_cmd::clear_ai_cache,
             commands::planning_cmd::planning_get_ai_settings,
             commands::planning_cmd::planning_save_ai_settings,
+            commands::planning_cmd::planning_get_planning_settings,
+            commands::planning_cmd::planning_save_planning_settings,
+            commands::planning_cmd::planning_set_vault_layout,
+            commands::planning_cmd::planning_consolidate_task_notes,
+            commands::planning_cmd::planning_repair_task_slugs,
+            commands::planning_cmd::planning_health_check,
+            commands::planning_cmd::planning_db_encryption_status,
+            commands::planning_cmd::planning_unlock_db,
+            commands::planning_cmd::planning_lock_db,
+            commands::planning_cmd::planning_enable_db_encryption,
+            commands::planning_cmd::planning_disable_db_encryption,
+            commands::metrics_cmd::get_perf_metrics,
+            commands::http_api_cmd::http_api_get_settings,
+            commands::http_api_cmd::http_api_save_settings,
+            commands::github_cmd::github_get_status,
+            commands::github_cmd::github_set_token,
+            commands::github_cmd::github_link_board,
+            commands::github_cmd::github_sync_now,
+            commands::webhook_cmd::webhook_list,
+            commands::webhook_cmd::webhook_save,
+            commands::webhook_cmd::webhook_list_deliveries,
+            commands::focus_cmd::focus_start,
+            commands::focus_cmd::focus_end,
+            commands::focus_cmd::focus_get_state,
             commands::ai_cmd::ai_generate_embeddings,
-            commands::ai_cmd::ai_search_similar
+            commands::ai_cmd::ai_search_similar,
+            commands::ai_cmd::get_embedding_status,
+            commands::ai_cmd::download_embedding_model,
+            commands::ai_cmd::rebuild_semantic_index,
+            commands::clipper_cmd::clip_url,
+            commands::webview_cmd::webview_open,
+            commands::webview_cmd::webview_close,
+            commands::webview_cmd::webview_list,
+            commands::webview_cmd::webview_get_history,
+            commands::reading_list_cmd::reading_list_add,
+            commands::reading_list_cmd::reading_list_list,
+            commands::reading_list_cmd::reading_list_mark_read,
+            commands::reading_list_cmd::reading_list_to_task,
+            commands::reading_list_cmd::reading_list_clip,
+            commands::reading_list_cmd::reading_list_remove,
+            commands::reminder_cmd::reminder_create,
+            commands::reminder_cmd::reminder_update,
+            commands::reminder_cmd::reminder_delete,
+            commands::reminder_cmd::reminder_list,
+            commands::reminder_cmd::list_notifications,
+            commands::reminder_cmd::snooze_notification,
+            commands::reminder_cmd::dismiss_notification,
+            commands::reminder_cmd::test_notification,
+            commands::workspace_cmd::get_workspace_state,
+            commands::workspace_cmd::favorites_list,
+            commands::workspace_cmd::favorites_add,
+            commands::workspace_cmd::favorites_remove,
+            commands::workspace_cmd::favorites_reorder,
+            commands::workspace_cmd::record_note_opened,
+            commands::workspace_cmd::get_recent_notes,
+            commands::workspace_cmd::save_layout,
+            commands::workspace_cmd::load_layout,
+            commands::workspace_cmd::list_layouts,
+            commands::workspace_cmd::delete_layout,
+            commands::settings_cmd::validate_settings,
+            commands::settings_cmd::reset_settings_section,
+            commands::settings_cmd::get_settings_layers,
+            commands::settings_cmd::get_effective_ai_config,
+            commands::settings_cmd::set_settings_layer_section,
+            commands::settings_cmd::move_settings_section,
+            commands::ai_cmd::search_hybrid,
+            commands::ai_cmd::suggest_related_notes
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // Windows and Linux launch the app with the deep link as an argument
+            if let tauri::RunEvent::Ready = event {
+                let args: Vec<String> = std::env::args().skip(1).collect();
+                for link in features::deep_link::links_from_args(&args) {
+                    features::deep_link::dispatch(app_handle, &link);
+                }
+            }
+        });
 }
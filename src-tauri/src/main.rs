@@ -2,5 +2,15 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // `--cli` runs a single command against a vault without starting the GUI
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "--cli") {
+        std::process::exit(tauri_planning_app_lib::run_cli(&args[1..]));
+    }
+    // `--mcp` serves the vault to AI agents over stdio until stdin closes
+    if args.first().is_some_and(|arg| arg == "--mcp") {
+        std::process::exit(tauri_planning_app_lib::run_mcp(&args[1..]));
+    }
+
     tauri_planning_app_lib::run()
 }
@@ -0,0 +1,291 @@
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::cli;
+use crate::domain::planning::{CreateTaskInput, TaskStatus};
+use crate::domain::timezone;
+use crate::ipc::{ApiError, ErrorCode};
+use crate::repo::settings_repo;
+use crate::services::planning_service::PlanningService;
+use crate::services::vault_service;
+
+// MCP revision this server speaks
+const PROTOCOL_VERSION: &str = "2024-11-05";
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+// Default and maximum number of search_notes results
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+const MAX_SEARCH_LIMIT: usize = 100;
+// Characters of context kept on each side of a search match
+const SNIPPET_CONTEXT_CHARS: usize = 60;
+
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "search_notes",
+            "description": "Search markdown notes in the vault for all of the given words (case-insensitive).",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string" },
+                    "limit": { "type": "integer", "minimum": 1, "maximum": MAX_SEARCH_LIMIT }
+                },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "read_note",
+            "description": "Read a markdown note by its vault-relative path.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "path": { "type": "string" } },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "create_task",
+            "description": "Create a planning task. Todo tasks without a due date are due today.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string" },
+                    "description": { "type": "string" },
+                    "status": { "type": "string", "enum": ["todo", "doing", "verify", "done"] },
+                    "priority": { "type": "string", "enum": ["p0", "p1", "p2", "p3"] },
+                    "due_date": { "type": "string", "description": "YYYY-MM-DD" },
+                    "estimate_min": { "type": "integer" },
+                    "tags": { "type": "array", "items": { "type": "string" } }
+                },
+                "required": ["title"]
+            }
+        },
+        {
+            "name": "list_today",
+            "description": "Get today's planning data: kanban boards, timeline and current timer.",
+            "inputSchema": { "type": "object", "properties": {} }
+        }
+    ])
+}
+
+fn invalid(message: &str) -> ApiError {
+    ApiError {
+        code: ErrorCode::InvalidInput,
+        message: message.to_string(),
+        details: None,
+    }
+}
+
+fn encode(value: impl serde::Serialize) -> Result<String, ApiError> {
+    serde_json::to_string_pretty(&value).map_err(|e| ApiError {
+        code: ErrorCode::JsonError,
+        message: format!("Failed to encode result: {}", e),
+        details: None,
+    })
+}
+
+// Text around the first occurrence of `needle` in `content`
+fn snippet(content: &str, needle: &str) -> String {
+    let lower = content.to_lowercase();
+    let Some(byte_idx) = lower.find(needle) else {
+        return String::new();
+    };
+    // Lowercasing can shift byte offsets, so work in chars from the matching prefix
+    let match_char = lower[..byte_idx].chars().count();
+    let chars: Vec<char> = content.chars().collect();
+    let start = match_char.saturating_sub(SNIPPET_CONTEXT_CHARS);
+    let end = (match_char + needle.chars().count() + SNIPPET_CONTEXT_CHARS).min(chars.len());
+    chars[start.min(end)..end]
+        .iter()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn search_notes(vault_root: &Path, arguments: &Value) -> Result<String, ApiError> {
+    let query = arguments
+        .get("query")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_lowercase();
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    if terms.is_empty() {
+        return Err(invalid("query must not be empty"));
+    }
+    let limit = arguments
+        .get("limit")
+        .and_then(Value::as_u64)
+        .map_or(DEFAULT_SEARCH_LIMIT, |limit| limit as usize)
+        .clamp(1, MAX_SEARCH_LIMIT);
+
+    let (files, _) = vault_service::list_markdown_files(vault_root)?;
+    let mut hits = Vec::new();
+    for path in files {
+        // Unreadable or non-UTF-8 notes are skipped rather than failing the search
+        let Ok(note) = vault_service::read_text_file(vault_root, Path::new(&path)) else {
+            continue;
+        };
+        let lower = note.content.to_lowercase();
+        if !terms.iter().all(|term| lower.contains(term)) {
+            continue;
+        }
+        let matches: usize = terms.iter().map(|term| lower.matches(term).count()).sum();
+        hits.push(json!({
+            "path": path,
+            "matches": matches,
+            "snippet": snippet(&note.content, terms[0]),
+        }));
+    }
+    hits.sort_by_key(|hit| std::cmp::Reverse(hit["matches"].as_u64().unwrap_or(0)));
+    hits.truncate(limit);
+
+    encode(hits)
+}
+
+fn read_note(vault_root: &Path, arguments: &Value) -> Result<String, ApiError> {
+    let path = arguments
+        .get("path")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .unwrap_or_default();
+    if !path.to_lowercase().ends_with(".md") {
+        return Err(invalid("Only markdown notes can be read"));
+    }
+    // Same path policy as the app: no absolute paths, parent segments or symlinks
+    let note = vault_service::read_text_file(vault_root, Path::new(path))?;
+    Ok(note.content)
+}
+
+fn create_task(vault_root: &Path, arguments: &Value) -> Result<String, ApiError> {
+    let mut input = arguments.clone();
+    let fields = input
+        .as_object_mut()
+        .ok_or_else(|| invalid("arguments must be an object"))?;
+    fields.entry("status").or_insert_with(|| json!("todo"));
+
+    let mut input: CreateTaskInput = serde_json::from_value(input)
+        .map_err(|e| invalid(&format!("Invalid task arguments: {}", e)))?;
+    if input.due_date.is_none() && matches!(input.status, TaskStatus::Todo | TaskStatus::Doing) {
+        let planning_settings =
+            settings_repo::get_planning_settings(vault_root).unwrap_or_default();
+        let tz = settings_repo::resolve_timezone(&planning_settings);
+        input.due_date = Some(timezone::today_in(tz).format("%Y-%m-%d").to_string());
+    }
+
+    let service = PlanningService::open(vault_root)?;
+    encode(service.create_task(input)?)
+}
+
+fn list_today(vault_root: &Path) -> Result<String, ApiError> {
+    let service = PlanningService::open(vault_root)?;
+    encode(service.get_today_data(None)?)
+}
+
+fn call_tool(vault_root: &Path, params: &Value) -> Result<Value, (i64, String)> {
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let empty = json!({});
+    let arguments = params.get("arguments").unwrap_or(&empty);
+
+    let result = match name {
+        "search_notes" => search_notes(vault_root, arguments),
+        "read_note" => read_note(vault_root, arguments),
+        "create_task" => create_task(vault_root, arguments),
+        "list_today" => list_today(vault_root),
+        _ => return Err((INVALID_PARAMS, format!("Unknown tool: {}", name))),
+    };
+
+    // Tool failures are reported in the result so the agent can see and react to them
+    Ok(match result {
+        Ok(text) => json!({ "content": [{ "type": "text", "text": text }], "isError": false }),
+        Err(err) => json!({
+            "content": [{ "type": "text", "text": format!("[{}] {}", err.code.as_str(), err.message) }],
+            "isError": true
+        }),
+    })
+}
+
+// Response for one JSON-RPC message; notifications get none
+fn handle_message(vault_root: &Path, message: &Value) -> Option<Value> {
+    let id = message.get("id")?.clone();
+    let method = message
+        .get("method")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "initialize" => Ok(json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "tauri-planning-app", "version": env!("CARGO_PKG_VERSION") }
+        })),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+        "tools/call" => call_tool(vault_root, &params),
+        _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+    };
+
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => {
+            json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+        }
+    })
+}
+
+// Serve MCP over stdio (newline-delimited JSON-RPC) for one vault; returns the process exit code
+pub fn run(args: &[String]) -> i32 {
+    // stdout carries protocol messages only, so logs go to stderr
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::WARN)
+        .with_target(false)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let vault_arg = args
+        .iter()
+        .position(|arg| arg == "--vault")
+        .and_then(|index| args.get(index + 1));
+    let vault_root = match cli::resolve_vault(vault_arg.map(String::as_str)) {
+        Ok(vault_root) => vault_root,
+        Err(err) => {
+            eprintln!("error: [{}] {}", err.code.as_str(), err.message);
+            return 2;
+        }
+    };
+
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle_message(&vault_root, &message),
+            Err(e) => Some(json!({
+                "jsonrpc": "2.0",
+                "id": Value::Null,
+                "error": { "code": PARSE_ERROR, "message": e.to_string() }
+            })),
+        };
+        if let Some(response) = response {
+            if writeln!(stdout, "{}", response)
+                .and_then(|_| stdout.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+    }
+    0
+}
@@ -1,12 +1,19 @@
 use std::path::Path;
 use std::path::PathBuf;
 
+use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
+use unicode_normalization::UnicodeNormalization;
 
 pub fn canonical_to_string(path: &Path) -> String {
     path.to_string_lossy().to_string()
 }
 
+// Composed (NFC) form; macOS hands out decomposed names that otherwise compare unequal
+pub fn nfc(name: &str) -> String {
+    name.nfc().collect()
+}
+
 pub fn rel_path_string(path: &Path) -> String {
     path.iter()
         .map(|part| part.to_string_lossy())
@@ -21,14 +28,14 @@ pub fn get_app_config_dir(app_handle: &AppHandle) -> Result<PathBuf, crate::ipc:
         .path()
         .app_data_dir()
         .map_err(|e| crate::ipc::ApiError {
-            code: "ConfigDirNotFound".to_string(),
+            code: crate::ipc::ErrorCode::ConfigDirNotFound,
             message: format!("Failed to get application data directory: {}", e),
             details: None,
         })?;
 
     // Ensure the directory exists
     std::fs::create_dir_all(&config_dir).map_err(|e| crate::ipc::ApiError {
-        code: "ConfigDirNotFound".to_string(),
+        code: crate::ipc::ErrorCode::ConfigDirNotFound,
         message: format!("Failed to create config directory: {}", e),
         details: None,
     })?;
@@ -40,19 +47,130 @@ pub fn get_app_config_dir(app_handle: &AppHandle) -> Result<PathBuf, crate::ipc:
 // Planning System Path Management
 // ============================================================================
 
-/// Get the .planning directory path within a vault
-pub fn planning_dir(vault_root: &Path) -> PathBuf {
-    vault_root.join(".planning")
+/// Per-vault names of the planning directories and task note file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultLayout {
+    #[serde(default = "default_planning_dir")]
+    pub planning_dir: String, // Vault-relative, holds the database and daily logs
+    #[serde(default = "default_tasks_dir")]
+    pub tasks_dir: String, // Vault-relative, one sub-directory per task
+    #[serde(default = "default_task_note_name")]
+    pub task_note_name: String, // File name of the note inside each task directory
+}
+
+impl Default for VaultLayout {
+    fn default() -> Self {
+        Self {
+            planning_dir: default_planning_dir(),
+            tasks_dir: default_tasks_dir(),
+            task_note_name: default_task_note_name(),
+        }
+    }
+}
+
+fn default_planning_dir() -> String {
+    ".planning".to_string()
+}
+
+fn default_tasks_dir() -> String {
+    "tasks".to_string()
+}
+
+fn default_task_note_name() -> String {
+    "任务详情.md".to_string()
+}
+
+/// Get the planning directory path within a vault
+pub fn planning_dir(vault_root: &Path, layout: &VaultLayout) -> PathBuf {
+    vault_root.join(&layout.planning_dir)
 }
 
 /// Get the planning database file path
-pub fn planning_db_path(vault_root: &Path) -> PathBuf {
-    planning_dir(vault_root).join("planning.db")
+pub fn planning_db_path(vault_root: &Path, layout: &VaultLayout) -> PathBuf {
+    planning_dir(vault_root, layout).join("planning.db")
 }
 
 /// Get the vault metadata file path
-pub fn vault_meta_path(vault_root: &Path) -> PathBuf {
-    planning_dir(vault_root).join("vault.json")
+pub fn vault_meta_path(vault_root: &Path, layout: &VaultLayout) -> PathBuf {
+    planning_dir(vault_root, layout).join("vault.json")
+}
+
+/// Get the directory holding all task directories
+pub fn tasks_dir(vault_root: &Path, layout: &VaultLayout) -> PathBuf {
+    vault_root.join(&layout.tasks_dir)
+}
+
+/// Device names Windows reserves in every directory, with or without an extension
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Slugs are capped in bytes as well as characters so CJK titles stay well inside path limits
+const MAX_SLUG_CHARS: usize = 50;
+const MAX_SLUG_BYTES: usize = 80;
+
+/// Whether a file or directory name cannot be created on Windows ("con", "Nul.md", "LPT1.txt")
+pub fn is_reserved_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or_default().trim_end();
+    RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+/// Escape a reserved name by suffixing its stem: "con" -> "con_", "nul.md" -> "nul_.md"
+pub fn escape_reserved_name(name: &str) -> String {
+    if !is_reserved_name(name) {
+        return name.to_string();
+    }
+    let stem_end = name.find('.').unwrap_or(name.len());
+    format!("{}_{}", &name[..stem_end], &name[stem_end..])
+}
+
+/// Prefix an absolute path with `\\?\` on Windows once it nears MAX_PATH; other paths are unchanged
+#[cfg(windows)]
+pub fn long_path(path: &Path) -> PathBuf {
+    use std::ffi::OsString;
+    use std::path::{Component, Prefix};
+
+    const MAX_PATH: usize = 260;
+    if path.as_os_str().len() < MAX_PATH {
+        return path.to_path_buf();
+    }
+
+    let mut components = path.components();
+    let mut long = match components.next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(drive) => OsString::from(format!(r"\\?\{}:", drive as char)),
+            Prefix::UNC(server, share) => {
+                let mut long = OsString::from(r"\\?\UNC\");
+                long.push(server);
+                long.push("\\");
+                long.push(share);
+                long
+            }
+            // Verbatim and device paths already bypass MAX_PATH
+            _ => return path.to_path_buf(),
+        },
+        _ => return path.to_path_buf(),
+    };
+    // Verbatim paths are not normalized, so separators and "." are resolved here
+    for component in components {
+        match component {
+            Component::Normal(part) => {
+                long.push("\\");
+                long.push(part);
+            }
+            Component::RootDir | Component::CurDir => {}
+            Component::ParentDir | Component::Prefix(_) => return path.to_path_buf(),
+        }
+    }
+    PathBuf::from(long)
+}
+
+#[cfg(not(windows))]
+pub fn long_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
 }
 
 /// Generate a safe slug from a title for use in directory names
@@ -62,8 +180,9 @@ pub fn generate_slug(title: &str) -> String {
     let illegal_chars = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
 
     // Replace illegal characters with underscore and collapse multiple underscores
-    let mut slug = title
+    let mut slug = nfc(title)
         .chars()
+        .filter_map(slug_char)
         .map(|c| {
             if illegal_chars.contains(&c) || c.is_control() {
                 '_'
@@ -84,33 +203,160 @@ pub fn generate_slug(title: &str) -> String {
     // Trim leading/trailing underscores
     slug = slug.trim_matches('_').to_string();
 
-    // Limit length to 50 characters to avoid path length issues
+    // Limit length to avoid path length issues
     // Use char_indices to avoid splitting in the middle of a multi-byte character
-    if slug.len() > 50 {
-        if let Some((idx, _)) = slug.char_indices().nth(50) {
-            slug.truncate(idx);
-        }
+    if let Some((idx, _)) = slug
+        .char_indices()
+        .find(|(idx, c)| *idx >= MAX_SLUG_BYTES || idx + c.len_utf8() > MAX_SLUG_BYTES)
+    {
+        slug.truncate(idx);
+    }
+    if let Some((idx, _)) = slug.char_indices().nth(MAX_SLUG_CHARS) {
+        slug.truncate(idx);
     }
 
+    // Windows drops trailing dots and spaces, so names ending in them do not round-trip
+    slug = slug.trim_end_matches(['.', ' ', '_']).to_string();
+
     // Ensure we have at least some content; fallback to "task" if empty
     if slug.is_empty() {
         slug = "task".to_string();
     }
 
-    slug
+    // "con" and friends cannot be created on Windows
+    escape_reserved_name(&slug)
+}
+
+/// Fold full-width forms to ASCII, turn CJK punctuation and emoji into word breaks and drop
+/// emoji joiners and variation selectors
+fn slug_char(c: char) -> Option<char> {
+    match c {
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0),
+        '\u{3000}' => Some(' '),
+        '\u{3001}'..='\u{303F}' => Some('_'),
+        '\u{200D}' | '\u{FE0E}' | '\u{FE0F}' | '\u{1F3FB}'..='\u{1F3FF}' => None,
+        '\u{2600}'..='\u{27BF}' | '\u{1F000}'..='\u{1FAFF}' => Some(' '),
+        c => Some(c),
+    }
 }
 
 /// Get the task directory path (slug only)
-pub fn task_dir_path(vault_root: &Path, _task_id: &str, slug: &str) -> PathBuf {
-    vault_root.join("tasks").join(slug)
+pub fn task_dir_path(
+    vault_root: &Path,
+    layout: &VaultLayout,
+    _task_id: &str,
+    slug: &str,
+) -> PathBuf {
+    tasks_dir(vault_root, layout).join(slug)
 }
 
 /// Get the task markdown file path
-pub fn task_md_path(vault_root: &Path, task_id: &str, slug: &str) -> PathBuf {
-    task_dir_path(vault_root, task_id, slug).join("任务详情.md")
+pub fn task_md_path(vault_root: &Path, layout: &VaultLayout, task_id: &str, slug: &str) -> PathBuf {
+    task_dir_path(vault_root, layout, task_id, slug).join(&layout.task_note_name)
 }
 
 /// Get the task relative path (for storing in DB)
-pub fn task_md_relative_path(_task_id: &str, slug: &str) -> String {
-    format!("tasks/{}/任务详情.md", slug)
+pub fn task_md_relative_path(layout: &VaultLayout, _task_id: &str, slug: &str) -> String {
+    format!("{}/{}/{}", layout.tasks_dir, slug, layout.task_note_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserved_names_match_with_and_without_extension() {
+        assert!(is_reserved_name("CON"));
+        assert!(is_reserved_name("nul.md"));
+        assert!(is_reserved_name("Lpt1.tar.gz"));
+        assert!(is_reserved_name("aux .txt"));
+        assert!(!is_reserved_name("console"));
+        assert!(!is_reserved_name("COM10"));
+        assert!(!is_reserved_name("my-con"));
+    }
+
+    #[test]
+    fn escape_reserved_name_suffixes_the_stem() {
+        assert_eq!(escape_reserved_name("con"), "con_");
+        assert_eq!(escape_reserved_name("NUL.md"), "NUL_.md");
+        assert_eq!(escape_reserved_name("notes.md"), "notes.md");
+        assert!(!is_reserved_name(&escape_reserved_name("prn.txt")));
+    }
+
+    #[test]
+    fn generate_slug_escapes_reserved_names() {
+        assert_eq!(generate_slug("CON"), "CON_");
+        assert_eq!(generate_slug("com1"), "com1_");
+        // Existing slugs are recognised by regenerating them, so this must be stable
+        assert_eq!(generate_slug(&generate_slug("aux")), "aux_");
+    }
+
+    #[test]
+    fn generate_slug_caps_multibyte_titles_in_bytes() {
+        let title =
+            "这是一个非常非常长的中文任务标题用于测试目录名长度限制是否生效以及截断位置是否正确";
+        let slug = generate_slug(title);
+        assert!(slug.len() <= MAX_SLUG_BYTES);
+        assert!(title.starts_with(&slug));
+        assert_eq!(generate_slug(&slug), slug);
+    }
+
+    #[test]
+    fn generate_slug_caps_ascii_titles_in_chars() {
+        let slug = generate_slug(&"a".repeat(120));
+        assert_eq!(slug.len(), MAX_SLUG_CHARS);
+    }
+
+    #[test]
+    fn generate_slug_drops_trailing_dots_and_spaces() {
+        assert_eq!(generate_slug("Release v1. "), "Release_v1");
+        assert_eq!(generate_slug("..."), "task");
+        assert_eq!(generate_slug("a/b:c"), "a_b_c");
+    }
+
+    #[test]
+    fn generate_slug_folds_full_width_punctuation() {
+        assert_eq!(generate_slug("会议：讨论（第一版）"), "会议_讨论(第一版)");
+        assert_eq!(generate_slug("计划，明天。"), "计划,明天");
+        assert_eq!(generate_slug("周报　草稿"), "周报_草稿");
+    }
+
+    #[test]
+    fn generate_slug_drops_emoji() {
+        assert_eq!(generate_slug("📝 Write report"), "Write_report");
+        assert_eq!(generate_slug("Ship it 🚀✨"), "Ship_it");
+        assert_eq!(generate_slug("👍🏽"), "task");
+        assert_eq!(generate_slug("👨‍👩‍👧 family"), "family");
+    }
+
+    #[test]
+    fn generate_slug_composes_decomposed_titles() {
+        assert_eq!(generate_slug("Cafe\u{301}"), "Caf\u{e9}");
+        assert_eq!(nfc("Cafe\u{301}"), nfc("Caf\u{e9}"));
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn long_path_is_a_no_op_off_windows() {
+        let path = PathBuf::from("/vault").join("x".repeat(300));
+        assert_eq!(long_path(&path), path);
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn long_path_prefixes_only_long_absolute_paths() {
+        let short = PathBuf::from(r"C:\vault\note.md");
+        assert_eq!(long_path(&short), short);
+
+        let long = PathBuf::from(r"C:\vault/tasks").join("x".repeat(300));
+        let prefixed = long_path(&long);
+        let prefixed = prefixed.to_string_lossy();
+        assert!(prefixed.starts_with(r"\\?\C:\vault\tasks\"));
+        assert!(!prefixed.contains('/'));
+
+        let unc = PathBuf::from(r"\\server\share\vault").join("x".repeat(300));
+        assert!(long_path(&unc)
+            .to_string_lossy()
+            .starts_with(r"\\?\UNC\server\share\vault\"));
+    }
 }
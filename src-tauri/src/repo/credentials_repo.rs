@@ -0,0 +1,89 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ipc::{map_read_error, map_write_error, ApiError, ErrorCode};
+
+// Kept in the app config dir rather than the vault so tokens are never synced or committed
+const CREDENTIALS_FILE: &str = "credentials.json";
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Credentials {
+    #[serde(default)]
+    pub github_token: Option<String>,
+}
+
+fn credentials_path(config_dir: &Path) -> PathBuf {
+    config_dir.join(CREDENTIALS_FILE)
+}
+
+pub fn load_credentials(config_dir: &Path) -> Result<Credentials, ApiError> {
+    let path = credentials_path(config_dir);
+    if !path.exists() {
+        return Ok(Credentials::default());
+    }
+    let content = fs::read_to_string(&path).map_err(map_read_error)?;
+    serde_json::from_str(&content).map_err(|err| ApiError {
+        code: ErrorCode::DecodeFailed,
+        message: "Failed to decode credentials.json".to_string(),
+        details: Some(serde_json::json!({ "error": err.to_string() })),
+    })
+}
+
+fn save_credentials(config_dir: &Path, credentials: &Credentials) -> Result<(), ApiError> {
+    let path = credentials_path(config_dir);
+    let data = serde_json::to_string_pretty(credentials).map_err(|err| ApiError {
+        code: ErrorCode::WriteFailed,
+        message: "Failed to encode credentials.json".to_string(),
+        details: Some(serde_json::json!({ "error": err.to_string() })),
+    })?;
+
+    // Written in full to a private temp file and renamed over the old one, so the token is never
+    // readable by other users or left half-written
+    let temp_path = config_dir.join(format!(".{}.tmp", CREDENTIALS_FILE));
+    let written =
+        write_private(&temp_path, data.as_bytes()).and_then(|()| fs::rename(&temp_path, &path));
+    if let Err(err) = written {
+        let _ = fs::remove_file(&temp_path);
+        return Err(map_write_error("Failed to write credentials.json", err));
+    }
+    Ok(())
+}
+
+// Create a file readable by the current user only; the mode is set at creation, not afterwards
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    // A leftover temp file would keep whatever mode it was created with
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(data)?;
+    file.sync_all()
+}
+
+pub fn get_github_token(config_dir: &Path) -> Result<Option<String>, ApiError> {
+    let credentials = load_credentials(config_dir)?;
+    Ok(credentials
+        .github_token
+        .filter(|token| !token.trim().is_empty()))
+}
+
+// Store or clear the GitHub personal access token
+pub fn save_github_token(config_dir: &Path, token: Option<&str>) -> Result<(), ApiError> {
+    let mut credentials = load_credentials(config_dir)?;
+    credentials.github_token = token
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(str::to_string);
+    save_credentials(config_dir, &credentials)
+}
@@ -1,5 +1,7 @@
+pub mod credentials_repo;
 pub mod planning_md_repo;
 pub mod planning_repo;
+pub mod scan_snapshot_repo;
 pub mod settings_repo;
 pub mod vault_repo;
 
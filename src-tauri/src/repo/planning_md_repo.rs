@@ -1,17 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::fs::File;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
-use crate::ipc::ApiError;
-use crate::paths::{planning_dir, task_md_path, task_md_relative_path};
+use crate::ipc::{ApiError, ErrorCode};
+use crate::paths::{long_path, planning_dir, task_md_path, task_md_relative_path, VaultLayout};
+use crate::security::note_locks::{self, WriteOrigin};
 use crate::security::path_policy;
 const FRONTMATTER_VERSION: i32 = 2;
 
 // System-managed frontmatter fields
-const SYSTEM_FIELDS: &[&str] = &[
+pub const SYSTEM_FIELDS: &[&str] = &[
     "fm_version",
     "id",
     "title",
@@ -20,6 +21,7 @@ const SYSTEM_FIELDS: &[&str] = &[
     "tags",
     "estimate_min",
     "due_date",
+    "color",
     "created_at",
     "updated_at",
 ];
@@ -27,15 +29,17 @@ const SYSTEM_FIELDS: &[&str] = &[
 // Markdown repository for planning data
 pub struct PlanningMdRepo {
     pub vault_root: PathBuf,
+    pub layout: VaultLayout,
     // Task-level write locks to prevent concurrent updates
     task_locks: Mutex<HashMap<String, Mutex<()>>>,
 }
 
 impl PlanningMdRepo {
     // Create a new instance of PlanningMdRepo
-    pub fn new(vault_root: &Path) -> Result<Self, ApiError> {
+    pub fn new(vault_root: &Path, layout: &VaultLayout) -> Result<Self, ApiError> {
         let repo = Self {
             vault_root: vault_root.to_path_buf(),
+            layout: layout.clone(),
             task_locks: Mutex::new(HashMap::new()),
         };
 
@@ -48,10 +52,16 @@ impl PlanningMdRepo {
         &self.vault_root
     }
 
+    // Syncs and generated files leave notes the editor holds alone
+    fn ensure_writable(&self, md_path: &Path) -> Result<(), ApiError> {
+        let rel_path = md_path.strip_prefix(&self.vault_root).unwrap_or(md_path);
+        note_locks::ensure_writable(&self.vault_root, rel_path, WriteOrigin::Background)
+    }
+
     // Ensure the required directories exist
     fn ensure_directories(&self) -> Result<(), ApiError> {
-        // Ensure .planning directory exists
-        let planning_dir_path = planning_dir(&self.vault_root);
+        // Ensure planning directory exists
+        let planning_dir_path = planning_dir(&self.vault_root, &self.layout);
         path_policy::ensure_or_create_dir_in_vault(&self.vault_root, &planning_dir_path)?;
 
         // Ensure daily directory exists
@@ -64,7 +74,7 @@ impl PlanningMdRepo {
 
     // Get the path for a task markdown file
     fn get_task_md_path(&self, task_id: &str, slug: &str) -> Result<PathBuf, ApiError> {
-        let md_path = task_md_path(&self.vault_root, task_id, slug);
+        let md_path = task_md_path(&self.vault_root, &self.layout, task_id, slug);
 
         // Ensure task directory exists
         if let Some(parent) = md_path.parent() {
@@ -74,7 +84,7 @@ impl PlanningMdRepo {
         // Check if the path is within the vault without requiring the file to exist
         if !md_path.starts_with(&self.vault_root) {
             return Err(ApiError {
-                code: "PathOutsideVault".to_string(),
+                code: ErrorCode::PathOutsideVault,
                 message: "Task note path is outside vault".to_string(),
                 details: Some(serde_json::json!({ "path": md_path.to_string_lossy().to_string() })),
             });
@@ -86,14 +96,14 @@ impl PlanningMdRepo {
     // Get the path for a daily log markdown file
     fn get_daily_md_path(&self, day: &str) -> Result<PathBuf, ApiError> {
         let md_filename = format!("{}.md", day);
-        let md_path = planning_dir(&self.vault_root)
+        let md_path = planning_dir(&self.vault_root, &self.layout)
             .join("daily")
             .join(md_filename);
 
         // Check if the path is within the vault without requiring the file to exist
         if !md_path.starts_with(&self.vault_root) {
             return Err(ApiError {
-                code: "PathOutsideVault".to_string(),
+                code: ErrorCode::PathOutsideVault,
                 message: "Daily log path is outside vault".to_string(),
                 details: Some(serde_json::json!({ "path": md_path.to_string_lossy().to_string() })),
             });
@@ -152,6 +162,16 @@ impl PlanningMdRepo {
             }
         }
 
+        // Then custom task fields and any other keys, sorted so rewrites stay stable
+        let mut custom: Vec<(&String, &String)> = frontmatter
+            .iter()
+            .filter(|(key, _)| !SYSTEM_FIELDS.contains(&key.as_str()))
+            .collect();
+        custom.sort();
+        for (key, value) in custom {
+            lines.push(format!("{}: {}", key, value));
+        }
+
         lines.push("---".to_string());
         lines.push("".to_string());
 
@@ -164,10 +184,46 @@ impl PlanningMdRepo {
         task_id: &str,
         slug: &str,
         frontmatter_updates: &HashMap<String, String>,
+    ) -> Result<(), ApiError> {
+        self.rewrite_task_frontmatter(task_id, slug, |frontmatter| {
+            // Only update system fields
+            for (key, value) in frontmatter_updates {
+                if SYSTEM_FIELDS.contains(&key.as_str()) {
+                    frontmatter.insert(key.clone(), value.clone());
+                }
+            }
+        })
+    }
+
+    // Mirror custom task fields into the frontmatter; None removes a field
+    pub fn set_task_custom_fields(
+        &self,
+        task_id: &str,
+        slug: &str,
+        fields: &BTreeMap<String, Option<String>>,
+    ) -> Result<(), ApiError> {
+        self.rewrite_task_frontmatter(task_id, slug, |frontmatter| {
+            for (key, value) in fields {
+                if SYSTEM_FIELDS.contains(&key.as_str()) {
+                    continue;
+                }
+                match value {
+                    Some(value) => frontmatter.insert(key.clone(), value.clone()),
+                    None => frontmatter.remove(key),
+                };
+            }
+        })
+    }
+
+    fn rewrite_task_frontmatter(
+        &self,
+        task_id: &str,
+        slug: &str,
+        update: impl FnOnce(&mut HashMap<String, String>),
     ) -> Result<(), ApiError> {
         // Get or create a lock for this task
         let mut task_locks = self.task_locks.lock().map_err(|_| ApiError {
-            code: "LockError".to_string(),
+            code: ErrorCode::LockError,
             message: "Failed to acquire task lock".to_string(),
             details: None,
         })?;
@@ -178,17 +234,18 @@ impl PlanningMdRepo {
 
         // Lock this task's update
         let _task_lock_guard = task_lock.lock().map_err(|_| ApiError {
-            code: "LockError".to_string(),
+            code: ErrorCode::LockError,
             message: "Failed to acquire task lock".to_string(),
             details: None,
         })?;
 
         let md_path = self.get_task_md_path(task_id, slug)?;
+        self.ensure_writable(&md_path)?;
 
         // Read current content
         let current_content = if md_path.exists() {
             fs::read_to_string(&md_path).map_err(|e| ApiError {
-                code: "FileReadError".to_string(),
+                code: ErrorCode::FileReadError,
                 message: format!("Failed to read task markdown file: {}", e),
                 details: None,
             })?
@@ -202,13 +259,7 @@ impl PlanningMdRepo {
 
         // Merge updates with existing frontmatter
         let mut merged_frontmatter = existing_frontmatter.unwrap_or_default();
-
-        // Only update system fields
-        for (key, value) in frontmatter_updates {
-            if SYSTEM_FIELDS.contains(&key.as_str()) {
-                merged_frontmatter.insert(key.clone(), value.clone());
-            }
-        }
+        update(&mut merged_frontmatter);
 
         // Ensure version is set
         merged_frontmatter.insert("fm_version".to_string(), FRONTMATTER_VERSION.to_string());
@@ -223,8 +274,8 @@ impl PlanningMdRepo {
         let temp_path = md_path.with_extension(".tmp");
 
         // Write to temp file
-        let mut temp_file = File::create(&temp_path).map_err(|e| ApiError {
-            code: "FileWriteError".to_string(),
+        let mut temp_file = File::create(long_path(&temp_path)).map_err(|e| ApiError {
+            code: ErrorCode::FileWriteError,
             message: format!("Failed to write temp file: {}", e),
             details: None,
         })?;
@@ -232,21 +283,21 @@ impl PlanningMdRepo {
         temp_file
             .write_all(full_content.as_bytes())
             .map_err(|e| ApiError {
-                code: "FileWriteError".to_string(),
+                code: ErrorCode::FileWriteError,
                 message: format!("Failed to write temp file content: {}", e),
                 details: None,
             })?;
 
         // Flush and sync to disk
         temp_file.flush().map_err(|e| ApiError {
-            code: "FileWriteError".to_string(),
+            code: ErrorCode::FileWriteError,
             message: format!("Failed to flush temp file: {}", e),
             details: None,
         })?;
 
         // Atomic rename
-        fs::rename(&temp_path, &md_path).map_err(|e| ApiError {
-            code: "FileRenameError".to_string(),
+        fs::rename(long_path(&temp_path), long_path(&md_path)).map_err(|e| ApiError {
+            code: ErrorCode::FileRenameError,
             message: format!("Failed to rename temp file: {}", e),
             details: None,
         })?;
@@ -266,7 +317,7 @@ impl PlanningMdRepo {
 
         // Get or create a lock for this task
         let mut task_locks = self.task_locks.lock().map_err(|_| ApiError {
-            code: "LockError".to_string(),
+            code: ErrorCode::LockError,
             message: "Failed to acquire task lock".to_string(),
             details: None,
         })?;
@@ -277,7 +328,7 @@ impl PlanningMdRepo {
 
         // Lock this task's update
         let _task_lock_guard = task_lock.lock().map_err(|_| ApiError {
-            code: "LockError".to_string(),
+            code: ErrorCode::LockError,
             message: "Failed to acquire task lock".to_string(),
             details: None,
         })?;
@@ -301,8 +352,8 @@ impl PlanningMdRepo {
         let temp_path = md_path.with_extension(".tmp");
 
         // Write to temp file
-        let mut temp_file = File::create(&temp_path).map_err(|e| ApiError {
-            code: "FileWriteError".to_string(),
+        let mut temp_file = File::create(long_path(&temp_path)).map_err(|e| ApiError {
+            code: ErrorCode::FileWriteError,
             message: format!("Failed to write temp file: {}", e),
             details: None,
         })?;
@@ -310,21 +361,21 @@ impl PlanningMdRepo {
         temp_file
             .write_all(full_content.as_bytes())
             .map_err(|e| ApiError {
-                code: "FileWriteError".to_string(),
+                code: ErrorCode::FileWriteError,
                 message: format!("Failed to write temp file content: {}", e),
                 details: None,
             })?;
 
         // Flush and sync to disk
         temp_file.flush().map_err(|e| ApiError {
-            code: "FileWriteError".to_string(),
+            code: ErrorCode::FileWriteError,
             message: format!("Failed to flush temp file: {}", e),
             details: None,
         })?;
 
         // Atomic rename
-        fs::rename(&temp_path, &md_path).map_err(|e| ApiError {
-            code: "FileRenameError".to_string(),
+        fs::rename(long_path(&temp_path), long_path(&md_path)).map_err(|e| ApiError {
+            code: ErrorCode::FileRenameError,
             message: format!("Failed to rename temp file: {}", e),
             details: None,
         })?;
@@ -343,7 +394,7 @@ impl PlanningMdRepo {
 
         // Read file content
         let content = fs::read_to_string(&md_path).map_err(|e| ApiError {
-            code: "FileReadError".to_string(),
+            code: ErrorCode::FileReadError,
             message: format!("Failed to read task markdown file: {}", e),
             details: None,
         })?;
@@ -351,6 +402,23 @@ impl PlanningMdRepo {
         Ok(content)
     }
 
+    // Modification stamp of a task markdown file, None if it does not exist
+    pub fn task_md_stamp(&self, task_id: &str, slug: &str) -> Option<String> {
+        let modified = fs::metadata(task_md_path(&self.vault_root, &self.layout, task_id, slug))
+            .ok()?
+            .modified()
+            .ok()?;
+        let since_epoch = modified.duration_since(std::time::UNIX_EPOCH).ok()?;
+        Some(since_epoch.as_nanos().to_string())
+    }
+
+    // Read a task markdown body without its frontmatter
+    pub fn read_task_body(&self, task_id: &str, slug: &str) -> Result<String, ApiError> {
+        let content = self.read_task_md(task_id, slug)?;
+        let (_, body) = self.parse_frontmatter(&content);
+        Ok(body)
+    }
+
     // Delete a task markdown file
     #[allow(dead_code)]
     pub fn delete_task_md(&self, task_id: &str, slug: &str) -> Result<(), ApiError> {
@@ -360,7 +428,7 @@ impl PlanningMdRepo {
         if md_path.exists() {
             // Delete file
             fs::remove_file(&md_path).map_err(|e| ApiError {
-                code: "FileDeleteError".to_string(),
+                code: ErrorCode::FileDeleteError,
                 message: format!("Failed to delete task markdown file: {}", e),
                 details: None,
             })?;
@@ -369,6 +437,23 @@ impl PlanningMdRepo {
         Ok(())
     }
 
+    // Write a markdown file at a vault-relative path, creating parent directories
+    pub fn write_vault_md(&self, rel_path: &str, content: &str) -> Result<PathBuf, ApiError> {
+        let md_path = self.vault_root.join(rel_path);
+        self.ensure_writable(&md_path)?;
+        if let Some(parent) = md_path.parent() {
+            path_policy::ensure_or_create_dir_in_vault(&self.vault_root, parent)?;
+        }
+
+        fs::write(long_path(&md_path), content).map_err(|e| ApiError {
+            code: ErrorCode::FileWriteError,
+            message: format!("Failed to write markdown file: {}", e),
+            details: Some(serde_json::json!({ "path": rel_path })),
+        })?;
+
+        Ok(md_path)
+    }
+
     // Create or update a daily log markdown file
     pub fn upsert_daily_md(&self, day: &str, content: &str) -> Result<PathBuf, ApiError> {
         let md_path = self.get_daily_md_path(day)?;
@@ -380,8 +465,8 @@ impl PlanningMdRepo {
         let full_content = format!("{}{}", frontmatter, content);
 
         // Write to file
-        fs::write(&md_path, full_content).map_err(|e| ApiError {
-            code: "FileWriteError".to_string(),
+        fs::write(long_path(&md_path), full_content).map_err(|e| ApiError {
+            code: ErrorCode::FileWriteError,
             message: format!("Failed to write daily log markdown file: {}", e),
             details: None,
         })?;
@@ -389,36 +474,48 @@ impl PlanningMdRepo {
         Ok(md_path)
     }
 
-    // Read a daily log markdown file
-    pub fn read_daily_md(&self, day: &str) -> Result<String, ApiError> {
-        let md_path = self.get_daily_md_path(day)?;
-
-        // Check if file exists
-        if !md_path.exists() {
-            // Return default content if file doesn't exist
-            return Ok(format!(
-                "---\nday: {}\n---\n\n# {}\n\n## 今日完成\n\n- \n\n## 明日计划\n\n- \n\n## 反思与总结\n\n",
-                day, day
-            ));
-        }
-
-        // Read file content
-        let content = fs::read_to_string(&md_path).map_err(|e| ApiError {
-            code: "FileReadError".to_string(),
-            message: format!("Failed to read daily log markdown file: {}", e),
-            details: None,
-        })?;
+    // Check whether a daily log markdown file exists for a day
+    pub fn daily_md_exists(&self, day: &str) -> bool {
+        self.get_daily_md_path(day)
+            .map(|path| path.is_file())
+            .unwrap_or(false)
+    }
 
-        Ok(content)
+    // Days that have a daily log markdown file, whether or not the database knows them
+    pub fn list_daily_days(&self) -> Result<Vec<String>, ApiError> {
+        let daily_dir = planning_dir(&self.vault_root, &self.layout).join("daily");
+        let entries = match fs::read_dir(long_path(&daily_dir)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(ApiError {
+                    code: ErrorCode::FileReadError,
+                    message: format!("Failed to read daily log directory: {}", e),
+                    details: None,
+                })
+            }
+        };
+        let mut days: Vec<String> = entries
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let day = name.strip_suffix(".md")?;
+                chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()?;
+                Some(day.to_string())
+            })
+            .collect();
+        days.sort();
+        Ok(days)
     }
 
     // Get the relative path for a task markdown file
     pub fn get_task_md_relative_path(&self, task_id: &str, slug: &str) -> String {
-        task_md_relative_path(task_id, slug)
+        task_md_relative_path(&self.layout, task_id, slug)
     }
 
     // Get the relative path for a daily log markdown file
     pub fn get_daily_md_relative_path(&self, day: &str) -> String {
-        format!(".planning/daily/{}.md", day)
+        format!("{}/daily/{}.md", self.layout.planning_dir, day)
     }
 }
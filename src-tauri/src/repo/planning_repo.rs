@@ -1,21 +1,61 @@
-use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
 use rusqlite::params;
-use rusqlite::{Connection, OptionalExtension, Result};
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Result};
 use serde_json;
 use tauri::AppHandle;
 use tracing::{info, span, Level};
 use uuid::Uuid;
 
+use crate::domain::board::{self, BoardColumn, BoardMeta, ColumnTally, DEFAULT_BOARD_ID};
+use crate::domain::chunking::{IndexedChunk, NoteChunk};
+use crate::domain::export::PlanningExport;
+use crate::domain::github::{GithubBoardLink, GithubIssueLink};
+use crate::domain::links::{TaskNoteLink, LINK_SOURCE_AUTO};
+use crate::domain::notifications::{Notification, NotificationKind, NotificationState};
 use crate::domain::planning::{
-    DayLog, KanbanTasks, ReorderTaskInput, Task, TaskPriority, TaskStatus, Timer, TodayDTO,
+    DayLog, KanbanTasks, ReorderTaskInput, Task, TaskDueRef, TaskPriority, TaskStatus, Timer,
+    TodayDTO,
 };
-use crate::ipc::ApiError;
-use crate::paths::{planning_db_path, planning_dir, vault_meta_path};
+use crate::domain::reading_list::{ReadingItem, ReadingStatus};
+use crate::domain::recurrence;
+use crate::domain::reminders::{Reminder, ReminderRepeat};
+use crate::domain::schedule;
+use crate::domain::search::{HIGHLIGHT_CLOSE, HIGHLIGHT_OPEN};
+use crate::domain::sprint::{Sprint, SprintInput, SprintTask};
+use crate::domain::tagging::TagCount;
+use crate::domain::task_fields::{BoardField, FieldKind, TaskFieldValue};
+use crate::domain::timezone;
+use crate::domain::webhooks::WebhookDelivery;
+use crate::domain::webview::WebviewHistoryEntry;
+use crate::domain::week::WeekConfig;
+use crate::domain::workspace::{Favorite, FavoriteKind, FavoriteRef, NoteCursor, RecentNote};
+use crate::features::metrics;
+use crate::ipc::{ApiError, ErrorCode};
+use crate::paths::{planning_db_path, planning_dir, vault_meta_path, VaultLayout};
+use crate::security::db_crypto::{self, FieldCipher};
+use crate::security::path_policy;
 use serde::{Deserialize, Serialize};
 
+// How long applied idempotency keys are remembered
+const OP_RETENTION_DAYS: i64 = 7;
+// Webhook deliveries kept in the log
+const WEBHOOK_DELIVERY_RETENTION: i64 = 500;
+// Visits kept per embedded webview
+const WEBVIEW_HISTORY_RETENTION: i64 = 200;
+// Recently opened notes remembered per vault
+const RECENT_NOTES_RETENTION: i64 = 100;
+// vault_meta keys of an encrypted database
+pub const ENCRYPTION_SALT_KEY: &str = "encryption_salt";
+pub const ENCRYPTION_VERIFIER_KEY: &str = "encryption_verifier";
+
 // Database repository for planning data
 pub struct PlanningRepo {
     conn: Connection,
+    layout: VaultLayout,
+    cipher: Option<FieldCipher>, // Set when the database is encrypted and unlocked
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -26,20 +66,38 @@ struct VaultMeta {
 }
 
 impl PlanningRepo {
-    // Create a new instance of PlanningRepo
-    pub fn new(vault_root: &std::path::Path) -> Result<Self, ApiError> {
-        // Ensure .planning directory exists
-        let planning_dir_path = planning_dir(vault_root);
+    // Create a new instance of PlanningRepo; an encrypted database must be unlocked first
+    pub fn new(vault_root: &std::path::Path, layout: &VaultLayout) -> Result<Self, ApiError> {
+        let mut repo = Self::open_sealed(vault_root, layout)?;
+        if repo.get_vault_meta_value(ENCRYPTION_SALT_KEY)?.is_some() {
+            let cipher = db_crypto::unlocked_cipher(vault_root)?.ok_or_else(|| ApiError {
+                code: ErrorCode::DatabaseLocked,
+                message: "Planning database is encrypted; unlock it first".to_string(),
+                details: None,
+            })?;
+            repo.cipher = Some(cipher);
+        }
+        Ok(repo)
+    }
+
+    // Open without the vault key; sealed columns stay unreadable. Only for managing encryption.
+    pub fn open_sealed(
+        vault_root: &std::path::Path,
+        layout: &VaultLayout,
+    ) -> Result<Self, ApiError> {
+        // Ensure planning directory exists
+        path_policy::ensure_vault_present(vault_root)?;
+        let planning_dir_path = planning_dir(vault_root, layout);
         std::fs::create_dir_all(&planning_dir_path).map_err(|e| ApiError {
-            code: "DatabaseError".to_string(),
-            message: format!("Failed to create .planning directory: {}", e),
+            code: ErrorCode::DatabaseError,
+            message: format!("Failed to create planning directory: {}", e),
             details: None,
         })?;
 
-        let db_path = planning_db_path(vault_root);
+        let db_path = planning_db_path(vault_root, layout);
 
-        let conn = Connection::open(db_path).map_err(|e| ApiError {
-            code: "DatabaseError".to_string(),
+        let mut conn = Connection::open(db_path).map_err(|e| ApiError {
+            code: ErrorCode::DatabaseError,
             message: format!("Failed to open database: {}", e),
             details: None,
         })?;
@@ -50,19 +108,24 @@ impl PlanningRepo {
         let _mode: String = conn
             .query_row("PRAGMA journal_mode=WAL", [], |row| row.get(0))
             .map_err(|e| ApiError {
-                code: "DatabaseError".to_string(),
+                code: ErrorCode::DatabaseError,
                 message: format!("Failed to set WAL mode: {}", e),
                 details: None,
             })?;
 
         conn.pragma_update(None, "busy_timeout", 5000)
             .map_err(|e| ApiError {
-                code: "DatabaseError".to_string(),
+                code: ErrorCode::DatabaseError,
                 message: format!("Failed to set busy timeout: {}", e),
                 details: None,
             })?;
+        conn.profile(Some(metrics::record_db_query));
 
-        let repo = Self { conn };
+        let repo = Self {
+            conn,
+            layout: layout.clone(),
+            cipher: None,
+        };
         repo.init()?;
 
         Ok(repo)
@@ -95,7 +158,7 @@ impl PlanningRepo {
                 [],
             )
             .map_err(|e| ApiError {
-                code: "DatabaseError".to_string(),
+                code: ErrorCode::DatabaseError,
                 message: format!("Failed to create tasks table: {}", e),
                 details: None,
             })?;
@@ -111,7 +174,7 @@ impl PlanningRepo {
             self.conn
                 .execute("ALTER TABLE tasks ADD COLUMN priority TEXT", [])
                 .map_err(|e| ApiError {
-                    code: "DatabaseError".to_string(),
+                    code: ErrorCode::DatabaseError,
                     message: format!("Failed to add priority column: {}", e),
                     details: None,
                 })?;
@@ -128,7 +191,7 @@ impl PlanningRepo {
             self.conn
                 .execute("ALTER TABLE tasks ADD COLUMN tags TEXT", [])
                 .map_err(|e| ApiError {
-                    code: "DatabaseError".to_string(),
+                    code: ErrorCode::DatabaseError,
                     message: format!("Failed to add tags column: {}", e),
                     details: None,
                 })?;
@@ -145,7 +208,7 @@ impl PlanningRepo {
             self.conn
                 .execute("ALTER TABLE tasks ADD COLUMN description TEXT", [])
                 .map_err(|e| ApiError {
-                    code: "DatabaseError".to_string(),
+                    code: ErrorCode::DatabaseError,
                     message: format!("Failed to add description column: {}", e),
                     details: None,
                 })?;
@@ -162,7 +225,7 @@ impl PlanningRepo {
             self.conn
                 .execute("ALTER TABLE tasks ADD COLUMN due_date TEXT", [])
                 .map_err(|e| ApiError {
-                    code: "DatabaseError".to_string(),
+                    code: ErrorCode::DatabaseError,
                     message: format!("Failed to add due_date column: {}", e),
                     details: None,
                 })?;
@@ -179,12 +242,29 @@ impl PlanningRepo {
             self.conn
                 .execute("ALTER TABLE tasks ADD COLUMN board_id TEXT", [])
                 .map_err(|e| ApiError {
-                    code: "DatabaseError".to_string(),
+                    code: ErrorCode::DatabaseError,
                     message: format!("Failed to add board_id column: {}", e),
                     details: None,
                 })?;
         }
 
+        // Add column_key column if not exists
+        let has_column_key: i32 = self.conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('tasks') WHERE name = 'column_key'",
+            [],
+            |row| row.get(0),
+        )?;
+
+        if has_column_key == 0 {
+            self.conn
+                .execute("ALTER TABLE tasks ADD COLUMN column_key TEXT", [])
+                .map_err(|e| ApiError {
+                    code: ErrorCode::DatabaseError,
+                    message: format!("Failed to add column_key column: {}", e),
+                    details: None,
+                })?;
+        }
+
         // Add subtasks column if not exists
         let has_subtasks: i32 = self.conn.query_row(
             "SELECT COUNT(*) FROM pragma_table_info('tasks') WHERE name = 'subtasks'",
@@ -196,7 +276,7 @@ impl PlanningRepo {
             self.conn
                 .execute("ALTER TABLE tasks ADD COLUMN subtasks TEXT", [])
                 .map_err(|e| ApiError {
-                    code: "DatabaseError".to_string(),
+                    code: ErrorCode::DatabaseError,
                     message: format!("Failed to add subtasks column: {}", e),
                     details: None,
                 })?;
@@ -213,7 +293,7 @@ impl PlanningRepo {
             self.conn
                 .execute("ALTER TABLE tasks ADD COLUMN periodicity TEXT", [])
                 .map_err(|e| ApiError {
-                    code: "DatabaseError".to_string(),
+                    code: ErrorCode::DatabaseError,
                     message: format!("Failed to add periodicity column: {}", e),
                     details: None,
                 })?;
@@ -230,7 +310,7 @@ impl PlanningRepo {
             self.conn
                 .execute("ALTER TABLE tasks ADD COLUMN task_dir_slug TEXT", [])
                 .map_err(|e| ApiError {
-                    code: "DatabaseError".to_string(),
+                    code: ErrorCode::DatabaseError,
                     message: format!("Failed to add task_dir_slug column: {}", e),
                     details: None,
                 })?;
@@ -247,18 +327,55 @@ impl PlanningRepo {
             self.conn
                 .execute("ALTER TABLE tasks ADD COLUMN md_rel_path TEXT", [])
                 .map_err(|e| ApiError {
-                    code: "DatabaseError".to_string(),
+                    code: ErrorCode::DatabaseError,
                     message: format!("Failed to add md_rel_path column: {}", e),
                     details: None,
                 })?;
         }
 
+        // Add card preview columns, cached from the task note
+        let has_cover_image: i32 = self.conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('tasks') WHERE name = 'cover_image'",
+            [],
+            |row| row.get(0),
+        )?;
+
+        if has_cover_image == 0 {
+            self.conn
+                .execute_batch(
+                    "ALTER TABLE tasks ADD COLUMN cover_image TEXT;
+                     ALTER TABLE tasks ADD COLUMN excerpt TEXT;",
+                )
+                .map_err(|e| ApiError {
+                    code: ErrorCode::DatabaseError,
+                    message: format!("Failed to add card preview columns: {}", e),
+                    details: None,
+                })?;
+        }
+
+        // Add color column
+        let has_color: i32 = self.conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('tasks') WHERE name = 'color'",
+            [],
+            |row| row.get(0),
+        )?;
+
+        if has_color == 0 {
+            self.conn
+                .execute("ALTER TABLE tasks ADD COLUMN color TEXT", [])
+                .map_err(|e| ApiError {
+                    code: ErrorCode::DatabaseError,
+                    message: format!("Failed to add color column: {}", e),
+                    details: None,
+                })?;
+        }
+
         // Create indexes for tasks table
         self.conn.execute(
             r#"CREATE INDEX IF NOT EXISTS idx_tasks_status_order ON tasks(status, order_index)"#,
             [],
         ).map_err(|e| ApiError {
-            code: "DatabaseError".to_string(),
+            code: ErrorCode::DatabaseError,
             message: format!("Failed to create tasks index: {}", e),
             details: None,
         })?;
@@ -269,11 +386,117 @@ impl PlanningRepo {
                 [],
             )
             .map_err(|e| ApiError {
-                code: "DatabaseError".to_string(),
+                code: ErrorCode::DatabaseError,
                 message: format!("Failed to create tasks schedule index: {}", e),
                 details: None,
             })?;
 
+        // One task per directory; vaults that already share slugs open without the index until
+        // planning_repair_task_slugs separates them
+        if let Err(e) = self.ensure_slug_index() {
+            tracing::debug!(target: "planning", "task slug index not created: {}", e.message);
+        }
+
+        // Create board_columns table (custom kanban columns per board)
+        self.conn
+            .execute(
+                r#"CREATE TABLE IF NOT EXISTS board_columns (
+                board_id TEXT NOT NULL,
+                key TEXT NOT NULL,
+                name TEXT NOT NULL,
+                wip_limit INTEGER,
+                maps_to TEXT NOT NULL,
+                order_index INTEGER NOT NULL,
+                PRIMARY KEY (board_id, key)
+            )"#,
+                [],
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create board_columns table: {}", e),
+                details: None,
+            })?;
+
+        // Create boards table (per-board settings such as the theme color)
+        self.conn
+            .execute(
+                r#"CREATE TABLE IF NOT EXISTS boards (
+                board_id TEXT PRIMARY KEY,
+                color TEXT,
+                updated_at TEXT NOT NULL
+            )"#,
+                [],
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create boards table: {}", e),
+                details: None,
+            })?;
+
+        // Create full-text index over tasks and their note bodies (trigram handles CJK text)
+        self.conn
+            .execute(
+                r#"CREATE VIRTUAL TABLE IF NOT EXISTS task_fts USING fts5(
+                task_id UNINDEXED,
+                title,
+                description,
+                tags,
+                body,
+                tokenize = 'trigram'
+            )"#,
+                [],
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create task_fts table: {}", e),
+                details: None,
+            })?;
+
+        // Create task_fts_meta table tracking what each indexed row was built from
+        self.conn
+            .execute(
+                r#"CREATE TABLE IF NOT EXISTS task_fts_meta (
+                task_id TEXT PRIMARY KEY,
+                stamp TEXT NOT NULL
+            )"#,
+                [],
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create task_fts_meta table: {}", e),
+                details: None,
+            })?;
+
+        // Create task_note_links table (tasks <-> arbitrary vault notes)
+        self.conn
+            .execute(
+                r#"CREATE TABLE IF NOT EXISTS task_note_links (
+                task_id TEXT NOT NULL,
+                note_path TEXT NOT NULL,
+                source TEXT NOT NULL DEFAULT 'manual',
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (task_id, note_path)
+            )"#,
+                [],
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create task_note_links table: {}", e),
+                details: None,
+            })?;
+
+        // Create index for task_note_links table
+        self.conn
+            .execute(
+                r#"CREATE INDEX IF NOT EXISTS idx_note_links_path ON task_note_links(note_path)"#,
+                [],
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create task_note_links index: {}", e),
+                details: None,
+            })?;
+
         // Create task_timer table
         self.conn
             .execute(
@@ -288,7 +511,7 @@ impl PlanningRepo {
                 [],
             )
             .map_err(|e| ApiError {
-                code: "DatabaseError".to_string(),
+                code: ErrorCode::DatabaseError,
                 message: format!("Failed to create task_timer table: {}", e),
                 details: None,
             })?;
@@ -300,7 +523,7 @@ impl PlanningRepo {
                 [],
             )
             .map_err(|e| ApiError {
-                code: "DatabaseError".to_string(),
+                code: ErrorCode::DatabaseError,
                 message: format!("Failed to create task_timer index: {}", e),
                 details: None,
             })?;
@@ -317,7 +540,7 @@ impl PlanningRepo {
                 [],
             )
             .map_err(|e| ApiError {
-                code: "DatabaseError".to_string(),
+                code: ErrorCode::DatabaseError,
                 message: format!("Failed to create day_log table: {}", e),
                 details: None,
             })?;
@@ -334,11 +557,28 @@ impl PlanningRepo {
                 [],
             )
             .map_err(|e| ApiError {
-                code: "DatabaseError".to_string(),
+                code: ErrorCode::DatabaseError,
                 message: format!("Failed to create ui_state table: {}", e),
                 details: None,
             })?;
 
+        // Create task_ops table recording applied idempotency keys
+        self.conn
+            .execute(
+                r#"CREATE TABLE IF NOT EXISTS task_ops (
+                op_id TEXT PRIMARY KEY,
+                kind TEXT NOT NULL,
+                task_id TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )"#,
+                [],
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create task_ops table: {}", e),
+                details: None,
+            })?;
+
         // Create vault_meta table for vault identification and metadata
         self.conn
             .execute(
@@ -349,21 +589,384 @@ impl PlanningRepo {
                 [],
             )
             .map_err(|e| ApiError {
-                code: "DatabaseError".to_string(),
+                code: ErrorCode::DatabaseError,
                 message: format!("Failed to create vault_meta table: {}", e),
                 details: None,
             })?;
 
+        // Create GitHub link tables (board <-> repository, task <-> issue)
+        self.conn
+            .execute_batch(
+                r#"CREATE TABLE IF NOT EXISTS github_board_links (
+                board_id TEXT PRIMARY KEY,
+                repo TEXT NOT NULL,
+                last_synced_at TEXT
+            );
+            CREATE TABLE IF NOT EXISTS github_issue_links (
+                task_id TEXT PRIMARY KEY,
+                repo TEXT NOT NULL,
+                issue_number INTEGER NOT NULL,
+                synced_closed INTEGER NOT NULL DEFAULT 0,
+                synced_labels TEXT NOT NULL DEFAULT '[]',
+                synced_at TEXT NOT NULL,
+                UNIQUE (repo, issue_number)
+            );"#,
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create github link tables: {}", e),
+                details: None,
+            })?;
+
+        // Create webhook tables (delivery log, overdue notifications already sent)
+        self.conn
+            .execute_batch(
+                r#"CREATE TABLE IF NOT EXISTS webhook_deliveries (
+                id TEXT PRIMARY KEY,
+                webhook_id TEXT NOT NULL,
+                event TEXT NOT NULL,
+                task_id TEXT NOT NULL,
+                status_code INTEGER,
+                attempts INTEGER NOT NULL,
+                success INTEGER NOT NULL,
+                error TEXT,
+                delivered_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_at ON webhook_deliveries(delivered_at);
+            CREATE TABLE IF NOT EXISTS webhook_overdue_sent (
+                task_id TEXT NOT NULL,
+                due_date TEXT NOT NULL,
+                sent_at TEXT NOT NULL,
+                PRIMARY KEY (task_id, due_date)
+            );"#,
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create webhook tables: {}", e),
+                details: None,
+            })?;
+
+        // Create AI response cache, keyed by model and a hash of the full request
+        self.conn
+            .execute_batch(
+                r#"CREATE TABLE IF NOT EXISTS ai_cache (
+                model TEXT NOT NULL,
+                prompt_hash TEXT NOT NULL,
+                content TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                expires_at TEXT NOT NULL,
+                PRIMARY KEY (model, prompt_hash)
+            );
+            CREATE INDEX IF NOT EXISTS idx_ai_cache_expires ON ai_cache(expires_at);"#,
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create ai_cache table: {}", e),
+                details: None,
+            })?;
+
+        // Create semantic index: one row per indexed note, embeddings per chunk as little-endian f32
+        self.conn
+            .execute_batch(
+                r#"CREATE TABLE IF NOT EXISTS note_index (
+                path TEXT PRIMARY KEY,
+                content_hash TEXT NOT NULL,
+                model TEXT NOT NULL,
+                indexed_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS note_chunks (
+                path TEXT NOT NULL,
+                chunk_index INTEGER NOT NULL,
+                heading TEXT,
+                anchor TEXT,
+                start_offset INTEGER NOT NULL,
+                end_offset INTEGER NOT NULL,
+                text TEXT NOT NULL,
+                embedding BLOB NOT NULL,
+                PRIMARY KEY (path, chunk_index)
+            );
+            CREATE VIRTUAL TABLE IF NOT EXISTS note_chunk_fts USING fts5(
+                path UNINDEXED,
+                chunk_index UNINDEXED,
+                heading,
+                text,
+                tokenize = 'trigram'
+            );
+            INSERT INTO note_chunk_fts (path, chunk_index, heading, text)
+                SELECT path, chunk_index, heading, text FROM note_chunks
+                WHERE NOT EXISTS (SELECT 1 FROM note_chunk_fts);"#,
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create semantic index tables: {}", e),
+                details: None,
+            })?;
+
+        // Create frontmatter index for note queries; a row is refreshed when the note's mtime changes
+        self.conn
+            .execute_batch(
+                r#"CREATE TABLE IF NOT EXISTS note_frontmatter (
+                path TEXT PRIMARY KEY,
+                mtime INTEGER NOT NULL,
+                data TEXT NOT NULL
+            );"#,
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create note_frontmatter table: {}", e),
+                details: None,
+            })?;
+
+        // Create embedded browser history, one row per page visited in a webview
+        self.conn
+            .execute_batch(
+                r#"CREATE TABLE IF NOT EXISTS webview_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                vault_id TEXT NOT NULL,
+                label TEXT NOT NULL,
+                url TEXT NOT NULL,
+                title TEXT,
+                visited_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_webview_history_label ON webview_history(vault_id, label, id);"#,
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create webview_history table: {}", e),
+                details: None,
+            })?;
+
+        // Create reading list of pages saved to read later
+        self.conn
+            .execute_batch(
+                r#"CREATE TABLE IF NOT EXISTS reading_list (
+                id TEXT PRIMARY KEY,
+                url TEXT NOT NULL UNIQUE,
+                title TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'unread',
+                added_at TEXT NOT NULL,
+                read_at TEXT,
+                note_path TEXT,
+                task_id TEXT,
+                source_label TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_reading_list_status ON reading_list(status, added_at);"#,
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create reading_list table: {}", e),
+                details: None,
+            })?;
+
+        // Create task reminders, independent of the due date
+        self.conn
+            .execute_batch(
+                r#"CREATE TABLE IF NOT EXISTS task_reminders (
+                id TEXT PRIMARY KEY,
+                task_id TEXT NOT NULL,
+                remind_at TEXT NOT NULL,
+                repeat TEXT,
+                fired_at TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_task_reminders_due ON task_reminders(fired_at, remind_at);
+            CREATE INDEX IF NOT EXISTS idx_task_reminders_task ON task_reminders(task_id);"#,
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create task_reminders table: {}", e),
+                details: None,
+            })?;
+
+        // Create notification state: each delivered reminder occurrence and what became of it
+        self.conn
+            .execute_batch(
+                r#"CREATE TABLE IF NOT EXISTS notifications (
+                id TEXT PRIMARY KEY,
+                source_id TEXT NOT NULL,
+                occurrence_at TEXT NOT NULL,
+                task_id TEXT NOT NULL,
+                title TEXT NOT NULL,
+                state TEXT NOT NULL,
+                snoozed_until TEXT,
+                delivered_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                UNIQUE (source_id, occurrence_at)
+            );
+            CREATE INDEX IF NOT EXISTS idx_notifications_state ON notifications(state, snoozed_until);"#,
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create notifications table: {}", e),
+                details: None,
+            })?;
+
+        // Add notification kind; earlier rows are all reminders
+        let has_notification_kind: i32 = self.conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('notifications') WHERE name = 'kind'",
+            [],
+            |row| row.get(0),
+        )?;
+
+        if has_notification_kind == 0 {
+            self.conn
+                .execute(
+                    "ALTER TABLE notifications ADD COLUMN kind TEXT NOT NULL DEFAULT 'reminder'",
+                    [],
+                )
+                .map_err(|e| ApiError {
+                    code: ErrorCode::DatabaseError,
+                    message: format!("Failed to add notification kind column: {}", e),
+                    details: None,
+                })?;
+        }
+
+        // Create md sync journal: frontmatter syncs committed to the database but not yet written
+        // to the task note; leftovers are replayed at startup
+        self.conn
+            .execute_batch(
+                r#"CREATE TABLE IF NOT EXISTS md_sync_journal (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                task_id TEXT NOT NULL,
+                fields TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );"#,
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create md sync journal table: {}", e),
+                details: None,
+            })?;
+
+        // Create daily log queue: entries for daily notes that were open in the editor, appended
+        // once the note is released
+        self.conn
+            .execute_batch(
+                r#"CREATE TABLE IF NOT EXISTS daily_log_queue (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                day TEXT NOT NULL,
+                entry TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );"#,
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create daily log queue table: {}", e),
+                details: None,
+            })?;
+
+        // Create favorites: pinned notes and tasks per vault, in the user's order
+        self.conn
+            .execute_batch(
+                r#"CREATE TABLE IF NOT EXISTS favorites (
+                vault_id TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                target TEXT NOT NULL,
+                order_index INTEGER NOT NULL,
+                added_at TEXT NOT NULL,
+                PRIMARY KEY (vault_id, kind, target)
+            );"#,
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create favorites table: {}", e),
+                details: None,
+            })?;
+
+        // Create recently opened notes with the editor position to resume at
+        self.conn
+            .execute_batch(
+                r#"CREATE TABLE IF NOT EXISTS recent_notes (
+                vault_id TEXT NOT NULL,
+                path TEXT NOT NULL,
+                opened_at TEXT NOT NULL,
+                cursor_line INTEGER,
+                cursor_column INTEGER,
+                scroll_top REAL,
+                PRIMARY KEY (vault_id, path)
+            );
+            CREATE INDEX IF NOT EXISTS idx_recent_notes_opened ON recent_notes(vault_id, opened_at);"#,
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create recent_notes table: {}", e),
+                details: None,
+            })?;
+
+        // Create custom field schemas per board and the field values of tasks
+        self.conn
+            .execute_batch(
+                r#"CREATE TABLE IF NOT EXISTS board_fields (
+                board_id TEXT NOT NULL,
+                key TEXT NOT NULL,
+                name TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                options TEXT,
+                required INTEGER NOT NULL DEFAULT 0,
+                order_index INTEGER NOT NULL,
+                PRIMARY KEY (board_id, key)
+            );
+            CREATE TABLE IF NOT EXISTS task_fields (
+                task_id TEXT NOT NULL,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (task_id, key)
+            );
+            CREATE INDEX IF NOT EXISTS idx_task_fields_key ON task_fields(key, value);"#,
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create task field tables: {}", e),
+                details: None,
+            })?;
+
+        // Create sprints and the tasks committed to them; a task is in at most one sprint
+        self.conn
+            .execute_batch(
+                r#"CREATE TABLE IF NOT EXISTS sprints (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                start_date TEXT NOT NULL,
+                end_date TEXT NOT NULL,
+                capacity REAL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS sprint_tasks (
+                task_id TEXT PRIMARY KEY,
+                sprint_id TEXT NOT NULL,
+                points REAL,
+                added_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_sprint_tasks_sprint ON sprint_tasks(sprint_id);"#,
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create sprint tables: {}", e),
+                details: None,
+            })?;
+
         Ok(())
     }
 
-    // Get all tasks for today's home page
-    pub fn get_today_data(&self, today: &str) -> Result<TodayDTO, ApiError> {
-        // Get all tasks
-        let mut stmt = self
-            .conn
-            .prepare("SELECT * FROM tasks ORDER BY status, order_index")?;
-        let task_iter = stmt.query_map([], |row| task_from_row(row))?;
+    // Get all tasks for today's home page; dates are matched in the vault timezone
+    pub fn get_today_data(
+        &self,
+        today: &str,
+        tz: Option<Tz>,
+        week: &WeekConfig,
+        done_since: Option<&str>,
+    ) -> Result<TodayDTO, ApiError> {
+        // Get all tasks, minus done tasks finished before done_since
+        let mut stmt = self.conn.prepare(
+            r#"SELECT * FROM tasks
+               WHERE ?1 IS NULL OR status != 'done' OR COALESCE(completed_at, updated_at) >= ?1
+               ORDER BY status, order_index"#,
+        )?;
+        let task_iter =
+            stmt.query_map([done_since], |row| task_from_row(row, self.cipher.as_ref()))?;
 
         let mut all_tasks: Vec<Task> = Vec::new();
         for task in task_iter {
@@ -388,8 +991,7 @@ impl PlanningRepo {
         }
 
         // Filter timeline tasks (scheduled_start is today)
-        let today_start = format!("{today}T00:00:00");
-        let today_end = format!("{today}T23:59:59");
+        let today_date = NaiveDate::parse_from_str(today, "%Y-%m-%d").ok();
 
         let timeline: Vec<Task> = all_tasks
             .iter()
@@ -398,7 +1000,7 @@ impl PlanningRepo {
 
                 // 1. Check scheduled_start (exact match for one-off or base occurrence)
                 if let Some(start) = &task.scheduled_start {
-                    if start >= &today_start && start <= &today_end {
+                    if today_date.is_some() && timezone::to_local_date(start, tz) == today_date {
                         tasks_for_timeline.push(task.clone());
                         return tasks_for_timeline;
                     }
@@ -406,81 +1008,16 @@ impl PlanningRepo {
 
                 // 2. Check periodicity
                 if let Some(periodicity) = &task.periodicity {
-                    // Parse today's date
-                    let Ok(current_date) = NaiveDate::parse_from_str(today, "%Y-%m-%d") else {
+                    let Some(current_date) = today_date else {
                         return tasks_for_timeline;
                     };
 
-                    // Try parsing as DateTime (RFC3339) -> NaiveDateTime (YYYY-MM-DDTHH:MM:SS) -> Date (YYYY-MM-DD)
-                    let (start_date, start_time_str) = if let Ok(dt) =
-                        DateTime::parse_from_rfc3339(&periodicity.start_date)
-                    {
-                        (
-                            dt.date_naive(),
-                            dt.format("%H:%M:%S").to_string(), // Extract time part
-                        )
-                    } else if let Ok(ndt) =
-                        NaiveDateTime::parse_from_str(&periodicity.start_date, "%Y-%m-%dT%H:%M:%S")
-                    {
-                        (ndt.date(), ndt.time().to_string())
-                    } else if let Ok(d) =
-                        NaiveDate::parse_from_str(&periodicity.start_date, "%Y-%m-%d")
+                    if let Some(scheduled_start) =
+                        recurrence::occurrence_on(periodicity, current_date, tz, week)
                     {
-                        (d, "00:00:00".to_string())
-                    } else {
-                        return tasks_for_timeline;
-                    };
-
-                    if current_date < start_date {
-                        return tasks_for_timeline;
-                    }
-
-                    // Check end_date if rule is 'date'
-                    if periodicity.end_rule == "date" {
-                        if let Some(end_date_str) = &periodicity.end_date {
-                            if let Ok(end_date) =
-                                NaiveDate::parse_from_str(end_date_str, "%Y-%m-%d")
-                            {
-                                if current_date > end_date {
-                                    return tasks_for_timeline;
-                                }
-                            }
-                        }
-                    }
-
-                    // Calculate recurrence
-                    let diff = current_date.signed_duration_since(start_date);
-                    let days = diff.num_days();
-                    let interval = periodicity.interval.max(1) as i64;
-
-                    let is_recurrence = match periodicity.strategy.as_str() {
-                        "day" => days % interval == 0,
-                        "week" => days % (7 * interval) == 0,
-                        "month" => {
-                            if current_date.day() != start_date.day() {
-                                false
-                            } else {
-                                let year_diff = current_date.year() - start_date.year();
-                                let month_diff =
-                                    current_date.month() as i32 - start_date.month() as i32;
-                                let total_months = year_diff * 12 + month_diff;
-                                total_months % (interval as i32) == 0
-                            }
-                        }
-                        "year" => {
-                            current_date.day() == start_date.day()
-                                && current_date.month() == start_date.month()
-                                && (current_date.year() - start_date.year()) % (interval as i32)
-                                    == 0
-                        }
-                        _ => false,
-                    };
-
-                    if is_recurrence {
-                        // Create a virtual instance for today
+                        // Create a virtual instance for today at the series' start time
                         let mut instance = task.clone();
-                        // Construct scheduled_start with today's date and the original start time
-                        instance.scheduled_start = Some(format!("{}T{}", today, start_time_str));
+                        instance.scheduled_start = Some(scheduled_start);
                         tasks_for_timeline.push(instance);
                     }
                 }
@@ -495,8 +1032,15 @@ impl PlanningRepo {
         // Get server current time
         let server_now = Utc::now().to_rfc3339();
 
+        // Group by each board's column registry
+        let registry = self.list_all_board_columns()?;
+        let boards = board::group_boards(&registry, &all_tasks, &self.list_board_colors()?);
+        let stats = board::summarize_tallies(&registry, &self.tally_columns(today)?);
+
         Ok(TodayDTO {
             kanban,
+            boards,
+            stats,
             timeline,
             current_doing,
             current_timer,
@@ -533,722 +1077,3329 @@ impl PlanningRepo {
         }
     }
 
-    // Get task by id
-    pub fn get_task_by_id(&self, task_id: &str) -> Result<Task, ApiError> {
-        let mut stmt = self.conn.prepare("SELECT * FROM tasks WHERE id = ?")?;
-        let task = stmt.query_row([task_id], |row| task_from_row(row))?;
-
-        Ok(task)
+    // Get the running timer, if any
+    pub fn get_active_timer(&self) -> Result<Option<Timer>, ApiError> {
+        let timer = self
+            .conn
+            .query_row(
+                "SELECT * FROM task_timer WHERE stop_at IS NULL LIMIT 1",
+                [],
+                timer_from_row,
+            )
+            .optional()?;
+        Ok(timer)
+    }
+
+    // Get task by id
+    pub fn get_task_by_id(&self, task_id: &str) -> Result<Task, ApiError> {
+        let mut stmt = self.conn.prepare("SELECT * FROM tasks WHERE id = ?")?;
+        let task = stmt.query_row([task_id], |row| task_from_row(row, self.cipher.as_ref()))?;
+
+        Ok(task)
     }
 
     // Get task by id, returns None if not found
     pub fn get_task(&self, task_id: &str) -> Result<Option<Task>, ApiError> {
         let mut stmt = self.conn.prepare("SELECT * FROM tasks WHERE id = ?")?;
         let task = stmt
-            .query_row([task_id], |row| task_from_row(row))
+            .query_row([task_id], |row| task_from_row(row, self.cipher.as_ref()))
             .optional()?;
 
         Ok(task)
     }
 
-    // Update task's note_path
-    pub fn update_task_note_path(&self, task_id: &str, note_path: &str) -> Result<(), ApiError> {
-        let now = Utc::now().to_rfc3339();
+    // Get non-archived tasks whose scheduled block overlaps [from, to), both in the stored UTC form
+    pub fn list_scheduled_tasks(&self, from: &str, to: &str) -> Result<Vec<Task>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT * FROM tasks
+               WHERE archived = 0
+                 AND scheduled_start IS NOT NULL AND scheduled_end IS NOT NULL
+                 AND scheduled_start < ? AND scheduled_end > ?
+               ORDER BY scheduled_start"#,
+        )?;
+        let task_iter = stmt.query_map(params![to, from], |row| {
+            task_from_row(row, self.cipher.as_ref())
+        })?;
 
-        self.conn.execute(
-            "UPDATE tasks SET note_path = ?, updated_at = ? WHERE id = ?",
-            params![note_path, now, task_id],
+        let mut tasks = Vec::new();
+        for task in task_iter {
+            tasks.push(task?);
+        }
+
+        Ok(tasks)
+    }
+
+    // Get non-archived tasks whose raw due_date sorts within [from, to)
+    pub fn list_tasks_due_between(&self, from: &str, to: &str) -> Result<Vec<Task>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT * FROM tasks
+               WHERE archived = 0 AND due_date IS NOT NULL
+                 AND due_date >= ? AND due_date < ?
+               ORDER BY due_date, order_index"#,
         )?;
+        let task_iter = stmt.query_map(params![from, to], |row| {
+            task_from_row(row, self.cipher.as_ref())
+        })?;
 
-        Ok(())
+        let mut tasks = Vec::new();
+        for task in task_iter {
+            tasks.push(task?);
+        }
+
+        Ok(tasks)
     }
 
-    // Create a new task
-    pub fn create_task(
+    // Get a page of done tasks, most recently completed first, with the total across all pages
+    pub fn list_done_tasks(
         &self,
-        title: &str,
-        description: Option<&str>,
-        status: TaskStatus,
-        priority: Option<TaskPriority>,
-        due_date: Option<&str>,
+        offset: i64,
+        limit: i64,
         board_id: Option<&str>,
-        estimate_min: Option<i64>,
-        tags: Option<&Vec<String>>,
-        subtasks: Option<&Vec<crate::domain::planning::Subtask>>,
-        periodicity: Option<&crate::domain::planning::TaskPeriodicity>,
-        scheduled_start: Option<&str>,
-        scheduled_end: Option<&str>,
-        note_path: Option<&str>,
-        completed_at: Option<&str>,
-        task_dir_slug: Option<&str>,
-        md_rel_path: Option<&str>,
-    ) -> Result<Task, ApiError> {
-        let id = Uuid::new_v4().to_string();
-        let now = Utc::now().to_rfc3339();
-
-        // Get max order index for the status
-        let max_order: i64 = self.conn.query_row(
-            "SELECT COALESCE(MAX(order_index), -1) FROM tasks WHERE status = ?",
-            [status.to_string()],
+    ) -> Result<(Vec<Task>, i64), ApiError> {
+        let filter = "status = 'done' AND (?1 IS NULL OR COALESCE(board_id, ?2) = ?1)";
+        let total: i64 = self.conn.query_row(
+            &format!("SELECT COUNT(*) FROM tasks WHERE {}", filter),
+            params![board_id, DEFAULT_BOARD_ID],
             |row| row.get(0),
         )?;
 
-        let order_index = max_order + 1;
-
-        let tags_json = match tags {
-            Some(tags_vec) if !tags_vec.is_empty() => match serde_json::to_string(tags_vec) {
-                Ok(json) => Some(json),
-                Err(e) => {
-                    log::warn!("Failed to serialize tags: {}", e);
-                    None
-                }
-            },
-            _ => None,
-        };
+        let mut stmt = self.conn.prepare(&format!(
+            r#"SELECT * FROM tasks WHERE {}
+               ORDER BY COALESCE(completed_at, updated_at) DESC, id
+               LIMIT ?3 OFFSET ?4"#,
+            filter
+        ))?;
+        let task_iter = stmt
+            .query_map(params![board_id, DEFAULT_BOARD_ID, limit, offset], |row| {
+                task_from_row(row, self.cipher.as_ref())
+            })?;
 
-        // Convert subtasks to JSON string
-        let subtasks_json = match subtasks {
-            Some(subtasks_vec) if !subtasks_vec.is_empty() => {
-                match serde_json::to_string(subtasks_vec) {
-                    Ok(json) => Some(json),
-                    Err(e) => {
-                        log::warn!("Failed to serialize subtasks: {}", e);
-                        None
-                    }
-                }
-            }
-            _ => None,
-        };
+        let mut tasks = Vec::new();
+        for task in task_iter {
+            tasks.push(task?);
+        }
 
-        // Convert periodicity to JSON string
-        let periodicity_json = match periodicity {
-            Some(p) => match serde_json::to_string(p) {
-                Ok(json) => Some(json),
-                Err(e) => {
-                    log::warn!("Failed to serialize periodicity: {}", e);
-                    None
-                }
-            },
-            None => None,
-        };
+        Ok((tasks, total))
+    }
 
-        self.conn.execute(
-            r#"INSERT INTO tasks (
-                id, title, description, status, priority, tags, subtasks, periodicity, 
-                due_date, board_id, order_index, estimate_min, scheduled_start, scheduled_end, 
-                note_path, created_at, updated_at, completed_at, archived,
-                task_dir_slug, md_rel_path
-            ) 
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 0, ?, ?)"#,
-            params![
-                id,
-                title,
-                description,
-                status.to_string(),
-                priority.map(|p| p.to_string()),
-                tags_json,
-                subtasks_json,
-                periodicity_json,
-                due_date,
-                board_id,
-                order_index,
-                estimate_min,
-                scheduled_start,
-                scheduled_end,
-                note_path,
-                now,
-                now,
-                completed_at,
-                task_dir_slug,
-                md_rel_path
-            ],
+    // Count, remaining estimate and overdue tasks per board, column key and status
+    fn tally_columns(&self, today: &str) -> Result<Vec<ColumnTally>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT COALESCE(board_id, ?1) AS board, column_key, status, COUNT(*),
+                      COALESCE(SUM(CASE WHEN status != 'done' THEN estimate_min END), 0),
+                      SUM(CASE WHEN status != 'done' AND due_date IS NOT NULL AND due_date < ?2
+                               THEN 1 ELSE 0 END)
+               FROM tasks
+               WHERE archived = 0
+               GROUP BY board, column_key, status"#,
         )?;
+        let tally_iter = stmt.query_map(params![DEFAULT_BOARD_ID, today], |row| {
+            Ok(ColumnTally {
+                board_id: row.get(0)?,
+                column_key: row.get(1)?,
+                status: TaskStatus::from(row.get::<_, String>(2)?.as_str()),
+                count: row.get(3)?,
+                estimate_remaining_min: row.get(4)?,
+                overdue: row.get(5)?,
+            })
+        })?;
 
-        self.get_task_by_id(&id)
-    }
+        let mut tallies = Vec::new();
+        for tally in tally_iter {
+            tallies.push(tally?);
+        }
 
-    // Update an existing task
-    pub fn update_task(
-        &self,
-        task_id: &str,
-        title: Option<&str>,
-        description: Option<&str>,
-        status: Option<TaskStatus>,
-        priority: Option<TaskPriority>,
-        tags: Option<&Vec<String>>,
-        subtasks: Option<&Vec<crate::domain::planning::Subtask>>,
-        periodicity: Option<&crate::domain::planning::TaskPeriodicity>,
-        order_index: Option<i64>,
-        estimate_min: Option<i64>,
-        scheduled_start: Option<&str>,
-        scheduled_end: Option<&str>,
-        due_date: Option<Option<String>>,
-        board_id: Option<&str>,
-        note_path: Option<&str>,
-        archived: Option<i32>,
-        completed_at: Option<Option<String>>,
-    ) -> Result<Task, ApiError> {
-        let now = Utc::now().to_rfc3339();
+        Ok(tallies)
+    }
 
-        // Get current task to preserve unchanged fields
-        let mut current_task = self.get_task_by_id(task_id)?;
+    // Get non-archived tasks that carry a recurrence
+    pub fn list_recurring_tasks(&self) -> Result<Vec<Task>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM tasks WHERE archived = 0 AND periodicity IS NOT NULL")?;
+        let task_iter = stmt.query_map([], |row| task_from_row(row, self.cipher.as_ref()))?;
 
-        // Update fields if provided
-        if let Some(new_title) = title {
-            current_task.title = new_title.to_string();
+        let mut tasks = Vec::new();
+        for task in task_iter {
+            tasks.push(task?);
         }
 
-        if let Some(new_description) = description {
-            current_task.description = Some(new_description.to_string());
-        }
+        Ok(tasks)
+    }
 
-        if let Some(new_status) = status {
-            current_task.status = new_status;
-            // Update order_index if status changed
-            let max_order: i64 = self.conn.query_row(
-                "SELECT COALESCE(MAX(order_index), -1) FROM tasks WHERE status = ?",
-                [new_status.to_string()],
-                |row| row.get(0),
-            )?;
-            current_task.order_index = max_order + 1;
-        }
+    // Get unarchived, unfinished tasks
+    pub fn list_open_tasks(&self) -> Result<Vec<Task>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT * FROM tasks WHERE archived = 0 AND status != 'done' ORDER BY order_index",
+        )?;
+        let task_iter = stmt.query_map([], |row| task_from_row(row, self.cipher.as_ref()))?;
 
-        if let Some(new_priority) = priority {
-            current_task.priority = Some(new_priority);
+        let mut tasks = Vec::new();
+        for task in task_iter {
+            tasks.push(task?);
         }
 
-        if let Some(new_tags) = tags {
-            current_task.tags = Some(new_tags.clone());
-            current_task.labels = Some(new_tags.clone());
-        }
+        Ok(tasks)
+    }
 
-        if let Some(new_subtasks) = subtasks {
-            current_task.subtasks = Some(new_subtasks.clone());
-        }
+    // Every tag on a task with the number of tasks carrying it, most used first
+    pub fn list_tag_counts(&self) -> Result<Vec<TagCount>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, tags FROM tasks WHERE tags IS NOT NULL AND tags != ''")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+        })?;
 
-        if let Some(new_periodicity) = periodicity {
-            current_task.periodicity = Some(new_periodicity.clone());
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for row in rows {
+            let (task_id, tags) = row?;
+            let tags: HashSet<String> = parse_tags(tags, &task_id)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|tag| !tag.trim().is_empty())
+                .collect();
+            for tag in tags {
+                *counts.entry(tag).or_default() += 1;
+            }
         }
 
-        if let Some(new_order) = order_index {
-            current_task.order_index = new_order;
-        }
+        let mut counts: Vec<TagCount> = counts
+            .into_iter()
+            .map(|(tag, count)| TagCount { tag, count })
+            .collect();
+        counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+        Ok(counts)
+    }
 
-        if let Some(new_estimate) = estimate_min {
-            current_task.estimate_min = Some(new_estimate);
-        }
+    // Get unarchived, unfinished tasks that have a due date
+    pub fn list_open_tasks_with_due_date(&self) -> Result<Vec<Task>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT * FROM tasks WHERE archived = 0 AND status != 'done' AND due_date IS NOT NULL",
+        )?;
+        let task_iter = stmt.query_map([], |row| task_from_row(row, self.cipher.as_ref()))?;
 
-        if let Some(new_start) = scheduled_start {
-            current_task.scheduled_start = Some(new_start.to_string());
+        let mut tasks = Vec::new();
+        for task in task_iter {
+            tasks.push(task?);
         }
 
-        if let Some(new_end) = scheduled_end {
-            current_task.scheduled_end = Some(new_end.to_string());
-        }
+        Ok(tasks)
+    }
 
-        if let Some(new_due_date) = due_date {
-            current_task.due_date = new_due_date;
-        }
+    // Id, title, due date and priority of open tasks that have a due date, soonest first
+    pub fn list_open_due_dates(&self) -> Result<Vec<TaskDueRef>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT id, title, due_date, priority FROM tasks
+               WHERE archived = 0 AND status != 'done' AND due_date IS NOT NULL
+               ORDER BY due_date, COALESCE(priority, 'p3')"#,
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let priority: Option<String> = row.get(3)?;
+            Ok(TaskDueRef {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                due_date: row.get(2)?,
+                priority: priority.as_deref().map(TaskPriority::from),
+            })
+        })?;
 
-        if let Some(new_board_id) = board_id {
-            current_task.board_id = Some(new_board_id.to_string());
+        let mut tasks = Vec::new();
+        for row in rows {
+            tasks.push(row?);
         }
 
-        if let Some(new_note_path) = note_path {
-            current_task.note_path = Some(new_note_path.to_string());
-        }
+        Ok(tasks)
+    }
 
-        if let Some(new_archived) = archived {
-            current_task.archived = new_archived;
+    // Get every task, including archived ones
+    pub fn list_all_tasks(&self) -> Result<Vec<Task>, ApiError> {
+        let mut stmt = self.conn.prepare("SELECT * FROM tasks")?;
+        let task_iter = stmt.query_map([], |row| task_from_row(row, self.cipher.as_ref()))?;
+
+        let mut tasks = Vec::new();
+        for task in task_iter {
+            tasks.push(task?);
         }
 
-        if let Some(new_completed_at) = completed_at {
-            current_task.completed_at = new_completed_at;
+        Ok(tasks)
+    }
+
+    // Get the stamp each task was last indexed with
+    pub fn list_fts_stamps(&self) -> Result<HashMap<String, String>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT task_id, stamp FROM task_fts_meta")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        let mut stamps = HashMap::new();
+        for row in rows {
+            let (task_id, stamp) = row?;
+            stamps.insert(task_id, stamp);
         }
 
-        current_task.updated_at = now;
+        Ok(stamps)
+    }
 
-        // Serialize tags to JSON string
-        let tags_json = match &current_task.tags {
-            Some(tags) if !tags.is_empty() => match serde_json::to_string(tags) {
-                Ok(json) => Some(json),
-                Err(e) => {
-                    log::warn!("Failed to serialize tags: {} for task {}", e, task_id);
-                    None
-                }
-            },
-            _ => None,
-        };
+    // Replace the indexed row of a task
+    pub fn upsert_task_fts(&self, task: &Task, body: &str, stamp: &str) -> Result<(), ApiError> {
+        let transaction = self.conn.unchecked_transaction()?;
 
-        // Serialize subtasks to JSON string
-        let subtasks_json = match &current_task.subtasks {
-            Some(subtasks) if !subtasks.is_empty() => match serde_json::to_string(subtasks) {
-                Ok(json) => Some(json),
-                Err(e) => {
-                    log::warn!("Failed to serialize subtasks: {} for task {}", e, task_id);
-                    None
-                }
-            },
-            _ => None,
-        };
+        transaction.execute("DELETE FROM task_fts WHERE task_id = ?", [&task.id])?;
+        transaction.execute(
+            "INSERT INTO task_fts (task_id, title, description, tags, body) VALUES (?, ?, ?, ?, ?)",
+            params![
+                task.id,
+                task.title,
+                // Never index sealed text in plaintext
+                match self.cipher {
+                    Some(_) => "",
+                    None => task.description.as_deref().unwrap_or(""),
+                },
+                task.tags
+                    .as_ref()
+                    .map(|tags| tags.join(" "))
+                    .unwrap_or_default(),
+                body
+            ],
+        )?;
+        transaction.execute(
+            "INSERT OR REPLACE INTO task_fts_meta (task_id, stamp) VALUES (?, ?)",
+            params![task.id, stamp],
+        )?;
 
-        // Serialize periodicity to JSON string
-        let periodicity_json = match &current_task.periodicity {
-            Some(p) => match serde_json::to_string(p) {
-                Ok(json) => Some(json),
-                Err(e) => {
-                    log::warn!(
-                        "Failed to serialize periodicity: {} for task {}",
-                        e,
-                        task_id
-                    );
-                    None
-                }
-            },
-            None => None,
-        };
+        transaction.commit()?;
 
-        // Update in database
-        self.conn.execute(
-            r#"UPDATE tasks SET title = ?, description = ?, status = ?, priority = ?, tags = ?, subtasks = ?, periodicity = ?, due_date = ?, board_id = ?, order_index = ?, estimate_min = ?,
-               scheduled_start = ?, scheduled_end = ?, note_path = ?, updated_at = ?, archived = ?, completed_at = ?
-               WHERE id = ?"#,
+        Ok(())
+    }
+
+    // Drop the indexed row of a task
+    pub fn delete_task_fts(&self, task_id: &str) -> Result<(), ApiError> {
+        self.conn
+            .execute("DELETE FROM task_fts WHERE task_id = ?", [task_id])?;
+        self.conn
+            .execute("DELETE FROM task_fts_meta WHERE task_id = ?", [task_id])?;
+        Ok(())
+    }
+
+    // Ranked full-text search returning (task_id, score, title highlight, snippet)
+    pub fn search_task_fts(
+        &self,
+        match_expr: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, f64, String, String)>, ApiError> {
+        // Title matches weigh most, then tags, description and note body
+        let mut stmt = self.conn.prepare(
+            r#"SELECT task_id,
+                      -bm25(task_fts, 0.0, 10.0, 3.0, 5.0, 1.0) AS score,
+                      highlight(task_fts, 1, ?, ?) AS title_highlight,
+                      snippet(task_fts, -1, ?, ?, '…', 16) AS snippet
+               FROM task_fts
+               WHERE task_fts MATCH ?
+               ORDER BY score DESC
+               LIMIT ?"#,
+        )?;
+        let rows = stmt.query_map(
             params![
-                current_task.title, current_task.description, current_task.status.to_string(),
-                current_task.priority.map(|p| p.to_string()), tags_json, subtasks_json, periodicity_json, current_task.due_date,
-                current_task.board_id, current_task.order_index, current_task.estimate_min,
-                current_task.scheduled_start, current_task.scheduled_end, current_task.note_path,
-                current_task.updated_at, current_task.archived, current_task.completed_at, task_id
+                HIGHLIGHT_OPEN,
+                HIGHLIGHT_CLOSE,
+                HIGHLIGHT_OPEN,
+                HIGHLIGHT_CLOSE,
+                match_expr,
+                limit as i64
             ],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
         )?;
 
-        self.get_task_by_id(task_id)
+        let mut hits = Vec::new();
+        for row in rows {
+            hits.push(row?);
+        }
+
+        Ok(hits)
     }
 
-    // Mark a task as done
-    pub fn mark_task_done(&self, task_id: &str) -> Result<Task, ApiError> {
-        let now = Utc::now().to_rfc3339();
+    // Substring search for terms too short for the trigram index, ordered by recency
+    pub fn search_task_like(
+        &self,
+        terms: &[String],
+        include_notes: bool,
+        limit: usize,
+    ) -> Result<Vec<String>, ApiError> {
+        let haystack = if include_notes {
+            "title || ' ' || description || ' ' || tags || ' ' || body"
+        } else {
+            "title || ' ' || description || ' ' || tags"
+        };
+        let conditions = vec![format!("({}) LIKE ? ESCAPE '\\'", haystack); terms.len()];
+        let sql = format!(
+            "SELECT f.task_id FROM task_fts f JOIN tasks t ON t.id = f.task_id WHERE {} ORDER BY t.updated_at DESC LIMIT {}",
+            conditions.join(" AND "),
+            limit
+        );
 
-        self.conn.execute(
-            "UPDATE tasks SET status = 'done', completed_at = ?, updated_at = ? WHERE id = ?",
-            params![now, now, task_id],
-        )?;
+        let patterns: Vec<String> = terms
+            .iter()
+            .map(|term| {
+                let escaped = term
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_");
+                format!("%{}%", escaped)
+            })
+            .collect();
 
-        self.get_task_by_id(task_id)
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(patterns.iter()), |row| {
+            row.get(0)
+        })?;
+
+        let mut task_ids = Vec::new();
+        for row in rows {
+            task_ids.push(row?);
+        }
+
+        Ok(task_ids)
     }
 
-    // Reopen a completed task
-    pub fn reopen_task(&self, task_id: &str) -> Result<Task, ApiError> {
+    // Link a task to a note; a manual link upgrades an existing auto link
+    pub fn add_note_link(
+        &self,
+        task_id: &str,
+        note_path: &str,
+        source: &str,
+    ) -> Result<TaskNoteLink, ApiError> {
         let now = Utc::now().to_rfc3339();
-
         self.conn.execute(
-            "UPDATE tasks SET status = 'todo', completed_at = NULL, updated_at = ? WHERE id = ?",
-            params![now, task_id],
+            r#"INSERT INTO task_note_links (task_id, note_path, source, created_at) VALUES (?, ?, ?, ?)
+               ON CONFLICT(task_id, note_path) DO UPDATE SET source = CASE WHEN excluded.source = 'manual' THEN 'manual' ELSE source END"#,
+            params![task_id, note_path, source, now],
         )?;
 
-        self.get_task_by_id(task_id)
+        let link = self.conn.query_row(
+            "SELECT * FROM task_note_links WHERE task_id = ? AND note_path = ?",
+            params![task_id, note_path],
+            note_link_from_row,
+        )?;
+        Ok(link)
     }
 
-    // Start a task (create a timer and update task status)
-    pub fn start_task(&self, task_id: &str) -> Result<(), ApiError> {
-        // First, stop any existing active timer
-        self.stop_all_active_timers()?;
+    // Remove a task-note link; returns whether it existed
+    pub fn remove_note_link(&self, task_id: &str, note_path: &str) -> Result<bool, ApiError> {
+        let removed = self.conn.execute(
+            "DELETE FROM task_note_links WHERE task_id = ? AND note_path = ?",
+            params![task_id, note_path],
+        )?;
+        Ok(removed > 0)
+    }
 
+    // Point links at a renamed note (or anything under a renamed directory) to the new path
+    pub fn rename_note_link_paths(
+        &self,
+        old_path: &str,
+        new_path: &str,
+    ) -> Result<usize, ApiError> {
+        let updated = self.conn.execute(
+            r#"UPDATE OR REPLACE task_note_links
+               SET note_path = ?2 || substr(note_path, length(?1) + 1)
+               WHERE note_path = ?1 OR substr(note_path, 1, length(?1) + 1) = ?1 || '/'"#,
+            params![old_path, new_path],
+        )?;
+        Ok(updated)
+    }
+
+    // Replace the links extracted from a task body, leaving manual links alone
+    pub fn replace_auto_note_links(
+        &self,
+        task_id: &str,
+        note_paths: &[String],
+    ) -> Result<(), ApiError> {
+        let transaction = self.conn.unchecked_transaction()?;
         let now = Utc::now().to_rfc3339();
-        let timer_id = Uuid::new_v4().to_string();
 
-        // Create new timer
-        self.conn.execute(
-            r#"INSERT INTO task_timer (id, task_id, start_at, duration_sec, source) 
-               VALUES (?, ?, ?, 0, 'manual')"#,
-            params![timer_id, task_id, now],
+        transaction.execute(
+            "DELETE FROM task_note_links WHERE task_id = ? AND source = ?",
+            params![task_id, LINK_SOURCE_AUTO],
         )?;
+        for note_path in note_paths {
+            transaction.execute(
+                "INSERT OR IGNORE INTO task_note_links (task_id, note_path, source, created_at) VALUES (?, ?, ?, ?)",
+                params![task_id, note_path, LINK_SOURCE_AUTO, now],
+            )?;
+        }
 
-        // Update task status to doing
-        self.conn.execute(
-            "UPDATE tasks SET status = 'doing', updated_at = ? WHERE id = ?",
-            params![now, task_id],
-        )?;
+        transaction.commit()?;
 
         Ok(())
     }
 
-    // Stop a task (update timer and task status)
-    pub fn stop_task(&self, task_id: &str) -> Result<(), ApiError> {
-        let now = Utc::now().to_rfc3339();
+    // Get the notes a task references
+    pub fn list_note_links_for_task(&self, task_id: &str) -> Result<Vec<TaskNoteLink>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM task_note_links WHERE task_id = ? ORDER BY note_path")?;
+        let link_iter = stmt.query_map([task_id], note_link_from_row)?;
 
-        // Find active timer for this task
+        let mut links = Vec::new();
+        for link in link_iter {
+            links.push(link?);
+        }
+
+        Ok(links)
+    }
+
+    // Get the tasks referencing a note
+    pub fn list_tasks_for_note(&self, note_path: &str) -> Result<Vec<Task>, ApiError> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, start_at FROM task_timer WHERE task_id = ? AND stop_at IS NULL LIMIT 1",
+            r#"SELECT t.* FROM tasks t
+               JOIN task_note_links l ON l.task_id = t.id
+               WHERE l.note_path = ?
+               ORDER BY t.updated_at DESC"#,
         )?;
+        let task_iter =
+            stmt.query_map([note_path], |row| task_from_row(row, self.cipher.as_ref()))?;
 
-        let mut timer_iter = stmt.query_map([task_id], |row| {
-            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
-        })?;
-
-        if let Some(timer_result) = timer_iter.next() {
-            let (timer_id, start_at) = timer_result?;
+        let mut tasks = Vec::new();
+        for task in task_iter {
+            tasks.push(task?);
+        }
 
-            // Calculate duration
-            let start_dt = DateTime::parse_from_rfc3339(&start_at)
-                .map_err(|e| ApiError {
-                    code: "DateTimeError".to_string(),
-                    message: format!("Failed to parse start time: {}", e),
-                    details: None,
-                })?
-                .with_timezone(&Utc);
+        Ok(tasks)
+    }
 
-            let end_dt = Utc::now();
-            let duration_sec = end_dt.signed_duration_since(start_dt).num_seconds();
+    // Get unarchived tasks on a board (tasks without a board belong to the default board)
+    pub fn list_board_tasks(&self, board_id: &str) -> Result<Vec<Task>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM tasks WHERE archived = 0 AND COALESCE(board_id, ?) = ?")?;
+        let task_iter = stmt.query_map(params![DEFAULT_BOARD_ID, board_id], |row| {
+            task_from_row(row, self.cipher.as_ref())
+        })?;
 
-            // Update timer
-            self.conn.execute(
-                "UPDATE task_timer SET stop_at = ?, duration_sec = ? WHERE id = ?",
-                params![now, duration_sec, timer_id],
-            )?;
+        let mut tasks = Vec::new();
+        for task in task_iter {
+            tasks.push(task?);
         }
 
-        // Update task status to todo
+        Ok(tasks)
+    }
+
+    // Set or clear a task's custom kanban column
+    pub fn set_task_column(&self, task_id: &str, column_key: Option<&str>) -> Result<(), ApiError> {
         self.conn.execute(
-            "UPDATE tasks SET status = 'todo', updated_at = ? WHERE id = ?",
-            params![now, task_id],
+            "UPDATE tasks SET column_key = ? WHERE id = ?",
+            params![column_key, task_id],
         )?;
-
         Ok(())
     }
 
-    // Stop all active timers
-    fn stop_all_active_timers(&self) -> Result<(), ApiError> {
-        let now = Utc::now().to_rfc3339();
-
-        // Find all active timers
-        let mut stmt = self
-            .conn
-            .prepare("SELECT id, start_at FROM task_timer WHERE stop_at IS NULL")?;
-
-        let timer_iter = stmt.query_map([], |row| {
-            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    // Get tasks completed in [from, to)
+    pub fn list_completed_tasks_between(
+        &self,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<Task>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT * FROM tasks WHERE completed_at IS NOT NULL AND completed_at >= ? AND completed_at < ? ORDER BY completed_at",
+        )?;
+        let task_iter = stmt.query_map(params![from, to], |row| {
+            task_from_row(row, self.cipher.as_ref())
         })?;
 
-        for timer_result in timer_iter {
-            let (timer_id, start_at) = timer_result?;
+        let mut tasks = Vec::new();
+        for task in task_iter {
+            tasks.push(task?);
+        }
 
-            // Calculate duration
-            let start_dt = DateTime::parse_from_rfc3339(&start_at)
-                .map_err(|e| ApiError {
-                    code: "DateTimeError".to_string(),
-                    message: format!("Failed to parse start time: {}", e),
-                    details: None,
-                })?
-                .with_timezone(&Utc);
+        Ok(tasks)
+    }
 
-            let end_dt = Utc::now();
-            let duration_sec = end_dt.signed_duration_since(start_dt).num_seconds();
+    // Completion instants of every finished task, for day-level aggregation
+    pub fn list_completion_times(&self) -> Result<Vec<String>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT completed_at FROM tasks WHERE completed_at IS NOT NULL")?;
+        let time_iter = stmt.query_map([], |row| row.get::<_, String>(0))?;
 
-            // Update timer
-            self.conn.execute(
-                "UPDATE task_timer SET stop_at = ?, duration_sec = ? WHERE id = ?",
-                params![now, duration_sec, timer_id],
-            )?;
+        let mut times = Vec::new();
+        for time in time_iter {
+            times.push(time?);
         }
 
-        // Update all doing tasks to todo
-        self.conn.execute(
-            "UPDATE tasks SET status = 'todo', updated_at = ? WHERE status = 'doing'",
-            [now],
+        Ok(times)
+    }
+
+    // Get stopped timers that started in [from, to)
+    pub fn list_timers_between(&self, from: &str, to: &str) -> Result<Vec<Timer>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT * FROM task_timer WHERE stop_at IS NOT NULL AND start_at >= ? AND start_at < ? ORDER BY start_at",
         )?;
+        let timer_iter = stmt.query_map(params![from, to], timer_from_row)?;
 
-        Ok(())
+        let mut timers = Vec::new();
+        for timer in timer_iter {
+            timers.push(timer?);
+        }
+
+        Ok(timers)
     }
 
-    // Get day log for a specific day
-    pub fn get_day_log(&self, day: &str) -> Result<Option<DayLog>, ApiError> {
-        let mut stmt = self.conn.prepare("SELECT * FROM day_log WHERE day = ?")?;
+    // Get a task's timers, newest first
+    pub fn list_task_timers(&self, task_id: &str) -> Result<Vec<Timer>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM task_timer WHERE task_id = ? ORDER BY start_at DESC")?;
+        let timer_iter = stmt.query_map([task_id], timer_from_row)?;
 
-        let day_log = stmt
-            .query_row([day], |row| {
-                Ok(DayLog {
-                    day: row.get(0)?,
-                    daily_md_path: row.get(1)?,
-                    created_at: row.get(2)?,
-                    updated_at: row.get(3)?,
-                })
+        let mut timers = Vec::new();
+        for timer in timer_iter {
+            timers.push(timer?);
+        }
+
+        Ok(timers)
+    }
+
+    pub fn get_timer(&self, timer_id: &str) -> Result<Option<Timer>, ApiError> {
+        let timer = self
+            .conn
+            .query_row("SELECT * FROM task_timer WHERE id = ?", [timer_id], |row| {
+                timer_from_row(row)
             })
             .optional()?;
-
-        Ok(day_log)
+        Ok(timer)
     }
 
-    // Create or update a day log
-    pub fn upsert_day_log(&self, day: &str, daily_md_path: &str) -> Result<DayLog, ApiError> {
+    // Get timers (of any task) overlapping [start, end); a running timer counts as running until now
+    pub fn list_overlapping_timers(
+        &self,
+        start: &str,
+        end: &str,
+        exclude_timer_id: Option<&str>,
+    ) -> Result<Vec<Timer>, ApiError> {
         let now = Utc::now().to_rfc3339();
+        let mut stmt = self.conn.prepare(
+            r#"SELECT * FROM task_timer
+               WHERE start_at < ? AND COALESCE(stop_at, ?) > ? AND id != COALESCE(?, '')
+               ORDER BY start_at"#,
+        )?;
+        let timer_iter = stmt.query_map(params![end, now, start, exclude_timer_id], |row| {
+            timer_from_row(row)
+        })?;
 
-        // Check if day log exists
-        if let Some(mut existing_log) = self.get_day_log(day)? {
-            // Update existing log
-            existing_log.daily_md_path = daily_md_path.to_string();
-            existing_log.updated_at = now.clone();
+        let mut timers = Vec::new();
+        for timer in timer_iter {
+            timers.push(timer?);
+        }
 
-            self.conn.execute(
-                "UPDATE day_log SET daily_md_path = ?, updated_at = ? WHERE day = ?",
-                params![daily_md_path, now, day],
-            )?;
+        Ok(timers)
+    }
 
-            Ok(existing_log)
-        } else {
-            // Create new log
-            let day_log = DayLog {
-                day: day.to_string(),
-                daily_md_path: daily_md_path.to_string(),
-                created_at: now.clone(),
-                updated_at: now,
-            };
+    // Insert a completed timer entry
+    pub fn insert_timer(
+        &self,
+        task_id: &str,
+        start: &str,
+        end: &str,
+        duration_sec: i64,
+        source: &str,
+    ) -> Result<Timer, ApiError> {
+        let timer_id = Uuid::new_v4().to_string();
+        self.conn.execute(
+            r#"INSERT INTO task_timer (id, task_id, start_at, stop_at, duration_sec, source)
+               VALUES (?, ?, ?, ?, ?, ?)"#,
+            params![timer_id, task_id, start, end, duration_sec, source],
+        )?;
 
-            self.conn.execute(
-                "INSERT INTO day_log (day, daily_md_path, created_at, updated_at) VALUES (?, ?, ?, ?)",
-                params![day, daily_md_path, day_log.created_at.clone(), day_log.updated_at.clone()],
-            )?;
+        self.get_timer(&timer_id)?.ok_or_else(|| ApiError {
+            code: ErrorCode::DatabaseError,
+            message: "Inserted timer could not be read back".to_string(),
+            details: None,
+        })
+    }
 
-            Ok(day_log)
-        }
+    // Update the range of a completed timer
+    pub fn update_timer_range(
+        &self,
+        timer_id: &str,
+        start: &str,
+        end: &str,
+        duration_sec: i64,
+    ) -> Result<(), ApiError> {
+        self.conn.execute(
+            "UPDATE task_timer SET start_at = ?, stop_at = ?, duration_sec = ? WHERE id = ?",
+            params![start, end, duration_sec, timer_id],
+        )?;
+        Ok(())
     }
 
-    // Batch update tasks order and status
-    pub fn reorder_tasks(&self, tasks: Vec<ReorderTaskInput>) -> Result<(), ApiError> {
+    pub fn delete_timer(&self, timer_id: &str) -> Result<(), ApiError> {
+        self.conn
+            .execute("DELETE FROM task_timer WHERE id = ?", [timer_id])?;
+        Ok(())
+    }
+
+    // Stop a timer at a given instant and put its task back to todo
+    pub fn close_timer_at(
+        &self,
+        timer_id: &str,
+        task_id: &str,
+        stop_at: &str,
+        duration_sec: i64,
+    ) -> Result<(), ApiError> {
         let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            "UPDATE task_timer SET stop_at = ?, duration_sec = ? WHERE id = ?",
+            params![stop_at, duration_sec, timer_id],
+        )?;
+        self.conn.execute(
+            "UPDATE tasks SET status = 'todo', updated_at = ? WHERE id = ? AND status = 'doing'",
+            params![now, task_id],
+        )?;
+        Ok(())
+    }
 
-        for task in tasks {
-            match task.status {
-                Some(status) => {
-                    // Update both status and order_index
-                    self.conn.execute(
-                        r#"UPDATE tasks SET status = ?, order_index = ?, updated_at = ? WHERE id = ?"#,
-                        params![status.to_string(), task.order_index, now, task.id],
-                    )?;
-                }
-                None => {
-                    // Update only order_index
-                    self.conn.execute(
-                        r#"UPDATE tasks SET order_index = ?, updated_at = ? WHERE id = ?"#,
-                        params![task.order_index, now, task.id],
-                    )?;
-                }
-            }
-        }
+    pub fn get_vault_meta_value(&self, key: &str) -> Result<Option<String>, ApiError> {
+        let value = self
+            .conn
+            .query_row("SELECT value FROM vault_meta WHERE key = ?", [key], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        Ok(value)
+    }
 
+    pub fn set_vault_meta_value(&self, key: &str, value: &str) -> Result<(), ApiError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO vault_meta (key, value) VALUES (?, ?)",
+            params![key, value],
+        )?;
         Ok(())
     }
 
-    // Delete a task and its associated timers
-    pub fn delete_task(&mut self, task_id: &str) -> Result<(), ApiError> {
-        let span = span!(Level::INFO, "planning.delete_task", task_id = task_id);
-        let _enter = span.enter();
+    // Get tasks with an estimate paired with seconds tracked by stopped timers in [from, to)
+    pub fn list_estimated_tasks_with_actuals(
+        &self,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<(Task, i64)>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT t.*, SUM(tt.duration_sec) AS actual_sec
+               FROM tasks t
+               JOIN task_timer tt ON tt.task_id = t.id
+               WHERE t.estimate_min IS NOT NULL
+                 AND tt.stop_at IS NOT NULL
+                 AND tt.start_at >= ? AND tt.start_at < ?
+               GROUP BY t.id"#,
+        )?;
+        let rows = stmt.query_map(params![from, to], |row| {
+            Ok((
+                task_from_row(row, self.cipher.as_ref())?,
+                row.get::<_, i64>("actual_sec")?,
+            ))
+        })?;
 
-        // First, check if task exists
-        if self.get_task(task_id)?.is_none() {
-            return Err(ApiError {
-                code: "NotFound".to_string(),
-                message: format!("Task with id {} not found", task_id),
-                details: None,
-            });
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
         }
 
-        // Start a transaction to ensure atomicity
-        let transaction = self.conn.transaction()?;
+        Ok(result)
+    }
 
-        // Delete associated timers
-        transaction.execute("DELETE FROM task_timer WHERE task_id = ?", [task_id])?;
+    // Update task's scheduled block
+    pub fn update_task_schedule(
+        &self,
+        task_id: &str,
+        scheduled_start: &str,
+        scheduled_end: &str,
+    ) -> Result<Task, ApiError> {
+        let now = Utc::now().to_rfc3339();
 
-        // Delete the task
-        transaction.execute("DELETE FROM tasks WHERE id = ?", [task_id])?;
+        self.conn.execute(
+            "UPDATE tasks SET scheduled_start = ?, scheduled_end = ?, updated_at = ? WHERE id = ?",
+            params![scheduled_start, scheduled_end, now, task_id],
+        )?;
 
-        // Commit the transaction
+        self.get_task_by_id(task_id)
+    }
+
+    // Rewrite schedule values not yet stored as UTC instants, such as the vault wall-clock
+    // values written by earlier versions; returns how many tasks changed
+    pub fn normalize_schedule_times(&self, tz: Option<Tz>) -> Result<usize, ApiError> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT id, scheduled_start, scheduled_end FROM tasks
+               WHERE (scheduled_start IS NOT NULL AND scheduled_start NOT GLOB '????-??-??T??:??:??Z')
+                  OR (scheduled_end IS NOT NULL AND scheduled_end NOT GLOB '????-??-??T??:??:??Z')"#,
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        if rows.is_empty() {
+            return Ok(0);
+        }
+
+        // Values that are not times are left for the user to fix
+        let normalize = |value: Option<String>| {
+            value.map(|value| schedule::normalize_schedule_time(&value, tz).unwrap_or(value))
+        };
+        let transaction = self.conn.unchecked_transaction()?;
+        for (task_id, scheduled_start, scheduled_end) in rows.iter().cloned() {
+            transaction.execute(
+                "UPDATE tasks SET scheduled_start = ?, scheduled_end = ? WHERE id = ?",
+                params![
+                    normalize(scheduled_start),
+                    normalize(scheduled_end),
+                    task_id
+                ],
+            )?;
+        }
         transaction.commit()?;
+        Ok(rows.len())
+    }
 
-        info!(target: "planning", "delete_task succeeded: task_id={}", task_id);
+    // Update the scheduled blocks of several tasks in one transaction
+    pub fn update_task_schedules(
+        &self,
+        schedules: &[(String, String, String)],
+    ) -> Result<Vec<Task>, ApiError> {
+        let now = Utc::now().to_rfc3339();
+        let transaction = self.conn.unchecked_transaction()?;
+
+        for (task_id, scheduled_start, scheduled_end) in schedules {
+            transaction.execute(
+                "UPDATE tasks SET scheduled_start = ?, scheduled_end = ?, updated_at = ? WHERE id = ?",
+                params![scheduled_start, scheduled_end, now, task_id],
+            )?;
+        }
+
+        transaction.commit()?;
+
+        schedules
+            .iter()
+            .map(|(task_id, _, _)| self.get_task_by_id(task_id))
+            .collect()
+    }
+
+    // Update task's note_path
+    // Move a task to a new directory slug; note_path follows when it pointed at the task note
+    pub fn rename_task_slug(
+        &self,
+        task_id: &str,
+        slug: &str,
+        old_md_rel_path: &str,
+        md_rel_path: &str,
+    ) -> Result<(), ApiError> {
+        let now = Utc::now().to_rfc3339();
+
+        self.conn.execute(
+            r#"UPDATE tasks SET task_dir_slug = ?1, md_rel_path = ?2,
+               note_path = CASE WHEN note_path = ?3 THEN ?2 ELSE note_path END,
+               updated_at = ?4 WHERE id = ?5"#,
+            params![slug, md_rel_path, old_md_rel_path, now, task_id],
+        )?;
 
         Ok(())
     }
 
-    // Get UI state for a vault
-    #[allow(dead_code)]
-    pub fn get_ui_state(&self, vault_id: &str) -> Result<Option<String>, ApiError> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT state_json FROM ui_state WHERE vault_id = ?")?;
-        let result = stmt.query_row([vault_id], |row| row.get(0)).optional()?;
+    // Slugs name directories, so they compare without case like macOS and Windows filesystems do
+    pub fn ensure_slug_index(&self) -> Result<(), ApiError> {
+        self.conn
+            .execute(
+                "CREATE UNIQUE INDEX IF NOT EXISTS idx_tasks_dir_slug ON tasks(task_dir_slug COLLATE NOCASE)",
+                [],
+            )
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to create task slug index: {}", e),
+                details: None,
+            })?;
+        Ok(())
+    }
 
-        Ok(result)
+    pub fn has_slug_index(&self) -> Result<bool, ApiError> {
+        let exists = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'index' AND name = 'idx_tasks_dir_slug')",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(exists)
     }
 
-    // Set UI state for a vault (merge with existing state if it exists)
-    #[allow(dead_code)]
-    pub fn set_ui_state(&self, vault_id: &str, partial_state_json: &str) -> Result<(), ApiError> {
-        let now = Utc::now().to_rfc3339();
+    pub fn is_slug_taken(&self, slug: &str) -> Result<bool, ApiError> {
+        let taken = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM tasks WHERE task_dir_slug = ? COLLATE NOCASE)",
+            [slug],
+            |row| row.get(0),
+        )?;
+        Ok(taken)
+    }
 
-        // Get existing state if it exists
-        let existing_state_json = self.get_ui_state(vault_id)?;
+    // Tasks sharing a slug with another task, grouped by slug and oldest first within a group
+    pub fn list_duplicate_slug_tasks(&self) -> Result<Vec<Task>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT * FROM tasks WHERE lower(task_dir_slug) IN (
+                SELECT lower(task_dir_slug) FROM tasks WHERE task_dir_slug IS NOT NULL
+                GROUP BY lower(task_dir_slug) HAVING COUNT(*) > 1
+            )
+            ORDER BY lower(task_dir_slug), created_at, id"#,
+        )?;
+        let task_iter = stmt.query_map([], |row| task_from_row(row, self.cipher.as_ref()))?;
 
-        // Merge partial state with existing state
-        let merged_state_json = match existing_state_json {
-            Some(existing) => {
-                // Parse existing and partial states
-                let existing_state: serde_json::Value = serde_json::from_str(&existing)?;
-                let partial_state: serde_json::Value = serde_json::from_str(partial_state_json)?;
+        let mut tasks = Vec::new();
+        for task in task_iter {
+            tasks.push(task?);
+        }
 
-                // Merge partial into existing (partial takes precedence)
-                let merged_state = merge_json(existing_state, partial_state);
+        Ok(tasks)
+    }
 
-                // Serialize back to string
-                serde_json::to_string(&merged_state)?
-            }
-            None => {
-                // No existing state, use partial as full state
-                partial_state_json.to_string()
-            }
-        };
+    pub fn update_task_note_path(&self, task_id: &str, note_path: &str) -> Result<(), ApiError> {
+        let now = Utc::now().to_rfc3339();
 
-        // Upsert into database
         self.conn.execute(
-            r#"INSERT INTO ui_state (vault_id, state_json, updated_at)
-               VALUES (?, ?, ?)
-               ON CONFLICT(vault_id) DO UPDATE SET
-               state_json = excluded.state_json,
-               updated_at = excluded.updated_at"#,
-            params![vault_id, merged_state_json, now],
+            "UPDATE tasks SET note_path = ?, updated_at = ? WHERE id = ?",
+            params![note_path, now, task_id],
         )?;
 
         Ok(())
     }
 
-    // Get or generate vault_id for this vault
-    pub fn ensure_vault_id(&self, vault_root: &std::path::Path) -> Result<String, ApiError> {
-        let ids = self.get_vault_meta_from_db()?;
-        let db_vault_id = ids.0;
-        let db_created_at = ids.1;
+    // Run `f` in one transaction: repo calls it makes commit together, or roll back when it fails
+    pub fn in_transaction<T>(
+        &self,
+        f: impl FnOnce() -> Result<T, ApiError>,
+    ) -> Result<T, ApiError> {
+        let transaction = self.conn.unchecked_transaction()?;
+        let value = f()?;
+        transaction.commit()?;
+        Ok(value)
+    }
 
-        let meta_path = vault_meta_path(vault_root);
-        let file_meta = if meta_path.exists() {
-            let content = std::fs::read_to_string(&meta_path).map_err(|e| ApiError {
-                code: "IOError".to_string(),
-                message: format!("Failed to read vault.json: {}", e),
-                details: None,
-            })?;
-            serde_json::from_str::<VaultMeta>(&content).ok()
-        } else {
-            None
+    // Column value as stored: sealed when the database is encrypted
+    fn seal_text(&self, value: Option<&str>) -> Result<Option<String>, ApiError> {
+        match (value, &self.cipher) {
+            (Some(value), Some(cipher)) => cipher.seal(value).map(Some),
+            (value, _) => Ok(value.map(str::to_string)),
+        }
+    }
+
+    pub fn is_encrypted(&self) -> Result<bool, ApiError> {
+        Ok(self.get_vault_meta_value(ENCRYPTION_SALT_KEY)?.is_some())
+    }
+
+    // Pass every stored description through `rewrite`; returns how many changed
+    fn rewrite_descriptions(
+        &self,
+        rewrite: impl Fn(&str) -> Result<String, ApiError>,
+    ) -> Result<usize, ApiError> {
+        let rows: Vec<(String, String)> = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT id, description FROM tasks WHERE description IS NOT NULL")?;
+            let row_iter = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            row_iter.collect::<Result<_, _>>()?
         };
+        let mut changed = 0;
+        for (task_id, value) in rows {
+            let rewritten = rewrite(&value)?;
+            if rewritten != value {
+                self.conn.execute(
+                    "UPDATE tasks SET description = ? WHERE id = ?",
+                    params![rewritten, task_id],
+                )?;
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
 
-        let (vault_id, created_at) = match (db_vault_id, file_meta) {
-            (Some(id), Some(meta)) => {
-                if id != meta.vault_id {
-                    info!(target: "planning", "Vault ID mismatch! DB: {}, File: {}", id, meta.vault_id);
-                    // For now, trust DB as source of truth for the app
+    // Rewrite the file so pages that held the old column values are dropped
+    fn compact(&self) -> Result<(), ApiError> {
+        self.conn
+            .execute_batch("PRAGMA wal_checkpoint(TRUNCATE); VACUUM;")
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to compact database: {}", e),
+                details: None,
+            })
+    }
+
+    // Seal existing descriptions and record the key parameters; the search index is dropped so it
+    // gets rebuilt without them. Returns how many tasks were encrypted.
+    pub fn enable_encryption(
+        &mut self,
+        cipher: FieldCipher,
+        salt: &str,
+    ) -> Result<usize, ApiError> {
+        let sealed = self.in_transaction(|| {
+            let sealed = self.rewrite_descriptions(|value| {
+                if db_crypto::is_sealed(value) {
+                    Ok(value.to_string())
+                } else {
+                    cipher.seal(value)
                 }
-                (id, meta.created_at) // Use DB id, file time? Or DB time?
-            }
-            (Some(id), None) => {
-                // DB has ID, file missing -> sync to file
-                let created_at = db_created_at.unwrap_or_else(|| Utc::now().to_rfc3339());
-                self.write_vault_meta_file(&meta_path, &id, &created_at)?;
-                (id, created_at)
-            }
-            (None, Some(meta)) => {
-                // DB missing, file has ID -> restore to DB
-                self.store_vault_meta_to_db(&meta.vault_id, &meta.created_at)?;
-                (meta.vault_id, meta.created_at)
-            }
-            (None, None) => {
-                // Both missing -> new vault
-                let id = Uuid::new_v4().to_string();
-                let now = Utc::now().to_rfc3339();
-                self.store_vault_meta_to_db(&id, &now)?;
-                self.write_vault_meta_file(&meta_path, &id, &now)?;
-                (id, now)
-            }
-        };
+            })?;
+            self.set_vault_meta_value(ENCRYPTION_SALT_KEY, salt)?;
+            self.set_vault_meta_value(ENCRYPTION_VERIFIER_KEY, &cipher.verifier())?;
+            self.conn
+                .execute_batch("DELETE FROM task_fts; DELETE FROM task_fts_meta;")?;
+            Ok(sealed)
+        })?;
+        self.compact()?;
+        self.cipher = Some(cipher);
+        Ok(sealed)
+    }
 
-        Ok(vault_id)
+    // Open every sealed description and forget the key parameters. Returns how many tasks changed.
+    pub fn disable_encryption(&mut self, cipher: &FieldCipher) -> Result<usize, ApiError> {
+        let opened = self.in_transaction(|| {
+            let opened = self.rewrite_descriptions(|value| cipher.open(value))?;
+            self.conn.execute(
+                "DELETE FROM vault_meta WHERE key IN (?, ?)",
+                params![ENCRYPTION_SALT_KEY, ENCRYPTION_VERIFIER_KEY],
+            )?;
+            self.conn
+                .execute_batch("DELETE FROM task_fts; DELETE FROM task_fts_meta;")?;
+            Ok(opened)
+        })?;
+        self.compact()?;
+        self.cipher = None;
+        Ok(opened)
     }
 
-    fn get_vault_meta_from_db(&self) -> Result<(Option<String>, Option<String>), ApiError> {
-        let vault_id: Option<String> = self
-            .conn
-            .query_row(
-                "SELECT value FROM vault_meta WHERE key = 'vault_id'",
-                [],
-                |row| row.get(0),
-            )
-            .optional()?;
+    // Create a new task
+    pub fn create_task(
+        &self,
+        title: &str,
+        description: Option<&str>,
+        status: TaskStatus,
+        priority: Option<TaskPriority>,
+        due_date: Option<&str>,
+        board_id: Option<&str>,
+        estimate_min: Option<i64>,
+        tags: Option<&Vec<String>>,
+        subtasks: Option<&Vec<crate::domain::planning::Subtask>>,
+        periodicity: Option<&crate::domain::planning::TaskPeriodicity>,
+        scheduled_start: Option<&str>,
+        scheduled_end: Option<&str>,
+        note_path: Option<&str>,
+        color: Option<&str>,
+        completed_at: Option<&str>,
+        task_dir_slug: Option<&str>,
+        md_rel_path: Option<&str>,
+    ) -> Result<Task, ApiError> {
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now().to_rfc3339();
+
+        // Get max order index for the status
+        let max_order: i64 = self.conn.query_row(
+            "SELECT COALESCE(MAX(order_index), -1) FROM tasks WHERE status = ?",
+            [status.to_string()],
+            |row| row.get(0),
+        )?;
+
+        let order_index = max_order + 1;
+        let description = self.seal_text(description)?;
+
+        let tags_json = match tags {
+            Some(tags_vec) if !tags_vec.is_empty() => match serde_json::to_string(tags_vec) {
+                Ok(json) => Some(json),
+                Err(e) => {
+                    log::warn!("Failed to serialize tags: {}", e);
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        // Convert subtasks to JSON string
+        let subtasks_json = match subtasks {
+            Some(subtasks_vec) if !subtasks_vec.is_empty() => {
+                match serde_json::to_string(subtasks_vec) {
+                    Ok(json) => Some(json),
+                    Err(e) => {
+                        log::warn!("Failed to serialize subtasks: {}", e);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        // Convert periodicity to JSON string
+        let periodicity_json = match periodicity {
+            Some(p) => match serde_json::to_string(p) {
+                Ok(json) => Some(json),
+                Err(e) => {
+                    log::warn!("Failed to serialize periodicity: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        self.conn.execute(
+            r#"INSERT INTO tasks (
+                id, title, description, status, priority, tags, subtasks, periodicity, 
+                due_date, board_id, order_index, estimate_min, scheduled_start, scheduled_end, 
+                note_path, color, created_at, updated_at, completed_at, archived,
+                task_dir_slug, md_rel_path
+            ) 
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 0, ?, ?)"#,
+            params![
+                id,
+                title,
+                description,
+                status.to_string(),
+                priority.map(|p| p.to_string()),
+                tags_json,
+                subtasks_json,
+                periodicity_json,
+                due_date,
+                board_id,
+                order_index,
+                estimate_min,
+                scheduled_start,
+                scheduled_end,
+                note_path,
+                color,
+                now,
+                now,
+                completed_at,
+                task_dir_slug,
+                md_rel_path
+            ],
+        )?;
+
+        self.get_task_by_id(&id)
+    }
+
+    // Update an existing task
+    pub fn update_task(
+        &self,
+        task_id: &str,
+        title: Option<&str>,
+        description: Option<&str>,
+        status: Option<TaskStatus>,
+        priority: Option<TaskPriority>,
+        tags: Option<&Vec<String>>,
+        subtasks: Option<&Vec<crate::domain::planning::Subtask>>,
+        periodicity: Option<&crate::domain::planning::TaskPeriodicity>,
+        order_index: Option<i64>,
+        estimate_min: Option<i64>,
+        scheduled_start: Option<&str>,
+        scheduled_end: Option<&str>,
+        due_date: Option<Option<String>>,
+        board_id: Option<&str>,
+        color: Option<Option<String>>,
+        note_path: Option<&str>,
+        archived: Option<i32>,
+        completed_at: Option<Option<String>>,
+    ) -> Result<Task, ApiError> {
+        let now = Utc::now().to_rfc3339();
+
+        // Get current task to preserve unchanged fields
+        let mut current_task = self.get_task_by_id(task_id)?;
+
+        // Update fields if provided
+        if let Some(new_title) = title {
+            current_task.title = new_title.to_string();
+        }
+
+        if let Some(new_description) = description {
+            current_task.description = Some(new_description.to_string());
+        }
+
+        if let Some(new_status) = status {
+            current_task.status = new_status;
+            // Update order_index if status changed
+            let max_order: i64 = self.conn.query_row(
+                "SELECT COALESCE(MAX(order_index), -1) FROM tasks WHERE status = ?",
+                [new_status.to_string()],
+                |row| row.get(0),
+            )?;
+            current_task.order_index = max_order + 1;
+        }
+
+        if let Some(new_priority) = priority {
+            current_task.priority = Some(new_priority);
+        }
+
+        if let Some(new_tags) = tags {
+            current_task.tags = Some(new_tags.clone());
+            current_task.labels = Some(new_tags.clone());
+        }
+
+        if let Some(new_subtasks) = subtasks {
+            current_task.subtasks = Some(new_subtasks.clone());
+        }
+
+        if let Some(new_periodicity) = periodicity {
+            current_task.periodicity = Some(new_periodicity.clone());
+        }
+
+        if let Some(new_order) = order_index {
+            current_task.order_index = new_order;
+        }
+
+        if let Some(new_estimate) = estimate_min {
+            current_task.estimate_min = Some(new_estimate);
+        }
+
+        if let Some(new_start) = scheduled_start {
+            current_task.scheduled_start = Some(new_start.to_string());
+        }
+
+        if let Some(new_end) = scheduled_end {
+            current_task.scheduled_end = Some(new_end.to_string());
+        }
+
+        if let Some(new_due_date) = due_date {
+            current_task.due_date = new_due_date;
+        }
+
+        if let Some(new_board_id) = board_id {
+            current_task.board_id = Some(new_board_id.to_string());
+        }
+
+        if let Some(new_color) = color {
+            current_task.color = new_color;
+        }
+
+        if let Some(new_note_path) = note_path {
+            current_task.note_path = Some(new_note_path.to_string());
+        }
+
+        if let Some(new_archived) = archived {
+            current_task.archived = new_archived;
+        }
+
+        if let Some(new_completed_at) = completed_at {
+            current_task.completed_at = new_completed_at;
+        }
+
+        current_task.updated_at = now;
+
+        // Serialize tags to JSON string
+        let tags_json = match &current_task.tags {
+            Some(tags) if !tags.is_empty() => match serde_json::to_string(tags) {
+                Ok(json) => Some(json),
+                Err(e) => {
+                    log::warn!("Failed to serialize tags: {} for task {}", e, task_id);
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        // Serialize subtasks to JSON string
+        let subtasks_json = match &current_task.subtasks {
+            Some(subtasks) if !subtasks.is_empty() => match serde_json::to_string(subtasks) {
+                Ok(json) => Some(json),
+                Err(e) => {
+                    log::warn!("Failed to serialize subtasks: {} for task {}", e, task_id);
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        // Serialize periodicity to JSON string
+        let periodicity_json = match &current_task.periodicity {
+            Some(p) => match serde_json::to_string(p) {
+                Ok(json) => Some(json),
+                Err(e) => {
+                    log::warn!(
+                        "Failed to serialize periodicity: {} for task {}",
+                        e,
+                        task_id
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+
+        // Update in database
+        self.conn.execute(
+            r#"UPDATE tasks SET title = ?, description = ?, status = ?, priority = ?, tags = ?, subtasks = ?, periodicity = ?, due_date = ?, board_id = ?, color = ?, order_index = ?, estimate_min = ?,
+               scheduled_start = ?, scheduled_end = ?, note_path = ?, updated_at = ?, archived = ?, completed_at = ?
+               WHERE id = ?"#,
+            params![
+                current_task.title, self.seal_text(current_task.description.as_deref())?, current_task.status.to_string(),
+                current_task.priority.map(|p| p.to_string()), tags_json, subtasks_json, periodicity_json, current_task.due_date,
+                current_task.board_id, current_task.color, current_task.order_index, current_task.estimate_min,
+                current_task.scheduled_start, current_task.scheduled_end, current_task.note_path,
+                current_task.updated_at, current_task.archived, current_task.completed_at, task_id
+            ],
+        )?;
+
+        self.get_task_by_id(task_id)
+    }
+
+    // Mark a task as done
+    pub fn mark_task_done(&self, task_id: &str) -> Result<Task, ApiError> {
+        let now = Utc::now().to_rfc3339();
+
+        self.conn.execute(
+            "UPDATE tasks SET status = 'done', completed_at = ?, updated_at = ? WHERE id = ?",
+            params![now, now, task_id],
+        )?;
+
+        self.get_task_by_id(task_id)
+    }
+
+    // Reopen a completed task
+    pub fn reopen_task(&self, task_id: &str) -> Result<Task, ApiError> {
+        let now = Utc::now().to_rfc3339();
+
+        self.conn.execute(
+            "UPDATE tasks SET status = 'todo', completed_at = NULL, updated_at = ? WHERE id = ?",
+            params![now, task_id],
+        )?;
+
+        self.get_task_by_id(task_id)
+    }
+
+    // Start a task (create a timer and update task status)
+    pub fn start_task(&self, task_id: &str) -> Result<(), ApiError> {
+        // First, stop any existing active timer
+        self.stop_all_active_timers()?;
+
+        let now = Utc::now().to_rfc3339();
+        let timer_id = Uuid::new_v4().to_string();
+
+        // Create new timer
+        self.conn.execute(
+            r#"INSERT INTO task_timer (id, task_id, start_at, duration_sec, source) 
+               VALUES (?, ?, ?, 0, 'manual')"#,
+            params![timer_id, task_id, now],
+        )?;
+
+        // Update task status to doing
+        self.conn.execute(
+            "UPDATE tasks SET status = 'doing', updated_at = ? WHERE id = ?",
+            params![now, task_id],
+        )?;
+
+        Ok(())
+    }
+
+    // Stop a task (update timer and task status)
+    pub fn stop_task(&self, task_id: &str) -> Result<(), ApiError> {
+        let now = Utc::now().to_rfc3339();
+
+        // Find active timer for this task
+        let mut stmt = self.conn.prepare(
+            "SELECT id, start_at FROM task_timer WHERE task_id = ? AND stop_at IS NULL LIMIT 1",
+        )?;
+
+        let mut timer_iter = stmt.query_map([task_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        if let Some(timer_result) = timer_iter.next() {
+            let (timer_id, start_at) = timer_result?;
+
+            // Calculate duration
+            let start_dt = DateTime::parse_from_rfc3339(&start_at)
+                .map_err(|e| ApiError {
+                    code: ErrorCode::DateTimeError,
+                    message: format!("Failed to parse start time: {}", e),
+                    details: None,
+                })?
+                .with_timezone(&Utc);
+
+            let end_dt = Utc::now();
+            let duration_sec = end_dt.signed_duration_since(start_dt).num_seconds();
+
+            // Update timer
+            self.conn.execute(
+                "UPDATE task_timer SET stop_at = ?, duration_sec = ? WHERE id = ?",
+                params![now, duration_sec, timer_id],
+            )?;
+        }
+
+        // Update task status to todo
+        self.conn.execute(
+            "UPDATE tasks SET status = 'todo', updated_at = ? WHERE id = ?",
+            params![now, task_id],
+        )?;
+
+        Ok(())
+    }
+
+    // Stop all active timers
+    fn stop_all_active_timers(&self) -> Result<(), ApiError> {
+        let now = Utc::now().to_rfc3339();
+
+        // Find all active timers
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, start_at FROM task_timer WHERE stop_at IS NULL")?;
+
+        let timer_iter = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        for timer_result in timer_iter {
+            let (timer_id, start_at) = timer_result?;
+
+            // Calculate duration
+            let start_dt = DateTime::parse_from_rfc3339(&start_at)
+                .map_err(|e| ApiError {
+                    code: ErrorCode::DateTimeError,
+                    message: format!("Failed to parse start time: {}", e),
+                    details: None,
+                })?
+                .with_timezone(&Utc);
+
+            let end_dt = Utc::now();
+            let duration_sec = end_dt.signed_duration_since(start_dt).num_seconds();
+
+            // Update timer
+            self.conn.execute(
+                "UPDATE task_timer SET stop_at = ?, duration_sec = ? WHERE id = ?",
+                params![now, duration_sec, timer_id],
+            )?;
+        }
+
+        // Update all doing tasks to todo
+        self.conn.execute(
+            "UPDATE tasks SET status = 'todo', updated_at = ? WHERE status = 'doing'",
+            [now],
+        )?;
+
+        Ok(())
+    }
+
+    // Get day log for a specific day
+    pub fn get_day_log(&self, day: &str) -> Result<Option<DayLog>, ApiError> {
+        let mut stmt = self.conn.prepare("SELECT * FROM day_log WHERE day = ?")?;
+
+        let day_log = stmt
+            .query_row([day], |row| {
+                Ok(DayLog {
+                    day: row.get(0)?,
+                    daily_md_path: row.get(1)?,
+                    created_at: row.get(2)?,
+                    updated_at: row.get(3)?,
+                })
+            })
+            .optional()?;
+
+        Ok(day_log)
+    }
+
+    // Create or update a day log
+    pub fn upsert_day_log(&self, day: &str, daily_md_path: &str) -> Result<DayLog, ApiError> {
+        let now = Utc::now().to_rfc3339();
+
+        // Check if day log exists
+        if let Some(mut existing_log) = self.get_day_log(day)? {
+            // Update existing log
+            existing_log.daily_md_path = daily_md_path.to_string();
+            existing_log.updated_at = now.clone();
+
+            self.conn.execute(
+                "UPDATE day_log SET daily_md_path = ?, updated_at = ? WHERE day = ?",
+                params![daily_md_path, now, day],
+            )?;
+
+            Ok(existing_log)
+        } else {
+            // Create new log
+            let day_log = DayLog {
+                day: day.to_string(),
+                daily_md_path: daily_md_path.to_string(),
+                created_at: now.clone(),
+                updated_at: now,
+            };
+
+            self.conn.execute(
+                "INSERT INTO day_log (day, daily_md_path, created_at, updated_at) VALUES (?, ?, ?, ?)",
+                params![day, daily_md_path, day_log.created_at.clone(), day_log.updated_at.clone()],
+            )?;
+
+            Ok(day_log)
+        }
+    }
+
+    // Batch update tasks order and status
+    pub fn reorder_tasks(&self, tasks: Vec<ReorderTaskInput>) -> Result<(), ApiError> {
+        let now = Utc::now().to_rfc3339();
+
+        for task in tasks {
+            match task.status {
+                Some(status) => {
+                    // Update status, column and order_index; a plain status move leaves the custom column
+                    self.conn.execute(
+                        r#"UPDATE tasks SET status = ?, column_key = ?, order_index = ?, updated_at = ? WHERE id = ?"#,
+                        params![status.to_string(), task.column_key, task.order_index, now, task.id],
+                    )?;
+                }
+                None => {
+                    // Update only order_index
+                    self.conn.execute(
+                        r#"UPDATE tasks SET order_index = ?, updated_at = ? WHERE id = ?"#,
+                        params![task.order_index, now, task.id],
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Get registered columns of every board
+    pub fn list_all_board_columns(&self) -> Result<Vec<BoardColumn>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM board_columns ORDER BY board_id, order_index")?;
+        let column_iter = stmt.query_map([], board_column_from_row)?;
+
+        let mut columns = Vec::new();
+        for column in column_iter {
+            columns.push(column?);
+        }
+
+        Ok(columns)
+    }
+
+    // Get registered columns of a board
+    pub fn list_board_columns(&self, board_id: &str) -> Result<Vec<BoardColumn>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM board_columns WHERE board_id = ? ORDER BY order_index")?;
+        let column_iter = stmt.query_map([board_id], board_column_from_row)?;
+
+        let mut columns = Vec::new();
+        for column in column_iter {
+            columns.push(column?);
+        }
+
+        Ok(columns)
+    }
+
+    // Replace the column registry of a board; tasks in removed columns fall back to their status column
+    pub fn replace_board_columns(
+        &self,
+        board_id: &str,
+        columns: &[BoardColumn],
+    ) -> Result<(), ApiError> {
+        let transaction = self.conn.unchecked_transaction()?;
+
+        transaction.execute("DELETE FROM board_columns WHERE board_id = ?", [board_id])?;
+        for column in columns {
+            transaction.execute(
+                r#"INSERT INTO board_columns (board_id, key, name, wip_limit, maps_to, order_index)
+                   VALUES (?, ?, ?, ?, ?, ?)"#,
+                params![
+                    board_id,
+                    column.key,
+                    column.name,
+                    column.wip_limit,
+                    column.maps_to.to_string(),
+                    column.order_index
+                ],
+            )?;
+        }
+
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    // Settings of every board that has any
+    pub fn list_boards(&self) -> Result<Vec<BoardMeta>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM boards ORDER BY board_id")?;
+        let board_iter = stmt.query_map([], board_meta_from_row)?;
+
+        let mut boards = Vec::new();
+        for meta in board_iter {
+            boards.push(meta?);
+        }
+
+        Ok(boards)
+    }
+
+    // Colors by board id, for boards that have one
+    pub fn list_board_colors(&self) -> Result<BTreeMap<String, String>, ApiError> {
+        Ok(self
+            .list_boards()?
+            .into_iter()
+            .filter_map(|meta| Some((meta.board_id, meta.color?)))
+            .collect())
+    }
+
+    // Set or clear a board's color
+    pub fn set_board_color(
+        &self,
+        board_id: &str,
+        color: Option<&str>,
+    ) -> Result<BoardMeta, ApiError> {
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            r#"INSERT INTO boards (board_id, color, updated_at) VALUES (?, ?, ?)
+               ON CONFLICT(board_id) DO UPDATE SET color = excluded.color, updated_at = excluded.updated_at"#,
+            params![board_id, color, now],
+        )?;
+        Ok(BoardMeta {
+            board_id: board_id.to_string(),
+            color: color.map(str::to_string),
+            updated_at: now,
+        })
+    }
+
+    // Get the custom field schema of every board
+    pub fn list_all_board_fields(&self) -> Result<Vec<BoardField>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM board_fields ORDER BY board_id, order_index")?;
+        let field_iter = stmt.query_map([], board_field_from_row)?;
+
+        let mut fields = Vec::new();
+        for field in field_iter {
+            fields.push(field?);
+        }
+
+        Ok(fields)
+    }
+
+    // Get the custom field schema of a board
+    pub fn list_board_fields(&self, board_id: &str) -> Result<Vec<BoardField>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM board_fields WHERE board_id = ? ORDER BY order_index")?;
+        let field_iter = stmt.query_map([board_id], board_field_from_row)?;
+
+        let mut fields = Vec::new();
+        for field in field_iter {
+            fields.push(field?);
+        }
+
+        Ok(fields)
+    }
+
+    // Replace the field schema of a board; values of removed fields stay on the tasks as text
+    pub fn replace_board_fields(
+        &self,
+        board_id: &str,
+        fields: &[BoardField],
+    ) -> Result<(), ApiError> {
+        let transaction = self.conn.unchecked_transaction()?;
+
+        transaction.execute("DELETE FROM board_fields WHERE board_id = ?", [board_id])?;
+        for field in fields {
+            transaction.execute(
+                r#"INSERT INTO board_fields (board_id, key, name, kind, options, required, order_index)
+                   VALUES (?, ?, ?, ?, ?, ?, ?)"#,
+                params![
+                    board_id,
+                    field.key,
+                    field.name,
+                    field.kind.as_str(),
+                    serde_json::to_string(&field.options).ok(),
+                    field.required,
+                    field.order_index
+                ],
+            )?;
+        }
+
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    pub fn get_task_fields(&self, task_id: &str) -> Result<BTreeMap<String, String>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT key, value FROM task_fields WHERE task_id = ?")?;
+        let rows = stmt.query_map([task_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        let mut fields = BTreeMap::new();
+        for row in rows {
+            let (key, value) = row?;
+            fields.insert(key, value);
+        }
+
+        Ok(fields)
+    }
+
+    // Set or (with None) clear field values of a task in one transaction
+    pub fn set_task_fields(
+        &self,
+        task_id: &str,
+        fields: &BTreeMap<String, Option<String>>,
+    ) -> Result<(), ApiError> {
+        let now = Utc::now().to_rfc3339();
+        let transaction = self.conn.unchecked_transaction()?;
+
+        for (key, value) in fields {
+            match value {
+                Some(value) => transaction.execute(
+                    r#"INSERT INTO task_fields (task_id, key, value, updated_at) VALUES (?, ?, ?, ?)
+                       ON CONFLICT(task_id, key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at"#,
+                    params![task_id, key, value, now],
+                )?,
+                None => transaction.execute(
+                    "DELETE FROM task_fields WHERE task_id = ? AND key = ?",
+                    params![task_id, key],
+                )?,
+            };
+        }
+
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    // Every stored field value, optionally only of tasks on one board
+    pub fn list_task_field_values(
+        &self,
+        board_id: Option<&str>,
+    ) -> Result<Vec<TaskFieldValue>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT f.task_id, f.key, f.value FROM task_fields f
+               JOIN tasks t ON t.id = f.task_id
+               WHERE ?1 IS NULL OR COALESCE(t.board_id, ?2) = ?1
+               ORDER BY f.task_id, f.key"#,
+        )?;
+        let rows = stmt.query_map(params![board_id, DEFAULT_BOARD_ID], |row| {
+            Ok(TaskFieldValue {
+                task_id: row.get(0)?,
+                key: row.get(1)?,
+                value: row.get(2)?,
+            })
+        })?;
+
+        let mut values = Vec::new();
+        for value in rows {
+            values.push(value?);
+        }
+
+        Ok(values)
+    }
+
+    pub fn create_sprint(&self, input: &SprintInput) -> Result<Sprint, ApiError> {
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            r#"INSERT INTO sprints (id, name, start_date, end_date, capacity, created_at, updated_at)
+               VALUES (?, ?, ?, ?, ?, ?, ?)"#,
+            params![
+                id,
+                input.name.trim(),
+                input.start_date.trim(),
+                input.end_date.trim(),
+                input.capacity,
+                now,
+                now
+            ],
+        )?;
+        self.get_sprint(&id)?.ok_or_else(|| ApiError {
+            code: ErrorCode::DatabaseError,
+            message: "Sprint missing after insert".to_string(),
+            details: Some(serde_json::json!({ "id": id })),
+        })
+    }
+
+    // None when no sprint has the id
+    pub fn update_sprint(&self, id: &str, input: &SprintInput) -> Result<Option<Sprint>, ApiError> {
+        let now = Utc::now().to_rfc3339();
+        self.conn.execute(
+            r#"UPDATE sprints SET name = ?, start_date = ?, end_date = ?, capacity = ?, updated_at = ?
+               WHERE id = ?"#,
+            params![
+                input.name.trim(),
+                input.start_date.trim(),
+                input.end_date.trim(),
+                input.capacity,
+                now,
+                id
+            ],
+        )?;
+        self.get_sprint(id)
+    }
+
+    // Tasks of a deleted sprint become unassigned
+    pub fn delete_sprint(&self, id: &str) -> Result<bool, ApiError> {
+        let transaction = self.conn.unchecked_transaction()?;
+        transaction.execute("DELETE FROM sprint_tasks WHERE sprint_id = ?", [id])?;
+        let deleted = transaction.execute("DELETE FROM sprints WHERE id = ?", [id])?;
+        transaction.commit()?;
+        Ok(deleted > 0)
+    }
+
+    pub fn get_sprint(&self, id: &str) -> Result<Option<Sprint>, ApiError> {
+        let sprint = self
+            .conn
+            .query_row("SELECT * FROM sprints WHERE id = ?", [id], |row| {
+                sprint_from_row(row)
+            })
+            .optional()?;
+        Ok(sprint)
+    }
+
+    // Most recent sprints first
+    pub fn list_sprints(&self) -> Result<Vec<Sprint>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM sprints ORDER BY start_date DESC, created_at DESC")?;
+        let sprint_iter = stmt.query_map([], sprint_from_row)?;
+
+        let mut sprints = Vec::new();
+        for sprint in sprint_iter {
+            sprints.push(sprint?);
+        }
+
+        Ok(sprints)
+    }
+
+    // Commit tasks to a sprint, moving them out of any other one. Without points a task keeps
+    // the points it had.
+    pub fn assign_to_sprint(
+        &self,
+        sprint_id: &str,
+        task_ids: &[String],
+        points: Option<f64>,
+    ) -> Result<(), ApiError> {
+        let now = Utc::now().to_rfc3339();
+        let transaction = self.conn.unchecked_transaction()?;
+
+        for task_id in task_ids {
+            transaction.execute(
+                r#"INSERT INTO sprint_tasks (task_id, sprint_id, points, added_at) VALUES (?, ?, ?, ?)
+                   ON CONFLICT(task_id) DO UPDATE SET
+                     added_at = CASE WHEN sprint_id = excluded.sprint_id THEN added_at ELSE excluded.added_at END,
+                     sprint_id = excluded.sprint_id,
+                     points = COALESCE(excluded.points, points)"#,
+                params![task_id, sprint_id, points, now],
+            )?;
+        }
+
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    pub fn unassign_from_sprint(&self, task_ids: &[String]) -> Result<usize, ApiError> {
+        let transaction = self.conn.unchecked_transaction()?;
+        let mut removed = 0;
+        for task_id in task_ids {
+            removed +=
+                transaction.execute("DELETE FROM sprint_tasks WHERE task_id = ?", [task_id])?;
+        }
+        transaction.commit()?;
+        Ok(removed)
+    }
+
+    pub fn list_sprint_tasks(&self, sprint_id: &str) -> Result<Vec<SprintTask>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT t.*, s.points AS sprint_points, s.added_at AS sprint_added_at
+               FROM sprint_tasks s JOIN tasks t ON t.id = s.task_id
+               WHERE s.sprint_id = ?
+               ORDER BY t.status, t.order_index"#,
+        )?;
+        let task_iter = stmt.query_map([sprint_id], |row| {
+            Ok(SprintTask {
+                task: task_from_row(row, self.cipher.as_ref())?,
+                points: row.get("sprint_points")?,
+                added_at: row.get("sprint_added_at")?,
+            })
+        })?;
+
+        let mut tasks = Vec::new();
+        for task in task_iter {
+            tasks.push(task?);
+        }
+
+        Ok(tasks)
+    }
+
+    // Delete a task and its associated timers
+    pub fn delete_task(&mut self, task_id: &str) -> Result<(), ApiError> {
+        let span = span!(Level::INFO, "planning.delete_task", task_id = task_id);
+        let _enter = span.enter();
+
+        // First, check if task exists
+        if self.get_task(task_id)?.is_none() {
+            return Err(ApiError {
+                code: ErrorCode::NotFound,
+                message: format!("Task with id {} not found", task_id),
+                details: None,
+            });
+        }
+
+        // Start a transaction to ensure atomicity
+        let transaction = self.conn.transaction()?;
+
+        // Delete associated timers
+        transaction.execute("DELETE FROM task_timer WHERE task_id = ?", [task_id])?;
+
+        // Delete its note links
+        transaction.execute("DELETE FROM task_note_links WHERE task_id = ?", [task_id])?;
+
+        // Delete its custom field values and sprint commitment
+        transaction.execute("DELETE FROM task_fields WHERE task_id = ?", [task_id])?;
+        transaction.execute("DELETE FROM sprint_tasks WHERE task_id = ?", [task_id])?;
+        transaction.execute("DELETE FROM task_reminders WHERE task_id = ?", [task_id])?;
+        transaction.execute("DELETE FROM notifications WHERE task_id = ?", [task_id])?;
+
+        // Delete its search index rows
+        transaction.execute("DELETE FROM task_fts WHERE task_id = ?", [task_id])?;
+        transaction.execute("DELETE FROM task_fts_meta WHERE task_id = ?", [task_id])?;
+
+        // Delete the task
+        transaction.execute("DELETE FROM tasks WHERE id = ?", [task_id])?;
+
+        // Commit the transaction
+        transaction.commit()?;
+
+        info!(target: "planning", "delete_task succeeded: task_id={}", task_id);
+
+        Ok(())
+    }
+
+    // Get the task id recorded for an already applied op_id
+    pub fn get_op_task_id(&self, op_id: &str) -> Result<Option<String>, ApiError> {
+        let task_id = self
+            .conn
+            .query_row(
+                "SELECT task_id FROM task_ops WHERE op_id = ?",
+                [op_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(task_id)
+    }
+
+    // Record that frontmatter `fields` of a task are to be synced; returns the journal entry id
+    pub fn journal_md_sync(&self, task_id: &str, fields: &[&str]) -> Result<i64, ApiError> {
+        self.conn.execute(
+            "INSERT INTO md_sync_journal (task_id, fields, created_at) VALUES (?, ?, ?)",
+            params![
+                task_id,
+                serde_json::to_string(fields)?,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn clear_md_sync(&self, id: i64) -> Result<(), ApiError> {
+        self.conn
+            .execute("DELETE FROM md_sync_journal WHERE id = ?", [id])?;
+        Ok(())
+    }
+
+    // Drop every journaled sync; returns how many there were
+    pub fn clear_md_sync_journal(&self) -> Result<usize, ApiError> {
+        let cleared = self.conn.execute("DELETE FROM md_sync_journal", [])?;
+        Ok(cleared)
+    }
+
+    // Journaled syncs never cleared, oldest first
+    pub fn list_pending_md_syncs(&self) -> Result<Vec<PendingMdSync>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, task_id, fields FROM md_sync_journal ORDER BY id")?;
+        let rows = stmt.query_map([], |row| {
+            let fields: String = row.get(2)?;
+            Ok(PendingMdSync {
+                id: row.get(0)?,
+                task_id: row.get(1)?,
+                fields: serde_json::from_str(&fields).unwrap_or_default(),
+            })
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    // Queue a daily log entry whose note could not be written
+    pub fn queue_daily_entry(&self, day: &str, entry: &str) -> Result<(), ApiError> {
+        self.conn.execute(
+            "INSERT INTO daily_log_queue (day, entry, created_at) VALUES (?, ?, ?)",
+            params![day, entry, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    pub fn clear_daily_entry(&self, id: i64) -> Result<(), ApiError> {
+        self.conn
+            .execute("DELETE FROM daily_log_queue WHERE id = ?", [id])?;
+        Ok(())
+    }
+
+    // Queued daily log entries, oldest first
+    pub fn list_queued_daily_entries(&self) -> Result<Vec<QueuedDailyEntry>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, day, entry FROM daily_log_queue ORDER BY id")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(QueuedDailyEntry {
+                id: row.get(0)?,
+                day: row.get(1)?,
+                entry: row.get(2)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
+    // Record an applied op_id and prune entries older than the retention window
+    pub fn record_op(&self, op_id: &str, kind: &str, task_id: &str) -> Result<(), ApiError> {
+        let now = Utc::now();
+        let cutoff = (now - chrono::Duration::days(OP_RETENTION_DAYS)).to_rfc3339();
+
+        self.conn.execute(
+            "INSERT OR IGNORE INTO task_ops (op_id, kind, task_id, created_at) VALUES (?, ?, ?, ?)",
+            params![op_id, kind, task_id, now.to_rfc3339()],
+        )?;
+        self.conn
+            .execute("DELETE FROM task_ops WHERE created_at < ?", [cutoff])?;
+
+        Ok(())
+    }
+
+    // Get UI state for a vault
+    #[allow(dead_code)]
+    pub fn get_ui_state(&self, vault_id: &str) -> Result<Option<String>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT state_json FROM ui_state WHERE vault_id = ?")?;
+        let result = stmt.query_row([vault_id], |row| row.get(0)).optional()?;
+
+        Ok(result)
+    }
+
+    // Set UI state for a vault (merge with existing state if it exists)
+    #[allow(dead_code)]
+    pub fn set_ui_state(&self, vault_id: &str, partial_state_json: &str) -> Result<(), ApiError> {
+        let now = Utc::now().to_rfc3339();
+
+        // Get existing state if it exists
+        let existing_state_json = self.get_ui_state(vault_id)?;
+
+        // Merge partial state with existing state
+        let merged_state_json = match existing_state_json {
+            Some(existing) => {
+                // Parse existing and partial states
+                let existing_state: serde_json::Value = serde_json::from_str(&existing)?;
+                let partial_state: serde_json::Value = serde_json::from_str(partial_state_json)?;
+
+                // Merge partial into existing (partial takes precedence)
+                let merged_state = merge_json(existing_state, partial_state);
+
+                // Serialize back to string
+                serde_json::to_string(&merged_state)?
+            }
+            None => {
+                // No existing state, use partial as full state
+                partial_state_json.to_string()
+            }
+        };
+
+        // Upsert into database
+        self.conn.execute(
+            r#"INSERT INTO ui_state (vault_id, state_json, updated_at)
+               VALUES (?, ?, ?)
+               ON CONFLICT(vault_id) DO UPDATE SET
+               state_json = excluded.state_json,
+               updated_at = excluded.updated_at"#,
+            params![vault_id, merged_state_json, now],
+        )?;
+
+        Ok(())
+    }
+
+    // Replace one top-level key of a vault's UI state; unlike set_ui_state nothing is merged,
+    // so entries missing from `value` are dropped
+    pub fn replace_ui_state_key(
+        &self,
+        vault_id: &str,
+        key: &str,
+        value: serde_json::Value,
+    ) -> Result<(), ApiError> {
+        let mut state = match self.get_ui_state(vault_id)? {
+            Some(existing) => serde_json::from_str::<serde_json::Value>(&existing)?,
+            None => serde_json::json!({}),
+        };
+        if !state.is_object() {
+            state = serde_json::json!({});
+        }
+        if let Some(map) = state.as_object_mut() {
+            map.insert(key.to_string(), value);
+        }
+
+        self.conn.execute(
+            r#"INSERT INTO ui_state (vault_id, state_json, updated_at)
+               VALUES (?, ?, ?)
+               ON CONFLICT(vault_id) DO UPDATE SET
+               state_json = excluded.state_json,
+               updated_at = excluded.updated_at"#,
+            params![
+                vault_id,
+                serde_json::to_string(&state)?,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        Ok(())
+    }
+
+    // Get or generate vault_id for this vault
+    pub fn ensure_vault_id(&self, vault_root: &std::path::Path) -> Result<String, ApiError> {
+        let ids = self.get_vault_meta_from_db()?;
+        let db_vault_id = ids.0;
+        let db_created_at = ids.1;
+
+        let meta_path = vault_meta_path(vault_root, &self.layout);
+        let file_meta = if meta_path.exists() {
+            let content = std::fs::read_to_string(&meta_path).map_err(|e| ApiError {
+                code: ErrorCode::IoError,
+                message: format!("Failed to read vault.json: {}", e),
+                details: None,
+            })?;
+            serde_json::from_str::<VaultMeta>(&content).ok()
+        } else {
+            None
+        };
+
+        let (vault_id, created_at) = match (db_vault_id, file_meta) {
+            (Some(id), Some(meta)) => {
+                if id != meta.vault_id {
+                    info!(target: "planning", "Vault ID mismatch! DB: {}, File: {}", id, meta.vault_id);
+                    // For now, trust DB as source of truth for the app
+                }
+                (id, meta.created_at) // Use DB id, file time? Or DB time?
+            }
+            (Some(id), None) => {
+                // DB has ID, file missing -> sync to file
+                let created_at = db_created_at.unwrap_or_else(|| Utc::now().to_rfc3339());
+                self.write_vault_meta_file(&meta_path, &id, &created_at)?;
+                (id, created_at)
+            }
+            (None, Some(meta)) => {
+                // DB missing, file has ID -> restore to DB
+                self.store_vault_meta_to_db(&meta.vault_id, &meta.created_at)?;
+                (meta.vault_id, meta.created_at)
+            }
+            (None, None) => {
+                // Both missing -> new vault
+                let id = Uuid::new_v4().to_string();
+                let now = Utc::now().to_rfc3339();
+                self.store_vault_meta_to_db(&id, &now)?;
+                self.write_vault_meta_file(&meta_path, &id, &now)?;
+                (id, now)
+            }
+        };
+
+        Ok(vault_id)
+    }
+
+    fn get_vault_meta_from_db(&self) -> Result<(Option<String>, Option<String>), ApiError> {
+        let vault_id: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT value FROM vault_meta WHERE key = 'vault_id'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        let created_at: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT value FROM vault_meta WHERE key = 'created_at'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok((vault_id, created_at))
+    }
+
+    fn store_vault_meta_to_db(&self, vault_id: &str, created_at: &str) -> Result<(), ApiError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO vault_meta (key, value) VALUES ('vault_id', ?)",
+            params![vault_id],
+        )?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO vault_meta (key, value) VALUES ('created_at', ?)",
+            params![created_at],
+        )?;
+        Ok(())
+    }
+
+    fn write_vault_meta_file(
+        &self,
+        path: &std::path::Path,
+        vault_id: &str,
+        created_at: &str,
+    ) -> Result<(), ApiError> {
+        let meta = VaultMeta {
+            vault_id: vault_id.to_string(),
+            created_at: created_at.to_string(),
+            schema_version: 1,
+        };
+        let content = serde_json::to_string_pretty(&meta)?;
+        std::fs::write(path, content).map_err(|e| ApiError {
+            code: ErrorCode::IoError,
+            message: format!("Failed to write vault.json: {}", e),
+            details: None,
+        })
+    }
+
+    // Perform WAL checkpoint to reduce wal file size
+    pub fn checkpoint(&self) -> Result<(), ApiError> {
+        self.conn
+            .execute("PRAGMA wal_checkpoint(TRUNCATE)", [])
+            .map_err(|e| ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to checkpoint WAL: {}", e),
+                details: None,
+            })?;
+        Ok(())
+    }
+
+    // Cached card preview; not an edit of the task, so updated_at stays
+    pub fn set_task_preview(
+        &self,
+        task_id: &str,
+        cover_image: Option<&str>,
+        excerpt: Option<&str>,
+    ) -> Result<(), ApiError> {
+        self.conn.execute(
+            "UPDATE tasks SET cover_image = ?, excerpt = ? WHERE id = ?",
+            params![cover_image, excerpt, task_id],
+        )?;
+        Ok(())
+    }
+
+    // Completion time carried over from an imported history; updated_at stays
+    pub fn set_task_completed_at(&self, task_id: &str, completed_at: &str) -> Result<(), ApiError> {
+        self.conn.execute(
+            "UPDATE tasks SET completed_at = ? WHERE id = ? AND status = 'done'",
+            params![completed_at, task_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn find_task_by_md_path(&self, md_rel_path: &str) -> Result<Option<Task>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM tasks WHERE md_rel_path = ? LIMIT 1")?;
+        let task = stmt
+            .query_row([md_rel_path], |row| {
+                task_from_row(row, self.cipher.as_ref())
+            })
+            .optional()?;
+        Ok(task)
+    }
+
+    // Update task's markdown relative path and slug
+    pub fn update_task_path_info(
+        &self,
+        task_id: &str,
+        slug: &str,
+        md_rel_path: &str,
+    ) -> Result<(), ApiError> {
+        let now = Utc::now().to_rfc3339();
+
+        self.conn.execute(
+            "UPDATE tasks SET task_dir_slug = ?, md_rel_path = ?, updated_at = ? WHERE id = ?",
+            params![slug, md_rel_path, now, task_id],
+        )?;
+
+        Ok(())
+    }
+
+    // Rewrite stored markdown paths after a layout change without touching updated_at
+    pub fn rewrite_md_rel_paths(&self, paths: &[(String, String)]) -> Result<(), ApiError> {
+        // Run inside in_transaction by the caller, which commits it with the layout change
+        for (task_id, md_rel_path) in paths {
+            self.conn.execute(
+                "UPDATE tasks SET md_rel_path = ? WHERE id = ?",
+                params![md_rel_path, task_id],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn list_github_board_links(&self) -> Result<Vec<GithubBoardLink>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM github_board_links ORDER BY board_id")?;
+        let link_iter = stmt.query_map([], github_board_link_from_row)?;
+
+        let mut links = Vec::new();
+        for link in link_iter {
+            links.push(link?);
+        }
+
+        Ok(links)
+    }
+
+    // Link a board to a repository, or unlink it when repo is None
+    pub fn set_github_board_link(
+        &self,
+        board_id: &str,
+        repo: Option<&str>,
+    ) -> Result<(), ApiError> {
+        match repo {
+            // Switching repositories starts the next sync from scratch
+            Some(repo) => self.conn.execute(
+                r#"INSERT INTO github_board_links (board_id, repo, last_synced_at) VALUES (?, ?, NULL)
+                   ON CONFLICT(board_id) DO UPDATE SET repo = excluded.repo,
+                   last_synced_at = CASE WHEN repo = excluded.repo THEN last_synced_at ELSE NULL END"#,
+                params![board_id, repo],
+            )?,
+            None => self.conn.execute(
+                "DELETE FROM github_board_links WHERE board_id = ?",
+                params![board_id],
+            )?,
+        };
+        Ok(())
+    }
+
+    pub fn set_github_sync_cursor(&self, board_id: &str, synced_at: &str) -> Result<(), ApiError> {
+        self.conn.execute(
+            "UPDATE github_board_links SET last_synced_at = ? WHERE board_id = ?",
+            params![synced_at, board_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_github_issue_link(
+        &self,
+        repo: &str,
+        issue_number: i64,
+    ) -> Result<Option<GithubIssueLink>, ApiError> {
+        let link = self
+            .conn
+            .query_row(
+                "SELECT * FROM github_issue_links WHERE repo = ? AND issue_number = ?",
+                params![repo, issue_number],
+                github_issue_link_from_row,
+            )
+            .optional()?;
+        Ok(link)
+    }
+
+    pub fn list_github_issue_links(&self, repo: &str) -> Result<Vec<GithubIssueLink>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM github_issue_links WHERE repo = ? ORDER BY issue_number")?;
+        let link_iter = stmt.query_map([repo], github_issue_link_from_row)?;
+
+        let mut links = Vec::new();
+        for link in link_iter {
+            links.push(link?);
+        }
+
+        Ok(links)
+    }
+
+    pub fn save_github_issue_link(&self, link: &GithubIssueLink) -> Result<(), ApiError> {
+        let labels = serde_json::to_string(&link.synced_labels)?;
+        self.conn.execute(
+            r#"INSERT INTO github_issue_links (task_id, repo, issue_number, synced_closed, synced_labels, synced_at)
+               VALUES (?, ?, ?, ?, ?, ?)
+               ON CONFLICT(task_id) DO UPDATE SET repo = excluded.repo, issue_number = excluded.issue_number,
+               synced_closed = excluded.synced_closed, synced_labels = excluded.synced_labels,
+               synced_at = excluded.synced_at"#,
+            params![
+                link.task_id,
+                link.repo,
+                link.issue_number,
+                link.synced_closed,
+                labels,
+                link.synced_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    // Record a webhook delivery, keeping only the most recent entries
+    pub fn record_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<(), ApiError> {
+        let transaction = self.conn.unchecked_transaction()?;
+        transaction.execute(
+            r#"INSERT INTO webhook_deliveries (id, webhook_id, event, task_id, status_code, attempts, success, error, delivered_at)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+            params![
+                delivery.id,
+                delivery.webhook_id,
+                delivery.event,
+                delivery.task_id,
+                delivery.status_code,
+                delivery.attempts,
+                delivery.success,
+                delivery.error,
+                delivery.delivered_at
+            ],
+        )?;
+        transaction.execute(
+            r#"DELETE FROM webhook_deliveries WHERE id NOT IN
+               (SELECT id FROM webhook_deliveries ORDER BY delivered_at DESC LIMIT ?)"#,
+            params![WEBHOOK_DELIVERY_RETENTION],
+        )?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    pub fn list_webhook_deliveries(&self, limit: i64) -> Result<Vec<WebhookDelivery>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM webhook_deliveries ORDER BY delivered_at DESC LIMIT ?")?;
+        let delivery_iter = stmt.query_map([limit], webhook_delivery_from_row)?;
+
+        let mut deliveries = Vec::new();
+        for delivery in delivery_iter {
+            deliveries.push(delivery?);
+        }
+
+        Ok(deliveries)
+    }
+
+    // Record a page visit. Repeated state events for the page the webview is already on update
+    // that visit (the title arrives after the URL) instead of adding another.
+    pub fn record_webview_visit(
+        &self,
+        vault_id: &str,
+        entry: &WebviewHistoryEntry,
+    ) -> Result<(), ApiError> {
+        let transaction = self.conn.unchecked_transaction()?;
+        let last: Option<(i64, String)> = transaction
+            .query_row(
+                r#"SELECT id, url FROM webview_history WHERE vault_id = ? AND label = ?
+                   ORDER BY id DESC LIMIT 1"#,
+                params![vault_id, entry.label],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        match last {
+            Some((id, url)) if url == entry.url => {
+                transaction.execute(
+                    "UPDATE webview_history SET title = COALESCE(?, title), visited_at = ? WHERE id = ?",
+                    params![entry.title, entry.visited_at, id],
+                )?;
+            }
+            _ => {
+                transaction.execute(
+                    r#"INSERT INTO webview_history (vault_id, label, url, title, visited_at)
+                       VALUES (?, ?, ?, ?, ?)"#,
+                    params![
+                        vault_id,
+                        entry.label,
+                        entry.url,
+                        entry.title,
+                        entry.visited_at
+                    ],
+                )?;
+                transaction.execute(
+                    r#"DELETE FROM webview_history WHERE vault_id = ?1 AND label = ?2 AND id NOT IN
+                       (SELECT id FROM webview_history WHERE vault_id = ?1 AND label = ?2
+                        ORDER BY id DESC LIMIT ?3)"#,
+                    params![vault_id, entry.label, WEBVIEW_HISTORY_RETENTION],
+                )?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    // Visits of one webview, or of all webviews of the vault, newest first
+    pub fn list_webview_history(
+        &self,
+        vault_id: &str,
+        label: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<WebviewHistoryEntry>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT * FROM webview_history WHERE vault_id = ?1 AND (?2 IS NULL OR label = ?2)
+               ORDER BY id DESC LIMIT ?3"#,
+        )?;
+        let entry_iter = stmt.query_map(params![vault_id, label, limit], |row| {
+            webview_history_entry_from_row(row)
+        })?;
+
+        let mut entries = Vec::new();
+        for entry in entry_iter {
+            entries.push(entry?);
+        }
+
+        Ok(entries)
+    }
+
+    // Most recent title seen for a URL in any webview of the vault
+    pub fn latest_webview_title(
+        &self,
+        vault_id: &str,
+        url: &str,
+    ) -> Result<Option<String>, ApiError> {
+        let title = self
+            .conn
+            .query_row(
+                r#"SELECT title FROM webview_history
+                   WHERE vault_id = ? AND url = ? AND title IS NOT NULL
+                   ORDER BY id DESC LIMIT 1"#,
+                params![vault_id, url],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(title)
+    }
+
+    // Drop the history of a webview whose session was closed
+    pub fn clear_webview_history(&self, vault_id: &str, label: &str) -> Result<(), ApiError> {
+        self.conn.execute(
+            "DELETE FROM webview_history WHERE vault_id = ? AND label = ?",
+            params![vault_id, label],
+        )?;
+        Ok(())
+    }
+
+    pub fn insert_reading_item(&self, item: &ReadingItem) -> Result<(), ApiError> {
+        self.conn.execute(
+            r#"INSERT INTO reading_list (id, url, title, status, added_at, read_at, note_path, task_id, source_label)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+            params![
+                item.id,
+                item.url,
+                item.title,
+                item.status.as_str(),
+                item.added_at,
+                item.read_at,
+                item.note_path,
+                item.task_id,
+                item.source_label
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn update_reading_item(&self, item: &ReadingItem) -> Result<(), ApiError> {
+        self.conn.execute(
+            r#"UPDATE reading_list SET title = ?, status = ?, read_at = ?, note_path = ?, task_id = ?
+               WHERE id = ?"#,
+            params![
+                item.title,
+                item.status.as_str(),
+                item.read_at,
+                item.note_path,
+                item.task_id,
+                item.id
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_reading_item(&self, id: &str) -> Result<Option<ReadingItem>, ApiError> {
+        let item = self
+            .conn
+            .query_row("SELECT * FROM reading_list WHERE id = ?", [id], |row| {
+                reading_item_from_row(row)
+            })
+            .optional()?;
+        Ok(item)
+    }
+
+    pub fn find_reading_item_by_url(&self, url: &str) -> Result<Option<ReadingItem>, ApiError> {
+        let item = self
+            .conn
+            .query_row("SELECT * FROM reading_list WHERE url = ?", [url], |row| {
+                reading_item_from_row(row)
+            })
+            .optional()?;
+        Ok(item)
+    }
+
+    // Reading list, newest first, optionally only items with the given status
+    pub fn list_reading_items(
+        &self,
+        status: Option<ReadingStatus>,
+    ) -> Result<Vec<ReadingItem>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT * FROM reading_list WHERE ?1 IS NULL OR status = ?1
+               ORDER BY added_at DESC"#,
+        )?;
+        let item_iter = stmt.query_map([status.map(|status| status.as_str())], |row| {
+            reading_item_from_row(row)
+        })?;
+
+        let mut items = Vec::new();
+        for item in item_iter {
+            items.push(item?);
+        }
+
+        Ok(items)
+    }
+
+    // Returns whether an item was removed
+    pub fn delete_reading_item(&self, id: &str) -> Result<bool, ApiError> {
+        let deleted = self
+            .conn
+            .execute("DELETE FROM reading_list WHERE id = ?", [id])?;
+        Ok(deleted > 0)
+    }
+
+    pub fn insert_reminder(&self, reminder: &Reminder) -> Result<(), ApiError> {
+        self.conn.execute(
+            r#"INSERT INTO task_reminders (id, task_id, remind_at, repeat, fired_at, created_at, updated_at)
+               VALUES (?, ?, ?, ?, ?, ?, ?)"#,
+            params![
+                reminder.id,
+                reminder.task_id,
+                reminder.remind_at,
+                reminder.repeat.map(|repeat| repeat.as_str()),
+                reminder.fired_at,
+                reminder.created_at,
+                reminder.updated_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn update_reminder(&self, reminder: &Reminder) -> Result<(), ApiError> {
+        self.conn.execute(
+            r#"UPDATE task_reminders SET remind_at = ?, repeat = ?, fired_at = ?, updated_at = ?
+               WHERE id = ?"#,
+            params![
+                reminder.remind_at,
+                reminder.repeat.map(|repeat| repeat.as_str()),
+                reminder.fired_at,
+                reminder.updated_at,
+                reminder.id
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_reminder(&self, id: &str) -> Result<Option<Reminder>, ApiError> {
+        let reminder = self
+            .conn
+            .query_row("SELECT * FROM task_reminders WHERE id = ?", [id], |row| {
+                reminder_from_row(row)
+            })
+            .optional()?;
+        Ok(reminder)
+    }
+
+    // Reminders of a task, or of every task, soonest first
+    pub fn list_reminders(&self, task_id: Option<&str>) -> Result<Vec<Reminder>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT * FROM task_reminders WHERE ?1 IS NULL OR task_id = ?1
+               ORDER BY remind_at"#,
+        )?;
+        let reminder_iter = stmt.query_map([task_id], reminder_from_row)?;
+
+        let mut reminders = Vec::new();
+        for reminder in reminder_iter {
+            reminders.push(reminder?);
+        }
+
+        Ok(reminders)
+    }
+
+    // Reminders that have not gone off yet and are due at `now`
+    pub fn list_due_reminders(&self, now: &str) -> Result<Vec<Reminder>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT * FROM task_reminders WHERE fired_at IS NULL AND remind_at <= ?
+               ORDER BY remind_at"#,
+        )?;
+        let reminder_iter = stmt.query_map([now], reminder_from_row)?;
+
+        let mut reminders = Vec::new();
+        for reminder in reminder_iter {
+            reminders.push(reminder?);
+        }
+
+        Ok(reminders)
+    }
+
+    // Returns whether a reminder was removed
+    pub fn delete_reminder(&self, id: &str) -> Result<bool, ApiError> {
+        let deleted = self
+            .conn
+            .execute("DELETE FROM task_reminders WHERE id = ?", [id])?;
+        Ok(deleted > 0)
+    }
+
+    // Record a delivered occurrence; false if that occurrence was recorded before
+    pub fn insert_notification(&self, notification: &Notification) -> Result<bool, ApiError> {
+        let inserted = self.conn.execute(
+            r#"INSERT OR IGNORE INTO notifications (
+                id, kind, source_id, occurrence_at, task_id, title, state, snoozed_until, delivered_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+            params![
+                notification.id,
+                notification.kind.as_str(),
+                notification.source_id,
+                notification.occurrence_at,
+                notification.task_id,
+                notification.title,
+                notification.state.as_str(),
+                notification.snoozed_until,
+                notification.delivered_at,
+                notification.updated_at
+            ],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    pub fn update_notification(&self, notification: &Notification) -> Result<(), ApiError> {
+        self.conn.execute(
+            r#"UPDATE notifications SET state = ?, snoozed_until = ?, delivered_at = ?, updated_at = ?
+               WHERE id = ?"#,
+            params![
+                notification.state.as_str(),
+                notification.snoozed_until,
+                notification.delivered_at,
+                notification.updated_at,
+                notification.id
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_notification(&self, id: &str) -> Result<Option<Notification>, ApiError> {
+        let notification = self
+            .conn
+            .query_row("SELECT * FROM notifications WHERE id = ?", [id], |row| {
+                notification_from_row(row)
+            })
+            .optional()?;
+        Ok(notification)
+    }
+
+    // Notifications, most recently delivered first; dismissed ones only when asked for
+    pub fn list_notifications(
+        &self,
+        include_dismissed: bool,
+    ) -> Result<Vec<Notification>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT * FROM notifications WHERE ?1 OR state != 'dismissed'
+               ORDER BY delivered_at DESC"#,
+        )?;
+        let notification_iter = stmt.query_map([include_dismissed], notification_from_row)?;
+
+        let mut notifications = Vec::new();
+        for notification in notification_iter {
+            notifications.push(notification?);
+        }
+
+        Ok(notifications)
+    }
+
+    // Snoozed notifications whose snooze ended by `now`
+    pub fn list_expired_snoozes(&self, now: &str) -> Result<Vec<Notification>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT * FROM notifications WHERE state = 'snoozed' AND snoozed_until <= ?
+               ORDER BY snoozed_until"#,
+        )?;
+        let notification_iter = stmt.query_map([now], notification_from_row)?;
+
+        let mut notifications = Vec::new();
+        for notification in notification_iter {
+            notifications.push(notification?);
+        }
+
+        Ok(notifications)
+    }
+
+    // Forget dismissed notifications last touched before `before`; returns how many went
+    pub fn prune_dismissed_notifications(&self, before: &str) -> Result<usize, ApiError> {
+        let deleted = self.conn.execute(
+            "DELETE FROM notifications WHERE state = 'dismissed' AND updated_at < ?",
+            [before],
+        )?;
+        Ok(deleted)
+    }
+
+    // Append a favorite at the end of the list; false if it was already a favorite
+    pub fn add_favorite(
+        &self,
+        vault_id: &str,
+        favorite: &FavoriteRef,
+        added_at: &str,
+    ) -> Result<bool, ApiError> {
+        let inserted = self.conn.execute(
+            r#"INSERT OR IGNORE INTO favorites (vault_id, kind, target, order_index, added_at)
+               VALUES (?1, ?2, ?3,
+                       (SELECT COALESCE(MAX(order_index) + 1, 0) FROM favorites WHERE vault_id = ?1),
+                       ?4)"#,
+            params![vault_id, favorite.kind.as_str(), favorite.target, added_at],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    // Returns whether a favorite was removed
+    pub fn remove_favorite(
+        &self,
+        vault_id: &str,
+        favorite: &FavoriteRef,
+    ) -> Result<bool, ApiError> {
+        let deleted = self.conn.execute(
+            "DELETE FROM favorites WHERE vault_id = ? AND kind = ? AND target = ?",
+            params![vault_id, favorite.kind.as_str(), favorite.target],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    pub fn list_favorites(&self, vault_id: &str) -> Result<Vec<Favorite>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM favorites WHERE vault_id = ? ORDER BY order_index, added_at")?;
+        let favorite_iter = stmt.query_map([vault_id], favorite_from_row)?;
+
+        let mut favorites = Vec::new();
+        for favorite in favorite_iter {
+            favorites.push(favorite?);
+        }
+
+        Ok(favorites)
+    }
+
+    // Number the given favorites in order
+    pub fn set_favorite_order(
+        &self,
+        vault_id: &str,
+        order: &[FavoriteRef],
+    ) -> Result<(), ApiError> {
+        let transaction = self.conn.unchecked_transaction()?;
+        for (index, favorite) in order.iter().enumerate() {
+            transaction.execute(
+                "UPDATE favorites SET order_index = ? WHERE vault_id = ? AND kind = ? AND target = ?",
+                params![index as i64, vault_id, favorite.kind.as_str(), favorite.target],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    // Move a note to the top of the recent notes. Without a cursor the one saved last time is
+    // kept, so reopening a note still resumes where it was left.
+    pub fn record_note_opened(
+        &self,
+        vault_id: &str,
+        path: &str,
+        cursor: Option<NoteCursor>,
+        opened_at: &str,
+    ) -> Result<(), ApiError> {
+        let transaction = self.conn.unchecked_transaction()?;
+        transaction.execute(
+            r#"INSERT INTO recent_notes (vault_id, path, opened_at, cursor_line, cursor_column, scroll_top)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+               ON CONFLICT(vault_id, path) DO UPDATE SET
+               opened_at = excluded.opened_at,
+               cursor_line = CASE WHEN ?4 IS NULL THEN cursor_line ELSE excluded.cursor_line END,
+               cursor_column = CASE WHEN ?4 IS NULL THEN cursor_column ELSE excluded.cursor_column END,
+               scroll_top = CASE WHEN ?4 IS NULL THEN scroll_top ELSE excluded.scroll_top END"#,
+            params![
+                vault_id,
+                path,
+                opened_at,
+                cursor.map(|cursor| cursor.line),
+                cursor.map(|cursor| cursor.column),
+                cursor.and_then(|cursor| cursor.scroll_top)
+            ],
+        )?;
+        transaction.execute(
+            r#"DELETE FROM recent_notes WHERE vault_id = ?1 AND path NOT IN
+               (SELECT path FROM recent_notes WHERE vault_id = ?1
+                ORDER BY opened_at DESC LIMIT ?2)"#,
+            params![vault_id, RECENT_NOTES_RETENTION],
+        )?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    // Recently opened notes, most recent first
+    pub fn list_recent_notes(
+        &self,
+        vault_id: &str,
+        limit: i64,
+    ) -> Result<Vec<RecentNote>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT * FROM recent_notes WHERE vault_id = ? ORDER BY opened_at DESC LIMIT ?",
+        )?;
+        let note_iter = stmt.query_map(params![vault_id, limit], recent_note_from_row)?;
+
+        let mut notes = Vec::new();
+        for note in note_iter {
+            notes.push(note?);
+        }
+
+        Ok(notes)
+    }
+
+    // Remember an overdue notification; false if one was already sent for this due date
+    pub fn mark_overdue_sent(&self, task_id: &str, due_date: &str) -> Result<bool, ApiError> {
+        let inserted = self.conn.execute(
+            "INSERT OR IGNORE INTO webhook_overdue_sent (task_id, due_date, sent_at) VALUES (?, ?, ?)",
+            params![task_id, due_date, Utc::now().to_rfc3339()],
+        )?;
+        Ok(inserted > 0)
+    }
 
-        let created_at: Option<String> = self
+    // Cached AI reply that has not expired by `now` (RFC 3339)
+    pub fn get_ai_cache(
+        &self,
+        model: &str,
+        prompt_hash: &str,
+        now: &str,
+    ) -> Result<Option<String>, ApiError> {
+        let content = self
             .conn
             .query_row(
-                "SELECT value FROM vault_meta WHERE key = 'created_at'",
-                [],
+                "SELECT content FROM ai_cache WHERE model = ? AND prompt_hash = ? AND expires_at > ?",
+                params![model, prompt_hash, now],
                 |row| row.get(0),
             )
             .optional()?;
-
-        Ok((vault_id, created_at))
+        Ok(content)
     }
 
-    fn store_vault_meta_to_db(&self, vault_id: &str, created_at: &str) -> Result<(), ApiError> {
-        self.conn.execute(
-            "INSERT OR REPLACE INTO vault_meta (key, value) VALUES ('vault_id', ?)",
-            params![vault_id],
+    // Store an AI reply, dropping entries that have expired meanwhile
+    pub fn put_ai_cache(
+        &self,
+        model: &str,
+        prompt_hash: &str,
+        content: &str,
+        now: &str,
+        expires_at: &str,
+    ) -> Result<(), ApiError> {
+        let transaction = self.conn.unchecked_transaction()?;
+        transaction.execute(
+            r#"INSERT OR REPLACE INTO ai_cache (model, prompt_hash, content, created_at, expires_at)
+               VALUES (?, ?, ?, ?, ?)"#,
+            params![model, prompt_hash, content, now, expires_at],
         )?;
+        transaction.execute("DELETE FROM ai_cache WHERE expires_at <= ?", params![now])?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    // Returns the number of entries removed
+    pub fn clear_ai_cache(&self) -> Result<usize, ApiError> {
+        Ok(self.conn.execute("DELETE FROM ai_cache", [])?)
+    }
+
+    // Indexed frontmatter by note path: mtime and the frontmatter as JSON
+    pub fn list_note_frontmatter(&self) -> Result<HashMap<String, (i64, String)>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, mtime, data FROM note_frontmatter")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?)))
+        })?;
+        let mut index = HashMap::new();
+        for row in rows {
+            let (path, entry) = row?;
+            index.insert(path, entry);
+        }
+        Ok(index)
+    }
+
+    pub fn upsert_note_frontmatter(
+        &self,
+        path: &str,
+        mtime: i64,
+        data: &str,
+    ) -> Result<(), ApiError> {
         self.conn.execute(
-            "INSERT OR REPLACE INTO vault_meta (key, value) VALUES ('created_at', ?)",
-            params![created_at],
+            "INSERT OR REPLACE INTO note_frontmatter (path, mtime, data) VALUES (?, ?, ?)",
+            params![path, mtime, data],
         )?;
         Ok(())
     }
 
-    fn write_vault_meta_file(
+    pub fn delete_note_frontmatter(&self, path: &str) -> Result<(), ApiError> {
+        self.conn
+            .execute("DELETE FROM note_frontmatter WHERE path = ?", params![path])?;
+        Ok(())
+    }
+
+    // Indexed notes with the content hash and model they were embedded with
+    pub fn list_note_index(&self) -> Result<HashMap<String, (String, String)>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, content_hash, model FROM note_index")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?)))
+        })?;
+        let mut index = HashMap::new();
+        for row in rows {
+            let (path, entry) = row?;
+            index.insert(path, entry);
+        }
+        Ok(index)
+    }
+
+    // Replace every chunk of a note in one transaction
+    pub fn replace_note_chunks(
         &self,
-        path: &std::path::Path,
-        vault_id: &str,
-        created_at: &str,
+        path: &str,
+        content_hash: &str,
+        model: &str,
+        chunks: &[(NoteChunk, Vec<f32>)],
     ) -> Result<(), ApiError> {
-        let meta = VaultMeta {
-            vault_id: vault_id.to_string(),
-            created_at: created_at.to_string(),
-            schema_version: 1,
-        };
-        let content = serde_json::to_string_pretty(&meta)?;
-        std::fs::write(path, content).map_err(|e| ApiError {
-            code: "IOError".to_string(),
-            message: format!("Failed to write vault.json: {}", e),
-            details: None,
-        })
+        let transaction = self.conn.unchecked_transaction()?;
+        transaction.execute("DELETE FROM note_chunks WHERE path = ?", params![path])?;
+        transaction.execute("DELETE FROM note_chunk_fts WHERE path = ?", params![path])?;
+        for (chunk, embedding) in chunks {
+            let blob: Vec<u8> = embedding
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect();
+            transaction.execute(
+                r#"INSERT INTO note_chunks (path, chunk_index, heading, anchor, start_offset, end_offset, text, embedding)
+                   VALUES (?, ?, ?, ?, ?, ?, ?, ?)"#,
+                params![
+                    path,
+                    chunk.index as i64,
+                    chunk.heading,
+                    chunk.anchor,
+                    chunk.start as i64,
+                    chunk.end as i64,
+                    chunk.text,
+                    blob
+                ],
+            )?;
+            transaction.execute(
+                "INSERT INTO note_chunk_fts (path, chunk_index, heading, text) VALUES (?, ?, ?, ?)",
+                params![path, chunk.index as i64, chunk.heading, chunk.text],
+            )?;
+        }
+        transaction.execute(
+            r#"INSERT OR REPLACE INTO note_index (path, content_hash, model, indexed_at)
+               VALUES (?, ?, ?, ?)"#,
+            params![path, content_hash, model, Utc::now().to_rfc3339()],
+        )?;
+        transaction.commit()?;
+        Ok(())
     }
 
-    // Perform WAL checkpoint to reduce wal file size
-    pub fn checkpoint(&self) -> Result<(), ApiError> {
-        self.conn
-            .execute("PRAGMA wal_checkpoint(TRUNCATE)", [])
-            .map_err(|e| ApiError {
-                code: "DatabaseError".to_string(),
-                message: format!("Failed to checkpoint WAL: {}", e),
-                details: None,
-            })?;
+    // Every chunk embedded with the given model
+    pub fn list_note_chunks(&self, model: &str) -> Result<Vec<IndexedChunk>, ApiError> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT c.* FROM note_chunks c JOIN note_index i ON i.path = c.path
+               WHERE i.model = ? ORDER BY c.path, c.chunk_index"#,
+        )?;
+        let rows = stmt.query_map([model], |row| {
+            let blob: Vec<u8> = row.get("embedding")?;
+            Ok(IndexedChunk {
+                path: row.get("path")?,
+                chunk: note_chunk_from_row(row)?,
+                embedding: blob
+                    .chunks_exact(4)
+                    .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                    .collect(),
+            })
+        })?;
+        let mut chunks = Vec::new();
+        for chunk in rows {
+            chunks.push(chunk?);
+        }
+        Ok(chunks)
+    }
+
+    // Ranked full-text search over indexed chunks returning (path, chunk, score, snippet)
+    pub fn search_note_chunk_fts(
+        &self,
+        match_expr: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, NoteChunk, f64, String)>, ApiError> {
+        // Heading matches weigh more than body text
+        let mut stmt = self.conn.prepare(
+            r#"SELECT c.path, c.chunk_index, c.heading, c.anchor, c.start_offset, c.end_offset,
+                      c.text,
+                      -bm25(note_chunk_fts, 0.0, 0.0, 3.0, 1.0) AS score,
+                      snippet(note_chunk_fts, 3, ?, ?, '…', 16) AS snippet
+               FROM note_chunk_fts f
+               JOIN note_chunks c ON c.path = f.path AND c.chunk_index = f.chunk_index
+               WHERE note_chunk_fts MATCH ?
+               ORDER BY score DESC
+               LIMIT ?"#,
+        )?;
+        let rows = stmt.query_map(
+            params![HIGHLIGHT_OPEN, HIGHLIGHT_CLOSE, match_expr, limit as i64],
+            |row| {
+                Ok((
+                    row.get("path")?,
+                    note_chunk_from_row(row)?,
+                    row.get("score")?,
+                    row.get("snippet")?,
+                ))
+            },
+        )?;
+
+        let mut hits = Vec::new();
+        for row in rows {
+            hits.push(row?);
+        }
+        Ok(hits)
+    }
+
+    // Substring search over indexed chunks for terms too short for the trigram index
+    pub fn search_note_chunk_like(
+        &self,
+        terms: &[String],
+        limit: usize,
+    ) -> Result<Vec<(String, NoteChunk)>, ApiError> {
+        let conditions =
+            vec!["(IFNULL(heading, '') || ' ' || text) LIKE ? ESCAPE '\\'"; terms.len()];
+        let sql = format!(
+            "SELECT * FROM note_chunks WHERE {} ORDER BY path, chunk_index LIMIT {}",
+            conditions.join(" AND "),
+            limit
+        );
+        let patterns: Vec<String> = terms
+            .iter()
+            .map(|term| {
+                let escaped = term
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_");
+                format!("%{}%", escaped)
+            })
+            .collect();
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(patterns.iter()), |row| {
+            Ok((row.get("path")?, note_chunk_from_row(row)?))
+        })?;
+
+        let mut hits = Vec::new();
+        for row in rows {
+            hits.push(row?);
+        }
+        Ok(hits)
+    }
+
+    pub fn remove_note_chunks(&self, path: &str) -> Result<(), ApiError> {
+        let transaction = self.conn.unchecked_transaction()?;
+        transaction.execute("DELETE FROM note_chunks WHERE path = ?", params![path])?;
+        transaction.execute("DELETE FROM note_chunk_fts WHERE path = ?", params![path])?;
+        transaction.execute("DELETE FROM note_index WHERE path = ?", params![path])?;
+        transaction.commit()?;
         Ok(())
     }
 
-    // Update task's markdown relative path and slug
-    pub fn update_task_path_info(
+    pub fn list_all_timers(&self) -> Result<Vec<Timer>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM task_timer ORDER BY start_at")?;
+        let timer_iter = stmt.query_map([], timer_from_row)?;
+
+        let mut timers = Vec::new();
+        for timer in timer_iter {
+            timers.push(timer?);
+        }
+
+        Ok(timers)
+    }
+
+    pub fn list_day_logs(&self) -> Result<Vec<DayLog>, ApiError> {
+        let mut stmt = self.conn.prepare("SELECT * FROM day_log ORDER BY day")?;
+        let day_log_iter = stmt.query_map([], |row| {
+            Ok(DayLog {
+                day: row.get("day")?,
+                daily_md_path: row.get("daily_md_path")?,
+                created_at: row.get("created_at")?,
+                updated_at: row.get("updated_at")?,
+            })
+        })?;
+
+        let mut day_logs = Vec::new();
+        for day_log in day_log_iter {
+            day_logs.push(day_log?);
+        }
+
+        Ok(day_logs)
+    }
+
+    pub fn list_all_note_links(&self) -> Result<Vec<TaskNoteLink>, ApiError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM task_note_links ORDER BY task_id, note_path")?;
+        let link_iter = stmt.query_map([], note_link_from_row)?;
+
+        let mut links = Vec::new();
+        for link in link_iter {
+            links.push(link?);
+        }
+
+        Ok(links)
+    }
+
+    // Write an exported dataset in one transaction; rows with the same key are overwritten
+    pub fn import_snapshot(
         &self,
-        task_id: &str,
-        slug: &str,
-        md_rel_path: &str,
+        snapshot: &PlanningExport,
+        replace: bool,
     ) -> Result<(), ApiError> {
+        let transaction = self.conn.unchecked_transaction()?;
+
+        if replace {
+            transaction.execute_batch(
+                r#"DELETE FROM tasks;
+                DELETE FROM task_timer;
+                DELETE FROM day_log;
+                DELETE FROM board_columns;
+                DELETE FROM boards;
+                DELETE FROM task_note_links;
+                DELETE FROM board_fields;
+                DELETE FROM task_fields;
+                DELETE FROM task_reminders;
+                DELETE FROM notifications;
+                DELETE FROM task_fts;
+                DELETE FROM task_fts_meta;"#,
+            )?;
+        }
+
+        for task in &snapshot.tasks {
+            let tags = task.labels.as_ref().or(task.tags.as_ref());
+            transaction.execute(
+                r#"INSERT OR REPLACE INTO tasks (
+                    id, title, description, status, priority, tags, subtasks, periodicity,
+                    due_date, board_id, column_key, order_index, estimate_min, scheduled_start,
+                    scheduled_end, note_path, created_at, updated_at, completed_at, archived,
+                    task_dir_slug, md_rel_path, cover_image, excerpt, color
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+                params![
+                    task.id,
+                    task.title,
+                    self.seal_text(task.description.as_deref())?,
+                    task.status.to_string(),
+                    task.priority.map(|priority| priority.to_string()),
+                    tags.filter(|tags| !tags.is_empty())
+                        .map(serde_json::to_string)
+                        .transpose()?,
+                    task.subtasks
+                        .as_ref()
+                        .filter(|subtasks| !subtasks.is_empty())
+                        .map(serde_json::to_string)
+                        .transpose()?,
+                    task.periodicity
+                        .as_ref()
+                        .map(serde_json::to_string)
+                        .transpose()?,
+                    task.due_date,
+                    task.board_id,
+                    task.column_key,
+                    task.order_index,
+                    task.estimate_min,
+                    task.scheduled_start,
+                    task.scheduled_end,
+                    task.note_path,
+                    task.created_at,
+                    task.updated_at,
+                    task.completed_at,
+                    task.archived,
+                    task.task_dir_slug,
+                    task.md_rel_path,
+                    task.cover_image,
+                    task.excerpt,
+                    task.color
+                ],
+            )?;
+            // Rebuilt from the imported row on the next search
+            transaction.execute("DELETE FROM task_fts WHERE task_id = ?", [&task.id])?;
+            transaction.execute("DELETE FROM task_fts_meta WHERE task_id = ?", [&task.id])?;
+        }
+
+        for timer in &snapshot.timers {
+            transaction.execute(
+                r#"INSERT OR REPLACE INTO task_timer (id, task_id, start_at, stop_at, duration_sec, source)
+                   VALUES (?, ?, ?, ?, ?, ?)"#,
+                params![
+                    timer.id,
+                    timer.task_id,
+                    timer.start_at,
+                    timer.stop_at,
+                    timer.duration_sec,
+                    timer.source
+                ],
+            )?;
+        }
+
+        for day_log in &snapshot.day_logs {
+            transaction.execute(
+                r#"INSERT OR REPLACE INTO day_log (day, daily_md_path, created_at, updated_at)
+                   VALUES (?, ?, ?, ?)"#,
+                params![
+                    day_log.day,
+                    day_log.daily_md_path,
+                    day_log.created_at,
+                    day_log.updated_at
+                ],
+            )?;
+        }
+
+        for column in &snapshot.board_columns {
+            transaction.execute(
+                r#"INSERT OR REPLACE INTO board_columns (board_id, key, name, wip_limit, maps_to, order_index)
+                   VALUES (?, ?, ?, ?, ?, ?)"#,
+                params![
+                    column.board_id,
+                    column.key,
+                    column.name,
+                    column.wip_limit,
+                    column.maps_to.to_string(),
+                    column.order_index
+                ],
+            )?;
+        }
+
+        for meta in &snapshot.boards {
+            transaction.execute(
+                r#"INSERT OR REPLACE INTO boards (board_id, color, updated_at) VALUES (?, ?, ?)"#,
+                params![meta.board_id, meta.color, meta.updated_at],
+            )?;
+        }
+
+        for link in &snapshot.note_links {
+            transaction.execute(
+                r#"INSERT OR REPLACE INTO task_note_links (task_id, note_path, source, created_at)
+                   VALUES (?, ?, ?, ?)"#,
+                params![link.task_id, link.note_path, link.source, link.created_at],
+            )?;
+        }
+
+        for field in &snapshot.board_fields {
+            transaction.execute(
+                r#"INSERT OR REPLACE INTO board_fields (board_id, key, name, kind, options, required, order_index)
+                   VALUES (?, ?, ?, ?, ?, ?, ?)"#,
+                params![
+                    field.board_id,
+                    field.key,
+                    field.name,
+                    field.kind.as_str(),
+                    serde_json::to_string(&field.options).ok(),
+                    field.required,
+                    field.order_index
+                ],
+            )?;
+        }
+
         let now = Utc::now().to_rfc3339();
+        for value in &snapshot.task_fields {
+            transaction.execute(
+                r#"INSERT OR REPLACE INTO task_fields (task_id, key, value, updated_at)
+                   VALUES (?, ?, ?, ?)"#,
+                params![value.task_id, value.key, value.value, now],
+            )?;
+        }
 
-        self.conn.execute(
-            "UPDATE tasks SET task_dir_slug = ?, md_rel_path = ?, updated_at = ? WHERE id = ?",
-            params![slug, md_rel_path, now, task_id],
-        )?;
+        transaction.commit()?;
 
         Ok(())
     }
@@ -1261,7 +4412,7 @@ impl PlanningRepo {
             old_db_path.to_string_lossy()
         );
         self.conn.execute(&attach_sql, []).map_err(|e| ApiError {
-            code: "DatabaseError".to_string(),
+            code: ErrorCode::DatabaseError,
             message: format!("Failed to attach legacy DB: {}", e),
             details: None,
         })?;
@@ -1289,7 +4440,7 @@ impl PlanningRepo {
                 [],
             )
             .map_err(|e| ApiError {
-                code: "DatabaseError".to_string(),
+                code: ErrorCode::DatabaseError,
                 message: format!("Failed to import tasks from legacy DB: {}", e),
                 details: None,
             })?;
@@ -1298,15 +4449,127 @@ impl PlanningRepo {
         self.conn
             .execute("DETACH DATABASE old_db", [])
             .map_err(|e| ApiError {
-                code: "DatabaseError".to_string(),
+                code: ErrorCode::DatabaseError,
                 message: format!("Failed to detach legacy DB: {}", e),
                 details: None,
             })?;
 
+        if let Some(cipher) = &self.cipher {
+            self.rewrite_descriptions(|value| {
+                if db_crypto::is_sealed(value) {
+                    Ok(value.to_string())
+                } else {
+                    cipher.seal(value)
+                }
+            })?;
+        }
+
         Ok(count as i32)
     }
 }
 
+// A task frontmatter sync journaled before the note was written
+pub struct PendingMdSync {
+    pub id: i64,
+    pub task_id: String,
+    pub fields: Vec<String>,
+}
+
+// A daily log entry waiting for its note to be released by the editor
+pub struct QueuedDailyEntry {
+    pub id: i64,
+    pub day: String,
+    pub entry: String,
+}
+
+// Planning rows read from another vault's database
+pub struct ForeignPlanningData {
+    pub tasks: Vec<Task>,
+    pub timers: Vec<Timer>,
+    pub day_logs: Vec<DayLog>,
+    pub task_fields: Vec<TaskFieldValue>,
+}
+
+// Read another vault's planning database without migrating or otherwise modifying it
+pub fn read_foreign_planning_db(
+    db_path: &std::path::Path,
+) -> Result<ForeignPlanningData, ApiError> {
+    let conn =
+        Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(|e| {
+            ApiError {
+                code: ErrorCode::DatabaseError,
+                message: format!("Failed to open database: {}", e),
+                details: Some(serde_json::json!({ "path": db_path.to_string_lossy() })),
+            }
+        })?;
+    conn.pragma_update(None, "busy_timeout", 5000)?;
+
+    let has_table = |name: &str| -> Result<bool, ApiError> {
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?",
+            [name],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    };
+
+    let mut tasks = Vec::new();
+    if has_table("tasks")? {
+        let mut stmt = conn.prepare("SELECT * FROM tasks ORDER BY created_at")?;
+        let task_iter = stmt.query_map([], |row| task_from_row(row, None))?;
+        for task in task_iter {
+            tasks.push(task?);
+        }
+    }
+
+    let mut timers = Vec::new();
+    if has_table("task_timer")? {
+        let mut stmt = conn.prepare("SELECT * FROM task_timer ORDER BY start_at")?;
+        let timer_iter = stmt.query_map([], timer_from_row)?;
+        for timer in timer_iter {
+            timers.push(timer?);
+        }
+    }
+
+    let mut day_logs = Vec::new();
+    if has_table("day_log")? {
+        let mut stmt = conn.prepare("SELECT * FROM day_log ORDER BY day")?;
+        let day_log_iter = stmt.query_map([], |row| {
+            Ok(DayLog {
+                day: row.get("day")?,
+                daily_md_path: row.get("daily_md_path")?,
+                created_at: row.get("created_at")?,
+                updated_at: row.get("updated_at")?,
+            })
+        })?;
+        for day_log in day_log_iter {
+            day_logs.push(day_log?);
+        }
+    }
+
+    let mut task_fields = Vec::new();
+    if has_table("task_fields")? {
+        let mut stmt = conn.prepare("SELECT task_id, key, value FROM task_fields")?;
+        let value_iter = stmt.query_map([], |row| {
+            Ok(TaskFieldValue {
+                task_id: row.get(0)?,
+                key: row.get(1)?,
+                value: row.get(2)?,
+            })
+        })?;
+        for value in value_iter {
+            task_fields.push(value?);
+        }
+    }
+
+    Ok(ForeignPlanningData {
+        tasks,
+        timers,
+        day_logs,
+        task_fields,
+    })
+}
+
 // Helper function to merge two JSON objects
 #[allow(dead_code)]
 fn merge_json(existing: serde_json::Value, partial: serde_json::Value) -> serde_json::Value {
@@ -1382,7 +4645,209 @@ fn parse_periodicity(
     }
 }
 
-fn task_from_row(row: &rusqlite::Row<'_>) -> Result<Task, rusqlite::Error> {
+fn timer_from_row(row: &rusqlite::Row<'_>) -> Result<Timer, rusqlite::Error> {
+    Ok(Timer {
+        id: row.get("id")?,
+        task_id: row.get("task_id")?,
+        start_at: row.get("start_at")?,
+        stop_at: row.get("stop_at")?,
+        duration_sec: row.get("duration_sec")?,
+        source: row.get("source")?,
+    })
+}
+
+fn note_link_from_row(row: &rusqlite::Row<'_>) -> Result<TaskNoteLink, rusqlite::Error> {
+    Ok(TaskNoteLink {
+        task_id: row.get("task_id")?,
+        note_path: row.get("note_path")?,
+        source: row.get("source")?,
+        created_at: row.get("created_at")?,
+    })
+}
+
+fn github_board_link_from_row(row: &rusqlite::Row<'_>) -> Result<GithubBoardLink, rusqlite::Error> {
+    Ok(GithubBoardLink {
+        board_id: row.get("board_id")?,
+        repo: row.get("repo")?,
+        last_synced_at: row.get("last_synced_at")?,
+    })
+}
+
+fn github_issue_link_from_row(row: &rusqlite::Row<'_>) -> Result<GithubIssueLink, rusqlite::Error> {
+    let labels: String = row.get("synced_labels")?;
+    Ok(GithubIssueLink {
+        task_id: row.get("task_id")?,
+        repo: row.get("repo")?,
+        issue_number: row.get("issue_number")?,
+        synced_closed: row.get("synced_closed")?,
+        synced_labels: serde_json::from_str(&labels).unwrap_or_default(),
+        synced_at: row.get("synced_at")?,
+    })
+}
+
+fn webhook_delivery_from_row(row: &rusqlite::Row<'_>) -> Result<WebhookDelivery, rusqlite::Error> {
+    Ok(WebhookDelivery {
+        id: row.get("id")?,
+        webhook_id: row.get("webhook_id")?,
+        event: row.get("event")?,
+        task_id: row.get("task_id")?,
+        status_code: row.get("status_code")?,
+        attempts: row.get("attempts")?,
+        success: row.get("success")?,
+        error: row.get("error")?,
+        delivered_at: row.get("delivered_at")?,
+    })
+}
+
+fn recent_note_from_row(row: &rusqlite::Row<'_>) -> Result<RecentNote, rusqlite::Error> {
+    let line: Option<u32> = row.get("cursor_line")?;
+    let column: Option<u32> = row.get("cursor_column")?;
+    let scroll_top: Option<f64> = row.get("scroll_top")?;
+    let cursor = line.map(|line| NoteCursor {
+        line,
+        column: column.unwrap_or(0),
+        scroll_top,
+    });
+    Ok(RecentNote {
+        path: row.get("path")?,
+        opened_at: row.get("opened_at")?,
+        cursor,
+    })
+}
+
+fn favorite_from_row(row: &rusqlite::Row<'_>) -> Result<Favorite, rusqlite::Error> {
+    Ok(Favorite {
+        kind: FavoriteKind::from(row.get::<_, String>("kind")?.as_str()),
+        target: row.get("target")?,
+        order_index: row.get("order_index")?,
+        added_at: row.get("added_at")?,
+    })
+}
+
+fn reading_item_from_row(row: &rusqlite::Row<'_>) -> Result<ReadingItem, rusqlite::Error> {
+    Ok(ReadingItem {
+        id: row.get("id")?,
+        url: row.get("url")?,
+        title: row.get("title")?,
+        status: ReadingStatus::from(row.get::<_, String>("status")?.as_str()),
+        added_at: row.get("added_at")?,
+        read_at: row.get("read_at")?,
+        note_path: row.get("note_path")?,
+        task_id: row.get("task_id")?,
+        source_label: row.get("source_label")?,
+    })
+}
+
+fn reminder_from_row(row: &rusqlite::Row<'_>) -> Result<Reminder, rusqlite::Error> {
+    let repeat: Option<String> = row.get("repeat")?;
+    Ok(Reminder {
+        id: row.get("id")?,
+        task_id: row.get("task_id")?,
+        remind_at: row.get("remind_at")?,
+        repeat: repeat.as_deref().and_then(ReminderRepeat::parse),
+        fired_at: row.get("fired_at")?,
+        created_at: row.get("created_at")?,
+        updated_at: row.get("updated_at")?,
+    })
+}
+
+fn notification_from_row(row: &rusqlite::Row<'_>) -> Result<Notification, rusqlite::Error> {
+    Ok(Notification {
+        id: row.get("id")?,
+        kind: NotificationKind::from(row.get::<_, String>("kind")?.as_str()),
+        source_id: row.get("source_id")?,
+        occurrence_at: row.get("occurrence_at")?,
+        task_id: row.get("task_id")?,
+        title: row.get("title")?,
+        state: NotificationState::from(row.get::<_, String>("state")?.as_str()),
+        snoozed_until: row.get("snoozed_until")?,
+        delivered_at: row.get("delivered_at")?,
+        updated_at: row.get("updated_at")?,
+    })
+}
+
+fn webview_history_entry_from_row(
+    row: &rusqlite::Row<'_>,
+) -> Result<WebviewHistoryEntry, rusqlite::Error> {
+    Ok(WebviewHistoryEntry {
+        label: row.get("label")?,
+        url: row.get("url")?,
+        title: row.get("title")?,
+        visited_at: row.get("visited_at")?,
+    })
+}
+
+fn board_column_from_row(row: &rusqlite::Row<'_>) -> Result<BoardColumn, rusqlite::Error> {
+    Ok(BoardColumn {
+        board_id: row.get("board_id")?,
+        key: row.get("key")?,
+        name: row.get("name")?,
+        wip_limit: row.get("wip_limit")?,
+        maps_to: TaskStatus::from(row.get::<_, String>("maps_to")?.as_str()),
+        order_index: row.get("order_index")?,
+    })
+}
+
+fn sprint_from_row(row: &rusqlite::Row<'_>) -> Result<Sprint, rusqlite::Error> {
+    Ok(Sprint {
+        id: row.get("id")?,
+        name: row.get("name")?,
+        start_date: row.get("start_date")?,
+        end_date: row.get("end_date")?,
+        capacity: row.get("capacity")?,
+        created_at: row.get("created_at")?,
+        updated_at: row.get("updated_at")?,
+    })
+}
+
+fn board_meta_from_row(row: &rusqlite::Row<'_>) -> Result<BoardMeta, rusqlite::Error> {
+    Ok(BoardMeta {
+        board_id: row.get("board_id")?,
+        color: row.get("color")?,
+        updated_at: row.get("updated_at")?,
+    })
+}
+
+fn board_field_from_row(row: &rusqlite::Row<'_>) -> Result<BoardField, rusqlite::Error> {
+    let options: Option<String> = row.get("options")?;
+    Ok(BoardField {
+        board_id: row.get("board_id")?,
+        key: row.get("key")?,
+        name: row.get("name")?,
+        kind: FieldKind::from(row.get::<_, String>("kind")?.as_str()),
+        options: options
+            .and_then(|options| serde_json::from_str(&options).ok())
+            .unwrap_or_default(),
+        required: row.get("required")?,
+        order_index: row.get("order_index")?,
+    })
+}
+
+// Sealed columns are opened with the vault cipher; reading one without it is an error
+fn task_from_row(
+    row: &rusqlite::Row<'_>,
+    cipher: Option<&FieldCipher>,
+) -> Result<Task, rusqlite::Error> {
+    let description: Option<String> = row.get("description")?;
+    let description = match (description, cipher) {
+        (Some(value), Some(cipher)) => Some(cipher.open(&value).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(
+                0,
+                rusqlite::types::Type::Text,
+                Box::<dyn std::error::Error + Send + Sync>::from(e.message),
+            )
+        })?),
+        (Some(value), None) if db_crypto::is_sealed(&value) => {
+            return Err(rusqlite::Error::FromSqlConversionFailure(
+                0,
+                rusqlite::types::Type::Text,
+                Box::<dyn std::error::Error + Send + Sync>::from(
+                    "Task description is encrypted and the vault is locked",
+                ),
+            ))
+        }
+        (value, _) => value,
+    };
     let id: String = row.get("id")?;
     let priority_str: Option<String> = row.get("priority")?;
     let priority = priority_str.as_deref().map(TaskPriority::from);
@@ -1396,7 +4861,7 @@ fn task_from_row(row: &rusqlite::Row<'_>) -> Result<Task, rusqlite::Error> {
     Ok(Task {
         id,
         title: row.get("title")?,
-        description: row.get("description")?,
+        description,
         status: TaskStatus::from(row.get::<_, String>("status")?.as_str()),
         priority,
         tags: tags.clone(),
@@ -1409,12 +4874,27 @@ fn task_from_row(row: &rusqlite::Row<'_>) -> Result<Task, rusqlite::Error> {
         scheduled_end: row.get("scheduled_end")?,
         due_date: row.get("due_date")?,
         board_id: row.get("board_id")?,
+        column_key: row.get("column_key").unwrap_or(None),
+        color: row.get("color").unwrap_or(None),
         note_path: row.get("note_path")?,
         task_dir_slug: row.get("task_dir_slug").unwrap_or(None),
         md_rel_path: row.get("md_rel_path").unwrap_or(None),
+        cover_image: row.get("cover_image").unwrap_or(None),
+        excerpt: row.get("excerpt").unwrap_or(None),
         created_at: row.get("created_at")?,
         updated_at: row.get("updated_at")?,
         completed_at: row.get("completed_at")?,
         archived: row.get("archived")?,
     })
 }
+
+fn note_chunk_from_row(row: &rusqlite::Row<'_>) -> Result<NoteChunk, rusqlite::Error> {
+    Ok(NoteChunk {
+        index: row.get::<_, i64>("chunk_index")? as usize,
+        heading: row.get("heading")?,
+        anchor: row.get("anchor")?,
+        start: row.get::<_, i64>("start_offset")? as usize,
+        end: row.get::<_, i64>("end_offset")? as usize,
+        text: row.get("text")?,
+    })
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::ipc::{map_read_error, map_write_error, ApiError, ErrorCode};
+use crate::security::path_policy;
+
+const SNAPSHOT_DIR: &str = ".yourapp";
+const SNAPSHOT_FILE: &str = "scan_snapshot.json";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SnapshotEntry {
+    #[serde(default)]
+    pub dir: bool,
+    #[serde(default)]
+    pub mtime: Option<u64>,
+    pub seen_at: u64, // Scan that first found the path; files present at the first scan use their mtime
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct ScanSnapshot {
+    #[serde(default)]
+    pub scanned_at: u64,
+    #[serde(default)]
+    pub entries: BTreeMap<String, SnapshotEntry>,
+    #[serde(default)]
+    pub deleted: BTreeMap<String, u64>, // Path -> scan that noticed it was gone
+}
+
+fn snapshot_path(vault_root: &Path) -> PathBuf {
+    vault_root.join(SNAPSHOT_DIR).join(SNAPSHOT_FILE)
+}
+
+// Last persisted scan; None before the first one
+pub fn load_snapshot(vault_root: &Path) -> Result<Option<ScanSnapshot>, ApiError> {
+    let path = snapshot_path(vault_root);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let resolved = path_policy::ensure_abs_file_in_vault(vault_root, &path)?;
+    let content = fs::read_to_string(&resolved).map_err(map_read_error)?;
+    // A corrupt snapshot only costs one full rescan
+    Ok(serde_json::from_str(&content).ok())
+}
+
+pub fn save_snapshot(vault_root: &Path, snapshot: &ScanSnapshot) -> Result<(), ApiError> {
+    let snapshot_dir = vault_root.join(SNAPSHOT_DIR);
+    path_policy::ensure_or_create_dir_in_vault(vault_root, &snapshot_dir)?;
+    let path = snapshot_path(vault_root);
+    let data = serde_json::to_string(snapshot).map_err(|err| ApiError {
+        code: ErrorCode::WriteFailed,
+        message: "Failed to encode scan_snapshot.json".to_string(),
+        details: Some(serde_json::json!({ "error": err.to_string() })),
+    })?;
+    fs::write(&path, data)
+        .map_err(|err| map_write_error("Failed to write scan_snapshot.json", err))?;
+    Ok(())
+}
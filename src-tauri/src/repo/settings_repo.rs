@@ -1,14 +1,31 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::ipc::{map_read_error, map_write_error, ApiError};
-use crate::security::path_policy;
+use chrono_tz::Tz;
+
+use crate::domain::board::{BoardIndexConfig, DoneColumnConfig, MAX_DONE_WINDOW_DAYS};
+use crate::domain::daily_log::DailyLogConfig;
+use crate::domain::notifications::NotificationSettings;
+use crate::domain::planning::MdSyncPolicy;
+use crate::domain::rules::EscalationRule;
+use crate::domain::timezone;
+use crate::domain::webhooks::WebhookConfig;
+use crate::domain::week::WeekConfig;
+use crate::features::ai::embedding::EmbeddingModelChoice;
+use crate::ipc::{map_read_error, map_write_error, ApiError, ErrorCode};
+use crate::paths::VaultLayout;
+use crate::security::path_policy::{self, SymlinkPolicy};
 
 const SETTINGS_DIR: &str = ".yourapp";
 const SETTINGS_FILE: &str = "settings.json";
+// Schema version written into settings.json; older files are migrated step by step on load
+pub const SETTINGS_VERSION: u32 = 1;
 
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct PluginDisabledInfo {
@@ -34,6 +51,124 @@ pub struct AiSettings {
     pub api_key: String,
     #[serde(default = "default_ai_model")]
     pub model_name: String, // e.g. "gpt-4o", "deepseek-chat", "llama3"
+    #[serde(default = "default_ai_context_tokens")]
+    pub context_tokens: usize, // Model context window, prompt plus reply
+    #[serde(default = "default_ai_reply_tokens")]
+    pub reply_tokens: usize, // Reserved for the reply out of context_tokens
+    #[serde(default)]
+    pub overflow: PromptOverflow,
+    #[serde(default = "default_ai_cache_ttl_hours")]
+    pub cache_ttl_hours: u64, // 0 disables the response cache
+    #[serde(default)]
+    pub requests_per_minute: u32, // Ceiling per provider endpoint; 0 is unlimited
+    #[serde(default = "default_ai_max_retries")]
+    pub max_retries: u32, // Retries of 429, 5xx and connection failures
+    #[serde(default)]
+    pub embedding_model: EmbeddingModelChoice,
+    #[serde(default)]
+    pub preload_embedding_model: bool, // Load the model in the background at startup, not on first use
+    #[serde(default)]
+    pub transcription: TranscriptionSettings,
+    #[serde(default)]
+    pub ocr: OcrSettings,
+}
+
+// Text recognition for image capture
+#[derive(Serialize, Deserialize, Clone)]
+pub struct OcrSettings {
+    #[serde(default)]
+    pub provider: OcrProvider,
+    #[serde(default)]
+    pub model: String, // Vision-capable model; empty uses the AI model_name
+    #[serde(default = "default_tesseract_binary")]
+    pub tesseract_binary: String, // By path or on PATH
+    #[serde(default = "default_tesseract_languages")]
+    pub tesseract_languages: String, // Tesseract language codes joined by '+', e.g. "eng+deu"
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OcrProvider {
+    #[default]
+    Api, // Vision model behind the AI provider's chat endpoint
+    Local, // Tesseract on this machine
+}
+
+impl Default for OcrSettings {
+    fn default() -> Self {
+        Self {
+            provider: OcrProvider::default(),
+            model: String::new(),
+            tesseract_binary: default_tesseract_binary(),
+            tesseract_languages: default_tesseract_languages(),
+        }
+    }
+}
+
+fn default_tesseract_binary() -> String {
+    "tesseract".to_string()
+}
+
+fn default_tesseract_languages() -> String {
+    "eng".to_string()
+}
+
+// Speech-to-text for voice capture
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TranscriptionSettings {
+    #[serde(default)]
+    pub provider: TranscriptionProvider,
+    #[serde(default)]
+    pub base_url: String, // Empty uses the AI base_url
+    #[serde(default)]
+    pub api_key: String, // Empty uses the AI api_key
+    #[serde(default = "default_transcription_model")]
+    pub model: String,
+    #[serde(default)]
+    pub language: Option<String>, // ISO 639-1 hint; None lets the model detect it
+    #[serde(default = "default_whisper_binary")]
+    pub whisper_binary: String, // whisper.cpp command line program, by path or on PATH
+    #[serde(default)]
+    pub whisper_model: String, // Path to the ggml model file for the local provider
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TranscriptionProvider {
+    #[default]
+    Api, // OpenAI-compatible /audio/transcriptions endpoint
+    Local, // whisper.cpp on this machine
+}
+
+impl Default for TranscriptionSettings {
+    fn default() -> Self {
+        Self {
+            provider: TranscriptionProvider::default(),
+            base_url: String::new(),
+            api_key: String::new(),
+            model: default_transcription_model(),
+            language: None,
+            whisper_binary: default_whisper_binary(),
+            whisper_model: String::new(),
+        }
+    }
+}
+
+fn default_transcription_model() -> String {
+    "whisper-1".to_string()
+}
+
+fn default_whisper_binary() -> String {
+    "whisper-cli".to_string()
+}
+
+// What to do with input that does not fit the prompt budget
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PromptOverflow {
+    #[default]
+    Summarize, // Summarize the input chunk by chunk, then run the prompt over the summaries
+    Truncate, // Keep the head of the input that fits
 }
 
 impl Default for AiSettings {
@@ -43,6 +178,16 @@ impl Default for AiSettings {
             base_url: default_ai_base_url(),
             api_key: String::new(),
             model_name: default_ai_model(),
+            context_tokens: default_ai_context_tokens(),
+            reply_tokens: default_ai_reply_tokens(),
+            overflow: PromptOverflow::default(),
+            cache_ttl_hours: default_ai_cache_ttl_hours(),
+            requests_per_minute: 0,
+            max_retries: default_ai_max_retries(),
+            embedding_model: EmbeddingModelChoice::default(),
+            preload_embedding_model: false,
+            transcription: TranscriptionSettings::default(),
+            ocr: OcrSettings::default(),
         }
     }
 }
@@ -59,12 +204,136 @@ fn default_ai_model() -> String {
     "llama3".to_string()
 }
 
+fn default_ai_context_tokens() -> usize {
+    8192 // llama3's window; hosted models allow more but this is safe everywhere
+}
+
+fn default_ai_reply_tokens() -> usize {
+    1024
+}
+
+fn default_ai_cache_ttl_hours() -> u64 {
+    24 * 7
+}
+
+fn default_ai_max_retries() -> u32 {
+    3
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct PlanningSettings {
+    #[serde(default)]
+    pub timezone: Option<String>, // IANA name, e.g. "Asia/Shanghai"; None uses the system timezone
+    #[serde(default)]
+    pub week: WeekConfig,
+    #[serde(default)]
+    pub rules: Vec<EscalationRule>,
+    #[serde(default)]
+    pub layout: VaultLayout, // Changed only through a layout migration
+    #[serde(default)]
+    pub daily_log: DailyLogConfig,
+    #[serde(default)]
+    pub done_column: DoneColumnConfig,
+    #[serde(default)]
+    pub md_sync: MdSyncPolicy,
+    #[serde(default)]
+    pub board_index: BoardIndexConfig,
+}
+
+// Opt-in localhost REST API for launcher and automation scripts
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HttpApiSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_http_api_port")]
+    pub port: u16, // Bound on 127.0.0.1 only
+    #[serde(default)]
+    pub token: String, // Bearer token required on every request
+    #[serde(default)]
+    pub metrics: bool, // Serves GET /metrics in Prometheus text format
+}
+
+impl Default for HttpApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_http_api_port(),
+            token: String::new(),
+            metrics: false,
+        }
+    }
+}
+
+fn default_http_api_port() -> u16 {
+    17890
+}
+
+// Per-vault scan thresholds; unset values use the built-in defaults
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct ScanSettings {
+    #[serde(default)]
+    pub warning_entries: Option<usize>, // Warn that scanning may be slow above this
+    #[serde(default)]
+    pub limit_entries: Option<usize>, // Stop a scan after this many entries
+}
+
+// Checks run by lint_note; every check is on unless turned off
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LintSettings {
+    #[serde(default = "default_true")]
+    pub duplicate_words: bool,
+    #[serde(default = "default_true")]
+    pub long_paragraphs: bool,
+    #[serde(default = "default_max_paragraph_words")]
+    pub max_paragraph_words: usize, // CJK characters count as words
+    #[serde(default = "default_true")]
+    pub broken_links: bool,
+    #[serde(default = "default_true")]
+    pub frontmatter: bool,
+}
+
+impl Default for LintSettings {
+    fn default() -> Self {
+        Self {
+            duplicate_words: true,
+            long_paragraphs: true,
+            max_paragraph_words: default_max_paragraph_words(),
+            broken_links: true,
+            frontmatter: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_paragraph_words() -> usize {
+    250
+}
+
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct Settings {
+    #[serde(default)]
+    pub version: u32, // Files from before versioning have none and read as 0
     #[serde(default)]
     pub plugins: PluginsSettings,
     #[serde(default)]
     pub ai: AiSettings,
+    #[serde(default)]
+    pub planning: PlanningSettings,
+    #[serde(default)]
+    pub http_api: HttpApiSettings,
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+    #[serde(default)]
+    pub scan: ScanSettings,
+    #[serde(default)]
+    pub symlinks: SymlinkPolicy,
+    #[serde(default)]
+    pub lint: LintSettings,
+    #[serde(default)]
+    pub notifications: NotificationSettings,
 }
 
 fn now_unix_string() -> String {
@@ -79,30 +348,489 @@ fn settings_path(vault_root: &Path) -> PathBuf {
     vault_root.join(SETTINGS_DIR).join(SETTINGS_FILE)
 }
 
-pub fn load_settings(vault_root: &Path) -> Result<Settings, ApiError> {
-    let path = settings_path(vault_root);
+// Top-level sections of settings.json
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingsSection {
+    Plugins,
+    Ai,
+    Planning,
+    HttpApi,
+    Webhooks,
+    Scan,
+    Symlinks,
+    Lint,
+    Notifications,
+}
+
+impl SettingsSection {
+    pub const ALL: [SettingsSection; 9] = [
+        SettingsSection::Plugins,
+        SettingsSection::Ai,
+        SettingsSection::Planning,
+        SettingsSection::HttpApi,
+        SettingsSection::Webhooks,
+        SettingsSection::Scan,
+        SettingsSection::Symlinks,
+        SettingsSection::Lint,
+        SettingsSection::Notifications,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            SettingsSection::Plugins => "plugins",
+            SettingsSection::Ai => "ai",
+            SettingsSection::Planning => "planning",
+            SettingsSection::HttpApi => "http_api",
+            SettingsSection::Webhooks => "webhooks",
+            SettingsSection::Scan => "scan",
+            SettingsSection::Symlinks => "symlinks",
+            SettingsSection::Lint => "lint",
+            SettingsSection::Notifications => "notifications",
+        }
+    }
+
+    // Layer a section is saved to while neither layer stores it: secrets and machine-specific
+    // values stay out of the synced vault
+    pub fn home_layer(&self) -> SettingsLayer {
+        match self {
+            SettingsSection::Ai | SettingsSection::HttpApi => SettingsLayer::App,
+            _ => SettingsLayer::Vault,
+        }
+    }
+
+    // path_policy reads the symlink policy from the vault file only
+    pub fn allowed_in(&self, layer: SettingsLayer) -> bool {
+        !(*self == SettingsSection::Symlinks && layer == SettingsLayer::App)
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|section| section.key() == key)
+    }
+
+    // Default contents of the section as stored in settings.json
+    fn default_value(&self) -> Value {
+        let defaults = serde_json::to_value(Settings::default()).unwrap_or_default();
+        defaults.get(self.key()).cloned().unwrap_or(Value::Null)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SettingsIssueSeverity {
+    Error,   // The file does not load, or a value is rejected when saved
+    Warning, // Ignored or suspicious, but the app runs
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SettingsIssue {
+    pub section: Option<SettingsSection>,
+    pub path: String, // Dotted path of the value, e.g. "ai.reply_tokens"
+    pub severity: SettingsIssueSeverity,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SettingsValidation {
+    pub version: u32, // As stored in the file
+    pub current_version: u32,
+    pub valid: bool, // No errors; warnings allowed
+    pub issues: Vec<SettingsIssue>,
+}
+
+// Each step takes the raw settings from version N to N + 1
+const SETTINGS_MIGRATIONS: &[fn(&mut Value)] = &[migrate_settings_v0];
+
+// v0: files from before versioning. Earlier frontends stored an empty timezone instead of none,
+// mixed-case provider names and repeated plugin ids.
+fn migrate_settings_v0(settings: &mut Value) {
+    if let Some(planning) = settings.get_mut("planning").and_then(Value::as_object_mut) {
+        if planning
+            .get("timezone")
+            .and_then(Value::as_str)
+            .is_some_and(|timezone| timezone.trim().is_empty())
+        {
+            planning.remove("timezone");
+        }
+    }
+    if let Some(provider) = settings.pointer_mut("/ai/provider") {
+        if let Some(name) = provider.as_str() {
+            *provider = Value::from(name.trim().to_lowercase());
+        }
+    }
+    if let Some(enabled) = settings
+        .pointer_mut("/plugins/enabled")
+        .and_then(Value::as_array_mut)
+    {
+        let mut seen = Vec::new();
+        enabled.retain(|id| {
+            let first = !seen.contains(id);
+            seen.push(id.clone());
+            first
+        });
+    }
+}
+
+fn stored_version(settings: &Value) -> u32 {
+    settings
+        .get("version")
+        .and_then(Value::as_u64)
+        .map_or(0, |version| version as u32)
+}
+
+// Run the migrations the raw settings still need; returns whether anything ran. Files from a
+// newer version of the app are left alone.
+fn migrate_settings(settings: &mut Value) -> bool {
+    let version = stored_version(settings);
+    if version >= SETTINGS_VERSION || !settings.is_object() {
+        return false;
+    }
+    for migration in &SETTINGS_MIGRATIONS[version as usize..] {
+        migration(settings);
+    }
+    if let Some(map) = settings.as_object_mut() {
+        map.insert("version".to_string(), Value::from(SETTINGS_VERSION));
+    }
+    true
+}
+
+// Where a settings file lives. App settings stay on this machine in the app config directory;
+// vault settings travel with the vault in .yourapp/settings.json.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingsLayer {
+    App,
+    Vault,
+}
+
+fn app_settings_dir() -> &'static OnceLock<PathBuf> {
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+    &DIR
+}
+
+// Set once at startup; without it (CLI, MCP server) only the vault layer exists
+pub fn init_app_settings_dir(dir: PathBuf) {
+    let _ = app_settings_dir().set(dir);
+}
+
+fn layer_path(vault_root: &Path, layer: SettingsLayer) -> Option<PathBuf> {
+    match layer {
+        SettingsLayer::App => app_settings_dir().get().map(|dir| dir.join(SETTINGS_FILE)),
+        SettingsLayer::Vault => Some(settings_path(vault_root)),
+    }
+}
+
+fn read_layer_value(vault_root: &Path, layer: SettingsLayer) -> Result<Option<Value>, ApiError> {
+    let Some(path) = layer_path(vault_root, layer) else {
+        return Ok(None);
+    };
     if !path.exists() {
-        return Ok(Settings::default());
+        return Ok(None);
     }
-    let resolved = path_policy::ensure_abs_file_in_vault(vault_root, &path)?;
+    let resolved = match layer {
+        SettingsLayer::App => path,
+        SettingsLayer::Vault => path_policy::ensure_abs_file_in_vault(vault_root, &path)?,
+    };
     let content = fs::read_to_string(&resolved).map_err(map_read_error)?;
-    serde_json::from_str(&content).map_err(|err| ApiError {
-        code: "DecodeFailed".to_string(),
-        message: "Failed to decode settings.json".to_string(),
-        details: Some(serde_json::json!({ "error": err.to_string() })),
-    })
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|err| ApiError {
+            code: ErrorCode::DecodeFailed,
+            message: "Failed to decode settings.json".to_string(),
+            details: Some(serde_json::json!({ "layer": layer, "error": err.to_string() })),
+        })
 }
 
-fn save_settings(vault_root: &Path, settings: &Settings) -> Result<(), ApiError> {
-    let settings_dir = vault_root.join(SETTINGS_DIR);
-    path_policy::ensure_or_create_dir_in_vault(vault_root, &settings_dir)?;
-    let path = settings_path(vault_root);
+// `path` is the layer's settings.json or a backup of it next to it
+fn write_layer_file(
+    vault_root: &Path,
+    layer: SettingsLayer,
+    path: &Path,
+    settings: &Value,
+) -> Result<(), ApiError> {
+    match layer {
+        SettingsLayer::App => {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|err| {
+                    map_write_error("Failed to create the app settings directory", err)
+                })?;
+            }
+        }
+        SettingsLayer::Vault => {
+            let settings_dir = vault_root.join(SETTINGS_DIR);
+            path_policy::ensure_or_create_dir_in_vault(vault_root, &settings_dir)?;
+        }
+    }
     let data = serde_json::to_string_pretty(settings).map_err(|err| ApiError {
-        code: "WriteFailed".to_string(),
+        code: ErrorCode::WriteFailed,
         message: "Failed to encode settings.json".to_string(),
         details: Some(serde_json::json!({ "error": err.to_string() })),
     })?;
-    fs::write(&path, data).map_err(|err| map_write_error("Failed to write settings.json", err))?;
+    fs::write(path, data).map_err(|err| map_write_error("Failed to write settings.json", err))?;
+    Ok(())
+}
+
+fn write_layer_value(
+    vault_root: &Path,
+    layer: SettingsLayer,
+    settings: &Value,
+) -> Result<(), ApiError> {
+    let path = layer_path(vault_root, layer).ok_or_else(|| ApiError {
+        code: ErrorCode::ConfigDirNotFound,
+        message: "App settings are not available here".to_string(),
+        details: None,
+    })?;
+    write_layer_file(vault_root, layer, &path, settings)
+}
+
+// Raw settings of a layer brought up to the current version; a migrated file is written back,
+// keeping the original next to it as settings.json.v<N>.bak
+fn load_layer_value(vault_root: &Path, layer: SettingsLayer) -> Result<Option<Value>, ApiError> {
+    let Some(mut settings) = read_layer_value(vault_root, layer)? else {
+        return Ok(None);
+    };
+    let from_version = stored_version(&settings);
+    let original = settings.clone();
+    if migrate_settings(&mut settings) {
+        if let Some(path) = layer_path(vault_root, layer) {
+            let backup = path.with_file_name(format!("{}.v{}.bak", SETTINGS_FILE, from_version));
+            write_layer_file(vault_root, layer, &backup, &original)?;
+            write_layer_file(vault_root, layer, &path, &settings)?;
+        }
+        tracing::info!(target: "settings", "settings migrated: layer={:?}, from_version={}, to_version={}", layer, from_version, SETTINGS_VERSION);
+    }
+    Ok(Some(settings))
+}
+
+// Both layers as stored, `{}` when a layer has no file
+fn load_layers(vault_root: &Path) -> Result<(Value, Value), ApiError> {
+    let vault = load_layer_value(vault_root, SettingsLayer::Vault)?
+        .filter(Value::is_object)
+        .unwrap_or_else(|| serde_json::json!({}));
+    let app = load_layer_value(vault_root, SettingsLayer::App)?
+        .filter(Value::is_object)
+        .unwrap_or_else(|| serde_json::json!({}));
+    Ok((vault, app))
+}
+
+// Precedence is per section: the vault layer when it stores the section, else the app layer;
+// None when neither does and the defaults apply
+fn section_source(section: SettingsSection, vault: &Value, app: &Value) -> Option<SettingsLayer> {
+    if section.allowed_in(SettingsLayer::Vault) && vault.get(section.key()).is_some() {
+        Some(SettingsLayer::Vault)
+    } else if section.allowed_in(SettingsLayer::App) && app.get(section.key()).is_some() {
+        Some(SettingsLayer::App)
+    } else {
+        None
+    }
+}
+
+// Layer a section is written to: where it is read from, else its home layer
+fn section_target(section: SettingsSection, vault: &Value, app: &Value) -> SettingsLayer {
+    match section_source(section, vault, app).unwrap_or(section.home_layer()) {
+        SettingsLayer::App if app_settings_dir().get().is_none() => SettingsLayer::Vault,
+        layer => layer,
+    }
+}
+
+fn compose_layers(vault: &Value, app: &Value) -> Value {
+    let mut settings = serde_json::json!({ "version": SETTINGS_VERSION });
+    for section in SettingsSection::ALL {
+        let value = match section_source(section, vault, app) {
+            Some(SettingsLayer::Vault) => vault.get(section.key()),
+            Some(SettingsLayer::App) => app.get(section.key()),
+            None => None,
+        };
+        if let Some(value) = value {
+            settings[section.key()] = value.clone();
+        }
+    }
+    settings
+}
+
+// Decode failures name the section at fault so validate_settings and reset_settings_section can
+// be pointed at it
+fn decode_settings(settings: Value) -> Result<Settings, ApiError> {
+    serde_json::from_value::<Settings>(settings.clone()).map_err(|err| {
+        let section = SettingsSection::ALL.into_iter().find(|section| {
+            settings.get(section.key()).is_some_and(|value| {
+                let mut probe = serde_json::json!({});
+                probe[section.key()] = value.clone();
+                serde_json::from_value::<Settings>(probe).is_err()
+            })
+        });
+        ApiError {
+            code: ErrorCode::DecodeFailed,
+            message: match section {
+                Some(section) => format!("Failed to decode the {} settings", section.key()),
+                None => "Failed to decode settings.json".to_string(),
+            },
+            details: Some(serde_json::json!({
+                "section": section,
+                "error": err.to_string(),
+            })),
+        }
+    })
+}
+
+// Tauri event emitted with a SettingsChangedEvent after every settings write
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+// Settings keys whose values are masked in change events
+const SECRET_KEYS: &[&str] = &["api_key", "token", "secret"];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SettingsChange {
+    pub path: String, // Dotted path, e.g. "ai.model_name"; arrays change as a whole
+    pub old: Value,
+    pub new: Value,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SettingsChangedEvent {
+    pub vault_root: String,
+    pub sections: Vec<SettingsSection>,
+    pub changes: Vec<SettingsChange>,
+}
+
+type SettingsChangeHook = Box<dyn Fn(&SettingsChangedEvent) + Send + Sync>;
+
+fn settings_change_hook() -> &'static OnceLock<SettingsChangeHook> {
+    static HOOK: OnceLock<SettingsChangeHook> = OnceLock::new();
+    &HOOK
+}
+
+// Set once at startup to forward changes to the windows and background jobs
+pub fn on_settings_changed(hook: impl Fn(&SettingsChangedEvent) + Send + Sync + 'static) {
+    let _ = settings_change_hook().set(Box::new(hook));
+}
+
+// Effective settings with defaults filled in, so a section appearing in a layer with default
+// values is not reported as a change
+fn effective_settings(vault: &Value, app: &Value) -> Value {
+    let composed = compose_layers(vault, app);
+    serde_json::from_value::<Settings>(composed.clone())
+        .ok()
+        .and_then(|settings| serde_json::to_value(settings).ok())
+        .unwrap_or(composed)
+}
+
+fn mask_secret(path: &str, value: &Value) -> Value {
+    let key = path.rsplit('.').next().unwrap_or(path);
+    match value {
+        Value::String(secret) if SECRET_KEYS.contains(&key) && !secret.is_empty() => {
+            Value::from("********")
+        }
+        _ => value.clone(),
+    }
+}
+
+fn diff_settings(path: &str, old: &Value, new: &Value, changes: &mut Vec<SettingsChange>) {
+    if old == new {
+        return;
+    }
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            let keys: std::collections::BTreeSet<&String> =
+                old_map.keys().chain(new_map.keys()).collect();
+            for key in keys {
+                let child = if path.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                diff_settings(
+                    &child,
+                    old_map.get(key).unwrap_or(&Value::Null),
+                    new_map.get(key).unwrap_or(&Value::Null),
+                    changes,
+                );
+            }
+        }
+        _ => changes.push(SettingsChange {
+            path: path.to_string(),
+            old: mask_secret(path, old),
+            new: mask_secret(path, new),
+        }),
+    }
+}
+
+fn notify_settings_changed(vault_root: &Path, before: &Value, after: &Value) {
+    let Some(hook) = settings_change_hook().get() else {
+        return;
+    };
+    let mut changes = Vec::new();
+    diff_settings("", before, after, &mut changes);
+    changes.retain(|change| change.path != "version");
+    if changes.is_empty() {
+        return;
+    }
+    let mut sections = Vec::new();
+    for change in &changes {
+        let key = change.path.split('.').next().unwrap_or_default();
+        if let Some(section) = SettingsSection::from_key(key) {
+            if !sections.contains(&section) {
+                sections.push(section);
+            }
+        }
+    }
+    hook(&SettingsChangedEvent {
+        vault_root: vault_root.to_string_lossy().to_string(),
+        sections,
+        changes,
+    });
+}
+
+// Effective settings: each section from the layer that provides it, see `section_source`
+pub fn load_settings(vault_root: &Path) -> Result<Settings, ApiError> {
+    let (vault, app) = load_layers(vault_root)?;
+    decode_settings(compose_layers(&vault, &app))
+}
+
+// Read by path_policy itself, so this bypasses it: a symlinked or unreadable settings file means deny
+pub fn get_symlink_policy(vault_root: &Path) -> SymlinkPolicy {
+    let settings_dir = vault_root.join(SETTINGS_DIR);
+    let path = settings_path(vault_root);
+    let linked = [&settings_dir, &path].into_iter().any(|path| {
+        fs::symlink_metadata(path)
+            .map(|meta| meta.file_type().is_symlink())
+            .unwrap_or(true)
+    });
+    if linked {
+        return SymlinkPolicy::Deny;
+    }
+    fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<Settings>(&content).ok())
+        .map(|settings| settings.symlinks)
+        .unwrap_or_default()
+}
+
+// Each section is written back to the layer it was read from, so values kept on this machine
+// never end up in the vault
+fn save_settings(vault_root: &Path, settings: &Settings) -> Result<(), ApiError> {
+    let value = serde_json::to_value(settings)?;
+    let (mut vault, mut app) = load_layers(vault_root)?;
+    let before = effective_settings(&vault, &app);
+    let mut app_changed = false;
+    for section in SettingsSection::ALL {
+        let section_value = value.get(section.key()).cloned().unwrap_or(Value::Null);
+        match section_target(section, &vault, &app) {
+            SettingsLayer::Vault => vault[section.key()] = section_value,
+            SettingsLayer::App => {
+                if app.get(section.key()) != Some(&section_value) {
+                    app[section.key()] = section_value;
+                    app_changed = true;
+                }
+            }
+        }
+    }
+    vault["version"] = Value::from(SETTINGS_VERSION);
+    write_layer_value(vault_root, SettingsLayer::Vault, &vault)?;
+    if app_changed {
+        app["version"] = Value::from(SETTINGS_VERSION);
+        write_layer_value(vault_root, SettingsLayer::App, &app)?;
+    }
+    notify_settings_changed(vault_root, &before, &effective_settings(&vault, &app));
     Ok(())
 }
 
@@ -137,8 +865,896 @@ pub fn get_ai_settings(vault_root: &Path) -> Result<AiSettings, ApiError> {
     Ok(settings.ai)
 }
 
+// Developer fallback for the AI provider: OPENAI_* variables, from the process environment and
+// then from a .env file at the vault root. Fields stored in the settings always win; the
+// fallback only fills fields no settings layer sets.
+const AI_ENV_FILE: &str = ".env";
+const AI_ENV_VARS: &[(&str, &[&str])] = &[
+    ("api_key", &["OPENAI_API_KEY"]),
+    ("base_url", &["OPENAI_BASE_URL", "OPENAI_API_BASE"]),
+    ("model_name", &["OPENAI_MODEL"]),
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AiConfigSource {
+    Settings,
+    Environment,
+    DotEnv,
+    Default,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AiConfigField {
+    pub value: String, // Secrets are masked
+    pub source: AiConfigSource,
+    pub variable: Option<String>, // Variable the value came from, for env and .env sources
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EffectiveAiConfig {
+    pub provider: AiConfigField,
+    pub base_url: AiConfigField,
+    pub api_key: AiConfigField,
+    pub model_name: AiConfigField,
+    pub env_file: Option<String>, // Vault .env, when one was read
+}
+
+// KEY=value lines; blank lines, # comments and an `export ` prefix are skipped, and values may be
+// quoted. Anything unparseable is ignored rather than failing the settings load.
+fn parse_dotenv(content: &str) -> BTreeMap<String, String> {
+    let mut vars = BTreeMap::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) if value.len() >= 2 && value.ends_with(quote) => {
+                &value[1..value.len() - 1]
+            }
+            _ => value.split(" #").next().unwrap_or(value).trim(),
+        };
+        vars.insert(key.trim().to_string(), value.to_string());
+    }
+    vars
+}
+
+fn read_vault_dotenv(vault_root: &Path) -> Option<BTreeMap<String, String>> {
+    let path = vault_root.join(AI_ENV_FILE);
+    if fs::symlink_metadata(&path).ok()?.file_type().is_symlink() {
+        return None;
+    }
+    fs::read_to_string(&path)
+        .ok()
+        .map(|content| parse_dotenv(&content))
+}
+
+// First non-empty value for a field: the process environment, then the vault .env
+fn ai_env_value(
+    field: &str,
+    dotenv: Option<&BTreeMap<String, String>>,
+) -> Option<(String, AiConfigSource, String)> {
+    let (_, variables) = AI_ENV_VARS.iter().find(|(key, _)| *key == field)?;
+    let from_env = variables.iter().find_map(|variable| {
+        std::env::var(variable)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| (value, AiConfigSource::Environment, variable.to_string()))
+    });
+    from_env.or_else(|| {
+        variables.iter().find_map(|variable| {
+            dotenv?
+                .get(*variable)
+                .filter(|value| !value.trim().is_empty())
+                .map(|value| (value.clone(), AiConfigSource::DotEnv, variable.to_string()))
+        })
+    })
+}
+
+// Source of each provider field, with the variable it was read from
+type AiConfigSources = BTreeMap<&'static str, (AiConfigSource, Option<String>)>;
+
+// AI settings with the env fallback applied, plus where each provider field came from
+fn resolve_ai_config(vault_root: &Path) -> Result<(AiSettings, AiConfigSources, bool), ApiError> {
+    let (vault, app) = load_layers(vault_root)?;
+    let composed = compose_layers(&vault, &app);
+    let mut settings = decode_settings(composed.clone())?.ai;
+    let stored = |field: &str| {
+        composed
+            .get("ai")
+            .and_then(|ai| ai.get(field))
+            .and_then(Value::as_str)
+            .is_some_and(|value| !value.trim().is_empty())
+    };
+    let dotenv = read_vault_dotenv(vault_root);
+
+    let mut sources = BTreeMap::new();
+    for (field, _) in AI_ENV_VARS {
+        let source = if stored(field) {
+            (AiConfigSource::Settings, None)
+        } else if let Some((value, source, variable)) = ai_env_value(field, dotenv.as_ref()) {
+            match *field {
+                "api_key" => settings.api_key = value,
+                "base_url" => settings.base_url = value,
+                _ => settings.model_name = value,
+            }
+            (source, Some(variable))
+        } else {
+            (AiConfigSource::Default, None)
+        };
+        sources.insert(*field, source);
+    }
+
+    // OPENAI_* credentials mean an OpenAI-compatible endpoint unless the settings name a provider
+    let provider = if stored("provider") {
+        (AiConfigSource::Settings, None)
+    } else if let Some((source, variable)) = sources
+        .get("api_key")
+        .filter(|(source, _)| *source != AiConfigSource::Default)
+        .cloned()
+    {
+        settings.provider = "openai".to_string();
+        (source, variable)
+    } else {
+        (AiConfigSource::Default, None)
+    };
+    sources.insert("provider", provider);
+    Ok((settings, sources, dotenv.is_some()))
+}
+
+// Settings used to call the AI provider. `get_ai_settings` stays the stored settings, so the
+// settings editor never shows or saves a key that came from the environment.
+pub fn resolve_ai_settings(vault_root: &Path) -> Result<AiSettings, ApiError> {
+    resolve_ai_config(vault_root).map(|(settings, _, _)| settings)
+}
+
+pub fn get_effective_ai_config(vault_root: &Path) -> Result<EffectiveAiConfig, ApiError> {
+    let (settings, sources, has_env_file) = resolve_ai_config(vault_root)?;
+    let field = |key: &str, value: &str| {
+        let (source, variable) = sources
+            .get(key)
+            .cloned()
+            .unwrap_or((AiConfigSource::Default, None));
+        AiConfigField {
+            value: mask_secret(key, &Value::from(value))
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            source,
+            variable,
+        }
+    };
+    Ok(EffectiveAiConfig {
+        provider: field("provider", &settings.provider),
+        base_url: field("base_url", &settings.base_url),
+        api_key: field("api_key", &settings.api_key),
+        model_name: field("model_name", &settings.model_name),
+        env_file: has_env_file.then(|| vault_root.join(AI_ENV_FILE).to_string_lossy().to_string()),
+    })
+}
+
 pub fn save_ai_settings(vault_root: &Path, ai_settings: AiSettings) -> Result<(), ApiError> {
     let mut settings = load_settings(vault_root)?;
     settings.ai = ai_settings;
     save_settings(vault_root, &settings)
 }
+
+pub fn get_notification_settings(vault_root: &Path) -> Result<NotificationSettings, ApiError> {
+    let settings = load_settings(vault_root)?;
+    Ok(settings.notifications)
+}
+
+pub fn get_http_api_settings(vault_root: &Path) -> Result<HttpApiSettings, ApiError> {
+    let settings = load_settings(vault_root)?;
+    Ok(settings.http_api)
+}
+
+pub fn save_http_api_settings(
+    vault_root: &Path,
+    http_api_settings: HttpApiSettings,
+) -> Result<(), ApiError> {
+    if http_api_settings.port < 1024 {
+        return Err(ApiError {
+            code: ErrorCode::InvalidInput,
+            message: "HTTP API port must be 1024 or higher".to_string(),
+            details: Some(serde_json::json!({ "port": http_api_settings.port })),
+        });
+    }
+    let mut settings = load_settings(vault_root)?;
+    settings.http_api = http_api_settings;
+    save_settings(vault_root, &settings)
+}
+
+pub fn get_webhooks(vault_root: &Path) -> Result<Vec<WebhookConfig>, ApiError> {
+    let settings = load_settings(vault_root)?;
+    Ok(settings.webhooks)
+}
+
+// Replace the webhook list; new entries get an id
+pub fn save_webhooks(
+    vault_root: &Path,
+    mut webhooks: Vec<WebhookConfig>,
+) -> Result<Vec<WebhookConfig>, ApiError> {
+    for webhook in &mut webhooks {
+        webhook.url = webhook.url.trim().to_string();
+        if !(webhook.url.starts_with("https://") || webhook.url.starts_with("http://")) {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "Webhook URL must start with http:// or https://".to_string(),
+                details: Some(serde_json::json!({ "url": webhook.url })),
+            });
+        }
+        if webhook.id.trim().is_empty() {
+            webhook.id = uuid::Uuid::new_v4().to_string();
+        }
+        let mut seen = Vec::new();
+        webhook.events.retain(|event| {
+            let first = !seen.contains(event);
+            seen.push(*event);
+            first
+        });
+    }
+    let mut settings = load_settings(vault_root)?;
+    settings.webhooks = webhooks.clone();
+    save_settings(vault_root, &settings)?;
+    Ok(webhooks)
+}
+
+pub fn get_planning_settings(vault_root: &Path) -> Result<PlanningSettings, ApiError> {
+    let settings = load_settings(vault_root)?;
+    Ok(settings.planning)
+}
+
+pub fn save_planning_settings(
+    vault_root: &Path,
+    mut planning_settings: PlanningSettings,
+) -> Result<(), ApiError> {
+    // Reject unknown zones up front and drop empty values
+    planning_settings.timezone = match planning_settings.timezone.as_deref().map(str::trim) {
+        Some("") | None => None,
+        Some(name) => Some(timezone::parse_timezone(name)?.name().to_string()),
+    };
+    if planning_settings.week.workdays.is_empty() {
+        return Err(ApiError {
+            code: ErrorCode::InvalidInput,
+            message: "At least one workday is required".to_string(),
+            details: None,
+        });
+    }
+    planning_settings
+        .week
+        .workdays
+        .sort_by_key(|day| day.num_days_from_monday());
+    planning_settings.week.workdays.dedup();
+    planning_settings.daily_log.heading = planning_settings.daily_log.heading.trim().to_string();
+    if planning_settings
+        .daily_log
+        .template
+        .as_deref()
+        .is_some_and(|template| template.trim().is_empty())
+    {
+        planning_settings.daily_log.template = None;
+    }
+    if planning_settings
+        .daily_log
+        .heading
+        .trim_start_matches('#')
+        .trim()
+        .is_empty()
+    {
+        return Err(ApiError {
+            code: ErrorCode::InvalidInput,
+            message: "Daily log heading must not be empty".to_string(),
+            details: None,
+        });
+    }
+    let mut settings = load_settings(vault_root)?;
+    // Moving directories needs a migration, so the layout is kept as is here
+    planning_settings.layout = settings.planning.layout.clone();
+    settings.planning = planning_settings;
+    save_settings(vault_root, &settings)
+}
+
+fn invalid_layout(message: &str, value: &str) -> ApiError {
+    ApiError {
+        code: ErrorCode::InvalidInput,
+        message: message.to_string(),
+        details: Some(serde_json::json!({ "value": value })),
+    }
+}
+
+// Trimmed, forward-slash vault-relative directory made of plain components only
+fn normalize_layout_dir(value: &str) -> Result<String, ApiError> {
+    let normalized = value.trim().replace('\\', "/");
+    let normalized = normalized.trim_matches('/');
+    if normalized.is_empty() {
+        return Err(invalid_layout("Layout directory must not be empty", value));
+    }
+    let parts: Vec<&str> = normalized
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect();
+    if parts.iter().any(|part| *part == "." || *part == "..")
+        || Path::new(normalized).is_absolute()
+        || normalized.contains(':')
+    {
+        return Err(invalid_layout(
+            "Layout directory must be a plain vault-relative path",
+            value,
+        ));
+    }
+    Ok(parts.join("/"))
+}
+
+fn is_same_or_nested(a: &str, b: &str) -> bool {
+    a == b || a.starts_with(&format!("{}/", b)) || b.starts_with(&format!("{}/", a))
+}
+
+// Validate and normalize a vault layout before it is migrated to
+pub fn normalize_layout(layout: &VaultLayout) -> Result<VaultLayout, ApiError> {
+    let planning_dir = normalize_layout_dir(&layout.planning_dir)?;
+    let tasks_dir = normalize_layout_dir(&layout.tasks_dir)?;
+    let task_note_name = layout.task_note_name.trim().to_string();
+
+    if task_note_name.contains(['/', '\\', ':'])
+        || task_note_name.starts_with('.')
+        || !task_note_name.to_lowercase().ends_with(".md")
+    {
+        return Err(invalid_layout(
+            "Task note name must be a plain .md file name",
+            &layout.task_note_name,
+        ));
+    }
+    if is_same_or_nested(&planning_dir, &tasks_dir) {
+        return Err(invalid_layout(
+            "Planning and tasks directories must not overlap",
+            &format!("{} / {}", planning_dir, tasks_dir),
+        ));
+    }
+    for dir in [&planning_dir, &tasks_dir] {
+        if is_same_or_nested(dir, SETTINGS_DIR) {
+            return Err(invalid_layout(
+                "Layout directories must not overlap the settings directory",
+                dir,
+            ));
+        }
+    }
+
+    Ok(VaultLayout {
+        planning_dir,
+        tasks_dir,
+        task_note_name,
+    })
+}
+
+// Store a layout the vault has already been migrated to
+pub fn save_vault_layout(vault_root: &Path, layout: VaultLayout) -> Result<(), ApiError> {
+    let mut settings = load_settings(vault_root)?;
+    settings.planning.layout = layout;
+    save_settings(vault_root, &settings)
+}
+
+// Configured vault timezone; unknown names fall back to the system timezone
+pub fn resolve_timezone(planning_settings: &PlanningSettings) -> Option<Tz> {
+    let name = planning_settings.timezone.as_deref()?;
+    match timezone::parse_timezone(name) {
+        Ok(tz) => Some(tz),
+        Err(err) => {
+            tracing::warn!(target: "planning", "ignoring vault timezone: {}", err.message);
+            None
+        }
+    }
+}
+
+fn settings_issue(
+    section: Option<SettingsSection>,
+    path: &str,
+    severity: SettingsIssueSeverity,
+    message: &str,
+) -> SettingsIssue {
+    SettingsIssue {
+        section,
+        path: path.to_string(),
+        severity,
+        message: message.to_string(),
+    }
+}
+
+// Paths of keys in the stored value that decoding ignored, found by comparing it with the
+// decoded value written back out
+fn unknown_keys(stored: &Value, known: &Value, path: &str, out: &mut Vec<String>) {
+    match (stored, known) {
+        (Value::Object(stored), Value::Object(known)) => {
+            for (key, value) in stored {
+                let child = format!("{}.{}", path, key);
+                match known.get(key) {
+                    Some(known) => unknown_keys(value, known, &child, out),
+                    None => out.push(child),
+                }
+            }
+        }
+        (Value::Array(stored), Value::Array(known)) if stored.len() == known.len() => {
+            for (index, (stored, known)) in stored.iter().zip(known).enumerate() {
+                unknown_keys(stored, known, &format!("{}[{}]", path, index), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn decode_section<T: DeserializeOwned + Serialize>(
+    settings: &Value,
+    section: SettingsSection,
+    issues: &mut Vec<SettingsIssue>,
+) -> Option<T> {
+    let stored = settings.get(section.key())?;
+    match serde_json::from_value::<T>(stored.clone()) {
+        Ok(decoded) => {
+            let known = serde_json::to_value(&decoded).unwrap_or(Value::Null);
+            let mut unknown = Vec::new();
+            unknown_keys(stored, &known, section.key(), &mut unknown);
+            for path in unknown {
+                issues.push(settings_issue(
+                    Some(section),
+                    &path,
+                    SettingsIssueSeverity::Warning,
+                    "Unknown setting; it is ignored and dropped on the next save",
+                ));
+            }
+            Some(decoded)
+        }
+        Err(err) => {
+            issues.push(settings_issue(
+                Some(section),
+                section.key(),
+                SettingsIssueSeverity::Error,
+                &err.to_string(),
+            ));
+            None
+        }
+    }
+}
+
+fn ai_issues(ai: &AiSettings, issues: &mut Vec<SettingsIssue>) {
+    let section = Some(SettingsSection::Ai);
+    if !(ai.base_url.starts_with("http://") || ai.base_url.starts_with("https://")) {
+        issues.push(settings_issue(
+            section,
+            "ai.base_url",
+            SettingsIssueSeverity::Error,
+            "Base URL must start with http:// or https://",
+        ));
+    }
+    if ai.reply_tokens >= ai.context_tokens {
+        issues.push(settings_issue(
+            section,
+            "ai.reply_tokens",
+            SettingsIssueSeverity::Error,
+            "Reply tokens must be smaller than the context window",
+        ));
+    }
+    if ai.transcription.provider == TranscriptionProvider::Local
+        && ai.transcription.whisper_model.trim().is_empty()
+    {
+        issues.push(settings_issue(
+            section,
+            "ai.transcription.whisper_model",
+            SettingsIssueSeverity::Warning,
+            "Local transcription needs a whisper model file",
+        ));
+    }
+}
+
+fn planning_issues(planning: &PlanningSettings, issues: &mut Vec<SettingsIssue>) {
+    let section = Some(SettingsSection::Planning);
+    if let Some(name) = planning.timezone.as_deref() {
+        if let Err(err) = timezone::parse_timezone(name) {
+            issues.push(settings_issue(
+                section,
+                "planning.timezone",
+                SettingsIssueSeverity::Error,
+                &err.message,
+            ));
+        }
+    }
+    if planning.week.workdays.is_empty() {
+        issues.push(settings_issue(
+            section,
+            "planning.week.workdays",
+            SettingsIssueSeverity::Error,
+            "At least one workday is required",
+        ));
+    }
+    if planning
+        .daily_log
+        .heading
+        .trim()
+        .trim_start_matches('#')
+        .trim()
+        .is_empty()
+    {
+        issues.push(settings_issue(
+            section,
+            "planning.daily_log.heading",
+            SettingsIssueSeverity::Error,
+            "Daily log heading must not be empty",
+        ));
+    }
+    if planning.done_column.window_days > MAX_DONE_WINDOW_DAYS {
+        issues.push(settings_issue(
+            section,
+            "planning.done_column.window_days",
+            SettingsIssueSeverity::Warning,
+            &format!(
+                "Done window is capped at {} days; use 0 to keep every done task",
+                MAX_DONE_WINDOW_DAYS
+            ),
+        ));
+    }
+    if let Err(err) = normalize_layout(&planning.layout) {
+        issues.push(settings_issue(
+            section,
+            "planning.layout",
+            SettingsIssueSeverity::Error,
+            &err.message,
+        ));
+    }
+}
+
+fn http_api_issues(http_api: &HttpApiSettings, issues: &mut Vec<SettingsIssue>) {
+    let section = Some(SettingsSection::HttpApi);
+    if http_api.port < 1024 {
+        issues.push(settings_issue(
+            section,
+            "http_api.port",
+            SettingsIssueSeverity::Error,
+            "HTTP API port must be 1024 or higher",
+        ));
+    }
+    if http_api.enabled && http_api.token.trim().is_empty() {
+        issues.push(settings_issue(
+            section,
+            "http_api.token",
+            SettingsIssueSeverity::Warning,
+            "HTTP API is enabled without a token",
+        ));
+    }
+}
+
+fn webhook_issues(webhooks: &[WebhookConfig], issues: &mut Vec<SettingsIssue>) {
+    for (index, webhook) in webhooks.iter().enumerate() {
+        let url = webhook.url.trim();
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            issues.push(settings_issue(
+                Some(SettingsSection::Webhooks),
+                &format!("webhooks[{}].url", index),
+                SettingsIssueSeverity::Error,
+                "Webhook URL must start with http:// or https://",
+            ));
+        }
+    }
+}
+
+fn scan_issues(scan: &ScanSettings, issues: &mut Vec<SettingsIssue>) {
+    if let (Some(warning), Some(limit)) = (scan.warning_entries, scan.limit_entries) {
+        if warning > limit {
+            issues.push(settings_issue(
+                Some(SettingsSection::Scan),
+                "scan.warning_entries",
+                SettingsIssueSeverity::Warning,
+                "Warning threshold is above the scan limit and never shows",
+            ));
+        }
+    }
+}
+
+fn lint_issues(lint: &LintSettings, issues: &mut Vec<SettingsIssue>) {
+    if lint.long_paragraphs && lint.max_paragraph_words == 0 {
+        issues.push(settings_issue(
+            Some(SettingsSection::Lint),
+            "lint.max_paragraph_words",
+            SettingsIssueSeverity::Error,
+            "Paragraph word limit must be at least 1",
+        ));
+    }
+}
+
+fn notification_issues(notifications: &NotificationSettings, issues: &mut Vec<SettingsIssue>) {
+    let Some(quiet_hours) = &notifications.quiet_hours else {
+        return;
+    };
+    match quiet_hours.parse() {
+        None => issues.push(settings_issue(
+            Some(SettingsSection::Notifications),
+            "notifications.quiet_hours",
+            SettingsIssueSeverity::Error,
+            "Quiet hours start and end must be times like 22:00",
+        )),
+        Some((start, end)) if start == end => issues.push(settings_issue(
+            Some(SettingsSection::Notifications),
+            "notifications.quiet_hours",
+            SettingsIssueSeverity::Warning,
+            "Quiet hours start and end at the same time and never apply",
+        )),
+        Some(_) => {}
+    }
+}
+
+// Check a layer's settings.json without changing it: whether it decodes, keys the app ignores,
+// and values that cannot work. Migrations are applied in memory first, as on load.
+pub fn validate_settings(
+    vault_root: &Path,
+    layer: SettingsLayer,
+) -> Result<SettingsValidation, ApiError> {
+    let mut settings = match read_layer_value(vault_root, layer) {
+        Ok(Some(settings)) => settings,
+        Ok(None) => {
+            return Ok(SettingsValidation {
+                version: SETTINGS_VERSION,
+                current_version: SETTINGS_VERSION,
+                valid: true,
+                issues: Vec::new(),
+            })
+        }
+        Err(err) if matches!(err.code, ErrorCode::DecodeFailed) => {
+            let message = err
+                .details
+                .as_ref()
+                .and_then(|details| details.get("error"))
+                .and_then(Value::as_str)
+                .unwrap_or(&err.message)
+                .to_string();
+            return Ok(SettingsValidation {
+                version: 0,
+                current_version: SETTINGS_VERSION,
+                valid: false,
+                issues: vec![settings_issue(
+                    None,
+                    "",
+                    SettingsIssueSeverity::Error,
+                    &message,
+                )],
+            });
+        }
+        Err(err) => return Err(err),
+    };
+
+    let version = stored_version(&settings);
+    let mut issues = Vec::new();
+    if !settings.is_object() {
+        issues.push(settings_issue(
+            None,
+            "",
+            SettingsIssueSeverity::Error,
+            "settings.json must hold an object",
+        ));
+    }
+    if version > SETTINGS_VERSION {
+        issues.push(settings_issue(
+            None,
+            "version",
+            SettingsIssueSeverity::Warning,
+            "Settings were written by a newer version of the app; newer settings are dropped on the next save",
+        ));
+    }
+    migrate_settings(&mut settings);
+
+    if let Some(map) = settings.as_object() {
+        for key in map.keys() {
+            if key != "version" && SettingsSection::from_key(key).is_none() {
+                issues.push(settings_issue(
+                    None,
+                    key,
+                    SettingsIssueSeverity::Warning,
+                    "Unknown setting; it is ignored and dropped on the next save",
+                ));
+            }
+        }
+    }
+    for section in SettingsSection::ALL {
+        if !section.allowed_in(layer) {
+            if settings.get(section.key()).is_some() {
+                issues.push(settings_issue(
+                    Some(section),
+                    section.key(),
+                    SettingsIssueSeverity::Warning,
+                    "This section is only read from the vault settings",
+                ));
+            }
+            continue;
+        }
+        match section {
+            SettingsSection::Plugins => {
+                decode_section::<PluginsSettings>(&settings, section, &mut issues);
+            }
+            SettingsSection::Ai => {
+                if let Some(ai) = decode_section::<AiSettings>(&settings, section, &mut issues) {
+                    ai_issues(&ai, &mut issues);
+                }
+            }
+            SettingsSection::Planning => {
+                if let Some(planning) =
+                    decode_section::<PlanningSettings>(&settings, section, &mut issues)
+                {
+                    planning_issues(&planning, &mut issues);
+                }
+            }
+            SettingsSection::HttpApi => {
+                if let Some(http_api) =
+                    decode_section::<HttpApiSettings>(&settings, section, &mut issues)
+                {
+                    http_api_issues(&http_api, &mut issues);
+                }
+            }
+            SettingsSection::Webhooks => {
+                if let Some(webhooks) =
+                    decode_section::<Vec<WebhookConfig>>(&settings, section, &mut issues)
+                {
+                    webhook_issues(&webhooks, &mut issues);
+                }
+            }
+            SettingsSection::Scan => {
+                if let Some(scan) = decode_section::<ScanSettings>(&settings, section, &mut issues)
+                {
+                    scan_issues(&scan, &mut issues);
+                }
+            }
+            SettingsSection::Symlinks => {
+                decode_section::<SymlinkPolicy>(&settings, section, &mut issues);
+            }
+            SettingsSection::Lint => {
+                if let Some(lint) = decode_section::<LintSettings>(&settings, section, &mut issues)
+                {
+                    lint_issues(&lint, &mut issues);
+                }
+            }
+            SettingsSection::Notifications => {
+                if let Some(notifications) =
+                    decode_section::<NotificationSettings>(&settings, section, &mut issues)
+                {
+                    notification_issues(&notifications, &mut issues);
+                }
+            }
+        }
+    }
+
+    Ok(SettingsValidation {
+        version,
+        current_version: SETTINGS_VERSION,
+        valid: !issues
+            .iter()
+            .any(|issue| issue.severity == SettingsIssueSeverity::Error),
+        issues,
+    })
+}
+
+// Put one section back to its defaults in the layer it is read from, leaving the rest of the
+// file as stored. Works on the raw JSON so a section that no longer decodes can be reset.
+pub fn reset_settings_section(
+    vault_root: &Path,
+    section: SettingsSection,
+) -> Result<SettingsValidation, ApiError> {
+    let (mut vault, mut app) = load_layers(vault_root)?;
+    let before = effective_settings(&vault, &app);
+    let layer = section_target(section, &vault, &app);
+    let settings = match layer {
+        SettingsLayer::App => &mut app,
+        SettingsLayer::Vault => &mut vault,
+    };
+    settings[section.key()] = section.default_value();
+    settings["version"] = Value::from(SETTINGS_VERSION);
+    write_layer_value(vault_root, layer, settings)?;
+    notify_settings_changed(vault_root, &before, &effective_settings(&vault, &app));
+    tracing::info!(target: "settings", "settings section reset: section={}, layer={:?}", section.key(), layer);
+    validate_settings(vault_root, layer)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SettingsSectionSource {
+    pub section: SettingsSection,
+    pub layer: Option<SettingsLayer>, // None: neither layer stores it and the defaults apply
+    pub home_layer: SettingsLayer,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SettingsLayersDTO {
+    pub app: Option<Value>, // None when app settings are not available
+    pub vault: Value,
+    pub sources: Vec<SettingsSectionSource>,
+}
+
+// Both layers as stored and where each section of the effective settings comes from
+pub fn get_settings_layers(vault_root: &Path) -> Result<SettingsLayersDTO, ApiError> {
+    let (vault, app) = load_layers(vault_root)?;
+    let sources = SettingsSection::ALL
+        .into_iter()
+        .map(|section| SettingsSectionSource {
+            section,
+            layer: section_source(section, &vault, &app),
+            home_layer: section.home_layer(),
+        })
+        .collect();
+    Ok(SettingsLayersDTO {
+        app: app_settings_dir().get().map(|_| app),
+        vault,
+        sources,
+    })
+}
+
+// Write one section of one layer; None removes it so the other layer or the defaults apply.
+// The value must decode as that section.
+pub fn set_settings_layer_section(
+    vault_root: &Path,
+    layer: SettingsLayer,
+    section: SettingsSection,
+    value: Option<Value>,
+) -> Result<SettingsLayersDTO, ApiError> {
+    if !section.allowed_in(layer) {
+        return Err(ApiError {
+            code: ErrorCode::InvalidInput,
+            message: format!(
+                "The {} settings are only read from the vault",
+                section.key()
+            ),
+            details: Some(serde_json::json!({ "section": section, "layer": layer })),
+        });
+    }
+    if let Some(value) = &value {
+        let mut probe = serde_json::json!({});
+        probe[section.key()] = value.clone();
+        decode_settings(probe)?;
+    }
+
+    let (mut vault, mut app) = load_layers(vault_root)?;
+    let before = effective_settings(&vault, &app);
+    let settings = match layer {
+        SettingsLayer::App => &mut app,
+        SettingsLayer::Vault => &mut vault,
+    };
+    if let Some(map) = settings.as_object_mut() {
+        match value {
+            Some(value) => map.insert(section.key().to_string(), value),
+            None => map.remove(section.key()),
+        };
+        map.insert("version".to_string(), Value::from(SETTINGS_VERSION));
+    }
+    write_layer_value(vault_root, layer, settings)?;
+    notify_settings_changed(vault_root, &before, &effective_settings(&vault, &app));
+    tracing::info!(target: "settings", "settings layer section written: section={}, layer={:?}", section.key(), layer);
+    get_settings_layers(vault_root)
+}
+
+// Move a section to another layer, e.g. AI keys out of a synced vault: its effective value is
+// written to `to` and removed from the other layer
+pub fn move_settings_section(
+    vault_root: &Path,
+    section: SettingsSection,
+    to: SettingsLayer,
+) -> Result<SettingsLayersDTO, ApiError> {
+    let (vault, app) = load_layers(vault_root)?;
+    let value = match section_source(section, &vault, &app) {
+        Some(SettingsLayer::Vault) => vault.get(section.key()).cloned(),
+        Some(SettingsLayer::App) => app.get(section.key()).cloned(),
+        None => None,
+    }
+    .unwrap_or_else(|| section.default_value());
+    let from = match to {
+        SettingsLayer::App => SettingsLayer::Vault,
+        SettingsLayer::Vault => SettingsLayer::App,
+    };
+    set_settings_layer_section(vault_root, to, section, Some(value))?;
+    let from_stores_section = match from {
+        SettingsLayer::App => app.get(section.key()).is_some(),
+        SettingsLayer::Vault => vault.get(section.key()).is_some(),
+    };
+    if from_stores_section && layer_path(vault_root, from).is_some() {
+        set_settings_layer_section(vault_root, from, section, None)?;
+    }
+    get_settings_layers(vault_root)
+}
@@ -1,35 +1,63 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use tauri::State;
+use serde::{Deserialize, Serialize};
 
-use crate::ipc::{map_write_error, ApiError};
+use crate::ipc::{map_write_error, ApiError, ErrorCode};
+use crate::paths::vault_meta_path;
+use crate::repo::settings_repo;
 use crate::security::path_policy;
 use crate::state::VaultState;
 
-pub fn persist_vault(state: &State<'_, VaultState>, vault_root: &Path) -> Result<(), ApiError> {
-    let payload = serde_json::json!({ "vault_root": vault_root.to_string_lossy().to_string() });
+// The app's vault.json: the last opened vault and its id, so a moved vault can be recognized
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PersistedVault {
+    pub vault_root: String,
+    #[serde(default)]
+    pub vault_id: Option<String>, // Missing in files written before relocation support
+}
+
+// Persist the selected vault; clears any pending relocation
+pub fn persist_vault(state: &VaultState, vault_root: &Path) -> Result<(), ApiError> {
+    let payload = PersistedVault {
+        vault_root: vault_root.to_string_lossy().to_string(),
+        vault_id: read_vault_id(vault_root),
+    };
     let data = serde_json::to_string(&payload).map_err(|err| ApiError {
-        code: "WriteFailed".to_string(),
+        code: ErrorCode::WriteFailed,
         message: "Failed to encode vault state".to_string(),
         details: Some(serde_json::json!({ "error": err.to_string() })),
     })?;
     fs::write(&state.config_path, data).map_err(|err| map_write_error("Failed to persist vault", err))?;
+    if let Ok(mut stale) = state.stale.lock() {
+        *stale = None;
+    }
     Ok(())
 }
 
+// Id recorded in the vault's own planning vault.json, if it has one yet
+pub fn read_vault_id(vault_root: &Path) -> Option<String> {
+    let layout = settings_repo::get_planning_settings(vault_root).ok()?.layout;
+    let data = fs::read_to_string(vault_meta_path(vault_root, &layout)).ok()?;
+    let meta = serde_json::from_str::<serde_json::Value>(&data).ok()?;
+    meta.get("vault_id")?.as_str().map(str::to_string)
+}
+
+pub fn read_persisted(config_path: &Path) -> Option<PersistedVault> {
+    let data = fs::read_to_string(config_path).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+// The persisted vault when its folder no longer resolves (moved, renamed, drive letter changed)
+pub fn load_stale_vault(config_path: &Path) -> Option<PersistedVault> {
+    read_persisted(config_path).filter(|persisted| {
+        validate_vault_path(Path::new(&persisted.vault_root)).is_none()
+    })
+}
+
 pub fn load_persisted_vault(config_path: &Path) -> Option<PathBuf> {
-    if let Ok(data) = fs::read_to_string(config_path) {
-        if let Ok(payload) = serde_json::from_str::<serde_json::Value>(&data) {
-            if let Some(vault_root) = payload.get("vault_root").and_then(|v| v.as_str()) {
-                let path = PathBuf::from(vault_root);
-                if let Some(validated) = validate_vault_path(&path) {
-                    return Some(validated);
-                }
-            }
-        }
-    }
-    None
+    let persisted = read_persisted(config_path)?;
+    validate_vault_path(&PathBuf::from(persisted.vault_root))
 }
 
 fn validate_vault_path(path: &Path) -> Option<PathBuf> {
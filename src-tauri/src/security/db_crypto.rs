@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+use crate::ipc::{ApiError, ErrorCode};
+
+// Marks a column value sealed by FieldCipher; anything else is stored plaintext
+const SEALED_PREFIX: &str = "enc:v1:";
+// PBKDF2-HMAC-SHA256 rounds for turning the passphrase into a key
+const KDF_ROUNDS: u32 = 600_000;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+// AES-256-GCM over sensitive planning columns, keyed by the vault passphrase
+#[derive(Clone)]
+pub struct FieldCipher {
+    key: [u8; 32],
+}
+
+fn crypto_error(message: &str) -> ApiError {
+    ApiError {
+        code: ErrorCode::DatabaseError,
+        message: message.to_string(),
+        details: None,
+    }
+}
+
+impl FieldCipher {
+    pub fn derive(passphrase: &str, salt: &[u8]) -> Self {
+        let mut key = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, KDF_ROUNDS, &mut key);
+        Self { key }
+    }
+
+    // Hash stored next to the salt to tell a wrong passphrase from a right one
+    pub fn verifier(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(b"planning-db-verifier");
+        hasher.update(self.key);
+        format!("{:x}", hasher.finalize())
+    }
+
+    pub fn seal(&self, plaintext: &str) -> Result<String, ApiError> {
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.key));
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| crypto_error("Failed to encrypt value"))?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(format!(
+            "{}{}",
+            SEALED_PREFIX,
+            base64::engine::general_purpose::STANDARD.encode(sealed)
+        ))
+    }
+
+    // Plaintext of a sealed value; values written before encryption pass through unchanged
+    pub fn open(&self, value: &str) -> Result<String, ApiError> {
+        let Some(encoded) = value.strip_prefix(SEALED_PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let sealed = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|_| crypto_error("Encrypted value is malformed"))?;
+        if sealed.len() < NONCE_LEN {
+            return Err(crypto_error("Encrypted value is malformed"));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.key));
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| crypto_error("Failed to decrypt value"))?;
+        String::from_utf8(plaintext).map_err(|_| crypto_error("Decrypted value is not UTF-8"))
+    }
+}
+
+pub fn is_sealed(value: &str) -> bool {
+    value.starts_with(SEALED_PREFIX)
+}
+
+pub fn new_salt() -> String {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    base64::engine::general_purpose::STANDARD.encode(salt)
+}
+
+pub fn decode_salt(salt: &str) -> Result<Vec<u8>, ApiError> {
+    base64::engine::general_purpose::STANDARD
+        .decode(salt)
+        .map_err(|_| crypto_error("Stored encryption salt is malformed"))
+}
+
+// Keys of unlocked vaults, kept in memory until locked or the app exits
+fn unlocked() -> &'static Mutex<HashMap<PathBuf, FieldCipher>> {
+    static UNLOCKED: OnceLock<Mutex<HashMap<PathBuf, FieldCipher>>> = OnceLock::new();
+    UNLOCKED.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn poisoned() -> ApiError {
+    ApiError {
+        code: ErrorCode::MutexPoisoned,
+        message: "Database key registry poisoned".to_string(),
+        details: None,
+    }
+}
+
+pub fn unlock(vault_root: &Path, cipher: FieldCipher) -> Result<(), ApiError> {
+    let mut keys = unlocked().lock().map_err(|_| poisoned())?;
+    keys.insert(vault_root.to_path_buf(), cipher);
+    Ok(())
+}
+
+pub fn lock(vault_root: &Path) -> Result<(), ApiError> {
+    let mut keys = unlocked().lock().map_err(|_| poisoned())?;
+    keys.remove(vault_root);
+    Ok(())
+}
+
+pub fn unlocked_cipher(vault_root: &Path) -> Result<Option<FieldCipher>, ApiError> {
+    let keys = unlocked().lock().map_err(|_| poisoned())?;
+    Ok(keys.get(vault_root).cloned())
+}
@@ -1,2 +1,3 @@
+pub mod db_crypto;
+pub mod note_locks;
 pub mod path_policy;
-
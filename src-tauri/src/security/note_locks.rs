@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::domain::links::normalize_note_path;
+use crate::ipc::{ApiError, ErrorCode};
+
+// A lock the editor stops refreshing (crash, closed window) lapses after this long
+const LOCK_TTL: Duration = Duration::from_secs(5 * 60);
+
+// Who is writing a note; only the editor may write a note it has locked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WriteOrigin {
+    #[default]
+    Editor,
+    Plugin,
+    Ai,
+    Background, // Jobs inside the app, e.g. daily note logging
+}
+
+struct NoteLock {
+    refreshed: Instant,
+    locked_at: u64, // Unix seconds
+}
+
+fn registry() -> &'static Mutex<HashMap<PathBuf, NoteLock>> {
+    static LOCKS: OnceLock<Mutex<HashMap<PathBuf, NoteLock>>> = OnceLock::new();
+    LOCKS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn lock_key(vault_root: &Path, rel_path: &Path) -> Result<PathBuf, ApiError> {
+    let normalized = normalize_note_path(&rel_path.to_string_lossy()).ok_or_else(|| ApiError {
+        code: ErrorCode::InvalidInput,
+        message: "Invalid note path".to_string(),
+        details: Some(serde_json::json!({ "path": rel_path.to_string_lossy() })),
+    })?;
+    Ok(vault_root.join(normalized))
+}
+
+fn poisoned() -> ApiError {
+    ApiError {
+        code: ErrorCode::MutexPoisoned,
+        message: "Note lock registry poisoned".to_string(),
+        details: None,
+    }
+}
+
+// Take or refresh the editor's lock on a note; returns when it was first locked
+pub fn acquire(vault_root: &Path, rel_path: &Path) -> Result<u64, ApiError> {
+    let key = lock_key(vault_root, rel_path)?;
+    let mut locks = registry().lock().map_err(|_| poisoned())?;
+    locks.retain(|_, lock| lock.refreshed.elapsed() < LOCK_TTL);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let lock = locks.entry(key).or_insert(NoteLock {
+        refreshed: Instant::now(),
+        locked_at: now,
+    });
+    lock.refreshed = Instant::now();
+    Ok(lock.locked_at)
+}
+
+pub fn release(vault_root: &Path, rel_path: &Path) -> Result<(), ApiError> {
+    let key = lock_key(vault_root, rel_path)?;
+    let mut locks = registry().lock().map_err(|_| poisoned())?;
+    locks.remove(&key);
+    Ok(())
+}
+
+// Fail with NoteLocked when a note the editor holds is written from anywhere else
+pub fn ensure_writable(
+    vault_root: &Path,
+    rel_path: &Path,
+    origin: WriteOrigin,
+) -> Result<(), ApiError> {
+    if origin == WriteOrigin::Editor {
+        return Ok(());
+    }
+    let key = lock_key(vault_root, rel_path)?;
+    let locks = registry().lock().map_err(|_| poisoned())?;
+    match locks.get(&key) {
+        Some(lock) if lock.refreshed.elapsed() < LOCK_TTL => Err(ApiError {
+            code: ErrorCode::NoteLocked,
+            message: "Note is open in the editor".to_string(),
+            details: Some(serde_json::json!({
+                "path": rel_path.to_string_lossy(),
+                "locked_at": lock.locked_at,
+            })),
+        }),
+        _ => Ok(()),
+    }
+}
@@ -1,12 +1,69 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::ipc::{map_io_error, ApiError};
+use serde::{Deserialize, Serialize};
+
+use crate::ipc::{map_io_error, ApiError, ErrorCode};
+use crate::paths::nfc;
+use crate::repo::settings_repo;
+
+// Which symlinks inside a vault may be followed; set per vault in settings.json
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "kebab-case")]
+pub enum SymlinkPolicy {
+    #[default]
+    Deny,
+    AllowWithinVault,                 // Links whose target resolves inside the vault
+    AllowList { paths: Vec<String> }, // Exactly these vault-relative links, wherever they point
+}
+
+// Canonical target of a symlink inside the vault when the vault's policy lets it be followed
+fn allowed_symlink_target(vault_root: &Path, link: &Path) -> Option<PathBuf> {
+    let policy = settings_repo::get_symlink_policy(vault_root);
+    if matches!(policy, SymlinkPolicy::Deny) {
+        return None;
+    }
+    let canonical_root = vault_root.canonicalize().ok()?;
+    let target = link.canonicalize().ok()?;
+    match policy {
+        SymlinkPolicy::Deny => None,
+        SymlinkPolicy::AllowWithinVault => target.starts_with(&canonical_root).then_some(target),
+        SymlinkPolicy::AllowList { paths } => {
+            let rel = link.strip_prefix(vault_root).or_else(|_| link.strip_prefix(&canonical_root)).ok()?;
+            let rel = rel.components().map(|part| part.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+            paths
+                .iter()
+                .any(|path| path.replace('\\', "/").trim_matches('/') == rel)
+                .then_some(target)
+        }
+    }
+}
+
+pub fn symlink_allowed(vault_root: &Path, link: &Path) -> bool {
+    allowed_symlink_target(vault_root, link).is_some()
+}
+
+// Entry next to a missing path whose name differs only in Unicode normalization, e.g. an NFD name
+// synced from macOS looked up with the NFC form
+fn unicode_equivalent_sibling(path: &Path) -> Option<PathBuf> {
+    let name = nfc(&path.file_name()?.to_string_lossy());
+    let parent = path.parent()?;
+    fs::read_dir(parent)
+        .ok()?
+        .flatten()
+        .find(|entry| nfc(&entry.file_name().to_string_lossy()) == name)
+        .map(|entry| parent.join(entry.file_name()))
+}
+
+// Inside the vault, or under the target of a symlink the policy allowed on the way
+fn within_vault(canonical_path: &Path, canonical_root: &Path, allowed_targets: &[PathBuf]) -> bool {
+    canonical_path.starts_with(canonical_root) || allowed_targets.iter().any(|target| canonical_path.starts_with(target))
+}
 
 fn validate_rel_no_parent(rel_path: &Path) -> Result<(), ApiError> {
     if rel_path.is_absolute() {
         return Err(ApiError {
-            code: "PathOutsideVault".to_string(),
+            code: ErrorCode::PathOutsideVault,
             message: "Absolute paths are not allowed".to_string(),
             details: None,
         });
@@ -16,14 +73,14 @@ fn validate_rel_no_parent(rel_path: &Path) -> Result<(), ApiError> {
         match component {
             std::path::Component::ParentDir => {
                 return Err(ApiError {
-                    code: "PathOutsideVault".to_string(),
+                    code: ErrorCode::PathOutsideVault,
                     message: "Parent directory (..) is not allowed".to_string(),
                     details: Some(serde_json::json!({ "path": rel_path.to_string_lossy().to_string() })),
                 });
             }
             std::path::Component::Prefix(_) => {
                 return Err(ApiError {
-                    code: "PathOutsideVault".to_string(),
+                    code: ErrorCode::PathOutsideVault,
                     message: "Path prefix is not allowed".to_string(),
                     details: Some(serde_json::json!({ "path": rel_path.to_string_lossy().to_string() })),
                 });
@@ -42,10 +99,10 @@ pub fn ensure_no_symlink(path: &Path) -> Result<(), ApiError> {
             continue;
         }
         let meta = fs::symlink_metadata(&current)
-            .map_err(|err| map_io_error("Unknown", "Metadata failed", err))?;
+            .map_err(|err| map_io_error(ErrorCode::Unknown, "Metadata failed", err))?;
         if meta.file_type().is_symlink() {
             return Err(ApiError {
-                code: "SymlinkNotAllowed".to_string(),
+                code: ErrorCode::SymlinkNotAllowed,
                 message: "Symlink path is not allowed".to_string(),
                 details: Some(serde_json::json!({ "path": path.to_string_lossy().to_string() })),
             });
@@ -71,20 +128,30 @@ pub fn resolve_existing_path(vault_root: &Path, rel_path: &Path) -> Result<PathB
     validate_rel_no_parent(rel_path)?;
 
     let mut current = vault_root.to_path_buf();
+    let mut allowed_targets = Vec::new();
     for component in rel_path.components() {
         current.push(component);
+        if !current.exists() {
+            if let Some(sibling) = unicode_equivalent_sibling(&current) {
+                current = sibling;
+            }
+        }
         if !current.exists() {
             return Err(ApiError {
-                code: "NotFound".to_string(),
+                code: ErrorCode::NotFound,
                 message: "Path does not exist".to_string(),
                 details: Some(serde_json::json!({ "path": rel_path.to_string_lossy().to_string() })),
             });
         }
         let meta = fs::symlink_metadata(&current)
-            .map_err(|err| map_io_error("Unknown", "Metadata failed", err))?;
+            .map_err(|err| map_io_error(ErrorCode::Unknown, "Metadata failed", err))?;
         if meta.file_type().is_symlink() {
+            if let Some(target) = allowed_symlink_target(vault_root, &current) {
+                allowed_targets.push(target);
+                continue;
+            }
             return Err(ApiError {
-                code: "SymlinkNotAllowed".to_string(),
+                code: ErrorCode::SymlinkNotAllowed,
                 message: "Symlink path is not allowed".to_string(),
                 details: Some(serde_json::json!({ "path": rel_path.to_string_lossy().to_string() })),
             });
@@ -93,14 +160,14 @@ pub fn resolve_existing_path(vault_root: &Path, rel_path: &Path) -> Result<PathB
 
     let canonical_root = vault_root
         .canonicalize()
-        .map_err(|err| map_io_error("Unknown", "Vault resolve failed", err))?;
+        .map_err(|err| map_io_error(ErrorCode::Unknown, "Vault resolve failed", err))?;
     let canonical_path = current
         .canonicalize()
-        .map_err(|err| map_io_error("Unknown", "Path resolve failed", err))?;
+        .map_err(|err| map_io_error(ErrorCode::Unknown, "Path resolve failed", err))?;
 
-    if !canonical_path.starts_with(&canonical_root) {
+    if !within_vault(&canonical_path, &canonical_root, &allowed_targets) {
         return Err(ApiError {
-            code: "PathOutsideVault".to_string(),
+            code: ErrorCode::PathOutsideVault,
             message: "Path is outside vault".to_string(),
             details: Some(serde_json::json!({ "path": rel_path.to_string_lossy().to_string() })),
         });
@@ -112,10 +179,10 @@ pub fn resolve_existing_path(vault_root: &Path, rel_path: &Path) -> Result<PathB
 pub fn resolve_existing_dir(vault_root: &Path, rel_path: &Path) -> Result<PathBuf, ApiError> {
     let resolved = resolve_existing_path(vault_root, rel_path)?;
     let metadata = fs::metadata(&resolved)
-        .map_err(|err| map_io_error("Unknown", "Metadata failed", err))?;
+        .map_err(|err| map_io_error(ErrorCode::Unknown, "Metadata failed", err))?;
     if !metadata.is_dir() {
         return Err(ApiError {
-            code: "NotFound".to_string(),
+            code: ErrorCode::NotFound,
             message: "Path is not a directory".to_string(),
             details: Some(serde_json::json!({ "path": rel_path.to_string_lossy().to_string() })),
         });
@@ -126,13 +193,17 @@ pub fn resolve_existing_dir(vault_root: &Path, rel_path: &Path) -> Result<PathBu
 pub fn ensure_abs_file_in_vault(vault_root: &Path, abs_path: &Path) -> Result<PathBuf, ApiError> {
     let canonical_root = vault_root
         .canonicalize()
-        .map_err(|err| map_io_error("Unknown", "Vault resolve failed", err))?;
+        .map_err(|err| map_io_error(ErrorCode::Unknown, "Vault resolve failed", err))?;
     let canonical_path = abs_path
         .canonicalize()
-        .map_err(|err| map_io_error("Unknown", "Path resolve failed", err))?;
+        .map_err(|err| map_io_error(ErrorCode::Unknown, "Path resolve failed", err))?;
     if !canonical_path.starts_with(&canonical_root) {
+        // Possibly reached through a symlink the vault's policy allows
+        if let Ok(rel_path) = abs_path.strip_prefix(vault_root).or_else(|_| abs_path.strip_prefix(&canonical_root)) {
+            return resolve_existing_path(vault_root, rel_path);
+        }
         return Err(ApiError {
-            code: "PathOutsideVault".to_string(),
+            code: ErrorCode::PathOutsideVault,
             message: "Path is outside vault".to_string(),
             details: Some(serde_json::json!({ "path": abs_path.to_string_lossy().to_string() })),
         });
@@ -141,11 +212,25 @@ pub fn ensure_abs_file_in_vault(vault_root: &Path, abs_path: &Path) -> Result<Pa
     Ok(canonical_path)
 }
 
+// Fail instead of creating anything when the vault folder itself is missing; creating its
+// sub-directories would otherwise rebuild the vault on whatever drive now holds that path
+pub fn ensure_vault_present(vault_root: &Path) -> Result<(), ApiError> {
+    if vault_root.is_dir() {
+        return Ok(());
+    }
+    Err(ApiError {
+        code: ErrorCode::VaultUnavailable,
+        message: "Vault folder is unavailable".to_string(),
+        details: Some(serde_json::json!({ "vault_root": vault_root.to_string_lossy() })),
+    })
+}
+
 pub fn ensure_or_create_dir_in_vault(vault_root: &Path, abs_dir: &Path) -> Result<(), ApiError> {
+    ensure_vault_present(vault_root)?;
     for component in abs_dir.components() {
         if matches!(component, std::path::Component::ParentDir) {
             return Err(ApiError {
-                code: "PathOutsideVault".to_string(),
+                code: ErrorCode::PathOutsideVault,
                 message: "Parent directory (..) is not allowed".to_string(),
                 details: Some(serde_json::json!({ "path": abs_dir.to_string_lossy().to_string() })),
             });
@@ -154,7 +239,7 @@ pub fn ensure_or_create_dir_in_vault(vault_root: &Path, abs_dir: &Path) -> Resul
 
     let canonical_root = vault_root
         .canonicalize()
-        .map_err(|err| map_io_error("Unknown", "Vault resolve failed", err))?;
+        .map_err(|err| map_io_error(ErrorCode::Unknown, "Vault resolve failed", err))?;
 
     let abs_dir = if abs_dir.is_absolute() {
         abs_dir.to_path_buf()
@@ -163,7 +248,7 @@ pub fn ensure_or_create_dir_in_vault(vault_root: &Path, abs_dir: &Path) -> Resul
     };
     if !abs_dir.starts_with(vault_root) {
         return Err(ApiError {
-            code: "PathOutsideVault".to_string(),
+            code: ErrorCode::PathOutsideVault,
             message: "Path is outside vault".to_string(),
             details: Some(serde_json::json!({ "path": abs_dir.to_string_lossy().to_string() })),
         });
@@ -173,13 +258,14 @@ pub fn ensure_or_create_dir_in_vault(vault_root: &Path, abs_dir: &Path) -> Resul
     validate_rel_no_parent(rel_dir)?;
 
     let mut current = canonical_root.clone();
+    let mut allowed_targets = Vec::new();
     for component in rel_dir.components() {
         match component {
             std::path::Component::CurDir => continue,
             std::path::Component::Normal(part) => current.push(part),
             _ => {
                 return Err(ApiError {
-                    code: "PathOutsideVault".to_string(),
+                    code: ErrorCode::PathOutsideVault,
                     message: "Invalid path component".to_string(),
                     details: Some(serde_json::json!({ "path": rel_dir.to_string_lossy().to_string() })),
                 })
@@ -187,18 +273,22 @@ pub fn ensure_or_create_dir_in_vault(vault_root: &Path, abs_dir: &Path) -> Resul
         }
 
         if current.exists() {
-            let meta = fs::symlink_metadata(&current)
-                .map_err(|err| map_io_error("Unknown", "Metadata failed", err))?;
+            let mut meta = fs::symlink_metadata(&current)
+                .map_err(|err| map_io_error(ErrorCode::Unknown, "Metadata failed", err))?;
             if meta.file_type().is_symlink() {
-                return Err(ApiError {
-                    code: "SymlinkNotAllowed".to_string(),
-                    message: "Symlink path is not allowed".to_string(),
-                    details: Some(serde_json::json!({ "path": current.to_string_lossy().to_string() })),
-                });
+                let Some(target) = allowed_symlink_target(&canonical_root, &current) else {
+                    return Err(ApiError {
+                        code: ErrorCode::SymlinkNotAllowed,
+                        message: "Symlink path is not allowed".to_string(),
+                        details: Some(serde_json::json!({ "path": current.to_string_lossy().to_string() })),
+                    });
+                };
+                meta = fs::metadata(&target).map_err(|err| map_io_error(ErrorCode::Unknown, "Metadata failed", err))?;
+                allowed_targets.push(target);
             }
             if !meta.is_dir() {
                 return Err(ApiError {
-                    code: "WriteFailed".to_string(),
+                    code: ErrorCode::WriteFailed,
                     message: "Path component is not a directory".to_string(),
                     details: Some(serde_json::json!({ "path": current.to_string_lossy().to_string() })),
                 });
@@ -207,15 +297,15 @@ pub fn ensure_or_create_dir_in_vault(vault_root: &Path, abs_dir: &Path) -> Resul
         }
 
         fs::create_dir(&current)
-            .map_err(|err| map_io_error("WriteFailed", "Failed to create directory", err))?;
+            .map_err(|err| map_io_error(ErrorCode::WriteFailed, "Failed to create directory", err))?;
     }
 
     let canonical_dir = abs_dir
         .canonicalize()
-        .map_err(|err| map_io_error("Unknown", "Path resolve failed", err))?;
-    if !canonical_dir.starts_with(&canonical_root) {
+        .map_err(|err| map_io_error(ErrorCode::Unknown, "Path resolve failed", err))?;
+    if !within_vault(&canonical_dir, &canonical_root, &allowed_targets) {
         return Err(ApiError {
-            code: "PathOutsideVault".to_string(),
+            code: ErrorCode::PathOutsideVault,
             message: "Path is outside vault".to_string(),
             details: Some(serde_json::json!({ "path": abs_dir.to_string_lossy().to_string() })),
         });
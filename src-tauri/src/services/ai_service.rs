@@ -1,8 +1,35 @@
-use crate::ipc::ApiError;
-use crate::repo::settings_repo::AiSettings;
-use reqwest::Client;
+use std::path::{Path, PathBuf};
+use std::time::Duration as StdDuration;
+
+use crate::features::ai::rate_limit;
+use crate::ipc::{ApiError, ErrorCode};
+use crate::repo::planning_repo::PlanningRepo;
+use crate::repo::settings_repo::{self, AiSettings, PromptOverflow};
+use base64::prelude::*;
+use chrono::{Duration, Utc};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tiktoken_rs::cl100k_base_singleton;
+use tracing::{info, warn};
+
+// Framing tokens chat models add around each message, plus the reply primer
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+const REPLY_PRIMER_TOKENS: usize = 3;
+// Summarizing more chunks than this costs more than the input is worth; the rest is truncated
+const MAX_SUMMARY_CHUNKS: usize = 12;
+// Doubled after every failed attempt: 1s, 2s, 4s, ...
+const RETRY_BASE_DELAY: StdDuration = StdDuration::from_secs(1);
+// Longer waits are handed back to the caller as RateLimited instead of stalling the command
+const MAX_RETRY_WAIT: StdDuration = StdDuration::from_secs(30);
+
+const OCR_PROMPT: &str = "Transcribe all text in this image exactly as written, in reading order, as plain markdown. \
+Keep lists and checkboxes as markdown lists. Reply with the transcribed text only, or nothing if there is no text.";
+
+const CHUNK_SUMMARY_PROMPT: &str = "You condense part of a longer document. Summarize the text you are given in the same language, \
+keeping every action item, decision, owner, date and estimate verbatim. Reply with the summary only.";
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Message {
@@ -16,11 +43,114 @@ struct ChatCompletionRequest {
     messages: Vec<Message>,
     #[serde(skip_serializing_if = "Option::is_none")]
     temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<usize>,
 }
 
 #[derive(Deserialize, Debug)]
 struct ChatCompletionResponse {
     choices: Vec<ChatChoice>,
+    #[serde(default)]
+    usage: Option<ProviderUsage>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ProviderUsage {
+    prompt_tokens: usize,
+    completion_tokens: usize,
+}
+
+// Tokens spent by one AI operation, summed over every request it made
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TokenUsage {
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+    pub total_tokens: usize,
+    pub requests: usize,
+    pub cached_requests: usize, // Answered from the response cache, no tokens spent
+    pub estimated: bool,        // Counted locally for requests whose provider reported no usage
+    pub input_tokens: usize,    // Size of the caller's input before it was fitted to the budget
+    pub truncated: bool,        // Part of the input was dropped
+    pub summarized_chunks: usize, // Input chunks summarized before the final prompt
+}
+
+impl TokenUsage {
+    fn add(&mut self, other: &TokenUsage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+        self.requests += other.requests;
+        self.cached_requests += other.cached_requests;
+        self.estimated |= other.estimated;
+    }
+}
+
+// Reply of a budgeted completion
+pub struct ChatOutput {
+    pub content: String,
+    pub usage: TokenUsage,
+}
+
+// Token count under cl100k; other providers' tokenizers land within a few percent of it
+pub fn count_tokens(text: &str) -> usize {
+    cl100k_base_singleton().encode_ordinary(text).len()
+}
+
+pub fn count_message_tokens(messages: &[Message]) -> usize {
+    messages
+        .iter()
+        .map(|message| count_tokens(&message.content) + MESSAGE_OVERHEAD_TOKENS)
+        .sum::<usize>()
+        + REPLY_PRIMER_TOKENS
+}
+
+// Longest prefix of text within max_tokens, cut on a char boundary
+pub fn truncate_to_tokens(text: &str, max_tokens: usize) -> &str {
+    let bpe = cl100k_base_singleton();
+    let tokens = bpe.encode_ordinary(text);
+    if tokens.len() <= max_tokens {
+        return text;
+    }
+    let mut end: usize = bpe
+        ._decode_native_and_split(tokens[..max_tokens].to_vec())
+        .map(|bytes| bytes.len())
+        .sum();
+    end = end.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+// Split text into chunks of at most max_tokens, breaking between lines where possible
+pub fn split_into_chunks(text: &str, max_tokens: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_tokens = 0;
+    for line in text.split_inclusive('\n') {
+        let mut line = line;
+        let mut line_tokens = count_tokens(line);
+        if current_tokens + line_tokens > max_tokens && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            current_tokens = 0;
+        }
+        // A single line longer than a chunk is cut into pieces
+        while line_tokens > max_tokens {
+            let head = truncate_to_tokens(line, max_tokens);
+            if head.is_empty() {
+                break;
+            }
+            chunks.push(head.to_string());
+            line = &line[head.len()..];
+            line_tokens = count_tokens(line);
+        }
+        current.push_str(line);
+        current_tokens += line_tokens;
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
 }
 
 #[derive(Deserialize, Debug)]
@@ -31,62 +161,427 @@ struct ChatChoice {
 pub struct AiService {
     client: Client,
     settings: AiSettings,
+    cache_root: Option<PathBuf>, // Vault whose planning database caches replies
 }
 
 impl AiService {
     pub fn new(client: Client, settings: AiSettings) -> Self {
-        Self { client, settings }
+        Self {
+            client,
+            settings,
+            cache_root: None,
+        }
     }
 
-    pub async fn chat_completion(&self, messages: Vec<Message>) -> Result<String, ApiError> {
-        let url = format!(
-            "{}/chat/completions",
-            self.settings.base_url.trim_end_matches('/')
-        );
+    // Reuse replies to identical requests for cache_ttl_hours
+    pub fn with_cache(mut self, vault_root: &Path) -> Self {
+        if self.settings.cache_ttl_hours > 0 {
+            self.cache_root = Some(vault_root.to_path_buf());
+        }
+        self
+    }
 
-        let request_body = ChatCompletionRequest {
-            model: self.settings.model_name.clone(),
-            messages,
-            temperature: Some(0.7), // Default temperature
+    // Run a system prompt over input that may exceed the model context. Oversized input is
+    // truncated or summarized chunk by chunk according to the overflow setting.
+    pub async fn complete_with_budget(
+        &self,
+        system_prompt: &str,
+        input: &str,
+    ) -> Result<ChatOutput, ApiError> {
+        let mut usage = TokenUsage {
+            input_tokens: count_tokens(input),
+            ..TokenUsage::default()
+        };
+        let budget = self.input_budget(system_prompt)?;
+
+        let mut fitted = std::borrow::Cow::Borrowed(input);
+        if usage.input_tokens > budget {
+            if self.settings.overflow == PromptOverflow::Summarize {
+                let chunk_budget = self.input_budget(CHUNK_SUMMARY_PROMPT)?;
+                let mut chunks = split_into_chunks(input, chunk_budget);
+                if chunks.len() > MAX_SUMMARY_CHUNKS {
+                    chunks.truncate(MAX_SUMMARY_CHUNKS);
+                    usage.truncated = true;
+                }
+                let mut summaries = Vec::with_capacity(chunks.len());
+                for chunk in chunks {
+                    let (summary, chunk_usage) = self
+                        .send(prompt_messages(CHUNK_SUMMARY_PROMPT, &chunk))
+                        .await?;
+                    usage.add(&chunk_usage);
+                    usage.summarized_chunks += 1;
+                    summaries.push(summary);
+                }
+                fitted = std::borrow::Cow::Owned(summaries.join("\n\n"));
+            }
+            let head = truncate_to_tokens(&fitted, budget);
+            if head.len() < fitted.len() {
+                usage.truncated = true;
+                fitted = std::borrow::Cow::Owned(head.to_string());
+            }
+            info!(target: "ai", "prompt input fitted: input_tokens={}, budget={}, summarized_chunks={}, truncated={}", usage.input_tokens, budget, usage.summarized_chunks, usage.truncated);
+        }
+
+        let (content, final_usage) = self.send(prompt_messages(system_prompt, &fitted)).await?;
+        usage.add(&final_usage);
+        Ok(ChatOutput { content, usage })
+    }
+
+    // Tokens left for user input once the system prompt, framing and reply reserve are counted
+    fn input_budget(&self, system_prompt: &str) -> Result<usize, ApiError> {
+        let fixed =
+            count_message_tokens(&prompt_messages(system_prompt, "")) + self.settings.reply_tokens;
+        match self.settings.context_tokens.checked_sub(fixed) {
+            Some(budget) if budget > 0 => Ok(budget),
+            _ => Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "AI context window is too small for the prompt".to_string(),
+                details: Some(serde_json::json!({
+                    "context_tokens": self.settings.context_tokens,
+                    "reply_tokens": self.settings.reply_tokens,
+                    "prompt_tokens": fixed - self.settings.reply_tokens,
+                })),
+            }),
+        }
+    }
+
+    fn cache_repo(&self) -> Option<PlanningRepo> {
+        let vault_root = self.cache_root.as_ref()?;
+        let layout = settings_repo::get_planning_settings(vault_root)
+            .unwrap_or_default()
+            .layout;
+        // The cache is an optimisation; any failure just means a live request
+        PlanningRepo::new(vault_root, &layout)
+            .map_err(|e| warn!(target: "ai", "ai cache unavailable: {}", e.message))
+            .ok()
+    }
+
+    fn cached_reply(&self, prompt_hash: &str) -> Option<String> {
+        let now = Utc::now().to_rfc3339();
+        self.cache_repo()?
+            .get_ai_cache(&self.settings.model_name, prompt_hash, &now)
+            .map_err(|e| warn!(target: "ai", "ai cache read failed: {}", e.message))
+            .ok()
+            .flatten()
+    }
+
+    fn cache_reply(&self, prompt_hash: &str, content: &str) {
+        let Some(repo) = self.cache_repo() else {
+            return;
         };
+        let now = Utc::now();
+        let expires_at = now + Duration::hours(self.settings.cache_ttl_hours as i64);
+        if let Err(e) = repo.put_ai_cache(
+            &self.settings.model_name,
+            prompt_hash,
+            content,
+            &now.to_rfc3339(),
+            &expires_at.to_rfc3339(),
+        ) {
+            warn!(target: "ai", "ai cache write failed: {}", e.message);
+        }
+    }
+
+    // POST under the requests-per-minute ceiling. 429, 5xx and connection failures are retried
+    // with exponential backoff, waiting at least as long as the provider's Retry-After.
+    // `body` attaches the payload to each attempt; multipart forms cannot be reused
+    async fn post_with_retry(
+        &self,
+        url: &str,
+        body: impl Fn(RequestBuilder) -> RequestBuilder,
+    ) -> Result<Response, ApiError> {
+        let mut attempts = 0;
+        loop {
+            rate_limit::acquire(&self.settings.base_url, self.settings.requests_per_minute)
+                .map_err(|retry_after| rate_limited(retry_after, "local", attempts))?;
+            attempts += 1;
+
+            let mut request_builder = body(self.client.post(url));
+            if !self.settings.api_key.is_empty() {
+                request_builder = request_builder
+                    .header("Authorization", format!("Bearer {}", self.settings.api_key));
+            }
 
-        let mut request_builder = self.client.post(&url).json(&request_body);
+            let (error, retry_after) = match request_builder.send().await {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) => {
+                    let status = response.status();
+                    let retry_after = retry_after(&response);
+                    let error_text = response.text().await.unwrap_or_default();
+                    if status == StatusCode::TOO_MANY_REQUESTS {
+                        let wait = retry_after.unwrap_or(backoff(attempts));
+                        if attempts > self.settings.max_retries || wait > MAX_RETRY_WAIT {
+                            return Err(rate_limited(wait, "provider", attempts));
+                        }
+                    }
+                    let error = ApiError {
+                        code: ErrorCode::AiProviderError,
+                        message: format!("AI provider returned error: {}", error_text),
+                        details: Some(serde_json::json!({
+                            "status": status.as_u16(),
+                            "attempts": attempts,
+                        })),
+                    };
+                    if !status.is_server_error() && status != StatusCode::TOO_MANY_REQUESTS {
+                        return Err(error);
+                    }
+                    (error, retry_after)
+                }
+                Err(e) => {
+                    let transient = e.is_timeout() || e.is_connect();
+                    let error = ApiError {
+                        code: ErrorCode::AiRequestFailed,
+                        message: format!("Failed to send request to AI provider: {}", e),
+                        details: Some(serde_json::json!({ "attempts": attempts })),
+                    };
+                    if !transient {
+                        return Err(error);
+                    }
+                    (error, None)
+                }
+            };
+            if attempts > self.settings.max_retries {
+                return Err(error);
+            }
 
-        if !self.settings.api_key.is_empty() {
-            request_builder = request_builder
-                .header("Authorization", format!("Bearer {}", self.settings.api_key));
+            let delay = retry_after.unwrap_or_default().max(backoff(attempts));
+            warn!(target: "ai", "ai request failed, retrying: attempt={}, delay_ms={}, error={}", attempts, delay.as_millis(), error.message);
+            tokio::time::sleep(delay).await;
         }
+    }
+
+    // Speech to text through the provider's OpenAI-compatible transcription endpoint
+    pub async fn transcribe(&self, file_name: &str, audio: &[u8]) -> Result<String, ApiError> {
+        let url = format!(
+            "{}/audio/transcriptions",
+            self.settings.base_url.trim_end_matches('/')
+        );
+        let transcription = &self.settings.transcription;
+        let response = self
+            .post_with_retry(&url, |request| {
+                let mut form = Form::new()
+                    .part(
+                        "file",
+                        Part::bytes(audio.to_vec()).file_name(file_name.to_string()),
+                    )
+                    .text("model", transcription.model.clone())
+                    .text("response_format", "json");
+                if let Some(language) = &transcription.language {
+                    form = form.text("language", language.clone());
+                }
+                request.multipart(form)
+            })
+            .await?;
 
-        let response = request_builder.send().await.map_err(|e| ApiError {
-            code: "AiRequestFailed".to_string(),
-            message: format!("Failed to send request to AI provider: {}", e),
+        #[derive(Deserialize)]
+        struct TranscriptionResponse {
+            text: String,
+        }
+        let body: TranscriptionResponse = response.json().await.map_err(|e| ApiError {
+            code: ErrorCode::AiParseFailed,
+            message: format!("Failed to parse transcription response: {}", e),
             details: None,
         })?;
+        info!(target: "ai", "audio transcribed: model={}, audio_bytes={}, chars={}", transcription.model, audio.len(), body.text.chars().count());
+        Ok(body.text)
+    }
 
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
+    // Transcribe the text in an image with a vision-capable chat model
+    pub async fn read_image_text(
+        &self,
+        mime: &str,
+        image: &[u8],
+    ) -> Result<(String, TokenUsage), ApiError> {
+        let url = format!(
+            "{}/chat/completions",
+            self.settings.base_url.trim_end_matches('/')
+        );
+        let model = if self.settings.ocr.model.is_empty() {
+            &self.settings.model_name
+        } else {
+            &self.settings.ocr.model
+        };
+        let image_url = format!("data:{};base64,{}", mime, BASE64_STANDARD.encode(image));
+        let request_body = serde_json::json!({
+            "model": model,
+            "messages": [{
+                "role": "user",
+                "content": [
+                    { "type": "text", "text": OCR_PROMPT },
+                    { "type": "image_url", "image_url": { "url": image_url } },
+                ],
+            }],
+            "temperature": 0.0,
+            "max_tokens": self.settings.reply_tokens,
+        });
+
+        let response = self
+            .post_with_retry(&url, |request| request.json(&request_body))
+            .await?;
+        let response_body: ChatCompletionResponse =
+            response.json().await.map_err(|e| ApiError {
+                code: ErrorCode::AiParseFailed,
+                message: format!("Failed to parse AI response: {}", e),
+                details: None,
+            })?;
+        let Some(choice) = response_body.choices.first() else {
             return Err(ApiError {
-                code: "AiProviderError".to_string(),
-                message: format!("AI provider returned error: {}", error_text),
+                code: ErrorCode::AiEmptyResponse,
+                message: "AI provider returned no choices".to_string(),
                 details: None,
             });
+        };
+
+        let content = choice.message.content.clone();
+        // Image tokens are provider specific, so only reported usage is counted
+        let usage = match &response_body.usage {
+            Some(reported) => TokenUsage {
+                prompt_tokens: reported.prompt_tokens,
+                completion_tokens: reported.completion_tokens,
+                total_tokens: reported.prompt_tokens + reported.completion_tokens,
+                requests: 1,
+                ..TokenUsage::default()
+            },
+            None => TokenUsage {
+                requests: 1,
+                ..TokenUsage::default()
+            },
+        };
+        info!(target: "ai", "image text read: model={}, image_bytes={}, chars={}", model, image.len(), content.chars().count());
+        Ok((content, usage))
+    }
+
+    async fn send(&self, messages: Vec<Message>) -> Result<(String, TokenUsage), ApiError> {
+        let prompt_tokens = count_message_tokens(&messages);
+        let url = format!(
+            "{}/chat/completions",
+            self.settings.base_url.trim_end_matches('/')
+        );
+
+        let request_body = ChatCompletionRequest {
+            model: self.settings.model_name.clone(),
+            messages,
+            temperature: Some(0.7), // Default temperature
+            max_tokens: Some(self.settings.reply_tokens),
+        };
+
+        let prompt_hash = self
+            .cache_root
+            .as_ref()
+            .map(|_| request_hash(&self.settings.base_url, &request_body));
+        if let Some(content) = prompt_hash
+            .as_deref()
+            .and_then(|hash| self.cached_reply(hash))
+        {
+            let usage = TokenUsage {
+                cached_requests: 1,
+                ..TokenUsage::default()
+            };
+            return Ok((content, usage));
         }
 
+        let response = self
+            .post_with_retry(&url, |request| request.json(&request_body))
+            .await?;
+
         let response_body: ChatCompletionResponse =
             response.json().await.map_err(|e| ApiError {
-                code: "AiParseFailed".to_string(),
+                code: ErrorCode::AiParseFailed,
                 message: format!("Failed to parse AI response: {}", e),
                 details: None,
             })?;
 
         if let Some(choice) = response_body.choices.first() {
-            Ok(choice.message.content.clone())
+            let content = choice.message.content.clone();
+            let usage = match response_body.usage {
+                Some(reported) => TokenUsage {
+                    prompt_tokens: reported.prompt_tokens,
+                    completion_tokens: reported.completion_tokens,
+                    total_tokens: reported.prompt_tokens + reported.completion_tokens,
+                    requests: 1,
+                    ..TokenUsage::default()
+                },
+                None => {
+                    let completion_tokens = count_tokens(&content);
+                    TokenUsage {
+                        prompt_tokens,
+                        completion_tokens,
+                        total_tokens: prompt_tokens + completion_tokens,
+                        requests: 1,
+                        estimated: true,
+                        ..TokenUsage::default()
+                    }
+                }
+            };
+            if let Some(hash) = &prompt_hash {
+                self.cache_reply(hash, &content);
+            }
+            Ok((content, usage))
         } else {
             Err(ApiError {
-                code: "AiEmptyResponse".to_string(),
+                code: ErrorCode::AiEmptyResponse,
                 message: "AI provider returned no choices".to_string(),
                 details: None,
             })
         }
     }
 }
+
+fn prompt_messages(system_prompt: &str, input: &str) -> Vec<Message> {
+    vec![
+        Message {
+            role: "system".to_string(),
+            content: system_prompt.to_string(),
+        },
+        Message {
+            role: "user".to_string(),
+            content: input.to_string(),
+        },
+    ]
+}
+
+// Hash of everything that shapes the reply: endpoint, model, messages and sampling options
+fn request_hash(base_url: &str, request: &ChatCompletionRequest) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(base_url.trim_end_matches('/').as_bytes());
+    hasher.update([0]);
+    hasher.update(serde_json::to_vec(request).unwrap_or_default());
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn backoff(attempts: u32) -> StdDuration {
+    (RETRY_BASE_DELAY * 2u32.saturating_pow(attempts.saturating_sub(1))).min(MAX_RETRY_WAIT)
+}
+
+// Retry-After as delay seconds or an HTTP date
+fn retry_after(response: &Response) -> Option<StdDuration> {
+    let value = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(StdDuration::from_secs(seconds));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (at.with_timezone(&Utc) - Utc::now()).to_std().ok()
+}
+
+// `source` is "local" when the configured ceiling refused the request, "provider" for a 429
+fn rate_limited(retry_after: StdDuration, source: &str, attempts: u32) -> ApiError {
+    let retry_after_secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    ApiError {
+        code: ErrorCode::RateLimited,
+        message: format!("AI rate limit reached, retry in {}s", retry_after_secs),
+        details: Some(serde_json::json!({
+            "retry_after_secs": retry_after_secs,
+            "source": source,
+            "attempts": attempts,
+        })),
+    }
+}
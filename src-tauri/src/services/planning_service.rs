@@ -1,19 +1,91 @@
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
-use chrono::Utc;
-use tauri::AppHandle;
+use chrono::{NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use tauri::{AppHandle, Manager};
 use tracing::{error, info, span, warn, Level};
 use uuid::Uuid;
 
+use crate::domain::analytics::{self, EstimateReportDTO};
+use crate::domain::board::{
+    self, BoardColumn, BoardColumnInput, BoardIndexConfig, BoardIndexResult, BoardMeta,
+    DoneTasksPage, ExportBoardResponse,
+};
+use crate::domain::calendar::{self, CalendarRangeDTO};
+use crate::domain::capture::{
+    self, ImageCaptureResponse, LineRange, NoteCaptureResponse, SmartCaptureResponse,
+    VoiceCaptureResponse,
+};
+use crate::domain::clipping::READ_LATER_TAG;
+use crate::domain::daily_log::{self, DailyLogConfig, DailyTemplateData, TemplateTask};
+use crate::domain::export::{
+    ImportResult, MergeVaultResult, PlanningExport, EXPORT_FORMAT, EXPORT_VERSION,
+};
+use crate::domain::frontmatter;
+use crate::domain::github::{self, GithubBoardLink, GithubIssueLink};
+use crate::domain::journal::{self, JournalStreakDTO, MissingDay, StreakBasis};
+use crate::domain::links::{self, TaskNoteLink};
+use crate::domain::note_query::{self, IndexedNote, NoteQueryInput, NoteQueryResult};
+use crate::domain::notifications::{
+    Notification, NotificationKind, NotificationSettings, NotificationState,
+    DISMISSED_RETENTION_DAYS,
+};
+use crate::domain::outliner_import::{
+    self, OutlinerFormat, OutlinerImportResponse, OutlinerJournal,
+};
+use crate::domain::plaintext_export::{self, BoardTasks, PlainTextExportResponse, PlainTextFormat};
 use crate::domain::planning::{
-    CreateTaskInput, OpenDailyInput, OpenDailyResponse, OpenTaskNoteResponse, ReorderTaskInput,
-    Task, TaskStatus, TodayDTO, UpdateTaskInput,
+    BatchCreateItem, BatchCreateResponse, CreateTaskInput, DanglingTimer, DayLog,
+    DbEncryptionStatus, HealthCheckReport, LayoutMigrationResult, MdSyncPolicy, OpenDailyInput,
+    OpenDailyResponse, OpenTaskNoteResponse, ReorderTaskInput, SlugReassignment, SlugRepairResult,
+    Task, TaskDueBrief, TaskNoteConflict, TaskNoteConsolidation, TaskNoteMove, TaskPeriodicity,
+    TaskStatus, Timer, TimerRecoveryAction, TimerRecoveryResult, TodayDTO, UpdateTaskInput,
+    TIMER_SOURCE_MANUAL_ENTRY,
+};
+use crate::domain::reading_list::{self, ReadingItem, ReadingItemTaskResponse, ReadingStatus};
+use crate::domain::recurrence;
+use crate::domain::reminders::{self, Reminder, ReminderRepeat};
+use crate::domain::rules::{self, EscalationRule, RulesRunResult};
+use crate::domain::schedule::{
+    self, DayScheduleDTO, ScheduleBlock, ScheduleTaskInput, ScheduleTaskResponse,
+    ShiftScheduleResponse,
+};
+use crate::domain::search::{self, TaskSearchHit};
+use crate::domain::sprint::{self, Sprint, SprintBurndown, SprintInput, SprintSummaryDTO};
+use crate::domain::table_import::{self, TableImportResponse, TableMapping};
+use crate::domain::tagging::{self, TagCount, TagSuggestionResponse};
+use crate::domain::task_fields::{
+    self, BoardField, BoardFieldInput, FieldValuesByTask, TaskFieldFilter, TaskFieldValue,
+    TaskFieldsDTO,
+};
+use crate::domain::timezone;
+use crate::domain::webhooks::{WebhookDelivery, WebhookEvent};
+use crate::domain::webview::{
+    WebviewHistoryEntry, WebviewSession, WebviewStatePayload, WEBVIEW_SESSIONS_KEY,
 };
-use crate::ipc::ApiError;
-use crate::paths::{generate_slug, task_dir_path};
-use crate::repo::{planning_md_repo::PlanningMdRepo, planning_repo::PlanningRepo, settings_repo};
-use crate::services::ai_service::{AiService, Message};
+use crate::domain::week::{WeekConfig, WeekReviewDTO, WeekReviewDay};
+use crate::domain::workspace::{
+    self, FavoriteDTO, FavoriteKind, FavoriteRef, LayoutInput, LayoutSummary, NoteCursor,
+    RecentNote, RecentNoteDTO, WorkspaceLayout, WorkspaceStateDTO, DEFAULT_RECENT_NOTES_LIMIT,
+    LAYOUTS_KEY,
+};
+use crate::features::ai::embedding::EmbeddingEngine;
+use crate::features::ai::{ocr, transcription};
+use crate::features::webhooks;
+use crate::ipc::{map_io_error, map_read_error, ApiError, ErrorCode};
+use crate::paths::{self, generate_slug, task_dir_path, VaultLayout};
+use crate::repo::{
+    planning_md_repo::PlanningMdRepo,
+    planning_repo::{self, PlanningRepo},
+    settings_repo,
+};
+use crate::security::db_crypto::{self, FieldCipher};
+use crate::security::note_locks::WriteOrigin;
+use crate::security::path_policy;
+use crate::services::ai_service::AiService;
+use crate::services::vault_service;
+use crate::state::BoardIndexState;
 use reqwest::Client;
 
 const SMART_CAPTURE_SYSTEM_PROMPT: &str = r#"
@@ -38,25 +110,766 @@ Example Output:
 Return ONLY valid JSON.
 "#;
 
+const TAG_SUGGEST_SYSTEM_PROMPT: &str = r#"
+You are an AI assistant that tags tasks and notes.
+Choose tags that describe the input text, preferring the existing tags listed below.
+Propose at most {max_new} tags that are not in the list, and only when no existing tag fits.
+Return a JSON object with a "tags" key containing an array of objects with:
+- tag: string (single word or hyphenated, no leading #)
+- confidence: number (0.0 to 1.0, how well the tag fits)
+
+Existing tags:
+{vocabulary}
+
+Example Output:
+{ "tags": [ { "tag": "work", "confidence": 0.9 }, { "tag": "budget-review", "confidence": 0.6 } ] }
+Return ONLY valid JSON.
+"#;
+// Most used tags offered to the model as the vocabulary
+const TAG_VOCABULARY_LIMIT: usize = 200;
+
+// Parse an inclusive YYYY-MM-DD range, rejecting reversed ranges
+fn parse_day_range(
+    from: &str,
+    to: &str,
+) -> Result<(chrono::NaiveDate, chrono::NaiveDate), ApiError> {
+    let parse = |value: &str| {
+        chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|e| ApiError {
+            code: ErrorCode::DateTimeError,
+            message: format!("Failed to parse date: {}", e),
+            details: Some(serde_json::json!({ "value": value })),
+        })
+    };
+    let from_date = parse(from)?;
+    let to_date = parse(to)?;
+    if from_date > to_date {
+        return Err(ApiError {
+            code: ErrorCode::DateTimeError,
+            message: "from must not be after to".to_string(),
+            details: Some(serde_json::json!({ "from": from, "to": to })),
+        });
+    }
+    Ok((from_date, to_date))
+}
+
+fn sprint_not_found(id: &str) -> ApiError {
+    ApiError {
+        code: ErrorCode::NotFound,
+        message: "Sprint not found".to_string(),
+        details: Some(serde_json::json!({ "id": id })),
+    }
+}
+
+fn rename_error(message: &str, from: &Path, to: &Path, err: Option<std::io::Error>) -> ApiError {
+    ApiError {
+        code: ErrorCode::FileRenameError,
+        message: match err {
+            Some(err) => format!("{}: {}", message, err),
+            None => message.to_string(),
+        },
+        details: Some(serde_json::json!({
+            "from": from.to_string_lossy().to_string(),
+            "to": to.to_string_lossy().to_string(),
+        })),
+    }
+}
+
+// Move one path into place for a layout change, refusing to overwrite anything
+fn move_for_layout(
+    vault_root: &Path,
+    from: &Path,
+    to: &Path,
+    moves: &mut Vec<(std::path::PathBuf, std::path::PathBuf)>,
+) -> Result<(), ApiError> {
+    if to.exists() {
+        return Err(rename_error(
+            "Migration target already exists",
+            from,
+            to,
+            None,
+        ));
+    }
+    if let Some(parent) = to.parent() {
+        path_policy::ensure_or_create_dir_in_vault(vault_root, parent)?;
+    }
+    std::fs::rename(from, to)
+        .map_err(|err| rename_error("Failed to move for layout change", from, to, Some(err)))?;
+    moves.push((from.to_path_buf(), to.to_path_buf()));
+    Ok(())
+}
+
+// Move the planning directory, tasks directory and task notes to a new layout and record it.
+// Runs without an open PlanningService because the database itself may move.
+pub fn migrate_vault_layout(
+    vault_root: &Path,
+    layout: VaultLayout,
+) -> Result<LayoutMigrationResult, ApiError> {
+    let new_layout = settings_repo::normalize_layout(&layout)?;
+    let old_layout = settings_repo::get_planning_settings(vault_root)?.layout;
+
+    let mut result = LayoutMigrationResult {
+        layout: new_layout.clone(),
+        moved_dirs: Vec::new(),
+        renamed_notes: 0,
+        updated_tasks: 0,
+    };
+    if new_layout == old_layout {
+        return Ok(result);
+    }
+
+    let mut moves = Vec::new();
+    let migrated = (|| -> Result<(), ApiError> {
+        for (old_dir, new_dir) in [
+            (&old_layout.planning_dir, &new_layout.planning_dir),
+            (&old_layout.tasks_dir, &new_layout.tasks_dir),
+        ] {
+            if old_dir == new_dir || !vault_root.join(old_dir).exists() {
+                continue;
+            }
+            let from = path_policy::resolve_existing_dir(vault_root, Path::new(old_dir))?;
+            move_for_layout(vault_root, &from, &vault_root.join(new_dir), &mut moves)?;
+            result
+                .moved_dirs
+                .push(format!("{} -> {}", old_dir, new_dir));
+        }
+
+        let tasks_root = paths::tasks_dir(vault_root, &new_layout);
+        if old_layout.task_note_name != new_layout.task_note_name && tasks_root.is_dir() {
+            let entries = std::fs::read_dir(&tasks_root)
+                .map_err(|err| map_io_error(ErrorCode::IoError, "Failed to list tasks", err))?;
+            for entry in entries.flatten() {
+                let task_dir = entry.path();
+                let old_note = task_dir.join(&old_layout.task_note_name);
+                if !task_dir.is_dir() || !old_note.is_file() {
+                    continue;
+                }
+                path_policy::ensure_no_symlink(&old_note)?;
+                move_for_layout(
+                    vault_root,
+                    &old_note,
+                    &task_dir.join(&new_layout.task_note_name),
+                    &mut moves,
+                )?;
+                result.renamed_notes += 1;
+            }
+        }
+
+        let db_repo = PlanningRepo::new(vault_root, &new_layout)?;
+        let md_paths: Vec<(String, String)> = db_repo
+            .list_all_tasks()?
+            .into_iter()
+            .filter_map(|task| {
+                let slug = task.task_dir_slug.as_deref()?;
+                let md_rel_path = paths::task_md_relative_path(&new_layout, &task.id, slug);
+                Some((task.id, md_rel_path))
+            })
+            .collect();
+        // The new paths commit only once the layout is saved, so a failed save rolls them back
+        // together with the file moves
+        db_repo.in_transaction(|| {
+            db_repo.rewrite_md_rel_paths(&md_paths)?;
+            settings_repo::save_vault_layout(vault_root, new_layout.clone())
+        })?;
+        result.updated_tasks = md_paths.len();
+        Ok(())
+    })();
+
+    if let Err(e) = migrated {
+        // Put everything back so the old layout stays usable
+        for (from, to) in moves.iter().rev() {
+            if let Err(err) = std::fs::rename(to, from) {
+                warn!(target: "planning", "layout rollback failed: from={}, to={}, error={}", to.display(), from.display(), err);
+            }
+        }
+        // The commit can fail after the layout was saved
+        if let Err(err) = settings_repo::save_vault_layout(vault_root, old_layout) {
+            warn!(target: "planning", "layout rollback failed: error={}", err.message);
+        }
+        error!(target: "planning", "migrate_vault_layout failed: error_code={}, error_message={}", &e.code, &e.message);
+        return Err(e);
+    }
+
+    info!(target: "planning", "migrate_vault_layout succeeded: moved_dirs={}, renamed_notes={}, updated_tasks={}", result.moved_dirs.len(), result.renamed_notes, result.updated_tasks);
+    Ok(result)
+}
+
+// Shortest passphrase accepted for database encryption
+const MIN_PASSPHRASE_CHARS: usize = 8;
+
+fn wrong_passphrase() -> ApiError {
+    ApiError {
+        code: ErrorCode::PermissionDenied,
+        message: "Wrong passphrase".to_string(),
+        details: None,
+    }
+}
+
+// Key for the encrypted database of a vault, checked against the stored verifier
+fn derive_db_cipher(db_repo: &PlanningRepo, passphrase: &str) -> Result<FieldCipher, ApiError> {
+    let (Some(salt), Some(verifier)) = (
+        db_repo.get_vault_meta_value(planning_repo::ENCRYPTION_SALT_KEY)?,
+        db_repo.get_vault_meta_value(planning_repo::ENCRYPTION_VERIFIER_KEY)?,
+    ) else {
+        return Err(ApiError {
+            code: ErrorCode::InvalidStateTransition,
+            message: "Planning database is not encrypted".to_string(),
+            details: None,
+        });
+    };
+    let cipher = FieldCipher::derive(passphrase, &db_crypto::decode_salt(&salt)?);
+    if cipher.verifier() != verifier {
+        return Err(wrong_passphrase());
+    }
+    Ok(cipher)
+}
+
+pub fn db_encryption_status(vault_root: &Path) -> Result<DbEncryptionStatus, ApiError> {
+    let layout = settings_repo::get_planning_settings(vault_root)?.layout;
+    let encrypted = PlanningRepo::open_sealed(vault_root, &layout)?.is_encrypted()?;
+    Ok(DbEncryptionStatus {
+        encrypted,
+        unlocked: !encrypted || db_crypto::unlocked_cipher(vault_root)?.is_some(),
+    })
+}
+
+// Keep the database key in memory so the encrypted database can be opened until locked
+pub fn unlock_planning_db(vault_root: &Path, passphrase: &str) -> Result<(), ApiError> {
+    let layout = settings_repo::get_planning_settings(vault_root)?.layout;
+    let db_repo = PlanningRepo::open_sealed(vault_root, &layout)?;
+    let cipher = derive_db_cipher(&db_repo, passphrase).inspect_err(|e| {
+        warn!(target: "planning", "unlock_planning_db failed: error_code={}", &e.code);
+    })?;
+    db_crypto::unlock(vault_root, cipher)?;
+    info!(target: "planning", "unlock_planning_db succeeded");
+    Ok(())
+}
+
+pub fn lock_planning_db(vault_root: &Path) -> Result<(), ApiError> {
+    db_crypto::lock(vault_root)
+}
+
+// Encrypt task descriptions of an existing plaintext database under a passphrase.
+// Returns how many tasks were encrypted.
+pub fn enable_db_encryption(vault_root: &Path, passphrase: &str) -> Result<usize, ApiError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(ApiError {
+            code: ErrorCode::InvalidInput,
+            message: format!(
+                "Passphrase must be at least {} characters",
+                MIN_PASSPHRASE_CHARS
+            ),
+            details: None,
+        });
+    }
+    let layout = settings_repo::get_planning_settings(vault_root)?.layout;
+    let mut db_repo = PlanningRepo::open_sealed(vault_root, &layout)?;
+    if db_repo.is_encrypted()? {
+        return Err(ApiError {
+            code: ErrorCode::InvalidStateTransition,
+            message: "Planning database is already encrypted".to_string(),
+            details: None,
+        });
+    }
+    let salt = db_crypto::new_salt();
+    let cipher = FieldCipher::derive(passphrase, &db_crypto::decode_salt(&salt)?);
+    let sealed = db_repo.enable_encryption(cipher.clone(), &salt)?;
+    db_crypto::unlock(vault_root, cipher)?;
+    info!(target: "planning", "enable_db_encryption succeeded: tasks={}", sealed);
+    Ok(sealed)
+}
+
+// Decrypt the database back to plaintext; needs the current passphrase
+pub fn disable_db_encryption(vault_root: &Path, passphrase: &str) -> Result<usize, ApiError> {
+    let layout = settings_repo::get_planning_settings(vault_root)?.layout;
+    let mut db_repo = PlanningRepo::open_sealed(vault_root, &layout)?;
+    let cipher = derive_db_cipher(&db_repo, passphrase)?;
+    let opened = db_repo.disable_encryption(&cipher)?;
+    db_crypto::lock(vault_root)?;
+    info!(target: "planning", "disable_db_encryption succeeded: tasks={}", opened);
+    Ok(opened)
+}
+
+// Refresh the card preview of the task whose note was just written; other notes are ignored
+pub fn refresh_task_note_preview(vault_root: &Path, rel_path: &str) -> Result<(), ApiError> {
+    let layout = settings_repo::get_planning_settings(vault_root)
+        .unwrap_or_default()
+        .layout;
+    let is_task_note = rel_path
+        .strip_prefix(&format!("{}/", layout.tasks_dir))
+        .and_then(|rest| rest.split_once('/'))
+        .is_some_and(|(_, name)| name == layout.task_note_name);
+    if !is_task_note {
+        return Ok(());
+    }
+    let service = PlanningService::open(vault_root)?;
+    if let Some(task) = service.db_repo.find_task_by_md_path(rel_path)? {
+        let body = service
+            .md_repo
+            .read_task_body(&task.id, task.task_dir_slug.as_deref().unwrap_or("task"))?;
+        service.refresh_task_preview(&task, &body)?;
+    }
+    Ok(())
+}
+
+// Files of an outliner import, relative to its root and sorted. Hidden folders are skipped, as is
+// the logseq folder of a graph, which holds its config and backups.
+fn outliner_files(root: &Path, format: OutlinerFormat) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(root.join(&dir)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let rel = dir.join(&name);
+            if file_type.is_dir() {
+                let skipped = name.starts_with('.')
+                    || (format == OutlinerFormat::Logseq
+                        && dir.as_os_str().is_empty()
+                        && name == "logseq");
+                if !skipped {
+                    pending.push(rel);
+                }
+            } else if file_type.is_file()
+                && rel
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case(format.extension()))
+            {
+                files.push(rel);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+// Temp files younger than this may belong to a write still in progress
+const STALE_TEMP_AGE: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+// Startup cleanup: write journaled note syncs, truncate the WAL, drop temp files of
+// interrupted atomic writes and remove task directories left empty. A failed step is reported
+// and the others still run.
+pub fn run_health_check(vault_root: &Path) -> HealthCheckReport {
+    let mut report = HealthCheckReport::default();
+    let layout = settings_repo::get_planning_settings(vault_root)
+        .unwrap_or_default()
+        .layout;
+    match PlanningService::open(vault_root).and_then(|service| service.flush_md_sync_journal()) {
+        Ok(replayed) => report.replayed_md_syncs = replayed,
+        Err(e) => report.errors.push(e.message),
+    }
+    match PlanningRepo::open_sealed(vault_root, &layout) {
+        Ok(repo) => {
+            match repo.checkpoint() {
+                Ok(()) => report.checkpointed = true,
+                Err(e) => report.errors.push(e.message),
+            }
+            if !repo.has_slug_index().unwrap_or(true) {
+                report.errors.push(
+                    "Some tasks share a directory slug; run planning_repair_task_slugs".to_string(),
+                );
+            }
+        }
+        Err(e) => report.errors.push(e.message),
+    }
+    report.removed_temp_files = vault_service::prune_stale_temp_files(vault_root, STALE_TEMP_AGE);
+
+    let tasks_root = paths::tasks_dir(vault_root, &layout);
+    if let Ok(entries) = std::fs::read_dir(&tasks_root) {
+        for entry in entries.flatten() {
+            if !entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                continue;
+            }
+            let dir = entry.path();
+            let empty = std::fs::read_dir(&dir).is_ok_and(|mut inner| inner.next().is_none());
+            if !empty {
+                continue;
+            }
+            match std::fs::remove_dir(&dir) {
+                Ok(()) => report.removed_task_dirs.push(format!(
+                    "{}/{}",
+                    layout.tasks_dir,
+                    entry.file_name().to_string_lossy()
+                )),
+                Err(err) => report.errors.push(format!(
+                    "Failed to remove empty task directory {}: {}",
+                    dir.display(),
+                    err
+                )),
+            }
+        }
+    }
+    info!(target: "planning", "health check finished: checkpointed={}, replayed_md_syncs={}, removed_temp_files={}, removed_task_dirs={}, errors={}", report.checkpointed, report.replayed_md_syncs, report.removed_temp_files.len(), report.removed_task_dirs.len(), report.errors.len());
+    report
+}
+
+// Vault-relative directory for board exports
+const BOARD_EXPORT_DIR: &str = "exports";
+
+// vault_meta key set once stored schedule times have been converted to UTC instants
+const SCHEDULE_UTC_KEY: &str = "schedule_times_utc";
+
+// vault_meta key holding the last heartbeat recorded while a timer was running
+const LAST_ACTIVITY_KEY: &str = "last_activity_at";
+
+// Flat task notes of the earliest layout, "<planning_dir>/tasks/<id>.md"
+const LEGACY_TASK_NOTES_DIR: &str = "tasks";
+
+// Most task notes reorder_tasks rewrites at once
+const MD_SYNC_WORKERS: usize = 4;
+// Journaled custom fields are recorded as "field:<key>" next to the system fields
+const CUSTOM_FIELD_SYNC_PREFIX: &str = "field:";
+
+// Largest page planning_list_done returns
+const MAX_DONE_PAGE_SIZE: i64 = 200;
+// Most tasks planning_create_tasks_batch creates at once
+const MAX_BATCH_TASKS: usize = 1000;
+// Longest list planning_list_overdue and planning_list_upcoming return
+const MAX_DUE_BRIEFS: usize = 200;
+// How far ahead planning_list_upcoming looks by default, and at most
+pub const DEFAULT_UPCOMING_DAYS: i64 = 7;
+const MAX_UPCOMING_DAYS: i64 = 90;
+
 // Planning service that handles business logic
+// Initial markdown note written for a new (or restored) task
+fn task_md_template(task: &Task) -> String {
+    format!(
+        "---
+fm_version: 2
+id: {}
+title: {}
+status: {}
+priority: {}
+tags: {}
+estimate_min: {}
+due_date: {}
+color: {}
+created_at: {}
+updated_at: {}
+---
+
+<!-- 
+Frontmatter 由系统维护；正文为你的笔记区。
+-->
+
+## Notes
+
+- 
+",
+        task.id,
+        task.title,
+        task.status,
+        task.priority
+            .map(|p| p.to_string())
+            .unwrap_or("p3".to_string()),
+        task.tags
+            .as_ref()
+            .map(|tags| format!("[{}]", tags.join(", ")))
+            .unwrap_or("[]".to_string()),
+        task.estimate_min
+            .map(|min| min.to_string())
+            .unwrap_or("null".to_string()),
+        task.due_date.as_deref().unwrap_or("null"),
+        color_frontmatter(task.color.as_deref()),
+        task.created_at,
+        task.updated_at
+    )
+}
+
+// Quoted, since a bare #rrggbb reads as a YAML comment
+fn color_frontmatter(color: Option<&str>) -> String {
+    color
+        .map(|color| format!("\"{}\"", color))
+        .unwrap_or("null".to_string())
+}
+
+// Client op_id as looked up and recorded; surrounding whitespace is dropped and blank ids
+// are treated as absent
+fn normalized_op_id(op_id: Option<&str>) -> Option<&str> {
+    op_id.map(str::trim).filter(|op_id| !op_id.is_empty())
+}
+
+// Frontmatter values of a task for the given fields, as written to its note
+fn task_frontmatter_updates<S: AsRef<str>>(task: &Task, fields: &[S]) -> HashMap<String, String> {
+    let mut updates = HashMap::new();
+    for field in fields {
+        let field = field.as_ref();
+        let value = match field {
+            "updated_at" => task.updated_at.clone(),
+            "title" => task.title.clone(),
+            "status" => task.status.to_string(),
+            "priority" => task
+                .priority
+                .map(|p| p.to_string())
+                .unwrap_or("p3".to_string()),
+            "tags" => format!("[{}]", task.tags.clone().unwrap_or_default().join(", ")),
+            "estimate_min" => task
+                .estimate_min
+                .map(|min| min.to_string())
+                .unwrap_or("null".to_string()),
+            "due_date" => task.due_date.as_deref().unwrap_or("null").to_string(),
+            "color" => color_frontmatter(task.color.as_deref()),
+            _ => continue,
+        };
+        updates.insert(field.to_string(), value);
+    }
+    updates
+}
+
 pub struct PlanningService {
     db_repo: PlanningRepo,
     md_repo: PlanningMdRepo,
+    timezone: Option<Tz>, // Vault timezone; None falls back to the system timezone
+    week: WeekConfig,
+    rules: Vec<EscalationRule>,
+    daily_log: DailyLogConfig,
+    done_window_days: u32,
+    md_sync: MdSyncPolicy,
+    board_index: BoardIndexConfig,
+    vault_root: PathBuf,
+    vault_id: String,
+    app_handle: Option<AppHandle>, // Set inside the app; enables webhooks
 }
 
 impl PlanningService {
     // Create a new instance of PlanningService
-    pub fn new(_app_handle: &AppHandle, vault_root: &Path) -> Result<Self, ApiError> {
-        let db_repo = PlanningRepo::new(vault_root)?;
-        let md_repo = PlanningMdRepo::new(vault_root)?;
+    pub fn new(app_handle: &AppHandle, vault_root: &Path) -> Result<Self, ApiError> {
+        let mut service = Self::open(vault_root)?;
+        service.app_handle = Some(app_handle.clone());
+        Ok(service)
+    }
+
+    // Open a vault's planning data without a running app, e.g. from the CLI
+    pub fn open(vault_root: &Path) -> Result<Self, ApiError> {
+        // Unreadable settings fall back to defaults rather than blocking planning
+        let planning_settings =
+            settings_repo::get_planning_settings(vault_root).unwrap_or_default();
+
+        let db_repo = PlanningRepo::new(vault_root, &planning_settings.layout)?;
+        let md_repo = PlanningMdRepo::new(vault_root, &planning_settings.layout)?;
 
         // Ensure vault_id exists
-        db_repo.ensure_vault_id(vault_root)?;
+        let vault_id = db_repo.ensure_vault_id(vault_root)?;
+        let timezone = settings_repo::resolve_timezone(&planning_settings);
+
+        // Schedules of earlier versions were stored as wall-clock times; convert them once
+        if db_repo.get_vault_meta_value(SCHEDULE_UTC_KEY)?.is_none() {
+            let converted = db_repo.normalize_schedule_times(timezone)?;
+            db_repo.set_vault_meta_value(SCHEDULE_UTC_KEY, "1")?;
+            info!(target: "planning", "schedule times converted to UTC: tasks={}", converted);
+        }
+
+        Ok(Self {
+            db_repo,
+            md_repo,
+            timezone,
+            week: planning_settings.week,
+            rules: planning_settings.rules,
+            daily_log: planning_settings.daily_log,
+            done_window_days: planning_settings
+                .done_column
+                .window_days
+                .min(board::MAX_DONE_WINDOW_DAYS),
+            md_sync: planning_settings.md_sync,
+            board_index: planning_settings.board_index,
+            vault_root: vault_root.to_path_buf(),
+            vault_id,
+            app_handle: None,
+        })
+    }
+
+    // Fire webhooks for a task event; a no-op outside the app
+    // Side effects of a task reaching done: webhooks and the daily note entry
+    fn task_completed(&self, task_id: &str) {
+        self.emit_task_event(WebhookEvent::Completed, task_id);
+        if !self.daily_log.enabled {
+            return;
+        }
+        let result = self.db_repo.get_task(task_id).and_then(|task| {
+            let Some(task) = task else {
+                return Ok(());
+            };
+            let at = task
+                .completed_at
+                .as_deref()
+                .and_then(|completed_at| timezone::instant_to_local(completed_at, self.timezone))
+                .unwrap_or_else(|| timezone::now_in(self.timezone));
+            let tracked_sec = self
+                .db_repo
+                .list_task_timers(task_id)?
+                .iter()
+                .map(|timer| timer.duration_sec)
+                .sum();
+            self.append_to_daily(
+                at,
+                &daily_log::completion_entry(at, &task.title, tracked_sec),
+            )
+        });
+        if let Err(err) = result {
+            warn!(target: "planning", "daily log not updated: task_id={}, error={}", task_id, err.message);
+        }
+    }
+
+    // Log a stopped timer in the daily note of the day it ended
+    fn timer_stopped(&self, task: &Task, timer_id: &str) {
+        if !self.daily_log.enabled {
+            return;
+        }
+        let result = self.db_repo.get_timer(timer_id).and_then(|timer| {
+            let Some(timer) = timer else {
+                return Ok(());
+            };
+            let start = timezone::instant_to_local(&timer.start_at, self.timezone);
+            let end = timer
+                .stop_at
+                .as_deref()
+                .and_then(|stop_at| timezone::instant_to_local(stop_at, self.timezone));
+            let (Some(start), Some(end)) = (start, end) else {
+                return Ok(());
+            };
+            self.append_to_daily(
+                end,
+                &daily_log::timer_entry(start, end, &task.title, timer.duration_sec),
+            )
+        });
+        if let Err(err) = result {
+            warn!(target: "planning", "daily log not updated: task_id={}, error={}", task.id, err.message);
+        }
+    }
+
+    // Entries go through the queue so a note open in the editor gets them, in order, once it
+    // is released
+    fn append_to_daily(&self, at: NaiveDateTime, entry: &str) -> Result<(), ApiError> {
+        let day = at.date().format("%Y-%m-%d").to_string();
+        self.db_repo.queue_daily_entry(&day, entry)?;
+        self.flush_daily_log_queue().map(|_| ())
+    }
+
+    // Append queued daily log entries whose notes are not open in the editor, oldest first.
+    // Returns how many were appended; an entry failing for another reason is dropped with a
+    // warning.
+    pub fn flush_daily_log_queue(&self) -> Result<usize, ApiError> {
+        // The release command and the periodic flush must not append the same entry twice
+        static FLUSHING: std::sync::Mutex<()> = std::sync::Mutex::new(());
+        let _flushing = FLUSHING.lock().map_err(|_| ApiError {
+            code: ErrorCode::LockError,
+            message: "Failed to acquire daily log lock".to_string(),
+            details: None,
+        })?;
+        let mut locked_days = HashSet::new();
+        let mut appended = 0;
+        for queued in self.db_repo.list_queued_daily_entries()? {
+            // Later entries of a locked day wait as well, keeping the log in order
+            if locked_days.contains(&queued.day) {
+                continue;
+            }
+            match self.write_daily_entry(&queued.day, &queued.entry) {
+                Ok(()) => appended += 1,
+                Err(e) if e.code == ErrorCode::NoteLocked => {
+                    locked_days.insert(queued.day);
+                    continue;
+                }
+                Err(e) => {
+                    warn!(target: "planning", "daily log entry dropped: day={}, error={}", queued.day, e.message);
+                }
+            }
+            self.db_repo.clear_daily_entry(queued.id)?;
+        }
+        Ok(appended)
+    }
+
+    fn write_daily_entry(&self, day: &str, entry: &str) -> Result<(), ApiError> {
+        let daily = self.open_daily(OpenDailyInput {
+            day: day.to_string(),
+        })?;
+        vault_service::append_under_heading(
+            &self.vault_root,
+            Path::new(&daily.md_path),
+            &self.daily_log.heading,
+            entry,
+            WriteOrigin::Background,
+        )?;
+        Ok(())
+    }
+
+    fn emit_task_event(&self, event: WebhookEvent, task_id: &str) {
+        let Some(app_handle) = &self.app_handle else {
+            return;
+        };
+        match self.db_repo.get_task(task_id) {
+            Ok(Some(task)) => webhooks::dispatch(app_handle, &self.vault_root, event, &task),
+            Ok(None) => {}
+            Err(err) => {
+                warn!(target: "planning", "task event not sent: task_id={}, error={}", task_id, err.message);
+            }
+        }
+    }
+
+    // Have the board index files regenerated once task changes settle; a no-op outside the app
+    fn boards_changed(&self) {
+        if !self.board_index.enabled {
+            return;
+        }
+        let state = self
+            .app_handle
+            .as_ref()
+            .and_then(|app_handle| app_handle.try_state::<BoardIndexState>());
+        if let Some(state) = state {
+            state.mark_changed();
+        }
+    }
+
+    // Schedule value in its stored UTC form; values that are not times are kept as given
+    fn stored_schedule_time(&self, value: Option<&str>) -> Option<String> {
+        value.map(|value| {
+            schedule::normalize_schedule_time(value, self.timezone)
+                .unwrap_or_else(|| value.to_string())
+        })
+    }
+
+    // Today's date in the vault timezone as YYYY-MM-DD
+    pub fn today(&self) -> String {
+        timezone::today_in(self.timezone)
+            .format("%Y-%m-%d")
+            .to_string()
+    }
 
-        Ok(Self { db_repo, md_repo })
+    // List tasks, optionally filtered by status; archived tasks only when asked for
+    pub fn list_tasks(
+        &self,
+        status: Option<TaskStatus>,
+        include_archived: bool,
+    ) -> Result<Vec<Task>, ApiError> {
+        let tasks = self
+            .db_repo
+            .list_all_tasks()?
+            .into_iter()
+            .filter(|task| include_archived || task.archived == 0)
+            .filter(|task| status.is_none_or(|status| task.status == status))
+            .collect();
+        Ok(tasks)
     }
-    // Get all data needed for today's home page
-    pub fn get_today_data(&self, today: &str) -> Result<TodayDTO, ApiError> {
+
+    // Get all data needed for today's home page; "today" defaults to the vault-local date
+    pub fn get_today_data(&self, today: Option<&str>) -> Result<TodayDTO, ApiError> {
+        let today = match today {
+            Some(today) => today.to_string(),
+            None => timezone::today_in(self.timezone)
+                .format("%Y-%m-%d")
+                .to_string(),
+        };
+        let today = today.as_str();
+
+        // Escalation rules are applied before reading so the board reflects them
+        if self.rules.iter().any(|rule| rule.enabled) {
+            if let Err(e) = self.run_rules() {
+                warn!(target: "planning", "run_rules before get_today_data failed: error_code={}, error_message={}", &e.code, &e.message);
+            }
+        }
         let op_id = Uuid::new_v4().to_string();
         let span = span!(
             Level::INFO,
@@ -66,8 +879,15 @@ impl PlanningService {
         );
         let _enter = span.enter();
 
+        // Done tasks finished before the window are left to planning_list_done
+        let done_since = (self.done_window_days > 0).then(|| {
+            (Utc::now() - chrono::Duration::days(i64::from(self.done_window_days))).to_rfc3339()
+        });
+
         let start = std::time::Instant::now();
-        let result = self.db_repo.get_today_data(today);
+        let result =
+            self.db_repo
+                .get_today_data(today, self.timezone, &self.week, done_since.as_deref());
         let elapsed = start.elapsed();
 
         match &result {
@@ -85,6 +905,32 @@ impl PlanningService {
         result
     }
 
+    // Page through done tasks, optionally on one board
+    pub fn list_done_tasks(
+        &self,
+        offset: i64,
+        limit: i64,
+        board_id: Option<&str>,
+    ) -> Result<DoneTasksPage, ApiError> {
+        if offset < 0 || !(1..=MAX_DONE_PAGE_SIZE).contains(&limit) {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: format!(
+                    "offset must be >= 0 and limit between 1 and {}",
+                    MAX_DONE_PAGE_SIZE
+                ),
+                details: Some(serde_json::json!({ "offset": offset, "limit": limit })),
+            });
+        }
+        let (tasks, total) = self.db_repo.list_done_tasks(offset, limit, board_id)?;
+        Ok(DoneTasksPage {
+            tasks,
+            total,
+            offset,
+            limit,
+        })
+    }
+
     // Create a new task
     pub fn create_task(&self, input: CreateTaskInput) -> Result<Task, ApiError> {
         let op_id = Uuid::new_v4().to_string();
@@ -97,82 +943,327 @@ impl PlanningService {
         );
         let _enter = span.enter();
 
+        // Replayed invocation (e.g. webview reload mid-request): return the task created the first time
+        let op_id = normalized_op_id(input.op_id.as_deref());
+        if let Some(task_id) = self.find_completed_op(op_id)? {
+            info!(target: "planning", "create_task deduplicated: op_id={:?}, task_id={}", op_id, task_id);
+            return self.get_task_or_not_found(&task_id);
+        }
+
         let start = std::time::Instant::now();
-        let board_id = input
-            .board_id
-            .as_ref()
-            .map(|value| value.trim())
-            .filter(|value| !value.is_empty());
+        // Slug, row, markdown file, path info and op_id go in as one unit; a failure rolls the row
+        // back and removes the file if it was already written
+        let mut inserted = None;
+        let result = self.db_repo.in_transaction(|| {
+            let slug = self.unique_task_slug(&input.title, &HashSet::new())?;
+            let task = self.insert_task(&input, &slug)?;
+            inserted = Some((task.id.clone(), slug.clone()));
+            self.write_new_task_md(&task, &slug, input.note_path.is_none())?;
+            if let Some(op_id) = op_id {
+                self.db_repo.record_op(op_id, "create_task", &task.id)?;
+            }
+            self.get_task_or_not_found(&task.id)
+        });
+        let elapsed = start.elapsed();
 
-        let due_date_value = input
-            .due_date
-            .as_ref()
-            .map(|value| value.trim())
-            .filter(|value| !value.is_empty());
-        if matches!(input.status, TaskStatus::Todo | TaskStatus::Doing) && due_date_value.is_none()
-        {
+        match &result {
+            Ok(task) => {
+                info!(target: "planning", "create_task succeeded: task_id={}, elapsed_ms={}", &task.id, elapsed.as_millis());
+                self.boards_changed();
+                self.emit_task_event(WebhookEvent::Created, &task.id);
+            }
+            Err(e) => {
+                if let Some((task_id, slug)) = &inserted {
+                    self.remove_new_task_md(task_id, slug);
+                }
+                error!(target: "planning", "create_task failed: error_code={}, error_message={}, elapsed_ms={}", &e.code, &e.message, elapsed.as_millis());
+            }
+        }
+
+        result
+    }
+
+    // Create tasks from a pasted CSV or markdown table. A dry run returns the mapped rows only;
+    // otherwise every row must map cleanly and all tasks are inserted in one transaction.
+    pub fn import_table(
+        &self,
+        text: &str,
+        mapping: &TableMapping,
+        dry_run: bool,
+    ) -> Result<TableImportResponse, ApiError> {
+        let span = span!(Level::INFO, "planning.import_table", dry_run = dry_run);
+        let _enter = span.enter();
+
+        let table = table_import::parse_table(text, mapping.has_header)?;
+        let default_due = match mapping.default_due.as_deref().map(str::trim) {
+            Some(day) if !day.is_empty() => {
+                chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d").map_err(|e| ApiError {
+                    code: ErrorCode::DateTimeError,
+                    message: format!("Failed to parse date: {}", e),
+                    details: Some(serde_json::json!({ "default_due": day })),
+                })?;
+                day.to_string()
+            }
+            _ => self.today(),
+        };
+        let rows = table_import::map_rows(&table, mapping, &default_due)?;
+        let invalid = rows.iter().filter(|row| row.task.is_none()).count();
+        let mut response = TableImportResponse {
+            format: table.format,
+            headers: table.headers,
+            valid: rows.len() - invalid,
+            invalid,
+            rows,
+            dry_run,
+            created: Vec::new(),
+        };
+        if dry_run {
+            return Ok(response);
+        }
+        if invalid > 0 {
             return Err(ApiError {
-                code: "DUE_DATE_REQUIRED".to_string(),
-                message: "due_date is required for todo/doing tasks".to_string(),
-                details: None,
+                code: ErrorCode::InvalidInput,
+                message: "Some rows can't be imported".to_string(),
+                details: Some(serde_json::json!({
+                    "rows": response
+                        .rows
+                        .iter()
+                        .filter(|row| !row.errors.is_empty())
+                        .map(|row| serde_json::json!({ "line": row.line, "errors": row.errors }))
+                        .collect::<Vec<_>>(),
+                })),
             });
         }
 
-        let labels = input.labels.as_ref().or(input.tags.as_ref());
-        let completed_at = if input.status == TaskStatus::Done {
-            Some(Utc::now().to_rfc3339())
-        } else {
-            None
-        };
+        let inputs: Vec<CreateTaskInput> = response
+            .rows
+            .iter()
+            .filter_map(|row| row.task.clone())
+            .collect();
+        let batch = self.create_tasks_batch(inputs)?;
+        if !batch.committed {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "Some rows can't be imported".to_string(),
+                details: Some(serde_json::json!({
+                    "rows": batch
+                        .items
+                        .iter()
+                        .filter_map(|item| {
+                            let error = item.error.as_ref()?;
+                            Some(serde_json::json!({
+                                "line": response.rows[item.index].line,
+                                "errors": [error.message],
+                            }))
+                        })
+                        .collect::<Vec<_>>(),
+                })),
+            });
+        }
+        response.created = batch.created;
+        info!(target: "planning", "import_table succeeded: format={:?}, tasks={}", response.format, response.created.len());
+        Ok(response)
+    }
 
-        // Generate slug and ensure uniqueness
-        let base_slug = generate_slug(&input.title);
-        let mut slug = base_slug.clone();
-        let mut counter = 1;
+    // Import TODO items and journals of a Logseq graph or a folder of org files. Items become
+    // tasks whose description links back to their source line; journal pages become the daily
+    // notes of days without one. Importing the same source again returns the tasks created before.
+    pub fn import_outliner(
+        &self,
+        source: &Path,
+        format: OutlinerFormat,
+        dry_run: bool,
+    ) -> Result<OutlinerImportResponse, ApiError> {
+        let span = span!(
+            Level::INFO,
+            "planning.import_outliner",
+            format = format.label(),
+            dry_run = dry_run
+        );
+        let _enter = span.enter();
 
-        // Loop until we find a unique slug (directory does not exist)
-        loop {
-            // task_dir_path now ignores task_id, so we can pass an empty string
-            let dir_path = task_dir_path(&self.md_repo.vault_root, "", &slug);
-            if !dir_path.exists() {
-                break;
-            }
-            slug = format!("{}_{}", base_slug, counter);
-            counter += 1;
+        path_policy::ensure_no_symlink(source)?;
+        let root = source
+            .canonicalize()
+            .map_err(|err| map_io_error(ErrorCode::NotFound, "Source path not found", err))?;
+        if !root.is_dir() {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "Source path is not a directory".to_string(),
+                details: Some(serde_json::json!({ "path": source.to_string_lossy() })),
+            });
         }
 
-        // We can't know ID before DB insertion if DB generates it... wait, repo generates it using Uuid::new_v4().
-        // Be better to generate ID here or update repo to accept ID?
-        // Or simply:
-        // 1. Repo generates ID.
-        // 2. We pass slug to repo.
-        // 3. For md_rel_path, we need ID...
-
-        // Let's modify logic:
-        // We will execute DB insertion with slug.
-        // Then get task back.
-        // Then compute md_rel_path using real ID and slug.
-        // Then update DB with md_rel_path.
-        // Then create file.
-        // OR: Update repo to allow passing ID?
-        // Actually currently repo generates ID.
-        // Let's stick to: pass slug, get task (with ID), then generate md_rel_path, save file, update DB.
-        // Wait, if I want to store md_rel_path in DB properly in one go, I need ID.
-        // `planning_repo.rs` `create_task` generates ID.
-        // I will trust the repo generated ID is returned.
-
-        // Revision:
-        // 1. Generate slug.
-        // 2. We DON'T populate md_rel_path initially in DB call (pass None).
-        // 3. Get task back with ID.
-        // 4. Compute md_rel_path.
-        // 5. Update task with md_rel_path in DB.
-        // 6. Create MD file.
-
-        // Wait, I updated repo signature to accept md_rel_path.
-        // If I pass None, it's fine.
-
-        let result = self.db_repo.create_task(
+        let files = outliner_files(&root, format);
+        let mut response = OutlinerImportResponse {
+            format,
+            dry_run,
+            files: files.len(),
+            items: Vec::new(),
+            journals: Vec::new(),
+            canceled: 0,
+            created: Vec::new(),
+            daily_notes_created: 0,
+            daily_notes_skipped: 0,
+        };
+        let today = self.today();
+        let mut inputs = Vec::new();
+        let mut journal_notes = Vec::new();
+        for rel in &files {
+            let path = root.join(rel);
+            let text = match std::fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(err) => {
+                    warn!(target: "planning", "import source skipped: path={}, error={}", path.display(), err);
+                    continue;
+                }
+            };
+            let file = paths::rel_path_string(rel);
+            let link = match tauri::Url::from_file_path(&path) {
+                Ok(url) => format!("[{}]({})", file, url),
+                Err(()) => format!("`{}`", file),
+            };
+            // Logseq keeps journals in their own folder; any dated org file is a journal
+            let is_journal = format == OutlinerFormat::Org || rel.starts_with("journals");
+            let day = rel
+                .file_stem()
+                .and_then(|stem| outliner_import::journal_day(&stem.to_string_lossy()))
+                .filter(|_| is_journal);
+
+            let parsed = match format {
+                OutlinerFormat::Logseq => outliner_import::parse_logseq(&file, &text),
+                OutlinerFormat::Org => outliner_import::parse_org(&file, &text),
+            };
+            response.canceled += parsed.canceled;
+            for mut item in parsed.items {
+                // A done item on a journal page was done that day
+                if item.status == TaskStatus::Done && item.closed_on.is_none() {
+                    item.closed_on = day.clone();
+                }
+                let provenance = format!(
+                    "Imported from {} {}, line {}",
+                    format.label(),
+                    link,
+                    item.line
+                );
+                let op_id = format!("import:{}:{}:{}", root.display(), file, item.line);
+                inputs.push(outliner_import::to_create_input(
+                    &item,
+                    day.as_deref().unwrap_or(&today),
+                    provenance,
+                    op_id,
+                ));
+                response.items.push(item);
+            }
+            if let Some(day) = day {
+                response.journals.push(OutlinerJournal {
+                    file: file.clone(),
+                    day: day.clone(),
+                });
+                let body = match format {
+                    OutlinerFormat::Logseq => text,
+                    OutlinerFormat::Org => outliner_import::org_to_markdown(&text),
+                };
+                journal_notes.push((day, link, body));
+            }
+        }
+        if dry_run {
+            return Ok(response);
+        }
+
+        for chunk in inputs.chunks(MAX_BATCH_TASKS) {
+            let batch = self.create_tasks_batch(chunk.to_vec())?;
+            if !batch.committed {
+                return Err(batch
+                    .items
+                    .into_iter()
+                    .find_map(|item| item.error)
+                    .unwrap_or(ApiError {
+                        code: ErrorCode::InvalidInput,
+                        message: "Some items can't be imported".to_string(),
+                        details: None,
+                    }));
+            }
+            response
+                .created
+                .extend(batch.items.into_iter().filter_map(|item| item.task));
+        }
+        for (item, task) in response.items.iter().zip(response.created.iter_mut()) {
+            let closed_at = item
+                .closed_on
+                .as_deref()
+                .and_then(|day| chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d").ok())
+                .and_then(|day| day.and_hms_opt(12, 0, 0))
+                .and_then(|noon| timezone::from_wall_clock(noon, self.timezone));
+            if let (Some(closed_at), TaskStatus::Done) = (closed_at, task.status) {
+                let closed_at = closed_at.to_rfc3339();
+                self.db_repo.set_task_completed_at(&task.id, &closed_at)?;
+                task.completed_at = Some(closed_at);
+            }
+        }
+
+        for (day, link, body) in journal_notes {
+            if self.db_repo.get_day_log(&day)?.is_some() || self.md_repo.daily_md_exists(&day) {
+                response.daily_notes_skipped += 1;
+                continue;
+            }
+            let content = format!(
+                "> Imported from {} {}\n\n{}\n",
+                format.label(),
+                link,
+                body.trim()
+            );
+            self.md_repo.upsert_daily_md(&day, &content)?;
+            let relative_path = self.md_repo.get_daily_md_relative_path(&day);
+            self.db_repo.upsert_day_log(&day, &relative_path)?;
+            response.daily_notes_created += 1;
+        }
+
+        info!(target: "planning", "import_outliner succeeded: format={}, files={}, tasks={}, daily_notes={}", format.label(), response.files, response.created.len(), response.daily_notes_created);
+        Ok(response)
+    }
+
+    // Validate a new task and insert its row under `slug`; the markdown file is written separately
+    fn insert_task(&self, input: &CreateTaskInput, slug: &str) -> Result<Task, ApiError> {
+        let board_id = input
+            .board_id
+            .as_ref()
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty());
+
+        let due_date_value = input
+            .due_date
+            .as_ref()
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty());
+        if matches!(input.status, TaskStatus::Todo | TaskStatus::Doing) && due_date_value.is_none()
+        {
+            return Err(ApiError {
+                code: ErrorCode::DueDateRequired,
+                message: "due_date is required for todo/doing tasks".to_string(),
+                details: None,
+            });
+        }
+
+        if let Some(periodicity) = &input.periodicity {
+            recurrence::validate(periodicity)?;
+        }
+        let color = input
+            .color
+            .as_deref()
+            .filter(|value| !value.trim().is_empty())
+            .map(board::normalize_color)
+            .transpose()?;
+
+        let labels = input.labels.as_ref().or(input.tags.as_ref());
+        let completed_at = if input.status == TaskStatus::Done {
+            Some(Utc::now().to_rfc3339())
+        } else {
+            None
+        };
+
+        // The repo generates the id, so md_rel_path is filled in once the file is written
+        self.db_repo.create_task(
             &input.title,
             input.description.as_deref(),
             input.status,
@@ -180,98 +1271,169 @@ impl PlanningService {
             due_date_value,
             board_id,
             input.estimate_min,
-            labels.map(|tags| tags.as_ref()),
+            labels,
             input.subtasks.as_ref(),
             input.periodicity.as_ref(),
-            input.scheduled_start.as_deref(),
-            input.scheduled_end.as_deref(),
+            self.stored_schedule_time(input.scheduled_start.as_deref())
+                .as_deref(),
+            self.stored_schedule_time(input.scheduled_end.as_deref())
+                .as_deref(),
             input.note_path.as_deref(),
+            color.as_deref(),
             completed_at.as_deref(),
-            Some(&slug),
-            None, // md_rel_path will be updated after we get ID
-        );
-        let elapsed = start.elapsed();
+            Some(slug),
+            None,
+        )
+    }
 
-        match &result {
-            Ok(task) => {
-                info!(target: "planning", "create_task succeeded: task_id={}, elapsed_ms={}", &task.id, elapsed.as_millis());
+    // Write the markdown file of a just inserted task and record where it lives. Without a note
+    // path of its own, the task note doubles as note_path.
+    fn write_new_task_md(
+        &self,
+        task: &Task,
+        slug: &str,
+        set_note_path: bool,
+    ) -> Result<(), ApiError> {
+        // With md sync off no note is written; the path is kept for open_task_note to create it
+        if self.md_sync != MdSyncPolicy::Off {
+            let template = task_md_template(task);
+            self.md_repo
+                .upsert_task_md(&task.id, slug, &task.title, &template)?;
+        }
 
-                // Now create the markdown file
-                let template = format!(
-                    "---
-fm_version: 2
-id: {}
-title: {}
-status: {}
-priority: {}
-tags: {}
-estimate_min: {}
-due_date: {}
-created_at: {}
-updated_at: {}
----
+        let relative_path = self.md_repo.get_task_md_relative_path(&task.id, slug);
+        self.db_repo
+            .update_task_path_info(&task.id, slug, &relative_path)?;
+        if set_note_path {
+            self.db_repo
+                .update_task_note_path(&task.id, &relative_path)?;
+        }
+        Ok(())
+    }
 
-<!-- 
-Frontmatter 由系统维护；正文为你的笔记区。
--->
+    // Remove the markdown file (and the then empty task directory) of a task rolled back
+    fn remove_new_task_md(&self, task_id: &str, slug: &str) {
+        if self.md_sync == MdSyncPolicy::Off {
+            return;
+        }
+        if let Err(e) = self.md_repo.delete_task_md(task_id, slug) {
+            error!(target: "planning", "Failed to remove markdown of rolled back task: task_id={}, error={}", task_id, e);
+        }
+        let dir = task_dir_path(&self.vault_root, &self.md_repo.layout, task_id, slug);
+        let _ = std::fs::remove_dir(dir);
+    }
 
-## Notes
+    // Create several tasks as one unit: rows and markdown files are written inside one
+    // transaction, and any failing input rolls everything back, removing the files already
+    // written. Every input gets a result; replayed op_ids return the task created before.
+    pub fn create_tasks_batch(
+        &self,
+        inputs: Vec<CreateTaskInput>,
+    ) -> Result<BatchCreateResponse, ApiError> {
+        let span = span!(
+            Level::INFO,
+            "planning.create_tasks_batch",
+            count = inputs.len()
+        );
+        let _enter = span.enter();
 
-- 
-",
-                    task.id,
-                    task.title,
-                    task.status,
-                    task.priority
-                        .map(|p| p.to_string())
-                        .unwrap_or("p3".to_string()),
-                    task.tags
-                        .as_ref()
-                        .map(|tags| format!("[{}]", tags.join(", ")))
-                        .unwrap_or("[]".to_string()),
-                    task.estimate_min
-                        .map(|min| min.to_string())
-                        .unwrap_or("null".to_string()),
-                    task.due_date.as_deref().unwrap_or("null"),
-                    task.created_at,
-                    task.updated_at
-                );
+        if inputs.is_empty() || inputs.len() > MAX_BATCH_TASKS {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: format!("A batch holds 1 to {} tasks", MAX_BATCH_TASKS),
+                details: Some(serde_json::json!({ "count": inputs.len() })),
+            });
+        }
 
-                // Create MD file
-                if let Err(e) = self
-                    .md_repo
-                    .upsert_task_md(&task.id, &slug, &task.title, &template)
-                {
-                    error!(target: "planning", "Failed to create task markdown file: {}", e);
-                    // Non-fatal? Maybe we should return error?
-                    // For now just log, as task is created in DB.
-                } else {
-                    // Update md_rel_path in DB
-                    let relative_path = self.md_repo.get_task_md_relative_path(&task.id, &slug);
-                    if let Err(e) =
-                        self.db_repo
-                            .update_task_path_info(&task.id, &slug, &relative_path)
-                    {
-                        error!(target: "planning", "Failed to update md_rel_path: {}", e);
-                    }
+        let mut items: Vec<BatchCreateItem> = (0..inputs.len())
+            .map(|index| BatchCreateItem {
+                index,
+                task: None,
+                error: None,
+            })
+            .collect();
+        let mut replayed = HashSet::new();
+        for (index, input) in inputs.iter().enumerate() {
+            if let Some(task_id) =
+                self.find_completed_op(normalized_op_id(input.op_id.as_deref()))?
+            {
+                items[index].task = Some(self.get_task_or_not_found(&task_id)?);
+                replayed.insert(index);
+            }
+        }
 
-                    // Also update note_path for compatibility if needed?
-                    // note_path is already there. currently DB create_task uses input.note_path.
-                    // If input.note_path is None, we might want to set it to relative_path too?
-                    if input.note_path.is_none() {
-                        if let Err(e) = self.db_repo.update_task_note_path(&task.id, &relative_path)
-                        {
-                            error!(target: "planning", "Failed to update note_path: {}", e);
+        let mut written: Vec<(usize, String, String)> = Vec::new();
+        let mut failed = 0;
+        let result = self.db_repo.in_transaction(|| {
+            let mut reserved = HashSet::new();
+            for (index, input) in inputs.iter().enumerate() {
+                if replayed.contains(&index) {
+                    continue;
+                }
+                let created = self
+                    .unique_task_slug(&input.title, &reserved)
+                    .and_then(|slug| {
+                        let task = self.insert_task(input, &slug)?;
+                        written.push((index, task.id.clone(), slug.clone()));
+                        if let Some(op_id) = normalized_op_id(input.op_id.as_deref()) {
+                            self.db_repo.record_op(op_id, "create_task", &task.id)?;
                         }
+                        self.write_new_task_md(&task, &slug, input.note_path.is_none())?;
+                        Ok(slug)
+                    });
+                match created {
+                    Ok(slug) => {
+                        reserved.insert(slug);
+                    }
+                    Err(e) => {
+                        items[index].error = Some(e);
+                        failed += 1;
                     }
                 }
             }
-            Err(e) => {
-                error!(target: "planning", "create_task failed: error_code={}, error_message={}, elapsed_ms={}", &e.code, &e.message, elapsed.as_millis());
+            if failed > 0 {
+                return Err(ApiError {
+                    code: ErrorCode::InvalidInput,
+                    message: "Batch rolled back".to_string(),
+                    details: Some(serde_json::json!({ "failed": failed })),
+                });
+            }
+            Ok(())
+        });
+
+        if let Err(e) = result {
+            for (_, task_id, slug) in &written {
+                self.remove_new_task_md(task_id, slug);
             }
+            // Without a failing input the commit itself failed
+            if failed == 0 {
+                return Err(e);
+            }
+            error!(target: "planning", "create_tasks_batch rolled back: failed={}, count={}", failed, inputs.len());
+            return Ok(BatchCreateResponse {
+                committed: false,
+                created: Vec::new(),
+                items: items
+                    .into_iter()
+                    .map(|item| BatchCreateItem { task: None, ..item })
+                    .collect(),
+            });
         }
 
-        result
+        let mut created = Vec::new();
+        for (index, task_id, _) in written {
+            self.emit_task_event(WebhookEvent::Created, &task_id);
+            let task = self.get_task_or_not_found(&task_id)?;
+            items[index].task = Some(task.clone());
+            created.push(task);
+        }
+        info!(target: "planning", "create_tasks_batch succeeded: created={}, replayed={}", created.len(), replayed.len());
+        self.boards_changed();
+        Ok(BatchCreateResponse {
+            committed: true,
+            created,
+            items,
+        })
     }
 
     // Update an existing task
@@ -286,8 +1448,15 @@ Frontmatter 由系统维护；正文为你的笔记区。
         let _enter = span.enter();
 
         let start = std::time::Instant::now();
+        let op_id = normalized_op_id(input.op_id.as_deref());
 
         let result = (|| -> Result<(), ApiError> {
+            // Skip replayed invocations that were already applied
+            if self.find_completed_op(op_id)?.is_some() {
+                info!(target: "planning", "update_task deduplicated: op_id={:?}", op_id);
+                return Ok(());
+            }
+
             // Check if task exists
             let task = self.get_task_or_not_found(&input.id)?;
 
@@ -313,7 +1482,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
                 && effective_due_date.is_none()
             {
                 return Err(ApiError {
-                    code: "DUE_DATE_REQUIRED".to_string(),
+                    code: ErrorCode::DueDateRequired,
                     message: "due_date is required for todo/doing tasks".to_string(),
                     details: None,
                 });
@@ -322,7 +1491,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
             if matches!(next_status, TaskStatus::Todo | TaskStatus::Doing) {
                 if let Some(None) = due_date_update {
                     return Err(ApiError {
-                        code: "DUE_DATE_REQUIRED".to_string(),
+                        code: ErrorCode::DueDateRequired,
                         message: "due_date cannot be cleared for todo/doing tasks".to_string(),
                         details: None,
                     });
@@ -343,7 +1512,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
                     let trimmed = value.trim();
                     if trimmed.is_empty() {
                         return Err(ApiError {
-                            code: "BOARD_ID_REQUIRED".to_string(),
+                            code: ErrorCode::BoardIdRequired,
                             message: "board_id cannot be empty".to_string(),
                             details: None,
                         });
@@ -353,79 +1522,100 @@ Frontmatter 由系统维护；正文为你的笔记区。
                 None => None,
             };
 
-            let labels = input.labels.as_ref().or(input.tags.as_ref());
-
-            // Update task in database
-            let updated_task = self.db_repo.update_task(
-                &input.id,
-                input.title.as_deref(),
-                input.description.as_deref(),
-                input.status,
-                input.priority,
-                labels,
-                input.subtasks.as_ref(),
-                input.periodicity.as_ref(),
-                input.order_index,
-                input.estimate_min,
-                input.scheduled_start.as_deref(),
-                input.scheduled_end.as_deref(),
-                due_date_update.clone(),
-                board_id,
-                input.note_path.as_deref(),
-                input.archived,
-                completed_at_update,
-            )?;
-
-            // Prepare frontmatter updates
-            let mut frontmatter_updates = HashMap::new();
-
-            // Always update updated_at
-            frontmatter_updates.insert("updated_at".to_string(), updated_task.updated_at.clone());
-
-            // Update other fields if they changed
-            if input.title.is_some() {
-                frontmatter_updates.insert("title".to_string(), updated_task.title.clone());
+            if let Some(periodicity) = &input.periodicity {
+                recurrence::validate(periodicity)?;
             }
+            let color_update = match input.color.as_ref() {
+                None => None,
+                Some(None) => Some(None),
+                Some(Some(value)) if value.trim().is_empty() => Some(None),
+                Some(Some(value)) => Some(Some(board::normalize_color(value)?)),
+            };
 
-            if input.status.is_some() {
-                frontmatter_updates.insert("status".to_string(), updated_task.status.to_string());
+            // A status or board change leaves any custom column
+            let mut projected = task.clone();
+            projected.status = next_status;
+            if let Some(board_id) = board_id {
+                projected.board_id = Some(board_id.to_string());
             }
-
-            if input.priority.is_some() {
-                frontmatter_updates.insert(
-                    "priority".to_string(),
-                    updated_task
-                        .priority
-                        .map(|p| p.to_string())
-                        .unwrap_or("p3".to_string()),
-                );
+            if projected.status != task.status || projected.board_id != task.board_id {
+                projected.column_key = None;
             }
-
-            if labels.is_some() {
-                let tags_str = format!(
-                    "[{}]",
-                    updated_task.tags.clone().unwrap_or_default().join(", ")
-                );
-                frontmatter_updates.insert("tags".to_string(), tags_str);
+            if !input.override_wip_limit.unwrap_or(false) {
+                self.check_wip_limits(&[(task.clone(), projected.clone())])?;
             }
+            let labels = input.labels.as_ref().or(input.tags.as_ref());
 
-            if input.estimate_min.is_some() {
-                let estimate_str = updated_task
-                    .estimate_min
-                    .map(|min| min.to_string())
-                    .unwrap_or("null".to_string());
-                frontmatter_updates.insert("estimate_min".to_string(), estimate_str);
+            // Frontmatter fields to sync; updated_at always changes
+            let mut synced_fields = vec!["updated_at"];
+            for (field, changed) in [
+                ("title", input.title.is_some()),
+                ("status", input.status.is_some()),
+                ("priority", input.priority.is_some()),
+                ("tags", labels.is_some()),
+                ("estimate_min", input.estimate_min.is_some()),
+                ("due_date", due_date_update.is_some()),
+                ("color", color_update.is_some()),
+            ] {
+                if changed {
+                    synced_fields.push(field);
+                }
             }
 
-            if due_date_update.is_some() {
-                let due_date_str = updated_task.due_date.as_deref().unwrap_or("null");
-                frontmatter_updates.insert("due_date".to_string(), due_date_str.to_string());
+            // Update task in database, journaling the note sync in the same transaction so a
+            // crash before the note is written is caught up at the next startup
+            let (updated_task, journal_id) = self.db_repo.in_transaction(|| {
+                if task.column_key.is_some() && projected.column_key.is_none() {
+                    self.db_repo.set_task_column(&input.id, None)?;
+                }
+                let updated_task = self.db_repo.update_task(
+                    &input.id,
+                    input.title.as_deref(),
+                    input.description.as_deref(),
+                    input.status,
+                    input.priority,
+                    labels,
+                    input.subtasks.as_ref(),
+                    input.periodicity.as_ref(),
+                    input.order_index,
+                    input.estimate_min,
+                    self.stored_schedule_time(input.scheduled_start.as_deref())
+                        .as_deref(),
+                    self.stored_schedule_time(input.scheduled_end.as_deref())
+                        .as_deref(),
+                    due_date_update.clone(),
+                    board_id,
+                    color_update.clone(),
+                    input.note_path.as_deref(),
+                    input.archived,
+                    completed_at_update,
+                )?;
+                let journal_id = self.db_repo.journal_md_sync(&input.id, &synced_fields)?;
+                Ok((updated_task, journal_id))
+            })?;
+
+            let mut updated_task = updated_task;
+            if input.title.is_some() && input.rename_slug.unwrap_or(false) {
+                if let Some(slug) = self.rename_task_dir(&updated_task)? {
+                    updated_task = self.get_task_or_not_found(&input.id)?;
+                    info!(target: "planning", "task directory renamed: task_id={}, slug={}", &input.id, slug);
+                }
             }
 
             // Sync to markdown file
-            if !frontmatter_updates.is_empty() {
+            let frontmatter_updates = task_frontmatter_updates(&updated_task, &synced_fields);
+            // Lazy sync leaves the journal entry to the background flush
+            if self.md_sync != MdSyncPolicy::Lazy {
                 let slug = updated_task.task_dir_slug.as_deref().unwrap_or("task");
                 self.sync_task_to_md(&updated_task.id, slug, &frontmatter_updates)?;
+                self.db_repo.clear_md_sync(journal_id)?;
+            }
+
+            if let Some(op_id) = op_id {
+                self.db_repo.record_op(op_id, "update_task", &input.id)?;
+            }
+            if next_status == TaskStatus::Done && task.status != TaskStatus::Done {
+                self.task_completed(&input.id);
             }
 
             Ok(())
@@ -436,6 +1626,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
         match &result {
             Ok(_) => {
                 info!(target: "planning", "update_task succeeded: task_id={}, elapsed_ms={}", &input.id, elapsed.as_millis());
+                self.boards_changed();
             }
             Err(e) => {
                 error!(target: "planning", "update_task failed: task_id={}, error_code={}, error_message={}, elapsed_ms={}", &input.id, &e.code, &e.message, elapsed.as_millis());
@@ -445,13 +1636,21 @@ Frontmatter 由系统维护；正文为你的笔记区。
         result
     }
 
+    // Look up the task touched by a previously applied op_id, if any
+    fn find_completed_op(&self, op_id: Option<&str>) -> Result<Option<String>, ApiError> {
+        match op_id {
+            Some(op_id) => self.db_repo.get_op_task_id(op_id),
+            None => Ok(None),
+        }
+    }
+
     // Check if task exists and return it
     fn get_task_or_not_found(&self, task_id: &str) -> Result<Task, ApiError> {
         let task = self.db_repo.get_task(task_id)?;
         match task {
             Some(task) => Ok(task),
             None => Err(ApiError {
-                code: "NotFound".to_string(),
+                code: ErrorCode::NotFound,
                 message: format!("Task with id {} not found", task_id),
                 details: None,
             }),
@@ -477,7 +1676,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
             // Check if task is already done
             if task.status == crate::domain::planning::TaskStatus::Done {
                 return Err(ApiError {
-                    code: "InvalidStateTransition".to_string(),
+                    code: ErrorCode::InvalidStateTransition,
                     message: "Task is already done".to_string(),
                     details: None,
                 });
@@ -493,6 +1692,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
             frontmatter_updates.insert("completed_at".to_string(), now);
             let slug = task.task_dir_slug.as_deref().unwrap_or("task");
             self.sync_task_to_md(task_id, slug, &frontmatter_updates)?;
+            self.task_completed(task_id);
 
             Ok(())
         })();
@@ -502,6 +1702,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
         match &result {
             Ok(_) => {
                 info!(target: "planning", "mark_task_done succeeded: task_id={}, elapsed_ms={}", task_id, elapsed.as_millis());
+                self.boards_changed();
             }
             Err(e) => {
                 error!(target: "planning", "mark_task_done failed: task_id={}, error_code={}, error_message={}, elapsed_ms={}", task_id, &e.code, &e.message, elapsed.as_millis());
@@ -530,7 +1731,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
             // Check if task is already not done
             if task.status != crate::domain::planning::TaskStatus::Done {
                 return Err(ApiError {
-                    code: "InvalidStateTransition".to_string(),
+                    code: ErrorCode::InvalidStateTransition,
                     message: "Task is not done yet".to_string(),
                     details: None,
                 });
@@ -538,7 +1739,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
 
             if task.due_date.is_none() {
                 return Err(ApiError {
-                    code: "DUE_DATE_REQUIRED".to_string(),
+                    code: ErrorCode::DueDateRequired,
                     message: "due_date is required for todo/doing tasks".to_string(),
                     details: None,
                 });
@@ -563,6 +1764,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
         match &result {
             Ok(_) => {
                 info!(target: "planning", "reopen_task succeeded: task_id={}, elapsed_ms={}", task_id, elapsed.as_millis());
+                self.boards_changed();
             }
             Err(e) => {
                 error!(target: "planning", "reopen_task failed: task_id={}, error_code={}, error_message={}, elapsed_ms={}", task_id, &e.code, &e.message, elapsed.as_millis());
@@ -591,7 +1793,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
             // Check if task is already doing or done
             if task.status == crate::domain::planning::TaskStatus::Doing {
                 return Err(ApiError {
-                    code: "InvalidStateTransition".to_string(),
+                    code: ErrorCode::InvalidStateTransition,
                     message: "Task is already in progress".to_string(),
                     details: None,
                 });
@@ -599,7 +1801,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
 
             if task.status == crate::domain::planning::TaskStatus::Done {
                 return Err(ApiError {
-                    code: "InvalidStateTransition".to_string(),
+                    code: ErrorCode::InvalidStateTransition,
                     message: "Cannot start a done task".to_string(),
                     details: None,
                 });
@@ -607,7 +1809,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
 
             if task.due_date.is_none() {
                 return Err(ApiError {
-                    code: "DUE_DATE_REQUIRED".to_string(),
+                    code: ErrorCode::DueDateRequired,
                     message: "due_date is required for todo/doing tasks".to_string(),
                     details: None,
                 });
@@ -631,6 +1833,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
         match &result {
             Ok(_) => {
                 info!(target: "planning", "start_task succeeded: task_id={}, elapsed_ms={}", task_id, elapsed.as_millis());
+                self.boards_changed();
             }
             Err(e) => {
                 error!(target: "planning", "start_task failed: task_id={}, error_code={}, error_message={}, elapsed_ms={}", task_id, &e.code, &e.message, elapsed.as_millis());
@@ -659,7 +1862,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
             // Check if task is not doing
             if task.status != crate::domain::planning::TaskStatus::Doing {
                 return Err(ApiError {
-                    code: "InvalidStateTransition".to_string(),
+                    code: ErrorCode::InvalidStateTransition,
                     message: "Task is not in progress".to_string(),
                     details: None,
                 });
@@ -667,12 +1870,16 @@ Frontmatter 由系统维护；正文为你的笔记区。
 
             if task.due_date.is_none() {
                 return Err(ApiError {
-                    code: "DUE_DATE_REQUIRED".to_string(),
+                    code: ErrorCode::DueDateRequired,
                     message: "due_date is required for todo/doing tasks".to_string(),
                     details: None,
                 });
             }
 
+            let active_timer = self
+                .db_repo
+                .get_active_timer()?
+                .filter(|timer| timer.task_id == task_id);
             self.db_repo.stop_task(task_id)?;
 
             // Sync status change to markdown file
@@ -682,6 +1889,9 @@ Frontmatter 由系统维护；正文为你的笔记区。
             frontmatter_updates.insert("updated_at".to_string(), now);
             let slug = task.task_dir_slug.as_deref().unwrap_or("task");
             self.sync_task_to_md(task_id, slug, &frontmatter_updates)?;
+            if let Some(timer) = active_timer {
+                self.timer_stopped(&task, &timer.id);
+            }
 
             Ok(())
         })();
@@ -691,6 +1901,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
         match &result {
             Ok(_) => {
                 info!(target: "planning", "stop_task succeeded: task_id={}, elapsed_ms={}", task_id, elapsed.as_millis());
+                self.boards_changed();
             }
             Err(e) => {
                 error!(target: "planning", "stop_task failed: task_id={}, error_code={}, error_message={}, elapsed_ms={}", task_id, &e.code, &e.message, elapsed.as_millis());
@@ -700,6 +1911,213 @@ Frontmatter 由系统维护；正文为你的笔记区。
         result
     }
 
+    // Begin focusing on a task, starting its timer unless already running
+    pub fn start_focus(&self, task_id: &str) -> Result<Task, ApiError> {
+        let task = self.get_task_or_not_found(task_id)?;
+        if task.status != crate::domain::planning::TaskStatus::Doing {
+            self.start_task(task_id)?;
+        }
+        self.get_task_or_not_found(task_id)
+    }
+
+    // Finish focusing on a task, stopping its timer if still running
+    pub fn end_focus(&self, task_id: &str) -> Result<Task, ApiError> {
+        let task = self.get_task_or_not_found(task_id)?;
+        if task.status == crate::domain::planning::TaskStatus::Doing {
+            self.stop_task(task_id)?;
+        }
+        self.get_task_or_not_found(task_id)
+    }
+
+    // Note that the app is alive while a timer runs; used as the close time after a crash
+    pub fn record_timer_heartbeat(&self) -> Result<(), ApiError> {
+        if self.db_repo.get_active_timer()?.is_some() {
+            self.db_repo
+                .set_vault_meta_value(LAST_ACTIVITY_KEY, &Utc::now().to_rfc3339())?;
+        }
+        Ok(())
+    }
+
+    // Id of the timer left running by a previous session, if any
+    pub fn detect_dangling_timer(&self) -> Result<Option<String>, ApiError> {
+        Ok(self.db_repo.get_active_timer()?.map(|timer| timer.id))
+    }
+
+    // Report or resolve a timer left running by a previous session
+    pub fn recover_timers(
+        &self,
+        dangling_timer_id: Option<&str>,
+        action: Option<TimerRecoveryAction>,
+    ) -> Result<TimerRecoveryResult, ApiError> {
+        let timer = match dangling_timer_id {
+            Some(timer_id) => self
+                .db_repo
+                .get_timer(timer_id)?
+                .filter(|timer| timer.stop_at.is_none()),
+            None => None,
+        };
+        let Some(timer) = timer else {
+            return Ok(TimerRecoveryResult {
+                dangling: None,
+                resolved: None,
+            });
+        };
+
+        // Last sign of life: the latest heartbeat, but never before the timer started
+        let started_at = timezone::parse_instant(&timer.start_at, None).unwrap_or_else(Utc::now);
+        let last_activity_at = self
+            .db_repo
+            .get_vault_meta_value(LAST_ACTIVITY_KEY)?
+            .and_then(|value| timezone::parse_instant(&value, None))
+            .filter(|heartbeat| *heartbeat > started_at)
+            .unwrap_or(started_at);
+
+        let task = self.db_repo.get_task(&timer.task_id)?;
+
+        if action == Some(TimerRecoveryAction::Close) {
+            self.db_repo.close_timer_at(
+                &timer.id,
+                &timer.task_id,
+                &last_activity_at.to_rfc3339(),
+                (last_activity_at - started_at).num_seconds(),
+            )?;
+            if let Some(task) = &task {
+                let mut frontmatter_updates = HashMap::new();
+                frontmatter_updates.insert("status".to_string(), "todo".to_string());
+                frontmatter_updates.insert("updated_at".to_string(), Utc::now().to_rfc3339());
+                let slug = task.task_dir_slug.as_deref().unwrap_or("task");
+                self.sync_task_to_md(&task.id, slug, &frontmatter_updates)?;
+            }
+        }
+        if let Some(action) = action {
+            info!(target: "planning", "recover_timers resolved: timer_id={}, action={:?}, last_activity_at={}", timer.id, action, last_activity_at.to_rfc3339());
+        }
+
+        Ok(TimerRecoveryResult {
+            dangling: Some(DanglingTimer {
+                task: match action {
+                    Some(_) => self.db_repo.get_task(&timer.task_id)?,
+                    None => task,
+                },
+                timer: self.db_repo.get_timer(&timer.id)?.unwrap_or(timer),
+                last_activity_at: last_activity_at.to_rfc3339(),
+            }),
+            resolved: action,
+        })
+    }
+
+    // Get a task's timer history, newest first
+    pub fn list_timers(&self, task_id: &str) -> Result<Vec<Timer>, ApiError> {
+        self.get_task_or_not_found(task_id)?;
+        self.db_repo.list_task_timers(task_id)
+    }
+
+    // Record time tracked without a running timer
+    pub fn add_manual_timer(
+        &self,
+        task_id: &str,
+        start: &str,
+        end: &str,
+    ) -> Result<Timer, ApiError> {
+        self.get_task_or_not_found(task_id)?;
+        let (start_at, stop_at) = self.parse_timer_range(start, end)?;
+        self.ensure_no_timer_overlap(&start_at, &stop_at, None)?;
+
+        let timer = self.db_repo.insert_timer(
+            task_id,
+            &start_at.to_rfc3339(),
+            &stop_at.to_rfc3339(),
+            (stop_at - start_at).num_seconds(),
+            TIMER_SOURCE_MANUAL_ENTRY,
+        )?;
+        info!(target: "planning", "add_manual_timer succeeded: task_id={}, timer_id={}, duration_sec={}", task_id, timer.id, timer.duration_sec);
+
+        Ok(timer)
+    }
+
+    // Correct the range of a stopped timer
+    pub fn edit_timer(&self, timer_id: &str, start: &str, end: &str) -> Result<Timer, ApiError> {
+        let timer = self.get_stopped_timer(timer_id)?;
+        let (start_at, stop_at) = self.parse_timer_range(start, end)?;
+        self.ensure_no_timer_overlap(&start_at, &stop_at, Some(timer_id))?;
+
+        self.db_repo.update_timer_range(
+            timer_id,
+            &start_at.to_rfc3339(),
+            &stop_at.to_rfc3339(),
+            (stop_at - start_at).num_seconds(),
+        )?;
+        info!(target: "planning", "edit_timer succeeded: task_id={}, timer_id={}", timer.task_id, timer_id);
+
+        self.get_stopped_timer(timer_id)
+    }
+
+    // Delete a stopped timer
+    pub fn delete_timer(&self, timer_id: &str) -> Result<(), ApiError> {
+        let timer = self.get_stopped_timer(timer_id)?;
+        self.db_repo.delete_timer(timer_id)?;
+        info!(target: "planning", "delete_timer succeeded: task_id={}, timer_id={}", timer.task_id, timer_id);
+        Ok(())
+    }
+
+    fn get_stopped_timer(&self, timer_id: &str) -> Result<Timer, ApiError> {
+        let timer = self.db_repo.get_timer(timer_id)?.ok_or_else(|| ApiError {
+            code: ErrorCode::NotFound,
+            message: format!("Timer with id {} not found", timer_id),
+            details: None,
+        })?;
+        if timer.stop_at.is_none() {
+            return Err(ApiError {
+                code: ErrorCode::InvalidStateTransition,
+                message: "Stop the running timer before changing it".to_string(),
+                details: Some(serde_json::json!({ "timer_id": timer_id })),
+            });
+        }
+        Ok(timer)
+    }
+
+    // Parse a completed, non-empty range that does not end in the future
+    fn parse_timer_range(
+        &self,
+        start: &str,
+        end: &str,
+    ) -> Result<(chrono::DateTime<Utc>, chrono::DateTime<Utc>), ApiError> {
+        match (
+            timezone::parse_instant(start, self.timezone),
+            timezone::parse_instant(end, self.timezone),
+        ) {
+            (Some(start_at), Some(stop_at)) if stop_at > start_at && stop_at <= Utc::now() => {
+                Ok((start_at, stop_at))
+            }
+            _ => Err(ApiError {
+                code: ErrorCode::InvalidSchedule,
+                message: "start and end must be valid past times with end after start".to_string(),
+                details: Some(serde_json::json!({ "start": start, "end": end })),
+            }),
+        }
+    }
+
+    fn ensure_no_timer_overlap(
+        &self,
+        start_at: &chrono::DateTime<Utc>,
+        stop_at: &chrono::DateTime<Utc>,
+        exclude_timer_id: Option<&str>,
+    ) -> Result<(), ApiError> {
+        let overlapping = self.db_repo.list_overlapping_timers(
+            &start_at.to_rfc3339(),
+            &stop_at.to_rfc3339(),
+            exclude_timer_id,
+        )?;
+        if overlapping.is_empty() {
+            return Ok(());
+        }
+        Err(ApiError {
+            code: ErrorCode::TimerOverlap,
+            message: "Time entry overlaps existing timers".to_string(),
+            details: Some(serde_json::json!({ "timers": overlapping })),
+        })
+    }
+
     // Open a daily log file (create if not exists)
     pub fn open_daily(&self, input: OpenDailyInput) -> Result<OpenDailyResponse, ApiError> {
         let op_id = Uuid::new_v4().to_string();
@@ -722,12 +2140,11 @@ Frontmatter 由系统维护；正文为你的笔记区。
                     md_path: existing_log.daily_md_path,
                 })
             } else {
-                // Create new daily log
-                // First, read the markdown file (will create default content if not exists)
-                let content = self.md_repo.read_daily_md(&input.day)?;
-
-                // Write default content to file
-                let _md_path = self.md_repo.upsert_daily_md(&input.day, &content)?;
+                // Create new daily log; a note already on disk is kept as written
+                if !self.md_repo.daily_md_exists(&input.day) {
+                    let content = self.render_daily_template(&input.day)?;
+                    self.md_repo.upsert_daily_md(&input.day, &content)?;
+                }
 
                 // Get relative path for storage
                 let relative_path = self.md_repo.get_daily_md_relative_path(&input.day);
@@ -755,6 +2172,127 @@ Frontmatter 由系统维护；正文为你的笔记区。
         result
     }
 
+    // Body of a new daily note: the configured template filled with the day's tasks, timers and
+    // scheduled blocks
+    fn render_daily_template(&self, day: &str) -> Result<String, ApiError> {
+        let date = chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d").map_err(|e| ApiError {
+            code: ErrorCode::DateTimeError,
+            message: format!("Failed to parse day: {}", e),
+            details: Some(serde_json::json!({ "day": day })),
+        })?;
+        let template = self
+            .daily_log
+            .template
+            .as_deref()
+            .unwrap_or(daily_log::DEFAULT_TEMPLATE);
+        let mut data = DailyTemplateData {
+            date: day.to_string(),
+            ..Default::default()
+        };
+        // Each variable the template does not use is skipped rather than queried
+        let uses = |name: &str| template.contains(&format!("{{{{{}}}}}", name));
+
+        let note_dir = format!("{}/daily", self.md_repo.layout.planning_dir);
+        let template_task = |task: &Task, time: Option<String>, done: Option<bool>| TemplateTask {
+            title: task.title.clone(),
+            link: task
+                .md_rel_path
+                .as_deref()
+                .map(|path| links::relative_link(&note_dir, path).replace(' ', "%20")),
+            time,
+            done,
+        };
+        let pad = chrono::Duration::days(1);
+
+        if uses("due_today") || uses("events") {
+            let (day_start, day_end) = schedule::day_bounds(date);
+            let due_tasks = self.db_repo.list_tasks_due_between(
+                &(date - pad).format("%Y-%m-%d").to_string(),
+                &(date + pad + pad).format("%Y-%m-%d").to_string(),
+            )?;
+            let scheduled_tasks = self.db_repo.list_scheduled_tasks(
+                &schedule::format_schedule_time(day_start - pad, self.timezone),
+                &schedule::format_schedule_time(day_end + pad, self.timezone),
+            )?;
+            let recurring_tasks = self.db_repo.list_recurring_tasks()?;
+            let calendar = calendar::build_calendar(
+                date,
+                date,
+                due_tasks,
+                &scheduled_tasks,
+                &recurring_tasks,
+                self.timezone,
+                &self.week,
+            );
+            if let Some(calendar_day) = calendar.get(&date) {
+                data.due_today = calendar_day
+                    .due
+                    .iter()
+                    .filter(|task| task.status != TaskStatus::Done)
+                    .map(|task| template_task(task, None, Some(false)))
+                    .collect();
+
+                let clock = |value: &str| {
+                    schedule::parse_schedule_time(value, self.timezone)
+                        .map(|time| time.format("%H:%M").to_string())
+                };
+                let mut events: Vec<(String, TemplateTask)> = Vec::new();
+                for block in &calendar_day.blocks {
+                    let Some(task) = scheduled_tasks.iter().find(|task| task.id == block.task_id)
+                    else {
+                        continue;
+                    };
+                    let time = format!(
+                        "{}–{}",
+                        clock(&block.start).unwrap_or_default(),
+                        clock(&block.end).unwrap_or_default()
+                    );
+                    events.push((block.start.clone(), template_task(task, Some(time), None)));
+                }
+                for instance in &calendar_day.recurrences {
+                    let Some(start) = instance.scheduled_start.as_deref() else {
+                        continue;
+                    };
+                    let time = clock(start).map(|time| format!("{}–", time));
+                    events.push((start.to_string(), template_task(instance, time, None)));
+                }
+                events.sort_by(|a, b| a.0.cmp(&b.0));
+                data.events = events.into_iter().map(|(_, task)| task).collect();
+            }
+        }
+
+        if uses("completed_yesterday") {
+            let yesterday = date - pad;
+            data.completed_yesterday = self
+                .db_repo
+                .list_completed_tasks_between(
+                    &(yesterday - pad).format("%Y-%m-%d").to_string(),
+                    &(date + pad).format("%Y-%m-%d").to_string(),
+                )?
+                .iter()
+                .filter(|task| {
+                    task.completed_at
+                        .as_deref()
+                        .and_then(|value| timezone::to_local_date(value, self.timezone))
+                        == Some(yesterday)
+                })
+                .map(|task| template_task(task, None, Some(true)))
+                .collect();
+        }
+
+        // Timers run now, so only today's note lists them
+        if uses("active_timers") && date == timezone::today_in(self.timezone) {
+            if let Some(timer) = self.db_repo.get_active_timer()? {
+                let task = self.db_repo.get_task_by_id(&timer.task_id)?;
+                let time = timezone::instant_to_local(&timer.start_at, self.timezone)
+                    .map(|start| format!("{}–", start.format("%H:%M")));
+                data.active_timers = vec![template_task(&task, time, None)];
+            }
+        }
+
+        Ok(daily_log::render_template(template, &data))
+    }
+
     // Open a task note file (create if not exists)
     pub fn open_task_note(&self, task_id: &str) -> Result<OpenTaskNoteResponse, ApiError> {
         let op_id = Uuid::new_v4().to_string();
@@ -774,7 +2312,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
             // Check if task exists
             if task.is_none() {
                 return Err(ApiError {
-                    code: "NotFound".to_string(),
+                    code: ErrorCode::NotFound,
                     message: format!("Task with id {} not found", task_id),
                     details: None,
                 });
@@ -804,6 +2342,7 @@ priority: {}
 tags: {}
 estimate_min: {}
 due_date: {}
+color: {}
 created_at: {}
 updated_at: {}
 ---
@@ -829,128 +2368,3081 @@ Frontmatter 由系统维护；正文为你的笔记区。
                         .map(|min| min.to_string())
                         .unwrap_or("null".to_string()),
                     task.due_date.as_deref().unwrap_or("null"),
+                    color_frontmatter(task.color.as_deref()),
                     task.created_at,
                     task.updated_at
                 );
 
-                // Write template to file
-                self.md_repo
-                    .upsert_task_md(&task.id, &slug, &task.title, &template)?;
+                // Write template to file
+                self.md_repo
+                    .upsert_task_md(&task.id, &slug, &task.title, &template)?;
+            }
+
+            // Get relative path
+            let relative_path = self.md_repo.get_task_md_relative_path(&task.id, &slug);
+
+            // Update task's note_path in database if needed
+            if task.note_path.is_none() || task.note_path != Some(relative_path.clone()) {
+                self.db_repo
+                    .update_task_note_path(&task.id, &relative_path)?;
+            }
+
+            Ok(OpenTaskNoteResponse {
+                md_path: relative_path,
+            })
+        })();
+
+        let elapsed = start.elapsed();
+
+        match &result {
+            Ok(_) => {
+                info!(target: "planning", "open_task_note succeeded: task_id={}, elapsed_ms={}", task_id, elapsed.as_millis());
+            }
+            Err(e) => {
+                error!(target: "planning", "open_task_note failed: task_id={}, error_code={}, error_message={}, elapsed_ms={}", task_id, &e.code, &e.message, elapsed.as_millis());
+            }
+        }
+
+        result
+    }
+
+    // Reorder tasks in batch
+    pub fn reorder_tasks(
+        &self,
+        tasks: Vec<ReorderTaskInput>,
+        override_wip_limit: bool,
+    ) -> Result<(), ApiError> {
+        let op_id = Uuid::new_v4().to_string();
+        let span = span!(
+            Level::INFO,
+            "planning.reorder_tasks",
+            op_id = op_id,
+            task_count = tasks.len()
+        );
+        let _enter = span.enter();
+
+        let start = std::time::Instant::now();
+
+        let result = (|| -> Result<(), ApiError> {
+            // Moving into a custom column sets the status that column maps to
+            let tasks = self.resolve_reorder_columns(tasks)?;
+
+            if !override_wip_limit {
+                let mut moves = Vec::with_capacity(tasks.len());
+                for input in &tasks {
+                    let before = self.get_task_or_not_found(&input.id)?;
+                    let mut after = before.clone();
+                    if let Some(status) = input.status {
+                        after.status = status;
+                        after.column_key = input.column_key.clone();
+                    }
+                    moves.push((before, after));
+                }
+                self.check_wip_limits(&moves)?;
+            }
+
+            let mut completed = Vec::new();
+            let mut status_changed = Vec::new();
+            for input in &tasks {
+                let Some(status) = input.status else {
+                    continue;
+                };
+                let before = self.get_task_or_not_found(&input.id)?.status;
+                if status != before {
+                    status_changed.push(input.id.clone());
+                    if status == TaskStatus::Done {
+                        completed.push(input.id.clone());
+                    }
+                }
+            }
+
+            // First update tasks in database
+            self.db_repo.reorder_tasks(tasks)?;
+
+            // Order is not kept in frontmatter, so only notes of tasks whose status changed are
+            // rewritten
+            let mut syncs = Vec::with_capacity(status_changed.len());
+            for task_id in &status_changed {
+                let updated_task = self.get_task_or_not_found(task_id)?;
+                let slug = updated_task
+                    .task_dir_slug
+                    .clone()
+                    .unwrap_or("task".to_string());
+                let updates = task_frontmatter_updates(&updated_task, &["updated_at", "status"]);
+                syncs.push((updated_task.id, slug, updates));
+            }
+            self.sync_tasks_to_md(syncs)?;
+            for task_id in &completed {
+                self.task_completed(task_id);
+            }
+
+            Ok(())
+        })();
+
+        let elapsed = start.elapsed();
+
+        match &result {
+            Ok(_) => {
+                info!(target: "planning", "reorder_tasks succeeded: elapsed_ms={}", elapsed.as_millis());
+                self.boards_changed();
+            }
+            Err(e) => {
+                error!(target: "planning", "reorder_tasks failed: error_code={}, error_message={}, elapsed_ms={}", &e.code, &e.message, elapsed.as_millis());
+            }
+        }
+
+        result
+    }
+
+    fn resolve_reorder_columns(
+        &self,
+        tasks: Vec<ReorderTaskInput>,
+    ) -> Result<Vec<ReorderTaskInput>, ApiError> {
+        let mut columns_by_board: HashMap<String, Vec<BoardColumn>> = HashMap::new();
+        let mut resolved = Vec::with_capacity(tasks.len());
+
+        for mut input in tasks {
+            if let Some(column_key) = input.column_key.clone() {
+                let task = self.get_task_or_not_found(&input.id)?;
+                let board_id = board::task_board_id(&task).to_string();
+                if !columns_by_board.contains_key(&board_id) {
+                    let columns = self.board_columns_or_default(&board_id)?;
+                    columns_by_board.insert(board_id.clone(), columns);
+                }
+                let column = columns_by_board[&board_id]
+                    .iter()
+                    .find(|column| column.key == column_key)
+                    .ok_or_else(|| ApiError {
+                        code: ErrorCode::InvalidInput,
+                        message: format!("Unknown column '{}' on board '{}'", column_key, board_id),
+                        details: Some(
+                            serde_json::json!({ "board_id": board_id, "column_key": column_key }),
+                        ),
+                    })?;
+                input.status = Some(column.maps_to);
+            }
+            resolved.push(input);
+        }
+
+        Ok(resolved)
+    }
+
+    // Reject moves that push a column past its WIP limit; tasks already in a column never trip it
+    fn check_wip_limits(&self, moves: &[(Task, Task)]) -> Result<(), ApiError> {
+        let mut board_ids: Vec<&str> = moves
+            .iter()
+            .map(|(_, after)| board::task_board_id(after))
+            .collect();
+        board_ids.sort_unstable();
+        board_ids.dedup();
+
+        for board_id in board_ids {
+            let columns = self.board_columns_or_default(board_id)?;
+            if columns.iter().all(|column| column.wip_limit.is_none()) {
+                continue;
+            }
+
+            // Project the board as it would look after the moves
+            let mut tasks = self.db_repo.list_board_tasks(board_id)?;
+            tasks.retain(|task| !moves.iter().any(|(before, _)| before.id == task.id));
+            tasks.extend(
+                moves
+                    .iter()
+                    .filter(|(_, after)| board::task_board_id(after) == board_id)
+                    .map(|(_, after)| after.clone()),
+            );
+
+            for column in &columns {
+                let Some(wip_limit) = column.wip_limit else {
+                    continue;
+                };
+                let in_column = |task: &Task| {
+                    board::column_for_task(&columns, task).map(|c| c.key.as_str())
+                        == Some(column.key.as_str())
+                };
+
+                let entering: Vec<&str> = moves
+                    .iter()
+                    .filter(|(before, after)| {
+                        board::task_board_id(after) == board_id
+                            && in_column(after)
+                            && !(board::task_board_id(before) == board_id && in_column(before))
+                    })
+                    .map(|(_, after)| after.id.as_str())
+                    .collect();
+                if entering.is_empty() {
+                    continue;
+                }
+
+                let count = tasks.iter().filter(|task| in_column(task)).count() as i64;
+                if count > wip_limit {
+                    return Err(ApiError {
+                        code: ErrorCode::WipLimitExceeded,
+                        message: format!(
+                            "Column '{}' is limited to {} tasks",
+                            column.name, wip_limit
+                        ),
+                        details: Some(serde_json::json!({
+                            "board_id": board_id,
+                            "column_key": column.key,
+                            "wip_limit": wip_limit,
+                            "count": count,
+                            "task_ids": entering,
+                        })),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn board_columns_or_default(&self, board_id: &str) -> Result<Vec<BoardColumn>, ApiError> {
+        let columns = self.db_repo.list_board_columns(board_id)?;
+        if columns.is_empty() {
+            return Ok(board::default_columns(board_id));
+        }
+        Ok(columns)
+    }
+
+    // Rename a task directory to match its current title; None when the slug already matches.
+    // The directory is moved back if the database update fails.
+    fn rename_task_dir(&self, task: &Task) -> Result<Option<String>, ApiError> {
+        let Some(old_slug) = task.task_dir_slug.as_deref() else {
+            return Ok(None);
+        };
+        if generate_slug(&task.title) == old_slug {
+            return Ok(None);
+        }
+        let slug = self.unique_task_slug(&task.title, &HashSet::new())?;
+        let layout = &self.md_repo.layout;
+        let from = task_dir_path(&self.vault_root, layout, &task.id, old_slug);
+        let to = task_dir_path(&self.vault_root, layout, &task.id, &slug);
+        if !from.is_dir() {
+            return Ok(None);
+        }
+        path_policy::ensure_no_symlink(&from)?;
+        std::fs::rename(&from, &to).map_err(|err| {
+            rename_error("Failed to rename task directory", &from, &to, Some(err))
+        })?;
+
+        let old_relative_path = self.md_repo.get_task_md_relative_path(&task.id, old_slug);
+        let relative_path = self.md_repo.get_task_md_relative_path(&task.id, &slug);
+        if let Err(err) =
+            self.db_repo
+                .rename_task_slug(&task.id, &slug, &old_relative_path, &relative_path)
+        {
+            if let Err(rollback_err) = std::fs::rename(&to, &from) {
+                error!(target: "planning", "Failed to move task directory back: {}", rollback_err);
+            }
+            return Err(err);
+        }
+
+        // Links to the old note location are best effort; the rename itself already succeeded
+        let old_dir = format!("{}/{}", layout.tasks_dir, old_slug);
+        let new_dir = format!("{}/{}", layout.tasks_dir, slug);
+        if let Err(err) = self.rename_note_links(&old_dir, &new_dir) {
+            warn!(target: "planning", "task note links not updated: {}", err.message);
+        }
+        if let Err(err) =
+            vault_service::propagate_link_updates(&self.vault_root, &old_dir, &new_dir)
+        {
+            warn!(target: "planning", "note links not updated: {}", err.message);
+        }
+        Ok(Some(slug))
+    }
+
+    // Move task notes found outside the vault layout, in the flat legacy directory or at a stored
+    // md_rel_path the layout no longer produces, to their place in the layout and point the task
+    // at it. A note already in place wins: identical copies are removed, differing ones reported.
+    pub fn consolidate_task_notes(&self, dry_run: bool) -> Result<TaskNoteConsolidation, ApiError> {
+        let layout = self.md_repo.layout.clone();
+        let legacy_dir = paths::planning_dir(&self.vault_root, &layout).join(LEGACY_TASK_NOTES_DIR);
+        let legacy_rel = format!("{}/{}", layout.planning_dir, LEGACY_TASK_NOTES_DIR);
+        let mut report = TaskNoteConsolidation {
+            dry_run,
+            ..Default::default()
+        };
+        let mut known_ids = HashSet::new();
+        let mut reserved = HashSet::new();
+
+        for task in self.db_repo.list_all_tasks()? {
+            known_ids.insert(task.id.clone());
+            let slug = match task.task_dir_slug.clone() {
+                Some(slug) => slug,
+                None => self.unique_task_slug(&task.title, &reserved)?,
+            };
+            reserved.insert(slug.clone());
+            let target_rel = paths::task_md_relative_path(&layout, &task.id, &slug);
+            let target = self.vault_root.join(&target_rel);
+
+            let mut sources = Vec::new();
+            if let Some(md_rel_path) = task.md_rel_path.as_deref() {
+                if md_rel_path != target_rel && self.vault_root.join(md_rel_path).is_file() {
+                    sources.push(md_rel_path.to_string());
+                }
+            }
+            let legacy_note = format!("{}/{}.md", legacy_rel, task.id);
+            if !sources.contains(&legacy_note) && self.vault_root.join(&legacy_note).is_file() {
+                sources.push(legacy_note);
+            }
+
+            let mut target_exists = target.is_file();
+            let mut moved_from = None;
+            for source_rel in sources {
+                let source = self.vault_root.join(&source_rel);
+                if !target_exists {
+                    if !dry_run {
+                        path_policy::ensure_no_symlink(&source)?;
+                        if let Some(parent) = target.parent() {
+                            path_policy::ensure_or_create_dir_in_vault(&self.vault_root, parent)?;
+                        }
+                        std::fs::rename(&source, &target).map_err(|err| {
+                            rename_error("Failed to move task note", &source, &target, Some(err))
+                        })?;
+                    }
+                    target_exists = true;
+                    report.moved.push(TaskNoteMove {
+                        task_id: task.id.clone(),
+                        from: source_rel.clone(),
+                        to: target_rel.clone(),
+                    });
+                    moved_from = Some(source_rel);
+                    continue;
+                }
+                // In a dry run the planned move has not happened, so compare with its source
+                let in_place = match (&moved_from, dry_run) {
+                    (Some(from), true) => self.vault_root.join(from),
+                    _ => target.clone(),
+                };
+                let identical = matches!(
+                    (std::fs::read(&source), std::fs::read(&in_place)),
+                    (Ok(a), Ok(b)) if a == b
+                );
+                if identical {
+                    if !dry_run {
+                        std::fs::remove_file(&source).map_err(|err| ApiError {
+                            code: ErrorCode::FileDeleteError,
+                            message: format!("Failed to remove duplicate task note: {}", err),
+                            details: Some(serde_json::json!({ "path": source_rel })),
+                        })?;
+                    }
+                    report.deduplicated.push(source_rel);
+                } else {
+                    report.conflicts.push(TaskNoteConflict {
+                        task_id: Some(task.id.clone()),
+                        path: source_rel,
+                        reason: format!("Differs from the task note at {}", target_rel),
+                    });
+                }
+            }
+
+            let stale_path = task.md_rel_path.as_deref() != Some(target_rel.as_str())
+                || task.task_dir_slug.as_deref() != Some(slug.as_str());
+            if !target_exists || !stale_path {
+                continue;
+            }
+            report.updated_tasks += 1;
+            if dry_run {
+                continue;
+            }
+            let old_rel = task.md_rel_path.clone().unwrap_or_default();
+            self.db_repo
+                .rename_task_slug(&task.id, &slug, &old_rel, &target_rel)?;
+            // Links follow the note from wherever it actually was
+            let linked_from = match moved_from {
+                Some(from) => {
+                    if from != old_rel && task.note_path.as_deref() == Some(from.as_str()) {
+                        self.db_repo.update_task_note_path(&task.id, &target_rel)?;
+                    }
+                    from
+                }
+                None => old_rel,
+            };
+            if !linked_from.is_empty() {
+                if let Err(err) = vault_service::propagate_link_updates(
+                    &self.vault_root,
+                    &linked_from,
+                    &target_rel,
+                ) {
+                    warn!(target: "planning", "note links not updated: {}", err.message);
+                }
+            }
+        }
+
+        // Whatever is left in the legacy directory belongs to no task
+        if let Ok(entries) = std::fs::read_dir(&legacy_dir) {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let Some(task_id) = name.strip_suffix(".md") else {
+                    continue;
+                };
+                if !known_ids.contains(task_id) {
+                    report.conflicts.push(TaskNoteConflict {
+                        task_id: None,
+                        path: format!("{}/{}", legacy_rel, name),
+                        reason: "No task with this id".to_string(),
+                    });
+                }
+            }
+        }
+        if !dry_run {
+            let _ = std::fs::remove_dir(&legacy_dir);
+        }
+
+        info!(target: "planning", "consolidate_task_notes finished: dry_run={}, moved={}, deduplicated={}, conflicts={}, updated_tasks={}", dry_run, report.moved.len(), report.deduplicated.len(), report.conflicts.len(), report.updated_tasks);
+        if !dry_run {
+            self.boards_changed();
+        }
+        Ok(report)
+    }
+
+    // Give every task sharing a slug with an older task a directory of its own, then enforce
+    // unique slugs. The shared note stays with the oldest task; the others get a fresh note
+    // written from their database row.
+    pub fn repair_task_slugs(&self) -> Result<SlugRepairResult, ApiError> {
+        let mut reassigned = Vec::new();
+        let mut kept = HashSet::new();
+        let mut reserved = HashSet::new();
+        for task in self.db_repo.list_duplicate_slug_tasks()? {
+            let Some(old_slug) = task.task_dir_slug.clone() else {
+                continue;
+            };
+            if kept.insert(old_slug.to_lowercase()) {
+                continue;
+            }
+            let slug = self.unique_task_slug(&task.title, &reserved)?;
+            reserved.insert(slug.clone());
+            let set_note_path = task.note_path.is_none() || task.note_path == task.md_rel_path;
+            self.write_new_task_md(&task, &slug, set_note_path)?;
+            reassigned.push(SlugReassignment {
+                task_id: task.id.clone(),
+                old_slug,
+                new_slug: slug,
+            });
+        }
+        self.db_repo.ensure_slug_index()?;
+        info!(target: "planning", "repair_task_slugs succeeded: reassigned={}", reassigned.len());
+        self.boards_changed();
+        Ok(SlugRepairResult { reassigned })
+    }
+
+    // Slug for a new task whose directory does not exist yet and is not reserved
+    fn unique_task_slug(
+        &self,
+        title: &str,
+        reserved: &HashSet<String>,
+    ) -> Result<String, ApiError> {
+        let base_slug = generate_slug(title);
+        let mut slug = base_slug.clone();
+        let mut counter = 1;
+
+        // Loop until we find a unique slug: no task holds it and its directory does not exist
+        loop {
+            // task_dir_path now ignores task_id, so we can pass an empty string
+            let dir_path = task_dir_path(&self.md_repo.vault_root, &self.md_repo.layout, "", &slug);
+            if !dir_path.exists()
+                && !reserved.contains(&slug)
+                && !self.db_repo.is_slug_taken(&slug)?
+            {
+                break;
+            }
+            slug = format!("{}_{}", base_slug, counter);
+            counter += 1;
+        }
+        Ok(slug)
+    }
+
+    // Get the kanban columns of a board (built-in columns when none are registered)
+    pub fn list_board_columns(&self, board_id: &str) -> Result<Vec<BoardColumn>, ApiError> {
+        self.board_columns_or_default(board_id)
+    }
+
+    // Boards with settings of their own
+    pub fn list_boards(&self) -> Result<Vec<BoardMeta>, ApiError> {
+        self.db_repo.list_boards()
+    }
+
+    // Set a board's theme color; None or an empty string clears it
+    pub fn set_board_color(
+        &self,
+        board_id: &str,
+        color: Option<&str>,
+    ) -> Result<BoardMeta, ApiError> {
+        let board_id = board_id.trim();
+        if board_id.is_empty() {
+            return Err(ApiError {
+                code: ErrorCode::BoardIdRequired,
+                message: "board_id cannot be empty".to_string(),
+                details: None,
+            });
+        }
+        let color = color
+            .filter(|value| !value.trim().is_empty())
+            .map(board::normalize_color)
+            .transpose()?;
+        let meta = self.db_repo.set_board_color(board_id, color.as_deref())?;
+        info!(target: "planning", "set_board_color succeeded: board_id={}, color={:?}", board_id, meta.color);
+        Ok(meta)
+    }
+
+    // Entire planning dataset as one versioned document
+    pub fn export_all(&self) -> Result<PlanningExport, ApiError> {
+        let settings = settings_repo::get_planning_settings(&self.vault_root)?;
+        Ok(PlanningExport {
+            format: EXPORT_FORMAT.to_string(),
+            version: EXPORT_VERSION,
+            exported_at: Utc::now().to_rfc3339(),
+            tasks: self.db_repo.list_all_tasks()?,
+            timers: self.db_repo.list_all_timers()?,
+            day_logs: self.db_repo.list_day_logs()?,
+            board_columns: self.db_repo.list_all_board_columns()?,
+            boards: self.db_repo.list_boards()?,
+            note_links: self.db_repo.list_all_note_links()?,
+            board_fields: self.db_repo.list_all_board_fields()?,
+            task_fields: self.db_repo.list_task_field_values(None)?,
+            settings: Some(settings),
+        })
+    }
+
+    // Load an export into this vault; replace clears existing planning data and settings first
+    pub fn import_all(
+        &self,
+        mut snapshot: PlanningExport,
+        replace: bool,
+    ) -> Result<ImportResult, ApiError> {
+        let span = span!(Level::INFO, "planning.import_all", replace = replace);
+        let _enter = span.enter();
+
+        if snapshot.format != EXPORT_FORMAT || snapshot.version > EXPORT_VERSION {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "Unsupported planning export".to_string(),
+                details: Some(serde_json::json!({
+                    "format": snapshot.format,
+                    "version": snapshot.version,
+                    "supported_version": EXPORT_VERSION,
+                })),
+            });
+        }
+        let mut ids = std::collections::HashSet::new();
+        if let Some(task) = snapshot
+            .tasks
+            .iter()
+            .find(|task| task.id.trim().is_empty() || !ids.insert(task.id.as_str()))
+        {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "Export contains an empty or duplicate task id".to_string(),
+                details: Some(serde_json::json!({ "task_id": task.id })),
+            });
+        }
+
+        // Slugs are unique across tasks: an imported task keeps its slug unless another task, kept
+        // or imported before it, holds that slug already
+        let mut taken: HashSet<String> = HashSet::new();
+        if !replace {
+            for task in self.db_repo.list_all_tasks()? {
+                if let Some(slug) = task
+                    .task_dir_slug
+                    .filter(|_| !ids.contains(task.id.as_str()))
+                {
+                    taken.insert(slug.to_lowercase());
+                }
+            }
+        }
+        let mut imported_slugs = Vec::with_capacity(snapshot.tasks.len());
+        for task in &snapshot.tasks {
+            let wanted = task
+                .task_dir_slug
+                .clone()
+                .filter(|slug| !slug.starts_with('.') && generate_slug(slug) == *slug)
+                .unwrap_or_else(|| generate_slug(&task.title));
+            let slug = if taken.contains(&wanted.to_lowercase()) {
+                let reserved = imported_slugs.iter().cloned().collect();
+                self.unique_task_slug(&task.title, &reserved)?
+            } else {
+                wanted
+            };
+            taken.insert(slug.to_lowercase());
+            imported_slugs.push(slug);
+        }
+        for (task, slug) in snapshot.tasks.iter_mut().zip(&imported_slugs) {
+            task.task_dir_slug = Some(slug.clone());
+            task.md_rel_path = Some(self.md_repo.get_task_md_relative_path(&task.id, slug));
+        }
+
+        self.db_repo.import_snapshot(&snapshot, replace)?;
+        // Exports written before schedules were stored as UTC carry wall-clock times of their vault
+        let snapshot_tz = snapshot
+            .settings
+            .as_ref()
+            .map_or(self.timezone, settings_repo::resolve_timezone);
+        self.db_repo.normalize_schedule_times(snapshot_tz)?;
+
+        // Task notes live next to the database only when the vault itself was copied
+        let mut restored_notes = 0;
+        for (task, slug) in snapshot.tasks.iter().zip(imported_slugs) {
+            if self.md_repo.task_md_stamp(&task.id, &slug).is_none() {
+                self.md_repo.upsert_task_md(
+                    &task.id,
+                    &slug,
+                    &task.title,
+                    &task_md_template(task),
+                )?;
+                restored_notes += 1;
+            }
+        }
+
+        let settings_imported = match snapshot.settings.clone() {
+            Some(settings) if replace => {
+                settings_repo::save_planning_settings(&self.vault_root, settings)?;
+                true
+            }
+            _ => false,
+        };
+
+        let result = ImportResult {
+            replaced: replace,
+            tasks: snapshot.tasks.len(),
+            timers: snapshot.timers.len(),
+            day_logs: snapshot.day_logs.len(),
+            board_columns: snapshot.board_columns.len(),
+            note_links: snapshot.note_links.len(),
+            task_fields: snapshot.task_fields.len(),
+            restored_notes,
+            settings_imported,
+        };
+        info!(target: "planning", "import_all succeeded: replace={}, tasks={}, timers={}, restored_notes={}", replace, result.tasks, result.timers, restored_notes);
+        self.boards_changed();
+        Ok(result)
+    }
+
+    // Pull tasks, timers and day logs from another vault's planning.db into this one
+    pub fn merge_vault(&self, other_vault: &Path) -> Result<MergeVaultResult, ApiError> {
+        let span = span!(Level::INFO, "planning.merge_vault", other_vault = %other_vault.display());
+        let _enter = span.enter();
+
+        path_policy::ensure_no_symlink(other_vault)?;
+        let other_root = other_vault
+            .canonicalize()
+            .map_err(|err| map_io_error(ErrorCode::NotFound, "Vault path not found", err))?;
+        if !other_root.is_dir() {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "Vault path is not a directory".to_string(),
+                details: Some(serde_json::json!({ "path": other_vault.to_string_lossy() })),
+            });
+        }
+        if self.vault_root.canonicalize().ok().as_deref() == Some(other_root.as_path()) {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "Cannot merge a vault into itself".to_string(),
+                details: None,
+            });
+        }
+
+        let other_settings = settings_repo::get_planning_settings(&other_root)?;
+        let db_path = paths::planning_db_path(&other_root, &other_settings.layout);
+        if !db_path.is_file() {
+            return Err(ApiError {
+                code: ErrorCode::NotFound,
+                message: "Vault has no planning database".to_string(),
+                details: Some(serde_json::json!({ "path": db_path.to_string_lossy() })),
+            });
+        }
+        let foreign = planning_repo::read_foreign_planning_db(&db_path)?;
+
+        let mut result = MergeVaultResult::default();
+        let mut reserved_slugs: HashSet<String> = self
+            .db_repo
+            .list_all_tasks()?
+            .into_iter()
+            .filter_map(|task| task.task_dir_slug)
+            .collect();
+
+        // Imported task (with its new id and slug) and where its note lives in the other vault
+        let mut id_map: HashMap<String, String> = HashMap::new();
+        let mut merged_tasks = Vec::new();
+        for task in foreign.tasks {
+            let mut merged = task.clone();
+            if let Some(existing) = self.db_repo.get_task(&task.id)? {
+                // Same task already merged (or both vaults were copied from one another)
+                if existing.created_at == task.created_at && existing.title == task.title {
+                    id_map.insert(task.id.clone(), task.id.clone());
+                    result.tasks_skipped += 1;
+                    continue;
+                }
+                merged.id = Uuid::new_v4().to_string();
+                result.tasks_remapped += 1;
+            }
+            let slug = self.unique_task_slug(&task.title, &reserved_slugs)?;
+            reserved_slugs.insert(slug.clone());
+            let relative_path = self.md_repo.get_task_md_relative_path(&merged.id, &slug);
+            if merged.note_path.is_none() || merged.note_path == task.md_rel_path {
+                merged.note_path = Some(relative_path.clone());
+            }
+            merged.task_dir_slug = Some(slug);
+            merged.md_rel_path = Some(relative_path);
+
+            id_map.insert(task.id.clone(), merged.id.clone());
+            merged_tasks.push((task, merged));
+        }
+
+        let mut timers = Vec::new();
+        for mut timer in foreign.timers {
+            let Some(task_id) = id_map.get(&timer.task_id) else {
+                continue;
+            };
+            if let Some(existing) = self.db_repo.get_timer(&timer.id)? {
+                if existing.task_id == *task_id && existing.start_at == timer.start_at {
+                    continue;
+                }
+                timer.id = Uuid::new_v4().to_string();
+            }
+            timer.task_id = task_id.clone();
+            timers.push(timer);
+        }
+
+        // Days already logged here keep their own note
+        let mut day_logs = Vec::new();
+        for day_log in foreign.day_logs {
+            if self.db_repo.get_day_log(&day_log.day)?.is_some() {
+                result.day_logs_skipped += 1;
+                continue;
+            }
+            let relative_path = self.md_repo.get_daily_md_relative_path(&day_log.day);
+            if !self.md_repo.daily_md_exists(&day_log.day) {
+                let source = other_root.join(&day_log.daily_md_path);
+                let content = path_policy::ensure_abs_file_in_vault(&other_root, &source)
+                    .and_then(|source| std::fs::read_to_string(source).map_err(ApiError::from));
+                if let Ok(content) = content {
+                    self.md_repo.write_vault_md(&relative_path, &content)?;
+                    result.notes_copied += 1;
+                }
+            }
+            day_logs.push(DayLog {
+                daily_md_path: relative_path,
+                ..day_log
+            });
+        }
+
+        // Field values come along with the tasks imported now, under their new ids
+        let task_fields = foreign
+            .task_fields
+            .into_iter()
+            .filter_map(|value| {
+                let (_, merged) = merged_tasks
+                    .iter()
+                    .find(|(original, _)| original.id == value.task_id)?;
+                Some(TaskFieldValue {
+                    task_id: merged.id.clone(),
+                    ..value
+                })
+            })
+            .collect();
+
+        let snapshot = PlanningExport {
+            format: EXPORT_FORMAT.to_string(),
+            version: EXPORT_VERSION,
+            exported_at: Utc::now().to_rfc3339(),
+            tasks: merged_tasks
+                .iter()
+                .map(|(_, merged)| merged.clone())
+                .collect(),
+            timers,
+            day_logs,
+            board_columns: Vec::new(),
+            boards: Vec::new(),
+            note_links: Vec::new(),
+            board_fields: Vec::new(),
+            task_fields,
+            settings: None,
+        };
+        self.db_repo.import_snapshot(&snapshot, false)?;
+
+        // Bring each task note along; a note that cannot be read is recreated from the template
+        for (original, merged) in &merged_tasks {
+            let slug = merged.task_dir_slug.as_deref().unwrap_or_default();
+            let source = original.task_dir_slug.as_deref().map(|old_slug| {
+                paths::task_md_path(&other_root, &other_settings.layout, &original.id, old_slug)
+            });
+            let content = source
+                .filter(|source| source.is_file())
+                .and_then(|source| path_policy::ensure_abs_file_in_vault(&other_root, &source).ok())
+                .and_then(|source| std::fs::read_to_string(source).ok());
+            let copied = content.is_some();
+            let content = content.unwrap_or_else(|| task_md_template(merged));
+            self.md_repo
+                .upsert_task_md(&merged.id, slug, &merged.title, &content)?;
+            if copied {
+                result.notes_copied += 1;
+            }
+        }
+
+        // Schedules from a vault not yet converted are wall-clock times of that vault
+        self.db_repo
+            .normalize_schedule_times(settings_repo::resolve_timezone(&other_settings))?;
+
+        result.tasks_imported = snapshot.tasks.len();
+        result.timers_imported = snapshot.timers.len();
+        result.day_logs_imported = snapshot.day_logs.len();
+        info!(target: "planning", "merge_vault succeeded: tasks={}, remapped={}, skipped={}, timers={}, day_logs={}", result.tasks_imported, result.tasks_remapped, result.tasks_skipped, result.timers_imported, result.day_logs_imported);
+        self.boards_changed();
+        Ok(result)
+    }
+
+    // Render a board into a markdown file under exports/ for sharing or printing
+    pub fn export_board_md(&self, board_id: &str) -> Result<ExportBoardResponse, ApiError> {
+        let columns = self.board_columns_or_default(board_id)?;
+        let tasks = self.db_repo.list_board_tasks(board_id)?;
+        let colors = self.db_repo.list_board_colors()?;
+        let Some(board) = board::group_boards(&columns, &tasks, &colors)
+            .into_iter()
+            .find(|board| board.board_id == board_id)
+        else {
+            return Err(ApiError {
+                code: ErrorCode::NotFound,
+                message: format!("Board {} not found", board_id),
+                details: None,
+            });
+        };
+
+        let fields = self.list_board_field_values(board_id)?;
+        let now = timezone::now_in(self.timezone);
+        let content = board::render_board_markdown(
+            &board,
+            &fields,
+            &now.format("%Y-%m-%d %H:%M").to_string(),
+        );
+        let md_path = format!(
+            "{}/{}-{}.md",
+            BOARD_EXPORT_DIR,
+            generate_slug(board_id),
+            now.format("%Y-%m-%d")
+        );
+        self.md_repo.write_vault_md(&md_path, &content)?;
+        info!(target: "planning", "export_board_md succeeded: board_id={}, md_path={}", board_id, md_path);
+
+        Ok(ExportBoardResponse {
+            md_path,
+            task_count: tasks.len(),
+        })
+    }
+
+    // Write the task hierarchy, boards -> tasks -> subtasks with their dates, as an org-mode or
+    // TaskPaper file under exports/; every board unless one is given
+    pub fn export_plaintext(
+        &self,
+        format: PlainTextFormat,
+        board_id: Option<&str>,
+    ) -> Result<PlainTextExportResponse, ApiError> {
+        let tasks = match board_id {
+            Some(board_id) => self.db_repo.list_board_tasks(board_id)?,
+            None => self.list_tasks(None, false)?,
+        };
+        let mut grouped: BTreeMap<String, Vec<Task>> = BTreeMap::new();
+        if let Some(board_id) = board_id {
+            grouped.insert(board_id.to_string(), Vec::new());
+        }
+        for task in tasks {
+            grouped
+                .entry(board::task_board_id(&task).to_string())
+                .or_default()
+                .push(task);
+        }
+        let boards: Vec<BoardTasks> = grouped
+            .into_iter()
+            .map(|(board_id, mut tasks)| {
+                tasks.sort_by_key(|task| task.order_index);
+                BoardTasks { board_id, tasks }
+            })
+            .collect();
+
+        let now = timezone::now_in(self.timezone);
+        let content = match format {
+            PlainTextFormat::Org => plaintext_export::render_org(
+                &boards,
+                &now.format("[%Y-%m-%d %a %H:%M]").to_string(),
+                self.timezone,
+            ),
+            PlainTextFormat::Taskpaper => {
+                plaintext_export::render_taskpaper(&boards, self.timezone)
+            }
+        };
+        let name = board_id
+            .map(generate_slug)
+            .unwrap_or_else(|| "planning".to_string());
+        let path = format!(
+            "{}/{}-{}.{}",
+            BOARD_EXPORT_DIR,
+            name,
+            now.format("%Y-%m-%d"),
+            format.extension()
+        );
+        self.md_repo.write_vault_md(&path, &content)?;
+        let task_count = boards.iter().map(|board| board.tasks.len()).sum();
+        info!(target: "planning", "export_plaintext succeeded: format={}, path={}, boards={}, tasks={}", format.extension(), path, boards.len(), task_count);
+
+        Ok(PlainTextExportResponse {
+            format,
+            path,
+            board_count: boards.len(),
+            task_count,
+        })
+    }
+
+    // Write <dir>/<board>.md for every board: a checklist of its tasks by column, linking their
+    // notes. Files whose content is unchanged are not rewritten, and generated files of boards
+    // that no longer exist are removed; files without the generated marker are never touched.
+    pub fn write_board_indexes(&self) -> Result<BoardIndexResult, ApiError> {
+        let dir = self.board_index.dir.trim().trim_matches('/');
+        if dir.is_empty() {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "Board index directory is empty".to_string(),
+                details: None,
+            });
+        }
+        let index_dir = self.vault_root.join(dir);
+        path_policy::ensure_or_create_dir_in_vault(&self.vault_root, &index_dir)?;
+
+        // Done tasks follow the kanban's done window
+        let done_since = (self.done_window_days > 0).then(|| {
+            (Utc::now() - chrono::Duration::days(i64::from(self.done_window_days))).to_rfc3339()
+        });
+        let tasks: Vec<Task> = self
+            .list_tasks(None, false)?
+            .into_iter()
+            .filter(|task| match (&done_since, task.status) {
+                (Some(since), TaskStatus::Done) => task
+                    .completed_at
+                    .as_deref()
+                    .is_none_or(|completed_at| completed_at >= since.as_str()),
+                _ => true,
+            })
+            .collect();
+        let columns = self.db_repo.list_all_board_columns()?;
+        let colors = self.db_repo.list_board_colors()?;
+
+        let mut result = BoardIndexResult::default();
+        let mut current = HashSet::new();
+        for kanban in board::group_boards(&columns, &tasks, &colors) {
+            let rel_path = format!("{}/{}.md", dir, generate_slug(&kanban.board_id));
+            current.insert(rel_path.clone());
+            let content = board::render_board_index(&kanban, dir);
+            match std::fs::read_to_string(self.vault_root.join(&rel_path)) {
+                Ok(existing) if existing == content => continue,
+                Ok(existing) if board::board_index_owner(&existing).is_none() => {
+                    warn!(target: "planning", "board index skipped, file was not generated: path={}", rel_path);
+                    continue;
+                }
+                _ => {}
+            }
+            match self.md_repo.write_vault_md(&rel_path, &content) {
+                Ok(_) => result.written.push(rel_path),
+                Err(e) if e.code == ErrorCode::NoteLocked => result.locked.push(rel_path),
+                Err(e) => return Err(e),
+            }
+        }
+
+        let entries = std::fs::read_dir(&index_dir).map_err(|e| ApiError {
+            code: ErrorCode::FileReadError,
+            message: format!("Failed to read board index directory: {}", e),
+            details: Some(serde_json::json!({ "path": dir })),
+        })?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let rel_path = format!("{}/{}", dir, name);
+            if !name.ends_with(".md") || current.contains(&rel_path) {
+                continue;
+            }
+            let generated = std::fs::read_to_string(&path)
+                .is_ok_and(|content| board::board_index_owner(&content).is_some());
+            if generated && std::fs::remove_file(&path).is_ok() {
+                result.removed.push(rel_path);
+            }
+        }
+        info!(target: "planning", "write_board_indexes succeeded: written={}, removed={}, locked={}", result.written.len(), result.removed.len(), result.locked.len());
+
+        Ok(result)
+    }
+
+    // Replace the kanban columns of a board
+    pub fn save_board_columns(
+        &self,
+        board_id: &str,
+        inputs: Vec<BoardColumnInput>,
+    ) -> Result<Vec<BoardColumn>, ApiError> {
+        if inputs.is_empty() {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "A board needs at least one column".to_string(),
+                details: None,
+            });
+        }
+
+        let mut columns: Vec<BoardColumn> = Vec::with_capacity(inputs.len());
+        for (index, input) in inputs.into_iter().enumerate() {
+            let key = input.key.trim().to_string();
+            if key.is_empty() || columns.iter().any(|column| column.key == key) {
+                return Err(ApiError {
+                    code: ErrorCode::InvalidInput,
+                    message: "Column keys must be non-empty and unique".to_string(),
+                    details: Some(serde_json::json!({ "key": input.key })),
+                });
+            }
+            columns.push(BoardColumn {
+                board_id: board_id.to_string(),
+                key,
+                name: input.name,
+                wip_limit: input.wip_limit.filter(|limit| *limit > 0),
+                maps_to: input.maps_to,
+                order_index: index as i64,
+            });
+        }
+
+        self.db_repo.replace_board_columns(board_id, &columns)?;
+        info!(target: "planning", "save_board_columns succeeded: board_id={}, columns={}", board_id, columns.len());
+        self.boards_changed();
+
+        Ok(columns)
+    }
+
+    pub fn list_board_fields(&self, board_id: &str) -> Result<Vec<BoardField>, ApiError> {
+        self.db_repo.list_board_fields(board_id)
+    }
+
+    // Replace the custom field schema of a board; an empty list removes it
+    pub fn save_board_fields(
+        &self,
+        board_id: &str,
+        inputs: Vec<BoardFieldInput>,
+    ) -> Result<Vec<BoardField>, ApiError> {
+        task_fields::validate_schema(&inputs)?;
+        let fields: Vec<BoardField> = inputs
+            .into_iter()
+            .enumerate()
+            .map(|(index, input)| BoardField {
+                board_id: board_id.to_string(),
+                key: input.key,
+                name: input.name.trim().to_string(),
+                kind: input.kind,
+                options: input
+                    .options
+                    .iter()
+                    .map(|option| option.trim().to_string())
+                    .filter(|option| !option.is_empty())
+                    .collect(),
+                required: input.required,
+                order_index: index as i64,
+            })
+            .collect();
+
+        self.db_repo.replace_board_fields(board_id, &fields)?;
+        info!(target: "planning", "save_board_fields succeeded: board_id={}, fields={}", board_id, fields.len());
+
+        Ok(fields)
+    }
+
+    pub fn get_task_fields(&self, task_id: &str) -> Result<TaskFieldsDTO, ApiError> {
+        let task = self.get_task_or_not_found(task_id)?;
+        let board_id = board::task_board_id(&task).to_string();
+        let schema = self.db_repo.list_board_fields(&board_id)?;
+        let values = self.db_repo.get_task_fields(task_id)?;
+        Ok(TaskFieldsDTO {
+            task_id: task.id,
+            board_id,
+            missing_required: task_fields::missing_required(&schema, &values),
+            values,
+            schema,
+        })
+    }
+
+    // Set custom field values of a task; None or an empty value clears a field. Values are
+    // checked against the board schema, stored, then mirrored into the task frontmatter.
+    pub fn set_task_fields(
+        &self,
+        task_id: &str,
+        values: BTreeMap<String, Option<String>>,
+    ) -> Result<TaskFieldsDTO, ApiError> {
+        let span = span!(Level::INFO, "planning.set_task_fields", task_id = task_id);
+        let _enter = span.enter();
+
+        let task = self.get_task_or_not_found(task_id)?;
+        let schema = self
+            .db_repo
+            .list_board_fields(board::task_board_id(&task))?;
+        let mut updates = BTreeMap::new();
+        for (key, value) in values {
+            task_fields::validate_field_key(&key)?;
+            let field = schema.iter().find(|field| field.key == key);
+            let value = match value.as_deref().map(str::trim) {
+                Some(value) if !value.is_empty() => {
+                    Some(task_fields::normalize_value(field, &key, value)?)
+                }
+                _ => None,
+            };
+            if value.is_none() && field.is_some_and(|field| field.required) {
+                return Err(ApiError {
+                    code: ErrorCode::InvalidInput,
+                    message: format!("Field {} is required", key),
+                    details: Some(serde_json::json!({ "key": key })),
+                });
+            }
+            updates.insert(key, value);
+        }
+
+        self.db_repo.set_task_fields(task_id, &updates)?;
+        // Custom fields are not journaled, so lazy sync writes them right away
+        let mirrored = task
+            .task_dir_slug
+            .as_deref()
+            .filter(|_| self.md_sync != MdSyncPolicy::Off);
+        if let Some(slug) = mirrored {
+            match self.md_repo.set_task_custom_fields(task_id, slug, &updates) {
+                // The note is open in the editor; the journal flush mirrors the fields later
+                Err(e) if e.code == ErrorCode::NoteLocked => {
+                    let fields: Vec<String> = updates
+                        .keys()
+                        .map(|key| format!("{}{}", CUSTOM_FIELD_SYNC_PREFIX, key))
+                        .collect();
+                    let fields: Vec<&str> = fields.iter().map(String::as_str).collect();
+                    self.db_repo.journal_md_sync(task_id, &fields)?;
+                }
+                Err(e) => {
+                    error!(target: "planning", "Failed to mirror task fields to markdown: {}", e);
+                }
+                Ok(()) => {}
+            }
+        }
+        info!(target: "planning", "set_task_fields succeeded: task_id={}, fields={}", task_id, updates.len());
+
+        self.get_task_fields(task_id)
+    }
+
+    // Field values of every task on a board, by task id
+    pub fn list_board_field_values(&self, board_id: &str) -> Result<FieldValuesByTask, ApiError> {
+        self.field_values_by_task(Some(board_id))
+    }
+
+    fn field_values_by_task(&self, board_id: Option<&str>) -> Result<FieldValuesByTask, ApiError> {
+        let mut by_task = FieldValuesByTask::new();
+        for value in self.db_repo.list_task_field_values(board_id)? {
+            by_task
+                .entry(value.task_id)
+                .or_default()
+                .insert(value.key, value.value);
+        }
+        Ok(by_task)
+    }
+
+    // Tasks whose custom fields match every filter, optionally on one board
+    pub fn query_tasks_by_fields(
+        &self,
+        board_id: Option<&str>,
+        filters: &[TaskFieldFilter],
+    ) -> Result<Vec<Task>, ApiError> {
+        if filters.is_empty() {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "At least one field filter is required".to_string(),
+                details: None,
+            });
+        }
+
+        let mut tasks = Vec::new();
+        for (task_id, values) in self.field_values_by_task(board_id)? {
+            if task_fields::matches_filters(&values, filters) {
+                if let Some(task) = self.db_repo.get_task(&task_id)? {
+                    tasks.push(task);
+                }
+            }
+        }
+        tasks.sort_by(|a, b| {
+            a.due_date
+                .cmp(&b.due_date)
+                .then_with(|| a.order_index.cmp(&b.order_index))
+        });
+        Ok(tasks)
+    }
+
+    pub fn create_sprint(&self, input: SprintInput) -> Result<Sprint, ApiError> {
+        sprint::validate_input(&input)?;
+        let sprint = self.db_repo.create_sprint(&input)?;
+        info!(target: "planning", "create_sprint succeeded: sprint_id={}, start={}, end={}", sprint.id, sprint.start_date, sprint.end_date);
+        Ok(sprint)
+    }
+
+    pub fn update_sprint(&self, id: &str, input: SprintInput) -> Result<Sprint, ApiError> {
+        sprint::validate_input(&input)?;
+        self.db_repo
+            .update_sprint(id, &input)?
+            .ok_or_else(|| sprint_not_found(id))
+    }
+
+    pub fn delete_sprint(&self, id: &str) -> Result<(), ApiError> {
+        if !self.db_repo.delete_sprint(id)? {
+            return Err(sprint_not_found(id));
+        }
+        info!(target: "planning", "delete_sprint succeeded: sprint_id={}", id);
+        Ok(())
+    }
+
+    pub fn list_sprints(&self) -> Result<Vec<Sprint>, ApiError> {
+        self.db_repo.list_sprints()
+    }
+
+    fn get_sprint_or_not_found(&self, id: &str) -> Result<Sprint, ApiError> {
+        self.db_repo
+            .get_sprint(id)?
+            .ok_or_else(|| sprint_not_found(id))
+    }
+
+    // Commit tasks to a sprint, with the points given or, without them, the points they had
+    pub fn assign_to_sprint(
+        &self,
+        sprint_id: &str,
+        task_ids: Vec<String>,
+        points: Option<f64>,
+    ) -> Result<SprintSummaryDTO, ApiError> {
+        self.get_sprint_or_not_found(sprint_id)?;
+        if points.is_some_and(|points| !points.is_finite() || points < 0.0) {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "Points must be a non-negative number".to_string(),
+                details: Some(serde_json::json!({ "points": points })),
+            });
+        }
+        for task_id in &task_ids {
+            self.get_task_or_not_found(task_id)?;
+        }
+        self.db_repo
+            .assign_to_sprint(sprint_id, &task_ids, points)?;
+        info!(target: "planning", "assign_to_sprint succeeded: sprint_id={}, tasks={}", sprint_id, task_ids.len());
+        self.sprint_summary(sprint_id)
+    }
+
+    pub fn unassign_from_sprint(&self, task_ids: Vec<String>) -> Result<usize, ApiError> {
+        self.db_repo.unassign_from_sprint(&task_ids)
+    }
+
+    // Committed against completed points of a sprint, with its tasks
+    pub fn sprint_summary(&self, sprint_id: &str) -> Result<SprintSummaryDTO, ApiError> {
+        let sprint = self.get_sprint_or_not_found(sprint_id)?;
+        let tasks = self.db_repo.list_sprint_tasks(sprint_id)?;
+        Ok(sprint::summarize(sprint, tasks))
+    }
+
+    pub fn sprint_burndown(&self, sprint_id: &str) -> Result<SprintBurndown, ApiError> {
+        let sprint = self.get_sprint_or_not_found(sprint_id)?;
+        let tasks = self.db_repo.list_sprint_tasks(sprint_id)?;
+        Ok(sprint::burndown(
+            &sprint,
+            &tasks,
+            timezone::today_in(self.timezone),
+            self.timezone,
+            &self.week,
+        ))
+    }
+
+    // Schedule a task into a time block, reporting overlapping blocks
+    pub fn schedule_task(
+        &self,
+        input: ScheduleTaskInput,
+    ) -> Result<ScheduleTaskResponse, ApiError> {
+        let op_id = Uuid::new_v4().to_string();
+        let span = span!(
+            Level::INFO,
+            "planning.schedule_task",
+            op_id = op_id,
+            task_id = &input.task_id
+        );
+        let _enter = span.enter();
+
+        let start = std::time::Instant::now();
+        let result = (|| -> Result<ScheduleTaskResponse, ApiError> {
+            self.get_task_or_not_found(&input.task_id)?;
+
+            let (block_start, block_end) = match (
+                schedule::parse_schedule_time(&input.start, self.timezone),
+                schedule::parse_schedule_time(&input.end, self.timezone),
+            ) {
+                (Some(block_start), Some(block_end)) if block_end > block_start => {
+                    (block_start, block_end)
+                }
+                _ => {
+                    return Err(ApiError {
+                        code: ErrorCode::InvalidSchedule,
+                        message: "start and end must be valid times with end after start"
+                            .to_string(),
+                        details: Some(
+                            serde_json::json!({ "start": &input.start, "end": &input.end }),
+                        ),
+                    });
+                }
+            };
+            let start_str = schedule::format_schedule_time(block_start, self.timezone);
+            let end_str = schedule::format_schedule_time(block_end, self.timezone);
+
+            let conflicts: Vec<ScheduleBlock> = self
+                .db_repo
+                .list_scheduled_tasks(&start_str, &end_str)?
+                .iter()
+                .filter(|task| task.id != input.task_id)
+                .filter(
+                    |task| match schedule::task_schedule_range(task, self.timezone) {
+                        Some((other_start, other_end)) => {
+                            schedule::overlaps(block_start, block_end, other_start, other_end)
+                        }
+                        None => false,
+                    },
+                )
+                .filter_map(|task| schedule::block_from_task(task, self.timezone))
+                .collect();
+
+            if !conflicts.is_empty() && !input.force.unwrap_or(false) {
+                return Ok(ScheduleTaskResponse {
+                    scheduled: false,
+                    task: None,
+                    conflicts,
+                });
+            }
+
+            let task = self
+                .db_repo
+                .update_task_schedule(&input.task_id, &start_str, &end_str)?;
+
+            let mut frontmatter_updates = HashMap::new();
+            frontmatter_updates.insert("updated_at".to_string(), task.updated_at.clone());
+            let slug = task.task_dir_slug.as_deref().unwrap_or("task");
+            self.sync_task_to_md(&task.id, slug, &frontmatter_updates)?;
+
+            Ok(ScheduleTaskResponse {
+                scheduled: true,
+                task: Some(task),
+                conflicts,
+            })
+        })();
+
+        let elapsed = start.elapsed();
+
+        match &result {
+            Ok(response) => {
+                info!(target: "planning", "schedule_task succeeded: task_id={}, scheduled={}, conflicts={}, elapsed_ms={}", &input.task_id, response.scheduled, response.conflicts.len(), elapsed.as_millis());
+            }
+            Err(e) => {
+                error!(target: "planning", "schedule_task failed: task_id={}, error_code={}, error_message={}, elapsed_ms={}", &input.task_id, &e.code, &e.message, elapsed.as_millis());
+            }
+        }
+
+        result
+    }
+
+    // Move the scheduled blocks of several tasks by the same offset, all or nothing
+    pub fn shift_schedule(
+        &self,
+        task_ids: &[String],
+        delta_minutes: i64,
+        force: bool,
+    ) -> Result<ShiftScheduleResponse, ApiError> {
+        let op_id = Uuid::new_v4().to_string();
+        let span = span!(Level::INFO, "planning.shift_schedule", op_id = op_id);
+        let _enter = span.enter();
+
+        let start = std::time::Instant::now();
+        let result = (|| -> Result<ShiftScheduleResponse, ApiError> {
+            if task_ids.is_empty() {
+                return Err(ApiError {
+                    code: ErrorCode::InvalidInput,
+                    message: "task_ids must not be empty".to_string(),
+                    details: None,
+                });
+            }
+
+            let out_of_range = || ApiError {
+                code: ErrorCode::InvalidInput,
+                message: format!("delta_minutes is out of range: {}", delta_minutes),
+                details: None,
+            };
+            let delta = chrono::TimeDelta::try_minutes(delta_minutes).ok_or_else(out_of_range)?;
+            let mut shifted = Vec::new();
+            for task_id in task_ids {
+                if shifted
+                    .iter()
+                    .any(|(id, _, _): &(String, _, _)| id == task_id)
+                {
+                    continue;
+                }
+                let task = self.get_task_or_not_found(task_id)?;
+                let Some((block_start, block_end)) =
+                    schedule::task_schedule_range(&task, self.timezone)
+                else {
+                    return Err(ApiError {
+                        code: ErrorCode::InvalidSchedule,
+                        message: "Task has no valid scheduled block".to_string(),
+                        details: Some(serde_json::json!({ "task_id": task_id })),
+                    });
+                };
+                let (Some(new_start), Some(new_end)) = (
+                    block_start.checked_add_signed(delta),
+                    block_end.checked_add_signed(delta),
+                ) else {
+                    return Err(out_of_range());
+                };
+                shifted.push((task.id, new_start, new_end));
+            }
+
+            // Blocks outside the moved group that the new positions would collide with
+            let range_start = shifted.iter().map(|(_, s, _)| *s).min().unwrap_or_default();
+            let range_end = shifted.iter().map(|(_, _, e)| *e).max().unwrap_or_default();
+            let conflicts: Vec<ScheduleBlock> = self
+                .db_repo
+                .list_scheduled_tasks(
+                    &schedule::format_schedule_time(range_start, self.timezone),
+                    &schedule::format_schedule_time(range_end, self.timezone),
+                )?
+                .iter()
+                .filter(|task| !shifted.iter().any(|(id, _, _)| *id == task.id))
+                .filter(|task| {
+                    schedule::task_schedule_range(task, self.timezone).is_some_and(
+                        |(other_start, other_end)| {
+                            shifted
+                                .iter()
+                                .any(|(_, s, e)| schedule::overlaps(*s, *e, other_start, other_end))
+                        },
+                    )
+                })
+                .filter_map(|task| schedule::block_from_task(task, self.timezone))
+                .collect();
+
+            if !conflicts.is_empty() && !force {
+                return Ok(ShiftScheduleResponse {
+                    shifted: false,
+                    tasks: Vec::new(),
+                    conflicts,
+                });
+            }
+
+            let schedules: Vec<(String, String, String)> = shifted
+                .into_iter()
+                .map(|(id, s, e)| {
+                    (
+                        id,
+                        schedule::format_schedule_time(s, self.timezone),
+                        schedule::format_schedule_time(e, self.timezone),
+                    )
+                })
+                .collect();
+            let tasks = self.db_repo.update_task_schedules(&schedules)?;
+
+            for task in &tasks {
+                let mut frontmatter_updates = HashMap::new();
+                frontmatter_updates.insert("updated_at".to_string(), task.updated_at.clone());
+                let slug = task.task_dir_slug.as_deref().unwrap_or("task");
+                self.sync_task_to_md(&task.id, slug, &frontmatter_updates)?;
+            }
+
+            Ok(ShiftScheduleResponse {
+                shifted: true,
+                tasks,
+                conflicts,
+            })
+        })();
+
+        let elapsed = start.elapsed();
+
+        match &result {
+            Ok(response) => {
+                info!(target: "planning", "shift_schedule succeeded: tasks={}, delta_minutes={}, shifted={}, conflicts={}, elapsed_ms={}", task_ids.len(), delta_minutes, response.shifted, response.conflicts.len(), elapsed.as_millis());
+            }
+            Err(e) => {
+                error!(target: "planning", "shift_schedule failed: tasks={}, delta_minutes={}, error_code={}, error_message={}, elapsed_ms={}", task_ids.len(), delta_minutes, &e.code, &e.message, elapsed.as_millis());
+            }
+        }
+
+        result
+    }
+
+    // Get the ordered time blocks of a day together with the gaps between them
+    pub fn get_day_schedule(&self, day: &str) -> Result<DayScheduleDTO, ApiError> {
+        let date = chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d").map_err(|e| ApiError {
+            code: ErrorCode::DateTimeError,
+            message: format!("Failed to parse day: {}", e),
+            details: Some(serde_json::json!({ "day": day })),
+        })?;
+        let (day_start, day_end) = schedule::day_bounds(date);
+
+        let tasks = self.db_repo.list_scheduled_tasks(
+            &schedule::format_schedule_time(day_start, self.timezone),
+            &schedule::format_schedule_time(day_end, self.timezone),
+        )?;
+
+        let mut entries: Vec<_> = tasks
+            .iter()
+            .filter_map(|task| {
+                let range = schedule::task_schedule_range(task, self.timezone)?;
+                let block = schedule::block_from_task(task, self.timezone)?;
+                Some((range, block))
+            })
+            .collect();
+        entries.sort_by_key(|(range, _)| *range);
+
+        let ranges: Vec<_> = entries.iter().map(|(range, _)| *range).collect();
+        let gaps = schedule::compute_gaps(&ranges);
+
+        Ok(DayScheduleDTO {
+            day: day.to_string(),
+            blocks: entries.into_iter().map(|(_, block)| block).collect(),
+            gaps,
+        })
+    }
+
+    // Bring the full-text index and body-extracted note links in line with current tasks and note files
+    fn refresh_task_index(&self) -> Result<(), ApiError> {
+        let tasks = self.db_repo.list_all_tasks()?;
+        let mut stamps = self.db_repo.list_fts_stamps()?;
+
+        for task in &tasks {
+            let slug = task.task_dir_slug.as_deref().unwrap_or("task");
+            let md_stamp = self.md_repo.task_md_stamp(&task.id, slug);
+            let stamp = format!("{}|{}", task.updated_at, md_stamp.as_deref().unwrap_or(""));
+            if stamps.remove(&task.id).as_deref() == Some(stamp.as_str()) {
+                continue;
+            }
+
+            // An unreadable note keeps its task's old entry rather than failing every search;
+            // the stale stamp retries it next time
+            let refreshed = (|| -> Result<(), ApiError> {
+                let body = match md_stamp {
+                    Some(_) => self.md_repo.read_task_body(&task.id, slug)?,
+                    None => String::new(),
+                };
+                let links = links::extract_note_links(
+                    &body,
+                    &format!("{}/{}", self.md_repo.layout.tasks_dir, slug),
+                );
+                self.db_repo.replace_auto_note_links(&task.id, &links)?;
+                self.refresh_task_preview(task, &body)?;
+                self.db_repo.upsert_task_fts(task, &body, &stamp)
+            })();
+            if let Err(err) = refreshed {
+                warn!(target: "planning", "task index refresh skipped: task_id={}, error={}", task.id, err.message);
+            }
+        }
+
+        // Whatever is left belongs to tasks that no longer exist
+        for task_id in stamps.keys() {
+            self.db_repo.delete_task_fts(task_id)?;
+        }
+
+        Ok(())
+    }
+
+    // Link a task to an existing vault note
+    pub fn link_note(&self, task_id: &str, note_path: &str) -> Result<TaskNoteLink, ApiError> {
+        self.get_task_or_not_found(task_id)?;
+        let note_path = self.resolve_note_path(note_path)?;
+        path_policy::resolve_existing_path(self.md_repo.vault_root(), Path::new(&note_path))?;
+
+        let link = self
+            .db_repo
+            .add_note_link(task_id, &note_path, links::LINK_SOURCE_MANUAL)?;
+        info!(target: "planning", "link_note succeeded: task_id={}, note_path={}", task_id, note_path);
+
+        Ok(link)
+    }
+
+    // Remove a task-note link
+    pub fn unlink_note(&self, task_id: &str, note_path: &str) -> Result<(), ApiError> {
+        let note_path = self.resolve_note_path(note_path)?;
+        if !self.db_repo.remove_note_link(task_id, &note_path)? {
+            return Err(ApiError {
+                code: ErrorCode::NotFound,
+                message: "Link not found".to_string(),
+                details: Some(serde_json::json!({ "task_id": task_id, "note_path": note_path })),
+            });
+        }
+        Ok(())
+    }
+
+    // Re-derive the kanban card preview from a task note body; stored only when it changed
+    fn refresh_task_preview(&self, task: &Task, body: &str) -> Result<(), ApiError> {
+        let slug = task.task_dir_slug.as_deref().unwrap_or("task");
+        let preview =
+            board::card_preview(body, &format!("{}/{}", self.md_repo.layout.tasks_dir, slug));
+        if preview.cover_image != task.cover_image || preview.excerpt != task.excerpt {
+            self.db_repo.set_task_preview(
+                &task.id,
+                preview.cover_image.as_deref(),
+                preview.excerpt.as_deref(),
+            )?;
+        }
+        Ok(())
+    }
+
+    // Notes referenced by a task (manual and extracted from its body)
+    pub fn list_task_notes(&self, task_id: &str) -> Result<Vec<TaskNoteLink>, ApiError> {
+        self.get_task_or_not_found(task_id)?;
+        self.refresh_task_index()?;
+        self.db_repo.list_note_links_for_task(task_id)
+    }
+
+    // Keep manual task links pointing at a note that was renamed or moved
+    pub fn rename_note_links(&self, old_path: &str, new_path: &str) -> Result<usize, ApiError> {
+        let old_path = self.resolve_note_path(old_path)?;
+        let new_path = self.resolve_note_path(new_path)?;
+        self.db_repo.rename_note_link_paths(&old_path, &new_path)
+    }
+
+    // Tasks referencing a note
+    pub fn list_note_tasks(&self, note_path: &str) -> Result<Vec<Task>, ApiError> {
+        let note_path = self.resolve_note_path(note_path)?;
+        self.refresh_task_index()?;
+        self.db_repo.list_tasks_for_note(&note_path)
+    }
+
+    fn resolve_note_path(&self, note_path: &str) -> Result<String, ApiError> {
+        links::normalize_note_path(note_path).ok_or_else(|| ApiError {
+            code: ErrorCode::PathOutsideVault,
+            message: "Note path must be a vault-relative path".to_string(),
+            details: Some(serde_json::json!({ "path": note_path })),
+        })
+    }
+
+    // Task by id; None once it has been deleted
+    pub fn find_task(&self, task_id: &str) -> Result<Option<Task>, ApiError> {
+        self.db_repo.get_task(task_id)
+    }
+
+    pub fn list_github_board_links(&self) -> Result<Vec<GithubBoardLink>, ApiError> {
+        self.db_repo.list_github_board_links()
+    }
+
+    // Link a board to "owner/name", or unlink it when repo is None
+    pub fn link_board_to_github(&self, board_id: &str, repo: Option<&str>) -> Result<(), ApiError> {
+        let board_id = board_id.trim();
+        if board_id.is_empty() {
+            return Err(ApiError {
+                code: ErrorCode::BoardIdRequired,
+                message: "board_id is required".to_string(),
+                details: None,
+            });
+        }
+        let repo = repo.map(github::normalize_repo).transpose()?;
+        self.db_repo
+            .set_github_board_link(board_id, repo.as_deref())
+    }
+
+    pub fn set_github_sync_cursor(&self, board_id: &str, synced_at: &str) -> Result<(), ApiError> {
+        self.db_repo.set_github_sync_cursor(board_id, synced_at)
+    }
+
+    pub fn get_github_issue_link(
+        &self,
+        repo: &str,
+        issue_number: i64,
+    ) -> Result<Option<GithubIssueLink>, ApiError> {
+        self.db_repo.get_github_issue_link(repo, issue_number)
+    }
+
+    pub fn list_github_issue_links(&self, repo: &str) -> Result<Vec<GithubIssueLink>, ApiError> {
+        self.db_repo.list_github_issue_links(repo)
+    }
+
+    pub fn save_github_issue_link(&self, link: &GithubIssueLink) -> Result<(), ApiError> {
+        self.db_repo.save_github_issue_link(link)
+    }
+
+    pub fn record_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<(), ApiError> {
+        self.db_repo.record_webhook_delivery(delivery)
+    }
+
+    pub fn list_webhook_deliveries(&self, limit: i64) -> Result<Vec<WebhookDelivery>, ApiError> {
+        self.db_repo.list_webhook_deliveries(limit.clamp(1, 500))
+    }
+
+    // Open tasks whose due day, in the vault timezone, is `days_until_due` from today and
+    // accepted by `keep`; soonest first
+    fn due_briefs(&self, keep: impl Fn(i64) -> bool) -> Result<Vec<TaskDueBrief>, ApiError> {
+        let today = timezone::today_in(self.timezone);
+        let mut briefs: Vec<TaskDueBrief> = self
+            .db_repo
+            .list_open_due_dates()?
+            .into_iter()
+            .filter_map(|task| {
+                let due = timezone::to_local_date(&task.due_date, self.timezone)?;
+                let days_until_due = (due - today).num_days();
+                keep(days_until_due).then_some(TaskDueBrief {
+                    task,
+                    days_until_due,
+                })
+            })
+            .collect();
+        briefs.sort_by_key(|brief| brief.days_until_due);
+        briefs.truncate(MAX_DUE_BRIEFS);
+        Ok(briefs)
+    }
+
+    pub fn list_overdue(&self) -> Result<Vec<TaskDueBrief>, ApiError> {
+        self.due_briefs(|days| days < 0)
+    }
+
+    // Due today through `days` days from now
+    pub fn list_upcoming(&self, days: i64) -> Result<Vec<TaskDueBrief>, ApiError> {
+        let days = days.clamp(0, MAX_UPCOMING_DAYS);
+        self.due_briefs(|until| (0..=days).contains(&until))
+    }
+
+    // Completed task count per vault-local day
+    fn completions_by_day(&self) -> Result<BTreeMap<chrono::NaiveDate, usize>, ApiError> {
+        let mut counts = BTreeMap::new();
+        for completed_at in self.db_repo.list_completion_times()? {
+            if let Some(day) = timezone::to_local_date(&completed_at, self.timezone) {
+                *counts.entry(day).or_insert(0) += 1;
+            }
+        }
+        Ok(counts)
+    }
+
+    fn daily_note_days(&self) -> Result<BTreeSet<chrono::NaiveDate>, ApiError> {
+        Ok(self
+            .md_repo
+            .list_daily_days()?
+            .iter()
+            .filter_map(|day| chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d").ok())
+            .collect())
+    }
+
+    // Consecutive logged days up to today, and the longest run on record
+    pub fn journal_streak(&self, basis: StreakBasis) -> Result<JournalStreakDTO, ApiError> {
+        let notes = self.daily_note_days()?;
+        let completions = self.completions_by_day()?;
+        let logged = notes
+            .iter()
+            .chain(completions.keys())
+            .filter(|day| {
+                basis.is_logged(
+                    notes.contains(day),
+                    completions.get(day).copied().unwrap_or(0),
+                )
+            })
+            .copied()
+            .collect();
+        Ok(journal::streak(
+            &logged,
+            timezone::today_in(self.timezone),
+            basis,
+        ))
+    }
+
+    // Days of an inclusive range, up to today, that have no daily note
+    pub fn missing_days(&self, from: &str, to: &str) -> Result<Vec<MissingDay>, ApiError> {
+        let (from_date, to_date) = parse_day_range(from, to)?;
+        if (to_date - from_date).num_days() >= journal::MAX_MISSING_DAYS_RANGE {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: format!(
+                    "Range must not exceed {} days",
+                    journal::MAX_MISSING_DAYS_RANGE
+                ),
+                details: Some(serde_json::json!({ "from": from, "to": to })),
+            });
+        }
+        let notes = self.daily_note_days()?;
+        let completions = self.completions_by_day()?;
+        let today = timezone::today_in(self.timezone);
+        Ok(from_date
+            .iter_days()
+            .take_while(|day| *day <= to_date && *day <= today)
+            .filter(|day| !notes.contains(day))
+            .map(|day| MissingDay {
+                day: day.format("%Y-%m-%d").to_string(),
+                is_workday: self.week.is_workday(day),
+                completed_count: completions.get(&day).copied().unwrap_or(0),
+            })
+            .collect())
+    }
+
+    // Bring the frontmatter index up to date with the vault: notes whose mtime changed are
+    // re-read, deleted notes dropped. Notes with unreadable frontmatter index as empty.
+    fn refresh_frontmatter_index(&self) -> Result<Vec<IndexedNote>, ApiError> {
+        let mut files = Vec::new();
+        vault_service::scan_vault_stream(&self.vault_root, None, |nodes, _| {
+            files.extend(nodes.into_iter().filter(|node| node.node_type == "file"));
+        })?;
+        let mut stored = self.db_repo.list_note_frontmatter()?;
+
+        let notes = self.db_repo.in_transaction(|| {
+            let mut notes = Vec::with_capacity(files.len());
+            for file in &files {
+                let mtime = file.mtime.unwrap_or_default() as i64;
+                let data = match stored.remove(&file.path) {
+                    Some((stored_mtime, data)) if stored_mtime == mtime => data,
+                    _ => {
+                        let mapping =
+                            vault_service::read_text_file(&self.vault_root, Path::new(&file.path))
+                                .ok()
+                                .and_then(|note| {
+                                    frontmatter::parse(frontmatter::split(&note.content).0).ok()
+                                })
+                                .unwrap_or_default();
+                        let data = frontmatter::to_json(&mapping).to_string();
+                        self.db_repo
+                            .upsert_note_frontmatter(&file.path, mtime, &data)?;
+                        data
+                    }
+                };
+                let frontmatter = match serde_json::from_str(&data) {
+                    Ok(serde_json::Value::Object(map)) => map,
+                    _ => serde_json::Map::new(),
+                };
+                notes.push(IndexedNote {
+                    path: file.path.clone(),
+                    mtime,
+                    frontmatter,
+                });
+            }
+            for path in stored.keys() {
+                self.db_repo.delete_note_frontmatter(path)?;
+            }
+            Ok(notes)
+        })?;
+        Ok(notes)
+    }
+
+    pub fn query_notes(&self, input: &NoteQueryInput) -> Result<NoteQueryResult, ApiError> {
+        let notes = self.refresh_frontmatter_index()?;
+        note_query::run_query(notes, input)
+    }
+
+    // Send task_overdue once per passed due date; returns how many tasks were reported
+    pub fn notify_overdue_tasks(&self) -> Result<usize, ApiError> {
+        let Some(app_handle) = &self.app_handle else {
+            return Ok(0);
+        };
+        let subscribed = settings_repo::get_webhooks(&self.vault_root)?
+            .iter()
+            .any(|webhook| webhook.subscribes_to(WebhookEvent::Overdue));
+        if !subscribed {
+            return Ok(0);
+        }
+
+        let today = self.today();
+        let mut sent = 0;
+        for task in self.db_repo.list_open_tasks_with_due_date()? {
+            let Some(due_date) = task.due_date.as_deref() else {
+                continue;
+            };
+            if due_date >= today.as_str() || !self.db_repo.mark_overdue_sent(&task.id, due_date)? {
+                continue;
+            }
+            webhooks::dispatch(app_handle, &self.vault_root, WebhookEvent::Overdue, &task);
+            sent += 1;
+        }
+        Ok(sent)
+    }
+
+    // Search task titles, descriptions, tags and optionally note bodies
+    pub fn search_tasks(
+        &self,
+        query: &str,
+        include_notes: bool,
+        limit: usize,
+    ) -> Result<Vec<TaskSearchHit>, ApiError> {
+        let op_id = Uuid::new_v4().to_string();
+        let span = span!(Level::INFO, "planning.search_tasks", op_id = op_id);
+        let _enter = span.enter();
+
+        let start = std::time::Instant::now();
+        let result = (|| -> Result<Vec<TaskSearchHit>, ApiError> {
+            let terms = search::query_terms(query);
+            if terms.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            self.refresh_task_index()?;
+
+            let mut hits = Vec::new();
+            match search::fts_match_expr(&terms, include_notes) {
+                Some(match_expr) => {
+                    for (task_id, score, title_highlight, snippet) in
+                        self.db_repo.search_task_fts(&match_expr, limit)?
+                    {
+                        let Some(task) = self.db_repo.get_task(&task_id)? else {
+                            continue;
+                        };
+                        let snippet = snippet
+                            .contains(search::HIGHLIGHT_OPEN)
+                            .then(|| search::highlight_html(&snippet));
+                        hits.push(TaskSearchHit {
+                            task,
+                            score,
+                            title_highlight: search::highlight_html(&title_highlight),
+                            snippet,
+                        });
+                    }
+                }
+                None => {
+                    // Short terms: unranked substring match, most recently updated first
+                    for task_id in self
+                        .db_repo
+                        .search_task_like(&terms, include_notes, limit)?
+                    {
+                        let Some(task) = self.db_repo.get_task(&task_id)? else {
+                            continue;
+                        };
+                        hits.push(TaskSearchHit {
+                            title_highlight: search::escape_html(&task.title),
+                            task,
+                            score: 0.0,
+                            snippet: None,
+                        });
+                    }
+                }
+            }
+
+            Ok(hits)
+        })();
+
+        let elapsed = start.elapsed();
+
+        match &result {
+            Ok(hits) => {
+                info!(target: "planning", "search_tasks succeeded: hits={}, include_notes={}, elapsed_ms={}", hits.len(), include_notes, elapsed.as_millis());
+            }
+            Err(e) => {
+                error!(target: "planning", "search_tasks failed: error_code={}, error_message={}, elapsed_ms={}", &e.code, &e.message, elapsed.as_millis());
+            }
+        }
+
+        result
+    }
+
+    // Compare estimates with tracked time per task and tag over an inclusive day range
+    pub fn get_estimate_report(&self, from: &str, to: &str) -> Result<EstimateReportDTO, ApiError> {
+        let (from_date, to_date) = parse_day_range(from, to)?;
+        let to_exclusive = to_date + chrono::Duration::days(1);
+
+        let rows = self.db_repo.list_estimated_tasks_with_actuals(
+            &from_date.format("%Y-%m-%d").to_string(),
+            &to_exclusive.format("%Y-%m-%d").to_string(),
+        )?;
+
+        Ok(analytics::build_estimate_report(from, to, rows))
+    }
+
+    // Next `n` occurrence dates of a recurring task, starting today
+    pub fn preview_occurrences(&self, task_id: &str, n: usize) -> Result<Vec<String>, ApiError> {
+        let task = self.get_task_or_not_found(task_id)?;
+        let Some(periodicity) = &task.periodicity else {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "Task does not recur".to_string(),
+                details: Some(serde_json::json!({ "task_id": task_id })),
+            });
+        };
+
+        let today = timezone::today_in(self.timezone);
+        let dates: Vec<String> = recurrence::occurrences(periodicity, self.timezone, &self.week)
+            .skip_while(|date| *date < today)
+            .take(n.min(recurrence::MAX_PREVIEW_OCCURRENCES))
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .collect();
+
+        info!(target: "planning", "preview_occurrences succeeded: task_id={}, count={}", task_id, dates.len());
+        Ok(dates)
+    }
+
+    // Standard RRULE string for a periodicity
+    pub fn periodicity_to_rrule(&self, periodicity: &TaskPeriodicity) -> Result<String, ApiError> {
+        recurrence::to_rrule(periodicity, self.timezone, &self.week)
+    }
+
+    // Periodicity for an RRULE string anchored at start_date
+    pub fn periodicity_from_rrule(
+        &self,
+        rrule: &str,
+        start_date: &str,
+    ) -> Result<TaskPeriodicity, ApiError> {
+        recurrence::from_rrule(rrule, start_date, self.timezone, &self.week)
+    }
+
+    // Per-day due tasks, scheduled blocks, recurrence instances and daily-note flags over an inclusive range
+    pub fn get_calendar_range(&self, from: &str, to: &str) -> Result<CalendarRangeDTO, ApiError> {
+        let op_id = Uuid::new_v4().to_string();
+        let span = span!(Level::INFO, "planning.get_calendar_range", op_id = op_id);
+        let _enter = span.enter();
+
+        let start = std::time::Instant::now();
+        let result = (|| -> Result<CalendarRangeDTO, ApiError> {
+            let (from_date, to_date) = parse_day_range(from, to)?;
+            if (to_date - from_date).num_days() >= calendar::MAX_CALENDAR_DAYS {
+                return Err(ApiError {
+                    code: ErrorCode::InvalidInput,
+                    message: format!(
+                        "Calendar range must not exceed {} days",
+                        calendar::MAX_CALENDAR_DAYS
+                    ),
+                    details: Some(serde_json::json!({ "from": from, "to": to })),
+                });
+            }
+            let (range_start, _) = schedule::day_bounds(from_date);
+            let (_, range_end) = schedule::day_bounds(to_date);
+
+            // Stored instants may fall on a neighbouring day once shifted into the vault timezone
+            let pad = chrono::Duration::days(1);
+            let due_tasks = self.db_repo.list_tasks_due_between(
+                &(from_date - pad).format("%Y-%m-%d").to_string(),
+                &(to_date + pad + pad).format("%Y-%m-%d").to_string(),
+            )?;
+            let scheduled_tasks = self.db_repo.list_scheduled_tasks(
+                &schedule::format_schedule_time(range_start - pad, self.timezone),
+                &schedule::format_schedule_time(range_end + pad, self.timezone),
+            )?;
+            let recurring_tasks = self.db_repo.list_recurring_tasks()?;
+
+            let days = calendar::build_calendar(
+                from_date,
+                to_date,
+                due_tasks,
+                &scheduled_tasks,
+                &recurring_tasks,
+                self.timezone,
+                &self.week,
+            )
+            .into_values()
+            .map(|mut day| {
+                day.has_daily_note = self.md_repo.daily_md_exists(&day.day);
+                day
+            })
+            .collect();
+
+            Ok(CalendarRangeDTO {
+                from: from.to_string(),
+                to: to.to_string(),
+                days,
+            })
+        })();
+
+        let elapsed_ms = start.elapsed().as_millis();
+        match &result {
+            Ok(dto) => info!(
+                target: "planning",
+                "get_calendar_range succeeded: from={}, to={}, days={}, elapsed_ms={}",
+                from,
+                to,
+                dto.days.len(),
+                elapsed_ms
+            ),
+            Err(e) => error!(
+                target: "planning",
+                "get_calendar_range failed: from={}, to={}, error_code={}, error_message={}, elapsed_ms={}",
+                from,
+                to,
+                &e.code,
+                &e.message,
+                elapsed_ms
+            ),
+        }
+
+        result
+    }
+
+    // Evaluate due-date escalation rules against open tasks and apply their changes
+    pub fn run_rules(&self) -> Result<RulesRunResult, ApiError> {
+        let op_id = Uuid::new_v4().to_string();
+        let span = span!(Level::INFO, "planning.run_rules", op_id = op_id);
+        let _enter = span.enter();
+
+        let start = std::time::Instant::now();
+        let result = (|| -> Result<RulesRunResult, ApiError> {
+            let now = timezone::now_in(self.timezone);
+            let tasks = self.db_repo.list_open_tasks_with_due_date()?;
+
+            let mut changes = Vec::new();
+            for task in &tasks {
+                let Some(change) = rules::evaluate(&self.rules, task, now, self.timezone) else {
+                    continue;
+                };
+                self.update_task(UpdateTaskInput {
+                    id: task.id.clone(),
+                    title: None,
+                    description: None,
+                    status: None,
+                    priority: change.priority,
+                    tags: change.tags.clone(),
+                    labels: None,
+                    subtasks: None,
+                    periodicity: None,
+                    due_date: None,
+                    board_id: change.board_id.clone(),
+                    color: None,
+                    order_index: None,
+                    estimate_min: None,
+                    scheduled_start: None,
+                    scheduled_end: None,
+                    note_path: None,
+                    archived: None,
+                    op_id: None,
+                    // Automated escalation must not be blocked by column limits
+                    override_wip_limit: Some(true),
+                    rename_slug: None,
+                })?;
+                changes.push(change);
+            }
+
+            Ok(RulesRunResult {
+                evaluated: tasks.len(),
+                changes,
+            })
+        })();
+
+        let elapsed = start.elapsed();
+
+        match &result {
+            Ok(run) => {
+                info!(target: "planning", "run_rules succeeded: evaluated={}, changed={}, elapsed_ms={}", run.evaluated, run.changes.len(), elapsed.as_millis());
+            }
+            Err(e) => {
+                error!(target: "planning", "run_rules failed: error_code={}, error_message={}, elapsed_ms={}", &e.code, &e.message, elapsed.as_millis());
+            }
+        }
+
+        result
+    }
+
+    // Summarize the configured week containing `day` (defaults to the vault-local today)
+    pub fn get_week_review(&self, day: Option<&str>) -> Result<WeekReviewDTO, ApiError> {
+        let date = match day {
+            Some(day) => {
+                chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d").map_err(|e| ApiError {
+                    code: ErrorCode::DateTimeError,
+                    message: format!("Failed to parse day: {}", e),
+                    details: Some(serde_json::json!({ "day": day })),
+                })?
+            }
+            None => timezone::today_in(self.timezone),
+        };
+        let week_start = self.week.week_start(date);
+        let week_end = week_start + chrono::Duration::days(6);
+
+        // Stored instants are UTC; widen the query by a day and bucket in the vault timezone
+        let query_from = (week_start - chrono::Duration::days(1))
+            .format("%Y-%m-%d")
+            .to_string();
+        let query_to = (week_end + chrono::Duration::days(2))
+            .format("%Y-%m-%d")
+            .to_string();
+        let in_week = |value: &str| {
+            timezone::to_local_date(value, self.timezone)
+                .filter(|local| *local >= week_start && *local <= week_end)
+        };
+
+        let mut days: Vec<WeekReviewDay> = (0..7)
+            .map(|offset| {
+                let day = week_start + chrono::Duration::days(offset);
+                WeekReviewDay {
+                    day: day.format("%Y-%m-%d").to_string(),
+                    is_workday: self.week.is_workday(day),
+                    completed_count: 0,
+                    tracked_min: 0,
+                }
+            })
+            .collect();
+
+        let mut completed = Vec::new();
+        for task in self
+            .db_repo
+            .list_completed_tasks_between(&query_from, &query_to)?
+        {
+            let Some(local) = task.completed_at.as_deref().and_then(in_week) else {
+                continue;
+            };
+            days[(local - week_start).num_days() as usize].completed_count += 1;
+            completed.push(task);
+        }
+
+        for timer in self.db_repo.list_timers_between(&query_from, &query_to)? {
+            if let Some(local) = in_week(&timer.start_at) {
+                days[(local - week_start).num_days() as usize].tracked_min +=
+                    timer.duration_sec / 60;
+            }
+        }
+
+        let tracked_min = days.iter().map(|day| day.tracked_min).sum();
+        let workday_tracked_min = days
+            .iter()
+            .filter(|day| day.is_workday)
+            .map(|day| day.tracked_min)
+            .sum();
+
+        Ok(WeekReviewDTO {
+            week_start: week_start.format("%Y-%m-%d").to_string(),
+            week_end: week_end.format("%Y-%m-%d").to_string(),
+            days,
+            completed,
+            tracked_min,
+            workday_tracked_min,
+        })
+    }
+
+    // Get UI state for the current vault
+    #[allow(dead_code)]
+    pub fn get_ui_state(&self, vault_id: &str) -> Result<Option<String>, ApiError> {
+        self.db_repo.get_ui_state(vault_id)
+    }
+
+    // Set UI state for the current vault
+    #[allow(dead_code)]
+    pub fn set_ui_state(&self, vault_id: &str, partial_state_json: &str) -> Result<(), ApiError> {
+        self.db_repo.set_ui_state(vault_id, partial_state_json)
+    }
+
+    // One key of this vault's UI state
+    fn ui_state_value(&self, key: &str) -> Result<Option<serde_json::Value>, ApiError> {
+        let Some(state) = self.db_repo.get_ui_state(&self.vault_id)? else {
+            return Ok(None);
+        };
+        let mut state: serde_json::Value = serde_json::from_str(&state)?;
+        Ok(state.get_mut(key).map(serde_json::Value::take))
+    }
+
+    // Embedded browser sessions of this vault, oldest first
+    pub fn list_webview_sessions(&self) -> Result<Vec<WebviewSession>, ApiError> {
+        let state = self.ui_state_value(WEBVIEW_SESSIONS_KEY)?;
+        let mut sessions: Vec<WebviewSession> = state
+            .as_ref()
+            .and_then(|sessions| sessions.as_object())
+            .map(|sessions| {
+                sessions
+                    .values()
+                    .filter_map(|session| serde_json::from_value(session.clone()).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        sessions.sort_by(|a, b| a.opened_at.cmp(&b.opened_at));
+        Ok(sessions)
+    }
+
+    fn store_webview_sessions(&self, sessions: &[WebviewSession]) -> Result<(), ApiError> {
+        let value: serde_json::Map<String, serde_json::Value> = sessions
+            .iter()
+            .map(|session| Ok((session.label.clone(), serde_json::to_value(session)?)))
+            .collect::<Result<_, serde_json::Error>>()?;
+        self.db_repo.replace_ui_state_key(
+            &self.vault_id,
+            WEBVIEW_SESSIONS_KEY,
+            serde_json::Value::Object(value),
+        )
+    }
+
+    // Insert or replace the session with the same label
+    pub fn save_webview_session(&self, session: WebviewSession) -> Result<(), ApiError> {
+        let mut sessions = self.list_webview_sessions()?;
+        sessions.retain(|existing| existing.label != session.label);
+        sessions.push(session);
+        self.store_webview_sessions(&sessions)
+    }
+
+    // Returns whether a session was stored under the label
+    pub fn remove_webview_session(&self, label: &str) -> Result<bool, ApiError> {
+        let mut sessions = self.list_webview_sessions()?;
+        let before = sessions.len();
+        sessions.retain(|session| session.label != label);
+        if sessions.len() == before {
+            return Ok(false);
+        }
+        self.store_webview_sessions(&sessions)?;
+        self.db_repo.clear_webview_history(&self.vault_id, label)?;
+        Ok(true)
+    }
+
+    // Record a state event from the webview bridge: the visit goes into the history and the
+    // webview's session, if it has one, follows it so a restart reopens the page last shown.
+    // Events fired while a page is still loading, and pages other than http(s) (about:blank,
+    // error pages), are ignored.
+    pub fn record_webview_state(&self, payload: WebviewStatePayload) -> Result<(), ApiError> {
+        if payload.ready_state.as_deref() == Some("loading") {
+            return Ok(());
+        }
+        let Some(url) = payload
+            .url
+            .map(|url| url.trim().to_string())
+            .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+        else {
+            return Ok(());
+        };
+        // The bridge falls back to the URL while the page has no title yet
+        let title = payload
+            .title
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty() && *title != url);
+        let now = Utc::now().to_rfc3339();
+        let entry = WebviewHistoryEntry {
+            label: payload.label,
+            url,
+            title,
+            visited_at: now.clone(),
+        };
+        self.db_repo.record_webview_visit(&self.vault_id, &entry)?;
+
+        let mut sessions = self.list_webview_sessions()?;
+        let Some(session) = sessions
+            .iter_mut()
+            .find(|session| session.label == entry.label)
+        else {
+            return Ok(());
+        };
+        if session.url != entry.url {
+            session.url = entry.url.clone();
+            session.title = None;
+        }
+        if entry.title.is_some() {
+            session.title = entry.title.clone();
+        }
+        session.updated_at = now;
+        self.store_webview_sessions(&sessions)
+    }
+
+    // Task to read a page, linked to its clipping note when there is one
+    pub fn create_read_later_task(
+        &self,
+        title: &str,
+        url: &str,
+        note_path: Option<&str>,
+    ) -> Result<Task, ApiError> {
+        let task = self.create_task(CreateTaskInput {
+            title: format!("Read: {}", title),
+            description: Some(url.to_string()),
+            status: TaskStatus::Todo,
+            priority: None,
+            due_date: Some(self.today()), // Todo tasks need a due date
+            board_id: None,
+            estimate_min: None,
+            tags: Some(vec![READ_LATER_TAG.to_string()]),
+            labels: None,
+            subtasks: None,
+            periodicity: None,
+            scheduled_start: None,
+            scheduled_end: None,
+            note_path: None,
+            color: None,
+            op_id: None,
+        })?;
+        if let Some(note_path) = note_path {
+            self.link_note(&task.id, note_path)?;
+        }
+        Ok(task)
+    }
+
+    pub fn get_reading_item(&self, id: &str) -> Result<ReadingItem, ApiError> {
+        self.db_repo.get_reading_item(id)?.ok_or_else(|| ApiError {
+            code: ErrorCode::NotFound,
+            message: "Reading list item not found".to_string(),
+            details: Some(serde_json::json!({ "id": id })),
+        })
+    }
+
+    // Add a page to the reading list. A URL already on the list returns the existing item. Without
+    // a title, the last title seen for the URL in a webview is used, then the URL itself.
+    pub fn add_to_reading_list(
+        &self,
+        url: &str,
+        title: Option<String>,
+        source_label: Option<String>,
+    ) -> Result<ReadingItem, ApiError> {
+        let url = tauri::Url::parse(url.trim())
+            .ok()
+            .filter(|parsed| matches!(parsed.scheme(), "http" | "https"))
+            .ok_or_else(|| ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "Only http and https pages can be added to the reading list".to_string(),
+                details: Some(serde_json::json!({ "url": url })),
+            })?
+            .to_string();
+        let title = title
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty());
+
+        if let Some(mut existing) = self.db_repo.find_reading_item_by_url(&url)? {
+            if let Some(title) = title.filter(|title| *title != existing.title) {
+                existing.title = title;
+                self.db_repo.update_reading_item(&existing)?;
+            }
+            return Ok(existing);
+        }
+
+        let title = match title {
+            Some(title) => title,
+            None => self
+                .db_repo
+                .latest_webview_title(&self.vault_id, &url)?
+                .unwrap_or_else(|| reading_list::fallback_title(&url)),
+        };
+        let item = ReadingItem {
+            id: Uuid::new_v4().to_string(),
+            url,
+            title,
+            status: ReadingStatus::Unread,
+            added_at: Utc::now().to_rfc3339(),
+            read_at: None,
+            note_path: None,
+            task_id: None,
+            source_label,
+        };
+        self.db_repo.insert_reading_item(&item)?;
+        info!("Reading list item added: id={}, url={}", item.id, item.url);
+        Ok(item)
+    }
+
+    pub fn list_reading_list(
+        &self,
+        status: Option<ReadingStatus>,
+    ) -> Result<Vec<ReadingItem>, ApiError> {
+        self.db_repo.list_reading_items(status)
+    }
+
+    pub fn mark_reading_item(&self, id: &str, read: bool) -> Result<ReadingItem, ApiError> {
+        let mut item = self.get_reading_item(id)?;
+        let status = if read {
+            ReadingStatus::Read
+        } else {
+            ReadingStatus::Unread
+        };
+        if item.status != status {
+            item.status = status;
+            item.read_at = read.then(|| Utc::now().to_rfc3339());
+            self.db_repo.update_reading_item(&item)?;
+        }
+        Ok(item)
+    }
+
+    // Create a read-later task for the item. An item already converted returns its task, unless
+    // that task has since been deleted.
+    pub fn reading_item_to_task(&self, id: &str) -> Result<ReadingItemTaskResponse, ApiError> {
+        let mut item = self.get_reading_item(id)?;
+        if let Some(task) = match &item.task_id {
+            Some(task_id) => self.db_repo.get_task(task_id)?,
+            None => None,
+        } {
+            return Ok(ReadingItemTaskResponse { item, task });
+        }
+
+        let task =
+            self.create_read_later_task(&item.title, &item.url, item.note_path.as_deref())?;
+        item.task_id = Some(task.id.clone());
+        self.db_repo.update_reading_item(&item)?;
+        Ok(ReadingItemTaskResponse { item, task })
+    }
+
+    // Attach the clipping saved from the item's page, linking it to the item's task if any
+    pub fn set_reading_item_note(
+        &self,
+        id: &str,
+        note_path: &str,
+    ) -> Result<ReadingItem, ApiError> {
+        let mut item = self.get_reading_item(id)?;
+        if let Some(task_id) = &item.task_id {
+            if self.db_repo.get_task(task_id)?.is_some() {
+                self.link_note(task_id, note_path)?;
+            }
+        }
+        item.note_path = Some(note_path.to_string());
+        self.db_repo.update_reading_item(&item)?;
+        Ok(item)
+    }
+
+    // Returns whether an item was removed; its task and clipping are kept
+    pub fn remove_reading_item(&self, id: &str) -> Result<bool, ApiError> {
+        self.db_repo.delete_reading_item(id)
+    }
+
+    pub fn get_reminder(&self, id: &str) -> Result<Reminder, ApiError> {
+        self.db_repo.get_reminder(id)?.ok_or_else(|| ApiError {
+            code: ErrorCode::NotFound,
+            message: "Reminder not found".to_string(),
+            details: Some(serde_json::json!({ "id": id })),
+        })
+    }
+
+    // When a reminder next goes off: a one-off time must lie ahead, a repeating one is rolled
+    // forward to its first occurrence after now
+    fn schedule_reminder(
+        &self,
+        remind_at: &str,
+        repeat: Option<ReminderRepeat>,
+    ) -> Result<String, ApiError> {
+        let remind_at = reminders::parse_remind_at(remind_at, self.timezone)?;
+        let now = Utc::now();
+        let next = match repeat {
+            Some(repeat) => reminders::next_occurrence(remind_at, repeat, now, self.timezone),
+            None if remind_at <= now => {
+                return Err(ApiError {
+                    code: ErrorCode::InvalidInput,
+                    message: "remind_at is in the past".to_string(),
+                    details: Some(serde_json::json!({
+                        "remind_at": reminders::format_instant(remind_at),
+                    })),
+                });
+            }
+            None => remind_at,
+        };
+        Ok(reminders::format_instant(next))
+    }
+
+    pub fn create_reminder(
+        &self,
+        task_id: &str,
+        remind_at: &str,
+        repeat: Option<ReminderRepeat>,
+    ) -> Result<Reminder, ApiError> {
+        self.get_task_or_not_found(task_id)?;
+        let now = Utc::now().to_rfc3339();
+        let reminder = Reminder {
+            id: Uuid::new_v4().to_string(),
+            task_id: task_id.to_string(),
+            remind_at: self.schedule_reminder(remind_at, repeat)?,
+            repeat,
+            fired_at: None,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        self.db_repo.insert_reminder(&reminder)?;
+        info!(target: "planning", "reminder created: id={}, task_id={}, remind_at={}", reminder.id, task_id, reminder.remind_at);
+        Ok(reminder)
+    }
+
+    // Reschedule a reminder; one that already went off is armed again
+    pub fn update_reminder(
+        &self,
+        id: &str,
+        remind_at: &str,
+        repeat: Option<ReminderRepeat>,
+    ) -> Result<Reminder, ApiError> {
+        let mut reminder = self.get_reminder(id)?;
+        reminder.remind_at = self.schedule_reminder(remind_at, repeat)?;
+        reminder.repeat = repeat;
+        reminder.fired_at = None;
+        reminder.updated_at = Utc::now().to_rfc3339();
+        self.db_repo.update_reminder(&reminder)?;
+        Ok(reminder)
+    }
+
+    pub fn delete_reminder(&self, id: &str) -> Result<bool, ApiError> {
+        self.db_repo.delete_reminder(id)
+    }
+
+    pub fn list_reminders(&self, task_id: Option<&str>) -> Result<Vec<Reminder>, ApiError> {
+        self.db_repo.list_reminders(task_id)
+    }
+
+    // Notifications to show now, for the scheduler to deliver: reminders that came due, tasks
+    // that became overdue and snoozes that ended. One-off reminders are marked fired and repeating
+    // ones move to their next occurrence; a repeating reminder missed several times while the app
+    // was closed goes off once. Each occurrence is recorded in the notification store inside the
+    // same transaction, so it is never delivered twice. Reminders of finished or archived tasks,
+    // and all reminders while reminder notifications are off, are consumed silently.
+    pub fn take_due_notifications(
+        &self,
+        settings: &NotificationSettings,
+    ) -> Result<Vec<Notification>, ApiError> {
+        let now = Utc::now();
+        let now_text = reminders::format_instant(now);
+        self.db_repo.in_transaction(|| {
+            let mut due = Vec::new();
+            for reminder in self.db_repo.list_due_reminders(&now_text)? {
+                let Some(task) = self.db_repo.get_task(&reminder.task_id)? else {
+                    self.db_repo.delete_reminder(&reminder.id)?;
+                    continue;
+                };
+
+                let mut next = reminder.clone();
+                match reminder.repeat {
+                    Some(repeat) => {
+                        let remind_at = reminders::parse_remind_at(&reminder.remind_at, None)?;
+                        next.remind_at = reminders::format_instant(reminders::next_occurrence(
+                            remind_at,
+                            repeat,
+                            now,
+                            self.timezone,
+                        ));
+                    }
+                    None => next.fired_at = Some(now_text.clone()),
+                }
+                next.updated_at = now.to_rfc3339();
+                self.db_repo.update_reminder(&next)?;
+
+                if !(settings.enabled && settings.reminders)
+                    || task.status == TaskStatus::Done
+                    || task.archived != 0
+                {
+                    continue;
+                }
+                let notification = Notification {
+                    id: Uuid::new_v4().to_string(),
+                    kind: NotificationKind::Reminder,
+                    source_id: reminder.id,
+                    occurrence_at: reminder.remind_at,
+                    task_id: task.id,
+                    title: task.title,
+                    state: NotificationState::Delivered,
+                    snoozed_until: None,
+                    delivered_at: now_text.clone(),
+                    updated_at: now_text.clone(),
+                };
+                if self.db_repo.insert_notification(&notification)? {
+                    due.push(notification);
+                }
             }
 
-            // Get relative path
-            let relative_path = self.md_repo.get_task_md_relative_path(&task.id, &slug);
+            if settings.enabled && settings.overdue {
+                let today = self.today();
+                for task in self.db_repo.list_open_tasks_with_due_date()? {
+                    let Some(due_date) = task.due_date.filter(|due| *due < today) else {
+                        continue;
+                    };
+                    let notification = Notification {
+                        id: Uuid::new_v4().to_string(),
+                        kind: NotificationKind::Overdue,
+                        source_id: task.id.clone(),
+                        occurrence_at: due_date,
+                        task_id: task.id,
+                        title: task.title,
+                        state: NotificationState::Delivered,
+                        snoozed_until: None,
+                        delivered_at: now_text.clone(),
+                        updated_at: now_text.clone(),
+                    };
+                    if self.db_repo.insert_notification(&notification)? {
+                        due.push(notification);
+                    }
+                }
+            }
 
-            // Update task's note_path in database if needed
-            if task.note_path.is_none() || task.note_path != Some(relative_path.clone()) {
-                self.db_repo
-                    .update_task_note_path(&task.id, &relative_path)?;
+            // A snooze that ends while notifications are off waits for them to be on again
+            let expired = if settings.enabled {
+                self.db_repo.list_expired_snoozes(&now_text)?
+            } else {
+                Vec::new()
+            };
+            for mut notification in expired {
+                notification.state = NotificationState::Delivered;
+                notification.snoozed_until = None;
+                notification.delivered_at = now_text.clone();
+                notification.updated_at = now_text.clone();
+                self.db_repo.update_notification(&notification)?;
+                due.push(notification);
             }
 
-            Ok(OpenTaskNoteResponse {
-                md_path: relative_path,
-            })
-        })();
+            let retention = chrono::Duration::days(DISMISSED_RETENTION_DAYS);
+            self.db_repo
+                .prune_dismissed_notifications(&reminders::format_instant(now - retention))?;
+            Ok(due)
+        })
+    }
 
-        let elapsed = start.elapsed();
+    pub fn get_notification(&self, id: &str) -> Result<Notification, ApiError> {
+        self.db_repo.get_notification(id)?.ok_or_else(|| ApiError {
+            code: ErrorCode::NotFound,
+            message: "Notification not found".to_string(),
+            details: Some(serde_json::json!({ "id": id })),
+        })
+    }
 
-        match &result {
-            Ok(_) => {
-                info!(target: "planning", "open_task_note succeeded: task_id={}, elapsed_ms={}", task_id, elapsed.as_millis());
+    pub fn list_notifications(
+        &self,
+        include_dismissed: bool,
+    ) -> Result<Vec<Notification>, ApiError> {
+        self.db_repo.list_notifications(include_dismissed)
+    }
+
+    // Hide a notification until `until` (RFC3339, or a date and time in the vault timezone), when
+    // the scheduler delivers it again
+    pub fn snooze_notification(&self, id: &str, until: &str) -> Result<Notification, ApiError> {
+        let mut notification = self.get_notification(id)?;
+        let until = timezone::parse_instant(until, self.timezone)
+            .filter(|until| *until > Utc::now())
+            .ok_or_else(|| ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "Snooze needs a time in the future".to_string(),
+                details: Some(serde_json::json!({ "until": until })),
+            })?;
+        notification.state = NotificationState::Snoozed;
+        notification.snoozed_until = Some(reminders::format_instant(until));
+        notification.updated_at = reminders::format_instant(Utc::now());
+        self.db_repo.update_notification(&notification)?;
+        Ok(notification)
+    }
+
+    pub fn dismiss_notification(&self, id: &str) -> Result<Notification, ApiError> {
+        let mut notification = self.get_notification(id)?;
+        notification.state = NotificationState::Dismissed;
+        notification.snoozed_until = None;
+        notification.updated_at = reminders::format_instant(Utc::now());
+        self.db_repo.update_notification(&notification)?;
+        Ok(notification)
+    }
+
+    // Note paths are stored normalized so a favorite matches however the frontend spells it
+    fn normalize_favorite(&self, favorite: FavoriteRef) -> Result<FavoriteRef, ApiError> {
+        let target = match favorite.kind {
+            FavoriteKind::Note => self.resolve_note_path(&favorite.target)?,
+            FavoriteKind::Task => favorite.target.trim().to_string(),
+        };
+        Ok(FavoriteRef {
+            kind: favorite.kind,
+            target,
+        })
+    }
+
+    // Favorites in order, with titles; notes and tasks deleted since are flagged missing
+    pub fn list_favorites(&self) -> Result<Vec<FavoriteDTO>, ApiError> {
+        let favorites = self.db_repo.list_favorites(&self.vault_id)?;
+        let mut dtos = Vec::with_capacity(favorites.len());
+        for favorite in favorites {
+            let (title, missing) = match favorite.kind {
+                FavoriteKind::Note => {
+                    let title = Path::new(&favorite.target)
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().to_string())
+                        .unwrap_or_else(|| favorite.target.clone());
+                    (title, !self.vault_root.join(&favorite.target).is_file())
+                }
+                FavoriteKind::Task => match self.db_repo.get_task(&favorite.target)? {
+                    Some(task) => (task.title, false),
+                    None => (favorite.target.clone(), true),
+                },
+            };
+            dtos.push(FavoriteDTO {
+                favorite,
+                title,
+                missing,
+            });
+        }
+        Ok(dtos)
+    }
+
+    // Pin a note or task at the end of the favorites; pinning it again leaves it where it is
+    pub fn add_favorite(&self, favorite: FavoriteRef) -> Result<Vec<FavoriteDTO>, ApiError> {
+        let favorite = self.normalize_favorite(favorite)?;
+        match favorite.kind {
+            FavoriteKind::Note => {
+                path_policy::resolve_existing_path(
+                    self.md_repo.vault_root(),
+                    Path::new(&favorite.target),
+                )?;
             }
-            Err(e) => {
-                error!(target: "planning", "open_task_note failed: task_id={}, error_code={}, error_message={}, elapsed_ms={}", task_id, &e.code, &e.message, elapsed.as_millis());
+            FavoriteKind::Task => {
+                self.get_task_or_not_found(&favorite.target)?;
             }
         }
-
-        result
+        self.db_repo
+            .add_favorite(&self.vault_id, &favorite, &Utc::now().to_rfc3339())?;
+        self.list_favorites()
     }
 
-    // Reorder tasks in batch
-    pub fn reorder_tasks(&self, tasks: Vec<ReorderTaskInput>) -> Result<(), ApiError> {
-        let op_id = Uuid::new_v4().to_string();
-        let span = span!(
-            Level::INFO,
-            "planning.reorder_tasks",
-            op_id = op_id,
-            task_count = tasks.len()
-        );
-        let _enter = span.enter();
+    pub fn remove_favorite(&self, favorite: FavoriteRef) -> Result<Vec<FavoriteDTO>, ApiError> {
+        let favorite = self.normalize_favorite(favorite)?;
+        self.db_repo.remove_favorite(&self.vault_id, &favorite)?;
+        self.list_favorites()
+    }
 
-        let start = std::time::Instant::now();
+    // Put the given favorites first, in the given order; favorites left out keep their relative
+    // order after them, and unknown entries are ignored
+    pub fn reorder_favorites(&self, order: Vec<FavoriteRef>) -> Result<Vec<FavoriteDTO>, ApiError> {
+        let existing: Vec<FavoriteRef> = self
+            .db_repo
+            .list_favorites(&self.vault_id)?
+            .into_iter()
+            .map(|favorite| FavoriteRef {
+                kind: favorite.kind,
+                target: favorite.target,
+            })
+            .collect();
+        let mut ordered: Vec<FavoriteRef> = Vec::with_capacity(existing.len());
+        for favorite in order {
+            let favorite = self.normalize_favorite(favorite)?;
+            if existing.contains(&favorite) && !ordered.contains(&favorite) {
+                ordered.push(favorite);
+            }
+        }
+        for favorite in existing {
+            if !ordered.contains(&favorite) {
+                ordered.push(favorite);
+            }
+        }
+        self.db_repo.set_favorite_order(&self.vault_id, &ordered)?;
+        self.list_favorites()
+    }
 
-        let result = (|| -> Result<(), ApiError> {
-            // First update tasks in database
-            self.db_repo.reorder_tasks(tasks.clone())?;
+    // Record that a note was opened in the editor, or where its cursor is now
+    pub fn record_note_opened(
+        &self,
+        path: &str,
+        cursor: Option<NoteCursor>,
+    ) -> Result<RecentNote, ApiError> {
+        let path = self.resolve_note_path(path)?;
+        path_policy::resolve_existing_path(self.md_repo.vault_root(), Path::new(&path))?;
+        let opened_at = Utc::now().to_rfc3339();
+        self.db_repo
+            .record_note_opened(&self.vault_id, &path, cursor, &opened_at)?;
+        let cursor = match cursor {
+            Some(cursor) => Some(cursor),
+            None => self
+                .db_repo
+                .list_recent_notes(&self.vault_id, 1)?
+                .into_iter()
+                .find(|note| note.path == path)
+                .and_then(|note| note.cursor),
+        };
+        Ok(RecentNote {
+            path,
+            opened_at,
+            cursor,
+        })
+    }
 
-            // Then sync each task to markdown file
-            for task in tasks {
-                // Get the updated task from database
-                let updated_task = self.get_task_or_not_found(&task.id)?;
+    // Recently opened notes, most recent first; notes deleted or moved since are flagged missing
+    pub fn list_recent_notes(&self, limit: i64) -> Result<Vec<RecentNoteDTO>, ApiError> {
+        let notes = self.db_repo.list_recent_notes(&self.vault_id, limit)?;
+        Ok(notes
+            .into_iter()
+            .map(|note| RecentNoteDTO {
+                missing: !self.vault_root.join(&note.path).is_file(),
+                note,
+            })
+            .collect())
+    }
 
-                // Prepare frontmatter updates
-                let mut frontmatter_updates = HashMap::new();
-                frontmatter_updates
-                    .insert("updated_at".to_string(), updated_task.updated_at.clone());
+    fn stored_layouts(&self) -> Result<serde_json::Map<String, serde_json::Value>, ApiError> {
+        Ok(match self.ui_state_value(LAYOUTS_KEY)? {
+            Some(serde_json::Value::Object(layouts)) => layouts,
+            _ => serde_json::Map::new(),
+        })
+    }
 
-                // Update status if it changed
-                if let Some(status) = task.status {
-                    frontmatter_updates.insert("status".to_string(), status.to_string());
+    // Saved layouts by name; layouts that cannot be read are left out
+    pub fn list_layouts(&self) -> Result<Vec<LayoutSummary>, ApiError> {
+        let mut layouts: Vec<LayoutSummary> = self
+            .stored_layouts()?
+            .into_iter()
+            .filter_map(|(name, value)| match workspace::migrate_layout(value) {
+                Ok(layout) => Some(LayoutSummary::from(&layout)),
+                Err(err) => {
+                    warn!(target: "workspace", "skipping unreadable layout: name={}, error={}", name, err.message);
+                    None
                 }
+            })
+            .collect();
+        layouts.sort_by_key(|layout| layout.name.to_lowercase());
+        Ok(layouts)
+    }
 
-                // Always include current status and priority
-                frontmatter_updates.insert("status".to_string(), updated_task.status.to_string());
-                frontmatter_updates.insert(
-                    "priority".to_string(),
-                    updated_task
-                        .priority
-                        .map(|p| p.to_string())
-                        .unwrap_or("p3".to_string()),
-                );
+    // Validate and save a layout under the name, replacing any layout of that name
+    pub fn save_layout(&self, name: &str, input: LayoutInput) -> Result<WorkspaceLayout, ApiError> {
+        let layout = workspace::validate_layout(name, input, &Utc::now().to_rfc3339())?;
+        let mut layouts = self.stored_layouts()?;
+        layouts.insert(layout.name.clone(), serde_json::to_value(&layout)?);
+        self.db_repo.replace_ui_state_key(
+            &self.vault_id,
+            LAYOUTS_KEY,
+            serde_json::Value::Object(layouts),
+        )?;
+        info!(target: "workspace", "layout saved: name={}, panes={}", layout.name, layout.panes.len());
+        Ok(layout)
+    }
 
-                // Sync to markdown file
-                let slug = updated_task.task_dir_slug.as_deref().unwrap_or("task");
-                self.sync_task_to_md(&updated_task.id, slug, &frontmatter_updates)?;
-            }
+    pub fn load_layout(&self, name: &str) -> Result<WorkspaceLayout, ApiError> {
+        let name = workspace::validate_layout_name(name)?;
+        let value = self
+            .stored_layouts()?
+            .remove(&name)
+            .ok_or_else(|| ApiError {
+                code: ErrorCode::NotFound,
+                message: "Layout not found".to_string(),
+                details: Some(serde_json::json!({ "name": name })),
+            })?;
+        workspace::migrate_layout(value)
+    }
 
-            Ok(())
-        })();
+    // Returns whether a layout was removed
+    pub fn delete_layout(&self, name: &str) -> Result<bool, ApiError> {
+        let name = workspace::validate_layout_name(name)?;
+        let mut layouts = self.stored_layouts()?;
+        if layouts.remove(&name).is_none() {
+            return Ok(false);
+        }
+        self.db_repo.replace_ui_state_key(
+            &self.vault_id,
+            LAYOUTS_KEY,
+            serde_json::Value::Object(layouts),
+        )?;
+        Ok(true)
+    }
 
-        let elapsed = start.elapsed();
+    pub fn get_workspace_state(&self) -> Result<WorkspaceStateDTO, ApiError> {
+        Ok(WorkspaceStateDTO {
+            vault_id: self.vault_id.clone(),
+            favorites: self.list_favorites()?,
+            recent_notes: self.list_recent_notes(DEFAULT_RECENT_NOTES_LIMIT)?,
+            layouts: self.list_layouts()?,
+        })
+    }
 
-        match &result {
-            Ok(_) => {
-                info!(target: "planning", "reorder_tasks succeeded: elapsed_ms={}", elapsed.as_millis());
+    pub fn webview_history(
+        &self,
+        label: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<WebviewHistoryEntry>, ApiError> {
+        self.db_repo
+            .list_webview_history(&self.vault_id, label, limit)
+    }
+
+    // Write journaled note syncs from the tasks' current values, one write per task: syncs an
+    // interrupted update left behind, and the queue of lazy md sync. Returns how many notes were
+    // written; failures stay journaled for the next flush. Nothing is written while md sync is off.
+    pub fn flush_md_sync_journal(&self) -> Result<usize, ApiError> {
+        // Notes are not synced at all; entries left by a crash or an earlier lazy period go
+        if self.md_sync == MdSyncPolicy::Off {
+            let cleared = self.db_repo.clear_md_sync_journal()?;
+            if cleared > 0 {
+                info!(target: "planning", "md sync journal cleared: entries={}", cleared);
             }
-            Err(e) => {
-                error!(target: "planning", "reorder_tasks failed: error_code={}, error_message={}, elapsed_ms={}", &e.code, &e.message, elapsed.as_millis());
+            return Ok(0);
+        }
+        let mut by_task: BTreeMap<String, (Vec<i64>, BTreeSet<String>)> = BTreeMap::new();
+        for pending in self.db_repo.list_pending_md_syncs()? {
+            let (ids, fields) = by_task.entry(pending.task_id).or_default();
+            ids.push(pending.id);
+            fields.extend(pending.fields);
+        }
+        let mut written = 0;
+        for (task_id, (ids, fields)) in by_task {
+            // Deleted tasks have no note left to sync
+            if let Some(task) = self.db_repo.get_task(&task_id)? {
+                let (custom, fields): (Vec<String>, Vec<String>) = fields
+                    .into_iter()
+                    .partition(|field| field.starts_with(CUSTOM_FIELD_SYNC_PREFIX));
+                let updates = task_frontmatter_updates(&task, &fields);
+                let slug = task.task_dir_slug.as_deref().unwrap_or("task");
+                let flushed = self
+                    .md_repo
+                    .update_task_frontmatter(&task.id, slug, &updates)
+                    .and_then(|()| {
+                        if custom.is_empty() {
+                            return Ok(());
+                        }
+                        // Fields cleared since are removed from the note
+                        let values = self.db_repo.get_task_fields(&task.id)?;
+                        let custom: BTreeMap<String, Option<String>> = custom
+                            .iter()
+                            .filter_map(|field| field.strip_prefix(CUSTOM_FIELD_SYNC_PREFIX))
+                            .map(|key| (key.to_string(), values.get(key).cloned()))
+                            .collect();
+                        self.md_repo.set_task_custom_fields(&task.id, slug, &custom)
+                    });
+                match flushed {
+                    Ok(()) => written += 1,
+                    // Still open in the editor; retried on the next flush
+                    Err(e) if e.code == ErrorCode::NoteLocked => continue,
+                    Err(e) => {
+                        warn!(target: "planning", "md sync flush failed: task_id={}, error={}", &task.id, &e.message);
+                        continue;
+                    }
+                }
+            }
+            for id in ids {
+                self.db_repo.clear_md_sync(id)?;
             }
         }
-
-        result
-    }
-
-    // Get UI state for the current vault
-    #[allow(dead_code)]
-    pub fn get_ui_state(&self, vault_id: &str) -> Result<Option<String>, ApiError> {
-        self.db_repo.get_ui_state(vault_id)
+        Ok(written)
     }
 
-    // Set UI state for the current vault
-    #[allow(dead_code)]
-    pub fn set_ui_state(&self, vault_id: &str, partial_state_json: &str) -> Result<(), ApiError> {
-        self.db_repo.set_ui_state(vault_id, partial_state_json)
+    // Sync several task notes on a bounded pool of threads; the first failure is returned once
+    // all writes finished
+    fn sync_tasks_to_md(
+        &self,
+        syncs: Vec<(String, String, HashMap<String, String>)>,
+    ) -> Result<(), ApiError> {
+        let workers = MD_SYNC_WORKERS.min(syncs.len());
+        if workers <= 1 || self.md_sync != MdSyncPolicy::Eager {
+            for (task_id, slug, updates) in &syncs {
+                self.sync_task_to_md(task_id, slug, updates)?;
+            }
+            return Ok(());
+        }
+        let queue = std::sync::Mutex::new(syncs.into_iter());
+        let failure = std::sync::Mutex::new(None);
+        let locked = std::sync::Mutex::new(Vec::new());
+        let md_repo = &self.md_repo;
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let next = queue.lock().ok().and_then(|mut queue| queue.next());
+                    let Some((task_id, slug, updates)) = next else {
+                        break;
+                    };
+                    match md_repo.update_task_frontmatter(&task_id, &slug, &updates) {
+                        Err(e) if e.code == ErrorCode::NoteLocked => {
+                            if let Ok(mut locked) = locked.lock() {
+                                locked.push((task_id, updates));
+                            }
+                        }
+                        Err(e) => {
+                            if let Ok(mut failure) = failure.lock() {
+                                failure.get_or_insert(e);
+                            }
+                        }
+                        Ok(()) => {}
+                    }
+                });
+            }
+        });
+        for (task_id, updates) in locked.into_inner().unwrap_or_default() {
+            self.journal_task_sync(&task_id, &updates)?;
+        }
+        match failure.into_inner().ok().flatten() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
     }
 
-    // Sync task changes to markdown file
+    // Sync task changes to markdown file as the md sync policy says
     pub fn sync_task_to_md(
         &self,
         task_id: &str,
         slug: &str,
         frontmatter_updates: &HashMap<String, String>,
     ) -> Result<(), ApiError> {
-        self.md_repo
-            .update_task_frontmatter(task_id, slug, frontmatter_updates)
+        match self.md_sync {
+            MdSyncPolicy::Eager => {
+                match self
+                    .md_repo
+                    .update_task_frontmatter(task_id, slug, frontmatter_updates)
+                {
+                    // The note is open in the editor; the journal flush writes it once released
+                    Err(e) if e.code == ErrorCode::NoteLocked => {
+                        self.journal_task_sync(task_id, frontmatter_updates)
+                    }
+                    result => result,
+                }
+            }
+            MdSyncPolicy::Lazy => self.journal_task_sync(task_id, frontmatter_updates),
+            MdSyncPolicy::Off => Ok(()),
+        }
+    }
+
+    fn journal_task_sync(
+        &self,
+        task_id: &str,
+        frontmatter_updates: &HashMap<String, String>,
+    ) -> Result<(), ApiError> {
+        let fields: Vec<&str> = frontmatter_updates.keys().map(String::as_str).collect();
+        self.db_repo.journal_md_sync(task_id, &fields).map(|_| ())
     }
 
     // Delete a task and its associated resources
@@ -974,7 +5466,10 @@ Frontmatter 由系统维护；正文为你的笔记区。
             // Delete task from database
             self.db_repo.delete_task(task_id)?;
 
-            // Delete associated markdown file if it exists
+            // Delete associated markdown file if it exists; with md sync off notes are left alone
+            if self.md_sync == MdSyncPolicy::Off {
+                return Ok(());
+            }
             match self.md_repo.delete_task_md(task_id, slug) {
                 Ok(_) => {
                     info!(target: "planning", "delete_task_md succeeded: task_id={}", task_id);
@@ -993,6 +5488,7 @@ Frontmatter 由系统维护；正文为你的笔记区。
         match &result {
             Ok(_) => {
                 info!(target: "planning", "delete_task succeeded: task_id={}, elapsed_ms={}", task_id, elapsed.as_millis());
+                self.boards_changed();
             }
             Err(e) => {
                 error!(target: "planning", "delete_task failed: task_id={}, error_code={}, error_message={}, elapsed_ms={}", task_id, &e.code, &e.message, elapsed.as_millis());
@@ -1002,17 +5498,117 @@ Frontmatter 由系统维护；正文为你的笔记区。
         result
     }
 
+    pub fn list_tags(&self) -> Result<Vec<TagCount>, ApiError> {
+        self.db_repo.list_tag_counts()
+    }
+
+    // Suggest tags for free text or a vault note (a single-line .md path that exists), keeping
+    // to the existing tag vocabulary plus at most max_new new tags
+    pub async fn ai_suggest_tags(
+        vault_root: &Path,
+        client: &Client,
+        text_or_path: &str,
+        max_new: usize,
+    ) -> Result<TagSuggestionResponse, ApiError> {
+        let span = span!(Level::INFO, "planning.ai_suggest_tags");
+        let _enter = span.enter();
+
+        let candidate = text_or_path.trim();
+        let note = (!candidate.contains('\n') && candidate.to_lowercase().ends_with(".md"))
+            .then(|| vault_service::read_text_file(vault_root, Path::new(candidate)).ok())
+            .flatten();
+        let (path, text) = match note {
+            Some(note) => (Some(note.path), note.content),
+            None => (None, text_or_path.to_string()),
+        };
+        if text.trim().is_empty() {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "Nothing to tag".to_string(),
+                details: path.map(|path| serde_json::json!({ "path": path })),
+            });
+        }
+
+        let vocabulary: Vec<String> = Self::open(vault_root)?
+            .db_repo
+            .list_tag_counts()?
+            .into_iter()
+            .take(TAG_VOCABULARY_LIMIT)
+            .map(|count| count.tag)
+            .collect();
+        let listed = if vocabulary.is_empty() {
+            "(none yet)".to_string()
+        } else {
+            vocabulary.join(", ")
+        };
+        let system_prompt = TAG_SUGGEST_SYSTEM_PROMPT
+            .replace("{max_new}", &max_new.to_string())
+            .replace("{vocabulary}", &listed);
+
+        let settings = settings_repo::resolve_ai_settings(vault_root)?;
+        let ai_service = AiService::new(client.clone(), settings).with_cache(vault_root);
+        let output = ai_service
+            .complete_with_budget(&system_prompt, &text)
+            .await?;
+        let content = output.content;
+
+        let json_str = match (content.find('{'), content.rfind('}')) {
+            (Some(start), Some(end)) if start < end => &content[start..=end],
+            _ => &content,
+        };
+
+        #[derive(serde::Deserialize)]
+        struct ProposedTag {
+            tag: String,
+            confidence: Option<f32>,
+        }
+        #[derive(serde::Deserialize)]
+        struct AiResponse {
+            tags: Vec<ProposedTag>,
+        }
+
+        let response: AiResponse = serde_json::from_str(json_str).map_err(|e| ApiError {
+            code: ErrorCode::AiParseFailed,
+            message: format!("Failed to parse AI response: {}", e),
+            details: Some(serde_json::json!({ "raw": content })),
+        })?;
+
+        let proposals = response
+            .tags
+            .into_iter()
+            .map(|proposed| (proposed.tag, proposed.confidence.unwrap_or(0.5)))
+            .collect();
+        let suggestions = tagging::constrain_suggestions(proposals, &vocabulary, max_new);
+        info!(target: "planning", "ai_suggest_tags succeeded: suggestions={}, new={}", suggestions.len(), suggestions.iter().filter(|s| s.is_new).count());
+
+        Ok(TagSuggestionResponse {
+            path,
+            suggestions,
+            usage: output.usage,
+        })
+    }
+
+    pub fn clear_ai_cache(&self) -> Result<usize, ApiError> {
+        let cleared = self.db_repo.clear_ai_cache()?;
+        info!(target: "planning", "clear_ai_cache succeeded: cleared={}", cleared);
+        Ok(cleared)
+    }
+
     // AI Smart Capture (Standalone function to avoid Send/Sync issues with PlanningService)
     pub async fn ai_smart_capture(
         vault_root: &Path,
         client: &Client,
         input_text: &str,
-    ) -> Result<Vec<CreateTaskInput>, ApiError> {
+        engine: Option<&EmbeddingEngine>,
+    ) -> Result<SmartCaptureResponse, ApiError> {
         let span = span!(Level::INFO, "planning.ai_smart_capture");
         let _enter = span.enter();
 
         // 1. Load Settings
-        let settings = settings_repo::get_ai_settings(vault_root)?;
+        let settings = settings_repo::resolve_ai_settings(vault_root)?;
+        if let Some(engine) = engine {
+            engine.select(settings.embedding_model);
+        }
 
         if settings.api_key.is_empty() && !settings.base_url.contains("localhost") {
             // Heuristic check: if not local and no key, might fail.
@@ -1020,23 +5616,14 @@ Frontmatter 由系统维护；正文为你的笔记区。
             // Let's assume user knows what they are doing.
         }
 
-        // 2. Prepare Messages
-        let messages = vec![
-            Message {
-                role: "system".to_string(),
-                content: SMART_CAPTURE_SYSTEM_PROMPT.to_string(),
-            },
-            Message {
-                role: "user".to_string(),
-                content: input_text.to_string(),
-            },
-        ];
-
-        // 3. Call AI Service
-        let ai_service = AiService::new(client.clone(), settings);
-        let content = ai_service.chat_completion(messages).await?;
+        // 2. Call AI Service; long input is fitted to the model's context window
+        let ai_service = AiService::new(client.clone(), settings).with_cache(vault_root);
+        let output = ai_service
+            .complete_with_budget(SMART_CAPTURE_SYSTEM_PROMPT, input_text)
+            .await?;
+        let content = output.content;
 
-        // 4. Parse Result
+        // 3. Parse Result
         // Find JSON blob
         let json_str = if let Some(start) = content.find('{') {
             if let Some(end) = content.rfind('}') {
@@ -1062,13 +5649,13 @@ Frontmatter 由系统维护；正文为你的笔记区。
         }
 
         let response: AiResponse = serde_json::from_str(json_str).map_err(|e| ApiError {
-            code: "AiParseFailed".to_string(),
+            code: ErrorCode::AiParseFailed,
             message: format!("Failed to parse AI response: {}", e),
             details: Some(serde_json::json!({ "raw": content })),
         })?;
 
-        // 5. Convert to CreateTaskInput
-        let tasks = response
+        // 4. Convert to CreateTaskInput
+        let tasks: Vec<CreateTaskInput> = response
             .tasks
             .into_iter()
             .map(|t| CreateTaskInput {
@@ -1094,9 +5681,257 @@ Frontmatter 由系统维护；正文为你的笔记区。
                 scheduled_start: None,
                 scheduled_end: None,
                 note_path: None,
+                color: None,
+                op_id: None,
             })
             .collect();
 
-        Ok(tasks)
+        // 5. Flag suggestions that repeat open tasks
+        let open_tasks = Self::open(vault_root)?.db_repo.list_open_tasks()?;
+        let embeddings = engine
+            .filter(|_| !tasks.is_empty() && !open_tasks.is_empty())
+            .and_then(|engine| {
+                let titles = tasks
+                    .iter()
+                    .map(|task| task.title.clone())
+                    .chain(open_tasks.iter().map(|task| task.title.clone()))
+                    .collect();
+                engine
+                    .embed_documents(titles)
+                    .map_err(|e| {
+                        warn!(target: "planning", "smart capture embeddings failed, using title matching only: {}", e)
+                    })
+                    .ok()
+            });
+        // Proposal embeddings come first, open task embeddings after them
+        let task_count = tasks.len();
+        let similarity = embeddings.as_ref().map(|embeddings| {
+            move |task: usize, open: usize| {
+                EmbeddingEngine::cosine_similarity(
+                    &embeddings[task],
+                    &embeddings[task_count + open],
+                )
+            }
+        });
+
+        let tasks = capture::mark_duplicates(
+            tasks,
+            &open_tasks,
+            similarity
+                .as_ref()
+                .map(|similarity| similarity as &dyn Fn(usize, usize) -> f32),
+        );
+        Ok(SmartCaptureResponse {
+            tasks,
+            usage: output.usage,
+        })
+    }
+
+    // Smart capture over a vault note, or a line range of it, keeping the note as the source
+    pub async fn ai_capture_from_note(
+        vault_root: &Path,
+        client: &Client,
+        note_path: &str,
+        range: Option<LineRange>,
+        engine: Option<&EmbeddingEngine>,
+    ) -> Result<NoteCaptureResponse, ApiError> {
+        let note = vault_service::read_text_file(vault_root, Path::new(note_path))?;
+        let (text, range) = capture::slice_lines(&note.content, range).ok_or_else(|| ApiError {
+            code: ErrorCode::InvalidInput,
+            message: "Line range is outside the note".to_string(),
+            details: Some(serde_json::json!({ "path": note.path, "range": range })),
+        })?;
+        if text.trim().is_empty() {
+            return Err(ApiError {
+                code: ErrorCode::InvalidInput,
+                message: "Nothing to capture in the selected lines".to_string(),
+                details: Some(serde_json::json!({ "path": note.path, "range": range })),
+            });
+        }
+
+        let captured = Self::ai_smart_capture(vault_root, client, &text, engine).await?;
+        Ok(NoteCaptureResponse {
+            path: note.path,
+            range,
+            suggestions: captured.tasks,
+            created: Vec::new(),
+            annotated_lines: 0,
+            usage: captured.usage,
+        })
+    }
+
+    // Transcribe a voice memo, given as a vault file or as recorded bytes, and run smart capture
+    // over the transcript unless `capture` is off
+    pub async fn ai_voice_capture(
+        vault_root: &Path,
+        client: &Client,
+        audio_path: Option<&str>,
+        audio: Option<(String, Vec<u8>)>,
+        capture: bool,
+        engine: Option<&EmbeddingEngine>,
+    ) -> Result<VoiceCaptureResponse, ApiError> {
+        let span = span!(Level::INFO, "planning.ai_voice_capture");
+        let _enter = span.enter();
+
+        let (path, file_name, bytes) = match (audio_path, audio) {
+            (Some(rel_path), None) => {
+                let resolved = path_policy::resolve_existing_path(vault_root, Path::new(rel_path))?;
+                let bytes = std::fs::read(&resolved).map_err(map_read_error)?;
+                (Some(rel_path.to_string()), rel_path.to_string(), bytes)
+            }
+            (None, Some((file_name, bytes))) => (None, file_name, bytes),
+            _ => {
+                return Err(ApiError {
+                    code: ErrorCode::InvalidInput,
+                    message: "Provide either an audio file path or recorded audio".to_string(),
+                    details: None,
+                });
+            }
+        };
+
+        let settings = settings_repo::resolve_ai_settings(vault_root)?;
+        let transcript = transcription::transcribe(client, &settings, &file_name, bytes).await?;
+        let capture = if capture && !transcript.is_empty() {
+            Some(Self::ai_smart_capture(vault_root, client, &transcript, engine).await?)
+        } else {
+            None
+        };
+
+        info!(target: "planning", "ai_voice_capture succeeded: chars={}, suggestions={}", transcript.chars().count(), capture.as_ref().map_or(0, |c| c.tasks.len()));
+        Ok(VoiceCaptureResponse {
+            path,
+            transcript,
+            capture,
+        })
+    }
+
+    // Read the text of a vault image, then append it to a note (under a heading when given)
+    // and/or run smart capture over it
+    pub async fn ai_capture_from_image(
+        vault_root: &Path,
+        client: &Client,
+        image_path: &str,
+        note: Option<(&str, Option<&str>)>,
+        capture: bool,
+        engine: Option<&EmbeddingEngine>,
+    ) -> Result<ImageCaptureResponse, ApiError> {
+        let span = span!(Level::INFO, "planning.ai_capture_from_image");
+        let _enter = span.enter();
+
+        let resolved = path_policy::resolve_existing_path(vault_root, Path::new(image_path))?;
+        let settings = settings_repo::resolve_ai_settings(vault_root)?;
+        let (text, usage) = ocr::extract_text(client, &settings, &resolved).await?;
+        if text.is_empty() {
+            return Ok(ImageCaptureResponse {
+                path: image_path.to_string(),
+                text,
+                note: None,
+                capture: None,
+                usage,
+            });
+        }
+
+        let note = match note {
+            Some((note_path, heading)) => {
+                let note_dir = note_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+                let image_link = links::relative_link(note_dir, image_path).replace(' ', "%20");
+                let block = capture::image_text_block(&image_link, &text);
+                match heading {
+                    Some(heading) => {
+                        vault_service::append_under_heading(
+                            vault_root,
+                            Path::new(note_path),
+                            heading,
+                            &block,
+                            WriteOrigin::Ai,
+                        )?;
+                    }
+                    None => {
+                        let current =
+                            vault_service::read_text_file(vault_root, Path::new(note_path))?;
+                        let separator =
+                            if current.content.is_empty() || current.content.ends_with("\n\n") {
+                                ""
+                            } else if current.content.ends_with('\n') {
+                                "\n"
+                            } else {
+                                "\n\n"
+                            };
+                        let content = format!("{}{}{}", current.content, separator, block);
+                        vault_service::write_text_file(
+                            vault_root,
+                            Path::new(note_path),
+                            &content,
+                            WriteOrigin::Ai,
+                        )?;
+                    }
+                }
+                Some(note_path.to_string())
+            }
+            None => None,
+        };
+        let capture = if capture {
+            Some(Self::ai_smart_capture(vault_root, client, &text, engine).await?)
+        } else {
+            None
+        };
+
+        info!(target: "planning", "ai_capture_from_image succeeded: chars={}, note={:?}, suggestions={}", text.chars().count(), note, capture.as_ref().map_or(0, |c| c.tasks.len()));
+        Ok(ImageCaptureResponse {
+            path: image_path.to_string(),
+            text,
+            note,
+            capture,
+            usage,
+        })
+    }
+
+    // Create the new suggestions of a note capture, link each to the note and optionally append
+    // task links to the matching source lines
+    pub fn create_captured_tasks(
+        &self,
+        response: &mut NoteCaptureResponse,
+        annotate: bool,
+    ) -> Result<(), ApiError> {
+        for suggestion in response.suggestions.iter().filter(|s| s.is_new) {
+            let task = self.create_task(suggestion.task.clone())?;
+            self.link_note(&task.id, &response.path)?;
+            response.created.push(task);
+        }
+        if !annotate || response.created.is_empty() {
+            return Ok(());
+        }
+
+        let note_dir = response
+            .path
+            .rsplit_once('/')
+            .map(|(dir, _)| dir)
+            .unwrap_or("");
+        let task_links: Vec<(String, String)> = response
+            .created
+            .iter()
+            .filter_map(|task| {
+                let md_rel_path = task.md_rel_path.as_ref()?;
+                let target = links::relative_link(note_dir, md_rel_path).replace(' ', "%20");
+                Some((task.title.clone(), format!("([task]({}))", target)))
+            })
+            .collect();
+
+        let vault_root = self.md_repo.vault_root();
+        let note_path = Path::new(&response.path);
+        let note = vault_service::read_text_file(vault_root, note_path)?;
+        let (content, annotated) =
+            capture::annotate_lines(&note.content, response.range, &task_links);
+        if annotated == 0 {
+            return Ok(());
+        }
+        // The tasks exist either way; a locked or moved note only loses the annotations
+        match vault_service::write_text_file(vault_root, note_path, &content, WriteOrigin::Ai) {
+            Ok(_) => response.annotated_lines = annotated,
+            Err(e) => {
+                warn!(target: "planning", "capture annotation skipped: path={}, error_code={}, error_message={}", response.path, &e.code, &e.message)
+            }
+        }
+        Ok(())
     }
 }
@@ -2,9 +2,10 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::commands::plugins::PluginManifest;
-use crate::ipc::{map_read_error, map_write_error, ApiError};
+use crate::ipc::{map_read_error, map_write_error, ApiError, ErrorCode};
 use crate::paths::rel_path_string;
 use crate::repo::settings_repo;
+use crate::security::note_locks::{self, WriteOrigin};
 use crate::security::path_policy;
 use crate::services::vault_service;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -39,7 +40,7 @@ fn is_valid_plugin_id(plugin_id: &str) -> bool {
 fn validate_plugin_id(plugin_id: &str) -> Result<(), ApiError> {
     if !is_valid_plugin_id(plugin_id) {
         return Err(ApiError {
-            code: "InvalidManifest".to_string(),
+            code: ErrorCode::InvalidManifest,
             message: "Invalid plugin id".to_string(),
             details: Some(serde_json::json!({ "pluginId": plugin_id })),
         });
@@ -68,7 +69,7 @@ pub fn list_plugins(vault_root: &Path) -> Result<PluginsListResult, ApiError> {
                     enabled: false,
                     dir: "".to_string(),
                     error: Some(ApiError {
-                        code: "ScanFailed".to_string(),
+                        code: ErrorCode::ScanFailed,
                         message: "Failed to read plugin entry".to_string(),
                         details: Some(serde_json::json!({ "error": err.to_string() })),
                     }),
@@ -92,7 +93,7 @@ pub fn list_plugins(vault_root: &Path) -> Result<PluginsListResult, ApiError> {
                 enabled,
                 dir: dir_name,
                 error: Some(ApiError {
-                    code: "InvalidManifest".to_string(),
+                    code: ErrorCode::InvalidManifest,
                     message: "Invalid plugin directory name".to_string(),
                     details: None,
                 }),
@@ -107,7 +108,7 @@ pub fn list_plugins(vault_root: &Path) -> Result<PluginsListResult, ApiError> {
                 enabled,
                 dir: dir_name,
                 error: Some(ApiError {
-                    code: "InvalidManifest".to_string(),
+                    code: ErrorCode::InvalidManifest,
                     message: "manifest.json not found".to_string(),
                     details: None,
                 }),
@@ -135,7 +136,7 @@ pub fn list_plugins(vault_root: &Path) -> Result<PluginsListResult, ApiError> {
                     enabled,
                     dir: dir_name,
                     error: Some(ApiError {
-                        code: "InvalidManifest".to_string(),
+                        code: ErrorCode::InvalidManifest,
                         message: "Failed to parse manifest.json".to_string(),
                         details: Some(serde_json::json!({ "error": err.to_string() })),
                     }),
@@ -149,7 +150,7 @@ pub fn list_plugins(vault_root: &Path) -> Result<PluginsListResult, ApiError> {
                 enabled,
                 dir: dir_name,
                 error: Some(ApiError {
-                    code: "InvalidManifest".to_string(),
+                    code: ErrorCode::InvalidManifest,
                     message: "manifest.id must match directory name".to_string(),
                     details: Some(serde_json::json!({ "id": manifest.id })),
                 }),
@@ -162,7 +163,7 @@ pub fn list_plugins(vault_root: &Path) -> Result<PluginsListResult, ApiError> {
                 enabled,
                 dir: dir_name,
                 error: Some(ApiError {
-                    code: "InvalidManifest".to_string(),
+                    code: ErrorCode::InvalidManifest,
                     message: "Only entry=main.js is supported in v0".to_string(),
                     details: Some(serde_json::json!({ "entry": manifest.entry })),
                 }),
@@ -187,20 +188,20 @@ pub fn read_manifest(vault_root: &Path, plugin_id: &str) -> Result<PluginManifes
     let manifest_path = plugins_root(vault_root).join(plugin_id).join(MANIFEST_FILE);
     if !manifest_path.exists() {
         return Err(ApiError {
-            code: "NotFound".to_string(),
+            code: ErrorCode::NotFound,
             message: "manifest.json not found".to_string(),
             details: Some(serde_json::json!({ "pluginId": plugin_id })),
         });
     }
     let text = fs::read_to_string(&manifest_path).map_err(map_read_error)?;
     let manifest: PluginManifest = serde_json::from_str(&text).map_err(|err| ApiError {
-        code: "InvalidManifest".to_string(),
+        code: ErrorCode::InvalidManifest,
         message: "Failed to parse manifest.json".to_string(),
         details: Some(serde_json::json!({ "error": err.to_string() })),
     })?;
     if manifest.id != plugin_id {
         return Err(ApiError {
-            code: "InvalidManifest".to_string(),
+            code: ErrorCode::InvalidManifest,
             message: "manifest.id must match pluginId".to_string(),
             details: Some(serde_json::json!({ "id": manifest.id, "pluginId": plugin_id })),
         });
@@ -212,7 +213,7 @@ pub fn read_entry(vault_root: &Path, plugin_id: &str, entry: &str) -> Result<Str
     validate_plugin_id(plugin_id)?;
     if entry != "main.js" {
         return Err(ApiError {
-            code: "EntryNotFound".to_string(),
+            code: ErrorCode::EntryNotFound,
             message: "Only main.js is supported in v0".to_string(),
             details: Some(serde_json::json!({ "entry": entry })),
         });
@@ -220,7 +221,7 @@ pub fn read_entry(vault_root: &Path, plugin_id: &str, entry: &str) -> Result<Str
     let entry_path = plugins_root(vault_root).join(plugin_id).join(entry);
     if !entry_path.exists() {
         return Err(ApiError {
-            code: "EntryNotFound".to_string(),
+            code: ErrorCode::EntryNotFound,
             message: "Entry not found".to_string(),
             details: Some(serde_json::json!({ "entry": entry })),
         });
@@ -245,11 +246,12 @@ pub fn vault_write_text(
 ) -> Result<vault_service::WriteTextResult, ApiError> {
     if rel_path.is_absolute() {
         return Err(ApiError {
-            code: "PathOutsideVault".to_string(),
+            code: ErrorCode::PathOutsideVault,
             message: "Absolute paths are not allowed".to_string(),
             details: None,
         });
     }
+    note_locks::ensure_writable(vault_root, rel_path, WriteOrigin::Plugin)?;
     let abs_path = vault_root.join(rel_path);
     if let Some(parent) = abs_path.parent() {
         path_policy::ensure_or_create_dir_in_vault(vault_root, parent)?;
@@ -259,7 +261,7 @@ pub fn vault_write_text(
         let meta = fs::symlink_metadata(&abs_path).map_err(map_read_error)?;
         if meta.file_type().is_symlink() {
             return Err(ApiError {
-                code: "SymlinkNotAllowed".to_string(),
+                code: ErrorCode::SymlinkNotAllowed,
                 message: "Symlink file is not allowed".to_string(),
                 details: Some(serde_json::json!({ "path": rel_path_string(rel_path) })),
             });
@@ -267,7 +269,7 @@ pub fn vault_write_text(
     }
 
     let parent = abs_path.parent().ok_or_else(|| ApiError {
-        code: "WriteFailed".to_string(),
+        code: ErrorCode::WriteFailed,
         message: "Invalid target path".to_string(),
         details: None,
     })?;
@@ -289,7 +291,7 @@ pub fn vault_write_text(
                 if meta.file_type().is_symlink() {
                     let _ = fs::remove_file(&temp_path);
                     return Err(ApiError {
-                        code: "SymlinkNotAllowed".to_string(),
+                        code: ErrorCode::SymlinkNotAllowed,
                         message: "Symlink file is not allowed".to_string(),
                         details: Some(serde_json::json!({ "path": rel_path_string(rel_path) })),
                     });
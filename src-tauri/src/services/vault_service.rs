@@ -1,17 +1,37 @@
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
+use crate::domain::frontmatter;
+use crate::domain::links::{normalize_note_path, rewrite_note_links};
+use crate::domain::lint::{self, LintDiagnostic};
+use crate::domain::note_stats::{self, FolderStatsDTO, NoteStats, NoteStatsEntry};
+use crate::domain::outline::{self, HeadingNode};
+use crate::features::metrics;
 use crate::ipc::{
-    map_io_error, map_read_error, map_write_error, write_error_with_context, ApiError,
+    map_io_error, map_read_error, map_write_error, write_error_with_context, ApiError, ErrorCode,
 };
-use crate::paths::{canonical_to_string, rel_path_string};
+use crate::paths::{canonical_to_string, is_reserved_name, nfc, rel_path_string, tasks_dir, VaultLayout};
+use crate::repo::scan_snapshot_repo::{self, ScanSnapshot, SnapshotEntry};
+use crate::repo::settings_repo::{self, PlanningSettings};
+use crate::security::note_locks::{self, WriteOrigin};
 use crate::security::path_policy;
 
 const IGNORE_DIRS: [&str; 5] = [".git", "node_modules", "target", ".idea", ".vscode"];
-const MAX_SCAN_ENTRIES_WARNING: usize = 2000;
-const MAX_SCAN_ENTRIES_LIMIT: usize = 8000;
+// Atomic writes go through ".tmp-*" files next to their target. Batch renames park user entries
+// under ".tmp-rename-*", so those are never pruned.
+const TEMP_FILE_PREFIX: &str = ".tmp-";
+const RENAME_TEMP_PREFIX: &str = ".tmp-rename-";
+// Defaults for vaults whose settings.json does not override them
+const DEFAULT_SCAN_ENTRIES_WARNING: usize = 2000;
+const DEFAULT_SCAN_ENTRIES_LIMIT: usize = 8000;
+// Nodes per streamed scan batch, and the longest a partial batch is held back
+const SCAN_STREAM_BATCH: usize = 500;
+const SCAN_STREAM_FLUSH: std::time::Duration = std::time::Duration::from_millis(150);
+// Deletions older than this are forgotten by the scan snapshot
+const DELETED_RETENTION_SECS: u64 = 30 * 24 * 60 * 60;
 
 #[derive(Serialize, Clone)]
 pub struct FileNode {
@@ -27,15 +47,40 @@ pub struct FileNode {
 
 #[derive(Clone)]
 pub struct WarningItem {
-    pub code: String,
+    pub code: ErrorCode,
     pub message: String,
     pub path: Option<String>,
 }
 
+// Thresholds a scan ran with, after applying the vault's overrides
+#[derive(Serialize, Clone, Copy)]
+pub struct ScanLimits {
+    #[serde(rename = "warningEntries")]
+    pub warning_entries: usize,
+    #[serde(rename = "limitEntries")]
+    pub limit_entries: usize,
+}
+
 pub struct ScanVaultResult {
     pub vault_root: String,
     pub tree: Vec<FileNode>,
     pub warnings: Vec<WarningItem>,
+    pub limits: ScanLimits,
+    pub truncated: bool,
+}
+
+pub struct ScanStreamResult {
+    pub vault_root: String,
+    pub entry_count: usize,
+    pub warnings: Vec<WarningItem>,
+}
+
+pub struct ScanChangesResult {
+    pub scanned_at: u64, // Pass back as since_mtime on the next call
+    pub created: Vec<FileNode>,
+    pub modified: Vec<FileNode>,
+    pub deleted: Vec<String>,
+    pub warnings: Vec<WarningItem>,
 }
 
 pub struct ReadTextResult {
@@ -44,6 +89,12 @@ pub struct ReadTextResult {
     pub mtime: Option<u64>,
 }
 
+pub struct NoteFrontmatterResult {
+    pub path: String,
+    pub frontmatter: serde_json::Value, // Object; empty when the note has none
+    pub mtime: Option<u64>,
+}
+
 pub struct WriteTextResult {
     pub path: String,
     pub mtime: Option<u64>,
@@ -55,6 +106,27 @@ pub struct RenameEntryResult {
     pub mtime: Option<u64>,
 }
 
+pub struct PlannedRename {
+    pub old_path: String,
+    pub new_path: String,
+}
+
+pub struct BatchRenameResult {
+    pub renames: Vec<PlannedRename>,
+    pub applied: bool, // False for a dry run
+}
+
+// Notes rewritten after a rename so their links follow it
+#[derive(Serialize, Default)]
+pub struct LinkUpdateReport {
+    #[serde(rename = "updatedFiles")]
+    pub updated_files: Vec<String>,
+    #[serde(rename = "updatedLinks")]
+    pub updated_links: usize,
+    #[serde(rename = "skippedFiles")]
+    pub skipped_files: Vec<String>, // Open in the editor or unreadable; links there still point to the old path
+}
+
 pub struct DeleteEntryResult {
     pub path: String,
 }
@@ -64,20 +136,29 @@ pub struct CreateEntryResult {
     pub kind: String,
 }
 
+pub struct CopyEntryResult {
+    pub path: String,
+    pub kind: String,
+}
+
 pub fn scan_vault(vault_root: &Path, rel_path: Option<PathBuf>) -> Result<ScanVaultResult, ApiError> {
+    let start = Instant::now();
     let canonical_root = vault_root
         .canonicalize()
-        .map_err(|err| map_io_error("Unknown", "Vault resolve failed", err))?;
+        .map_err(|err| map_io_error(ErrorCode::Unknown, "Vault resolve failed", err))?;
     path_policy::ensure_no_symlink(&canonical_root)?;
 
     let mut warnings: Vec<WarningItem> = Vec::new();
     let target_rel = rel_path.unwrap_or_else(PathBuf::new);
+    // Listed by its in-vault path so entries under an allowed symlink still sit under the root
     let target_abs = if target_rel.as_os_str().is_empty() {
         canonical_root.clone()
     } else {
-        path_policy::resolve_existing_dir(&canonical_root, &target_rel)?
+        path_policy::resolve_existing_dir(&canonical_root, &target_rel)?;
+        canonical_root.join(&target_rel)
     };
 
+    let limits = scan_limits(&canonical_root);
     let mut entry_count: usize = 0;
     let tree = scan_dir_children(
         &canonical_root,
@@ -85,51 +166,268 @@ pub fn scan_vault(vault_root: &Path, rel_path: Option<PathBuf>) -> Result<ScanVa
         &target_rel,
         &mut warnings,
         &mut entry_count,
+        limits.limit_entries,
     )?;
 
-    if entry_count > MAX_SCAN_ENTRIES_WARNING {
+    let truncated = entry_count > limits.limit_entries;
+    if entry_count > limits.warning_entries {
         warnings.push(WarningItem {
-            code: "LargeVault".to_string(),
-            message: format!("Vault has {entry_count} entries, scanning may be slow"),
+            code: ErrorCode::LargeVault,
+            message: format!("Vault has {} entries, scanning may be slow", entry_count.min(limits.limit_entries)),
             path: None,
         });
     }
-    if entry_count > MAX_SCAN_ENTRIES_LIMIT {
+    if truncated {
         warnings.push(WarningItem {
-            code: "ScanLimited".to_string(),
-            message: format!("Scan stopped at {MAX_SCAN_ENTRIES_LIMIT} entries"),
+            code: ErrorCode::ScanLimited,
+            message: format!(
+                "Scan stopped at {} entries; raise scan.limit_entries in settings.json to see the rest",
+                limits.limit_entries
+            ),
             path: None,
         });
     }
+    metrics::observe(&metrics::SCAN_ENTRIES, "scan_vault", entry_count as f64);
+    metrics::observe_duration(&metrics::COMMAND_DURATION, "vault.scan_vault", start.elapsed());
 
     Ok(ScanVaultResult {
         vault_root: canonical_to_string(&canonical_root),
         tree,
         warnings,
+        limits,
+        truncated,
     })
 }
 
+// Walk the whole tree breadth first without the entry limit, handing flat batches of nodes to
+// `on_batch` as directories are read so the caller can render the tree incrementally. Each node's
+// parent is the directory part of its path; directories arrive before their children.
+pub fn scan_vault_stream(
+    vault_root: &Path,
+    rel_path: Option<PathBuf>,
+    mut on_batch: impl FnMut(Vec<FileNode>, usize),
+) -> Result<ScanStreamResult, ApiError> {
+    let start = Instant::now();
+    let canonical_root = vault_root
+        .canonicalize()
+        .map_err(|err| map_io_error(ErrorCode::Unknown, "Vault resolve failed", err))?;
+    path_policy::ensure_no_symlink(&canonical_root)?;
+
+    let target_rel = rel_path.unwrap_or_default();
+    if !target_rel.as_os_str().is_empty() {
+        path_policy::resolve_existing_dir(&canonical_root, &target_rel)?;
+    }
+
+    let mut warnings: Vec<WarningItem> = Vec::new();
+    let mut entry_count: usize = 0;
+    let mut batch: Vec<FileNode> = Vec::new();
+    let mut last_flush = Instant::now();
+    let mut pending = std::collections::VecDeque::from([target_rel.clone()]);
+    while let Some(dir_rel) = pending.pop_front() {
+        let dir_abs = canonical_root.join(&dir_rel);
+        let children = match scan_dir_children(
+            &canonical_root,
+            &dir_abs,
+            &dir_rel,
+            &mut warnings,
+            &mut entry_count,
+            usize::MAX,
+        ) {
+            Ok(children) => children,
+            // Only the starting directory failing fails the scan; deeper ones become warnings
+            Err(err) if dir_rel != target_rel => {
+                warnings.push(WarningItem {
+                    code: err.code,
+                    message: err.message,
+                    path: Some(rel_path_string(&dir_rel)),
+                });
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        for node in children {
+            if node.node_type == "dir" {
+                pending.push_back(PathBuf::from(&node.path));
+            }
+            batch.push(node);
+        }
+        while batch.len() >= SCAN_STREAM_BATCH {
+            on_batch(batch.drain(..SCAN_STREAM_BATCH).collect(), entry_count);
+            last_flush = Instant::now();
+        }
+        if !batch.is_empty() && last_flush.elapsed() >= SCAN_STREAM_FLUSH {
+            on_batch(std::mem::take(&mut batch), entry_count);
+            last_flush = Instant::now();
+        }
+    }
+    if !batch.is_empty() {
+        on_batch(batch, entry_count);
+    }
+    metrics::observe(&metrics::SCAN_ENTRIES, "scan_vault_stream", entry_count as f64);
+    metrics::observe_duration(&metrics::COMMAND_DURATION, "vault.scan_vault_stream", start.elapsed());
+
+    Ok(ScanStreamResult {
+        vault_root: canonical_to_string(&canonical_root),
+        entry_count,
+        warnings,
+    })
+}
+
+// Effective scan thresholds; unreadable settings fall back to the defaults rather than blocking the scan
+fn scan_limits(vault_root: &Path) -> ScanLimits {
+    let scan = settings_repo::load_settings(vault_root).map(|settings| settings.scan).unwrap_or_default();
+    let limit_entries = scan.limit_entries.unwrap_or(DEFAULT_SCAN_ENTRIES_LIMIT).max(1);
+    ScanLimits {
+        warning_entries: scan.warning_entries.unwrap_or(DEFAULT_SCAN_ENTRIES_WARNING).min(limit_entries),
+        limit_entries,
+    }
+}
+
+// Every directory and markdown file in the vault, depth first, with the warnings collected on the way
+fn list_entries(vault_root: &Path) -> Result<(Vec<FileNode>, Vec<WarningItem>), ApiError> {
+    let mut entries = Vec::new();
+    let mut warnings = Vec::new();
+    let mut pending = vec![None];
+    while let Some(rel_path) = pending.pop() {
+        let result = scan_vault(vault_root, rel_path)?;
+        warnings.extend(result.warnings);
+        // Reversed so the stack pops entries in tree order
+        for node in result.tree.into_iter().rev() {
+            if node.node_type == "dir" {
+                pending.push(Some(PathBuf::from(&node.path)));
+            }
+            entries.push(node);
+        }
+    }
+    Ok((entries, warnings))
+}
+
+// Every markdown file in the vault, depth first, with the warnings collected on the way
+pub fn list_markdown_files(vault_root: &Path) -> Result<(Vec<String>, Vec<WarningItem>), ApiError> {
+    let (entries, warnings) = list_entries(vault_root)?;
+    let files = entries
+        .into_iter()
+        .filter(|node| node.node_type == "file")
+        .map(|node| node.path)
+        .collect();
+    Ok((files, warnings))
+}
+
+// Entries created, modified or deleted after since_mtime (unix seconds), judged against the
+// snapshot persisted by the previous call; the first call reports by mtime only
+pub fn scan_vault_changes(vault_root: &Path, since_mtime: u64) -> Result<ScanChangesResult, ApiError> {
+    let scanned_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let start = Instant::now();
+    let previous = scan_snapshot_repo::load_snapshot(vault_root)?;
+    let first_scan = previous.is_none();
+    let previous = previous.unwrap_or_default();
+    let (entries, warnings) = list_entries(vault_root)?;
+    metrics::observe(&metrics::SCAN_ENTRIES, "scan_vault_changes", entries.len() as f64);
+
+    let mut snapshot = ScanSnapshot {
+        scanned_at,
+        entries: BTreeMap::new(),
+        deleted: BTreeMap::new(),
+    };
+    let mut created = Vec::new();
+    let mut modified = Vec::new();
+    for node in entries {
+        let dir = node.node_type == "dir";
+        let seen_at = match previous.entries.get(&node.path) {
+            Some(entry) if entry.dir == dir => entry.seen_at,
+            _ if first_scan => node.mtime.unwrap_or_default(),
+            _ => scanned_at,
+        };
+        if seen_at > since_mtime {
+            created.push(node.clone());
+        } else if !dir && node.mtime.is_some_and(|mtime| mtime > since_mtime) {
+            modified.push(node.clone());
+        }
+        snapshot.entries.insert(
+            node.path,
+            SnapshotEntry {
+                dir,
+                mtime: node.mtime,
+                seen_at,
+            },
+        );
+    }
+
+    // A scan that stopped at the entry limit cannot tell missing entries from unscanned ones
+    let limited = warnings
+        .iter()
+        .any(|warning| matches!(warning.code, ErrorCode::ScanLimited));
+    let retain_after = scanned_at.saturating_sub(DELETED_RETENTION_SECS);
+    for (path, deleted_at) in previous.deleted {
+        if deleted_at > retain_after && !snapshot.entries.contains_key(&path) {
+            snapshot.deleted.insert(path, deleted_at);
+        }
+    }
+    if !limited {
+        for path in previous.entries.into_keys() {
+            if !snapshot.entries.contains_key(&path) {
+                snapshot.deleted.insert(path, scanned_at);
+            }
+        }
+    }
+    let deleted = snapshot
+        .deleted
+        .iter()
+        .filter(|(_, deleted_at)| **deleted_at > since_mtime)
+        .map(|(path, _)| path.clone())
+        .collect();
+
+    scan_snapshot_repo::save_snapshot(vault_root, &snapshot)?;
+    metrics::observe_duration(&metrics::COMMAND_DURATION, "vault.scan_vault_changes", start.elapsed());
+
+    Ok(ScanChangesResult {
+        scanned_at,
+        created,
+        modified,
+        deleted,
+        warnings,
+    })
+}
+
+// Target of a symlink the scan may follow; links back to an ancestor would make the scan loop forever
+fn symlink_scan_target(canonical_root: &Path, dir_abs: &Path, link: &Path) -> Result<PathBuf, &'static str> {
+    if !path_policy::symlink_allowed(canonical_root, link) {
+        return Err("Symlink path is not allowed");
+    }
+    let target = link.canonicalize().map_err(|_| "Symlink target does not exist")?;
+    if dir_abs.canonicalize().is_ok_and(|dir| dir.starts_with(&target)) {
+        return Err("Symlink points to one of its parent directories");
+    }
+    Ok(target)
+}
+
 fn scan_dir_children(
     canonical_root: &Path,
     dir_abs: &Path,
     dir_rel: &Path,
     warnings: &mut Vec<WarningItem>,
     entry_count: &mut usize,
+    limit: usize,
 ) -> Result<Vec<FileNode>, ApiError> {
     let mut dirs = Vec::new();
     let mut files = Vec::new();
 
     let entries =
-        fs::read_dir(dir_abs).map_err(|err| map_io_error("ScanFailed", "Failed to read directory", err))?;
+        fs::read_dir(dir_abs).map_err(|err| map_io_error(ErrorCode::ScanFailed, "Failed to read directory", err))?;
     for entry in entries {
-        if *entry_count >= MAX_SCAN_ENTRIES_LIMIT {
+        if *entry_count >= limit {
+            // Counted past the limit so the caller can tell the scan was cut short
+            *entry_count += 1;
             break;
         }
         let entry = match entry {
             Ok(entry) => entry,
             Err(err) => {
                 warnings.push(WarningItem {
-                    code: "ScanFailed".to_string(),
+                    code: ErrorCode::ScanFailed,
                     message: format!("Failed to read entry: {err}"),
                     path: Some(rel_path_string(dir_rel)),
                 });
@@ -146,11 +444,11 @@ fn scan_dir_children(
         }
 
         let entry_path = entry.path();
-        let meta = match fs::symlink_metadata(&entry_path) {
+        let mut meta = match fs::symlink_metadata(&entry_path) {
             Ok(meta) => meta,
             Err(err) => {
                 warnings.push(WarningItem {
-                    code: "ScanFailed".to_string(),
+                    code: ErrorCode::ScanFailed,
                     message: format!("Metadata failed: {err}"),
                     path: Some(rel_path_string(dir_rel)),
                 });
@@ -158,17 +456,33 @@ fn scan_dir_children(
             }
         };
         if meta.file_type().is_symlink() {
-            warnings.push(WarningItem {
-                code: "SymlinkNotAllowed".to_string(),
-                message: "Symlink path is not allowed".to_string(),
-                path: Some(rel_path_string(dir_rel)),
-            });
-            continue;
+            let target = match symlink_scan_target(canonical_root, dir_abs, &entry_path) {
+                Ok(target) => target,
+                Err(message) => {
+                    warnings.push(WarningItem {
+                        code: ErrorCode::SymlinkNotAllowed,
+                        message: message.to_string(),
+                        path: Some(rel_path_string(dir_rel)),
+                    });
+                    continue;
+                }
+            };
+            meta = match fs::metadata(&target) {
+                Ok(meta) => meta,
+                Err(err) => {
+                    warnings.push(WarningItem {
+                        code: ErrorCode::ScanFailed,
+                        message: format!("Metadata failed: {err}"),
+                        path: Some(rel_path_string(dir_rel)),
+                    });
+                    continue;
+                }
+            };
         }
 
         if !entry_path.starts_with(canonical_root) {
             warnings.push(WarningItem {
-                code: "PathOutsideVault".to_string(),
+                code: ErrorCode::PathOutsideVault,
                 message: "Entry path outside vault".to_string(),
                 path: Some(rel_path_string(dir_rel)),
             });
@@ -218,7 +532,7 @@ pub fn read_text_file(vault_root: &Path, rel_path: &Path) -> Result<ReadTextResu
     let resolved = path_policy::resolve_existing_path(vault_root, rel_path)?;
     let bytes = fs::read(&resolved).map_err(map_read_error)?;
     let content = String::from_utf8(bytes).map_err(|err| ApiError {
-        code: "DecodeFailed".to_string(),
+        code: ErrorCode::DecodeFailed,
         message: "Failed to decode file as UTF-8".to_string(),
         details: Some(serde_json::json!({ "error": err.to_string() })),
     })?;
@@ -231,10 +545,65 @@ pub fn read_text_file(vault_root: &Path, rel_path: &Path) -> Result<ReadTextResu
     })
 }
 
-pub fn write_text_file(vault_root: &Path, rel_path: &Path, content: &str) -> Result<WriteTextResult, ApiError> {
+// Delete temp files at least `min_age` old, left by atomic writes interrupted before their rename.
+// Returns the vault-relative paths removed.
+pub fn prune_stale_temp_files(vault_root: &Path, min_age: std::time::Duration) -> Vec<String> {
+    let now = SystemTime::now();
+    let mut removed = Vec::new();
+    let mut pending = vec![vault_root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                if !IGNORE_DIRS.iter().any(|dir| dir.eq_ignore_ascii_case(&name)) {
+                    pending.push(entry.path());
+                }
+                continue;
+            }
+            if !file_type.is_file()
+                || !name.starts_with(TEMP_FILE_PREFIX)
+                || name.starts_with(RENAME_TEMP_PREFIX)
+            {
+                continue;
+            }
+            let stale = entry
+                .metadata()
+                .and_then(|meta| meta.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age >= min_age);
+            if !stale {
+                continue;
+            }
+            let path = entry.path();
+            let rel = rel_path_string(path.strip_prefix(vault_root).unwrap_or(&path));
+            match fs::remove_file(&path) {
+                Ok(()) => removed.push(rel),
+                Err(err) => {
+                    tracing::warn!(target: "vault", "stale temp file not removed: path={}, error={}", rel, err);
+                }
+            }
+        }
+    }
+    removed
+}
+
+pub fn write_text_file(
+    vault_root: &Path,
+    rel_path: &Path,
+    content: &str,
+    origin: WriteOrigin,
+) -> Result<WriteTextResult, ApiError> {
+    note_locks::ensure_writable(vault_root, rel_path, origin)?;
     let resolved = path_policy::resolve_existing_path(vault_root, rel_path)?;
     let parent = resolved.parent().ok_or_else(|| ApiError {
-        code: "WriteFailed".to_string(),
+        code: ErrorCode::WriteFailed,
         message: "Invalid target path".to_string(),
         details: None,
     })?;
@@ -294,18 +663,146 @@ pub fn write_text_file(vault_root: &Path, rel_path: &Path, content: &str) -> Res
     })
 }
 
+fn ensure_markdown_path(rel_path: &Path) -> Result<(), ApiError> {
+    let is_markdown = rel_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md"));
+    if !is_markdown {
+        return Err(ApiError {
+            code: ErrorCode::NotFound,
+            message: "Only markdown files have frontmatter".to_string(),
+            details: Some(serde_json::json!({ "path": rel_path_string(rel_path) })),
+        });
+    }
+    Ok(())
+}
+
+pub fn get_note_frontmatter(vault_root: &Path, rel_path: &Path) -> Result<NoteFrontmatterResult, ApiError> {
+    ensure_markdown_path(rel_path)?;
+    let note = read_text_file(vault_root, rel_path)?;
+    let (yaml, _) = frontmatter::split(&note.content);
+    let mapping = frontmatter::parse(yaml)?;
+    Ok(NoteFrontmatterResult {
+        path: note.path,
+        frontmatter: frontmatter::to_json(&mapping),
+        mtime: note.mtime,
+    })
+}
+
+// Merge a patch into a note's frontmatter (null removes a key); the body is kept byte for byte
+pub fn set_note_frontmatter(
+    vault_root: &Path,
+    rel_path: &Path,
+    patch: &serde_json::Map<String, serde_json::Value>,
+    origin: WriteOrigin,
+) -> Result<NoteFrontmatterResult, ApiError> {
+    ensure_markdown_path(rel_path)?;
+    note_locks::ensure_writable(vault_root, rel_path, origin)?;
+    let note = read_text_file(vault_root, rel_path)?;
+    let (yaml, body) = frontmatter::split(&note.content);
+    let mut mapping = frontmatter::parse(yaml)?;
+    frontmatter::apply_patch(&mut mapping, patch)?;
+    let content = frontmatter::render(&mapping, body)?;
+    let written = write_text_file(vault_root, rel_path, &content, origin)?;
+    Ok(NoteFrontmatterResult {
+        path: written.path,
+        frontmatter: frontmatter::to_json(&mapping),
+        mtime: written.mtime,
+    })
+}
+
+pub fn get_note_outline(vault_root: &Path, rel_path: &Path) -> Result<Vec<HeadingNode>, ApiError> {
+    let note = read_text_file(vault_root, rel_path)?;
+    Ok(outline::outline(&note.content))
+}
+
+pub fn get_note_stats(vault_root: &Path, rel_path: &Path) -> Result<NoteStats, ApiError> {
+    ensure_markdown_path(rel_path)?;
+    let note = read_text_file(vault_root, rel_path)?;
+    Ok(note_stats::note_stats(&note.content))
+}
+
+// Diagnostics from the checks enabled in the vault's lint settings; link targets are looked up
+// in the vault the same way reads resolve them
+pub fn lint_note(vault_root: &Path, rel_path: &Path) -> Result<Vec<LintDiagnostic>, ApiError> {
+    ensure_markdown_path(rel_path)?;
+    let note = read_text_file(vault_root, rel_path)?;
+    let settings = settings_repo::load_settings(vault_root)
+        .map(|settings| settings.lint)
+        .unwrap_or_default();
+    let base_dir = rel_path
+        .parent()
+        .map(rel_path_string)
+        .unwrap_or_default();
+    Ok(lint::lint(&note.content, &base_dir, &settings, |target| {
+        path_policy::resolve_existing_path(vault_root, Path::new(target)).is_ok()
+    }))
+}
+
+// Stats of every note under a folder (the whole vault when rel_path is None); notes that cannot
+// be read are left out of the totals
+pub fn get_folder_stats(vault_root: &Path, rel_path: Option<PathBuf>) -> Result<FolderStatsDTO, ApiError> {
+    let path = rel_path.as_deref().map(rel_path_string).unwrap_or_default();
+    let mut files = Vec::new();
+    scan_vault_stream(vault_root, rel_path, |nodes, _| {
+        files.extend(nodes.into_iter().filter(|node| node.node_type == "file").map(|node| node.path));
+    })?;
+
+    let mut totals = NoteStats::default();
+    let mut notes = Vec::new();
+    for file in files {
+        let note = match read_text_file(vault_root, Path::new(&file)) {
+            Ok(note) => note,
+            Err(err) => {
+                tracing::debug!(target: "vault", "note stats skipped: path={}, error={}", file, err.message);
+                continue;
+            }
+        };
+        let stats = note_stats::note_stats(&note.content);
+        totals.add(&stats);
+        notes.push(NoteStatsEntry { path: file, stats });
+    }
+    Ok(FolderStatsDTO {
+        path,
+        note_count: notes.len(),
+        totals,
+        notes,
+    })
+}
+
+// Add text at the end of a heading's section (the heading is created when missing)
+pub fn append_under_heading(
+    vault_root: &Path,
+    rel_path: &Path,
+    heading: &str,
+    text: &str,
+    origin: WriteOrigin,
+) -> Result<WriteTextResult, ApiError> {
+    if heading.trim().is_empty() {
+        return Err(ApiError {
+            code: ErrorCode::InvalidInput,
+            message: "Heading is empty".to_string(),
+            details: None,
+        });
+    }
+    note_locks::ensure_writable(vault_root, rel_path, origin)?;
+    let note = read_text_file(vault_root, rel_path)?;
+    let content = outline::append_under_heading(&note.content, heading, text);
+    write_text_file(vault_root, rel_path, &content, origin)
+}
+
 pub fn rename_entry(vault_root: &Path, rel_path: &Path, new_name: &str) -> Result<RenameEntryResult, ApiError> {
     let rel_path_text = rel_path_string(rel_path);
     if rel_path_text.trim().is_empty() {
         return Err(ApiError {
-            code: "WriteFailed".to_string(),
+            code: ErrorCode::WriteFailed,
             message: "Invalid path".to_string(),
             details: None,
         });
     }
 
     let source_abs = path_policy::resolve_existing_path(vault_root, rel_path)?;
-    let metadata = fs::metadata(&source_abs).map_err(|err| map_io_error("Unknown", "Metadata failed", err))?;
+    let metadata = fs::metadata(&source_abs).map_err(|err| map_io_error(ErrorCode::Unknown, "Metadata failed", err))?;
 
     let (target_name, err_exists_message) = if metadata.is_dir() {
         (sanitize_dir_name(new_name)?, "Target directory already exists")
@@ -313,7 +810,7 @@ pub fn rename_entry(vault_root: &Path, rel_path: &Path, new_name: &str) -> Resul
         let lower = rel_path_text.to_ascii_lowercase();
         if !lower.ends_with(".md") {
             return Err(ApiError {
-                code: "NotFound".to_string(),
+                code: ErrorCode::NotFound,
                 message: "Only markdown files can be renamed".to_string(),
                 details: Some(serde_json::json!({ "path": rel_path_text })),
             });
@@ -321,21 +818,21 @@ pub fn rename_entry(vault_root: &Path, rel_path: &Path, new_name: &str) -> Resul
         (sanitize_markdown_file_name(new_name)?, "Target file already exists")
     } else {
         return Err(ApiError {
-            code: "NotFound".to_string(),
+            code: ErrorCode::NotFound,
             message: "Path is not a file or directory".to_string(),
             details: Some(serde_json::json!({ "path": rel_path_text })),
         });
     };
 
     let parent = source_abs.parent().ok_or_else(|| ApiError {
-        code: "WriteFailed".to_string(),
+        code: ErrorCode::WriteFailed,
         message: "Invalid target path".to_string(),
         details: None,
     })?;
     let target_abs = parent.join(&target_name);
     if target_abs.exists() {
         return Err(ApiError {
-            code: "WriteFailed".to_string(),
+            code: ErrorCode::WriteFailed,
             message: err_exists_message.to_string(),
             details: Some(serde_json::json!({ "path": canonical_to_string(&target_abs) })),
         });
@@ -353,6 +850,215 @@ pub fn rename_entry(vault_root: &Path, rel_path: &Path, new_name: &str) -> Resul
     })
 }
 
+// Expand {name} (file stem), {date} (modification day) and {index} (1-based, zero-padded to the batch size)
+fn render_rename_pattern(pattern: &str, stem: &str, date: &str, index: usize, total: usize) -> String {
+    let width = total.to_string().len();
+    pattern
+        .replace("{name}", stem)
+        .replace("{date}", date)
+        .replace("{index}", &format!("{index:0width$}"))
+}
+
+// Rename several entries from one pattern; every target is checked before anything is renamed,
+// and renames already done are rolled back if a later one fails
+pub fn batch_rename(
+    vault_root: &Path,
+    rel_paths: &[PathBuf],
+    pattern: &str,
+    dry_run: bool,
+) -> Result<BatchRenameResult, ApiError> {
+    if pattern.trim().is_empty() {
+        return Err(ApiError {
+            code: ErrorCode::WriteFailed,
+            message: "Rename pattern is empty".to_string(),
+            details: None,
+        });
+    }
+
+    // (source, target, old rel, new rel)
+    let mut planned: Vec<(PathBuf, PathBuf, String, String)> = Vec::new();
+    for (position, rel_path) in rel_paths.iter().enumerate() {
+        let rel_path_text = rel_path_string(rel_path);
+        if rel_path_text.trim().is_empty() {
+            return Err(ApiError {
+                code: ErrorCode::WriteFailed,
+                message: "Invalid path".to_string(),
+                details: None,
+            });
+        }
+        let source_abs = path_policy::resolve_existing_path(vault_root, rel_path)?;
+        let metadata =
+            fs::metadata(&source_abs).map_err(|err| map_io_error(ErrorCode::Unknown, "Metadata failed", err))?;
+        let file_name = source_abs
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let date = metadata
+            .modified()
+            .map(|modified| chrono::DateTime::<chrono::Local>::from(modified).format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+
+        let target_name = if metadata.is_dir() {
+            let rendered = render_rename_pattern(pattern, &file_name, &date, position + 1, rel_paths.len());
+            sanitize_dir_name(&rendered)?
+        } else if metadata.is_file() && file_name.to_ascii_lowercase().ends_with(".md") {
+            let stem = &file_name[..file_name.len() - 3];
+            let rendered = render_rename_pattern(pattern, stem, &date, position + 1, rel_paths.len());
+            sanitize_markdown_file_name(&rendered)?
+        } else {
+            return Err(ApiError {
+                code: ErrorCode::NotFound,
+                message: "Only markdown files and directories can be renamed".to_string(),
+                details: Some(serde_json::json!({ "path": rel_path_text })),
+            });
+        };
+        if target_name == "." || target_name == ".." || target_name.starts_with(".tmp-") {
+            return Err(ApiError {
+                code: ErrorCode::WriteFailed,
+                message: "Invalid target name".to_string(),
+                details: Some(serde_json::json!({ "path": rel_path_text, "name": target_name })),
+            });
+        }
+
+        let parent = source_abs.parent().ok_or_else(|| ApiError {
+            code: ErrorCode::WriteFailed,
+            message: "Invalid target path".to_string(),
+            details: None,
+        })?;
+        let target_abs = parent.join(&target_name);
+        let new_rel = rel_path_string(&replace_last_component(rel_path, &target_name));
+        planned.push((source_abs, target_abs, rel_path_text, new_rel));
+    }
+
+    for (index, (source, target, old_rel, _)) in planned.iter().enumerate() {
+        let conflict = planned.iter().enumerate().find(|(other_index, (other_source, other_target, _, _))| {
+            *other_index != index
+                && (other_source == source || other_target == target || other_source.starts_with(source))
+        });
+        if let Some((_, (_, _, other_rel, _))) = conflict {
+            return Err(ApiError {
+                code: ErrorCode::WriteFailed,
+                message: "Paths overlap or would be renamed to the same name".to_string(),
+                details: Some(serde_json::json!({ "path": old_rel, "other": other_rel })),
+            });
+        }
+        // Taken names are fine only when their current owner is renamed away in this batch
+        if target != source && target.exists() && !planned.iter().any(|(other_source, _, _, _)| other_source == target) {
+            return Err(ApiError {
+                code: ErrorCode::WriteFailed,
+                message: "Target already exists".to_string(),
+                details: Some(serde_json::json!({ "path": canonical_to_string(target) })),
+            });
+        }
+    }
+
+    let renames = planned
+        .iter()
+        .map(|(_, _, old_path, new_path)| PlannedRename {
+            old_path: old_path.clone(),
+            new_path: new_path.clone(),
+        })
+        .collect();
+    if dry_run {
+        return Ok(BatchRenameResult { renames, applied: false });
+    }
+
+    // Two phases through temporary names so swaps and chains (a -> b, b -> c) work
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let moves: Vec<(&PathBuf, PathBuf, &PathBuf)> = planned
+        .iter()
+        .enumerate()
+        .filter(|(_, (source, target, _, _))| source != target)
+        .map(|(index, (source, target, _, _))| {
+            let temp = source.with_file_name(format!(".tmp-rename-{stamp}-{index}"));
+            (source, temp, target)
+        })
+        .collect();
+
+    let mut done: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let mut failure = None;
+    for (source, temp, _) in &moves {
+        match fs::rename(source, temp) {
+            Ok(()) => done.push(((*source).clone(), temp.clone())),
+            Err(err) => {
+                failure = Some(map_write_error("Failed to rename entry", err));
+                break;
+            }
+        }
+    }
+    if failure.is_none() {
+        for (_, temp, target) in &moves {
+            if target.exists() {
+                failure = Some(ApiError {
+                    code: ErrorCode::WriteFailed,
+                    message: "Target already exists".to_string(),
+                    details: Some(serde_json::json!({ "path": canonical_to_string(target) })),
+                });
+                break;
+            }
+            match fs::rename(temp, target) {
+                Ok(()) => done.push((temp.clone(), (*target).clone())),
+                Err(err) => {
+                    failure = Some(map_write_error("Failed to rename entry", err));
+                    break;
+                }
+            }
+        }
+    }
+    if let Some(err) = failure {
+        for (from, to) in done.into_iter().rev() {
+            let _ = fs::rename(&to, &from);
+        }
+        return Err(err);
+    }
+
+    Ok(BatchRenameResult { renames, applied: true })
+}
+
+// Rewrite wikilinks and markdown links across the vault after old_rel was renamed to new_rel
+pub fn propagate_link_updates(vault_root: &Path, old_rel: &str, new_rel: &str) -> Result<LinkUpdateReport, ApiError> {
+    let mut report = LinkUpdateReport::default();
+    let (Some(old_path), Some(new_path)) = (normalize_note_path(old_rel), normalize_note_path(new_rel)) else {
+        return Ok(report);
+    };
+    let parent_dir = |path: &str| path.rsplit_once('/').map(|(dir, _)| dir.to_string()).unwrap_or_default();
+
+    let (files, _) = list_markdown_files(vault_root)?;
+    for file in files {
+        let new_base_dir = parent_dir(&file);
+        // Notes that moved with a renamed directory resolve their relative links from where they were
+        let old_base_dir = match file.strip_prefix(&new_path).and_then(|rest| rest.strip_prefix('/')) {
+            Some(rest) => parent_dir(&format!("{}/{}", old_path, rest)),
+            None => new_base_dir.clone(),
+        };
+
+        let rel_path = PathBuf::from(&file);
+        let note = match read_text_file(vault_root, &rel_path) {
+            Ok(note) => note,
+            Err(_) => {
+                report.skipped_files.push(file);
+                continue;
+            }
+        };
+        let Some((content, count)) =
+            rewrite_note_links(&note.content, &old_base_dir, &new_base_dir, &old_path, &new_path)
+        else {
+            continue;
+        };
+        match write_text_file(vault_root, &rel_path, &content, WriteOrigin::Background) {
+            Ok(_) => {
+                report.updated_links += count;
+                report.updated_files.push(file);
+            }
+            Err(_) => report.skipped_files.push(file),
+        }
+    }
+    Ok(report)
+}
+
 fn replace_last_component(path: &Path, new_name: &str) -> PathBuf {
     let mut parts: Vec<_> = path.iter().map(|p| p.to_os_string()).collect();
     if !parts.is_empty() {
@@ -362,37 +1068,110 @@ fn replace_last_component(path: &Path, new_name: &str) -> PathBuf {
     parts.iter().collect()
 }
 
+// Create an empty vault directory under an existing parent and scaffold its layout
+pub fn create_vault(parent: &Path, name: &str) -> Result<PathBuf, ApiError> {
+    if !parent.is_absolute() {
+        return Err(ApiError {
+            code: ErrorCode::WriteFailed,
+            message: "Vault location must be an absolute path".to_string(),
+            details: Some(serde_json::json!({ "path": canonical_to_string(parent) })),
+        });
+    }
+    path_policy::ensure_no_symlink(parent)?;
+    let parent = parent
+        .canonicalize()
+        .map_err(|err| map_io_error(ErrorCode::NotFound, "Vault location does not exist", err))?;
+    if !parent.is_dir() {
+        return Err(ApiError {
+            code: ErrorCode::NotFound,
+            message: "Vault location is not a directory".to_string(),
+            details: Some(serde_json::json!({ "path": canonical_to_string(&parent) })),
+        });
+    }
+
+    let name = sanitize_dir_name(name)?;
+    if name == "." || name == ".." {
+        return Err(ApiError {
+            code: ErrorCode::WriteFailed,
+            message: "Invalid vault name".to_string(),
+            details: None,
+        });
+    }
+
+    let vault_root = parent.join(&name);
+    fs::create_dir(&vault_root).map_err(|err| {
+        let message = if err.kind() == std::io::ErrorKind::AlreadyExists {
+            "Vault directory already exists"
+        } else {
+            "Failed to create vault directory"
+        };
+        write_error_with_context(message, err, "create_vault_dir", &vault_root)
+    })?;
+    let vault_root = vault_root
+        .canonicalize()
+        .map_err(|err| map_io_error(ErrorCode::Unknown, "Vault resolve failed", err))?;
+
+    // Planning data itself is initialized by the planning service on first open
+    let layout = VaultLayout::default();
+    path_policy::ensure_or_create_dir_in_vault(&vault_root, &tasks_dir(&vault_root, &layout))?;
+    settings_repo::save_planning_settings(&vault_root, PlanningSettings::default())?;
+
+    Ok(vault_root)
+}
+
 fn sanitize_dir_name(input: &str) -> Result<String, ApiError> {
-    let trimmed = input.trim();
+    let normalized = nfc(input);
+    let trimmed = normalized.trim();
     if trimmed.is_empty() {
         return Err(ApiError {
-            code: "WriteFailed".to_string(),
+            code: ErrorCode::WriteFailed,
             message: "Directory name is empty".to_string(),
             details: None,
         });
     }
     if trimmed.contains(['/', '\\']) {
         return Err(ApiError {
-            code: "WriteFailed".to_string(),
+            code: ErrorCode::WriteFailed,
             message: "Directory name cannot contain path separators".to_string(),
             details: None,
         });
     }
+    ensure_portable_name(trimmed)?;
     Ok(trimmed.to_string())
 }
 
+// Names Windows cannot create; rejected everywhere so vaults stay portable
+fn ensure_portable_name(name: &str) -> Result<(), ApiError> {
+    if is_reserved_name(name) {
+        return Err(ApiError {
+            code: ErrorCode::WriteFailed,
+            message: "Name is reserved on Windows".to_string(),
+            details: Some(serde_json::json!({ "name": name })),
+        });
+    }
+    if name.ends_with('.') {
+        return Err(ApiError {
+            code: ErrorCode::WriteFailed,
+            message: "Name cannot end with a dot".to_string(),
+            details: Some(serde_json::json!({ "name": name })),
+        });
+    }
+    Ok(())
+}
+
 fn sanitize_markdown_file_name(input: &str) -> Result<String, ApiError> {
-    let trimmed = input.trim();
+    let normalized = nfc(input);
+    let trimmed = normalized.trim();
     if trimmed.is_empty() {
         return Err(ApiError {
-            code: "WriteFailed".to_string(),
+            code: ErrorCode::WriteFailed,
             message: "File name is empty".to_string(),
             details: None,
         });
     }
     if trimmed.contains(['/', '\\']) {
         return Err(ApiError {
-            code: "WriteFailed".to_string(),
+            code: ErrorCode::WriteFailed,
             message: "File name cannot contain path separators".to_string(),
             details: None,
         });
@@ -401,12 +1180,13 @@ fn sanitize_markdown_file_name(input: &str) -> Result<String, ApiError> {
     if !name.to_ascii_lowercase().ends_with(".md") {
         name.push_str(".md");
     }
+    ensure_portable_name(&name)?;
     Ok(name)
 }
 
 pub fn delete_entry(vault_root: &Path, rel_path: &Path) -> Result<DeleteEntryResult, ApiError> {
     let resolved = path_policy::resolve_existing_path(vault_root, rel_path)?;
-    let metadata = fs::metadata(&resolved).map_err(|err| map_io_error("Unknown", "Metadata failed", err))?;
+    let metadata = fs::metadata(&resolved).map_err(|err| map_io_error(ErrorCode::Unknown, "Metadata failed", err))?;
     if metadata.is_dir() {
         fs::remove_dir_all(&resolved).map_err(|err| map_write_error("Failed to delete directory", err))?;
     } else {
@@ -447,7 +1227,7 @@ pub fn create_entry(vault_root: &Path, parent_rel: Option<&Path>, kind: &str) ->
             }
         }
         return Err(ApiError {
-            code: "WriteFailed".to_string(),
+            code: ErrorCode::WriteFailed,
             message: "Failed to allocate file name".to_string(),
             details: Some(serde_json::json!({ "path": canonical_to_string(&parent_abs) })),
         });
@@ -475,19 +1255,178 @@ pub fn create_entry(vault_root: &Path, parent_rel: Option<&Path>, kind: &str) ->
             }
         }
         return Err(ApiError {
-            code: "WriteFailed".to_string(),
+            code: ErrorCode::WriteFailed,
             message: "Failed to allocate directory name".to_string(),
             details: Some(serde_json::json!({ "path": canonical_to_string(&parent_abs) })),
         });
     }
 
     Err(ApiError {
-        code: "WriteFailed".to_string(),
+        code: ErrorCode::WriteFailed,
         message: "Invalid create kind".to_string(),
         details: Some(serde_json::json!({ "kind": kind })),
     })
 }
 
+// "name", then "Copy of name", "Copy of name (2)", ...; extension kept for files
+fn copy_name_candidate(name: &str, is_dir: bool, index: usize) -> String {
+    let name = nfc(name);
+    let name = name.as_str();
+    if index == 0 {
+        return name.to_string();
+    }
+    let (stem, ext) = match name.rfind('.') {
+        Some(dot) if !is_dir && dot > 0 => name.split_at(dot),
+        _ => (name, ""),
+    };
+    if index == 1 {
+        format!("Copy of {stem}{ext}")
+    } else {
+        format!("Copy of {stem} ({index}){ext}")
+    }
+}
+
+// Files and directories under dir (relative to it), parents before children; symlinks are refused
+fn collect_copy_entries(dir: &Path, rel: &Path, entries: &mut Vec<(PathBuf, bool)>) -> Result<(), ApiError> {
+    let read_dir = fs::read_dir(dir).map_err(|err| map_io_error(ErrorCode::FileReadError, "Failed to read directory", err))?;
+    for entry in read_dir {
+        let entry = entry.map_err(|err| map_io_error(ErrorCode::FileReadError, "Failed to read entry", err))?;
+        let entry_path = entry.path();
+        let meta = fs::symlink_metadata(&entry_path)
+            .map_err(|err| map_io_error(ErrorCode::Unknown, "Metadata failed", err))?;
+        let entry_rel = rel.join(entry.file_name());
+        if meta.file_type().is_symlink() {
+            return Err(ApiError {
+                code: ErrorCode::SymlinkNotAllowed,
+                message: "Symlink path is not allowed".to_string(),
+                details: Some(serde_json::json!({ "path": rel_path_string(&entry_rel) })),
+            });
+        }
+        if meta.is_dir() {
+            entries.push((entry_rel.clone(), true));
+            collect_copy_entries(&entry_path, &entry_rel, entries)?;
+        } else if meta.is_file() {
+            entries.push((entry_rel, false));
+        }
+    }
+    Ok(())
+}
+
+// Copy a file or directory into target_dir; a taken name becomes "Copy of …"
+pub fn copy_entry(vault_root: &Path, rel_path: &Path, target_dir: Option<&Path>) -> Result<CopyEntryResult, ApiError> {
+    let rel_path_text = rel_path_string(rel_path);
+    if rel_path_text.trim().is_empty() {
+        return Err(ApiError {
+            code: ErrorCode::WriteFailed,
+            message: "Cannot copy the vault root".to_string(),
+            details: None,
+        });
+    }
+    let source = path_policy::resolve_existing_path(vault_root, rel_path)?;
+    let target_rel = target_dir.unwrap_or_else(|| Path::new(""));
+    let target_abs = if target_rel.as_os_str().is_empty() {
+        vault_root
+            .canonicalize()
+            .map_err(|err| map_io_error(ErrorCode::Unknown, "Vault resolve failed", err))?
+    } else {
+        path_policy::resolve_existing_dir(vault_root, target_rel)?
+    };
+
+    let metadata = fs::metadata(&source).map_err(|err| map_io_error(ErrorCode::Unknown, "Metadata failed", err))?;
+    let is_dir = metadata.is_dir();
+    if is_dir && target_abs.starts_with(&source) {
+        return Err(ApiError {
+            code: ErrorCode::WriteFailed,
+            message: "Cannot copy a directory into itself".to_string(),
+            details: Some(serde_json::json!({ "path": rel_path_text, "target": rel_path_string(target_rel) })),
+        });
+    }
+    let name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| ApiError {
+            code: ErrorCode::WriteFailed,
+            message: "Invalid source path".to_string(),
+            details: Some(serde_json::json!({ "path": rel_path_text })),
+        })?;
+
+    // Walk the whole tree before writing anything so a symlink inside does not leave a partial copy
+    let mut entries = Vec::new();
+    if is_dir {
+        collect_copy_entries(&source, Path::new(""), &mut entries)?;
+    }
+
+    for index in 0..100 {
+        let candidate_name = copy_name_candidate(&name, is_dir, index);
+        let candidate = target_abs.join(&candidate_name);
+        let claimed = if is_dir {
+            fs::create_dir(&candidate)
+        } else {
+            fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&candidate)
+                .map(|_file| ())
+        };
+        match claimed {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(map_write_error("Failed to create copy", err)),
+        }
+
+        let copied = if is_dir {
+            entries.iter().try_for_each(|(entry_rel, entry_is_dir)| {
+                let destination = candidate.join(entry_rel);
+                if *entry_is_dir {
+                    fs::create_dir(&destination)
+                } else {
+                    fs::copy(source.join(entry_rel), &destination).map(|_| ())
+                }
+            })
+        } else {
+            fs::copy(&source, &candidate).map(|_| ())
+        };
+        if let Err(err) = copied {
+            let _ = if is_dir {
+                fs::remove_dir_all(&candidate)
+            } else {
+                fs::remove_file(&candidate)
+            };
+            return Err(write_error_with_context("Failed to copy entry", err, "copy", &candidate));
+        }
+
+        let mut rel = target_rel.to_path_buf();
+        rel.push(candidate_name);
+        return Ok(CopyEntryResult {
+            path: rel_path_string(&rel),
+            kind: if is_dir { "dir" } else { "file" }.to_string(),
+        });
+    }
+
+    Err(ApiError {
+        code: ErrorCode::WriteFailed,
+        message: "Failed to allocate copy name".to_string(),
+        details: Some(serde_json::json!({ "path": canonical_to_string(&target_abs) })),
+    })
+}
+
+// Copy a markdown note next to itself as "Copy of …"
+pub fn duplicate_note(vault_root: &Path, rel_path: &Path) -> Result<CopyEntryResult, ApiError> {
+    let resolved = path_policy::resolve_existing_path(vault_root, rel_path)?;
+    let is_markdown = resolved
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md"));
+    if !resolved.is_file() || !is_markdown {
+        return Err(ApiError {
+            code: ErrorCode::WriteFailed,
+            message: "Only markdown notes can be duplicated".to_string(),
+            details: Some(serde_json::json!({ "path": rel_path_string(rel_path) })),
+        });
+    }
+    let parent_rel = rel_path.parent().unwrap_or_else(|| Path::new(""));
+    copy_entry(vault_root, rel_path, Some(parent_rel))
+}
+
 fn file_mtime(path: &Path) -> Option<u64> {
     let metadata = fs::metadata(path).ok()?;
     let modified = metadata.modified().ok()?;
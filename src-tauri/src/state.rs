@@ -1,10 +1,74 @@
 use reqwest::Client;
 use std::path::PathBuf;
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::domain::focus::FocusSession;
+use crate::features::vault_drive::QueuedWrite;
+use crate::repo::vault_repo::PersistedVault;
 
 pub struct VaultState {
     pub root: Mutex<Option<PathBuf>>,
     pub config_path: PathBuf,
+    pub stale: Mutex<Option<PersistedVault>>, // Persisted vault whose folder is gone; needs relocating
+}
+
+// Whether the vault folder is reachable, and note writes held while it is not
+pub struct VaultDriveState {
+    pub available: Mutex<bool>,
+    pub queue: Mutex<Vec<QueuedWrite>>,
+    pub queue_path: PathBuf, // Keeps the queue across restarts
+}
+
+pub struct FocusState {
+    pub session: Mutex<Option<FocusSession>>,
+}
+
+impl FocusState {
+    // Whether in-app notifications should currently be suppressed
+    pub fn do_not_disturb(&self) -> bool {
+        self.session
+            .lock()
+            .map(|session| session.is_some())
+            .unwrap_or(false)
+    }
+}
+
+// When tasks last changed, until the board index files are regenerated for it
+pub struct BoardIndexState {
+    pub changed_at: Mutex<Option<Instant>>,
+}
+
+impl BoardIndexState {
+    pub fn mark_changed(&self) {
+        if let Ok(mut changed_at) = self.changed_at.lock() {
+            *changed_at = Some(Instant::now());
+        }
+    }
+
+    // Whether changes are pending and none came in for `quiet`; clears the pending mark
+    pub fn take_settled(&self, quiet: Duration) -> bool {
+        let Ok(mut changed_at) = self.changed_at.lock() else {
+            return false;
+        };
+        match *changed_at {
+            Some(at) if at.elapsed() >= quiet => {
+                *changed_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+// Timer found running at startup, awaiting a resume/close decision
+pub struct TimerRecoveryState {
+    pub dangling_timer_id: Mutex<Option<String>>,
+}
+
+// Running localhost HTTP API server, if enabled
+pub struct HttpApiState {
+    pub server: Mutex<Option<crate::features::http_api::HttpApiServer>>,
 }
 
 pub struct AppState {
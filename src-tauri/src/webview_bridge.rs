@@ -1,8 +1,13 @@
+// Embedded browser webviews carry this label prefix; only they get the bridge script
+pub const WEBVIEW_LABEL_PREFIX: &str = "webview-";
+// Emitted by the bridge script whenever the page, its title or its load state changes
+pub const WEBVIEW_STATE_EVENT: &str = "webview-state";
+
 pub fn init_webview_bridge<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
     tauri::plugin::Builder::new("webview-bridge")
         .on_webview_ready(|webview| {
             let label = webview.label().to_string();
-            if !label.starts_with("webview-") {
+            if !label.starts_with(WEBVIEW_LABEL_PREFIX) {
                 return;
             }
             let script = webview_bridge_script(&label);
@@ -27,7 +32,7 @@ fn webview_bridge_script(label: &str) -> String {
 
   const emitState = () => {{
     try {{
-      tauri.event.emit("webview-state", {{
+      tauri.event.emit("{state_event}", {{
         label,
         url: window.location.href,
         title: document.title || window.location.href,
@@ -88,6 +93,7 @@ fn webview_bridge_script(label: &str) -> String {
   window.addEventListener("popstate", emitState);
   document.addEventListener("readystatechange", emitState);
 }})();"#,
-        label_json = label_json
+        label_json = label_json,
+        state_event = WEBVIEW_STATE_EVENT
     )
 }
@@ -1,5 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
-import { AiSettings, ChatMessage } from "./ai.types";
+import { AiSettings, ChatMessage, SmartCaptureResponse } from "./ai.types";
 import { CreateTaskInput } from "../../shared/types/planning";
 import { ApiResponse } from "../../shared/types/api";
 import { aiService } from "./ai.service";
@@ -19,7 +19,8 @@ export async function saveAiSettings(settings: AiSettings): Promise<void> {
 }
 
 export async function smartCapture(text: string): Promise<CreateTaskInput[]> {
-    return invokeApi<CreateTaskInput[]>("planning_ai_smart_capture", { text });
+    const response = await invokeApi<SmartCaptureResponse>("planning_ai_smart_capture", { text });
+    return response.tasks;
 }
 
 /**
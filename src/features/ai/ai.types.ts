@@ -3,10 +3,50 @@ export interface AiSettings {
     base_url: string;
     api_key: string;
     model_name: string;
+    context_tokens?: number; // Model context window, prompt plus reply
+    reply_tokens?: number;
+    overflow?: 'summarize' | 'truncate';
+    cache_ttl_hours?: number; // 0 disables the response cache
+    requests_per_minute?: number; // 0 is unlimited
+    max_retries?: number;
+    embedding_model?: 'all-mini-lm-l6-v2' | 'multilingual-e5-small' | 'bge-small-zh-v15';
+    preload_embedding_model?: boolean;
+    transcription?: TranscriptionSettings;
+    ocr?: OcrSettings;
+}
+
+export interface OcrSettings {
+    provider: 'api' | 'local';
+    model: string; // Vision-capable model; empty uses model_name
+    tesseract_binary: string;
+    tesseract_languages: string; // e.g. "eng+deu"
+}
+
+export interface TranscriptionSettings {
+    provider: 'api' | 'local';
+    base_url: string; // Empty uses the AI base_url
+    api_key: string; // Empty uses the AI api_key
+    model: string;
+    language?: string | null;
+    whisper_binary: string; // whisper.cpp program for the local provider
+    whisper_model: string; // ggml model file for the local provider
+}
+
+export interface TokenUsage {
+    prompt_tokens: number;
+    completion_tokens: number;
+    total_tokens: number;
+    requests: number;
+    cached_requests: number;
+    estimated: boolean;
+    input_tokens: number;
+    truncated: boolean;
+    summarized_chunks: number;
 }
 
 export interface SmartCaptureResponse {
     tasks: any[]; // will map to CreateTaskInput
+    usage: TokenUsage;
 }
 
 export interface ChatMessage {
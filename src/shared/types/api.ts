@@ -1,6 +1,57 @@
 import type { FileNode } from "./file";
 
-export type ApiError = { code: string; message: string; details?: unknown };
+// Mirrors ErrorCode::as_str in src-tauri/src/ipc.rs; add new codes there first
+export type ErrorCode =
+  | "VaultNotSelected"
+  | "NoVaultSelected"
+  | "NotFound"
+  | "PermissionDenied"
+  | "PathOutsideVault"
+  | "SymlinkNotAllowed"
+  | "WriteFailed"
+  | "DecodeFailed"
+  | "ScanFailed"
+  | "ScanLimited"
+  | "LargeVault"
+  | "FileReadError"
+  | "FileWriteError"
+  | "FileRenameError"
+  | "FileDeleteError"
+  | "IOError"
+  | "ConfigDirNotFound"
+  | "NoteLocked"
+  | "VaultUnavailable"
+  | "InvalidManifest"
+  | "EntryNotFound"
+  | "DatabaseError"
+  | "DateTimeError"
+  | "LockError"
+  | "DatabaseLocked"
+  | "MutexPoisoned"
+  | "JsonError"
+  | "InvalidStateTransition"
+  | "DUE_DATE_REQUIRED"
+  | "BOARD_ID_REQUIRED"
+  | "InvalidSchedule"
+  | "InvalidInput"
+  | "WipLimitExceeded"
+  | "TimerOverlap"
+  | "AiRequestFailed"
+  | "AiProviderError"
+  | "AiParseFailed"
+  | "AiEmptyResponse"
+  | "RateLimited"
+  | "Offline"
+  | "EmbeddingFailed"
+  | "TranscriptionFailed"
+  | "OcrFailed"
+  | "ClipFailed"
+  | "WebviewFailed"
+  | "GithubRequestFailed"
+  | "GithubRateLimited"
+  | "Unknown";
+
+export type ApiError = { code: ErrorCode; message: string; details?: unknown };
 export type ApiResponse<T> = { ok: true; data: T } | { ok: false; error: ApiError };
 
 export type WarningItem = {
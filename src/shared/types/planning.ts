@@ -17,8 +17,17 @@ export interface TaskPeriodicity {
   end_rule: string; // "never", "date", "count"
   end_date?: string;
   end_count?: number;
+  weekdays?: Weekday[]; // "week" only: recur on these days instead of the start weekday
+  monthly?: MonthlyPattern; // "month" only: replaces the start day of month
 }
 
+export type Weekday = 'Mon' | 'Tue' | 'Wed' | 'Thu' | 'Fri' | 'Sat' | 'Sun';
+
+// Day-of-month pattern for monthly recurrences
+export type MonthlyPattern =
+  | { type: 'last_day' }
+  | { type: 'nth_weekday'; nth: number; weekday: Weekday }; // nth 1..5, or -1 for the last one
+
 // Task model
 export interface Task {
   id: string;